@@ -0,0 +1,92 @@
+//! Benchmarks of each format's reader on synthetic raw files, including a
+//! wide NYC-style workbook with several races per ballot.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use ranked_vote::formats::{nyc_batch_reader, read_election};
+use ranked_vote::generate::{generate_election, write_raw, write_us_ny_nyc, GeneratorOptions};
+use std::path::PathBuf;
+
+const BALLOTS: usize = 100_000;
+/// Races in the NYC workbook, each with a column per rank.
+const NYC_RACES: usize = 8;
+const FORMATS: &[&str] = &[
+    "simple_json",
+    "us_mn_mpls",
+    "us_ca_sfo",
+    "us_vt_btv",
+    "dominion_rcr",
+    "nist_sp_1500",
+    "us_me",
+];
+
+fn options(seed: u64) -> GeneratorOptions {
+    GeneratorOptions {
+        num_ballots: BALLOTS,
+        num_candidates: 12,
+        seed,
+        ..GeneratorOptions::default()
+    }
+}
+
+/// A fresh directory for a format's raw files.
+fn raw_dir(format: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("ranked-vote-bench").join(format);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn readers(c: &mut Criterion) {
+    let election = generate_election(&options(1));
+
+    let mut group = c.benchmark_group("read");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(BALLOTS as u64));
+    for format in FORMATS {
+        let dir = raw_dir(format);
+        let params = write_raw(format, &election, &dir).unwrap();
+        group.bench_function(*format, |b| {
+            b.iter(|| read_election(format, &dir, params.clone()))
+        });
+    }
+    group.finish();
+}
+
+fn nyc_reader(c: &mut Criterion) {
+    let offices: Vec<String> = (1..=NYC_RACES).map(|i| format!("Office {}", i)).collect();
+    let elections: Vec<_> = (1..=NYC_RACES)
+        .map(|i| generate_election(&options(i as u64)))
+        .collect();
+    let races: Vec<_> = offices.iter().map(|o| o.as_str()).zip(&elections).collect();
+    let dir = raw_dir("us_ny_nyc");
+    write_us_ny_nyc(&races, &dir).unwrap();
+
+    let contests = || {
+        offices
+            .iter()
+            .map(|office| {
+                let params = [
+                    ("candidatesFile", "candidates.xlsx"),
+                    ("cvrPattern", r"cvr_\d+\.xlsx"),
+                    ("officeName", office),
+                    ("jurisdictionName", "Citywide"),
+                ]
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+                (office.clone(), params)
+            })
+            .collect()
+    };
+
+    let mut group = c.benchmark_group("read");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(BALLOTS as u64));
+    group.bench_function("us_ny_nyc", |b| {
+        b.iter(|| nyc_batch_reader(&dir, contests()))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, readers, nyc_reader);
+criterion_main!(benches);
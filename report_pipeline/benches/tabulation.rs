@@ -0,0 +1,77 @@
+//! Benchmarks of normalizing and tabulating synthetic contests of 100k to
+//! 5M ballots.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use ranked_vote::generate::{generate_election, GeneratorOptions};
+use ranked_vote::model::election::Election;
+use ranked_vote::model::metadata::{OvervotePolicy, SkippedRankPolicy, TabulationOptions};
+use ranked_vote::normalizers::normalize_election;
+use ranked_vote::tabulator::tabulate;
+
+const SIZES: &[(&str, usize)] = &[("100k", 100_000), ("1M", 1_000_000), ("5M", 5_000_000)];
+
+fn election(num_ballots: usize) -> Election {
+    generate_election(&GeneratorOptions {
+        num_ballots,
+        num_candidates: 12,
+        ..GeneratorOptions::default()
+    })
+}
+
+fn normalize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("normalize");
+    group.sample_size(10);
+    for normalization in ["simple", "maine", "nyc"] {
+        for (label, num_ballots) in SIZES {
+            group.throughput(Throughput::Elements(*num_ballots as u64));
+            group.bench_function(BenchmarkId::new(normalization, label), |b| {
+                b.iter_batched(
+                    || election(*num_ballots),
+                    |election| {
+                        normalize_election(
+                            normalization,
+                            OvervotePolicy::default(),
+                            SkippedRankPolicy::default(),
+                            election,
+                        )
+                    },
+                    BatchSize::PerIteration,
+                )
+            });
+        }
+    }
+    group.finish();
+}
+
+fn tabulation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tabulate");
+    group.sample_size(10);
+    for (label, num_ballots) in SIZES {
+        let election = normalize_election(
+            "simple",
+            OvervotePolicy::default(),
+            SkippedRankPolicy::default(),
+            election(*num_ballots),
+        );
+        group.throughput(Throughput::Elements(*num_ballots as u64));
+        for (name, options) in [
+            ("eager", TabulationOptions::default()),
+            (
+                "one_at_a_time",
+                TabulationOptions {
+                    eager: Some(false),
+                    nyc_style: Some(false),
+                    exact_thresholds: None,
+                },
+            ),
+        ] {
+            group.bench_with_input(BenchmarkId::new(name, label), &election, |b, election| {
+                b.iter(|| tabulate(&election.ballots, &options))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, normalize, tabulation);
+criterion_main!(benches);
@@ -0,0 +1,54 @@
+/*
+ * C interface to the ranked.vote tabulator, for cross-checking other
+ * implementations against the one that produces the reports.
+ *
+ * Ballots are passed as a packed buffer of 32-bit words: for each ballot, the
+ * number of choices n followed by n candidate IDs in ranked order, where the
+ * last choice may be RV_OVERVOTE if the ballot ended in an overvote. Ballots
+ * are normalized already (without skipped ranks or repeated candidates), as
+ * in the pipeline's preprocessed files, and are numbered from 1.
+ *
+ * Rounds are returned as JSON, in the format of the "rounds" of report.json.
+ */
+
+#ifndef RANKED_VOTE_H
+#define RANKED_VOTE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define RV_OK 0
+#define RV_INVALID_BALLOTS 1
+#define RV_TABULATION_FAILED 2
+
+/* Eliminate every candidate who can no longer win at once. */
+#define RV_EAGER 1u
+/* Tabulate by New York City's rules. */
+#define RV_NYC_STYLE 2u
+
+/* Marks the end of a ballot that ended in an overvote. */
+#define RV_OVERVOTE UINT32_MAX
+
+/*
+ * Tabulate len words of packed ballots with the RV_EAGER and RV_NYC_STYLE
+ * flags. On success, returns RV_OK and sets *out to the rounds as JSON;
+ * otherwise returns an error code and sets *out to an error message. Either
+ * way, *out must be freed with rv_free_string.
+ */
+int rv_tabulate(const uint32_t *ballots, size_t len, uint32_t flags, char **out);
+
+/* Free a string returned by rv_tabulate. */
+void rv_free_string(char *s);
+
+/* The version of the pipeline, as recorded in reports' run manifests. */
+const char *rv_version(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* RANKED_VOTE_H */
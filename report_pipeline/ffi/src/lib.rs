@@ -0,0 +1,176 @@
+//! A C ABI for the tabulator, so that other implementations can be
+//! cross-checked against the one that produces the reports on ranked.vote.
+//! See `include/ranked_vote.h` for the interface.
+
+use ranked_vote::commands::PIPELINE_VERSION;
+use ranked_vote::model::election::{CandidateId, NormalizedBallot};
+use ranked_vote::model::metadata::TabulationOptions;
+use ranked_vote::tabulator::tabulate;
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int};
+use std::panic::catch_unwind;
+use std::sync::OnceLock;
+
+pub const RV_OK: c_int = 0;
+pub const RV_INVALID_BALLOTS: c_int = 1;
+pub const RV_TABULATION_FAILED: c_int = 2;
+
+/// Eliminate every candidate who can no longer win at once.
+pub const RV_EAGER: u32 = 1;
+/// Tabulate by New York City's rules.
+pub const RV_NYC_STYLE: u32 = 2;
+
+/// Marks the end of a ballot that ended in an overvote.
+pub const RV_OVERVOTE: u32 = u32::MAX;
+
+/// Unpack ballots from a buffer of `[n, choice 1, ..., choice n]` records,
+/// where the last choice may be `RV_OVERVOTE`. Ballots are numbered from 1
+/// in the order they appear.
+fn unpack_ballots(buffer: &[u32]) -> Result<Vec<NormalizedBallot>, String> {
+    let mut ballots = Vec::new();
+    let mut rest = buffer;
+    while let Some((&len, tail)) = rest.split_first() {
+        let id = ballots.len() + 1;
+        let len = len as usize;
+        if tail.len() < len {
+            return Err(format!(
+                "Ballot {} has {} choices, but the buffer ends after {}",
+                id,
+                len,
+                tail.len()
+            ));
+        }
+        let (record, tail) = tail.split_at(len);
+        let overvoted = record.last() == Some(&RV_OVERVOTE);
+        let choices = &record[..len - usize::from(overvoted)];
+        if choices.contains(&RV_OVERVOTE) {
+            return Err(format!(
+                "Ballot {} has an overvote before its last choice",
+                id
+            ));
+        }
+        ballots.push(NormalizedBallot::new(
+            id.to_string(),
+            choices.iter().map(|&c| CandidateId(c)).collect(),
+            overvoted,
+        ));
+        rest = tail;
+    }
+    Ok(ballots)
+}
+
+/// Tabulate packed ballots, returning the rounds as JSON.
+fn tabulate_packed(buffer: &[u32], flags: u32) -> Result<String, (c_int, String)> {
+    let ballots = unpack_ballots(buffer).map_err(|e| (RV_INVALID_BALLOTS, e))?;
+    let options = TabulationOptions {
+        eager: Some(flags & RV_EAGER != 0),
+        nyc_style: Some(flags & RV_NYC_STYLE != 0),
+        exact_thresholds: None,
+    };
+    let rounds = catch_unwind(|| tabulate(&ballots, &options)).map_err(|panic| {
+        let message = panic
+            .downcast_ref::<String>()
+            .cloned()
+            .or_else(|| panic.downcast_ref::<&str>().map(|s| s.to_string()))
+            .unwrap_or_else(|| "Tabulation failed".to_string());
+        (RV_TABULATION_FAILED, message)
+    })?;
+    Ok(serde_json::to_string(&rounds).unwrap())
+}
+
+/// Tabulate `len` words of packed ballots with the `RV_EAGER` and
+/// `RV_NYC_STYLE` flags. On success, returns `RV_OK` and sets `*out` to the
+/// rounds as JSON; otherwise returns an error code and sets `*out` to an
+/// error message. Either way, `*out` must be freed with `rv_free_string`.
+///
+/// # Safety
+///
+/// `ballots` must point to `len` readable words (or may be null if `len`
+/// is 0), and `out` must be a valid pointer to write to.
+#[no_mangle]
+pub unsafe extern "C" fn rv_tabulate(
+    ballots: *const u32,
+    len: usize,
+    flags: u32,
+    out: *mut *mut c_char,
+) -> c_int {
+    let buffer = if len == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(ballots, len)
+    };
+    let (code, message) = match tabulate_packed(buffer, flags) {
+        Ok(json) => (RV_OK, json),
+        Err(error) => error,
+    };
+    *out = CString::new(message).unwrap().into_raw();
+    code
+}
+
+/// Free a string returned by `rv_tabulate`.
+///
+/// # Safety
+///
+/// `s` must be null or a string returned by `rv_tabulate` that has not
+/// already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn rv_free_string(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// The version of the pipeline, as recorded in reports' run manifests.
+#[no_mangle]
+pub extern "C" fn rv_version() -> *const c_char {
+    static VERSION: OnceLock<CString> = OnceLock::new();
+    VERSION
+        .get_or_init(|| CString::new(PIPELINE_VERSION).unwrap())
+        .as_ptr()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    fn call_tabulate(ballots: &[u32], flags: u32) -> (c_int, String) {
+        let mut out = std::ptr::null_mut();
+        unsafe {
+            let code = rv_tabulate(ballots.as_ptr(), ballots.len(), flags, &mut out);
+            let message = CStr::from_ptr(out).to_str().unwrap().to_string();
+            rv_free_string(out);
+            (code, message)
+        }
+    }
+
+    #[test]
+    fn test_unpack_ballots() {
+        let ballots = unpack_ballots(&[2, 1, 0, 0, 2, 2, RV_OVERVOTE]).unwrap();
+        assert_eq!(
+            vec![
+                NormalizedBallot::new("1".to_string(), vec![CandidateId(1), CandidateId(0)], false),
+                NormalizedBallot::new("2".to_string(), vec![], false),
+                NormalizedBallot::new("3".to_string(), vec![CandidateId(2)], true),
+            ],
+            ballots
+        );
+        assert!(unpack_ballots(&[3, 1, 0]).is_err());
+        assert!(unpack_ballots(&[2, RV_OVERVOTE, 0]).is_err());
+    }
+
+    #[test]
+    fn test_rv_tabulate() {
+        let (code, json) = call_tabulate(&[1, 0, 1, 0, 2, 1, 0], RV_EAGER);
+        assert_eq!(RV_OK, code);
+        let rounds: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(2, rounds[0]["allocations"][0]["votes"]);
+
+        let (code, message) = call_tabulate(&[2, 1], RV_EAGER);
+        assert_eq!(RV_INVALID_BALLOTS, code);
+        assert_eq!(
+            "Ballot 1 has 2 choices, but the buffer ends after 1",
+            message
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! Python bindings for the report pipeline: ballot loading, normalization
+//! and tabulation, using the same code that produces the reports on
+//! ranked.vote.
+//!
+//! Results are returned as the same JSON structures the pipeline writes,
+//! converted to Python dicts and lists. The pipeline panics on malformed
+//! input, which Python sees as a `pyo3_runtime.PanicException`.
+
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyList;
+use ranked_vote::formats;
+use ranked_vote::model::election::{
+    Ballot, Candidate, CandidateId, CandidateType, Choice, Election, ElectionInfo,
+    ElectionPreprocessed, NormalizedElection,
+};
+use ranked_vote::model::metadata::TabulationOptions;
+use ranked_vote::model::report::ContestReport;
+use ranked_vote::normalizers::normalize_election;
+use ranked_vote::preprocessed::read_preprocessed;
+use ranked_vote::report::generate_report;
+use ranked_vote::tabulator::tabulate;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+/// Convert a value to Python through its JSON form, so that Python sees the
+/// same field names as the files the pipeline writes.
+fn to_python<T: Serialize>(py: Python<'_>, value: &T) -> PyResult<PyObject> {
+    let json = serde_json::to_string(value).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok(py.import("json")?.call_method1("loads", (json,))?.unbind())
+}
+
+/// Parse a policy from its name as it appears in the election metadata,
+/// e.g. `"exhaustAfterTwo"`.
+fn parse_policy<T: DeserializeOwned>(name: &str) -> PyResult<T> {
+    serde_json::from_value(serde_json::Value::String(name.to_string()))
+        .map_err(|e| PyValueError::new_err(format!("Invalid policy {:?}: {}", name, e)))
+}
+
+fn candidate_names(candidates: &[Candidate]) -> Vec<String> {
+    candidates
+        .iter()
+        .map(|c| c.display_name().to_string())
+        .collect()
+}
+
+/// Raw ballots of a contest, as read from a cast vote record.
+#[pyclass(name = "Election", module = "ranked_vote")]
+struct PyElection {
+    /// The normalization usually used with the ballots' format.
+    normalization: &'static str,
+    election: Election,
+}
+
+#[pymethods]
+impl PyElection {
+    /// Build an election from rankings given as lists, one per ballot, of
+    /// candidate names. A rank may also be `None` for a skipped rank, or a
+    /// list of the names marked at an overvoted rank.
+    #[staticmethod]
+    fn from_rankings(rankings: &Bound<'_, PyList>) -> PyResult<PyElection> {
+        let mut candidates: Vec<Candidate> = Vec::new();
+        let mut ids: HashMap<String, CandidateId> = HashMap::new();
+        let mut ballots = Vec::with_capacity(rankings.len());
+
+        for (i, ranking) in rankings.iter().enumerate() {
+            let mut choices = Vec::new();
+            for rank in ranking.try_iter()? {
+                let rank = rank?;
+                let choice = if rank.is_none() {
+                    Choice::Undervote
+                } else if let Ok(name) = rank.extract::<String>() {
+                    let id = *ids.entry(name.clone()).or_insert_with(|| {
+                        candidates.push(Candidate::new(name, CandidateType::Regular));
+                        CandidateId(candidates.len() as u32 - 1)
+                    });
+                    Choice::Vote(id)
+                } else if rank.extract::<Vec<String>>().is_ok() {
+                    Choice::Overvote
+                } else {
+                    return Err(PyTypeError::new_err(format!(
+                        "Ballot {}: a rank must be a name, None or a list of names",
+                        i + 1
+                    )));
+                };
+                choices.push(choice);
+            }
+            ballots.push(Ballot::new((i + 1).to_string(), choices));
+        }
+
+        Ok(PyElection {
+            normalization: "simple",
+            election: Election::new(candidates, ballots),
+        })
+    }
+
+    /// Names of the candidates, in the order readers encountered them.
+    #[getter]
+    fn candidates(&self) -> Vec<String> {
+        candidate_names(&self.election.candidates)
+    }
+
+    fn __len__(&self) -> usize {
+        self.election.ballots.len()
+    }
+
+    /// Normalize the ballots, as the pipeline does before tabulating:
+    /// `normalization` is one of `simple`, `maine` or `nyc` (by default,
+    /// the one usually used with the ballots' format), and the policies are
+    /// named as in the election metadata.
+    #[pyo3(signature = (normalization=None, overvote_policy="exhaust", skipped_rank_policy="skip"))]
+    fn normalize(
+        &self,
+        normalization: Option<&str>,
+        overvote_policy: &str,
+        skipped_rank_policy: &str,
+    ) -> PyResult<PyNormalizedElection> {
+        let ballots = normalize_election(
+            normalization.unwrap_or(self.normalization),
+            parse_policy(overvote_policy)?,
+            parse_policy(skipped_rank_policy)?,
+            self.election.clone(),
+        );
+        Ok(PyNormalizedElection {
+            info: None,
+            ballots,
+        })
+    }
+}
+
+/// Normalized ballots of a contest, either normalized from an `Election` or
+/// read from the pipeline's preprocessed files, with the contest's
+/// information in the latter case.
+#[pyclass(name = "NormalizedElection", module = "ranked_vote")]
+struct PyNormalizedElection {
+    info: Option<ElectionInfo>,
+    ballots: NormalizedElection,
+}
+
+#[pymethods]
+impl PyNormalizedElection {
+    /// The contest's information from the preprocessed file, or `None` for
+    /// ballots normalized from an `Election`.
+    #[getter]
+    fn info(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_python(py, &self.info)
+    }
+
+    /// Names of the candidates, in candidate ID order.
+    #[getter]
+    fn candidates(&self) -> Vec<String> {
+        candidate_names(&self.ballots.candidates)
+    }
+
+    fn __len__(&self) -> usize {
+        self.ballots.ballots.len()
+    }
+
+    /// The ballots as `(ballot ID, candidate names in ranked order,
+    /// overvoted)` tuples, where `overvoted` is whether the ballot ended in
+    /// an overvote.
+    fn ballots(&self) -> Vec<(String, Vec<String>, bool)> {
+        let names = candidate_names(&self.ballots.candidates);
+        self.ballots
+            .ballots
+            .iter()
+            .map(|ballot| {
+                let choices = ballot
+                    .choices()
+                    .iter()
+                    .map(|c| names[c.0 as usize].clone())
+                    .collect();
+                (ballot.id.clone(), choices, ballot.overvoted)
+            })
+            .collect()
+    }
+
+    /// Tabulate the ballots, returning the rounds as written to
+    /// `report.json`. Options not given default to the contest's tabulation
+    /// options, or the pipeline's defaults.
+    #[pyo3(signature = (eager=None, nyc_style=None))]
+    fn tabulate(
+        &self,
+        py: Python<'_>,
+        eager: Option<bool>,
+        nyc_style: Option<bool>,
+    ) -> PyResult<PyObject> {
+        let defaults = self
+            .info
+            .as_ref()
+            .map(|info| info.tabulation_options.clone())
+            .unwrap_or_default();
+        let options = TabulationOptions {
+            eager: eager.or(defaults.eager),
+            nyc_style: nyc_style.or(defaults.nyc_style),
+            exact_thresholds: defaults.exact_thresholds,
+        };
+        let rounds = py.allow_threads(|| tabulate(&self.ballots.ballots, &options));
+        to_python(py, &rounds)
+    }
+
+    /// Generate the contest's report, as written to `report.json`. Only
+    /// available for ballots read from a preprocessed file, which have the
+    /// contest's information.
+    fn report(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let info = self.info.clone().ok_or_else(|| {
+            PyValueError::new_err("A report needs the contest information of a preprocessed file")
+        })?;
+        let preprocessed = ElectionPreprocessed {
+            info,
+            ballots: self.ballots.clone(),
+            provenance: None,
+        };
+        let report = py.allow_threads(|| generate_report(&preprocessed));
+        to_python(py, &report)
+    }
+
+    /// The normalized ballots in the pipeline's JSON form.
+    fn to_dict(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_python(py, &self.ballots)
+    }
+}
+
+/// Read the raw ballots of a contest in one of the pipeline's data formats
+/// (e.g. `us_ca_sfo`), with the contest's loader parameters from the
+/// election metadata.
+#[pyfunction]
+#[pyo3(signature = (format, path, params=None))]
+fn read_election(
+    py: Python<'_>,
+    format: &str,
+    path: PathBuf,
+    params: Option<BTreeMap<String, String>>,
+) -> PyElection {
+    let election =
+        py.allow_threads(|| formats::read_election(format, &path, params.unwrap_or_default()));
+    PyElection {
+        normalization: formats::default_normalization(format),
+        election,
+    }
+}
+
+/// Read a contest's preprocessed ballots (`normalized.json.gz` or
+/// `normalized.bin`) from the pipeline's preprocessed directory.
+#[pyfunction(name = "read_preprocessed")]
+fn py_read_preprocessed(py: Python<'_>, path: PathBuf) -> PyNormalizedElection {
+    let preprocessed = py.allow_threads(|| read_preprocessed(&path));
+    PyNormalizedElection {
+        info: Some(preprocessed.info),
+        ballots: preprocessed.ballots,
+    }
+}
+
+/// Read a `report.json` file, checking that it matches the pipeline's
+/// report format.
+#[pyfunction]
+fn read_report(py: Python<'_>, path: PathBuf) -> PyResult<PyObject> {
+    let file = std::fs::File::open(&path)?;
+    let report: ContestReport = serde_json::from_reader(std::io::BufReader::new(file))
+        .map_err(|e| PyValueError::new_err(format!("{}: {}", path.display(), e)))?;
+    to_python(py, &report)
+}
+
+#[pymodule]
+#[pyo3(name = "ranked_vote")]
+fn ranked_vote_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyElection>()?;
+    m.add_class::<PyNormalizedElection>()?;
+    m.add_function(wrap_pyfunction!(read_election, m)?)?;
+    m.add_function(wrap_pyfunction!(py_read_preprocessed, m)?)?;
+    m.add_function(wrap_pyfunction!(read_report, m)?)?;
+    Ok(())
+}
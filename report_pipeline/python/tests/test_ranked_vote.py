@@ -0,0 +1,70 @@
+import json
+import os
+import tempfile
+import unittest
+
+import ranked_vote
+
+
+class TestRankedVote(unittest.TestCase):
+    def test_from_rankings(self):
+        election = ranked_vote.Election.from_rankings(
+            [["A", "B"], ["B", None, "A"], ["C", ["A", "B"]], ["A"]]
+        )
+        self.assertEqual(["A", "B", "C"], election.candidates)
+        self.assertEqual(4, len(election))
+
+        normalized = election.normalize()
+        self.assertIsNone(normalized.info)
+        self.assertEqual(
+            [
+                ("1", ["A", "B"], False),
+                ("2", ["B", "A"], False),
+                ("3", ["C"], True),
+                ("4", ["A"], False),
+            ],
+            normalized.ballots(),
+        )
+
+    def test_normalize_policies(self):
+        election = ranked_vote.Election.from_rankings([["A", None, None, "B"]])
+        self.assertEqual(
+            [("1", ["A"], False)],
+            election.normalize(skipped_rank_policy="exhaustAfterTwo").ballots(),
+        )
+        with self.assertRaises(ValueError):
+            election.normalize(overvote_policy="bogus")
+
+    def test_tabulate(self):
+        election = ranked_vote.Election.from_rankings(
+            [["A"], ["A"], ["B", "C"], ["C", "B"], ["C", "B"]]
+        )
+        rounds = election.normalize().tabulate()
+        self.assertEqual(2, len(rounds))
+        final = {a["allocatee"]: a["votes"] for a in rounds[-1]["allocations"]}
+        self.assertEqual({0: 2, 2: 3, "X": 0}, final)
+
+    def test_read_election(self):
+        with tempfile.TemporaryDirectory() as directory:
+            with open(os.path.join(directory, "ballots.json"), "w") as f:
+                json.dump(
+                    {
+                        "ballots": [
+                            {"id": "1", "votes": ["Alice", "Bob"]},
+                            {"id": "2", "votes": ["under", "Bob"]},
+                        ]
+                    },
+                    f,
+                )
+            election = ranked_vote.read_election(
+                "simple_json", directory, {"file": "ballots.json"}
+            )
+        self.assertEqual(2, len(election))
+        self.assertEqual(
+            [("1", ["Alice", "Bob"], False), ("2", ["Bob"], False)],
+            election.normalize().ballots(),
+        )
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,133 @@
+//! Sample size planning for risk-limiting audits (RLAs) of a contest's
+//! reported outcome.
+//!
+//! Sizes are for auditing the final round as a two-candidate contest
+//! between the winner and the runner-up, over all of the contest's ballots.
+//! A complete IRV audit (e.g. with RAIRE assertions) must also confirm the
+//! elimination order, whose margins can be smaller, so these sizes are a
+//! lower bound on the work of an IRV audit.
+
+use crate::model::report::{AuditSampleSize, AuditSampleSizes};
+use crate::tabulator::TabulatorRound;
+
+/// Risk limits to give sample sizes for.
+pub const RISK_LIMITS: &[f64] = &[0.01, 0.05, 0.1];
+
+/// Error inflation factor of the comparison audit, as in Stark's
+/// "super-simple" audit.
+const GAMMA: f64 = 1.03905;
+
+/// Expected number of ballots a BRAVO ballot-polling audit examines if the
+/// reported results are correct, given the winner's and loser's shares of
+/// all ballots. The full count, `ballots`, if the winner did not win.
+fn ballot_polling_size(winner_share: f64, loser_share: f64, risk_limit: f64, ballots: u32) -> u32 {
+    if winner_share <= loser_share {
+        return ballots;
+    }
+    let share = winner_share / (winner_share + loser_share);
+    let z_winner = (2.0 * share).ln();
+    let z_loser = (2.0 - 2.0 * share).ln();
+    let size = ((1.0 / risk_limit).ln() + z_winner / 2.0)
+        / (winner_share * z_winner + loser_share * z_loser);
+    (size.ceil() as u32).min(ballots)
+}
+
+/// Number of ballots a ballot-level comparison audit examines if it finds no
+/// discrepancies, given the diluted margin. The full count, `ballots`, if
+/// the margin is zero.
+fn comparison_size(diluted_margin: f64, risk_limit: f64, ballots: u32) -> u32 {
+    if diluted_margin <= 0.0 {
+        return ballots;
+    }
+    let size = -2.0 * GAMMA * risk_limit.ln() / diluted_margin;
+    (size.ceil() as u32).min(ballots)
+}
+
+/// Compute audit sample sizes for the final round of a contest with
+/// `ballots` ballots. Returns `None` if the final round has fewer than two
+/// candidates.
+pub fn generate_audit_sample_sizes(
+    ballots: u32,
+    rounds: &[TabulatorRound],
+) -> Option<AuditSampleSizes> {
+    let mut votes: Vec<u32> = rounds
+        .last()?
+        .allocations
+        .iter()
+        .filter(|a| a.allocatee.candidate_id().is_some())
+        .map(|a| a.votes)
+        .collect();
+    if votes.len() < 2 || ballots == 0 {
+        return None;
+    }
+    votes.sort_unstable_by(|a, b| b.cmp(a));
+
+    let winner_share = votes[0] as f64 / ballots as f64;
+    let loser_share = votes[1] as f64 / ballots as f64;
+    let diluted_margin = winner_share - loser_share;
+    let sizes = RISK_LIMITS
+        .iter()
+        .map(|&risk_limit| AuditSampleSize {
+            risk_limit: risk_limit as f32,
+            ballot_polling: ballot_polling_size(winner_share, loser_share, risk_limit, ballots),
+            comparison: comparison_size(diluted_margin, risk_limit, ballots),
+        })
+        .collect();
+
+    Some(AuditSampleSizes {
+        diluted_margin: diluted_margin as f32,
+        sizes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::CandidateId;
+    use crate::tabulator::{Allocatee, TabulatorAllocation};
+
+    fn round(votes: &[u32]) -> TabulatorRound {
+        TabulatorRound {
+            allocations: votes
+                .iter()
+                .enumerate()
+                .map(|(i, &votes)| TabulatorAllocation {
+                    allocatee: Allocatee::Candidate(CandidateId(i as u32)),
+                    votes,
+                    shares: None,
+                })
+                .collect(),
+            undervote: 0,
+            overvote: 0,
+            continuing_ballots: votes.iter().sum(),
+            transfers: vec![],
+            threshold: None,
+        }
+    }
+
+    #[test]
+    fn test_sample_sizes() {
+        // A 60-40 contest: BRAVO's expected sample at a 10% risk limit is
+        // 119 ballots, and a comparison audit needs 6.2255 / 0.2 at 5%.
+        let sizes = generate_audit_sample_sizes(100_000, &[round(&[60_000, 40_000])]).unwrap();
+        assert!((sizes.diluted_margin - 0.2).abs() < 1e-6);
+        assert_eq!(
+            vec![(0.01, 234, 48), (0.05, 154, 32), (0.1, 119, 24)],
+            sizes
+                .sizes
+                .iter()
+                .map(|s| (s.risk_limit, s.ballot_polling, s.comparison))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_sample_sizes_tie() {
+        let sizes = generate_audit_sample_sizes(1_000, &[round(&[400, 400])]).unwrap();
+        assert!(sizes
+            .sizes
+            .iter()
+            .all(|s| s.ballot_polling == 1_000 && s.comparison == 1_000));
+        assert!(generate_audit_sample_sizes(1_000, &[round(&[400])]).is_none());
+    }
+}
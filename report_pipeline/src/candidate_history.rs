@@ -0,0 +1,182 @@
+use crate::formats::NameMatching;
+use crate::model::election::CandidateType;
+use crate::model::report::{
+    CandidateContestEntry, CandidateHistory, CandidateIndex, ContestReport,
+};
+use crate::tabulator::Allocatee;
+use std::collections::BTreeMap;
+
+/// File name of the candidate index, written in the root report directory.
+pub const CANDIDATE_INDEX_FILE: &str = "candidates.json";
+
+/// A candidate's result in one contest, to be grouped into the candidate
+/// index.
+pub struct CandidateAppearance {
+    pub id: String,
+    pub name: String,
+    pub contest: CandidateContestEntry,
+}
+
+/// Identifier of a candidate without one in metadata: the jurisdiction path
+/// and the candidate's name, case-folded, with runs of other characters
+/// than letters and digits replaced by `-`. Candidates of the same name in
+/// the same jurisdiction are assumed to be the same person.
+pub fn default_candidate_id(jurisdiction_path: &str, name: &str) -> String {
+    let matching = NameMatching {
+        case_fold: true,
+        ..NameMatching::default()
+    };
+    let key = matching.key(name);
+    let slug: Vec<&str> = key
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+    format!("{}/{}", jurisdiction_path, slug.join("-"))
+}
+
+/// The results of each candidate in a contest report. Aggregated write-ins
+/// are not a candidate, so they are left out.
+pub fn candidate_appearances(report: &ContestReport) -> Vec<CandidateAppearance> {
+    let info = &report.info;
+    let votes = |round: Option<usize>, candidate: usize| -> Option<u32> {
+        report
+            .rounds
+            .get(round?)?
+            .allocations
+            .iter()
+            .find_map(|a| match a.allocatee {
+                Allocatee::Candidate(c) if c.0 as usize == candidate => Some(a.votes),
+                _ => None,
+            })
+    };
+    let last_round = report.rounds.len().checked_sub(1);
+
+    report
+        .candidates
+        .iter()
+        .enumerate()
+        .filter(|(_, candidate)| candidate.candidate_type != CandidateType::WriteIn)
+        .map(|(i, candidate)| CandidateAppearance {
+            id: candidate.canonical_id.clone().unwrap_or_else(|| {
+                default_candidate_id(&info.jurisdiction_path, candidate.display_name())
+            }),
+            name: candidate.display_name().to_string(),
+            contest: CandidateContestEntry {
+                election_path: format!("{}/{}", info.jurisdiction_path, info.election_path),
+                election_name: info.election_name.clone(),
+                date: info.date.clone(),
+                office: info.office.clone(),
+                office_id: info.office_id(),
+                office_name: info.office_name.clone(),
+                first_round_votes: votes(Some(0), i).unwrap_or(0),
+                finalist: votes(last_round, i).is_some(),
+                won: report.winner.is_some_and(|w| w.0 as usize == i),
+            },
+        })
+        .collect()
+}
+
+/// Group candidates' results by their identifier. Candidates are ordered by
+/// identifier and their contests by date.
+pub fn generate_candidate_index(appearances: Vec<CandidateAppearance>) -> CandidateIndex {
+    let mut candidates: BTreeMap<String, Vec<(String, CandidateContestEntry)>> = BTreeMap::new();
+    for appearance in appearances {
+        candidates
+            .entry(appearance.id)
+            .or_default()
+            .push((appearance.name, appearance.contest));
+    }
+
+    CandidateIndex {
+        candidates: candidates
+            .into_iter()
+            .map(|(id, mut contests)| {
+                contests.sort_by(|(_, a), (_, b)| {
+                    (&a.date, &a.election_path, &a.office).cmp(&(
+                        &b.date,
+                        &b.election_path,
+                        &b.office,
+                    ))
+                });
+                CandidateHistory {
+                    id,
+                    name: contests.last().unwrap().0.clone(),
+                    contests: contests.into_iter().map(|(_, contest)| contest).collect(),
+                }
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn appearance(id: &str, name: &str, date: &str, won: bool) -> CandidateAppearance {
+        CandidateAppearance {
+            id: id.to_string(),
+            name: name.to_string(),
+            contest: CandidateContestEntry {
+                election_path: format!("us/ca/sfo/{}", &date[..7].replace('-', "/")),
+                election_name: "General Election".to_string(),
+                date: date.to_string(),
+                office: "mayor".to_string(),
+                office_id: "us/ca/sfo/mayor".to_string(),
+                office_name: "Mayor".to_string(),
+                first_round_votes: 100,
+                finalist: won,
+                won,
+            },
+        }
+    }
+
+    #[test]
+    fn test_default_candidate_id() {
+        assert_eq!(
+            "us/ca/sfo/london-breed",
+            default_candidate_id("us/ca/sfo", "London  Breed")
+        );
+        assert_eq!(
+            default_candidate_id("us/ca/sfo", "José O'Brien"),
+            default_candidate_id("us/ca/sfo", "JOSE\u{0301} O'BRIEN")
+        );
+    }
+
+    #[test]
+    fn test_candidate_index() {
+        let index = generate_candidate_index(vec![
+            appearance(
+                "us/ca/sfo/london-breed",
+                "London Breed",
+                "2024-11-05",
+                false,
+            ),
+            appearance("us/ca/sfo/daniel-lurie", "Daniel Lurie", "2024-11-05", true),
+            appearance(
+                "us/ca/sfo/london-breed",
+                "London N. Breed",
+                "2018-06-05",
+                true,
+            ),
+        ]);
+
+        assert_eq!(
+            vec!["us/ca/sfo/daniel-lurie", "us/ca/sfo/london-breed"],
+            index
+                .candidates
+                .iter()
+                .map(|c| c.id.as_str())
+                .collect::<Vec<_>>()
+        );
+        let breed = &index.candidates[1];
+        assert_eq!("London Breed", breed.name);
+        assert_eq!(
+            vec![("2018-06-05", true), ("2024-11-05", false)],
+            breed
+                .contests
+                .iter()
+                .map(|c| (c.date.as_str(), c.won))
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use crate::commands::schema::report_typescript;
+use crate::log_info;
+use std::path::Path;
+
+/// Name of the TypeScript definitions of the report files, which the web
+/// app imports as `report_types`.
+const REPORT_TYPES_FILE: &str = "report_types.d.ts";
+
+/// Write TypeScript definitions of the report files (see
+/// `report_typescript`) to `report_types.d.ts` in the given directory,
+/// the web app's source directory.
+pub fn codegen_ts(output_dir: &Path) {
+    let path = output_dir.join(REPORT_TYPES_FILE);
+    std::fs::write(&path, report_typescript())
+        .unwrap_or_else(|e| panic!("Failed to write {}: {}", path.display(), e));
+    log_info!("Wrote {}", path.display());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_types_are_current() {
+        // The web app's source is next to the pipeline in the repository, but
+        // not when the pipeline is built on its own.
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../src")
+            .join(REPORT_TYPES_FILE);
+        if let Ok(checked_in) = std::fs::read_to_string(&path) {
+            assert!(
+                checked_in == report_typescript(),
+                "{} is out of date; run `cargo run -- codegen-ts`",
+                path.display()
+            );
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use crate::model::comparison::RctabComparison;
+use crate::preprocessed::{contest_preprocessed_file, read_preprocessed};
+use crate::rctab::{compare_with_rctab, RctabConfig, RctabSummary};
+use crate::util::read_serialized;
+use std::path::Path;
+
+/// Tabulate a contest's preprocessed ballots under the options of an RCTab
+/// config, compare the rounds with RCTab's `summary.json`, and print the
+/// comparison to stdout as JSON. Returns the comparison.
+pub fn compare_rctab(
+    contest_path: &Path,
+    config_file: &Path,
+    summary_file: &Path,
+) -> RctabComparison {
+    let preprocessed = read_preprocessed(&contest_preprocessed_file(contest_path));
+    let config: RctabConfig = read_serialized(config_file);
+    let summary: RctabSummary = read_serialized(summary_file);
+
+    let comparison = compare_with_rctab(
+        &preprocessed.ballots.candidates,
+        &preprocessed.ballots.ballots,
+        preprocessed.provenance.as_ref(),
+        &config,
+        &summary,
+    );
+    println!("{}", serde_json::to_string_pretty(&comparison).unwrap());
+    comparison
+}
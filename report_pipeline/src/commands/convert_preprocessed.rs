@@ -0,0 +1,38 @@
+use crate::preprocessed::{read_preprocessed, write_preprocessed_to, LEGACY_PREPROCESSED_FILE};
+use crate::util::get_files_from_path;
+use crate::{log_info, log_warn};
+use rayon::prelude::*;
+use std::fs::remove_file;
+use std::path::Path;
+
+/// Convert preprocessed ballots in the legacy gzipped JSON format to the
+/// binary format, compressed with zstd at `zstd_level` if given, optionally
+/// removing the legacy files.
+pub fn convert_preprocessed(preprocessed_dir: &Path, remove_legacy: bool, zstd_level: Option<i32>) {
+    let legacy_files: Vec<_> = get_files_from_path(preprocessed_dir)
+        .unwrap()
+        .into_iter()
+        .filter(|path| {
+            path.file_name()
+                .is_some_and(|f| f == LEGACY_PREPROCESSED_FILE)
+        })
+        .collect();
+
+    log_info!("Converting {} preprocessed files...", legacy_files.len());
+
+    legacy_files.par_iter().for_each(|legacy_path| {
+        match std::panic::catch_unwind(|| read_preprocessed(legacy_path)) {
+            Ok(preprocessed) => {
+                write_preprocessed_to(legacy_path.parent().unwrap(), &preprocessed, zstd_level);
+                if remove_legacy {
+                    remove_file(legacy_path).unwrap();
+                }
+            }
+            Err(_) => {
+                log_warn!("Failed to read {}", legacy_path.display());
+            }
+        }
+    });
+
+    log_info!("Converted {} preprocessed files.", legacy_files.len());
+}
@@ -0,0 +1,58 @@
+use crate::formats::detect_format;
+use crate::model::metadata::{Contest, ElectionMetadata};
+use colored::*;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Guess the data format of an election's raw data and print an election
+/// metadata stub for it as JSON.
+pub fn detect(raw_election_dir: &Path) {
+    let detection = detect_format(raw_election_dir).unwrap_or_else(|| {
+        panic!(
+            "Could not detect the data format of {}.",
+            raw_election_dir.display()
+        )
+    });
+
+    eprintln!(
+        "Detected format {} with {} contest(s).",
+        detection.format.blue(),
+        detection.contests.len()
+    );
+
+    let election = ElectionMetadata {
+        name: "<election name>".to_string(),
+        date: "<YYYY-MM-DD>".to_string(),
+        data_format: detection.format,
+        tabulation_options: None,
+        normalization: detection.normalization,
+        rule_set: None,
+        overvote_policy: None,
+        skipped_rank_policy: None,
+        duplicate_ballot_policy: None,
+        contests: detection
+            .contests
+            .into_iter()
+            .map(|contest| Contest {
+                office: contest.office,
+                loader_params: Some(contest.loader_params),
+                reporting_status: None,
+                display_names: BTreeMap::new(),
+                candidate_ids: BTreeMap::new(),
+                variants: Vec::new(),
+                subsets: Vec::new(),
+                notes: Vec::new(),
+                official_first_round: None,
+                vote_for: None,
+            })
+            .collect(),
+        files: BTreeMap::new(),
+        downloads: BTreeMap::new(),
+        website: None,
+        precinct_map: None,
+        regions: BTreeMap::new(),
+        others_threshold: None,
+    };
+
+    println!("{}", serde_json::to_string_pretty(&election).unwrap());
+}
@@ -0,0 +1,128 @@
+use crate::log_warn;
+use crate::read_metadata::read_meta;
+use crate::util::{format_size, get_files_from_path};
+use itertools::Itertools;
+use std::path::Path;
+
+/// Artifact sizes for one election.
+struct ElectionUsage {
+    jurisdiction_path: String,
+    election_path: String,
+    contests: usize,
+    preprocessed: u64,
+    reports: u64,
+}
+
+impl ElectionUsage {
+    fn total(&self) -> u64 {
+        self.preprocessed + self.reports
+    }
+}
+
+/// Total size of the files under a directory, or zero if it doesn't exist.
+fn dir_size(path: &Path) -> u64 {
+    if !path.exists() {
+        return 0;
+    }
+    get_files_from_path(path)
+        .unwrap()
+        .iter()
+        .filter_map(|file| file.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+fn print_row(name: &str, preprocessed: u64, reports: u64) {
+    println!(
+        "{:>10} {:>10} {:>10}  {}",
+        format_size(preprocessed),
+        format_size(reports),
+        format_size(preprocessed + reports),
+        name
+    );
+}
+
+/// Print the size of preprocessed and report artifacts for each election
+/// and jurisdiction in the metadata. If a quota is given, warn if the
+/// artifacts exceed it, or would once elections without artifacts are
+/// generated.
+pub fn du(meta_dir: &Path, preprocessed_dir: &Path, report_dir: &Path, quota: Option<u64>) {
+    let mut usage: Vec<ElectionUsage> = Vec::new();
+    for (_, jurisdiction) in read_meta(meta_dir) {
+        for (election_path, election) in &jurisdiction.elections {
+            let path = Path::new(&jurisdiction.path).join(election_path);
+            usage.push(ElectionUsage {
+                jurisdiction_path: jurisdiction.path.clone(),
+                election_path: election_path.clone(),
+                contests: election.contests.len(),
+                preprocessed: dir_size(&preprocessed_dir.join(&path)),
+                reports: dir_size(&report_dir.join(&path)),
+            });
+        }
+    }
+    usage.sort_by(|a, b| {
+        (&a.jurisdiction_path, &a.election_path).cmp(&(&b.jurisdiction_path, &b.election_path))
+    });
+
+    println!("{:>10} {:>10} {:>10}  Path", "Preproc.", "Reports", "Total");
+    for (jurisdiction_path, elections) in &usage
+        .iter()
+        .group_by(|election| election.jurisdiction_path.clone())
+    {
+        let elections: Vec<&ElectionUsage> = elections.collect();
+        for election in &elections {
+            let name = format!("{}/{}", jurisdiction_path, election.election_path);
+            print_row(&name, election.preprocessed, election.reports);
+        }
+        print_row(
+            &format!("{} (total)", jurisdiction_path),
+            elections.iter().map(|e| e.preprocessed).sum(),
+            elections.iter().map(|e| e.reports).sum(),
+        );
+    }
+
+    // Files outside election directories: indexes, summaries, trends, and
+    // outputs of elections no longer in the metadata.
+    let preprocessed_total = dir_size(preprocessed_dir);
+    let reports_total = dir_size(report_dir);
+    print_row(
+        "(other)",
+        preprocessed_total.saturating_sub(usage.iter().map(|e| e.preprocessed).sum()),
+        reports_total.saturating_sub(usage.iter().map(|e| e.reports).sum()),
+    );
+    print_row("(total)", preprocessed_total, reports_total);
+
+    let quota = match quota {
+        Some(quota) => quota,
+        None => return,
+    };
+    let total = preprocessed_total + reports_total;
+    if total > quota {
+        log_warn!(
+            "Artifacts use {}, over the quota of {}.",
+            format_size(total),
+            format_size(quota)
+        );
+        return;
+    }
+
+    // Estimate the size of elections that haven't been generated from the
+    // average size per contest of those that have.
+    let (generated, missing): (Vec<&ElectionUsage>, Vec<&ElectionUsage>) =
+        usage.iter().partition(|e| e.total() > 0);
+    let generated_contests: usize = generated.iter().map(|e| e.contests).sum();
+    let missing_contests: usize = missing.iter().map(|e| e.contests).sum();
+    if generated_contests == 0 || missing_contests == 0 {
+        return;
+    }
+    let per_contest = generated.iter().map(|e| e.total()).sum::<u64>() / generated_contests as u64;
+    let estimate = total + per_contest * missing_contests as u64;
+    if estimate > quota {
+        log_warn!(
+            "Generating the {} contest(s) without artifacts would use about {}, over the quota of {}.",
+            missing_contests,
+            format_size(estimate),
+            format_size(quota)
+        );
+    }
+}
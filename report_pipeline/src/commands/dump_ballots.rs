@@ -0,0 +1,109 @@
+use crate::model::election::NormalizedElection;
+use crate::preprocessed::{contest_preprocessed_file, read_preprocessed};
+use std::io::Write;
+use std::path::Path;
+
+/// Marker written after a ballot's last ranking if it ended in an overvote.
+const OVERVOTE: &str = "overvote";
+
+/// Marker written where consecutive skipped ranks exhaust a ballot, before
+/// the rankings that aren't counted.
+const EXHAUSTED: &str = "exhausted";
+
+/// Write normalized ballots as CSV, one row per ballot: the ballot ID, then
+/// the names of the candidates in ranked order, followed by `overvote` if
+/// the ballot ended in an overvote. `exhausted` marks where a ballot stops
+/// being counted, if it is exhausted before its last ranking. Rows are
+/// padded to the longest ballot.
+fn write_ballots_csv<W: Write>(election: &NormalizedElection, writer: W) -> csv::Result<()> {
+    let ranks = election
+        .ballots
+        .iter()
+        .map(|b| {
+            b.marked_choices().len()
+                + usize::from(b.overvoted)
+                + usize::from(b.exhausted_after().is_some())
+        })
+        .max()
+        .unwrap_or(0);
+
+    let mut writer = csv::Writer::from_writer(writer);
+    let mut header = vec!["ballot_id".to_string()];
+    header.extend((1..=ranks).map(|r| format!("rank{}", r)));
+    writer.write_record(&header)?;
+
+    for ballot in &election.ballots {
+        let mut record = vec![ballot.id.as_str()];
+        record.extend(
+            ballot
+                .marked_choices()
+                .iter()
+                .map(|c| election.candidates[c.0 as usize].display_name()),
+        );
+        if let Some(counted) = ballot.exhausted_after() {
+            record.insert(counted as usize + 1, EXHAUSTED);
+        }
+        if ballot.overvoted {
+            record.push(OVERVOTE);
+        }
+        record.resize(ranks + 1, "");
+        writer.write_record(&record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Dump the normalized ballots of one contest as CSV, to a file or stdout,
+/// for spot-checking against the official CVR.
+pub fn dump_ballots(contest_path: &Path, output: Option<&Path>) {
+    let preprocessed = read_preprocessed(&contest_preprocessed_file(contest_path));
+
+    let result = match output {
+        Some(output) => std::fs::File::create(output)
+            .map_err(csv::Error::from)
+            .and_then(|file| write_ballots_csv(&preprocessed.ballots, file)),
+        None => write_ballots_csv(&preprocessed.ballots, std::io::stdout().lock()),
+    };
+    result.unwrap_or_else(|e| panic!("Failed to write ballots: {}", e));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::{Candidate, CandidateId, CandidateType, NormalizedBallot};
+
+    #[test]
+    fn test_write_ballots_csv() {
+        let election = NormalizedElection {
+            candidates: vec![
+                Candidate::new("Alice".to_string(), CandidateType::Regular),
+                Candidate::new("Smith, Bob".to_string(), CandidateType::Regular),
+            ],
+            ballots: vec![
+                NormalizedBallot::new("1".to_string(), vec![CandidateId(1), CandidateId(0)], false),
+                NormalizedBallot::new("2".to_string(), vec![CandidateId(0)], true),
+                NormalizedBallot::new("3".to_string(), vec![], false),
+                NormalizedBallot::new("4".to_string(), vec![CandidateId(0), CandidateId(1)], false)
+                    .with_exhausted_after(Some(1)),
+            ],
+            adjudicated_ballots: None,
+            adjudication: None,
+            duplicate_ballots: None,
+            candidate_reconciliation: None,
+            rank_positions: None,
+            duplicate_rankings: None,
+            first_round_adjustments: None,
+            overvote_handling: None,
+            skipped_rank_handling: None,
+            truncated_ballots: None,
+        };
+
+        let mut out = Vec::new();
+        write_ballots_csv(&election, &mut out).unwrap();
+        assert_eq!(
+            "ballot_id,rank1,rank2,rank3\n1,\"Smith, Bob\",Alice,\n2,Alice,overvote,\n3,,,\n\
+             4,Alice,exhausted,\"Smith, Bob\"\n",
+            String::from_utf8(out).unwrap()
+        );
+    }
+}
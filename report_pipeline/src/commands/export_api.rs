@@ -0,0 +1,147 @@
+use super::report::PIPELINE_VERSION;
+use crate::model::api::{ApiArtifact, ApiManifest, ArtifactKind};
+use crate::precinct_map::PRECINCT_MAP_FILE;
+use crate::site_metadata::CONTEST_METADATA_FILE;
+use crate::summary::{SUMMARY_FILE, TRENDS_CSV_FILE, TRENDS_JSON_FILE};
+use crate::util::{get_files_from_path, hash_file_sha256, signature_path, write_canonical};
+use crate::{log_info, log_warn};
+use std::ffi::OsStr;
+use std::fs::{copy, create_dir_all, write};
+use std::path::Path;
+
+/// Version prefix of artifact paths in the API directory. Bump it when the
+/// layout or the schema of an artifact changes incompatibly.
+const API_VERSION: &str = "v1";
+
+/// Classify a file in the report directory, or return `None` if it is not
+/// published through the API.
+fn artifact_kind(relative_path: &Path) -> Option<ArtifactKind> {
+    let file_name = relative_path.file_name()?.to_str()?;
+    let is_root = relative_path.parent() == Some(Path::new(""));
+
+    match file_name {
+        "index.json" if is_root => Some(ArtifactKind::Index),
+        TRENDS_JSON_FILE | TRENDS_CSV_FILE if is_root => Some(ArtifactKind::Trends),
+        SUMMARY_FILE => Some(ArtifactKind::Summary),
+        "report.json" => Some(ArtifactKind::Report),
+        // Reports on tabulation variants, e.g. report-single-elimination.json
+        name if name.starts_with("report-") && name.ends_with(".json") => {
+            Some(ArtifactKind::Report)
+        }
+        // Reports on ballot subsets, e.g. subsets/vote-by-mail.json
+        name if name.ends_with(".json")
+            && relative_path.parent()?.file_name() == Some(OsStr::new("subsets")) =>
+        {
+            Some(ArtifactKind::Report)
+        }
+        PRECINCT_MAP_FILE => Some(ArtifactKind::PrecinctMap),
+        CONTEST_METADATA_FILE => Some(ArtifactKind::ContestMetadata),
+        _ => None,
+    }
+}
+
+/// Copy the report corpus into a static API layout under `api_dir`. Each
+/// artifact is published at `{version}/{path in report_dir}`, with its ETag
+/// precomputed in a `.etag` file next to it, and listed with its hash in
+/// `manifest.json` at the root of `api_dir`.
+pub fn export_api(report_dir: &Path, api_dir: &Path) {
+    let mut artifacts: Vec<ApiArtifact> = Vec::new();
+
+    for path in get_files_from_path(report_dir).unwrap() {
+        let relative_path = path.strip_prefix(report_dir).unwrap();
+        let kind = match artifact_kind(relative_path) {
+            Some(kind) => kind,
+            None => continue,
+        };
+
+        let sha256 = match hash_file_sha256(&path) {
+            Ok(sha256) => sha256,
+            Err(e) => {
+                log_warn!("Failed to hash {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        let etag = format!("\"{}\"", sha256);
+
+        let api_path = Path::new(API_VERSION).join(relative_path);
+        let destination = api_dir.join(&api_path);
+        create_dir_all(destination.parent().unwrap()).unwrap();
+        let size = copy(&path, &destination).unwrap();
+
+        // Signed artifacts are published with their detached signature.
+        let signature = signature_path(&path);
+        if signature.exists() {
+            copy(&signature, signature_path(&destination)).unwrap();
+        }
+
+        let mut etag_path = destination.into_os_string();
+        etag_path.push(".etag");
+        write(&etag_path, &etag).unwrap();
+
+        artifacts.push(ApiArtifact {
+            // Paths are URL paths, so use forward slashes on every platform.
+            path: api_path
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/"),
+            kind,
+            size,
+            sha256,
+            etag,
+        });
+    }
+
+    let manifest = ApiManifest {
+        version: API_VERSION.to_string(),
+        pipeline_version: PIPELINE_VERSION.to_string(),
+        artifacts,
+    };
+    write_canonical(&api_dir.join("manifest.json"), &manifest);
+
+    log_info!(
+        "Exported {} artifacts to {}",
+        manifest.artifacts.len(),
+        api_dir.display()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_artifact_kind() {
+        assert_eq!(
+            Some(ArtifactKind::Index),
+            artifact_kind(Path::new("index.json"))
+        );
+        assert_eq!(None, artifact_kind(Path::new("us/ca/sfo/index.json")));
+        assert_eq!(
+            Some(ArtifactKind::Trends),
+            artifact_kind(Path::new("trends.csv"))
+        );
+        assert_eq!(
+            Some(ArtifactKind::Report),
+            artifact_kind(Path::new("us/ca/sfo/2024/11/mayor/report.json"))
+        );
+        assert_eq!(
+            Some(ArtifactKind::Report),
+            artifact_kind(Path::new("us/ca/sfo/2024/11/mayor/report-single.json"))
+        );
+        assert_eq!(
+            Some(ArtifactKind::Report),
+            artifact_kind(Path::new(
+                "us/ca/sfo/2024/11/mayor/subsets/vote-by-mail.json"
+            ))
+        );
+        assert_eq!(
+            Some(ArtifactKind::Summary),
+            artifact_kind(Path::new("us/ca/sfo/2024/11/summary.json"))
+        );
+        assert_eq!(
+            None,
+            artifact_kind(Path::new("us/ca/sfo/2024/11/mayor/card.png"))
+        );
+    }
+}
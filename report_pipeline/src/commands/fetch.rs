@@ -0,0 +1,368 @@
+use crate::read_metadata::read_meta;
+use crate::util::{format_size, hash_file};
+use crate::{log_info, log_warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, create_dir_all, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Time to wait for a server to accept a connection, or to send more of a
+/// file, before giving up on it and trying the next mirror.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(60);
+
+const CHUNK_SIZE: usize = 64 << 10;
+
+pub struct FetchOptions<'a> {
+    /// Jurisdiction or election path (e.g. `us/ny/nyc` or
+    /// `us/ny/nyc/2021/06`) to fetch files for. All elections if `None`.
+    pub jurisdiction: Option<&'a str>,
+    /// Bytes per second to limit downloads to.
+    pub max_rate: Option<u64>,
+}
+
+/// What a fetch did, printed to stdout.
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchSummary {
+    /// Raw data paths of the files downloaded.
+    pub downloaded: Vec<String>,
+    /// Files skipped because they are already in the raw data directory.
+    pub already_present: usize,
+    /// Files that couldn't be downloaded from any of their URLs, with the
+    /// errors.
+    pub failed: BTreeMap<String, String>,
+}
+
+/// What is known of a partial download, kept next to it so that a later run
+/// can resume it.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PartialDownload {
+    url: String,
+    /// ETag or Last-Modified of the response, so that a download is only
+    /// resumed from the same URL if the file hasn't changed since. Without
+    /// one, the download can't be resumed.
+    validator: Option<String>,
+}
+
+/// Hidden files next to a raw data file holding its partial download and
+/// the download's state. `sync` ignores hidden files.
+fn partial_paths(dest: &Path) -> (PathBuf, PathBuf) {
+    let name = dest.file_name().unwrap().to_string_lossy();
+    (
+        dest.with_file_name(format!(".{}.part", name)),
+        dest.with_file_name(format!(".{}.part.json", name)),
+    )
+}
+
+/// Limits the average rate of a download.
+struct Throttle {
+    max_rate: Option<u64>,
+    start: Instant,
+    bytes: u64,
+}
+
+impl Throttle {
+    fn new(max_rate: Option<u64>) -> Throttle {
+        Throttle {
+            max_rate,
+            start: Instant::now(),
+            bytes: 0,
+        }
+    }
+
+    /// Record bytes received, sleeping until the average rate is back under
+    /// the limit.
+    fn record(&mut self, bytes: usize) {
+        self.bytes += bytes as u64;
+        if let Some(rate) = self.max_rate {
+            let due = Duration::from_secs_f64(self.bytes as f64 / rate as f64);
+            if let Some(wait) = due.checked_sub(self.start.elapsed()) {
+                std::thread::sleep(wait);
+            }
+        }
+    }
+}
+
+/// Download `url` into `partial`, resuming from its current length if it
+/// was downloaded from the same URL with a validator and the server supports
+/// range requests, and starting over otherwise: mirrors may serve different
+/// bytes for the same file. On failure, what was downloaded is kept for the
+/// next attempt.
+fn download(
+    agent: &ureq::Agent,
+    url: &str,
+    partial: &Path,
+    state_path: &Path,
+    max_rate: Option<u64>,
+) -> Result<(), String> {
+    let validator = fs::read(state_path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<PartialDownload>(&bytes).ok())
+        .filter(|state| state.url == url)
+        .and_then(|state| state.validator);
+    let offset = match validator {
+        Some(_) => fs::metadata(partial).map_or(0, |m| m.len()),
+        None => 0,
+    };
+
+    let mut request = agent.get(url);
+    if let Some(validator) = validator.filter(|_| offset > 0) {
+        request = request
+            .set("Range", &format!("bytes={}-", offset))
+            .set("If-Range", &validator);
+    }
+    let response = match request.call() {
+        Ok(response) => response,
+        // Nothing is left to download; the hash check decides whether the
+        // partial file is whole.
+        Err(ureq::Error::Status(416, _)) if offset > 0 => return Ok(()),
+        Err(e) => return Err(e.to_string()),
+    };
+
+    let resume = response.status() == 206;
+    if resume {
+        let start = response
+            .header("Content-Range")
+            .and_then(|range| range.strip_prefix("bytes "))
+            .and_then(|range| range.split('-').next())
+            .and_then(|start| start.parse::<u64>().ok());
+        if start != Some(offset) {
+            return Err(format!(
+                "asked for bytes from {}, but got Content-Range {:?}",
+                offset,
+                response.header("Content-Range")
+            ));
+        }
+    }
+    let validator = response
+        .header("ETag")
+        .or_else(|| response.header("Last-Modified"))
+        .map(str::to_string);
+    let state = PartialDownload {
+        url: url.to_string(),
+        validator,
+    };
+    fs::write(state_path, serde_json::to_vec(&state).unwrap()).map_err(|e| e.to_string())?;
+
+    let mut file = if resume {
+        log_info!("Resuming {} at {}", url, format_size(offset));
+        OpenOptions::new().append(true).open(partial)
+    } else {
+        log_info!("Downloading {}", url);
+        File::create(partial)
+    }
+    .map_err(|e| e.to_string())?;
+
+    let mut reader = response.into_reader();
+    let mut buffer = vec![0; CHUNK_SIZE];
+    let mut throttle = Throttle::new(max_rate);
+    loop {
+        let read = reader.read(&mut buffer).map_err(|e| e.to_string())?;
+        if read == 0 {
+            return Ok(());
+        }
+        file.write_all(&buffer[..read]).map_err(|e| e.to_string())?;
+        throttle.record(read);
+    }
+}
+
+/// Download a raw data file from the first of its URLs that works, unless
+/// it is already present. A download whose hash doesn't match `expected` is
+/// discarded, and the next URL tried. Returns whether the file was
+/// downloaded.
+fn fetch_file(
+    agent: &ureq::Agent,
+    dest: &Path,
+    urls: &[String],
+    expected: Option<&String>,
+    max_rate: Option<u64>,
+) -> Result<bool, String> {
+    if dest.exists() {
+        return Ok(false);
+    }
+    let (partial, state) = partial_paths(dest);
+    let mut errors = Vec::new();
+    for url in urls {
+        if let Err(e) = download(agent, url, &partial, &state, max_rate) {
+            log_warn!("Failed to download {} from {}: {}", dest.display(), url, e);
+            errors.push(format!("{}: {}", url, e));
+            continue;
+        }
+        let hash = hash_file(partial.clone());
+        if let Some(expected) = expected.filter(|expected| **expected != hash) {
+            log_warn!(
+                "{} downloaded from {} has hash {}, but the metadata has {}",
+                dest.display(),
+                url,
+                hash,
+                expected
+            );
+            // A corrupt partial file can't be resumed.
+            fs::remove_file(&partial).map_err(|e| e.to_string())?;
+            errors.push(format!("{}: hash {} doesn't match", url, hash));
+            continue;
+        }
+        fs::rename(&partial, dest).map_err(|e| e.to_string())?;
+        if state.exists() {
+            fs::remove_file(&state).map_err(|e| e.to_string())?;
+        }
+        return Ok(true);
+    }
+    Err(errors.join("; "))
+}
+
+/// Download the raw data files that election metadata lists `downloads`
+/// for and that are missing from the raw data directory. Interrupted
+/// downloads are resumed from where they stopped, and each file's mirrors
+/// are tried in turn. Files already present are left alone, even if their
+/// hash differs; `sync` reports those.
+pub fn fetch(meta_dir: &Path, raw_dir: &Path, options: &FetchOptions) -> FetchSummary {
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(FETCH_TIMEOUT)
+        .timeout_read(FETCH_TIMEOUT)
+        .build();
+    let mut summary = FetchSummary::default();
+    for (_, ec) in read_meta(meta_dir) {
+        for (election_key, election) in &ec.elections {
+            let election_path = format!("{}/{}", ec.path, election_key);
+            if options
+                .jurisdiction
+                .is_some_and(|filter| filter != ec.path && filter != election_path)
+            {
+                continue;
+            }
+            let dir = raw_dir.join(&election_path);
+            for (filename, urls) in &election.downloads {
+                let path = format!("{}/{}", election_path, filename);
+                create_dir_all(&dir).unwrap();
+                match fetch_file(
+                    &agent,
+                    &dir.join(filename),
+                    urls,
+                    election.files.get(filename),
+                    options.max_rate,
+                ) {
+                    Ok(true) => summary.downloaded.push(path),
+                    Ok(false) => summary.already_present += 1,
+                    Err(e) => {
+                        summary.failed.insert(path, e);
+                    }
+                }
+            }
+        }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::TempDir;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+
+    /// Serve `body` to a single request, honoring a `Range` header, and
+    /// dropping the connection after `cut` bytes if given. Returns the URL,
+    /// and the start of the range requested, if any.
+    fn serve(
+        body: &'static [u8],
+        cut: Option<usize>,
+    ) -> (String, std::thread::JoinHandle<Option<usize>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/cvr.zip", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut range = None;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some(start) = line.to_lowercase().strip_prefix("range: bytes=") {
+                    range = start.trim().trim_end_matches('-').parse::<usize>().ok();
+                }
+            }
+            let start = range.unwrap_or(0);
+            let status = match range {
+                Some(_) => format!(
+                    "206 Partial Content\r\nContent-Range: bytes {}-{}/{}",
+                    start,
+                    body.len() - 1,
+                    body.len()
+                ),
+                None => "200 OK".to_string(),
+            };
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\nETag: \"v1\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                status,
+                body.len() - start
+            )
+            .unwrap();
+            let end = cut.map_or(body.len(), |cut| start + cut);
+            stream.write_all(&body[start..end]).unwrap();
+            range
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn test_fetch_file() {
+        static BODY: [u8; 2000] = [7; 2000];
+        let dir = TempDir::new("fetch");
+        fs::write(dir.join("expected"), BODY).unwrap();
+        let expected = hash_file(dir.join("expected"));
+
+        // Half of the file was downloaded from the second mirror before an
+        // interruption.
+        let (url, server) = serve(&BODY, None);
+        let dest = dir.join("cvr.zip");
+        let (partial, state) = partial_paths(&dest);
+        fs::write(&partial, &BODY[..1000]).unwrap();
+        let downloaded = PartialDownload {
+            url: url.clone(),
+            validator: Some("\"v1\"".to_string()),
+        };
+        fs::write(&state, serde_json::to_vec(&downloaded).unwrap()).unwrap();
+
+        // The first mirror is down, so the download resumes from the second,
+        // at 10,000 bytes per second.
+        let urls = vec!["http://127.0.0.1:1/cvr.zip".to_string(), url];
+        let agent = ureq::AgentBuilder::new().build();
+        let start = Instant::now();
+        let fetched = fetch_file(&agent, &dest, &urls, Some(&expected), Some(10_000));
+        assert_eq!(Ok(true), fetched);
+        assert!(start.elapsed() >= Duration::from_millis(90));
+        assert_eq!(Some(1000), server.join().unwrap());
+        assert_eq!(BODY.to_vec(), fs::read(&dest).unwrap());
+        assert!(!partial.exists() && !state.exists());
+
+        // Present files aren't downloaded again.
+        assert_eq!(Ok(false), fetch_file(&agent, &dest, &urls, None, None));
+    }
+
+    #[test]
+    fn test_fetch_file_other_mirror() {
+        static FIRST: [u8; 2000] = [7; 2000];
+        static SECOND: [u8; 2000] = [9; 2000];
+        let dir = TempDir::new("fetch-mirrors");
+        let dest = dir.join("cvr.zip");
+
+        // The first mirror drops the connection halfway through. The second
+        // serves different bytes, so its download starts over rather than
+        // resuming the first's, even though no hash is known to catch it.
+        let (first, first_server) = serve(&FIRST, Some(1000));
+        let (second, second_server) = serve(&SECOND, None);
+        let agent = ureq::AgentBuilder::new().build();
+        let fetched = fetch_file(&agent, &dest, &[first, second], None, None);
+        assert_eq!(Ok(true), fetched);
+        assert_eq!(None, first_server.join().unwrap());
+        assert_eq!(None, second_server.join().unwrap());
+        assert_eq!(SECOND.to_vec(), fs::read(&dest).unwrap());
+    }
+}
@@ -0,0 +1,57 @@
+use crate::formats::default_normalization;
+use crate::generate::{generate_election, write_raw, GeneratorOptions};
+use crate::model::metadata::{Contest, ElectionMetadata};
+use colored::*;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Write a synthetic election's ballots to a raw data directory in the given
+/// format, and print an election metadata stub for reading them as JSON.
+pub fn generate(raw_election_dir: &Path, format: &str, options: &GeneratorOptions) {
+    let election = generate_election(options);
+
+    std::fs::create_dir_all(raw_election_dir)
+        .unwrap_or_else(|e| panic!("Failed to create {}: {}", raw_election_dir.display(), e));
+    let loader_params = write_raw(format, &election, raw_election_dir)
+        .unwrap_or_else(|e| panic!("Failed to write ballots: {}", e));
+
+    eprintln!(
+        "Wrote {} ballots for {} candidates in format {} to {}.",
+        election.ballots.len(),
+        election.candidates.len(),
+        format.blue(),
+        raw_election_dir.display()
+    );
+
+    let election = ElectionMetadata {
+        name: "Synthetic Election".to_string(),
+        date: "<YYYY-MM-DD>".to_string(),
+        data_format: format.to_string(),
+        tabulation_options: None,
+        normalization: default_normalization(format).to_string(),
+        rule_set: None,
+        overvote_policy: None,
+        skipped_rank_policy: None,
+        duplicate_ballot_policy: None,
+        contests: vec![Contest {
+            office: "synthetic".to_string(),
+            loader_params: Some(loader_params),
+            reporting_status: None,
+            display_names: BTreeMap::new(),
+            candidate_ids: BTreeMap::new(),
+            variants: Vec::new(),
+            subsets: Vec::new(),
+            notes: Vec::new(),
+            official_first_round: None,
+            vote_for: None,
+        }],
+        files: BTreeMap::new(),
+        downloads: BTreeMap::new(),
+        website: None,
+        precinct_map: None,
+        regions: BTreeMap::new(),
+        others_threshold: None,
+    };
+
+    println!("{}", serde_json::to_string_pretty(&election).unwrap());
+}
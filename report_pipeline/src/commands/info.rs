@@ -1,22 +1,96 @@
 use std::path::Path;
 
+use crate::formats::{check_format, check_raw_data};
 use crate::read_metadata::read_meta;
 use colored::*;
 
-pub fn info(meta_dir: &Path) {
+/// Print the metadata in `meta_dir` and the problems found in it. Given
+/// `raw_dir`, also check that each contest's raw data files exist there and
+/// that their headers can be read.
+pub fn info(meta_dir: &Path, raw_dir: Option<&Path>) {
     for (_, ec) in read_meta(meta_dir) {
         eprintln!("Name: {}", ec.name.blue());
         eprintln!("Path: {}", ec.path.blue());
         eprintln!("Kind: {}", ec.kind.blue());
 
+        for (key, office) in &ec.offices {
+            eprintln!("Office: {}", key.blue());
+            eprintln!("  Name: {}", office.name.blue());
+            if let Some(seats) = office.seats {
+                eprintln!("  Seats: {}", seats.to_string().blue());
+            }
+            if let Some(district) = &office.district {
+                eprintln!("  District: {}", district.id.blue());
+            }
+            if let Some(term_years) = office.term_years {
+                eprintln!("  Term: {} years", term_years.to_string().blue());
+            }
+            for problem in office.problems() {
+                eprintln!("  Invalid: {}", problem.red());
+            }
+        }
+
         for (key, election) in &ec.elections {
             eprintln!("Election: {}", key.blue());
             eprintln!("  Name: {}", election.name.blue());
             eprintln!("  Date: {}", election.date.blue());
 
+            for contest in &election.contests {
+                if !ec.offices.contains_key(&contest.office) {
+                    eprintln!(
+                        "  Invalid: {}",
+                        format!("contest office {} is not in offices", contest.office).red()
+                    );
+                }
+                // Placeholders left from new-jurisdiction templates
+                for (param, value) in contest.loader_params.iter().flatten() {
+                    if value.starts_with('<') && value.ends_with('>') {
+                        eprintln!(
+                            "  Invalid: {}",
+                            format!(
+                                "contest {} loader parameter {} is not filled in",
+                                contest.office, param
+                            )
+                            .red()
+                        );
+                    }
+                }
+            }
+
             for file in election.files.keys() {
                 eprintln!("    File: {}", file.blue());
             }
+
+            if let Some(raw_dir) = raw_dir {
+                let election_dir = raw_dir.join(&ec.path).join(key);
+                if !election_dir.is_dir() {
+                    eprintln!(
+                        "  Invalid: {}",
+                        format!("raw data directory {} is missing", election_dir.display()).red()
+                    );
+                    continue;
+                }
+                for file in election.files.keys() {
+                    if !election_dir.join(file).exists() {
+                        eprintln!(
+                            "  Invalid: {}",
+                            format!("file {} is missing from the raw data", file).red()
+                        );
+                    }
+                }
+                if let Some(mismatch) = check_format(&election.data_format, &election_dir) {
+                    eprintln!("  Warning: {}", mismatch.to_string().yellow());
+                }
+                for contest in &election.contests {
+                    let params = contest.loader_params.clone().unwrap_or_default();
+                    for problem in check_raw_data(&election.data_format, &election_dir, &params) {
+                        eprintln!(
+                            "  Invalid: {}",
+                            format!("contest {} raw data: {}", contest.office, problem).red()
+                        );
+                    }
+                }
+            }
         }
     }
 }
@@ -1,7 +1,45 @@
+mod codegen_ts;
+mod compare_rctab;
+mod convert_preprocessed;
+mod detect;
+mod du;
+mod dump_ballots;
+mod export_api;
+mod fetch;
+mod generate;
 mod info;
+mod new_jurisdiction;
+mod prune;
+mod ranking_limits;
 mod report;
+mod report_config;
+mod schema;
+mod signatures;
 mod sync;
+mod tabulate_patterns;
+mod transfer_graph;
+mod view;
+mod watch;
 
+pub use codegen_ts::codegen_ts;
+pub use compare_rctab::compare_rctab;
+pub use convert_preprocessed::convert_preprocessed;
+pub use detect::detect;
+pub use du::du;
+pub use dump_ballots::dump_ballots;
+pub use export_api::export_api;
+pub use fetch::{fetch, FetchOptions};
+pub use generate::generate;
 pub use info::info;
-pub use report::{report, rebuild_index};
-pub use sync::sync;
+pub use new_jurisdiction::{new_jurisdiction, NewJurisdictionOptions};
+pub use prune::prune;
+pub use ranking_limits::ranking_limits;
+pub use report::{rebuild_index, recheck, report, ReportOptions, PIPELINE_VERSION};
+pub use report_config::{read_report_config, ReportConfig};
+pub use schema::schema;
+pub use signatures::{signing_public_key, verify_signatures};
+pub use sync::{sync, SyncOptions};
+pub use tabulate_patterns::tabulate_patterns;
+pub use transfer_graph::transfer_graph;
+pub use view::view;
+pub use watch::watch;
@@ -0,0 +1,169 @@
+use crate::formats::{default_normalization, loader_params_template, FORMATS};
+use crate::model::metadata::{Contest, ElectionMetadata, Jurisdiction, Office};
+use crate::util::{read_serialized, write_serialized};
+use colored::*;
+use std::collections::BTreeMap;
+use std::fs::create_dir_all;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+/// Values for a new jurisdiction, election and contests. Any left as `None`
+/// are asked for on the terminal.
+pub struct NewJurisdictionOptions {
+    pub name: Option<String>,
+    pub kind: String,
+    pub election: Option<String>,
+    pub election_name: Option<String>,
+    pub date: Option<String>,
+    pub format: Option<String>,
+    /// Contests, as `office` or `office=Office Name`.
+    pub contests: Vec<String>,
+}
+
+/// Ask for a value on the terminal until a non-empty one is given.
+fn prompt(label: &str) -> String {
+    let stdin = io::stdin();
+    loop {
+        eprint!("{}: ", label);
+        io::stderr().flush().unwrap();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap() == 0 {
+            panic!("No value given for {}.", label);
+        }
+        let value = line.trim();
+        if !value.is_empty() {
+            return value.to_string();
+        }
+    }
+}
+
+/// Split a contest argument into its office ID and name. The name defaults
+/// to the ID.
+fn parse_contest(contest: &str) -> (String, String) {
+    match contest.split_once('=') {
+        Some((office, name)) => (office.trim().to_string(), name.trim().to_string()),
+        None => (contest.trim().to_string(), contest.trim().to_string()),
+    }
+}
+
+/// Add an election with template contests to a jurisdiction's metadata
+/// file, creating the file if it doesn't exist.
+pub fn new_jurisdiction(meta_dir: &Path, path: &str, options: NewJurisdictionOptions) {
+    let meta_path = meta_dir.join(format!("{}.json", path));
+
+    let mut jurisdiction: Jurisdiction = if meta_path.exists() {
+        eprintln!("Adding to {}", meta_path.display().to_string().blue());
+        read_serialized(&meta_path)
+    } else {
+        Jurisdiction {
+            name: options.name.unwrap_or_else(|| prompt("Jurisdiction name")),
+            path: path.to_string(),
+            kind: options.kind,
+            locale: None,
+            percent_decimals: None,
+            offices: BTreeMap::new(),
+            elections: BTreeMap::new(),
+        }
+    };
+
+    let election_path = options
+        .election
+        .unwrap_or_else(|| prompt("Election path (e.g. 2024/11)"));
+    if jurisdiction.elections.contains_key(&election_path) {
+        panic!(
+            "Election {} already exists in {}.",
+            election_path,
+            meta_path.display()
+        );
+    }
+
+    let format = options
+        .format
+        .unwrap_or_else(|| prompt(&format!("Data format ({})", FORMATS.join(", "))));
+    let loader_params = loader_params_template(&format)
+        .unwrap_or_else(|| panic!("The format {} is not implemented.", format));
+
+    let mut contests = options.contests;
+    if contests.is_empty() {
+        contests.push(prompt("Contest office (e.g. mayor=Mayor)"));
+    }
+
+    let election = ElectionMetadata {
+        name: options
+            .election_name
+            .unwrap_or_else(|| prompt("Election name")),
+        date: options
+            .date
+            .unwrap_or_else(|| prompt("Election date (YYYY-MM-DD)")),
+        data_format: format.clone(),
+        tabulation_options: None,
+        normalization: default_normalization(&format).to_string(),
+        rule_set: None,
+        overvote_policy: None,
+        skipped_rank_policy: None,
+        duplicate_ballot_policy: None,
+        contests: contests
+            .iter()
+            .map(|contest| {
+                let (office, office_name) = parse_contest(contest);
+                jurisdiction
+                    .offices
+                    .entry(office.clone())
+                    .or_insert_with(|| Office {
+                        name: office_name,
+                        translations: BTreeMap::new(),
+                        seats: None,
+                        district: None,
+                        term_years: None,
+                        id: None,
+                    });
+
+                Contest {
+                    office,
+                    loader_params: Some(loader_params.clone()),
+                    reporting_status: None,
+                    display_names: BTreeMap::new(),
+                    candidate_ids: BTreeMap::new(),
+                    variants: Vec::new(),
+                    subsets: Vec::new(),
+                    notes: Vec::new(),
+                    official_first_round: None,
+                    vote_for: None,
+                }
+            })
+            .collect(),
+        files: BTreeMap::new(),
+        downloads: BTreeMap::new(),
+        website: None,
+        precinct_map: None,
+        regions: BTreeMap::new(),
+        others_threshold: None,
+    };
+    jurisdiction
+        .elections
+        .insert(election_path.clone(), election);
+
+    create_dir_all(meta_path.parent().unwrap()).unwrap();
+    write_serialized(&meta_path, &jurisdiction);
+
+    eprintln!(
+        "Wrote {}. Fill in the loader parameters marked with <...>, put the raw data in {}/{}, then run sync.",
+        meta_path.display().to_string().blue(),
+        path,
+        election_path
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_contest() {
+        assert_eq!(
+            ("mayor".to_string(), "Mayor".to_string()),
+            parse_contest("mayor=Mayor")
+        );
+        assert_eq!(("d5".to_string(), "d5".to_string()), parse_contest("d5"));
+    }
+}
@@ -0,0 +1,313 @@
+use crate::commands::report::RUN_MANIFEST_FILE;
+use crate::model::report::RunManifest;
+use crate::preprocessed::PREPROCESSED_FILES;
+use crate::read_metadata::read_meta;
+use crate::util::{format_size, get_files_from_path, storage_for, Storage};
+use crate::{log_debug, log_info, log_warn};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Why a preprocessed file is pruned.
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum PruneReason {
+    /// The contest has a file in a format that is preferred when reading
+    /// the cache (see `PREPROCESSED_FILES`), so this one is never read.
+    Superseded,
+    /// The contest wasn't reported in the last run, and the file is older
+    /// than the retention window.
+    Expired,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PrunedFile {
+    pub path: String,
+    pub reason: PruneReason,
+    pub size: u64,
+}
+
+/// What a prune did, printed to stdout.
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PruneSummary {
+    pub dry_run: bool,
+    pub pruned: Vec<PrunedFile>,
+    /// Bytes freed, or that would be freed in a dry run.
+    pub freed: u64,
+}
+
+/// The preprocessed directories of the contests whose current file is kept
+/// however old it is: those in the last report run's manifest in
+/// `report_dir`, including any that failed, since the next run tries them
+/// again. A run limited by filters only lists the contests it processed.
+/// Without a manifest, as before the first run, every contest in the
+/// metadata is kept.
+fn live_contests(
+    storage: &dyn Storage,
+    report_dir: &Path,
+    meta_dir: &Path,
+    preprocessed_dir: &Path,
+) -> Result<HashSet<PathBuf>, String> {
+    let manifest_path = report_dir.join(RUN_MANIFEST_FILE);
+    let manifest = match storage.read(&manifest_path) {
+        Ok(contents) => Some(
+            serde_json::from_slice::<RunManifest>(&contents)
+                .map_err(|e| format!("Failed to parse {}: {}", manifest_path.display(), e))?,
+        ),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(format!("Failed to read {}: {}", manifest_path.display(), e)),
+    };
+
+    let mut live: HashSet<PathBuf> = HashSet::new();
+    match manifest {
+        Some(manifest) => live.extend(
+            manifest
+                .contests
+                .iter()
+                .map(|contest| preprocessed_dir.join(&contest.path).join(&contest.office)),
+        ),
+        None => {
+            log_info!(
+                "No run manifest in {}, keeping the contests in the metadata",
+                report_dir.display()
+            );
+            for (_, jurisdiction) in read_meta(meta_dir) {
+                for (election_key, election) in &jurisdiction.elections {
+                    let election_dir = preprocessed_dir.join(&jurisdiction.path).join(election_key);
+                    live.extend(
+                        election
+                            .contests
+                            .iter()
+                            .map(|contest| election_dir.join(&contest.office)),
+                    );
+                }
+            }
+        }
+    }
+    Ok(live)
+}
+
+/// Preprocessed files to prune under `preprocessed_dir`. Only files named
+/// as preprocessed ballots are considered, so nothing else placed there is
+/// ever deleted. `live` has the contest directories whose current file is
+/// kept however old it is (see `live_contests`).
+fn plan_prune(
+    preprocessed_dir: &Path,
+    live: &HashSet<PathBuf>,
+    cutoff: SystemTime,
+) -> Result<Vec<(PathBuf, PruneReason)>, String> {
+    let mut contests: BTreeMap<PathBuf, Vec<(usize, PathBuf)>> = BTreeMap::new();
+    let paths = get_files_from_path(preprocessed_dir)
+        .map_err(|e| format!("Failed to list {}: {}", preprocessed_dir.display(), e))?;
+    for path in paths {
+        let (Some(name), Some(dir)) = (path.file_name(), path.parent()) else {
+            continue;
+        };
+        if let Some(rank) = PREPROCESSED_FILES.iter().position(|file| *file == name) {
+            contests
+                .entry(dir.to_path_buf())
+                .or_default()
+                .push((rank, path));
+        }
+    }
+
+    let mut pruned = Vec::new();
+    for (dir, mut files) in contests {
+        files.sort();
+        let mut files = files.into_iter().map(|(_, path)| path);
+        // Each directory was added with at least one file.
+        let Some(current) = files.next() else {
+            continue;
+        };
+        match fs::metadata(&current).and_then(|m| m.modified()) {
+            Ok(modified) if !live.contains(&dir) && modified < cutoff => {
+                pruned.push((current, PruneReason::Expired));
+            }
+            Ok(_) => {}
+            // Deleted since it was listed, e.g. by a report run.
+            Err(e) => {
+                log_debug!("Skipping {}: {}", current.display(), e);
+            }
+        }
+        pruned.extend(files.map(|path| (path, PruneReason::Superseded)));
+    }
+    Ok(pruned)
+}
+
+/// Remove `dir` and its parents up to `base` while they are empty.
+fn remove_empty_dirs(dir: &Path, base: &Path) {
+    let mut dir = dir;
+    while dir != base && dir.starts_with(base) {
+        if fs::remove_dir(dir).is_err() {
+            break;
+        }
+        dir = match dir.parent() {
+            Some(parent) => parent,
+            None => break,
+        };
+    }
+}
+
+/// Delete preprocessed ballots that the next report run won't read: files
+/// superseded by a preferred format in the same contest directory, and
+/// files of contests the last run didn't report (see `live_contests`) that
+/// haven't been written for `retention`. Raw data and reports are never
+/// touched.
+pub fn prune(
+    meta_dir: &Path,
+    preprocessed_dir: &Path,
+    report_dir: &Path,
+    retention: Duration,
+    dry_run: bool,
+) -> Result<PruneSummary, String> {
+    if !preprocessed_dir.is_dir() {
+        return Err(format!("{} is not a directory", preprocessed_dir.display()));
+    }
+    let live = live_contests(
+        &*storage_for(report_dir),
+        report_dir,
+        meta_dir,
+        preprocessed_dir,
+    )?;
+    let cutoff = SystemTime::now()
+        .checked_sub(retention)
+        .ok_or_else(|| format!("Retention of {}s is too long", retention.as_secs()))?;
+
+    let mut summary = PruneSummary {
+        dry_run,
+        ..Default::default()
+    };
+    for (path, reason) in plan_prune(preprocessed_dir, &live, cutoff)? {
+        let size = fs::metadata(&path).map_or(0, |m| m.len());
+        log_info!(
+            "{} {:?} {} ({})",
+            if dry_run { "Would prune" } else { "Pruning" },
+            reason,
+            path.display(),
+            format_size(size)
+        );
+        if !dry_run {
+            if let Err(e) = fs::remove_file(&path) {
+                log_warn!("Failed to delete {}: {}", path.display(), e);
+                continue;
+            }
+            if let Some(dir) = path.parent() {
+                remove_empty_dirs(dir, preprocessed_dir);
+            }
+        }
+        summary.freed += size;
+        summary.pruned.push(PrunedFile {
+            path: path
+                .strip_prefix(preprocessed_dir)
+                .unwrap_or(&path)
+                .display()
+                .to_string(),
+            reason,
+            size,
+        });
+    }
+    log_info!(
+        "{} {} files, {}",
+        if dry_run { "Would prune" } else { "Pruned" },
+        summary.pruned.len(),
+        format_size(summary.freed)
+    );
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::report::{ContestManifestEntry, ContestStatus};
+    use crate::util::{write_canonical_to, MemoryStorage, TempDir};
+
+    #[test]
+    fn test_live_contests_from_manifest() {
+        let storage = MemoryStorage::default();
+        let report_dir = Path::new("reports");
+        let entry = |office: &str, status| ContestManifestEntry {
+            path: "us/zz/2024/06".to_string(),
+            office: office.to_string(),
+            status,
+            source_files: vec![],
+            winner: None,
+            changes: None,
+            peak_memory: None,
+        };
+        write_canonical_to(
+            &storage,
+            &report_dir.join(RUN_MANIFEST_FILE),
+            &RunManifest {
+                pipeline_version: "test".to_string(),
+                contests: vec![
+                    entry("mayor", ContestStatus::Cached),
+                    entry("clerk", ContestStatus::Failed),
+                ],
+            },
+        );
+
+        // The metadata isn't read when there's a manifest.
+        let live = live_contests(
+            &storage,
+            report_dir,
+            Path::new("no-such-metadata"),
+            Path::new("preprocessed"),
+        )
+        .unwrap();
+        let expected: HashSet<PathBuf> = [
+            PathBuf::from("preprocessed/us/zz/2024/06/mayor"),
+            PathBuf::from("preprocessed/us/zz/2024/06/clerk"),
+        ]
+        .into();
+        assert_eq!(expected, live);
+    }
+
+    #[test]
+    fn test_plan_prune() {
+        let dir = TempDir::new("prune");
+        let contest = |office: &str| dir.join("us/zz/2024/06").join(office);
+        for office in ["mayor", "council", "clerk"] {
+            fs::create_dir_all(contest(office)).unwrap();
+        }
+        // The mayor's legacy file was left behind when it was preprocessed
+        // again; the council contest was renamed to clerk a year ago.
+        fs::write(contest("mayor").join("normalized.bin"), "").unwrap();
+        fs::write(contest("mayor").join("normalized.json.gz"), "").unwrap();
+        fs::write(contest("clerk").join("normalized.bin.zst"), "").unwrap();
+        fs::write(contest("council").join("normalized.bin"), "").unwrap();
+        fs::write(contest("council").join("notes.txt"), "").unwrap();
+        let year_ago = SystemTime::now() - Duration::from_secs(365 * 86400);
+        let council = fs::File::options()
+            .write(true)
+            .open(contest("council").join("normalized.bin"))
+            .unwrap();
+        council.set_modified(year_ago).unwrap();
+
+        let live: HashSet<PathBuf> = [contest("mayor"), contest("clerk")].into();
+        let month_ago = SystemTime::now() - Duration::from_secs(30 * 86400);
+        let mut pruned = plan_prune(&dir, &live, month_ago).unwrap();
+        pruned.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            vec![
+                (
+                    contest("council").join("normalized.bin"),
+                    PruneReason::Expired
+                ),
+                (
+                    contest("mayor").join("normalized.json.gz"),
+                    PruneReason::Superseded
+                ),
+            ],
+            pruned
+        );
+
+        // Within the retention window, even contests that aren't in the
+        // metadata are kept.
+        let pruned = plan_prune(&dir, &live, year_ago - Duration::from_secs(86400)).unwrap();
+        assert_eq!(1, pruned.len());
+    }
+}
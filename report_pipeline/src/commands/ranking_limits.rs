@@ -0,0 +1,207 @@
+use crate::model::election::{CandidateId, ElectionPreprocessed, NormalizedBallot};
+use crate::model::metadata::TabulationOptions;
+use crate::preprocessed::{contest_preprocessed_file, read_preprocessed, PREPROCESSED_FILES};
+use crate::report::winner;
+use crate::tabulator::tabulate_weighted;
+use crate::util::get_files_from_path;
+use crate::{log_info, log_warn};
+use rayon::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Winner of a contest had each ballot counted only up to its first `limit`
+/// rankings. A ballot that ended in an overvote after its first `limit`
+/// rankings no longer reaches it.
+fn truncated_winner(
+    ballots: &[NormalizedBallot],
+    options: &TabulationOptions,
+    limit: usize,
+) -> Option<CandidateId> {
+    let mut rankings: BTreeMap<(&[CandidateId], bool), u32> = BTreeMap::new();
+    for ballot in ballots {
+        let choices = ballot.choice_slice();
+        let truncated = &choices[..choices.len().min(limit)];
+        let overvoted = ballot.overvoted && truncated.len() == choices.len();
+        *rankings.entry((truncated, overvoted)).or_default() += 1;
+    }
+    let rounds = tabulate_weighted(
+        rankings.into_iter().map(|((choices, overvoted), count)| {
+            (
+                NormalizedBallot::new(String::new(), choices.to_vec(), overvoted),
+                count,
+            )
+        }),
+        options,
+    );
+    winner(&rounds)
+}
+
+/// One row of the dataset: a contest's winner, and its winner under each
+/// ranking limit.
+struct ContestLimits {
+    preprocessed: ElectionPreprocessed,
+    winner: Option<CandidateId>,
+    limited_winners: Vec<Option<CandidateId>>,
+}
+
+impl ContestLimits {
+    fn new(preprocessed: ElectionPreprocessed, limits: &[usize]) -> ContestLimits {
+        let ballots = &preprocessed.ballots.ballots;
+        let options = &preprocessed.info.tabulation_options;
+        let winner = truncated_winner(ballots, options, usize::MAX);
+        let limited_winners = limits
+            .iter()
+            .map(|&limit| truncated_winner(ballots, options, limit))
+            .collect();
+        ContestLimits {
+            preprocessed,
+            winner,
+            limited_winners,
+        }
+    }
+
+    fn name(&self, candidate: Option<CandidateId>) -> &str {
+        candidate.map_or("", |c| {
+            self.preprocessed.ballots.candidates[c.0 as usize].display_name()
+        })
+    }
+}
+
+fn write_limits_csv<W: Write>(
+    contests: &[ContestLimits],
+    limits: &[usize],
+    writer: W,
+) -> csv::Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    let mut header: Vec<String> = [
+        "jurisdiction",
+        "election",
+        "office",
+        "date",
+        "ballots",
+        "candidates",
+        "max_ranks",
+        "winner",
+    ]
+    .iter()
+    .map(|column| column.to_string())
+    .collect();
+    header.extend(limits.iter().map(|limit| format!("top{}_winner", limit)));
+    writer.write_record(&header)?;
+
+    for contest in contests {
+        let info = &contest.preprocessed.info;
+        let ballots = &contest.preprocessed.ballots;
+        let max_ranks = ballots
+            .ballots
+            .iter()
+            .map(|b| b.choice_slice().len())
+            .max()
+            .unwrap_or(0);
+        let mut record = vec![
+            info.jurisdiction_path.clone(),
+            info.election_path.clone(),
+            info.office.clone(),
+            info.date.clone(),
+            ballots.ballots.len().to_string(),
+            ballots.candidates.len().to_string(),
+            max_ranks.to_string(),
+            contest.name(contest.winner).to_string(),
+        ];
+        record.extend(
+            contest
+                .limited_winners
+                .iter()
+                .map(|&w| contest.name(w).to_string()),
+        );
+        writer.write_record(&record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Tabulate every preprocessed contest under each ranking limit, counting
+/// each ballot only up to that many rankings, and write each contest's
+/// winners as CSV, to a file or stdout. Logs how often each limit changes
+/// the winner.
+pub fn ranking_limits(preprocessed_dir: &Path, limits: &[usize], output: Option<&Path>) {
+    let contest_dirs: BTreeSet<PathBuf> = get_files_from_path(preprocessed_dir)
+        .unwrap()
+        .into_iter()
+        .filter(|path| {
+            path.file_name()
+                .is_some_and(|f| PREPROCESSED_FILES.iter().any(|name| f == *name))
+        })
+        .map(|path| path.parent().unwrap().to_path_buf())
+        .collect();
+
+    log_info!(
+        "Simulating ranking limits in {} contests...",
+        contest_dirs.len()
+    );
+    let mut contests: Vec<ContestLimits> = contest_dirs
+        .par_iter()
+        .filter_map(|dir| {
+            let path = contest_preprocessed_file(dir);
+            match std::panic::catch_unwind(|| read_preprocessed(&path)) {
+                Ok(preprocessed) => Some(ContestLimits::new(preprocessed, limits)),
+                Err(_) => {
+                    log_warn!("Failed to read {}", path.display());
+                    None
+                }
+            }
+        })
+        .collect();
+    contests.sort_by(|a, b| {
+        let (a, b) = (&a.preprocessed.info, &b.preprocessed.info);
+        (&a.jurisdiction_path, &a.election_path, &a.office).cmp(&(
+            &b.jurisdiction_path,
+            &b.election_path,
+            &b.office,
+        ))
+    });
+
+    for (i, limit) in limits.iter().enumerate() {
+        let changed = contests
+            .iter()
+            .filter(|c| c.limited_winners[i] != c.winner)
+            .count();
+        log_info!(
+            "Top {}: winner changes in {} of {} contests",
+            limit,
+            changed,
+            contests.len()
+        );
+    }
+
+    let result = match output {
+        Some(output) => std::fs::File::create(output)
+            .map_err(csv::Error::from)
+            .and_then(|file| write_limits_csv(&contests, limits, file)),
+        None => write_limits_csv(&contests, limits, std::io::stdout().lock()),
+    };
+    result.unwrap_or_else(|e| panic!("Failed to write ranking limits: {}", e));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncated_winner() {
+        let (a, b, c) = (CandidateId(0), CandidateId(1), CandidateId(2));
+        let mut ballots = vec![];
+        for (choices, count) in [(vec![a], 5), (vec![b], 4), (vec![c, b], 3)] {
+            for _ in 0..count {
+                ballots.push(NormalizedBallot::new(String::new(), choices.clone(), false));
+            }
+        }
+        // C's ballots elect B once C is eliminated, unless they are limited
+        // to a single ranking.
+        let options = TabulationOptions::default();
+        assert_eq!(Some(b), truncated_winner(&ballots, &options, usize::MAX));
+        assert_eq!(Some(b), truncated_winner(&ballots, &options, 2));
+        assert_eq!(Some(a), truncated_winner(&ballots, &options, 1));
+    }
+}
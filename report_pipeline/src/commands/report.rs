@@ -1,739 +1,1847 @@
-use crate::model::election::ElectionPreprocessed;
-use crate::model::metadata::{Contest, ElectionMetadata, Jurisdiction};
-use crate::model::report::{ContestIndexEntry, ContestReport, ElectionIndexEntry, ReportIndex};
+use crate::candidate_history::{
+    candidate_appearances, generate_candidate_index, CandidateAppearance, CANDIDATE_INDEX_FILE,
+};
+use crate::formats::{check_format, read_election};
+use crate::hypothetical_methods::generate_hypothetical_methods;
+use crate::model::election::{
+    CandidateId, Election, ElectionPreprocessed, NormalizedElection, Provenance, SourceFile,
+};
+use crate::model::metadata::{
+    BallotSubset, Contest, ElectionMetadata, Jurisdiction, TabulationVariant,
+};
+use crate::model::notification::{ContestFailure, Notification, RunSummary};
+use crate::model::report::{
+    ContestIndexEntry, ContestManifestEntry, ContestReport, ContestStatus, ContestSummary,
+    ElectionIndexEntry, ElectionSummary, RecheckResult, RecheckStatus, ReportChanges, ReportIndex,
+    RunManifest, SubsetEntry, TabulationStatus, VariantEntry,
+};
+use crate::notify::{send_notification, winner_changes};
+use crate::office_history::{generate_office_index, OFFICE_INDEX_FILE};
+use crate::patterns::{generate_ranking_patterns, RANKING_PATTERNS_FILE};
+use crate::precinct_map::{generate_precinct_map, PRECINCT_MAP_FILE};
+use crate::preprocessed::{read_preprocessed, write_preprocessed_to, PREPROCESSED_FILES};
 use crate::read_metadata::read_meta;
-use crate::report::{generate_report, preprocess_election};
-use crate::util::{read_serialized, write_serialized};
+use crate::report::{
+    candidate_series, condense_rounds, final_round_candidates, format_shares,
+    generate_region_breakdown, generate_report, generate_round_snapshots, group_others,
+    preprocess_election_from_data, reconcile_first_round, DEFAULT_PERCENT_DECIMALS,
+};
+use crate::robustness::generate_elimination_robustness;
+use crate::rules::{rule_set, StatutoryRules};
+use crate::site_metadata::{
+    contest_metadata_stub, generate_sitemap, CONTEST_METADATA_FILE, SITEMAP_FILE,
+};
+use crate::strategy::generate_strategy_susceptibility;
+use crate::summary::{
+    contest_summary, generate_election_summary, generate_trends, SUMMARY_FILE, TRENDS_CSV_FILE,
+    TRENDS_JSON_FILE,
+};
+use crate::util::{
+    format_size, get_files_from_path, hash_file_sha256, peak_rss, read_serialized,
+    read_serialized_from, run_with_timeout, sign_file, storage_for, thread_pool, to_canonical_json,
+    track_peak_memory, write_canonical_to, MemoryBudget, Storage, TimeoutGuard,
+};
+use crate::{log_debug, log_info, log_race, log_warn};
+use ed25519_dalek::SigningKey;
 use rayon::prelude::*;
-use std::collections::HashMap;
-use std::fs::{create_dir_all, read_dir};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::create_dir_all;
 use std::path::{Path, PathBuf};
-use crate::{log_warn, log_info, log_debug, log_race};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Options for the report command.
+pub struct ReportOptions<'a> {
+    /// Re-read raw CVRs even if preprocessed ballots are cached.
+    pub force_preprocess: bool,
+    /// Regenerate reports even if they exist.
+    pub force_report: bool,
+    /// Only generate reports that don't exist, leaving existing ones as they
+    /// are whatever the other cache options say.
+    pub only_missing: bool,
+    /// Only process the jurisdictions and elections with these paths, or all
+    /// of them if empty. An election's path includes its jurisdiction's, e.g.
+    /// `us/ca/alameda/2024/11`.
+    pub filters: &'a [String],
+    /// Number of jurisdictions to preprocess at once (default: number of CPUs).
+    pub preprocess_threads: Option<usize>,
+    /// Number of contests to generate reports for at once (default: number of CPUs).
+    pub report_threads: Option<usize>,
+    /// Limit on the estimated memory of elections preprocessed at once, in bytes.
+    pub max_memory: Option<u64>,
+    /// Base URL of the site. If given, a sitemap and per-contest metadata
+    /// stubs linking to it are written alongside the reports.
+    pub site_url: Option<&'a str>,
+    /// URLs to POST a run summary, and any winner changes, to when the run
+    /// completes.
+    pub webhooks: &'a [String],
+    /// Elections, as `{jurisdiction}/{election}` paths, whose raw data has
+    /// changed. These are preprocessed and reported again even if cached.
+    pub stale_elections: Option<&'a BTreeSet<String>>,
+    /// Also write per-round snapshots of ballots aggregated by remaining
+    /// ranking, for audits.
+    pub round_snapshots: bool,
+    /// Also write the frequency table of distinct rankings.
+    pub ranking_patterns: bool,
+    /// If given, analyze whether each winner is stable under alternative
+    /// elimination orders, treating candidates within this many votes of
+    /// last place as tied with it.
+    pub elimination_tolerance: Option<u32>,
+    /// Also count each contest under plurality, approval and Borda, as a
+    /// hypothetical comparison.
+    pub hypothetical_methods: bool,
+    /// Also check each contest for monotonicity failures.
+    pub strategy_susceptibility: bool,
+    /// Merge runs of rounds that don't change the leaders into one step in
+    /// each report's rounds, keeping the full sequence as well.
+    pub condense_rounds: bool,
+    /// Key to write a detached signature of each report and the index with.
+    pub signing_key: Option<&'a SigningKey>,
+    /// Time after which a contest still being preprocessed or reported is
+    /// marked failed, so that a pathological file can't hang the run.
+    pub contest_timeout: Option<Duration>,
+    /// Compress preprocessed ballots with zstd at this level, rather than
+    /// writing them uncompressed.
+    pub zstd_level: Option<i32>,
+}
 
-/// Check if a candidate name is a write-in (handles "Write-in", "Write in", "Undeclared Write-ins", "UWI", etc.)
-fn is_write_in_by_name(name: &str) -> bool {
-    let normalized = name.to_lowercase();
-    normalized == "write-in"
-        || normalized == "write in"
-        || normalized == "undeclared write-ins"
-        || normalized == "uwi"
+/// Sign a written file with the given key, if any.
+fn sign_artifact(storage: &dyn Storage, key: Option<&SigningKey>, path: &Path) {
+    if let Some(key) = key {
+        sign_file(storage, key, path)
+            .unwrap_or_else(|e| panic!("Failed to sign {}: {}", path.display(), e));
+    }
 }
 
-/// Process a single contest and return the ContestIndexEntry
-fn process_contest(
-    contest: &Contest,
-    election: &ElectionMetadata,
-    election_path: &str,
-    jurisdiction: &Jurisdiction,
-    raw_base: &Path,
-    report_dir: &Path,
-    preprocessed_dir: &Path,
-    force_preprocess: bool,
-    force_report: bool,
-) -> ContestIndexEntry {
-    let office = jurisdiction
-        .offices
-        .get(&contest.office)
-        .unwrap_or_else(|| panic!("Expected office {} to be in offices.", &contest.office));
-    log_race!(
-        &jurisdiction.name,
-        &election.name,
-        &office.name
-    );
+/// Whether a file exists in the report storage. An error checking, such as
+/// an unreachable bucket, panics rather than counting as a missing file.
+fn exists_in(storage: &dyn Storage, path: &Path) -> bool {
+    storage
+        .exists(path)
+        .unwrap_or_else(|e| panic!("Failed to check {}: {}", path.display(), e))
+}
 
-    let report_path = Path::new(report_dir)
-        .join(&jurisdiction.path)
-        .join(&election_path)
-        .join(&contest.office)
-        .join("report.json");
-    let preprocessed_path = Path::new(preprocessed_dir)
-        .join(&jurisdiction.path)
-        .join(&election_path)
-        .join(&contest.office)
-        .join("normalized.json.gz");
+/// Whether jurisdiction and election path filters (see
+/// `ReportOptions::filters`) include an election. No filters include all.
+fn filters_include(filters: &[String], jurisdiction_path: &str, election_path: &str) -> bool {
+    filters.is_empty()
+        || filters.iter().any(|filter| {
+            filter == jurisdiction_path
+                || *filter == format!("{}/{}", jurisdiction_path, election_path)
+        })
+}
 
-    let report =
-        if report_path.exists() && preprocessed_path.exists() && !force_report && !force_preprocess
-        {
-            log_debug!(
-                "Skipping because {} exists. Use --force-report to regenerate.",
-                report_path.to_str().unwrap()
-            );
-            read_serialized(&report_path)
-        } else {
-            create_dir_all(&report_path.parent().unwrap()).unwrap();
+impl<'a> ReportOptions<'a> {
+    fn is_stale(&self, election: &ElectionTasks) -> bool {
+        self.stale_elections
+            .is_some_and(|stale| stale.contains(&election.path()))
+    }
 
-            let preprocessed: ElectionPreprocessed = if preprocessed_path.exists()
-                && !force_preprocess
-            {
-                log_debug!(
-                    "Loading preprocessed {}.",
-                    preprocessed_path.to_str().unwrap()
-                );
-                read_serialized(&preprocessed_path)
-            } else {
-                create_dir_all(preprocessed_path.parent().unwrap()).unwrap();
+    fn contest_options(&self) -> ContestOptions {
+        ContestOptions {
+            site_url: self.site_url.map(|url| url.to_string()),
+            round_snapshots: self.round_snapshots,
+            ranking_patterns: self.ranking_patterns,
+            elimination_tolerance: self.elimination_tolerance,
+            hypothetical_methods: self.hypothetical_methods,
+            strategy_susceptibility: self.strategy_susceptibility,
+            condense_rounds: self.condense_rounds,
+            signing_key: self.signing_key.cloned(),
+        }
+    }
+}
 
-                log_debug!(
-                    "Generating preprocessed {}.",
-                    preprocessed_path.to_str().unwrap()
-                );
-                let preprocessed =
-                    preprocess_election(raw_base, election, election_path, jurisdiction, contest);
-                write_serialized(&preprocessed_path, &preprocessed);
-                log_debug!("Processed {} ballots", preprocessed.ballots.ballots.len());
-                preprocessed
-            };
+/// The options each contest's report is generated with, owned so that a
+/// contest can be reported on a thread of its own (see `run_contest_stage`).
+#[derive(Clone)]
+struct ContestOptions {
+    site_url: Option<String>,
+    round_snapshots: bool,
+    ranking_patterns: bool,
+    elimination_tolerance: Option<u32>,
+    hypothetical_methods: bool,
+    strategy_susceptibility: bool,
+    condense_rounds: bool,
+    signing_key: Option<SigningKey>,
+}
 
-            log_debug!("Generating report...");
-            let contest_report = generate_report(&preprocessed);
+/// File name of the per-round ballot snapshots, written next to `report.json`.
+const ROUND_SNAPSHOTS_FILE: &str = "round_snapshots.json";
 
-            log_debug!("Writing report to disk...");
-            write_serialized(&report_path, &contest_report);
-            log_debug!("Report written successfully.");
+/// Version of this pipeline recorded in report provenance.
+pub const PIPELINE_VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), "+", env!("GIT_VERSION"));
 
-            // Explicitly drop preprocessed data to free memory before next contest
-            drop(preprocessed);
+/// What the last run did to each contest, written to the report directory.
+pub const RUN_MANIFEST_FILE: &str = "run-manifest.json";
 
-            contest_report
-        };
+/// Hash the raw data files of an election, as listed in its metadata.
+fn hash_source_files(raw_election_path: &Path, election: &ElectionMetadata) -> Vec<SourceFile> {
+    election
+        .files
+        .keys()
+        .filter_map(
+            |name| match hash_file_sha256(&raw_election_path.join(name)) {
+                Ok(sha256) => Some(SourceFile {
+                    name: name.clone(),
+                    sha256,
+                }),
+                Err(e) => {
+                    log_warn!("Failed to hash source file {}: {}", name, e);
+                    None
+                }
+            },
+        )
+        .collect()
+}
 
-    // Check if any candidate is named "Write-in" or "Write in" (case-insensitive)
-    let has_write_in_by_name = report.candidates.iter().any(|c| {
-        is_write_in_by_name(&c.name)
-    });
+/// Rough ratio of peak memory while preprocessing an election to the size
+/// of its raw data. Raw CVRs are often compressed, and every contest read
+/// from them is held in memory at once by the batch readers.
+const MEMORY_PER_RAW_BYTE: u64 = 8;
 
-    // Extract just the index data we need
-    let index_entry = ContestIndexEntry {
-        office: report.info.office.clone(),
-        office_name: report.info.office_name.clone(),
-        name: report.info.name.clone(),
-        winner: report
-            .winner()
-            .map(|w| w.name.clone())
-            .unwrap_or_else(|| "No Winner".to_string()),
-        num_candidates: report.num_candidates,
-        num_rounds: report.rounds.len() as u32,
-        condorcet_winner: report
-            .condorcet
-            .map(|c| report.candidates[c.0 as usize].name.clone()),
-        has_non_condorcet_winner: report.condorcet.is_some() && report.condorcet != report.winner,
-        has_write_in_by_name,
-    };
+/// Estimate the memory needed to preprocess an election from the size of
+/// its raw data.
+fn estimate_preprocess_memory(raw_election_path: &Path) -> u64 {
+    let raw_bytes: u64 = get_files_from_path(raw_election_path)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|file| file.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum();
+
+    raw_bytes * MEMORY_PER_RAW_BYTE
+}
 
-    // Drop the full report to free memory
-    drop(report);
+/// Differences between a contest's previous report and the one replacing it.
+fn report_changes(previous: &ContestReport, report: &ContestReport) -> ReportChanges {
+    let previous_winner = previous.winner().map(|w| w.display_name().to_string());
+    let winner = report.winner().map(|w| w.display_name().to_string());
+    ReportChanges {
+        winner_changed: previous_winner != winner,
+        previous_winner,
+        rounds_changed: previous.full_rounds() != report.full_rounds(),
+    }
+}
 
-    index_entry
+/// Whether a report has no ballots, candidates or rounds, and is left out
+/// of the index. Reports on contests whose ballots rank no candidate, or
+/// only one, and on write-in-only contests are listed with their status.
+fn is_empty_report(report: &ContestReport) -> bool {
+    report.ballot_count == 0
+        || (report.num_candidates == 0 && report.status != TabulationStatus::WriteInOnly)
+        || report.rounds.is_empty()
 }
 
-/// Process a NYC election with batch optimization
-fn process_nyc_election_batch(
+/// Directory holding the outputs for one contest, under either the report
+/// or preprocessed output directory.
+fn contest_dir(
+    base: &Path,
+    jurisdiction: &Jurisdiction,
     election_path: &str,
+    contest: &Contest,
+) -> PathBuf {
+    base.join(&jurisdiction.path)
+        .join(election_path)
+        .join(&contest.office)
+}
+
+/// If the election has precinct boundaries, write per-precinct results as
+/// GeoJSON next to the report and record it on the report.
+fn write_precinct_map(
+    storage: &dyn Storage,
     election: &ElectionMetadata,
-    jurisdiction: &Jurisdiction,
-    raw_base: &Path,
-    report_dir: &Path,
-    preprocessed_dir: &Path,
-    force_preprocess: bool,
-    force_report: bool,
-) -> Vec<ContestIndexEntry> {
-    use crate::formats::nyc_batch_reader;
+    raw_election_path: &Path,
+    preprocessed: &ElectionPreprocessed,
+    report: &mut ContestReport,
+    report_path: &Path,
+) {
+    let precinct_map = match &election.precinct_map {
+        Some(precinct_map) => precinct_map,
+        None => return,
+    };
+    let ballots = &preprocessed.ballots.ballots;
+    if !ballots.iter().any(|b| b.precinct.is_some()) {
+        log_debug!("No precincts recorded on ballots, skipping precinct map.");
+        return;
+    }
 
-    // raw_base is the jurisdiction path, need to add election_path
-    let raw_path = raw_base.join(election_path);
+    let geojson_path = raw_election_path.join(&precinct_map.geojson);
+    if !geojson_path.exists() {
+        log_warn!("Precinct GeoJSON {} not found.", geojson_path.display());
+        return;
+    }
 
-    // Prepare contest data for batch processing
-    let contests_with_offices: Vec<(String, std::collections::BTreeMap<String, String>)> = election
-        .contests
-        .iter()
-        .filter_map(|contest| {
-            let params = contest.loader_params.clone()?;
-            Some((contest.office.clone(), params))
-        })
+    log_debug!("Generating precinct map...");
+    let geojson = generate_precinct_map(
+        read_serialized(&geojson_path),
+        &precinct_map.property,
+        ballots,
+        &report.candidates,
+        &final_round_candidates(&report.rounds),
+    );
+    write_canonical_to(
+        storage,
+        &report_path.with_file_name(PRECINCT_MAP_FILE),
+        &geojson,
+    );
+    report.precinct_map = Some(PRECINCT_MAP_FILE.to_string());
+}
+
+/// Write per-round snapshots of the ballots next to the report and record
+/// them on the report.
+fn write_round_snapshots(
+    storage: &dyn Storage,
+    preprocessed: &ElectionPreprocessed,
+    report: &mut ContestReport,
+    report_path: &Path,
+) {
+    log_debug!("Generating round snapshots...");
+    let candidates: Vec<CandidateId> = (0..report.candidates.len())
+        .map(|i| CandidateId(i as u32))
         .collect();
+    let snapshots = generate_round_snapshots(
+        &candidates,
+        &preprocessed.ballots.ballots,
+        report.full_rounds(),
+    );
+    write_canonical_to(
+        storage,
+        &report_path.with_file_name(ROUND_SNAPSHOTS_FILE),
+        &snapshots,
+    );
+    report.round_snapshots = Some(ROUND_SNAPSHOTS_FILE.to_string());
+}
 
-    // Batch read all contests at once
-    let mut elections_by_office = nyc_batch_reader(&raw_path, contests_with_offices);
+/// File name of the report on a contest tabulated under a variant, written
+/// next to the official `report.json`.
+fn variant_report_file(id: &str) -> String {
+    format!("report-{}.json", id)
+}
 
-    // Now process each contest using the pre-loaded election data
-    election
-        .contests
+/// Tabulate the preprocessed ballots under each variant's rules and write
+/// the variant reports next to the official report.
+fn write_variant_reports(
+    task: &ContestTask,
+    storage: &dyn Storage,
+    preprocessed: &mut ElectionPreprocessed,
+    report_path: &Path,
+) -> Vec<VariantEntry> {
+    task.contest
+        .variants
         .iter()
-        .filter_map(|contest| {
-            let office = jurisdiction
-                .offices
-                .get(&contest.office)
-                .unwrap_or_else(|| panic!("Expected office {} to be in offices.", &contest.office));
-            log_race!(
-                &jurisdiction.name,
-                &election.name,
-                &office.name
+        .map(|variant| {
+            log_debug!("Generating report for variant {}...", variant.id);
+            let official_options = std::mem::replace(
+                &mut preprocessed.info.tabulation_options,
+                variant.tabulation_options.clone(),
             );
+            let mut report = generate_report(preprocessed);
+            preprocessed.info.tabulation_options = official_options;
 
-            let report_path = Path::new(report_dir)
-                .join(&jurisdiction.path)
-                .join(election_path)
-                .join(&contest.office)
-                .join("report.json");
+            report.variant = Some(variant.id.clone());
+            task.apply_metadata(&mut report);
+            if let Some(provenance) = &mut report.provenance {
+                provenance.tabulation_options = variant.tabulation_options.clone();
+            }
 
-            let preprocessed_path = Path::new(preprocessed_dir)
-                .join(&jurisdiction.path)
-                .join(election_path)
-                .join(&contest.office)
-                .join("normalized.json.gz");
+            let file = variant_report_file(&variant.id);
+            write_canonical_to(storage, &report_path.with_file_name(&file), &report);
 
-            create_dir_all(report_path.parent().unwrap()).unwrap();
-            create_dir_all(preprocessed_path.parent().unwrap()).unwrap();
+            VariantEntry {
+                id: variant.id.clone(),
+                name: variant.name.clone(),
+                report: file,
+                winner: report.winner().map(|w| w.display_name().to_string()),
+                num_rounds: report.rounds.len() as u32,
+            }
+        })
+        .collect()
+}
 
-            // Take ownership of the election data from batch results
-            let raw_election = elections_by_office.remove(&contest.office)?;
+/// Whether a cached report lists the variants currently in the contest's
+/// metadata.
+fn has_variants(report: &ContestReport, variants: &[TabulationVariant]) -> bool {
+    report.variants.len() == variants.len()
+        && report
+            .variants
+            .iter()
+            .zip(variants)
+            .all(|(entry, variant)| entry.id == variant.id && entry.name == variant.name)
+}
 
-            // Preprocess with the loaded election data
-            let preprocessed = if force_preprocess || !preprocessed_path.exists() {
-                let preprocessed = crate::report::preprocess_election_from_data(
-                    raw_election,
-                    election,
-                    jurisdiction,
-                    contest,
-                    election_path,
-                );
-                write_serialized(&preprocessed_path, &preprocessed);
-                log_debug!("Processed {} ballots", preprocessed.ballots.ballots.len());
-                preprocessed
-            } else {
-                log_debug!(
-                    "Reading cached preprocessed {}",
-                    preprocessed_path.display()
+/// Path of the report on a subset of a contest's ballots, relative to the
+/// contest's report directory.
+fn subset_report_file(id: &str) -> String {
+    format!("subsets/{}.json", id)
+}
+
+/// The preprocessed ballots of a contest that are in a subset. Statistics
+/// of the raw ballots, which can't be recomputed for the subset, are left
+/// out.
+fn subset_preprocessed(
+    preprocessed: &ElectionPreprocessed,
+    subset: &BallotSubset,
+) -> ElectionPreprocessed {
+    ElectionPreprocessed {
+        info: preprocessed.info.clone(),
+        ballots: NormalizedElection {
+            candidates: preprocessed.ballots.candidates.clone(),
+            ballots: preprocessed
+                .ballots
+                .ballots
+                .iter()
+                .filter(|b| subset.includes(b.precinct.as_deref(), b.counting_group.as_deref()))
+                .cloned()
+                .collect(),
+            adjudicated_ballots: None,
+            adjudication: None,
+            duplicate_ballots: None,
+            candidate_reconciliation: None,
+            rank_positions: None,
+            duplicate_rankings: None,
+            first_round_adjustments: None,
+            overvote_handling: None,
+            skipped_rank_handling: None,
+            truncated_ballots: None,
+        },
+        provenance: preprocessed.provenance.clone(),
+    }
+}
+
+/// Tabulate the ballots in each of the contest's subsets and write the
+/// subset reports under the official report's directory.
+fn write_subset_reports(
+    task: &ContestTask,
+    storage: &dyn Storage,
+    preprocessed: &ElectionPreprocessed,
+    report_path: &Path,
+) -> Vec<SubsetEntry> {
+    task.contest
+        .subsets
+        .iter()
+        .map(|subset| {
+            log_debug!("Generating report for subset {}...", subset.id);
+            let mut report = generate_report(&subset_preprocessed(preprocessed, subset));
+            report.subset = Some(subset.id.clone());
+            task.apply_metadata(&mut report);
+            if report.ballot_count == 0 {
+                log_warn!(
+                    "Subset {} of {} in {} has no ballots",
+                    subset.id,
+                    task.contest.office,
+                    task.election_path
                 );
-                read_serialized(&preprocessed_path)
-            };
+            }
 
-            // Generate report
-            let report = if force_report || !report_path.exists() {
-                log_debug!("Generating report...");
-                let contest_report = generate_report(&preprocessed);
-                log_debug!("Writing report to disk...");
-                write_serialized(&report_path, &contest_report);
-                log_debug!("Report written successfully.");
-                contest_report
-            } else {
-                read_serialized(&report_path)
-            };
+            let file = subset_report_file(&subset.id);
+            let path = report_path.with_file_name(&file);
+            storage.create_dir_all(path.parent().unwrap()).unwrap();
+            write_canonical_to(storage, &path, &report);
+
+            SubsetEntry {
+                id: subset.id.clone(),
+                name: subset.name.clone(),
+                report: file,
+                ballot_count: report.ballot_count,
+                winner: report.winner().map(|w| w.display_name().to_string()),
+                num_rounds: report.rounds.len() as u32,
+            }
+        })
+        .collect()
+}
+
+/// Whether a cached report lists the subsets currently in the contest's
+/// metadata. Changing a subset's filters without renaming it requires
+/// regenerating the report.
+fn has_subsets(report: &ContestReport, subsets: &[BallotSubset]) -> bool {
+    report.subsets.len() == subsets.len()
+        && report
+            .subsets
+            .iter()
+            .zip(subsets)
+            .all(|(entry, subset)| entry.id == subset.id && entry.name == subset.name)
+}
+
+/// A contest to preprocess or report on, with the context needed to locate
+/// its input and output files.
+struct ContestTask<'a> {
+    contest: &'a Contest,
+    election: &'a ElectionMetadata,
+    election_path: &'a str,
+    /// Shared, so that a contest run on a thread of its own can hold on to
+    /// it (see `OwnedContestTask`).
+    jurisdiction: &'a Arc<Jurisdiction>,
+    /// Raw data directory of the jurisdiction.
+    raw_base: PathBuf,
+    /// Stages to run rather than load from cache.
+    plan: CachePlan,
+}
+
+/// Which stages of a contest to run rather than load from cache. It is
+/// decided once, before either pass writes anything, and both passes
+/// follow it, so a contest is treated the same whichever way its ballots
+/// are read.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct CachePlan {
+    /// Read the raw CVRs again.
+    preprocess: bool,
+    /// Generate the report again.
+    report: bool,
+    /// Use a cached report even if it lacks the contest's variants or
+    /// subsets, which otherwise has it generated again.
+    keep_report: bool,
+}
+
+impl CachePlan {
+    /// Each stage runs if it is forced or its output isn't cached, whether or
+    /// not the other stage's is. A report is also regenerated when its
+    /// ballots are forced to be preprocessed again, since a cached report
+    /// may not match them.
+    fn new(
+        force_preprocess: bool,
+        force_report: bool,
+        preprocessed_cached: bool,
+        report_cached: bool,
+    ) -> CachePlan {
+        let preprocess = force_preprocess || !preprocessed_cached;
+        CachePlan {
+            preprocess,
+            report: force_preprocess || force_report || !report_cached,
+            keep_report: false,
+        }
+    }
+
+    /// Run neither stage and keep the cached report as it is if there is
+    /// one, for `only_missing`.
+    fn unless_reported(self, report_cached: bool) -> CachePlan {
+        if report_cached {
+            CachePlan {
+                keep_report: true,
+                ..CachePlan::default()
+            }
+        } else {
+            self
+        }
+    }
+}
+
+impl<'a> ContestTask<'a> {
+    fn preprocessed_dir(&self, preprocessed_dir: &Path) -> PathBuf {
+        contest_dir(
+            preprocessed_dir,
+            self.jurisdiction,
+            self.election_path,
+            self.contest,
+        )
+    }
+
+    /// Path of the cached preprocessed ballots for this contest, if any,
+    /// preferring the binary formats over the legacy JSON format.
+    fn cached_preprocessed_path(&self, preprocessed_dir: &Path) -> Option<PathBuf> {
+        let dir = self.preprocessed_dir(preprocessed_dir);
+        PREPROCESSED_FILES
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|p| p.exists())
+    }
+
+    fn report_path(&self, report_dir: &Path) -> PathBuf {
+        contest_dir(
+            report_dir,
+            self.jurisdiction,
+            self.election_path,
+            self.contest,
+        )
+        .join("report.json")
+    }
+
+    fn log_race(&self) {
+        let office = self
+            .jurisdiction
+            .offices
+            .get(&self.contest.office)
+            .unwrap_or_else(|| {
+                panic!("Expected office {} to be in offices.", &self.contest.office)
+            });
+        log_race!(&self.jurisdiction.name, &self.election.name, &office.name);
+    }
+
+    /// Record the inputs and rules used to produce this contest's report.
+    fn provenance(&self, source_files: &[SourceFile]) -> Provenance {
+        Provenance {
+            source_files: source_files.to_vec(),
+            data_format: self.election.data_format.clone(),
+            loader_params: self.contest.loader_params.clone(),
+            normalization: self.election.normalization.clone(),
+            overvote_policy: self.election.overvote_policy,
+            skipped_rank_policy: self.election.skipped_rank_policy,
+            tabulation_options: self.election.tabulation_options.clone().unwrap_or_default(),
+            rule_set: self
+                .election
+                .rule_set
+                .as_deref()
+                .and_then(rule_set)
+                .map(StatutoryRules::reference),
+            pipeline_version: PIPELINE_VERSION.to_string(),
+        }
+    }
+
+    /// Apply the metadata that can change without tabulating again (reporting
+    /// status, display names, candidate identifiers, notes, office details,
+    /// percentage formatting and the reconciliation with official totals) to
+    /// a report of this contest. Returns whether the report changed.
+    fn apply_metadata(&self, report: &mut ContestReport) -> bool {
+        let mut changed = false;
+
+        if report.reporting_status != self.contest.reporting_status {
+            report.reporting_status = self.contest.reporting_status.clone();
+            changed = true;
+        }
 
-            // Skip empty reports (no ballots, candidates, or rounds)
-            if report.ballot_count == 0
-                || report.num_candidates == 0
-                || report.rounds.is_empty()
+        if report.info.locale != self.jurisdiction.locale {
+            report.info.locale = self.jurisdiction.locale.clone();
+            changed = true;
+        }
+
+        if report.info.notes != self.contest.notes {
+            report.info.notes = self.contest.notes.clone();
+            changed = true;
+        }
+
+        if let Some(office) = self.jurisdiction.offices.get(&self.contest.office) {
+            let info = &mut report.info;
+            if info.office_name_translations != office.translations
+                || info.seats != office.seats
+                || info.district != office.district
+                || info.term_years != office.term_years
+                || info.office_id != office.id
             {
-                log_debug!(
-                    "Skipping empty report: {}",
-                    report.info.office
+                info.office_name_translations = office.translations.clone();
+                info.seats = office.seats;
+                info.district = office.district.clone();
+                info.term_years = office.term_years;
+                info.office_id = office.id.clone();
+                changed = true;
+            }
+        }
+
+        for candidate in &mut report.candidates {
+            let display_name = self.contest.display_names.get(&candidate.name);
+            if candidate.display_name.as_ref() != display_name {
+                candidate.display_name = display_name.cloned();
+                changed = true;
+            }
+            let canonical_id = self.contest.candidate_ids.get(&candidate.name);
+            if candidate.canonical_id.as_ref() != canonical_id {
+                candidate.canonical_id = canonical_id.cloned();
+                changed = true;
+            }
+        }
+
+        let decimals = self
+            .jurisdiction
+            .percent_decimals
+            .unwrap_or(DEFAULT_PERCENT_DECIMALS);
+        if format_shares(&mut report.rounds, decimals) {
+            changed = true;
+        }
+        if let Some(expanded) = &mut report.expanded_rounds {
+            if format_shares(expanded, decimals) {
+                changed = true;
+            }
+        }
+        // Reports cached before the series was added lack it.
+        let series = candidate_series(report.full_rounds());
+        if report.candidate_series != series {
+            report.candidate_series = series;
+            changed = true;
+        }
+
+        let others = self
+            .election
+            .others_threshold
+            .and_then(|threshold| group_others(report, threshold));
+        if report.others != others {
+            report.others = others;
+            changed = true;
+        }
+
+        // Reconciled after display names, which official totals may use.
+        // Official totals cover all ballots, so subsets aren't reconciled.
+        let official_first_round = self
+            .contest
+            .official_first_round
+            .as_ref()
+            .filter(|_| report.subset.is_none());
+        let reconciliation = official_first_round.map(|official| {
+            reconcile_first_round(
+                &report.candidates,
+                &report.rounds,
+                report.first_round_adjustments.as_ref(),
+                official,
+            )
+        });
+        if report.reconciliation != reconciliation {
+            if let Some(reconciliation) = &reconciliation {
+                if reconciliation.total_discrepancy > 0 {
+                    log_warn!(
+                        "First round of {} in {} differs from the official canvass by {} votes",
+                        self.contest.office,
+                        self.election_path,
+                        reconciliation.total_discrepancy
+                    );
+                }
+            }
+            report.reconciliation = reconciliation;
+            changed = true;
+        }
+
+        changed
+    }
+
+    /// Generate this contest's report from its preprocessed ballots, with
+    /// its metadata applied and the analyses `options` asks for. Files
+    /// written alongside the report are left to the caller.
+    fn tabulate(
+        &self,
+        preprocessed: &ElectionPreprocessed,
+        options: &ContestOptions,
+    ) -> ContestReport {
+        let mut contest_report = generate_report(preprocessed);
+        if options.condense_rounds {
+            condense_rounds(&mut contest_report);
+        }
+        self.apply_metadata(&mut contest_report);
+        if contest_report.status == TabulationStatus::NoVotes {
+            log_warn!(
+                "No ballot ranks a candidate in {} in {}",
+                self.contest.office,
+                self.election_path
+            );
+        }
+        if let Some(tally) = &contest_report.plurality {
+            if !tally.tied.is_empty() {
+                log_warn!(
+                    "{} candidates are tied for the last place in {} in {}",
+                    tally.tied.len(),
+                    self.contest.office,
+                    self.election_path
                 );
-                drop(report);
-                drop(preprocessed);
-                return None;
             }
+            // The other analyses are of rankings, which a non-ranked contest
+            // doesn't have.
+            return contest_report;
+        }
+        if let Some(exhausted) = &contest_report.exhausted_ballots {
+            if exhausted.exceeds_margin {
+                log_warn!(
+                    "{} ballots exhausted by overvotes or skipped ranks in {} in {} exceed the final margin of {}",
+                    exhausted.rule_exhausted,
+                    self.contest.office,
+                    self.election_path,
+                    exhausted.final_margin
+                );
+            }
+        }
+        if !self.election.regions.is_empty() {
+            let candidates: Vec<CandidateId> = contest_report
+                .total_votes
+                .iter()
+                .map(|v| v.candidate)
+                .collect();
+            contest_report.regions = generate_region_breakdown(
+                &candidates,
+                &preprocessed.ballots.ballots,
+                &final_round_candidates(&contest_report.rounds),
+                &self.election.regions,
+            );
+        }
+        if let (Some(tolerance), Some(winner)) =
+            (options.elimination_tolerance, contest_report.winner)
+        {
+            let robustness =
+                generate_elimination_robustness(&preprocessed.ballots.ballots, winner, tolerance);
+            if !robustness.stable {
+                log_warn!(
+                    "Winner of {} in {} could change under elimination orders within {} votes",
+                    self.contest.office,
+                    self.election_path,
+                    tolerance
+                );
+            }
+            contest_report.elimination_robustness = Some(robustness);
+        }
+        if options.hypothetical_methods {
+            contest_report.hypothetical_methods = Some(generate_hypothetical_methods(
+                contest_report.candidates.len(),
+                &preprocessed.ballots.ballots,
+            ));
+        }
+        if options.strategy_susceptibility {
+            let susceptibility = generate_strategy_susceptibility(
+                &preprocessed.ballots.ballots,
+                &preprocessed.info.tabulation_options,
+                contest_report.full_rounds(),
+            );
+            if let Some(s) = &susceptibility {
+                if !s.downward_monotonicity.is_empty() || !s.upward_monotonicity.is_empty() {
+                    log_warn!(
+                        "{} in {} is not monotonic: ranking a candidate differently on some ballots changes the winner",
+                        self.contest.office,
+                        self.election_path
+                    );
+                }
+            }
+            contest_report.strategy_susceptibility = susceptibility;
+        }
+        contest_report
+    }
+
+    /// Write preprocessed ballots for this contest to the cache, along with
+    /// their provenance, compressed with zstd at `zstd_level` if given.
+    fn write_preprocessed(
+        &self,
+        preprocessed_dir: &Path,
+        mut preprocessed: ElectionPreprocessed,
+        source_files: &[SourceFile],
+        zstd_level: Option<i32>,
+    ) {
+        let dir = self.preprocessed_dir(preprocessed_dir);
+        create_dir_all(&dir).unwrap();
+        preprocessed.provenance = Some(self.provenance(source_files));
+        write_preprocessed_to(&dir, &preprocessed, zstd_level);
+        log_debug!("Processed {} ballots", preprocessed.ballots.ballots.len());
+    }
+}
+
+/// A contest task that shares ownership of its jurisdiction's metadata, for
+/// running on a thread that may outlive the run.
+struct OwnedContestTask {
+    contest: Contest,
+    election_path: String,
+    jurisdiction: Arc<Jurisdiction>,
+    raw_base: PathBuf,
+    plan: CachePlan,
+}
+
+impl OwnedContestTask {
+    fn new(task: &ContestTask) -> OwnedContestTask {
+        OwnedContestTask {
+            contest: task.contest.clone(),
+            election_path: task.election_path.to_string(),
+            jurisdiction: Arc::clone(task.jurisdiction),
+            raw_base: task.raw_base.clone(),
+            plan: task.plan,
+        }
+    }
+
+    fn task(&self) -> ContestTask<'_> {
+        ContestTask {
+            contest: &self.contest,
+            election: &self.jurisdiction.elections[&self.election_path],
+            election_path: &self.election_path,
+            jurisdiction: &self.jurisdiction,
+            raw_base: self.raw_base.clone(),
+            plan: self.plan,
+        }
+    }
+}
 
-            // Check if any candidate is named "Write-in" or "Write in" (case-insensitive)
-            let has_write_in_by_name = report.candidates.iter().any(|c| {
-                is_write_in_by_name(&c.name)
+/// Run a stage of a contest (named in the log by its verb, `stage`), with
+/// the peak memory it allocated. If it panics, or a timeout is given and the stage
+/// doesn't finish in time, the contest fails with the reason. With a
+/// timeout, the stage runs on a thread of its own, sharing the contest's
+/// metadata, so the run can move on without it. The stage must claim the
+/// guard it is given before writing anything (see `TimeoutGuard`).
+fn run_contest_stage<T: Send + 'static>(
+    task: &ContestTask,
+    stage: &str,
+    timeout: Option<Duration>,
+    run: impl FnOnce(&ContestTask, &TimeoutGuard) -> T + Send + 'static,
+) -> (Result<T, String>, Option<u64>) {
+    let (result, peak) = match timeout {
+        None => {
+            let (result, peak) = track_peak_memory(|| {
+                std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    run(task, &TimeoutGuard::default())
+                }))
             });
+            (Some(result), peak)
+        }
+        Some(timeout) => {
+            let owned = OwnedContestTask::new(task);
+            match run_with_timeout(timeout, move |guard| {
+                track_peak_memory(|| run(&owned.task(), guard))
+            }) {
+                Some(Ok((result, peak))) => (Some(Ok(result)), peak),
+                Some(Err(payload)) => (Some(Err(payload)), None),
+                None => (None, None),
+            }
+        }
+    };
+    let result = match result {
+        Some(Ok(result)) => Ok(result),
+        Some(Err(payload)) => Err(panic_message(&*payload)),
+        None => Err(format!("Timed out after {}s", timeout.unwrap().as_secs())),
+    };
+    if result.is_err() {
+        log_warn!(
+            "Failed to {} contest {} in election {}",
+            stage,
+            task.contest.office,
+            task.election_path
+        );
+    }
+    (result, peak)
+}
 
-            // Build index entry matching the existing format
-            let index_entry = ContestIndexEntry {
-                office: report.info.office.clone(),
-                office_name: report.info.office_name.clone(),
-                name: report.info.name.clone(),
-                winner: report
-                    .winner()
-                    .map(|w| w.name.clone())
-                    .unwrap_or_else(|| "No Winner".to_string()),
-                num_candidates: report.num_candidates,
-                num_rounds: report.rounds.len() as u32,
-                condorcet_winner: report
-                    .condorcet
-                    .map(|c| report.candidates[c.0 as usize].name.clone()),
-                has_non_condorcet_winner: report.condorcet.is_some()
-                    && report.condorcet != report.winner,
-                has_write_in_by_name,
-            };
+/// The contests of one election.
+struct ElectionTasks<'a> {
+    jurisdiction: &'a Arc<Jurisdiction>,
+    election_path: &'a str,
+    election: &'a ElectionMetadata,
+    contests: Vec<ContestTask<'a>>,
+}
+
+impl<'a> ElectionTasks<'a> {
+    /// Path of the election, including the jurisdiction path.
+    fn path(&self) -> String {
+        format!("{}/{}", self.jurisdiction.path, self.election_path)
+    }
+}
 
-            drop(report);
-            drop(preprocessed);
+/// A contest's index entry, summary and candidates' results, and the raw
+/// data files its report was generated from.
+struct ReportedContest {
+    entry: ContestIndexEntry,
+    summary: ContestSummary,
+    candidates: Vec<CandidateAppearance>,
+    source_files: Vec<SourceFile>,
+}
 
-            Some(index_entry)
-        })
+impl ReportedContest {
+    fn new(report: &ContestReport) -> ReportedContest {
+        ReportedContest {
+            entry: ContestIndexEntry::from_report(report),
+            summary: contest_summary(report),
+            candidates: candidate_appearances(report),
+            source_files: report
+                .provenance
+                .as_ref()
+                .map(|p| p.source_files.clone())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// How a contest's report was obtained by `report_contest`.
+enum ContestOutcome {
+    /// The report was generated, with its differences from the report it
+    /// replaced, if any.
+    Generated(ReportedContest, Option<ReportChanges>),
+    Cached(ReportedContest),
+    /// The report has no ballots, candidates or rounds.
+    Empty,
+    Failed(String),
+}
+
+/// The message a panic was raised with.
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Unknown error".to_string()
+    }
+}
+
+/// Where the raw ballots of an election's contests are read from.
+enum BallotSource {
+    /// Each contest's raw data is read on its own, by its format's reader.
+    PerContest,
+    /// The contests share CVR files, which were read once for all of them.
+    /// Ballots are keyed by office.
+    Batch(HashMap<String, Election>),
+}
+
+impl BallotSource {
+    /// Read the CVR files of NYC elections, and of NIST elections whose
+    /// contests share them, once for all of the given contests. Other
+    /// contests are read one at a time.
+    fn for_contests(tasks: &[&ContestTask]) -> BallotSource {
+        let first = tasks[0];
+        // raw_base is the jurisdiction path, need to add election_path
+        let raw_path = first.raw_base.join(first.election_path);
+        match first.election.data_format.as_str() {
+            // The batch reader uses the efficient reader which handles numeric candidate IDs correctly
+            "us_ny_nyc" => BallotSource::Batch(read_nyc_batch(tasks, &raw_path)),
+            "nist_sp_1500" if is_nist_batch(tasks) => {
+                BallotSource::Batch(read_nist_batch(tasks, &raw_path))
+            }
+            _ => BallotSource::PerContest,
+        }
+    }
+
+    /// A reader for the raw ballots of a contest. Batched ballots are taken
+    /// from the batch; others are read when the reader is called.
+    fn take(&mut self, task: &ContestTask) -> impl FnOnce(&ContestTask) -> Election + Send {
+        let batched = match self {
+            BallotSource::PerContest => None,
+            BallotSource::Batch(elections) => Some(elections.remove(&task.contest.office)),
+        };
+        move |task: &ContestTask| match batched {
+            None => read_election(
+                &task.election.data_format,
+                &task.raw_base.join(task.election_path),
+                task.contest.reader_params(),
+            ),
+            Some(election) => election.unwrap_or_else(|| {
+                panic!("Contest {} was not read in the batch", task.contest.office)
+            }),
+        }
+    }
+}
+
+/// Read the shared CVR files of a NYC election once for all of the given
+/// contests.
+fn read_nyc_batch(tasks: &[&ContestTask], raw_path: &Path) -> HashMap<String, Election> {
+    use crate::formats::nyc_batch_reader;
+
+    // Check if all contests use the same cvrPattern and candidatesFile
+    let first_params = tasks[0].contest.loader_params.as_ref();
+    let same_params = tasks.iter().all(|t| {
+        t.contest.loader_params.as_ref().map(|p| {
+            p.get("cvrPattern") == first_params.and_then(|f| f.get("cvrPattern"))
+                && p.get("candidatesFile") == first_params.and_then(|f| f.get("candidatesFile"))
+        }) == Some(true)
+    });
+    if !same_params {
+        // Use the batch reader even if params differ (shouldn't happen for NYC)
+        // This ensures we always use the efficient reader, not the buggy old reader
+        log_warn!("NYC contests don't share same params, but using batch reader anyway");
+    }
+
+    let contests_with_offices: Vec<(String, BTreeMap<String, String>)> = tasks
+        .iter()
+        .filter_map(|t| Some((t.contest.office.clone(), t.contest.loader_params.clone()?)))
+        .collect();
+    nyc_batch_reader(raw_path, contests_with_offices)
+        .into_iter()
         .collect()
 }
 
-/// Process a NIST election with batch optimization
-fn process_nist_election_batch(
-    election_path: &str,
-    election: &ElectionMetadata,
-    jurisdiction: &Jurisdiction,
-    raw_base: &Path,
-    report_dir: &Path,
-    preprocessed_dir: &Path,
-    force_preprocess: bool,
-    force_report: bool,
-) -> Vec<ContestIndexEntry> {
+/// Read the shared CVR files of a NIST election once for all of the given
+/// contests, keyed by office.
+fn read_nist_batch(tasks: &[&ContestTask], raw_path: &Path) -> HashMap<String, Election> {
     use crate::formats::nist_batch_reader;
 
-    // raw_base is the jurisdiction path, need to add election_path
-    let raw_path = raw_base.join(election_path);
+    let contest_id = |t: &ContestTask| -> Option<u32> {
+        t.contest
+            .loader_params
+            .as_ref()?
+            .get("contest")?
+            .parse()
+            .ok()
+    };
 
-    // Prepare contest data for batch processing
-    let contests_with_ids: Vec<(u32, std::collections::BTreeMap<String, String>)> = election
-        .contests
+    let contests_with_ids: Vec<(u32, BTreeMap<String, String>)> = tasks
         .iter()
-        .filter_map(|contest| {
-            let contest_id: u32 = contest
-                .loader_params
-                .as_ref()?
-                .get("contest")?
-                .parse()
-                .ok()?;
-            Some((contest_id, contest.loader_params.clone().unwrap()))
-        })
+        .filter_map(|t| Some((contest_id(t)?, t.contest.reader_params())))
         .collect();
+    let mut elections_by_contest = nist_batch_reader(raw_path, contests_with_ids);
+    tasks
+        .iter()
+        .filter_map(|t| {
+            let election = elections_by_contest.remove(&contest_id(t)?)?;
+            Some((t.contest.office.clone(), election))
+        })
+        .collect()
+}
 
-    // Batch read all contests at once
-    let mut elections_by_contest = nist_batch_reader(&raw_path, contests_with_ids);
+/// Whether the contests of a NIST election share CVR files, so they can be
+/// read in a single batch.
+fn is_nist_batch(tasks: &[&ContestTask]) -> bool {
+    // Check if all contests use the same CVR path and file selection
+    let cvr_param = |t: &ContestTask, key: &str| {
+        t.contest
+            .loader_params
+            .as_ref()
+            .and_then(|p| p.get(key))
+            .cloned()
+    };
+    let first_cvr = cvr_param(tasks[0], "cvr");
+    let first_cvr_files = cvr_param(tasks[0], "cvrFiles");
 
-    // Now process each contest using the pre-loaded election data
-    election
-        .contests
-        .iter()
-        .filter_map(|contest| {
-            let contest_id: u32 = contest
-                .loader_params
-                .as_ref()?
-                .get("contest")?
-                .parse()
-                .ok()?;
-
-            let office = jurisdiction
-                .offices
-                .get(&contest.office)
-                .unwrap_or_else(|| panic!("Expected office {} to be in offices.", &contest.office));
-            log_race!(
-                &jurisdiction.name,
-                &election.name,
-                &office.name
-            );
+    tasks.len() > 1
+        && first_cvr.is_some()
+        && tasks.iter().all(|t| {
+            cvr_param(t, "cvr") == first_cvr && cvr_param(t, "cvrFiles") == first_cvr_files
+        })
+}
 
-            let report_path = Path::new(report_dir)
-                .join(&jurisdiction.path)
-                .join(election_path)
-                .join(&contest.office)
-                .join("report.json");
+/// The results of preprocessing the contests of one election, by office.
+#[derive(Default)]
+struct PreprocessResults {
+    /// Reasons contests that were preprocessed one at a time failed.
+    failures: BTreeMap<String, String>,
+    /// Peak memory allocated while preprocessing each contest, in bytes.
+    /// Contests read in a batch share the batch's peak.
+    peak_memory: BTreeMap<String, u64>,
+}
 
-            let preprocessed_path = Path::new(preprocessed_dir)
-                .join(&jurisdiction.path)
-                .join(election_path)
-                .join(&contest.office)
-                .join("normalized.json.gz");
+/// Preprocess the contests of one election whose normalized ballots are not
+/// already cached. Raw CVRs are only read if at least one contest needs them,
+/// after reserving their estimated memory from the budget, if any.
+fn preprocess_election_tasks(
+    tasks: &[ContestTask],
+    preprocessed_dir: &Path,
+    budget: Option<&MemoryBudget>,
+    timeout: Option<Duration>,
+    zstd_level: Option<i32>,
+) -> PreprocessResults {
+    let mut results = PreprocessResults::default();
+    let pending: Vec<&ContestTask> = tasks.iter().filter(|t| t.plan.preprocess).collect();
+
+    if pending.is_empty() {
+        return results;
+    }
 
-            create_dir_all(report_path.parent().unwrap()).unwrap();
-            create_dir_all(preprocessed_path.parent().unwrap()).unwrap();
+    let election = pending[0].election;
+    log_debug!("Election: {}", pending[0].election_path);
 
-            // Take ownership of the election data from batch results
-            let raw_election = elections_by_contest.remove(&contest_id)?;
+    let raw_election_path = pending[0].raw_base.join(pending[0].election_path);
+    let estimate = estimate_preprocess_memory(&raw_election_path);
+    log_debug!(
+        "Estimated {} MiB to preprocess {}",
+        estimate >> 20,
+        pending[0].election_path
+    );
+    let _reservation = budget.map(|budget| budget.reserve(estimate));
 
-            // Preprocess with the loaded election data
-            let preprocessed = if force_preprocess || !preprocessed_path.exists() {
-                let preprocessed = crate::report::preprocess_election_from_data(
-                    raw_election,
-                    election,
-                    jurisdiction,
-                    contest,
-                    election_path,
-                );
-                write_serialized(&preprocessed_path, &preprocessed);
-                log_debug!("Processed {} ballots", preprocessed.ballots.ballots.len());
-                preprocessed
-            } else {
-                log_debug!(
-                    "Reading cached preprocessed {}",
-                    preprocessed_path.display()
-                );
-                read_serialized(&preprocessed_path)
-            };
+    let source_files = hash_source_files(&raw_election_path, election);
 
-            // Generate report
-            let report = if force_report || !report_path.exists() {
-                log_debug!("Generating report...");
-                let contest_report = generate_report(&preprocessed);
-                log_debug!("Writing report to disk...");
-                write_serialized(&report_path, &contest_report);
-                log_debug!("Report written successfully.");
-                contest_report
-            } else {
-                read_serialized(&report_path)
-            };
+    // A reader given another format's files fails with an unrelated error,
+    // so the likely cause is added to the failures.
+    let format_mismatch = check_format(&election.data_format, &raw_election_path);
+    if let Some(mismatch) = &format_mismatch {
+        log_warn!("{}: {}", pending[0].election_path, mismatch);
+    }
 
-            // Skip empty reports (no ballots, candidates, or rounds)
-            if report.ballot_count == 0
-                || report.num_candidates == 0
-                || report.rounds.is_empty()
-            {
-                log_debug!(
-                    "Skipping empty report: {}",
-                    report.info.office
-                );
-                drop(report);
-                drop(preprocessed);
-                return None;
+    let (source, batch_peak) = track_peak_memory(|| {
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            BallotSource::for_contests(&pending)
+        }))
+    });
+    let mut source = source.unwrap_or_else(|payload| match &format_mismatch {
+        Some(mismatch) => panic!("{} ({})", panic_message(&*payload), mismatch),
+        None => std::panic::resume_unwind(payload),
+    });
+    if let (BallotSource::Batch(_), Some(peak)) = (&source, batch_peak) {
+        log_info!(
+            "Peak memory reading {} as a batch of {} contests: {} (estimated {})",
+            pending[0].election_path,
+            pending.len(),
+            format_size(peak),
+            format_size(estimate)
+        );
+    }
+
+    // Process contests sequentially to avoid memory issues, with error handling
+    for task in pending {
+        let ballots = source.take(task);
+        let preprocessed_dir = preprocessed_dir.to_path_buf();
+        let source_files = source_files.clone();
+        let (result, peak) = run_contest_stage(task, "preprocess", timeout, move |task, guard| {
+            task.log_race();
+            let preprocessed = preprocess_election_from_data(
+                ballots(task),
+                task.election,
+                task.jurisdiction,
+                task.contest,
+                task.election_path,
+            );
+            if guard.start_writing() {
+                task.write_preprocessed(&preprocessed_dir, preprocessed, &source_files, zstd_level);
+            }
+        });
+        match result {
+            Ok(()) => {
+                if let Some(peak) = peak {
+                    log_info!(
+                        "Peak memory preprocessing {} in {}: {} (estimated {} for the election)",
+                        task.contest.office,
+                        task.election_path,
+                        format_size(peak),
+                        format_size(estimate)
+                    );
+                    // Contests read in a batch share the batch's peak.
+                    results.peak_memory.insert(
+                        task.contest.office.clone(),
+                        peak.max(batch_peak.unwrap_or(0)),
+                    );
+                }
+            }
+            Err(reason) => {
+                let reason = match &format_mismatch {
+                    Some(mismatch) => format!("{} ({})", reason, mismatch),
+                    None => reason,
+                };
+                results.failures.insert(task.contest.office.clone(), reason);
             }
+        }
+    }
 
-            // Check if any candidate is named "Write-in" or "Write in" (case-insensitive)
-            let has_write_in_by_name = report.candidates.iter().any(|c| {
-                is_write_in_by_name(&c.name)
-            });
+    results
+}
+
+/// Generate (or load the cached) report for a contest from its preprocessed
+/// ballots, and return its index entry and summary. Nothing is written
+/// unless `guard` is claimed first.
+fn report_contest(
+    task: &ContestTask,
+    storage: &dyn Storage,
+    report_dir: &Path,
+    preprocessed_dir: &Path,
+    options: &ContestOptions,
+    guard: &TimeoutGuard,
+) -> ContestOutcome {
+    // The run has moved on without a contest that timed out, so this is
+    // never seen.
+    let timed_out = || ContestOutcome::Failed("Timed out".to_string());
+    let report_path = task.report_path(report_dir);
+    let cached_report: Option<ContestReport> = if !task.plan.report {
+        Some(read_serialized_from(storage, &report_path)).filter(|report| {
+            task.plan.keep_report
+                || (has_variants(report, &task.contest.variants)
+                    && has_subsets(report, &task.contest.subsets))
+        })
+    } else {
+        None
+    };
 
-            // Build index entry matching the existing format
-            let index_entry = ContestIndexEntry {
-                office: report.info.office.clone(),
-                office_name: report.info.office_name.clone(),
-                name: report.info.name.clone(),
-                winner: report
+    // Preprocessed ballots are only needed to generate the report.
+    let preprocessed_path = task.cached_preprocessed_path(preprocessed_dir);
+    if cached_report.is_none() && preprocessed_path.is_none() {
+        log_warn!(
+            "No preprocessed ballots for contest {} in election {}",
+            task.contest.office,
+            task.election_path
+        );
+        return ContestOutcome::Failed("No preprocessed ballots".to_string());
+    }
+
+    let was_cached = cached_report.is_some();
+    let mut changes = None;
+    let report: ContestReport = if let Some(mut contest_report) = cached_report {
+        if !guard.start_writing() {
+            return timed_out();
+        }
+        log_debug!(
+            "Skipping because {} exists. Use --force-report to regenerate.",
+            report_path.to_str().unwrap()
+        );
+
+        // The reporting status and display names come from metadata, which
+        // may have changed (e.g. results declared final) since the report was
+        // generated.
+        if task.apply_metadata(&mut contest_report) {
+            for entry in &mut contest_report.variants {
+                let variant_path = report_path.with_file_name(&entry.report);
+                let mut variant_report: ContestReport =
+                    read_serialized_from(storage, &variant_path);
+                task.apply_metadata(&mut variant_report);
+                write_canonical_to(storage, &variant_path, &variant_report);
+                entry.winner = variant_report
                     .winner()
-                    .map(|w| w.name.clone())
-                    .unwrap_or_else(|| "No Winner".to_string()),
-                num_candidates: report.num_candidates,
-                num_rounds: report.rounds.len() as u32,
-                condorcet_winner: report
-                    .condorcet
-                    .map(|c| report.candidates[c.0 as usize].name.clone()),
-                has_non_condorcet_winner: report.condorcet.is_some()
-                    && report.condorcet != report.winner,
-                has_write_in_by_name,
-            };
+                    .map(|w| w.display_name().to_string());
+            }
+            for entry in &mut contest_report.subsets {
+                let subset_path = report_path.with_file_name(&entry.report);
+                let mut subset_report: ContestReport = read_serialized_from(storage, &subset_path);
+                task.apply_metadata(&mut subset_report);
+                write_canonical_to(storage, &subset_path, &subset_report);
+                entry.winner = subset_report.winner().map(|w| w.display_name().to_string());
+            }
+            write_canonical_to(storage, &report_path, &contest_report);
+        }
+
+        contest_report
+    } else {
+        storage
+            .create_dir_all(report_path.parent().unwrap())
+            .unwrap();
+
+        let preprocessed_path = preprocessed_path.unwrap();
+        log_debug!(
+            "Loading preprocessed {}.",
+            preprocessed_path.to_str().unwrap()
+        );
+        let mut preprocessed = read_preprocessed(&preprocessed_path);
+
+        log_debug!("Generating report...");
+        let mut contest_report = task.tabulate(&preprocessed, options);
+        if !guard.start_writing() {
+            return timed_out();
+        }
+        write_precinct_map(
+            storage,
+            task.election,
+            &task.raw_base.join(task.election_path),
+            &preprocessed,
+            &mut contest_report,
+            &report_path,
+        );
+        if options.round_snapshots {
+            write_round_snapshots(storage, &preprocessed, &mut contest_report, &report_path);
+        }
+        if options.ranking_patterns {
+            let patterns = generate_ranking_patterns(
+                contest_report
+                    .candidates
+                    .iter()
+                    .map(|c| c.display_name().to_string())
+                    .collect(),
+                &preprocessed.ballots.ballots,
+            );
+            write_canonical_to(
+                storage,
+                &report_path.with_file_name(RANKING_PATTERNS_FILE),
+                &patterns,
+            );
+            contest_report.ranking_patterns = Some(RANKING_PATTERNS_FILE.to_string());
+        }
+        contest_report.variants =
+            write_variant_reports(task, storage, &mut preprocessed, &report_path);
+        contest_report.subsets = write_subset_reports(task, storage, &preprocessed, &report_path);
+
+        // Reports written by older versions may not be readable.
+        if exists_in(storage, &report_path) {
+            changes = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                read_serialized_from(storage, &report_path)
+            }))
+            .ok()
+            .map(|previous| report_changes(&previous, &contest_report));
+        }
 
-            drop(report);
-            drop(preprocessed);
+        log_debug!("Writing report...");
+        write_canonical_to(storage, &report_path, &contest_report);
+        log_debug!("Report written successfully.");
 
-            Some(index_entry)
-        })
-        .collect()
-}
+        contest_report
+    };
 
-/// Process a single election and return its election index entry
-fn process_election(
-    election_path: &str,
-    election: &ElectionMetadata,
-    jurisdiction: &Jurisdiction,
-    raw_base: &Path,
-    report_dir: &Path,
-    preprocessed_dir: &Path,
-    force_preprocess: bool,
-    force_report: bool,
-) -> ElectionIndexEntry {
-    log_debug!("Election: {}", election_path);
-
-    // Check if this is a NIST election with multiple contests that share CVR files
-    let is_nist_batch = election.data_format == "nist_sp_1500" && election.contests.len() > 1;
-
-    // Check if this is a NYC election (batch reader works for single or multiple contests)
-    // The batch reader uses the efficient reader which handles numeric candidate IDs correctly
-    let is_nyc_batch = election.data_format == "us_ny_nyc" && !election.contests.is_empty();
-
-    let contest_index_entries: Vec<ContestIndexEntry> = if is_nyc_batch {
-        // Check if all contests use the same cvrPattern and candidatesFile
-        let first_params = election.contests[0].loader_params.as_ref();
-        let same_params = first_params.is_some()
-            && election.contests.iter().all(|c| {
-                c.loader_params.as_ref().map(|p| {
-                    p.get("cvrPattern") == first_params.unwrap().get("cvrPattern")
-                        && p.get("candidatesFile") == first_params.unwrap().get("candidatesFile")
-                }) == Some(true)
-            });
+    // Cached reports are signed again, in case they were rewritten above or
+    // the key changed.
+    sign_artifact(storage, options.signing_key.as_ref(), &report_path);
 
-        if same_params && first_params.is_some() {
-            process_nyc_election_batch(
-                election_path,
-                election,
-                jurisdiction,
-                raw_base,
-                report_dir,
-                preprocessed_dir,
-                force_preprocess,
-                force_report,
-            )
-        } else {
-            // Fall back to batch reader even if params differ (shouldn't happen for NYC)
-            // This ensures we always use the efficient reader, not the buggy old reader
-            crate::log_warn!(
-                "NYC contests don't share same params, but using batch reader anyway"
-            );
-            process_nyc_election_batch(
-                election_path,
-                election,
-                jurisdiction,
-                raw_base,
-                report_dir,
-                preprocessed_dir,
-                force_preprocess,
-                force_report,
-            )
-        }
-    } else if is_nist_batch {
-        // Check if all contests use the same CVR path
-        let first_cvr = election.contests[0]
-            .loader_params
-            .as_ref()
-            .and_then(|p| p.get("cvr"));
-        let same_cvr = election
-            .contests
-            .iter()
-            .all(|c| c.loader_params.as_ref().and_then(|p| p.get("cvr")) == first_cvr);
+    if is_empty_report(&report) {
+        log_debug!("Skipping empty report: {}", report.info.office);
+        return ContestOutcome::Empty;
+    }
 
-        if same_cvr && first_cvr.is_some() {
+    if let Some(site_url) = &options.site_url {
+        write_canonical_to(
+            storage,
+            &report_path.with_file_name(CONTEST_METADATA_FILE),
+            &contest_metadata_stub(site_url, &report),
+        );
+    }
 
-            process_nist_election_batch(
-                election_path,
-                election,
-                jurisdiction,
-                raw_base,
-                report_dir,
-                preprocessed_dir,
-                force_preprocess,
-                force_report,
-            )
-        } else {
-            // Fall back to sequential processing with error handling
-            election
-                .contests
-                .iter()
-                .filter_map(|contest| {
-                    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                        process_contest(
-                            contest,
-                            election,
-                            election_path,
-                            jurisdiction,
-                            raw_base,
-                            report_dir,
-                            preprocessed_dir,
-                            force_preprocess,
-                            force_report,
-                        )
-                    })) {
-                        Ok(result) => Some(result),
-                        Err(_) => {
-                            log_warn!(
-                                "Failed to process contest {} in election {}",
-                                contest.office,
-                                election_path
-                            );
-                            None
-                        }
-                    }
-                })
-                .collect()
-        }
+    if was_cached {
+        ContestOutcome::Cached(ReportedContest::new(&report))
     } else {
-        // Process contests sequentially for non-NIST or single contest elections with error handling
-        election
-            .contests
-            .iter()
-            .filter_map(|contest| {
-                match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                    process_contest(
-                        contest,
-                        election,
-                        election_path,
-                        jurisdiction,
-                        raw_base,
-                        report_dir,
-                        preprocessed_dir,
-                        force_preprocess,
-                        force_report,
-                    )
-                })) {
-                    Ok(result) => Some(result),
-                    Err(_) => {
-                        log_warn!(
-                            "Failed to process contest {} in election {}",
-                            contest.office,
-                            election_path
-                        );
-                        None
-                    }
-                }
-            })
-            .collect()
-    };
+        ContestOutcome::Generated(ReportedContest::new(&report), changes)
+    }
+}
 
-    // Sort contests alphabetically by office name
-    let mut sorted_contests = contest_index_entries;
-    sorted_contests.sort_by(|a, b| a.office_name.cmp(&b.office_name));
+/// Write the trend dataset, one row per contest, as JSON and CSV.
+fn write_trends(storage: &dyn Storage, report_dir: &Path, summaries: &[ElectionSummary]) {
+    let trends = generate_trends(summaries);
+    write_canonical_to(storage, &report_dir.join(TRENDS_JSON_FILE), &trends);
 
-    ElectionIndexEntry {
-        path: format!("{}/{}", jurisdiction.path, election_path),
-        jurisdiction_name: jurisdiction.name.clone(),
-        election_name: election.name.clone(),
-        date: election.date.clone(),
-        contests: sorted_contests,
+    let csv_path = report_dir.join(TRENDS_CSV_FILE);
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let result = trends
+        .iter()
+        .try_for_each(|trend| writer.serialize(trend))
+        .map_err(|e| e.to_string())
+        .and_then(|()| writer.into_inner().map_err(|e| e.to_string()))
+        .and_then(|csv| storage.write(&csv_path, &csv).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        log_warn!("Failed to write {}: {}", csv_path.display(), e);
     }
 }
 
-/// Process a single jurisdiction and return its election index entries
-fn process_jurisdiction(
-    jurisdiction: &Jurisdiction,
-    raw_path: &Path,
+/// Generate reports in two passes. The first pass reads raw CVRs into
+/// preprocessed ballots, in parallel by jurisdiction; it skips contests
+/// whose preprocessed ballots are cached. The second pass generates reports
+/// from preprocessed ballots, in parallel by contest. Reports are
+/// regenerated if their ballots were forced to be preprocessed again (see
+/// `CachePlan`). Returns a summary of the run, including why any contests
+/// failed. Reports are written to S3 if `report_dir` is an `s3://` path
+/// (see `storage_for`).
+pub fn report(
+    meta_dir: &Path,
+    raw_dir: &Path,
     report_dir: &Path,
     preprocessed_dir: &Path,
-    force_preprocess: bool,
-    force_report: bool,
-) -> Vec<ElectionIndexEntry> {
-    let raw_base = raw_path.join(jurisdiction.path.clone());
-
-    // Process elections sequentially to avoid memory issues
-    let election_results: Vec<ElectionIndexEntry> = jurisdiction
-        .elections
-        .iter()
-        .filter_map(|(election_path, election)| {
-            match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                process_election(
-                    election_path,
-                    election,
-                    jurisdiction,
-                    &raw_base,
-                    report_dir,
-                    preprocessed_dir,
-                    force_preprocess,
-                    force_report,
-                )
-            })) {
-                Ok(result) => Some(result),
-                Err(_) => {
-                    log_warn!(
-                        "Failed to process election {} in jurisdiction {}",
-                        election_path,
-                        jurisdiction.name
-                    );
-                    None
-                }
-            }
-        })
-        .collect();
-
-    election_results
+    options: &ReportOptions,
+) -> RunSummary {
+    report_to(
+        Arc::from(storage_for(report_dir)),
+        meta_dir,
+        raw_dir,
+        report_dir,
+        preprocessed_dir,
+        options,
+    )
 }
 
-pub fn report(
+/// Generate reports as for `report`, reading and writing everything under
+/// `report_dir` through the given storage.
+fn report_to(
+    storage: Arc<dyn Storage>,
     meta_dir: &Path,
     raw_dir: &Path,
     report_dir: &Path,
     preprocessed_dir: &Path,
-    force_preprocess: bool,
-    force_report: bool,
-    jurisdiction_filter: Option<&str>,
-) {
+    options: &ReportOptions,
+) -> RunSummary {
+    let mut run_summary = RunSummary {
+        pipeline_version: PIPELINE_VERSION.to_string(),
+        total_contests: 0,
+        successful_contests: 0,
+        generated_contests: 0,
+        cached_contests: 0,
+        empty_contests: 0,
+        failed_contests: 0,
+        failures: Vec::new(),
+        elections: 0,
+        winner_changes: Vec::new(),
+        peak_rss: None,
+    };
+
     let raw_path = Path::new(raw_dir);
 
     // Collect all jurisdictions first
     let jurisdictions: Vec<_> = read_meta(meta_dir).collect();
 
-    // Filter jurisdictions if a filter is provided
-    let filtered_jurisdictions: Vec<_> = if let Some(filter) = jurisdiction_filter {
-        log_info!("Filtering to jurisdiction: {}", filter);
-        jurisdictions
-            .into_iter()
-            .filter(|(_, jurisdiction)| jurisdiction.path == filter)
-            .collect()
-    } else {
-        jurisdictions
-    };
+    // Filter jurisdictions if filters are provided
+    if !options.filters.is_empty() {
+        log_info!("Filtering to: {}", options.filters.join(", "));
+    }
+    let filtered_jurisdictions: Vec<Arc<Jurisdiction>> = jurisdictions
+        .into_iter()
+        .map(|(_, jurisdiction)| jurisdiction)
+        .filter(|jurisdiction| {
+            jurisdiction.elections.keys().any(|election_path| {
+                filters_include(options.filters, &jurisdiction.path, election_path)
+            })
+        })
+        .map(Arc::new)
+        .collect();
 
     if filtered_jurisdictions.is_empty() {
-        if let Some(filter) = jurisdiction_filter {
+        if options.filters.is_empty() {
+            log_warn!("No jurisdictions found");
+        } else {
             log_warn!(
-                "No jurisdictions found matching filter '{}'",
-                filter
+                "No jurisdictions found matching filters '{}'",
+                options.filters.join("', '")
             );
-        } else {
-            log_warn!("No jurisdictions found");
         }
-        return;
+        return run_summary;
     }
 
-    // Track success/failure counts
-    let mut total_contests = 0;
-    let mut successful_contests = 0;
-    let mut failed_contests = 0;
-
-    // Process jurisdictions in parallel
-    let jurisdiction_results: Vec<(Vec<ElectionIndexEntry>, usize, usize)> = filtered_jurisdictions
-        .par_iter()
-        .map(|(_, jurisdiction)| {
-            // Count total contests in this jurisdiction
-            let contests_in_jurisdiction: usize = jurisdiction
+    // One task per contest, grouped by election and jurisdiction
+    let mut jurisdiction_tasks: Vec<Vec<ElectionTasks>> = filtered_jurisdictions
+        .iter()
+        .map(|jurisdiction| {
+            let raw_base = raw_path.join(&jurisdiction.path);
+            jurisdiction
                 .elections
-                .values()
-                .map(|e| e.contests.len())
-                .sum();
-
-            // Process with error handling
-            match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                process_jurisdiction(
+                .iter()
+                .filter(|(election_path, _)| {
+                    filters_include(options.filters, &jurisdiction.path, election_path)
+                })
+                .map(|(election_path, election)| ElectionTasks {
                     jurisdiction,
-                    &raw_path,
-                    report_dir,
-                    preprocessed_dir,
-                    force_preprocess,
-                    force_report,
-                )
-            })) {
-                Ok(result) => {
-                    // Count successful contests
-                    let successful = result.iter().map(|e| e.contests.len()).sum::<usize>();
-                    (result, successful, contests_in_jurisdiction - successful)
-                }
-                Err(_) => {
-                    log_warn!("Jurisdiction {} panicked during processing", jurisdiction.name);
-                    (Vec::new(), 0, contests_in_jurisdiction)
-                }
-            }
+                    election_path,
+                    election,
+                    contests: election
+                        .contests
+                        .iter()
+                        .map(|contest| ContestTask {
+                            contest,
+                            election,
+                            election_path,
+                            jurisdiction,
+                            raw_base: raw_base.clone(),
+                            plan: CachePlan::default(),
+                        })
+                        .collect(),
+                })
+                .collect()
         })
         .collect();
 
-    // Extract results and counts
+    // Check the caches of both stages before either pass runs
+    for election in jurisdiction_tasks.iter_mut().flatten() {
+        let force_preprocess = options.force_preprocess || options.is_stale(election);
+        for task in &mut election.contests {
+            let report_cached = exists_in(&*storage, &task.report_path(report_dir));
+            let plan = CachePlan::new(
+                force_preprocess,
+                options.force_report,
+                task.cached_preprocessed_path(preprocessed_dir).is_some(),
+                report_cached,
+            );
+            task.plan = if options.only_missing {
+                plan.unless_reported(report_cached)
+            } else {
+                plan
+            };
+        }
+    }
+
+    // Preprocess in parallel by jurisdiction; elections within a jurisdiction
+    // are processed sequentially to avoid memory issues
+    log_info!("Preprocessing...");
+    let budget = options.max_memory.map(MemoryBudget::new);
+    // Reasons preprocessing failed (`Err`) or its peak memory (`Ok`), by
+    // election path or by contest path (`{election path}/{office}`).
+    let preprocess_results: Vec<(String, Result<u64, String>)> =
+        thread_pool(options.preprocess_threads).install(|| {
+            jurisdiction_tasks
+                .par_iter()
+                .flat_map_iter(|elections| {
+                    let mut results = Vec::new();
+                    for election in elections {
+                        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                            preprocess_election_tasks(
+                                &election.contests,
+                                preprocessed_dir,
+                                budget.as_ref(),
+                                options.contest_timeout,
+                                options.zstd_level,
+                            )
+                        })) {
+                            Ok(contest_results) => {
+                                let contest_path =
+                                    |office| format!("{}/{}", election.path(), office);
+                                results.extend(
+                                    contest_results
+                                        .failures
+                                        .into_iter()
+                                        .map(|(office, reason)| {
+                                            (contest_path(office), Err(reason))
+                                        }),
+                                );
+                                results.extend(
+                                    contest_results
+                                        .peak_memory
+                                        .into_iter()
+                                        .map(|(office, peak)| (contest_path(office), Ok(peak))),
+                                );
+                            }
+                            Err(payload) => {
+                                log_warn!(
+                                    "Failed to preprocess election {} in jurisdiction {}",
+                                    election.election_path,
+                                    election.jurisdiction.name
+                                );
+                                results.push((election.path(), Err(panic_message(&*payload))));
+                            }
+                        }
+                    }
+                    results
+                })
+                .collect()
+        });
+    let mut preprocess_failures: HashMap<String, String> = HashMap::new();
+    let mut preprocess_peak_memory: HashMap<String, u64> = HashMap::new();
+    for (path, result) in preprocess_results {
+        match result {
+            Ok(peak) => {
+                preprocess_peak_memory.insert(path, peak);
+            }
+            Err(reason) => {
+                preprocess_failures.insert(path, reason);
+            }
+        }
+    }
+
+    // A contest whose preprocessing failed isn't reported from older
+    // preprocessed ballots, which may not match its raw data. A cached report
+    // that the plan keeps is still used.
+    let preprocess_failure = |election: &ElectionTasks, task: &ContestTask| {
+        let path = election.path();
+        preprocess_failures
+            .get(&format!("{}/{}", path, task.contest.office))
+            .or_else(|| preprocess_failures.get(&path))
+            .filter(|_| task.plan.preprocess && task.plan.report)
+            .map(|e| format!("Failed to preprocess: {}", e))
+    };
+
+    // Generate reports in parallel by contest
+    log_info!("Generating reports...");
+    let contest_options = options.contest_options();
+    let elections: Vec<&ElectionTasks> = jurisdiction_tasks.iter().flatten().collect();
+    let contest_results: Vec<Vec<(ContestOutcome, Option<u64>)>> =
+        thread_pool(options.report_threads).install(|| {
+            elections
+                .par_iter()
+                .map(|election| {
+                    election
+                        .contests
+                        .par_iter()
+                        .map(|task| {
+                            if let Some(reason) = preprocess_failure(election, task) {
+                                return (ContestOutcome::Failed(reason), None);
+                            }
+                            let storage = storage.clone();
+                            let report_dir = report_dir.to_path_buf();
+                            let preprocessed_dir = preprocessed_dir.to_path_buf();
+                            let contest_options = contest_options.clone();
+                            let (outcome, peak) = run_contest_stage(
+                                task,
+                                "process",
+                                options.contest_timeout,
+                                move |task, guard| {
+                                    report_contest(
+                                        task,
+                                        &*storage,
+                                        &report_dir,
+                                        &preprocessed_dir,
+                                        &contest_options,
+                                        guard,
+                                    )
+                                },
+                            );
+                            let outcome = outcome.unwrap_or_else(ContestOutcome::Failed);
+                            // Only generating a report takes significant memory.
+                            let peak =
+                                peak.filter(|_| matches!(outcome, ContestOutcome::Generated(..)));
+                            if let Some(peak) = peak {
+                                log_info!(
+                                    "Peak memory generating report for {} in {}: {}",
+                                    task.contest.office,
+                                    task.election_path,
+                                    format_size(peak)
+                                );
+                            }
+                            (outcome, peak)
+                        })
+                        .collect()
+                })
+                .collect()
+        });
+
     let mut election_index_entries: Vec<ElectionIndexEntry> = Vec::new();
-    for (results, successful, failed) in jurisdiction_results {
-        total_contests += successful + failed;
-        successful_contests += successful;
-        failed_contests += failed;
-        election_index_entries.extend(results);
+    let mut election_summaries: Vec<ElectionSummary> = Vec::new();
+    let mut manifest_contests: Vec<ContestManifestEntry> = Vec::new();
+    let mut appearances: Vec<CandidateAppearance> = Vec::new();
+    for (election, results) in elections.iter().zip(contest_results) {
+        let path = election.path();
+        let mut contests: Vec<ContestIndexEntry> = Vec::new();
+        let mut summaries: Vec<ContestSummary> = Vec::new();
+        for (task, (outcome, report_peak_memory)) in election.contests.iter().zip(results) {
+            run_summary.total_contests += 1;
+            let contest_path = format!("{}/{}", path, task.contest.office);
+            let mut manifest_entry = ContestManifestEntry {
+                path: path.clone(),
+                office: task.contest.office.clone(),
+                status: ContestStatus::Failed,
+                source_files: Vec::new(),
+                winner: None,
+                changes: None,
+                peak_memory: preprocess_peak_memory
+                    .get(&contest_path)
+                    .copied()
+                    .max(report_peak_memory),
+            };
+            let reported = match outcome {
+                ContestOutcome::Generated(reported, changes) => {
+                    run_summary.generated_contests += 1;
+                    manifest_entry.status = ContestStatus::Generated;
+                    manifest_entry.changes = changes;
+                    Some(reported)
+                }
+                ContestOutcome::Cached(reported) => {
+                    run_summary.cached_contests += 1;
+                    manifest_entry.status = ContestStatus::Cached;
+                    Some(reported)
+                }
+                ContestOutcome::Empty => {
+                    run_summary.empty_contests += 1;
+                    manifest_entry.status = ContestStatus::Empty;
+                    None
+                }
+                ContestOutcome::Failed(reason) => {
+                    run_summary.failures.push(ContestFailure {
+                        path: path.clone(),
+                        office: task.contest.office.clone(),
+                        reason,
+                    });
+                    None
+                }
+            };
+            if let Some(reported) = reported {
+                manifest_entry.source_files = reported.source_files;
+                manifest_entry.winner = Some(reported.entry.winner.clone());
+                contests.push(reported.entry);
+                summaries.push(reported.summary);
+                appearances.extend(reported.candidates);
+            }
+            manifest_contests.push(manifest_entry);
+        }
+        run_summary.successful_contests += contests.len();
+        run_summary.failed_contests = run_summary.failures.len();
+
+        // Sort contests alphabetically by office name
+        contests.sort_by(|a, b| (&a.office_name, &a.office).cmp(&(&b.office_name, &b.office)));
+        summaries.sort_by(|a, b| (&a.office_name, &a.office).cmp(&(&b.office_name, &b.office)));
+
+        if !summaries.is_empty() {
+            let summary = generate_election_summary(
+                path.clone(),
+                election.jurisdiction.path.clone(),
+                election.jurisdiction.name.clone(),
+                election.election.name.clone(),
+                election.election.date.clone(),
+                summaries,
+            );
+            let summary_dir = report_dir.join(&path);
+            storage.create_dir_all(&summary_dir).unwrap();
+            write_canonical_to(&*storage, &summary_dir.join(SUMMARY_FILE), &summary);
+            election_summaries.push(summary);
+        }
+
+        election_index_entries.push(ElectionIndexEntry {
+            path,
+            jurisdiction_name: election.jurisdiction.name.clone(),
+            election_name: election.election.name.clone(),
+            date: election.election.date.clone(),
+            contests,
+        });
     }
 
     election_index_entries.sort_by(|a, b| (&b.date, &b.path).cmp(&(&a.date, &a.path)));
     let report_index = ReportIndex {
         elections: election_index_entries,
     };
+    run_summary.elections = report_index.elections.len();
 
     // Always write index.json, even if there were errors
     let index_path = Path::new(report_dir).join("index.json");
 
     // Ensure the report directory exists
-    if let Err(e) = std::fs::create_dir_all(report_dir) {
-        log_warn!("Failed to create report directory {}: {}", report_dir.display(), e);
-        return;
+    if let Err(e) = storage.create_dir_all(report_dir) {
+        log_warn!(
+            "Failed to create report directory {}: {}",
+            report_dir.display(),
+            e
+        );
+        return run_summary;
     }
 
-    write_serialized(&index_path, &report_index);
+    // Read the previous run's index before overwriting it, to find contests
+    // whose winner changed.
+    let previous_index: Option<ReportIndex> = if exists_in(&*storage, &index_path) {
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            read_serialized_from(&*storage, &index_path)
+        }))
+        .ok()
+    } else {
+        None
+    };
+
+    write_canonical_to(&*storage, &index_path, &report_index);
+    sign_artifact(&*storage, options.signing_key, &index_path);
     log_info!("Index written: {} elections", report_index.elections.len());
+    write_canonical_to(
+        &*storage,
+        &report_dir.join(OFFICE_INDEX_FILE),
+        &generate_office_index(&report_index.elections),
+    );
+    write_canonical_to(
+        &*storage,
+        &report_dir.join(CANDIDATE_INDEX_FILE),
+        &generate_candidate_index(appearances),
+    );
+
+    write_canonical_to(
+        &*storage,
+        &report_dir.join(RUN_MANIFEST_FILE),
+        &RunManifest {
+            pipeline_version: PIPELINE_VERSION.to_string(),
+            contests: manifest_contests,
+        },
+    );
+
+    write_trends(&*storage, report_dir, &election_summaries);
+
+    if let Some(site_url) = options.site_url {
+        let sitemap_path = report_dir.join(SITEMAP_FILE);
+        if let Err(e) = storage.write(
+            &sitemap_path,
+            generate_sitemap(site_url, &report_index.elections).as_bytes(),
+        ) {
+            log_warn!("Failed to write {}: {}", sitemap_path.display(), e);
+        }
+    }
+
+    run_summary.winner_changes = previous_index
+        .map(|previous| winner_changes(&previous, &report_index))
+        .unwrap_or_default();
 
     // Print summary
     log_info!("=== Report Generation Summary ===");
-    log_info!("Total contests: {}", total_contests);
-    log_info!("Successful: {}", successful_contests);
-    if failed_contests > 0 {
-        log_warn!("Failed: {}", failed_contests);
+    log_info!("Total contests: {}", run_summary.total_contests);
+    log_info!(
+        "Successful: {} ({} generated, {} cached)",
+        run_summary.successful_contests,
+        run_summary.generated_contests,
+        run_summary.cached_contests
+    );
+    if run_summary.empty_contests > 0 {
+        log_info!("Empty: {}", run_summary.empty_contests);
+    }
+    if run_summary.failed_contests > 0 {
+        log_warn!("Failed: {}", run_summary.failed_contests);
+        for failure in &run_summary.failures {
+            log_warn!("  {} {}: {}", failure.path, failure.office, failure.reason);
+        }
     }
     log_info!("Index entries: {}", report_index.elections.len());
+    run_summary.peak_rss = peak_rss();
+    if let Some(peak_rss) = run_summary.peak_rss {
+        log_info!("Peak resident memory: {}", format_size(peak_rss));
+    }
+
+    if !options.webhooks.is_empty() {
+        for change in &run_summary.winner_changes {
+            log_info!(
+                "Winner changed in {} {}: {} -> {}",
+                change.path,
+                change.office,
+                change.previous_winner,
+                change.winner
+            );
+            send_notification(
+                options.webhooks,
+                &Notification::WinnerChanged(change.clone()),
+            );
+        }
+
+        send_notification(
+            options.webhooks,
+            &Notification::RunCompleted(run_summary.clone()),
+        );
+    }
+
+    run_summary
 }
 
 /// Rebuild the index.json by scanning all existing report.json files
@@ -744,23 +1852,20 @@ pub fn rebuild_index(report_dir: &Path) {
     let mut election_map: HashMap<String, ElectionIndexEntry> = HashMap::new();
     let mut reports_found = 0;
     let mut reports_processed = 0;
+    let mut appearances: Vec<CandidateAppearance> = Vec::new();
+    let storage = storage_for(report_dir);
 
     // Recursively find all report.json files
-    fn find_report_files(dir: &Path, reports: &mut Vec<PathBuf>) {
-        if let Ok(entries) = read_dir(dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.is_dir() {
-                    find_report_files(&path, reports);
-                } else if path.file_name().and_then(|n| n.to_str()) == Some("report.json") {
-                    reports.push(path);
-                }
-            }
+    let report_files: Vec<PathBuf> = match storage.list(report_dir) {
+        Ok(files) => files
+            .into_iter()
+            .filter(|path| path.file_name().and_then(|n| n.to_str()) == Some("report.json"))
+            .collect(),
+        Err(e) => {
+            log_warn!("Failed to list {}: {}", report_dir.display(), e);
+            Vec::new()
         }
-    }
-
-    let mut report_files = Vec::new();
-    find_report_files(report_dir, &mut report_files);
+    };
 
     for report_path in report_files {
         reports_found += 1;
@@ -773,16 +1878,12 @@ pub fn rebuild_index(report_dir: &Path) {
             .map(|s| s.to_string());
 
         // Read the report (skip if it fails to parse)
-        let report = std::panic::catch_unwind(|| {
-            read_serialized::<ContestReport>(&report_path)
-        });
+        let report = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            read_serialized_from::<ContestReport>(&*storage, &report_path)
+        }));
 
         if let Ok(report) = report {
-            // Skip empty reports (no ballots, candidates, or rounds)
-            if report.ballot_count == 0
-                || report.num_candidates == 0
-                || report.rounds.is_empty()
-            {
+            if is_empty_report(&report) {
                 log_debug!(
                     "Skipping empty report: {}",
                     report_path.display()
@@ -797,30 +1898,8 @@ pub fn rebuild_index(report_dir: &Path) {
                 format!("{}/{}", report.info.jurisdiction_path, report.info.election_path)
             });
 
-            // Check if any candidate is named "Write-in" or "Write in" (case-insensitive)
-            let has_write_in_by_name = report.candidates.iter().any(|c| {
-                is_write_in_by_name(&c.name)
-            });
-
-            let contest_entry = ContestIndexEntry {
-                office: report.info.office.clone(),
-                office_name: report.info.office_name.clone(),
-                name: report.info.name.clone(),
-                winner: report
-                    .winner()
-                    .map(|w| w.name.clone())
-                    .unwrap_or_else(|| "No Winner".to_string()),
-                num_candidates: report.num_candidates,
-                num_rounds: report.rounds.len() as u32,
-                condorcet_winner: report
-                    .condorcet
-                    .and_then(|c| {
-                        report.candidates.get(c.0 as usize).map(|candidate| candidate.name.clone())
-                    }),
-                has_non_condorcet_winner: report.condorcet.is_some()
-                    && report.condorcet != report.winner,
-                has_write_in_by_name,
-            };
+            let contest_entry = ContestIndexEntry::from_report(&report);
+            appearances.extend(candidate_appearances(&report));
 
             // Get or create election entry
             let election_entry = election_map.entry(full_election_path.clone()).or_insert_with(|| {
@@ -843,7 +1922,9 @@ pub fn rebuild_index(report_dir: &Path) {
 
     // Sort contests within each election
     for election in &mut election_index_entries {
-        election.contests.sort_by(|a, b| a.office_name.cmp(&b.office_name));
+        election
+            .contests
+            .sort_by(|a, b| (&a.office_name, &a.office).cmp(&(&b.office_name, &b.office)));
     }
 
     let report_index = ReportIndex {
@@ -851,13 +1932,370 @@ pub fn rebuild_index(report_dir: &Path) {
     };
 
     // Ensure the report directory exists before writing
-    if let Err(e) = std::fs::create_dir_all(report_dir) {
+    if let Err(e) = storage.create_dir_all(report_dir) {
         log_warn!("Failed to create report directory {}: {}", report_dir.display(), e);
         return;
     }
 
     let index_path = report_dir.join("index.json");
-    write_serialized(&index_path, &report_index);
+    write_canonical_to(&*storage, &index_path, &report_index);
+    write_canonical_to(
+        &*storage,
+        &report_dir.join(OFFICE_INDEX_FILE),
+        &generate_office_index(&report_index.elections),
+    );
+    write_canonical_to(
+        &*storage,
+        &report_dir.join(CANDIDATE_INDEX_FILE),
+        &generate_candidate_index(appearances),
+    );
     log_info!("Found {} report.json files, processed {} successfully", reports_found, reports_processed);
     log_info!("Index updated: {} elections", report_index.elections.len());
 }
+
+/// Top-level fields whose values differ between two reports, as JSON.
+fn differing_fields(a: &serde_json::Value, b: &serde_json::Value) -> Vec<String> {
+    let (a, b) = match (a.as_object(), b.as_object()) {
+        (Some(a), Some(b)) => (a, b),
+        _ => return vec![String::new()],
+    };
+    let keys: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+    keys.into_iter()
+        .filter(|key| a.get(*key) != b.get(*key))
+        .cloned()
+        .collect()
+}
+
+/// Compare a contest's published report with the report tabulated again
+/// from its cached preprocessed ballots, with the analyses the published
+/// report has. Files written alongside the report (precinct maps,
+/// snapshots, variant and subset reports) aren't regenerated, so their
+/// references are taken from the published report. Returns the status,
+/// and the fields that differ.
+fn recheck_contest(
+    task: &ContestTask,
+    storage: &dyn Storage,
+    preprocessed_path: &Path,
+    report_path: &Path,
+) -> (RecheckStatus, Vec<String>) {
+    let published_bytes = storage
+        .read(report_path)
+        .unwrap_or_else(|e| panic!("Failed to read {}: {}", report_path.display(), e));
+    let published: ContestReport = serde_json::from_slice(&published_bytes)
+        .unwrap_or_else(|e| panic!("Failed to parse {}: {}", report_path.display(), e));
+    let options = ContestOptions {
+        site_url: None,
+        round_snapshots: false,
+        ranking_patterns: false,
+        elimination_tolerance: published
+            .elimination_robustness
+            .as_ref()
+            .map(|r| r.tolerance),
+        hypothetical_methods: published.hypothetical_methods.is_some(),
+        strategy_susceptibility: published.strategy_susceptibility.is_some(),
+        condense_rounds: published.expanded_rounds.is_some(),
+        signing_key: None,
+    };
+
+    let mut report = task.tabulate(&read_preprocessed(preprocessed_path), &options);
+    report.precinct_map = published.precinct_map;
+    report.round_snapshots = published.round_snapshots;
+    report.ranking_patterns = published.ranking_patterns;
+    report.variants = published.variants;
+    report.subsets = published.subsets;
+
+    let regenerated = to_canonical_json(&report);
+    if regenerated.as_bytes() == published_bytes.as_slice() {
+        return (RecheckStatus::Identical, vec![]);
+    }
+    // Compared as JSON rather than as deserialized reports, so that fields
+    // the current schema no longer has count as differences. Both are
+    // parsed from text, so that numbers are rounded the same way.
+    let published: serde_json::Value = serde_json::from_slice(&published_bytes).unwrap();
+    let regenerated: serde_json::Value = serde_json::from_str(&regenerated).unwrap();
+    let fields = differing_fields(&published, &regenerated);
+    if fields.is_empty() {
+        (RecheckStatus::Equivalent, fields)
+    } else {
+        (RecheckStatus::Differs, fields)
+    }
+}
+
+/// Tabulate every published contest again from its cached preprocessed
+/// ballots, and check that it reproduces the published report, to catch
+/// nondeterminism and schema drift before publishing. Contests without a
+/// report or cached ballots are skipped. `filters` are jurisdiction and
+/// election paths, as for the report command. Prints the result for each
+/// contest as JSON, and returns them.
+pub fn recheck(
+    meta_dir: &Path,
+    preprocessed_dir: &Path,
+    report_dir: &Path,
+    filters: &[String],
+) -> Vec<RecheckResult> {
+    let storage = storage_for(report_dir);
+    let jurisdictions: Vec<Arc<Jurisdiction>> =
+        read_meta(meta_dir).map(|(_, j)| Arc::new(j)).collect();
+    let tasks: Vec<(ContestTask, PathBuf, PathBuf)> = jurisdictions
+        .iter()
+        .flat_map(|jurisdiction| {
+            jurisdiction
+                .elections
+                .iter()
+                .filter(move |(election_path, _)| {
+                    filters_include(filters, &jurisdiction.path, election_path)
+                })
+                .flat_map(move |(election_path, election)| {
+                    election.contests.iter().map(move |contest| ContestTask {
+                        contest,
+                        election,
+                        election_path,
+                        jurisdiction,
+                        // Raw data isn't read.
+                        raw_base: PathBuf::new(),
+                        plan: CachePlan::default(),
+                    })
+                })
+        })
+        .filter_map(|task| {
+            let report_path = task.report_path(report_dir);
+            let preprocessed_path = task.cached_preprocessed_path(preprocessed_dir)?;
+            exists_in(&*storage, &report_path).then_some((task, preprocessed_path, report_path))
+        })
+        .collect();
+
+    log_info!("Rechecking {} published contests...", tasks.len());
+    let results: Vec<RecheckResult> = tasks
+        .par_iter()
+        .map(|(task, preprocessed_path, report_path)| {
+            let path = format!(
+                "{}/{}/{}",
+                task.jurisdiction.path, task.election_path, task.contest.office
+            );
+            let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                recheck_contest(task, &*storage, preprocessed_path, report_path)
+            }));
+            let (status, fields, error) = match outcome {
+                Ok((status, fields)) => (status, fields, None),
+                Err(payload) => (
+                    RecheckStatus::Failed,
+                    vec![],
+                    Some(panic_message(&*payload)),
+                ),
+            };
+            if status == RecheckStatus::Differs {
+                log_warn!(
+                    "{} differs from its published report in: {}",
+                    path,
+                    fields.join(", ")
+                );
+            }
+            RecheckResult {
+                path,
+                status,
+                fields,
+                error,
+            }
+        })
+        .collect();
+
+    let count = |status| results.iter().filter(|r| r.status == status).count();
+    log_info!(
+        "{} identical, {} equivalent, {} differ, {} failed",
+        count(RecheckStatus::Identical),
+        count(RecheckStatus::Equivalent),
+        count(RecheckStatus::Differs),
+        count(RecheckStatus::Failed)
+    );
+    println!("{}", serde_json::to_string_pretty(&results).unwrap());
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{MemoryStorage, TempDir};
+
+    #[test]
+    fn test_differing_fields() {
+        let published = serde_json::json!({"winner": 0, "rounds": [1, 2], "removed": true});
+        let regenerated = serde_json::json!({"winner": 0, "rounds": [1, 3], "added": 1});
+        assert_eq!(
+            vec!["added", "removed", "rounds"],
+            differing_fields(&published, &regenerated)
+        );
+        assert!(differing_fields(&published, &published).is_empty());
+    }
+
+    #[test]
+    fn test_cache_plan() {
+        let plan = |preprocess, report| CachePlan {
+            preprocess,
+            report,
+            keep_report: false,
+        };
+        for force_report in [false, true] {
+            for preprocessed_cached in [false, true] {
+                // Forcing preprocessing regenerates both stages
+                for report_cached in [false, true] {
+                    assert_eq!(
+                        plan(true, true),
+                        CachePlan::new(true, force_report, preprocessed_cached, report_cached)
+                    );
+                }
+                // A missing report is generated
+                assert_eq!(
+                    plan(!preprocessed_cached, true),
+                    CachePlan::new(false, force_report, preprocessed_cached, false)
+                );
+            }
+            // Missing ballots are preprocessed without regenerating the report
+            assert_eq!(
+                plan(true, force_report),
+                CachePlan::new(false, force_report, false, true)
+            );
+        }
+        assert_eq!(plan(false, true), CachePlan::new(false, true, true, true));
+        assert_eq!(plan(false, false), CachePlan::new(false, false, true, true));
+
+        // Only missing reports are generated, whatever is forced
+        assert_eq!(
+            CachePlan {
+                keep_report: true,
+                ..plan(false, false)
+            },
+            CachePlan::new(true, true, true, true).unless_reported(true)
+        );
+        assert_eq!(
+            plan(true, true),
+            CachePlan::new(true, true, true, false).unless_reported(false)
+        );
+    }
+
+    /// Options of a report run with everything optional turned off.
+    fn test_options() -> ReportOptions<'static> {
+        ReportOptions {
+            force_preprocess: false,
+            force_report: false,
+            only_missing: false,
+            filters: &[],
+            preprocess_threads: Some(1),
+            report_threads: Some(1),
+            max_memory: None,
+            site_url: None,
+            webhooks: &[],
+            stale_elections: None,
+            round_snapshots: false,
+            ranking_patterns: false,
+            elimination_tolerance: None,
+            hypothetical_methods: false,
+            strategy_susceptibility: false,
+            condense_rounds: false,
+            signing_key: None,
+            contest_timeout: None,
+            zstd_level: None,
+        }
+    }
+
+    /// Write the metadata and raw ballots of a jurisdiction with one contest
+    /// under `dir`, returning the metadata and raw data directories.
+    fn write_test_election(dir: &Path) -> (PathBuf, PathBuf) {
+        let (meta_dir, raw_dir) = (dir.join("meta"), dir.join("raw"));
+        create_dir_all(&meta_dir).unwrap();
+        create_dir_all(raw_dir.join("us/zz/2024")).unwrap();
+        std::fs::write(
+            meta_dir.join("zz.json"),
+            r#"{"name": "Zed", "path": "us/zz", "kind": "city",
+                "offices": {"mayor": {"name": "Mayor"}},
+                "elections": {"2024": {"name": "General", "date": "2024-11-05",
+                    "dataFormat": "simple_json", "normalization": "simple",
+                    "contests": [{"office": "mayor", "loaderParams": {"file": "ballots.json"}}],
+                    "files": {}}}}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            raw_dir.join("us/zz/2024/ballots.json"),
+            r#"{"ballots": [{"id": "1", "votes": ["A", "B"]}, {"id": "2", "votes": ["B"]},
+                {"id": "3", "votes": ["A"]}]}"#,
+        )
+        .unwrap();
+        (meta_dir, raw_dir)
+    }
+
+    /// Run the report command on the election written by
+    /// `write_test_election` to `dir`, preprocessing to `dir` and writing
+    /// the reports to `storage`. Nothing is written to the local report
+    /// directory.
+    fn run_report(storage: &Arc<MemoryStorage>, dir: &Path, options: &ReportOptions) -> RunSummary {
+        let report_dir = dir.join("reports");
+        let summary = report_to(
+            storage.clone(),
+            &dir.join("meta"),
+            &dir.join("raw"),
+            &report_dir,
+            &dir.join("preprocessed"),
+            options,
+        );
+        assert!(!report_dir.exists());
+        summary
+    }
+
+    #[test]
+    fn test_report_preprocess_failure() {
+        let dir = TempDir::new("report-failure");
+        let (_, raw_dir) = write_test_election(&dir);
+        let storage = Arc::new(MemoryStorage::default());
+        let summary = run_report(&storage, &dir, &test_options());
+        assert_eq!(1, summary.generated_contests);
+        assert!(storage
+            .exists(&dir.join("reports/us/zz/2024/mayor/report.json"))
+            .unwrap());
+
+        // The raw data can no longer be read, but the ballots preprocessed
+        // from it before are still cached.
+        std::fs::write(raw_dir.join("us/zz/2024/ballots.json"), "{").unwrap();
+        let options = ReportOptions {
+            force_preprocess: true,
+            ..test_options()
+        };
+        let summary = run_report(&storage, &dir, &options);
+        assert_eq!(
+            (0, 0, 1),
+            (
+                summary.generated_contests,
+                summary.cached_contests,
+                summary.failed_contests
+            )
+        );
+        assert!(summary.failures[0]
+            .reason
+            .starts_with("Failed to preprocess"));
+    }
+
+    #[test]
+    fn test_report_only_missing() {
+        let dir = TempDir::new("report-missing");
+        write_test_election(&dir);
+        let preprocessed_dir = dir.join("preprocessed");
+        let storage = Arc::new(MemoryStorage::default());
+        run_report(&storage, &dir, &test_options());
+
+        // A report is kept without its preprocessed ballots.
+        std::fs::remove_dir_all(&preprocessed_dir).unwrap();
+        let options = ReportOptions {
+            only_missing: true,
+            ..test_options()
+        };
+        let summary = run_report(&storage, &dir, &options);
+        assert_eq!((1, 0), (summary.cached_contests, summary.failed_contests));
+        assert!(!preprocessed_dir.exists());
+
+        // Otherwise the ballots are preprocessed again, and the report is
+        // still taken from the cache.
+        let summary = run_report(&storage, &dir, &test_options());
+        assert_eq!((1, 0), (summary.cached_contests, summary.failed_contests));
+        assert!(preprocessed_dir
+            .join("us/zz/2024/mayor/normalized.bin")
+            .exists());
+    }
+}
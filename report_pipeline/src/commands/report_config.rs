@@ -0,0 +1,132 @@
+use crate::util::{parse_size, read_serialized};
+use serde::de::{self, Deserializer};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Options for the report and watch commands, and the CLI-wide thread and
+/// log options, read from a JSON run-config file, so that scheduled runs and
+/// container deployments can keep their directories, filters, cache policy
+/// and analyses in one file instead of a long command line. Options given on
+/// the command line or in environment variables take precedence over the
+/// file.
+#[derive(Deserialize, Default, Debug, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ReportConfig {
+    pub meta_dir: Option<PathBuf>,
+    pub raw_data_dir: Option<PathBuf>,
+    pub preprocessed_dir: Option<PathBuf>,
+    pub report_dir: Option<PathBuf>,
+    /// Jurisdiction and election paths to process, e.g. `us/ca/alameda` or
+    /// `us/ny/nyc/2021/06`. All elections are processed if empty.
+    #[serde(default)]
+    pub filters: Vec<String>,
+    #[serde(default)]
+    pub use_cache_preprocess: bool,
+    #[serde(default)]
+    pub use_cache_report: bool,
+    #[serde(default)]
+    pub only_missing: bool,
+    pub preprocess_threads: Option<usize>,
+    pub report_threads: Option<usize>,
+    /// Memory limit, as a size such as `"16G"`.
+    #[serde(default, deserialize_with = "deserialize_size")]
+    pub max_memory: Option<u64>,
+    pub site_url: Option<String>,
+    #[serde(default)]
+    pub webhooks: Vec<String>,
+    pub signing_key: Option<PathBuf>,
+    #[serde(default)]
+    pub round_snapshots: bool,
+    #[serde(default)]
+    pub ranking_patterns: bool,
+    pub elimination_tolerance: Option<u32>,
+    #[serde(default)]
+    pub hypothetical_methods: bool,
+    #[serde(default)]
+    pub strategy_susceptibility: bool,
+    #[serde(default)]
+    pub condense_rounds: bool,
+    /// Contest timeout, in seconds.
+    pub contest_timeout: Option<u64>,
+    pub zstd_level: Option<i32>,
+    /// Number of threads for parallel work, as for `--threads`.
+    pub threads: Option<usize>,
+    #[serde(default)]
+    pub pin_threads: bool,
+    /// Log level: `error`, `warn`, `info`, `debug` or `trace`.
+    pub log_level: Option<String>,
+}
+
+fn deserialize_size<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|size| parse_size(&size).map_err(de::Error::custom))
+        .transpose()
+}
+
+impl ReportConfig {
+    /// Resolve relative paths against the directory of the config file, so
+    /// that a config works regardless of the directory it is run from.
+    fn resolve_paths(&mut self, base: &Path) {
+        let paths = [
+            &mut self.meta_dir,
+            &mut self.raw_data_dir,
+            &mut self.preprocessed_dir,
+            &mut self.report_dir,
+            &mut self.signing_key,
+        ];
+        for path in IntoIterator::into_iter(paths).flatten() {
+            *path = base.join(&*path);
+        }
+    }
+}
+
+/// Read a run-config file. Panics if it can't be read, or has options the
+/// report command doesn't know, so that a typo doesn't silently change a
+/// scheduled run.
+pub fn read_report_config(path: &Path) -> ReportConfig {
+    let mut config: ReportConfig = read_serialized(path);
+    config.resolve_paths(path.parent().unwrap_or_else(|| Path::new("")));
+    config
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::TempDir;
+
+    #[test]
+    fn test_read_report_config() {
+        let dir = TempDir::new("report-config");
+        let path = dir.join("nightly.json");
+        std::fs::write(
+            &path,
+            r#"{
+                "metaDir": "election-metadata",
+                "reportDir": "/srv/reports",
+                "filters": ["us/ca/alameda", "us/ny/nyc/2021/06"],
+                "useCachePreprocess": true,
+                "maxMemory": "16G",
+                "hypotheticalMethods": true,
+                "contestTimeout": 600,
+                "threads": 8,
+                "logLevel": "info"
+            }"#,
+        )
+        .unwrap();
+
+        let config = read_report_config(&path);
+        assert_eq!(Some(dir.join("election-metadata")), config.meta_dir);
+        assert_eq!(Some(PathBuf::from("/srv/reports")), config.report_dir);
+        assert_eq!(None, config.raw_data_dir);
+        assert_eq!(2, config.filters.len());
+        assert!(config.use_cache_preprocess && !config.use_cache_report);
+        assert_eq!(Some(16 << 30), config.max_memory);
+        assert!(config.hypothetical_methods);
+        assert_eq!(Some(600), config.contest_timeout);
+        assert_eq!(Some(8), config.threads);
+        assert_eq!(Some("info"), config.log_level.as_deref());
+
+        let unknown: Result<ReportConfig, _> = serde_json::from_str(r#"{"reportdir": "out"}"#);
+        assert!(unknown.is_err());
+    }
+}
@@ -0,0 +1,279 @@
+use crate::candidate_history::CANDIDATE_INDEX_FILE;
+use crate::model::report::{
+    CandidateIndex, ContestMetadataStub, ContestReport, ElectionSummary, OfficeIndex,
+    RankingPatterns, ReportIndex, RoundSnapshot, RunManifest, TrendPoint,
+};
+use crate::office_history::OFFICE_INDEX_FILE;
+use crate::summary::{SUMMARY_FILE, TRENDS_JSON_FILE};
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::schema::{InstanceType, RootSchema, Schema, SchemaObject, SingleOrVec};
+use schemars::Map;
+use std::fmt::Write;
+
+/// The JSON files the pipeline writes, with the schema of their contents.
+fn file_schemas(gen: &mut SchemaGenerator) -> Vec<(&'static str, Schema)> {
+    vec![
+        ("index.json", gen.subschema_for::<ReportIndex>()),
+        (OFFICE_INDEX_FILE, gen.subschema_for::<OfficeIndex>()),
+        (CANDIDATE_INDEX_FILE, gen.subschema_for::<CandidateIndex>()),
+        ("run-manifest.json", gen.subschema_for::<RunManifest>()),
+        (SUMMARY_FILE, gen.subschema_for::<ElectionSummary>()),
+        (
+            "round_snapshots.json",
+            gen.subschema_for::<Vec<RoundSnapshot>>(),
+        ),
+        (
+            "ranking_patterns.json",
+            gen.subschema_for::<RankingPatterns>(),
+        ),
+        ("meta.json", gen.subschema_for::<ContestMetadataStub>()),
+        (TRENDS_JSON_FILE, gen.subschema_for::<Vec<TrendPoint>>()),
+        ("report.json", gen.subschema_for::<ContestReport>()),
+    ]
+}
+
+/// A JSON Schema (draft 7) of the files the pipeline writes. The schema
+/// matches any of them; each file's type is one of its `anyOf` entries,
+/// titled with the file name.
+pub fn report_json_schema() -> RootSchema {
+    let mut gen = SchemaSettings::draft07().into_generator();
+    let files = file_schemas(&mut gen)
+        .into_iter()
+        .map(|(file, schema)| {
+            let mut schema = schema.into_object();
+            schema.metadata().title = Some(file.to_string());
+            schema.into()
+        })
+        .collect();
+
+    let mut schema = SchemaObject::default();
+    schema.metadata().title = Some("ranked.vote report files".to_string());
+    schema.subschemas().any_of = Some(files);
+    RootSchema {
+        meta_schema: gen.settings().meta_schema.clone(),
+        schema,
+        definitions: gen.take_definitions(),
+    }
+}
+
+/// TypeScript definitions of the files the pipeline writes, derived from
+/// their JSON Schema. Object types become interfaces prefixed with `I`, and
+/// other types become type aliases.
+pub fn report_typescript() -> String {
+    let mut gen = SchemaSettings::draft07().into_generator();
+    let files = file_schemas(&mut gen);
+    let definitions = gen.take_definitions();
+
+    let mut ts = String::from(
+        "// Generated from the report pipeline's Rust types by `codegen-ts`. Do not edit.\n\n",
+    );
+    for (file, schema) in &files {
+        writeln!(ts, "// {}: {}", file, ts_type(schema, &definitions)).unwrap();
+    }
+    for (name, schema) in &definitions {
+        ts.push('\n');
+        let schema = match schema {
+            Schema::Object(schema) => schema,
+            Schema::Bool(_) => continue,
+        };
+        write_doc(&mut ts, "", schema);
+        match schema.object.as_ref().filter(|_| is_interface(schema)) {
+            Some(object) => {
+                writeln!(ts, "export interface I{} {{", name).unwrap();
+                for (property, schema) in &object.properties {
+                    // Properties with a default are optional when reading a
+                    // file, but always written unless the default is skipped.
+                    let optional = !object.required.contains(property) && !has_default(schema);
+                    if let Schema::Object(schema) = schema {
+                        write_doc(&mut ts, "  ", schema);
+                    }
+                    let mut ty = ts_type(schema, &definitions);
+                    if optional {
+                        ty = without_null(&ty);
+                    }
+                    let marker = if optional { "?" } else { "" };
+                    writeln!(ts, "  {}{}: {};", property, marker, ty).unwrap();
+                }
+                ts.push_str("}\n");
+            }
+            None => {
+                let ty = ts_type(&Schema::Object(schema.clone()), &definitions);
+                writeln!(ts, "export type {} = {};", name, ty).unwrap();
+            }
+        }
+    }
+    ts
+}
+
+/// Whether a definition becomes an interface: an object with named
+/// properties.
+fn is_interface(schema: &SchemaObject) -> bool {
+    schema
+        .object
+        .as_ref()
+        .is_some_and(|object| !object.properties.is_empty())
+}
+
+/// Whether a property has a default value, which is only given for
+/// properties that are written even when they have the default.
+fn has_default(schema: &Schema) -> bool {
+    match schema {
+        Schema::Object(schema) => schema
+            .metadata
+            .as_ref()
+            .is_some_and(|m| m.default.is_some()),
+        Schema::Bool(_) => false,
+    }
+}
+
+/// Write a schema's description, if any, as a doc comment.
+fn write_doc(ts: &mut String, indent: &str, schema: &SchemaObject) {
+    let description = schema
+        .metadata
+        .as_ref()
+        .and_then(|m| m.description.as_ref());
+    if let Some(description) = description {
+        writeln!(ts, "{}/** {} */", indent, description.replace('\n', " ")).unwrap();
+    }
+}
+
+/// Whether a type is a union at the top level, and so needs parentheses
+/// to be an array's item type.
+fn is_union(ty: &str) -> bool {
+    let mut depth = 0;
+    for (i, c) in ty.char_indices() {
+        match c {
+            '(' | '<' => depth += 1,
+            ')' | '>' => depth -= 1,
+            '|' if depth == 0 && ty[..i].ends_with(' ') => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// A union type without its `null` member.
+fn without_null(ty: &str) -> String {
+    let members: Vec<&str> = ty.split(" | ").filter(|m| *m != "null").collect();
+    members.join(" | ")
+}
+
+/// The TypeScript type of a schema.
+fn ts_type(schema: &Schema, definitions: &Map<String, Schema>) -> String {
+    let schema = match schema {
+        Schema::Bool(_) => return "unknown".to_string(),
+        Schema::Object(schema) => schema,
+    };
+
+    if let Some(reference) = &schema.reference {
+        let name = reference.trim_start_matches("#/definitions/");
+        return match definitions.get(name) {
+            Some(Schema::Object(definition)) if is_interface(definition) => format!("I{}", name),
+            _ => name.to_string(),
+        };
+    }
+    if let Some(values) = &schema.enum_values {
+        return values
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(" | ");
+    }
+    if let Some(subschemas) = &schema.subschemas {
+        let members = subschemas
+            .any_of
+            .as_ref()
+            .or(subschemas.one_of.as_ref())
+            .or(subschemas.all_of.as_ref().filter(|all| all.len() == 1));
+        if let Some(members) = members {
+            return members
+                .iter()
+                .map(|member| ts_type(member, definitions))
+                .collect::<Vec<_>>()
+                .join(" | ");
+        }
+    }
+
+    let types: Vec<InstanceType> = match &schema.instance_type {
+        Some(SingleOrVec::Single(ty)) => vec![**ty],
+        Some(SingleOrVec::Vec(types)) => types.clone(),
+        None => return "unknown".to_string(),
+    };
+    types
+        .iter()
+        .map(|ty| match ty {
+            InstanceType::Null => "null".to_string(),
+            InstanceType::Boolean => "boolean".to_string(),
+            InstanceType::Integer | InstanceType::Number => "number".to_string(),
+            InstanceType::String => "string".to_string(),
+            InstanceType::Array => {
+                let items = match schema.array.as_ref().and_then(|a| a.items.as_ref()) {
+                    Some(SingleOrVec::Single(items)) => ts_type(items, definitions),
+                    _ => "unknown".to_string(),
+                };
+                if is_union(&items) {
+                    format!("({})[]", items)
+                } else {
+                    format!("{}[]", items)
+                }
+            }
+            InstanceType::Object => {
+                let values = schema
+                    .object
+                    .as_ref()
+                    .and_then(|o| o.additional_properties.as_ref())
+                    .map_or("unknown".to_string(), |v| ts_type(v, definitions));
+                format!("Record<string, {}>", values)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+/// Print the schema of the files the pipeline writes to stdout, as JSON
+/// Schema or as TypeScript definitions.
+pub fn schema(typescript: bool) {
+    if typescript {
+        print!("{}", report_typescript());
+    } else {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report_json_schema()).unwrap()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_schema_defines_report_types() {
+        let schema = report_json_schema();
+        for name in [
+            "ContestReport",
+            "TabulatorRound",
+            "ReportIndex",
+            "Allocatee",
+        ] {
+            assert!(schema.definitions.contains_key(name), "{}", name);
+        }
+        let files = schema.schema.subschemas.unwrap().any_of.unwrap();
+        assert_eq!(10, files.len());
+    }
+
+    #[test]
+    fn test_typescript() {
+        let ts = report_typescript();
+        assert!(ts.contains("// report.json: IContestReport\n"));
+        assert!(ts.contains("// trends.json: ITrendPoint[]\n"));
+        assert!(ts.contains("export type CandidateId = number;\n"));
+        assert!(ts.contains("export type Allocatee = CandidateId | \"X\";\n"));
+        assert!(ts.contains("export interface IContestReport {\n"));
+        assert!(ts.contains("  rounds: ITabulatorRound[];\n"));
+        assert!(ts.contains("  winner?: CandidateId;\n"));
+        assert!(ts.contains("  totalBallots: number;\n"));
+        assert!(ts.contains("  patterns: Record<string, number>;\n"));
+        assert!(ts.contains("  entries: (ICandidatePairEntry | null)[][];\n"));
+    }
+}
@@ -0,0 +1,52 @@
+use crate::util::{
+    get_files_from_path, public_key_hex, read_signing_key, read_verifying_key, storage_for,
+    verify_file,
+};
+use crate::{log_info, log_warn};
+use std::path::Path;
+
+/// Files that the report command signs.
+const SIGNED_FILES: [&str; 2] = ["index.json", "report.json"];
+
+/// Print the public key of a signing key, to publish for verification.
+pub fn signing_public_key(signing_key_file: &Path) {
+    let key = read_signing_key(signing_key_file).unwrap_or_else(|e| panic!("{}", e));
+    println!("{}", public_key_hex(&key));
+}
+
+/// Check the detached signatures of every report and index under a
+/// directory (a report directory, or an exported API directory) against a
+/// public key. Returns whether all of them are signed and valid.
+pub fn verify_signatures(dir: &Path, public_key_file: &Path) -> bool {
+    let key = read_verifying_key(public_key_file).unwrap_or_else(|e| panic!("{}", e));
+
+    let mut verified = 0;
+    let mut failed = 0;
+    for path in get_files_from_path(dir).unwrap() {
+        let is_signed = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| SIGNED_FILES.contains(&name));
+        if !is_signed {
+            continue;
+        }
+
+        match verify_file(&*storage_for(&path), &key, &path) {
+            Ok(true) => {
+                log_info!("Verified {}", path.display());
+                verified += 1;
+            }
+            Ok(false) => {
+                log_warn!("Invalid signature: {}", path.display());
+                failed += 1;
+            }
+            Err(e) => {
+                log_warn!("Failed to verify {}: {}", path.display(), e);
+                failed += 1;
+            }
+        }
+    }
+
+    println!("{} verified, {} failed", verified, failed);
+    failed == 0
+}
@@ -1,58 +1,280 @@
 use crate::read_metadata::read_meta;
 use crate::util::{hash_file, write_serialized};
+use crate::{log_info, log_warn};
 use colored::*;
-use std::collections::HashSet;
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::fs;
 use std::fs::create_dir_all;
 use std::path::Path;
 
-pub fn sync(meta_dir: &Path, raw_dir: &Path) {
-    for (path, mut ec) in read_meta(meta_dir) {
-        let ec_path = raw_dir.join(ec.path.clone());
-        if !ec_path.is_dir() {
-            eprintln!(
-                "Creating missing directory: {}",
-                ec_path.to_string_lossy().red()
+/// What `sync` may change. By default it adds files it finds to the
+/// metadata, but never deletes anything: metadata entries of missing files
+/// and files the metadata doesn't list are only removed when explicitly
+/// allowed.
+#[derive(Default, Clone, Copy)]
+pub struct SyncOptions {
+    /// Report differences without creating directories, writing metadata
+    /// or deleting files.
+    pub dry_run: bool,
+    /// Remove the metadata entries of files missing from the raw data.
+    pub allow_delete_entries: bool,
+    /// Delete files that the metadata doesn't list from the raw data,
+    /// instead of adding them to the metadata.
+    pub allow_delete_files: bool,
+}
+
+/// Differences between the files an election's metadata lists and those in
+/// its raw data directory.
+#[derive(Serialize, Default, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ElectionSync {
+    /// Path of the election, as `{jurisdiction}/{election}`.
+    pub election: String,
+    /// Files in the directory that the metadata doesn't list.
+    pub extra: Vec<String>,
+    /// Files the metadata lists that aren't in the directory.
+    pub missing: Vec<String>,
+    /// Files whose hash differs from the one in the metadata.
+    pub changed: Vec<String>,
+}
+
+impl ElectionSync {
+    fn is_empty(&self) -> bool {
+        self.extra.is_empty() && self.missing.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Summary of a sync, printed to stdout.
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncSummary {
+    pub dry_run: bool,
+    /// Elections whose files differ from their metadata.
+    pub elections: Vec<ElectionSync>,
+    /// Files added to the metadata.
+    pub added_entries: usize,
+    /// Metadata entries removed because their file is missing.
+    pub deleted_entries: usize,
+    /// Files deleted because the metadata doesn't list them.
+    pub deleted_files: usize,
+}
+
+impl SyncSummary {
+    /// Whether any file listed in the metadata was missing or had changed,
+    /// either of which should stop a publish.
+    pub fn has_integrity_errors(&self) -> bool {
+        self.elections
+            .iter()
+            .any(|e| !e.changed.is_empty() || !e.missing.is_empty())
+    }
+}
+
+/// Compare the files listed in an election's metadata, with their hashes,
+/// to the files in its raw data directory. Hidden files are ignored.
+fn compare_files(election: &str, files: &BTreeMap<String, String>, dir: &Path) -> ElectionSync {
+    let mut sync = ElectionSync {
+        election: election.to_string(),
+        ..Default::default()
+    };
+    let mut found: BTreeMap<String, String> = BTreeMap::new();
+    if dir.is_dir() {
+        for entry in fs::read_dir(dir).unwrap() {
+            let entry = entry.unwrap();
+            let filename = String::from(entry.file_name().to_str().unwrap());
+            if !filename.starts_with('.') {
+                found.insert(filename, hash_file(entry.path()));
+            }
+        }
+    }
+
+    for (filename, hash) in &found {
+        match files.get(filename) {
+            None => sync.extra.push(filename.clone()),
+            Some(expected) if expected != hash => sync.changed.push(filename.clone()),
+            Some(_) => {}
+        }
+    }
+    sync.missing = files
+        .keys()
+        .filter(|filename| !found.contains_key(*filename))
+        .cloned()
+        .collect();
+    sync
+}
+
+fn create_missing_dir(path: &Path, dry_run: bool) {
+    if path.is_dir() {
+        return;
+    }
+    if dry_run {
+        log_info!(
+            "Would create missing directory: {}",
+            path.to_string_lossy().red()
+        );
+    } else {
+        log_info!(
+            "Creating missing directory: {}",
+            path.to_string_lossy().red()
+        );
+        create_dir_all(path).unwrap();
+    }
+}
+
+/// Bring an election's metadata and raw data directory in line, as far as
+/// the options allow, and return how they differed.
+fn sync_election(
+    election: &str,
+    files: &mut BTreeMap<String, String>,
+    dir: &Path,
+    options: SyncOptions,
+    summary: &mut SyncSummary,
+) -> ElectionSync {
+    let sync = compare_files(election, files, dir);
+    let action = |done: &str, dry: &str| if options.dry_run { dry } else { done }.to_string();
+
+    for filename in &sync.extra {
+        let path = dir.join(filename);
+        if options.allow_delete_files {
+            log_warn!(
+                "{} unlisted file {}",
+                action("Deleting", "Would delete"),
+                path.display().to_string().red()
             );
-            create_dir_all(ec_path.clone()).unwrap();
+            if !options.dry_run {
+                fs::remove_file(&path)
+                    .unwrap_or_else(|e| panic!("Failed to delete {}: {}", path.display(), e));
+                summary.deleted_files += 1;
+            }
+        } else {
+            let hash = hash_file(path);
+            log_info!(
+                "{} data file: {} (hash {})",
+                action("Found", "Would add"),
+                filename.red(),
+                hash.green()
+            );
+            if !options.dry_run {
+                files.insert(filename.clone(), hash);
+                summary.added_entries += 1;
+            }
         }
+    }
+
+    for filename in &sync.missing {
+        if options.allow_delete_entries {
+            log_warn!(
+                "{} entry of missing file {} in {}",
+                action("Removing", "Would remove"),
+                filename.blue(),
+                dir.display()
+            );
+            if !options.dry_run {
+                files.remove(filename);
+                summary.deleted_entries += 1;
+            }
+        } else {
+            log_warn!("Missing file {} in {}", filename.blue(), dir.display());
+        }
+    }
+
+    for filename in &sync.changed {
+        log_warn!(
+            "File {} in {} doesn't match the hash in the metadata",
+            filename.red(),
+            dir.display()
+        );
+    }
+
+    sync
+}
 
+/// Check the raw data directory against the files listed in the metadata,
+/// adding files found in the directory to the metadata and reporting files
+/// that are missing or whose contents changed. Deletes only what the
+/// options allow, and with `dry_run`, nothing is changed at all.
+pub fn sync(meta_dir: &Path, raw_dir: &Path, options: SyncOptions) -> SyncSummary {
+    let mut summary = SyncSummary {
+        dry_run: options.dry_run,
+        ..Default::default()
+    };
+    for (path, mut ec) in read_meta(meta_dir) {
+        let ec_path = raw_dir.join(ec.path.clone());
+        create_missing_dir(&ec_path, options.dry_run);
         for (election_key, election) in ec.elections.iter_mut() {
             let election_path = ec_path.join(election_key);
-            if !election_path.is_dir() {
-                eprintln!(
-                    "Creating missing directory: {}",
-                    election_path.to_string_lossy().red()
-                );
-                create_dir_all(election_path.clone()).unwrap();
-            }
+            create_missing_dir(&election_path, options.dry_run);
 
-            let mut expected_files: HashSet<String> = election.files.keys().cloned().collect();
-
-            for entry in fs::read_dir(election_path).unwrap() {
-                let entry = entry.unwrap();
-                let filename = String::from(entry.file_name().to_str().unwrap());
-                if filename.starts_with('.') {
-                    continue;
-                };
-                if !expected_files.remove(&filename) {
-                    eprintln!(
-                        "Found data file: {}",
-                        entry.file_name().to_string_lossy().red()
-                    );
-
-                    let hash_str = hash_file(entry.path());
-                    eprintln!("Hash: {}", hash_str.green());
-
-                    election.files.insert(filename, hash_str);
-                }
+            let election_sync = sync_election(
+                &format!("{}/{}", ec.path, election_key),
+                &mut election.files,
+                &election_path,
+                options,
+                &mut summary,
+            );
+            if !election_sync.is_empty() {
+                summary.elections.push(election_sync);
             }
+        }
 
-            for missing_file in expected_files {
-                eprintln!("{}: missing file {}", "Warning".red(), missing_file.blue());
-            }
+        if !options.dry_run {
+            write_serialized(&path, &ec);
         }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::TempDir;
+
+    #[test]
+    fn test_sync_election() {
+        let dir = TempDir::new("sync");
+        fs::write(dir.join("cvr.json"), "{}").unwrap();
+        fs::write(dir.join("changed.csv"), "a,b").unwrap();
+        fs::write(dir.join("new.csv"), "c,d").unwrap();
+        fs::write(dir.join(".DS_Store"), "").unwrap();
+
+        let mut files: BTreeMap<String, String> = BTreeMap::new();
+        files.insert("cvr.json".to_string(), hash_file(dir.join("cvr.json")));
+        files.insert("changed.csv".to_string(), "0000".to_string());
+        files.insert("gone.zip".to_string(), "1111".to_string());
+        let expected = ElectionSync {
+            election: "us/zz/2024".to_string(),
+            extra: vec!["new.csv".to_string()],
+            missing: vec!["gone.zip".to_string()],
+            changed: vec!["changed.csv".to_string()],
+        };
+
+        // A dry run reports the differences but changes nothing.
+        let mut summary = SyncSummary::default();
+        let dry_run = SyncOptions {
+            dry_run: true,
+            allow_delete_entries: true,
+            allow_delete_files: true,
+        };
+        let sync = sync_election("us/zz/2024", &mut files, &dir, dry_run, &mut summary);
+        assert_eq!(expected, sync);
+        assert_eq!(3, files.len());
+        assert!(dir.join("new.csv").exists());
+
+        // By default, new files are added and nothing is deleted.
+        let options = SyncOptions::default();
+        sync_election("us/zz/2024", &mut files, &dir, options, &mut summary);
+        assert!(files.contains_key("new.csv") && files.contains_key("gone.zip"));
+        assert_eq!(1, summary.added_entries);
 
-        write_serialized(&path, &ec);
+        files.remove("new.csv");
+        let options = SyncOptions {
+            allow_delete_entries: true,
+            allow_delete_files: true,
+            ..Default::default()
+        };
+        sync_election("us/zz/2024", &mut files, &dir, options, &mut summary);
+        assert!(!files.contains_key("gone.zip"));
+        assert!(!dir.join("new.csv").exists());
+        assert_eq!((1, 1), (summary.deleted_entries, summary.deleted_files));
     }
 }
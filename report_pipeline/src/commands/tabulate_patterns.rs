@@ -0,0 +1,17 @@
+use crate::model::metadata::TabulationOptions;
+use crate::model::report::RankingPatterns;
+use crate::patterns::weighted_ballots;
+use crate::tabulator::tabulate_weighted;
+use crate::util::read_serialized;
+use std::path::Path;
+
+/// Re-tabulate a contest from its ranking pattern frequency table, and
+/// write the resulting rounds to stdout as JSON.
+pub fn tabulate_patterns(patterns_file: &Path, tabulation_options: &TabulationOptions) {
+    let patterns: RankingPatterns = read_serialized(patterns_file);
+    let ballots = weighted_ballots(&patterns)
+        .unwrap_or_else(|err| panic!("Invalid patterns in {}: {}", patterns_file.display(), err));
+
+    let rounds = tabulate_weighted(ballots, tabulation_options);
+    println!("{}", serde_json::to_string_pretty(&rounds).unwrap());
+}
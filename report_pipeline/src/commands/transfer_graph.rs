@@ -0,0 +1,17 @@
+use crate::model::report::ContestReport;
+use crate::transfer_graph::transfer_graph as build_transfer_graph;
+use crate::util::read_serialized;
+use std::path::Path;
+
+/// Write the graph of vote flows between candidates across the rounds of a
+/// contest report to stdout, as Graphviz DOT or as a Mermaid flowchart.
+pub fn transfer_graph(report_file: &Path, mermaid: bool) {
+    let report: ContestReport = read_serialized(report_file);
+    let graph = build_transfer_graph(&report.candidates, &report.rounds);
+
+    if mermaid {
+        print!("{}", graph.to_mermaid());
+    } else {
+        print!("{}", graph.to_dot());
+    }
+}
@@ -0,0 +1,380 @@
+use crate::model::election::Candidate;
+use crate::model::report::ContestReport;
+use crate::tabulator::{Allocatee, TabulatorRound};
+use crate::util::read_serialized;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Row, Table};
+use ratatui::Frame;
+use std::path::Path;
+
+const BAR_WIDTH: usize = 20;
+
+/// Which round is shown, and which panels are visible.
+struct ViewState {
+    round: usize,
+    num_rounds: usize,
+    show_transfers: bool,
+    show_candidates: bool,
+}
+
+impl ViewState {
+    fn new(num_rounds: usize) -> ViewState {
+        ViewState {
+            round: 0,
+            num_rounds,
+            show_transfers: true,
+            show_candidates: false,
+        }
+    }
+
+    /// Update the state for a key press. Returns false if the viewer should
+    /// quit.
+    fn handle_key(&mut self, key: KeyCode) -> bool {
+        let last_round = self.num_rounds.saturating_sub(1);
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Char('n') => {
+                self.round = (self.round + 1).min(last_round)
+            }
+            KeyCode::Left | KeyCode::Char('h') | KeyCode::Char('p') => {
+                self.round = self.round.saturating_sub(1)
+            }
+            KeyCode::Home | KeyCode::Char('g') => self.round = 0,
+            KeyCode::End | KeyCode::Char('G') => self.round = last_round,
+            KeyCode::Char('t') => self.show_transfers = !self.show_transfers,
+            KeyCode::Char('c') => self.show_candidates = !self.show_candidates,
+            _ => {}
+        }
+        true
+    }
+}
+
+/// A candidate's (or the exhausted ballots') votes in a round.
+#[derive(PartialEq, Debug)]
+struct TallyRow {
+    name: String,
+    votes: u32,
+    /// Fraction of the round's continuing ballots, for candidates.
+    share: Option<f32>,
+    /// Change in votes from the previous round.
+    change: Option<i64>,
+}
+
+fn allocatee_name(candidates: &[Candidate], allocatee: Allocatee) -> &str {
+    match allocatee {
+        Allocatee::Candidate(c) => candidates[c.0 as usize].display_name(),
+        Allocatee::Exhausted => "Exhausted",
+    }
+}
+
+fn tally_rows(candidates: &[Candidate], rounds: &[TabulatorRound], round: usize) -> Vec<TallyRow> {
+    let current = &rounds[round];
+    let previous = round.checked_sub(1).map(|r| &rounds[r]);
+
+    current
+        .allocations
+        .iter()
+        .map(|allocation| {
+            let previous_votes = previous.map(|previous| {
+                previous
+                    .allocations
+                    .iter()
+                    .find(|a| a.allocatee == allocation.allocatee)
+                    .map(|a| a.votes)
+                    .unwrap_or(0)
+            });
+            let share = match allocation.allocatee {
+                Allocatee::Candidate(_) if current.continuing_ballots > 0 => {
+                    Some(allocation.votes as f32 / current.continuing_ballots as f32)
+                }
+                _ => None,
+            };
+
+            TallyRow {
+                name: allocatee_name(candidates, allocation.allocatee).to_string(),
+                votes: allocation.votes,
+                share,
+                change: previous_votes.map(|v| allocation.votes as i64 - v as i64),
+            }
+        })
+        .collect()
+}
+
+/// Lines describing the transfers of eliminated candidates' ballots that
+/// produced a round.
+fn transfer_lines(candidates: &[Candidate], round: &TabulatorRound) -> Vec<String> {
+    round
+        .transfers
+        .iter()
+        .map(|transfer| {
+            format!(
+                "{} → {}: {}",
+                candidates[transfer.from.0 as usize].display_name(),
+                allocatee_name(candidates, transfer.to),
+                transfer.count
+            )
+        })
+        .collect()
+}
+
+fn draw(frame: &mut Frame, report: &ContestReport, state: &ViewState) {
+    let [header_area, main_area, footer_area] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(0),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let winner = report.winner().map(|w| w.display_name()).unwrap_or("none");
+    frame.render_widget(
+        Paragraph::new(vec![
+            Line::from(format!(
+                "{} — {}",
+                report.info.jurisdiction_name, report.info.office_name
+            ))
+            .bold(),
+            Line::from(format!(
+                "{} · {} ballots · winner: {}",
+                report.info.election_name, report.ballot_count, winner
+            )),
+        ])
+        .block(Block::bordered()),
+        header_area,
+    );
+
+    let mut constraints = vec![Constraint::Fill(2)];
+    if state.show_transfers {
+        constraints.push(Constraint::Fill(1));
+    }
+    if state.show_candidates {
+        constraints.push(Constraint::Fill(1));
+    }
+    let panels = Layout::horizontal(constraints).split(main_area);
+
+    let round = &report.rounds[state.round];
+    let max_votes = round.allocations.iter().map(|a| a.votes).max().unwrap_or(0);
+    let rows = tally_rows(&report.candidates, &report.rounds, state.round)
+        .into_iter()
+        .map(|row| {
+            let bar_len = if max_votes > 0 {
+                row.votes as usize * BAR_WIDTH / max_votes as usize
+            } else {
+                0
+            };
+            Row::new(vec![
+                row.name,
+                row.votes.to_string(),
+                row.share
+                    .map(|s| format!("{:.1}%", s * 100.))
+                    .unwrap_or_default(),
+                row.change
+                    .filter(|c| *c != 0)
+                    .map(|c| format!("{:+}", c))
+                    .unwrap_or_default(),
+                "█".repeat(bar_len),
+            ])
+        });
+    let title = format!(
+        " Round {} of {} · {} continuing ",
+        state.round + 1,
+        state.num_rounds,
+        round.continuing_ballots
+    );
+    frame.render_widget(
+        Table::new(
+            rows,
+            [
+                Constraint::Min(12),
+                Constraint::Length(9),
+                Constraint::Length(7),
+                Constraint::Length(8),
+                Constraint::Length(BAR_WIDTH as u16),
+            ],
+        )
+        .header(
+            Row::new(vec!["Candidate", "Votes", "Share", "Change", ""])
+                .style(Style::new().add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::bordered().title(title)),
+        panels[0],
+    );
+
+    let mut next_panel = 1;
+    if state.show_transfers {
+        let mut lines = transfer_lines(&report.candidates, round);
+        if lines.is_empty() {
+            lines.push("No transfers into this round".to_string());
+        }
+        frame.render_widget(
+            Paragraph::new(lines.into_iter().map(Line::from).collect::<Vec<_>>())
+                .block(Block::bordered().title(" Transfers ")),
+            panels[next_panel],
+        );
+        next_panel += 1;
+    }
+    if state.show_candidates {
+        let rows = report.total_votes.iter().map(|total| {
+            Row::new(vec![
+                report.candidates[total.candidate.0 as usize]
+                    .display_name()
+                    .to_string(),
+                total.first_round_votes.to_string(),
+                total.transfer_votes.to_string(),
+                total
+                    .round_eliminated
+                    .map(|r| r.to_string())
+                    .unwrap_or_default(),
+            ])
+        });
+        frame.render_widget(
+            Table::new(
+                rows,
+                [
+                    Constraint::Fill(1),
+                    Constraint::Length(9),
+                    Constraint::Length(9),
+                    Constraint::Length(5),
+                ],
+            )
+            .header(
+                Row::new(vec!["Candidate", "First", "Transfer", "Out"])
+                    .style(Style::new().add_modifier(Modifier::BOLD)),
+            )
+            .block(Block::bordered().title(" Candidates ")),
+            panels[next_panel],
+        );
+    }
+
+    frame.render_widget(
+        Line::from("←/→ round · g/G first/last · t transfers · c candidates · q quit").dim(),
+        footer_area,
+    );
+}
+
+/// Explore a contest report in the terminal, stepping through its rounds.
+pub fn view(report_file: &Path) {
+    let report: ContestReport = read_serialized(report_file);
+    if report.rounds.is_empty() {
+        panic!("{} has no rounds to show", report_file.display());
+    }
+
+    let mut state = ViewState::new(report.rounds.len());
+    let mut terminal = ratatui::init();
+    let result = (|| -> std::io::Result<()> {
+        loop {
+            terminal.draw(|frame| draw(frame, &report, &state))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !state.handle_key(key.code) {
+                    return Ok(());
+                }
+            }
+        }
+    })();
+    ratatui::restore();
+    result.unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::{CandidateId, CandidateType};
+    use crate::tabulator::{TabulatorAllocation, Transfer};
+
+    fn round(allocations: Vec<(Allocatee, u32)>, transfers: Vec<Transfer>) -> TabulatorRound {
+        let continuing_ballots = allocations
+            .iter()
+            .filter(|(a, _)| *a != Allocatee::Exhausted)
+            .map(|(_, v)| v)
+            .sum();
+        TabulatorRound {
+            allocations: allocations
+                .into_iter()
+                .map(|(allocatee, votes)| TabulatorAllocation {
+                    allocatee,
+                    votes,
+                    shares: None,
+                })
+                .collect(),
+            undervote: 0,
+            overvote: 0,
+            continuing_ballots,
+            transfers,
+            threshold: None,
+        }
+    }
+
+    #[test]
+    fn test_tally_and_transfers() {
+        let candidates = vec![
+            Candidate::new("Alice".to_string(), CandidateType::Regular),
+            Candidate::new("Bob".to_string(), CandidateType::Regular),
+            Candidate::new("Carol".to_string(), CandidateType::Regular),
+        ];
+        let (alice, bob, carol) = (
+            Allocatee::Candidate(CandidateId(0)),
+            Allocatee::Candidate(CandidateId(1)),
+            Allocatee::Candidate(CandidateId(2)),
+        );
+        let rounds = vec![
+            round(vec![(alice, 5), (bob, 4), (carol, 1)], vec![]),
+            round(
+                vec![(alice, 5), (bob, 4), (Allocatee::Exhausted, 1)],
+                vec![Transfer {
+                    from: CandidateId(2),
+                    to: Allocatee::Exhausted,
+                    count: 1,
+                    shares: None,
+                }],
+            ),
+        ];
+
+        let rows = tally_rows(&candidates, &rounds, 1);
+        assert_eq!(
+            TallyRow {
+                name: "Alice".to_string(),
+                votes: 5,
+                share: Some(5. / 9.),
+                change: Some(0),
+            },
+            rows[0]
+        );
+        assert_eq!(
+            TallyRow {
+                name: "Exhausted".to_string(),
+                votes: 1,
+                share: None,
+                change: Some(1),
+            },
+            rows[2]
+        );
+        assert_eq!(None, tally_rows(&candidates, &rounds, 0)[0].change);
+
+        assert_eq!(
+            vec!["Carol → Exhausted: 1".to_string()],
+            transfer_lines(&candidates, &rounds[1])
+        );
+    }
+
+    #[test]
+    fn test_view_state_keys() {
+        let mut state = ViewState::new(3);
+        assert!(state.handle_key(KeyCode::Left));
+        assert_eq!(0, state.round);
+        state.handle_key(KeyCode::Right);
+        state.handle_key(KeyCode::Right);
+        state.handle_key(KeyCode::Right);
+        assert_eq!(2, state.round);
+        state.handle_key(KeyCode::Home);
+        assert_eq!(0, state.round);
+
+        state.handle_key(KeyCode::Char('t'));
+        state.handle_key(KeyCode::Char('c'));
+        assert!(!state.show_transfers);
+        assert!(state.show_candidates);
+
+        assert!(!state.handle_key(KeyCode::Char('q')));
+    }
+}
@@ -0,0 +1,151 @@
+use super::report::{report, ReportOptions};
+use crate::read_metadata::read_meta;
+use crate::util::get_files_from_path;
+use crate::{log_debug, log_info};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::thread::sleep;
+use std::time::{Duration, SystemTime};
+
+/// Size and modification time of each file under a directory.
+type Snapshot = BTreeMap<PathBuf, (u64, Option<SystemTime>)>;
+
+fn snapshot_files(dir: &Path) -> Snapshot {
+    get_files_from_path(dir)
+        .unwrap()
+        .into_iter()
+        .filter_map(|path| {
+            let metadata = path.metadata().ok()?;
+            Some((path, (metadata.len(), metadata.modified().ok())))
+        })
+        .collect()
+}
+
+/// Files that were added, modified or removed between two snapshots.
+fn changed_files(previous: &Snapshot, current: &Snapshot) -> BTreeSet<PathBuf> {
+    let modified = current
+        .iter()
+        .filter(|(path, stat)| previous.get(*path) != Some(stat))
+        .map(|(path, _)| path.clone());
+    let removed = previous
+        .keys()
+        .filter(|path| !current.contains_key(*path))
+        .cloned();
+    modified.chain(removed).collect()
+}
+
+/// Elections, as `{jurisdiction}/{election}` paths, whose raw data directory
+/// contains one of the changed files.
+fn stale_elections(
+    meta_dir: &Path,
+    raw_dir: &Path,
+    changed: &BTreeSet<PathBuf>,
+) -> BTreeSet<String> {
+    let mut stale = BTreeSet::new();
+    for (_, jurisdiction) in read_meta(meta_dir) {
+        for election_path in jurisdiction.elections.keys() {
+            let election_dir = raw_dir.join(&jurisdiction.path).join(election_path);
+            if changed.iter().any(|path| path.starts_with(&election_dir)) {
+                stale.insert(format!("{}/{}", jurisdiction.path, election_path));
+            }
+        }
+    }
+    stale
+}
+
+/// Generate reports, then poll the raw data directory for new, modified or
+/// removed files and regenerate the reports of the elections they belong
+/// to. Other elections are reported from cache. Changes are only acted on
+/// once a poll finds no further changes, so that files still being copied
+/// in aren't read half-written.
+pub fn watch(
+    meta_dir: &Path,
+    raw_dir: &Path,
+    report_dir: &Path,
+    preprocessed_dir: &Path,
+    options: &ReportOptions,
+    interval: Duration,
+) {
+    report(meta_dir, raw_dir, report_dir, preprocessed_dir, options);
+
+    let mut snapshot = snapshot_files(raw_dir);
+    let mut changed: BTreeSet<PathBuf> = BTreeSet::new();
+    log_info!("Watching {} for changes...", raw_dir.display());
+
+    loop {
+        sleep(interval);
+
+        let current = snapshot_files(raw_dir);
+        let new_changes = changed_files(&snapshot, &current);
+        snapshot = current;
+        if !new_changes.is_empty() {
+            log_debug!(
+                "{} files changed, waiting for them to settle",
+                new_changes.len()
+            );
+            changed.extend(new_changes);
+            continue;
+        }
+        if changed.is_empty() {
+            continue;
+        }
+
+        let stale = stale_elections(meta_dir, raw_dir, &changed);
+        changed.clear();
+        if stale.is_empty() {
+            log_debug!("Changed files don't belong to any election");
+            continue;
+        }
+
+        for election in &stale {
+            log_info!("Raw data changed for {}", election);
+        }
+        report(
+            meta_dir,
+            raw_dir,
+            report_dir,
+            preprocessed_dir,
+            &ReportOptions {
+                force_preprocess: false,
+                force_report: false,
+                stale_elections: Some(&stale),
+                ..*options
+            },
+        );
+        log_info!("Watching {} for changes...", raw_dir.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed_files() {
+        let t = SystemTime::UNIX_EPOCH;
+        let previous: Snapshot = vec![
+            (PathBuf::from("a/cvr1.json"), (10, Some(t))),
+            (PathBuf::from("a/cvr2.json"), (10, Some(t))),
+            (PathBuf::from("b/cvr.json"), (10, Some(t))),
+        ]
+        .into_iter()
+        .collect();
+        let current: Snapshot = vec![
+            (PathBuf::from("a/cvr1.json"), (10, Some(t))),
+            (PathBuf::from("a/cvr2.json"), (12, Some(t))),
+            (PathBuf::from("c/cvr.json"), (10, Some(t))),
+        ]
+        .into_iter()
+        .collect();
+
+        let changed: Vec<PathBuf> = changed_files(&previous, &current).into_iter().collect();
+        assert_eq!(
+            vec![
+                PathBuf::from("a/cvr2.json"),
+                PathBuf::from("b/cvr.json"),
+                PathBuf::from("c/cvr.json"),
+            ],
+            changed
+        );
+    }
+}
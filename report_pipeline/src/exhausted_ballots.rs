@@ -0,0 +1,183 @@
+//! Classification of the ballots exhausted before the final round.
+//!
+//! A ballot that ranks a finalist always counts for one in the final
+//! round, so an exhausted ballot either ranked none of the finalists, or
+//! ranked one only after a point at which the overvote or skipped-rank
+//! rules cut it off. When ballots of the second kind outnumber the final
+//! margin, the rules alone could have decided the contest. Candidates
+//! marked within an overvote are not known, so ballots are only classified
+//! by the candidates marked on their own.
+
+use crate::model::election::{CandidateId, NormalizedElection};
+use crate::model::report::{ExhaustedBallots, FinalistPreference};
+use crate::tabulator::TabulatorRound;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+
+/// Classify the ballots that count for no candidate in the final round.
+/// Returns `None` if normalization did not record the ballots it cut off,
+/// or the final round has fewer than two candidates.
+pub fn generate_exhausted_ballots(
+    election: &NormalizedElection,
+    rounds: &[TabulatorRound],
+) -> Option<ExhaustedBallots> {
+    let truncated = election.truncated_ballots.as_ref()?;
+    let mut finalists: Vec<(CandidateId, u32)> = rounds
+        .last()?
+        .allocations
+        .iter()
+        .flat_map(|a| Some((a.allocatee.candidate_id()?, a.votes)))
+        .collect();
+    if finalists.len() < 2 {
+        return None;
+    }
+    finalists.sort_by_key(|&(_, votes)| Reverse(votes));
+    let final_margin = finalists[0].1 - finalists[1].1;
+    let is_finalist = |candidate: &CandidateId| finalists.iter().any(|(c, _)| c == candidate);
+
+    let dropped: HashMap<u32, &[CandidateId]> = truncated
+        .iter()
+        .map(|t| (t.ballot, t.dropped.as_slice()))
+        .collect();
+    let mut exhausted = 0;
+    let mut ranked_no_finalist = 0;
+    let mut preferences: BTreeMap<CandidateId, u32> = BTreeMap::new();
+    for (i, ballot) in election.ballots.iter().enumerate() {
+        let dropped = dropped.get(&(i as u32)).copied().unwrap_or_default();
+        let choices = ballot.choices();
+        if choices.iter().any(is_finalist)
+            || (choices.is_empty() && !ballot.overvoted && dropped.is_empty())
+        {
+            // Counts in the final round, or is blank.
+            continue;
+        }
+        exhausted += 1;
+        match dropped.iter().find(|c| is_finalist(c)) {
+            Some(&candidate) => *preferences.entry(candidate).or_default() += 1,
+            None => ranked_no_finalist += 1,
+        }
+    }
+
+    let mut rule_exhausted_preferences: Vec<FinalistPreference> = preferences
+        .into_iter()
+        .map(|(candidate, ballots)| FinalistPreference { candidate, ballots })
+        .collect();
+    rule_exhausted_preferences.sort_by_key(|p| Reverse(p.ballots));
+    let rule_exhausted = exhausted - ranked_no_finalist;
+
+    Some(ExhaustedBallots {
+        exhausted,
+        ranked_no_finalist,
+        rule_exhausted,
+        rule_exhausted_preferences,
+        final_margin,
+        exceeds_margin: rule_exhausted > final_margin,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::{Candidate, CandidateType, NormalizedBallot, TruncatedBallot};
+    use crate::tabulator::{Allocatee, TabulatorAllocation};
+
+    fn election(ballots: &[(&[u32], bool)], truncated: Vec<TruncatedBallot>) -> NormalizedElection {
+        NormalizedElection {
+            candidates: ["A", "B", "C"]
+                .iter()
+                .map(|name| Candidate::new(name.to_string(), CandidateType::Regular))
+                .collect(),
+            ballots: ballots
+                .iter()
+                .enumerate()
+                .map(|(i, &(choices, overvoted))| {
+                    let choices = choices.iter().map(|&c| CandidateId(c)).collect();
+                    NormalizedBallot::new(i.to_string(), choices, overvoted)
+                })
+                .collect(),
+            adjudicated_ballots: None,
+            adjudication: None,
+            duplicate_ballots: None,
+            candidate_reconciliation: None,
+            rank_positions: None,
+            duplicate_rankings: None,
+            first_round_adjustments: None,
+            overvote_handling: None,
+            skipped_rank_handling: None,
+            truncated_ballots: Some(truncated),
+        }
+    }
+
+    fn final_round(votes: &[(u32, u32)]) -> TabulatorRound {
+        TabulatorRound {
+            allocations: votes
+                .iter()
+                .map(|&(c, votes)| TabulatorAllocation {
+                    allocatee: Allocatee::Candidate(CandidateId(c)),
+                    votes,
+                    shares: None,
+                })
+                .collect(),
+            undervote: 0,
+            overvote: 0,
+            continuing_ballots: votes.iter().map(|(_, v)| v).sum(),
+            transfers: vec![],
+            threshold: None,
+        }
+    }
+
+    #[test]
+    fn test_exhausted_ballots() {
+        // A and B are the finalists. C's voters exhausted: one ranked no one
+        // else, one was cut off at an overvote before B, and one was cut off
+        // at skipped ranks before A.
+        let truncated = |ballot, dropped: &[u32]| TruncatedBallot {
+            ballot,
+            dropped: dropped.iter().map(|&c| CandidateId(c)).collect(),
+        };
+        let election = election(
+            &[
+                (&[0], false),
+                (&[1, 2], false),
+                (&[2], false),
+                (&[2], true),
+                (&[2], false),
+                (&[], false),
+                (&[], true),
+            ],
+            vec![truncated(3, &[1, 0]), truncated(4, &[0])],
+        );
+        let exhausted =
+            generate_exhausted_ballots(&election, &[final_round(&[(0, 1), (1, 1)])]).unwrap();
+        assert_eq!(
+            ExhaustedBallots {
+                exhausted: 4,
+                ranked_no_finalist: 2,
+                rule_exhausted: 2,
+                rule_exhausted_preferences: vec![
+                    FinalistPreference {
+                        candidate: CandidateId(0),
+                        ballots: 1,
+                    },
+                    FinalistPreference {
+                        candidate: CandidateId(1),
+                        ballots: 1,
+                    },
+                ],
+                final_margin: 0,
+                exceeds_margin: true,
+            },
+            exhausted
+        );
+    }
+
+    #[test]
+    fn test_not_recorded() {
+        let mut election = election(&[(&[0], false), (&[1], false)], vec![]);
+        let rounds = [final_round(&[(0, 1), (1, 1)])];
+        assert!(generate_exhausted_ballots(&election, &rounds).is_some());
+        assert!(generate_exhausted_ballots(&election, &[final_round(&[(0, 2)])]).is_none());
+        election.truncated_ballots = None;
+        assert!(generate_exhausted_ballots(&election, &rounds).is_none());
+    }
+}
@@ -0,0 +1,331 @@
+//! Quick checks that a contest's raw data is where its loader parameters
+//! say, without reading any ballots.
+
+use crate::formats::common::{glob_regex, CsvDialect};
+use crate::formats::detect::{first_line, zip_contains};
+use crate::formats::{dominion_rcr, nist_sp_1500, us_vt_btv};
+use crate::util::open_raw;
+use calamine::{open_workbook, DataType, Reader, Xlsx, XlsxError};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fs::read_dir;
+use std::io::Read;
+use std::path::Path;
+
+/// Collects problems with a contest's raw data.
+struct Check<'a> {
+    dir: &'a Path,
+    params: &'a BTreeMap<String, String>,
+    problems: Vec<String>,
+}
+
+impl<'a> Check<'a> {
+    fn param(&mut self, name: &str) -> Option<&'a str> {
+        let value = self.params.get(name).map(|v| v.as_str());
+        if value.is_none() {
+            self.problem(format!("loader parameter {} is missing", name));
+        }
+        value
+    }
+
+    fn number_param(&mut self, name: &str) -> Option<u32> {
+        let value = self.param(name)?;
+        let number = value.parse().ok();
+        if number.is_none() {
+            self.problem(format!(
+                "loader parameter {} is not a number: {}",
+                name, value
+            ));
+        }
+        number
+    }
+
+    /// The first line of a text file, if it can be opened and isn't empty.
+    fn text_file(&mut self, file: &str) -> Option<String> {
+        match first_line(&self.dir.join(file)) {
+            Some(line) if !line.trim().is_empty() => Some(line),
+            Some(_) => {
+                self.problem(format!("{} is empty", file));
+                None
+            }
+            None => {
+                self.problem(format!("{} can't be read", file));
+                None
+            }
+        }
+    }
+
+    /// The header row of the first sheet of a workbook, if it can be read.
+    fn workbook(&mut self, file: &str) -> Option<Vec<String>> {
+        match workbook_header(&self.dir.join(file)) {
+            Ok(header) => Some(header),
+            Err(e) => {
+                self.problem(format!("{} can't be read as a workbook: {}", file, e));
+                None
+            }
+        }
+    }
+
+    fn problem(&mut self, problem: String) {
+        self.problems.push(problem);
+    }
+}
+
+/// The first row of the first sheet of an XLSX workbook. Cells are read
+/// one at a time, so only the first row of a large CVR is parsed.
+fn workbook_header(path: &Path) -> Result<Vec<String>, String> {
+    let mut workbook: Xlsx<_> = open_workbook(path).map_err(|e: XlsxError| e.to_string())?;
+    let sheet = match workbook.sheet_names().first() {
+        Some(sheet) => sheet.clone(),
+        None => return Err("it has no sheets".to_string()),
+    };
+    let mut cells = workbook
+        .worksheet_cells_reader(&sheet)
+        .map_err(|e| e.to_string())?;
+    let mut header = Vec::new();
+    let mut header_row = None;
+    while let Some(cell) = cells.next_cell().map_err(|e| e.to_string())? {
+        let row = cell.get_position().0;
+        if *header_row.get_or_insert(row) != row {
+            break;
+        }
+        header.extend(cell.get_value().as_string());
+    }
+    Ok(header)
+}
+
+fn check_sfo(check: &mut Check) {
+    check.number_param("contest");
+    for param in ["masterLookup", "ballotImage"] {
+        if let Some(file) = check.param(param) {
+            check.text_file(file);
+        }
+    }
+}
+
+fn check_nist(check: &mut Check) {
+    let contest = check.number_param("contest");
+    let cvr = match check.param("cvr") {
+        Some(cvr) => cvr,
+        None => return,
+    };
+    let cvr_path = check.dir.join(cvr);
+    if !cvr_path.exists() {
+        check.problem(format!("{} doesn't exist", cvr));
+        return;
+    }
+    match nist_sp_1500::read_contest_manifest(&cvr_path) {
+        Some(contests) => {
+            if let Some(contest) = contest.filter(|c| !contests.contains_key(c)) {
+                check.problem(format!(
+                    "contest {} is not in the ContestManifest.json of {}",
+                    contest, cvr
+                ));
+            }
+        }
+        None => check.problem(format!("{} has no readable ContestManifest.json", cvr)),
+    }
+    let cvr_files = check.params.get("cvrFiles").map(|g| glob_regex(g));
+    if !nist_sp_1500::has_cvr_files(&cvr_path, cvr_files.as_ref()) {
+        check.problem(format!("{} has no CVR files", cvr));
+    }
+}
+
+fn check_btv(check: &mut Check) {
+    let ballots = match check.param("ballots") {
+        Some(ballots) => ballots,
+        None => return,
+    };
+    let archive = check.params.get("archive").map(|a| a.as_str());
+    let path = us_vt_btv::ballots_path(check.dir, ballots, archive);
+    match first_line(&path) {
+        Some(line) if line.starts_with('.') => (),
+        Some(_) => check.problem(format!("{} doesn't start with a .CANDIDATE line", ballots)),
+        None => match archive {
+            Some(archive) if zip_contains(&check.dir.join(archive), ballots) => check.problem(
+                format!("{} has not been extracted from {}", ballots, archive),
+            ),
+            _ => check.problem(format!("{} can't be read", ballots)),
+        },
+    }
+}
+
+fn check_rcr(check: &mut Check) {
+    if let Some(rcr) = check.param("rcr") {
+        if let Some(line) = check.text_file(rcr) {
+            if !dominion_rcr::is_header(&line) {
+                check.problem(format!("{} doesn't start with an RCR header", rcr));
+            }
+        }
+    }
+}
+
+fn check_maine(check: &mut Check) {
+    if let Some(files) = check.param("files") {
+        for file in files.split(';') {
+            check.workbook(file);
+        }
+    }
+}
+
+fn check_mpls(check: &mut Check) {
+    let file = match check.param("file") {
+        Some(file) => file,
+        None => return,
+    };
+    let columns = open_raw(&check.dir.join(file))
+        .and_then(|raw| CsvDialect::from_params(check.params).reader(raw, true))
+        .ok()
+        .and_then(|mut reader| reader.headers().ok().map(|h| h.len()));
+    match columns {
+        Some(columns) if columns >= 5 => (),
+        Some(columns) => check.problem(format!(
+            "{} has {} columns, expected precinct, three choices and a count",
+            file, columns
+        )),
+        None => check.problem(format!("{} can't be read as CSV", file)),
+    }
+}
+
+fn check_json(check: &mut Check) {
+    let file = match check.param("file") {
+        Some(file) => file,
+        None => return,
+    };
+    let mut start = [0u8; 64];
+    let read = open_raw(&check.dir.join(file)).and_then(|mut raw| raw.read(&mut start));
+    match read {
+        Ok(n) if start[..n].trim_ascii_start().starts_with(b"{") => (),
+        Ok(_) => check.problem(format!("{} is not a JSON object", file)),
+        Err(_) => check.problem(format!("{} can't be read", file)),
+    }
+}
+
+fn check_nyc(check: &mut Check) {
+    if let Some(candidates_file) = check.param("candidatesFile") {
+        check.workbook(candidates_file);
+    }
+    let pattern = match check.param("cvrPattern") {
+        Some(pattern) => pattern,
+        None => return,
+    };
+    let cvr_rx = match Regex::new(&format!("^{}$", pattern)) {
+        Ok(rx) => rx,
+        Err(e) => {
+            check.problem(format!("cvrPattern is not a valid regex: {}", e));
+            return;
+        }
+    };
+    let mut cvr_files: Vec<String> = read_dir(check.dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str().map(|s| s.to_string()))
+        .filter(|f| cvr_rx.is_match(f))
+        .collect();
+    cvr_files.sort();
+    match cvr_files.first() {
+        Some(first) => {
+            let header = check.workbook(first);
+            if header.is_some_and(|h| !h.iter().any(|c| c == "Cast Vote Record")) {
+                check.problem(format!("{} has no Cast Vote Record column", first));
+            }
+        }
+        None => check.problem(format!("no files match cvrPattern {}", pattern)),
+    }
+}
+
+/// Check that the files a contest's loader parameters name exist in `dir`,
+/// the election's raw data directory, and that their headers can be read.
+/// Returns the problems found.
+pub fn check_raw_data(format: &str, dir: &Path, params: &BTreeMap<String, String>) -> Vec<String> {
+    let mut check = Check {
+        dir,
+        params,
+        problems: Vec::new(),
+    };
+    match format {
+        "us_ca_sfo" => check_sfo(&mut check),
+        "nist_sp_1500" => check_nist(&mut check),
+        "us_vt_btv" => check_btv(&mut check),
+        "dominion_rcr" => check_rcr(&mut check),
+        "us_me" => check_maine(&mut check),
+        "us_mn_mpls" => check_mpls(&mut check),
+        "simple_json" => check_json(&mut check),
+        "us_ny_nyc" => check_nyc(&mut check),
+        _ => check.problem(format!("format {} is not implemented", format)),
+    }
+    check.problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::TempDir;
+    use std::fs::write;
+
+    fn params(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_check_raw_data() {
+        let dir = TempDir::new("rcv-check");
+        write(dir.join("ballots.json"), r#"{"ballots": []}"#).unwrap();
+        write(dir.join("1 Mayor.rcr"), "1\t3\t2\t2\nAlice\n").unwrap();
+        write(dir.join("cvr.csv"), "Precinct,1st Choice,2nd Choice\n").unwrap();
+
+        assert!(
+            check_raw_data("simple_json", &dir, &params(&[("file", "ballots.json")])).is_empty()
+        );
+        assert!(
+            check_raw_data("dominion_rcr", &dir, &params(&[("rcr", "1 Mayor.rcr")])).is_empty()
+        );
+        assert_eq!(
+            vec!["missing.json can't be read".to_string()],
+            check_raw_data("simple_json", &dir, &params(&[("file", "missing.json")]))
+        );
+        assert_eq!(
+            vec!["1 Mayor.rcr is not a JSON object".to_string()],
+            check_raw_data("simple_json", &dir, &params(&[("file", "1 Mayor.rcr")]))
+        );
+        assert_eq!(
+            vec!["cvr.csv has 3 columns, expected precinct, three choices and a count".to_string()],
+            check_raw_data("us_mn_mpls", &dir, &params(&[("file", "cvr.csv")]))
+        );
+        assert_eq!(
+            vec![
+                "loader parameter contest is missing".to_string(),
+                "loader parameter cvr is missing".to_string(),
+            ],
+            check_raw_data("nist_sp_1500", &dir, &params(&[]))
+        );
+
+        let mut workbook = rust_xlsxwriter::Workbook::new();
+        let sheet = workbook.add_worksheet();
+        sheet.write_string(0, 0, "Cast Vote Record").unwrap();
+        sheet.write_string(1, 0, "1").unwrap();
+        workbook.save(dir.join("P1V1.xlsx")).unwrap();
+        let nyc = |candidates_file, cvr_pattern| {
+            check_raw_data(
+                "us_ny_nyc",
+                &dir,
+                &params(&[
+                    ("candidatesFile", candidates_file),
+                    ("cvrPattern", cvr_pattern),
+                ]),
+            )
+        };
+        assert!(nyc("P1V1.xlsx", r"P\dV\d\.xlsx").is_empty());
+        let problems = nyc("Candidates.xlsx", r"P\dV\d\.xlsx");
+        assert_eq!(1, problems.len());
+        assert!(problems[0].starts_with("Candidates.xlsx can't be read as a workbook"));
+        assert_eq!(
+            vec!["no files match cvrPattern Q.+".to_string()],
+            nyc("P1V1.xlsx", "Q.+")
+        );
+    }
+}
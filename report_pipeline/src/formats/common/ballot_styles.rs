@@ -0,0 +1,92 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Counts the ballots of each ballot style, and how many of them list each
+/// contest, to check that a CVR covering many contests lists the same
+/// contests on every ballot of a style. A style whose ballots list a
+/// contest only some of the time points to a parse error that dropped the
+/// contest from the others.
+#[derive(Default)]
+pub struct BallotStyleTally {
+    ballots: BTreeMap<String, u32>,
+    contests: BTreeMap<(String, String), u32>,
+}
+
+/// A contest listed on some, but not all, ballots of a style.
+#[derive(Debug, PartialEq)]
+pub struct StyleInconsistency {
+    pub contest: String,
+    pub style: String,
+    /// Ballots of the style.
+    pub ballots: u32,
+    /// Ballots of the style that don't list the contest.
+    pub missing: u32,
+}
+
+impl fmt::Display for StyleInconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} ballots of style {}",
+            self.missing, self.ballots, self.style
+        )
+    }
+}
+
+impl BallotStyleTally {
+    /// Record a ballot (or card) of a style, with the contests it lists.
+    pub fn record<I: IntoIterator<Item = String>>(&mut self, style: &str, contests: I) {
+        *self.ballots.entry(style.to_string()).or_default() += 1;
+        for contest in contests {
+            *self
+                .contests
+                .entry((style.to_string(), contest))
+                .or_default() += 1;
+        }
+    }
+
+    /// Contests that ballots of a style list only some of the time.
+    pub fn inconsistencies(&self) -> Vec<StyleInconsistency> {
+        self.contests
+            .iter()
+            .filter_map(|((style, contest), &listed)| {
+                let ballots = self.ballots[style];
+                (listed < ballots).then(|| StyleInconsistency {
+                    contest: contest.clone(),
+                    style: style.clone(),
+                    ballots,
+                    missing: ballots - listed,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contests(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_inconsistencies() {
+        let mut tally = BallotStyleTally::default();
+        // Style 1 has the citywide contest and district 5, style 2 only the
+        // citywide contest. One ballot of style 1 lost its district contest.
+        tally.record("1", contests(&["city", "district5"]));
+        tally.record("1", contests(&["city", "district5"]));
+        tally.record("1", contests(&["city"]));
+        tally.record("2", contests(&["city"]));
+        assert_eq!(
+            vec![StyleInconsistency {
+                contest: "district5".to_string(),
+                style: "1".to_string(),
+                ballots: 3,
+                missing: 1,
+            }],
+            tally.inconsistencies()
+        );
+    }
+}
@@ -1,28 +1,118 @@
-use crate::model::election::{Candidate, CandidateId, Choice};
-use std::collections::HashMap;
-use std::fmt::Debug;
+use crate::model::election::{Candidate, CandidateId, Choice, UnknownCandidate};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Debug, Display};
 use std::hash::Hash;
+use unicode_normalization::UnicodeNormalization;
+
+/// How candidate names are compared when deciding whether two external
+/// candidate IDs refer to the same candidate.
+#[derive(Debug, Clone, Copy)]
+pub struct NameMatching {
+    /// Compare names in Unicode NFC form, after converting spacing accents
+    /// that follow a letter (e.g. "Jose´") into combining accents, so that
+    /// differently-encoded accented names match.
+    pub normalize_unicode: bool,
+    /// Trim names and collapse runs of whitespace into a single space.
+    pub collapse_whitespace: bool,
+    /// Compare names case-insensitively, using Unicode case folding.
+    pub case_fold: bool,
+}
+
+impl Default for NameMatching {
+    fn default() -> Self {
+        NameMatching {
+            normalize_unicode: true,
+            collapse_whitespace: true,
+            case_fold: false,
+        }
+    }
+}
+
+/// Combining form of a spacing accent, if it is one.
+fn combining_accent(ch: char) -> Option<char> {
+    match ch {
+        '\u{00B4}' => Some('\u{0301}'), // acute
+        '\u{02CB}' => Some('\u{0300}'), // grave
+        '\u{02C6}' => Some('\u{0302}'), // circumflex
+        '\u{02DC}' => Some('\u{0303}'), // tilde
+        '\u{00A8}' => Some('\u{0308}'), // diaeresis
+        '\u{00B8}' => Some('\u{0327}'), // cedilla
+        _ => None,
+    }
+}
+
+impl NameMatching {
+    /// Read name matching options from loader parameters. Case-insensitive
+    /// matching is enabled with `"caseInsensitiveNames": "true"`.
+    pub fn from_params(params: &BTreeMap<String, String>) -> NameMatching {
+        NameMatching {
+            case_fold: params.get("caseInsensitiveNames").map(|v| v.as_str()) == Some("true"),
+            ..NameMatching::default()
+        }
+    }
+
+    /// The form of a name that is compared to match candidates.
+    pub fn key(&self, name: &str) -> String {
+        let mut key = name.to_string();
+
+        if self.normalize_unicode {
+            let mut chars: Vec<char> = Vec::with_capacity(key.len());
+            for ch in key.chars() {
+                match combining_accent(ch) {
+                    Some(accent) if chars.last().is_some_and(|c| c.is_alphabetic()) => {
+                        chars.push(accent)
+                    }
+                    _ => chars.push(ch),
+                }
+            }
+            key = chars.into_iter().nfc().collect();
+        }
+
+        if self.collapse_whitespace {
+            key = key.split_whitespace().collect::<Vec<&str>>().join(" ");
+        }
+
+        if self.case_fold {
+            key = caseless::default_case_fold_str(&key);
+        }
+
+        key
+    }
+}
 
 #[derive(Debug)]
 pub struct CandidateMap<ExternalCandidateId: Eq + Hash + Clone> {
     /// Mapping from external candidate numbers to our candidate numbers.
     id_to_index: HashMap<ExternalCandidateId, CandidateId>,
+    /// Mapping from the matching keys of candidate names to our candidate numbers.
+    name_to_index: HashMap<String, CandidateId>,
     candidates: Vec<Candidate>,
+    name_matching: NameMatching,
+    /// Marks for external IDs missing from the manifest, by ID.
+    unknown: BTreeMap<String, u32>,
 }
 
-impl<ExternalCandidateId: Eq + Hash + Clone + Debug> CandidateMap<ExternalCandidateId> {
+impl<ExternalCandidateId: Eq + Hash + Clone + Debug + Display> CandidateMap<ExternalCandidateId> {
     pub fn new() -> CandidateMap<ExternalCandidateId> {
+        CandidateMap::with_name_matching(NameMatching::default())
+    }
+
+    pub fn with_name_matching(name_matching: NameMatching) -> CandidateMap<ExternalCandidateId> {
         CandidateMap {
             id_to_index: HashMap::new(),
+            name_to_index: HashMap::new(),
             candidates: Vec::new(),
+            name_matching,
+            unknown: BTreeMap::new(),
         }
     }
 
     pub fn add(&mut self, external_candidate_id: ExternalCandidateId, candidate: Candidate) {
-        self.id_to_index.insert(
-            external_candidate_id,
-            CandidateId(self.candidates.len() as u32),
-        );
+        let index = CandidateId(self.candidates.len() as u32);
+        self.id_to_index.insert(external_candidate_id, index);
+        self.name_to_index
+            .entry(self.name_matching.key(&candidate.name))
+            .or_insert(index);
         self.candidates.push(candidate);
     }
 
@@ -33,16 +123,11 @@ impl<ExternalCandidateId: Eq + Hash + Clone + Debug> CandidateMap<ExternalCandid
     ) -> Choice {
         if !self.id_to_index.contains_key(&external_candidate_id) {
             // Check if a candidate with the same name already exists
-            if let Some(existing_index) = self
-                .candidates
-                .iter()
-                .position(|c| c.name == candidate.name)
-            {
+            let key = self.name_matching.key(&candidate.name);
+            if let Some(existing_index) = self.name_to_index.get(&key) {
                 // Map this external ID to the existing candidate
-                self.id_to_index.insert(
-                    external_candidate_id.clone(),
-                    CandidateId(existing_index as u32),
-                );
+                self.id_to_index
+                    .insert(external_candidate_id.clone(), *existing_index);
             } else {
                 // New candidate, add it
                 self.add(external_candidate_id.clone(), candidate);
@@ -52,16 +137,98 @@ impl<ExternalCandidateId: Eq + Hash + Clone + Debug> CandidateMap<ExternalCandid
         self.id_to_choice(external_candidate_id)
     }
 
-    pub fn id_to_choice(&self, external_candidate_id: ExternalCandidateId) -> Choice {
-        let index = self
-            .id_to_index
-            .get(&external_candidate_id)
-            .expect("Candidate on ballot but not in master lookup.");
+    /// Our choice for an external candidate ID, if it is in the manifest.
+    pub fn get(&self, external_candidate_id: &ExternalCandidateId) -> Option<Choice> {
+        self.id_to_index
+            .get(external_candidate_id)
+            .map(|index| Choice::Vote(*index))
+    }
 
-        Choice::Vote(*index)
+    /// Our choice for an external candidate ID marked on a ballot. An ID
+    /// that isn't in the manifest is recorded as unknown, and its mark
+    /// counts as a skipped rank.
+    pub fn id_to_choice(&mut self, external_candidate_id: ExternalCandidateId) -> Choice {
+        self.get(&external_candidate_id).unwrap_or_else(|| {
+            self.record_unknown(external_candidate_id.to_string());
+            Choice::Undervote
+        })
+    }
+
+    /// Record a mark for an ID that isn't in the manifest.
+    pub fn record_unknown(&mut self, external_id: String) {
+        *self.unknown.entry(external_id).or_default() += 1;
+    }
+
+    /// IDs marked on ballots that aren't in the manifest, in order of ID.
+    pub fn unknown_candidates(&self) -> Vec<UnknownCandidate> {
+        self.unknown
+            .iter()
+            .map(|(external_id, &marks)| UnknownCandidate {
+                external_id: external_id.clone(),
+                marks,
+            })
+            .collect()
     }
 
     pub fn into_vec(self) -> Vec<Candidate> {
         self.candidates
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::CandidateType;
+
+    fn add(map: &mut CandidateMap<u32>, id: u32, name: &str) -> Choice {
+        map.add_id_to_choice(id, Candidate::new(name.to_string(), CandidateType::Regular))
+    }
+
+    #[test]
+    fn test_accented_names() {
+        let mut map = CandidateMap::new();
+        let jose = add(&mut map, 1, "José Peters");
+        // Decomposed: "e" followed by a combining acute accent.
+        assert_eq!(jose, add(&mut map, 2, "Jose\u{0301} Peters"));
+        // Spacing acute accent after the letter.
+        assert_eq!(jose, add(&mut map, 3, "Jose\u{00B4}  Peters "));
+        assert_ne!(jose, add(&mut map, 4, "Jose Peters"));
+        assert_eq!(2, map.into_vec().len());
+    }
+
+    #[test]
+    fn test_unknown_ids() {
+        let mut map: CandidateMap<u32> = CandidateMap::new();
+        map.add(
+            7,
+            Candidate::new("Alice".to_string(), CandidateType::Regular),
+        );
+        assert_eq!(Choice::Vote(CandidateId(0)), map.id_to_choice(7));
+        assert_eq!(Choice::Undervote, map.id_to_choice(9));
+        assert_eq!(Choice::Undervote, map.id_to_choice(9));
+        assert_eq!(
+            vec![UnknownCandidate {
+                external_id: "9".to_string(),
+                marks: 2,
+            }],
+            map.unknown_candidates()
+        );
+    }
+
+    #[test]
+    fn test_non_latin_names() {
+        let mut map = CandidateMap::new();
+        let name = add(&mut map, 1, "Ελένη Παπαδοπούλου");
+        assert_eq!(name, add(&mut map, 2, " Ελένη Παπαδοπούλου"));
+        assert_ne!(name, add(&mut map, 3, "ΕΛΈΝΗ ΠΑΠΑΔΟΠΟΎΛΟΥ"));
+
+        let mut map = CandidateMap::with_name_matching(NameMatching {
+            case_fold: true,
+            ..NameMatching::default()
+        });
+        let name = add(&mut map, 1, "Ελένη Παπαδοπούλου");
+        assert_eq!(name, add(&mut map, 2, "ΕΛΈΝΗ ΠΑΠΑΔΟΠΟΎΛΟΥ"));
+        let strauss = add(&mut map, 3, "Anna Strauß");
+        assert_eq!(strauss, add(&mut map, 4, "ANNA STRAUSS"));
+    }
+}
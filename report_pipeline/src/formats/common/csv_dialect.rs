@@ -0,0 +1,162 @@
+use csv::{Reader, ReaderBuilder};
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Read};
+
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Delimiters tried, in order of preference, when none is given.
+const DELIMITERS: &[u8] = b",;\t|";
+
+/// How a CSV file is written. Official exports vary, e.g. using semicolons
+/// or starting with a UTF-8 byte order mark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvDialect {
+    /// Field delimiter, or `None` to detect it from the first line.
+    pub delimiter: Option<u8>,
+    /// Quote character, or `None` if fields are never quoted.
+    pub quote: Option<u8>,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        CsvDialect {
+            delimiter: None,
+            quote: Some(b'"'),
+        }
+    }
+}
+
+/// Parse a single-byte character parameter, allowing `tab` for a tab.
+fn parse_char(name: &str, value: &str) -> u8 {
+    match value {
+        "tab" | "\\t" => b'\t',
+        v if v.len() == 1 => v.as_bytes()[0],
+        _ => panic!("{} should be a single character, not {:?}.", name, value),
+    }
+}
+
+/// Guess the delimiter from the first line of a file: the candidate
+/// appearing most often outside of quotes, or a comma if none appear.
+fn detect_delimiter(first_line: &[u8], quote: Option<u8>) -> u8 {
+    let mut counts = [0usize; DELIMITERS.len()];
+    let mut in_quote = false;
+    for &b in first_line {
+        if Some(b) == quote {
+            in_quote = !in_quote;
+        } else if !in_quote {
+            if let Some(i) = DELIMITERS.iter().position(|&d| d == b) {
+                counts[i] += 1;
+            }
+        }
+    }
+
+    // max_by_key returns the last maximum, so iterate in reverse to prefer
+    // earlier delimiters on ties.
+    DELIMITERS
+        .iter()
+        .zip(counts.iter())
+        .rev()
+        .filter(|(_, &count)| count > 0)
+        .max_by_key(|(_, &count)| count)
+        .map(|(&d, _)| d)
+        .unwrap_or(b',')
+}
+
+impl CsvDialect {
+    /// Read the dialect from loader parameters: `csvDelimiter` (a single
+    /// character or `tab`; detected if absent) and `csvQuote` (a single
+    /// character, or `none` to disable quoting).
+    pub fn from_params(params: &BTreeMap<String, String>) -> CsvDialect {
+        CsvDialect {
+            delimiter: params
+                .get("csvDelimiter")
+                .map(|d| parse_char("csvDelimiter", d)),
+            quote: match params.get("csvQuote").map(|q| q.as_str()) {
+                None => Some(b'"'),
+                Some("none") => None,
+                Some(q) => Some(parse_char("csvQuote", q)),
+            },
+        }
+    }
+
+    /// Open a CSV reader in this dialect. A UTF-8 byte order mark at the
+    /// start of the input is skipped, so it doesn't end up in the first
+    /// header.
+    pub fn reader<R: Read>(
+        &self,
+        reader: R,
+        has_headers: bool,
+    ) -> io::Result<Reader<BufReader<R>>> {
+        let mut reader = BufReader::with_capacity(1024 * 1024, reader);
+
+        let buffer = reader.fill_buf()?;
+        let bom_len = if buffer.starts_with(BOM) {
+            BOM.len()
+        } else {
+            0
+        };
+        let delimiter = self.delimiter.unwrap_or_else(|| {
+            let rest = &buffer[bom_len..];
+            let line_end = rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len());
+            detect_delimiter(&rest[..line_end], self.quote)
+        });
+        reader.consume(bom_len);
+
+        let mut builder = ReaderBuilder::new();
+        builder
+            .has_headers(has_headers)
+            .delimiter(delimiter)
+            .buffer_capacity(1024 * 1024);
+        match self.quote {
+            Some(quote) => builder.quote(quote),
+            None => builder.quoting(false),
+        };
+
+        Ok(builder.from_reader(reader))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(dialect: CsvDialect, data: &str) -> (Vec<String>, Vec<Vec<String>>) {
+        let mut reader = dialect.reader(data.as_bytes(), true).unwrap();
+        let headers = reader.headers().unwrap().iter().map(String::from).collect();
+        let rows = reader
+            .records()
+            .map(|r| r.unwrap().iter().map(String::from).collect())
+            .collect();
+        (headers, rows)
+    }
+
+    #[test]
+    fn test_bom_and_semicolons() {
+        let (headers, rows) = read(
+            CsvDialect::default(),
+            "\u{feff}Precinct;1st Choice\nP-1;\"Smith, Jane\"\n",
+        );
+        assert_eq!(vec!["Precinct", "1st Choice"], headers);
+        assert_eq!(vec![vec!["P-1", "Smith, Jane"]], rows);
+    }
+
+    #[test]
+    fn test_explicit_dialect() {
+        let mut params = BTreeMap::new();
+        params.insert("csvDelimiter".to_string(), "tab".to_string());
+        params.insert("csvQuote".to_string(), "none".to_string());
+        let dialect = CsvDialect::from_params(&params);
+
+        let (headers, rows) = read(dialect, "a\tb\n\"x\ty\n");
+        assert_eq!(vec!["a", "b"], headers);
+        assert_eq!(vec![vec!["\"x", "y"]], rows);
+    }
+
+    #[test]
+    fn test_detect_delimiter() {
+        assert_eq!(b',', detect_delimiter(b"a,b,c", Some(b'"')));
+        assert_eq!(b';', detect_delimiter(b"\"a,b\";c;d", Some(b'"')));
+        assert_eq!(b',', detect_delimiter(b"a", Some(b'"')));
+        assert_eq!(b',', detect_delimiter(b"a,b;c", Some(b'"')));
+    }
+}
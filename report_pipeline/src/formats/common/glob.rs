@@ -0,0 +1,46 @@
+use regex::Regex;
+
+/// Compile a shell-style file name glob into a regular expression. `*` matches
+/// any run of characters and `?` matches a single character; everything else
+/// matches literally.
+pub fn glob_regex(pattern: &str) -> Regex {
+    let mut rx = String::from("^");
+    for ch in pattern.chars() {
+        match ch {
+            '*' => rx.push_str(".*"),
+            '?' => rx.push('.'),
+            _ => rx.push_str(&regex::escape(&ch.to_string())),
+        }
+    }
+    rx.push('$');
+
+    Regex::new(&rx).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_star() {
+        let rx = glob_regex("CvrExport_*.json");
+        assert!(rx.is_match("CvrExport_1.json"));
+        assert!(rx.is_match("CvrExport_.json"));
+        assert!(!rx.is_match("CvrExport_1.json.gz"));
+        assert!(!rx.is_match("CandidateManifest.json"));
+    }
+
+    #[test]
+    fn test_question_mark() {
+        let rx = glob_regex("CvrExport_?.json");
+        assert!(rx.is_match("CvrExport_1.json"));
+        assert!(!rx.is_match("CvrExport_12.json"));
+    }
+
+    #[test]
+    fn test_literal() {
+        let rx = glob_regex("CvrExport.json");
+        assert!(rx.is_match("CvrExport.json"));
+        assert!(!rx.is_match("CvrExportXjson"));
+    }
+}
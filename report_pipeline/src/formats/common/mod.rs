@@ -1,5 +1,15 @@
+mod ballot_styles;
 mod candidate_map;
+mod csv_dialect;
+mod glob;
 mod normalize_name;
+#[cfg(feature = "streaming-xlsx")]
+mod xlsx_stream;
 
-pub use candidate_map::CandidateMap;
+pub use ballot_styles::BallotStyleTally;
+pub use candidate_map::{CandidateMap, NameMatching};
+pub use csv_dialect::CsvDialect;
+pub use glob::glob_regex;
 pub use normalize_name::normalize_name;
+#[cfg(feature = "streaming-xlsx")]
+pub use xlsx_stream::XlsxStream;
@@ -0,0 +1,346 @@
+//! A streaming reader for XLSX worksheets, used for workbooks too large to
+//! read comfortably with calamine. Worksheet XML is parsed event by event
+//! straight out of the archive, and only cells in the requested columns are
+//! decoded, so memory use doesn't grow with the size of the worksheet.
+
+use calamine::Data;
+use quick_xml::escape::unescape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::Path;
+use zip::result::ZipError;
+use zip::ZipArchive;
+
+pub struct XlsxStream {
+    archive: ZipArchive<BufReader<File>>,
+    shared_strings: Vec<String>,
+    /// Worksheet names and their paths within the archive, in workbook order.
+    sheets: Vec<(String, String)>,
+}
+
+/// Open an XML file within the archive, or return `None` if it is missing.
+fn xml_entry<'a, R: Read + Seek>(
+    archive: &'a mut ZipArchive<R>,
+    name: &str,
+) -> Result<Option<Reader<BufReader<zip::read::ZipFile<'a>>>>, String> {
+    match archive.by_name(name) {
+        Ok(file) => Ok(Some(Reader::from_reader(BufReader::new(file)))),
+        Err(ZipError::FileNotFound) => Ok(None),
+        Err(e) => Err(format!("{}: {}", name, e)),
+    }
+}
+
+fn attribute(element: &BytesStart, name: &[u8]) -> Result<Option<String>, String> {
+    for attribute in element.attributes() {
+        let attribute = attribute.map_err(|e| e.to_string())?;
+        if attribute.key.local_name().as_ref() == name {
+            let value = std::str::from_utf8(&attribute.value).map_err(|e| e.to_string())?;
+            let value = unescape(value).map_err(|e| e.to_string())?;
+            return Ok(Some(value.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+/// Zero-based column index of a cell reference such as `AB12`.
+fn column_index(cell_ref: &str) -> Option<usize> {
+    let letters = cell_ref.bytes().take_while(|b| b.is_ascii_uppercase());
+    let column = letters.fold(0, |col, b| col * 26 + (b - b'A') as usize + 1);
+    column.checked_sub(1)
+}
+
+/// Map relationship IDs in the workbook to the paths of the parts they refer to.
+fn read_relationships<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+) -> Result<HashMap<String, String>, String> {
+    let mut relationships = HashMap::new();
+    let Some(mut xml) = xml_entry(archive, "xl/_rels/workbook.xml.rels")? else {
+        return Ok(relationships);
+    };
+
+    let mut buf = Vec::new();
+    loop {
+        match xml.read_event_into(&mut buf).map_err(|e| e.to_string())? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"Relationship" => {
+                if let (Some(id), Some(target)) = (attribute(&e, b"Id")?, attribute(&e, b"Target")?)
+                {
+                    let path = match target.strip_prefix('/') {
+                        Some(absolute) => absolute.to_string(),
+                        None => format!("xl/{}", target),
+                    };
+                    relationships.insert(id, path);
+                }
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+        buf.clear();
+    }
+    Ok(relationships)
+}
+
+fn read_sheets<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    relationships: &HashMap<String, String>,
+) -> Result<Vec<(String, String)>, String> {
+    let mut sheets = Vec::new();
+    let mut xml = xml_entry(archive, "xl/workbook.xml")?
+        .ok_or_else(|| "Missing xl/workbook.xml".to_string())?;
+
+    let mut buf = Vec::new();
+    loop {
+        match xml.read_event_into(&mut buf).map_err(|e| e.to_string())? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"sheet" => {
+                let name = attribute(&e, b"name")?;
+                let path = attribute(&e, b"id")?.and_then(|id| relationships.get(&id).cloned());
+                if let (Some(name), Some(path)) = (name, path) {
+                    sheets.push((name, path));
+                }
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+        buf.clear();
+    }
+    Ok(sheets)
+}
+
+fn read_shared_strings<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<Vec<String>, String> {
+    let mut strings = Vec::new();
+    let Some(mut xml) = xml_entry(archive, "xl/sharedStrings.xml")? else {
+        return Ok(strings);
+    };
+
+    let mut current = String::new();
+    let mut in_text = false;
+    // Phonetic runs (<rPh>) hold readings of the text, not part of it.
+    let mut in_phonetic = false;
+    let mut buf = Vec::new();
+    loop {
+        match xml.read_event_into(&mut buf).map_err(|e| e.to_string())? {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"si" => current.clear(),
+                b"t" => in_text = !in_phonetic,
+                b"rPh" => in_phonetic = true,
+                _ => (),
+            },
+            Event::Empty(e) if e.local_name().as_ref() == b"si" => strings.push(String::new()),
+            Event::Text(e) if in_text => {
+                current.push_str(&e.unescape().map_err(|e| e.to_string())?);
+            }
+            Event::End(e) => match e.local_name().as_ref() {
+                b"si" => strings.push(std::mem::take(&mut current)),
+                b"t" => in_text = false,
+                b"rPh" => in_phonetic = false,
+                _ => (),
+            },
+            Event::Eof => break,
+            _ => (),
+        }
+        buf.clear();
+    }
+    Ok(strings)
+}
+
+/// Decode a cell's value given its type attribute.
+fn decode(shared_strings: &[String], cell_type: Option<&str>, value: &str) -> Data {
+    match cell_type {
+        Some("s") => value
+            .parse::<usize>()
+            .ok()
+            .and_then(|i| shared_strings.get(i))
+            .map(|s| Data::String(s.clone()))
+            .unwrap_or(Data::Empty),
+        Some("str") | Some("inlineStr") => Data::String(value.to_string()),
+        Some("b") => Data::Bool(value == "1"),
+        Some("e") => Data::Empty,
+        _ => value.parse().map(Data::Float).unwrap_or(Data::Empty),
+    }
+}
+
+impl XlsxStream {
+    pub fn open(path: &Path) -> Result<XlsxStream, String> {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        let mut archive = ZipArchive::new(BufReader::new(file)).map_err(|e| e.to_string())?;
+        let relationships = read_relationships(&mut archive)?;
+        let sheets = read_sheets(&mut archive, &relationships)?;
+        let shared_strings = read_shared_strings(&mut archive)?;
+
+        Ok(XlsxStream {
+            archive,
+            shared_strings,
+            sheets,
+        })
+    }
+
+    pub fn sheet_names(&self) -> Vec<String> {
+        self.sheets.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    /// Call `on_row` for each non-empty row of a worksheet with the row's
+    /// cells as `(column, value)` pairs, skipping empty cells. Only cells in
+    /// columns for which `keep_column` returns true are decoded. Reading
+    /// stops early if `on_row` returns false.
+    pub fn for_each_row(
+        &mut self,
+        sheet: &str,
+        keep_column: impl Fn(usize) -> bool,
+        mut on_row: impl FnMut(&[(usize, Data)]) -> bool,
+    ) -> Result<(), String> {
+        let path = match self.sheets.iter().find(|(name, _)| name == sheet) {
+            Some((_, path)) => path.clone(),
+            None => return Err(format!("No worksheet named {}", sheet)),
+        };
+        let mut xml = xml_entry(&mut self.archive, &path)?
+            .ok_or_else(|| format!("Missing worksheet {}", path))?;
+
+        let mut cells: Vec<(usize, Data)> = Vec::new();
+        let mut next_col = 0;
+        // The column and type of the cell being read, if it is kept.
+        let mut cell: Option<(usize, Option<String>)> = None;
+        let mut value = String::new();
+        let mut in_value = false;
+        let mut buf = Vec::new();
+        loop {
+            match xml.read_event_into(&mut buf).map_err(|e| e.to_string())? {
+                Event::Start(e) => match e.local_name().as_ref() {
+                    b"row" => {
+                        cells.clear();
+                        next_col = 0;
+                    }
+                    b"c" => {
+                        let col = match attribute(&e, b"r")? {
+                            Some(cell_ref) => column_index(&cell_ref).unwrap_or(next_col),
+                            None => next_col,
+                        };
+                        next_col = col + 1;
+                        cell = if keep_column(col) {
+                            Some((col, attribute(&e, b"t")?))
+                        } else {
+                            None
+                        };
+                        value.clear();
+                    }
+                    b"v" | b"t" => in_value = cell.is_some(),
+                    _ => (),
+                },
+                Event::Empty(e) if e.local_name().as_ref() == b"c" => {
+                    if let Some(cell_ref) = attribute(&e, b"r")? {
+                        next_col = column_index(&cell_ref).unwrap_or(next_col);
+                    }
+                    next_col += 1;
+                }
+                Event::Text(e) if in_value => {
+                    value.push_str(&e.unescape().map_err(|e| e.to_string())?);
+                }
+                Event::End(e) => match e.local_name().as_ref() {
+                    b"v" | b"t" => in_value = false,
+                    b"c" => {
+                        if let Some((col, cell_type)) = cell.take() {
+                            let data = decode(&self.shared_strings, cell_type.as_deref(), &value);
+                            if data != Data::Empty {
+                                cells.push((col, data));
+                            }
+                        }
+                    }
+                    b"row" if !cells.is_empty() && !on_row(&cells) => break,
+                    _ => (),
+                },
+                Event::Eof => break,
+                _ => (),
+            }
+            buf.clear();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::TempDir;
+    use std::io::Write;
+    use zip::write::{FileOptions, ZipWriter};
+
+    fn write_workbook(path: &Path) {
+        let mut zip = ZipWriter::new(File::create(path).unwrap());
+        let files = [
+            (
+                "xl/workbook.xml",
+                r#"<workbook xmlns:r="r"><sheets><sheet name="Info" sheetId="1" r:id="rId1"/><sheet name="CVR" sheetId="2" r:id="rId2"/></sheets></workbook>"#,
+            ),
+            (
+                "xl/_rels/workbook.xml.rels",
+                r#"<Relationships><Relationship Id="rId1" Target="worksheets/sheet1.xml"/><Relationship Id="rId2" Target="/xl/worksheets/sheet2.xml"/></Relationships>"#,
+            ),
+            (
+                "xl/sharedStrings.xml",
+                r#"<sst><si><t>Cast Vote Record</t></si><si><r><t>Smith &amp; </t></r><r><t>Jones</t></r><rPh><t>x</t></rPh></si></sst>"#,
+            ),
+            (
+                "xl/worksheets/sheet1.xml",
+                r#"<worksheet><sheetData/></worksheet>"#,
+            ),
+            (
+                "xl/worksheets/sheet2.xml",
+                r#"<worksheet><sheetData>
+                <row r="1"><c r="A1" t="s"><v>0</v></c><c r="C1" t="inlineStr"><is><t>Choice</t></is></c></row>
+                <row r="2"><c r="A2" t="s"><v>1</v></c><c r="B2"><v>7</v></c><c r="C2"><v>12</v></c></row>
+                <row r="3"><c r="A3"/><c><v>5</v></c><c t="b"><v>1</v></c></row>
+                </sheetData></worksheet>"#,
+            ),
+        ];
+        for (name, contents) in files {
+            zip.start_file(name, FileOptions::default()).unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_column_index() {
+        assert_eq!(Some(0), column_index("A1"));
+        assert_eq!(Some(27), column_index("AB12"));
+        assert_eq!(None, column_index("12"));
+    }
+
+    #[test]
+    fn test_for_each_row() {
+        let dir = TempDir::new("xlsx-stream");
+        let path = dir.join("cvr.xlsx");
+        write_workbook(&path);
+
+        let mut stream = XlsxStream::open(&path).unwrap();
+        assert_eq!(vec!["Info", "CVR"], stream.sheet_names());
+
+        let mut rows = Vec::new();
+        stream
+            .for_each_row(
+                "CVR",
+                |col| col != 1,
+                |cells| {
+                    rows.push(cells.to_vec());
+                    true
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                vec![
+                    (0, Data::String("Cast Vote Record".into())),
+                    (2, Data::String("Choice".into())),
+                ],
+                vec![
+                    (0, Data::String("Smith & Jones".into())),
+                    (2, Data::Float(12.0)),
+                ],
+                vec![(2, Data::Bool(true))],
+            ],
+            rows
+        );
+    }
+}
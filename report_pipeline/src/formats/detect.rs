@@ -0,0 +1,383 @@
+//! Guess the data format and loader parameters of an election from the
+//! files in its raw data directory.
+
+use crate::formats::default_normalization;
+use crate::formats::nist_sp_1500::read_contest_manifest;
+use crate::util::{open_raw, uncompressed_name};
+use calamine::{open_workbook_auto, Data, Reader};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{read_dir, File};
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// A contest found in raw data, with a suggested office ID.
+pub struct DetectedContest {
+    pub office: String,
+    pub loader_params: BTreeMap<String, String>,
+}
+
+/// The guessed format of an election's raw data.
+pub struct Detection {
+    pub format: String,
+    pub normalization: String,
+    pub contests: Vec<DetectedContest>,
+}
+
+impl Detection {
+    fn new(format: &str, contests: Vec<DetectedContest>) -> Detection {
+        Detection {
+            format: format.to_string(),
+            normalization: default_normalization(format).to_string(),
+            contests,
+        }
+    }
+}
+
+/// Turn a contest description or file name into an office ID, e.g.
+/// "Mayor - City of Oakland" into "mayor-city-of-oakland".
+pub fn office_id(description: &str) -> String {
+    description
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>()
+        .join("-")
+}
+
+fn params(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+/// Whether a ZIP archive contains the given file.
+pub(super) fn zip_contains(path: &Path, name: &str) -> bool {
+    File::open(path)
+        .ok()
+        .and_then(|file| zip::ZipArchive::new(file).ok())
+        .is_some_and(|mut archive| archive.by_name(name).is_ok())
+}
+
+/// Whether the first row of the first sheet of a workbook has a column
+/// with the given name.
+fn workbook_has_header(path: &Path, header: &str) -> bool {
+    let mut workbook = match open_workbook_auto(path) {
+        Ok(workbook) => workbook,
+        Err(_) => return false,
+    };
+    let sheet = match workbook.sheet_names().first() {
+        Some(sheet) => sheet.clone(),
+        None => return false,
+    };
+    workbook
+        .worksheet_range(&sheet)
+        .ok()
+        .and_then(|range| range.rows().next().map(|row| row.to_vec()))
+        .is_some_and(|row| {
+            row.iter()
+                .any(|cell| matches!(cell, Data::String(s) if s == header))
+        })
+}
+
+/// First line of a text file.
+pub(super) fn first_line(path: &Path) -> Option<String> {
+    let mut line = String::new();
+    BufReader::new(open_raw(path).ok()?)
+        .read_line(&mut line)
+        .ok()?;
+    Some(line)
+}
+
+/// The NIST CVR export in a directory: the directory itself (`.`) or a ZIP
+/// archive in it, whichever has a candidate manifest.
+fn nist_cvr(dir: &Path, files: &[String]) -> Option<String> {
+    if open_raw(&dir.join("CandidateManifest.json")).is_ok() {
+        return Some(".".to_string());
+    }
+    files
+        .iter()
+        .find(|f| f.ends_with(".zip") && zip_contains(&dir.join(f), "CandidateManifest.json"))
+        .cloned()
+}
+
+fn detect_nist(dir: &Path, files: &[String]) -> Option<Detection> {
+    let cvr = nist_cvr(dir, files)?;
+
+    let contests = match read_contest_manifest(&dir.join(&cvr)) {
+        Some(contests) => contests
+            .into_iter()
+            .map(|(id, description)| DetectedContest {
+                office: office_id(&description),
+                loader_params: params(&[("contest", &id.to_string()), ("cvr", &cvr)]),
+            })
+            .collect(),
+        None => vec![DetectedContest {
+            office: "<office>".to_string(),
+            loader_params: params(&[("contest", "<contest ID>"), ("cvr", &cvr)]),
+        }],
+    };
+
+    Some(Detection::new("nist_sp_1500", contests))
+}
+
+/// The NYC candidates file among `files`, with the prefix its CVR files
+/// share.
+fn nyc_candidates_file(files: &[String]) -> Option<(String, String)> {
+    let candidates_rx = Regex::new(r"^(.*?)_?CandidacyID_To_Name\.xlsx$").unwrap();
+    files.iter().find_map(|f| {
+        candidates_rx
+            .captures(f)
+            .map(|caps| (f.clone(), caps[1].to_string()))
+    })
+}
+
+fn detect_nyc(dir: &Path, files: &[String]) -> Option<Detection> {
+    let (candidates_file, prefix) = nyc_candidates_file(files)?;
+
+    let cvr_pattern = format!(r"{}\d?V.+\.xlsx", regex::escape(&prefix));
+    let cvr_rx = Regex::new(&format!("^{}$", cvr_pattern)).unwrap();
+    let has_cvr = files
+        .iter()
+        .any(|f| cvr_rx.is_match(f) && workbook_has_header(&dir.join(f), "Cast Vote Record"));
+    if !has_cvr {
+        return None;
+    }
+
+    Some(Detection::new(
+        "us_ny_nyc",
+        vec![DetectedContest {
+            office: "<office>".to_string(),
+            loader_params: params(&[
+                ("candidatesFile", &candidates_file),
+                ("cvrPattern", &cvr_pattern),
+                ("jurisdictionName", "<jurisdiction name>"),
+                ("officeName", "<office name>"),
+            ]),
+        }],
+    ))
+}
+
+fn detect_sfo(files: &[String]) -> Option<Detection> {
+    let master_lookup = files.iter().find(|f| f.contains("MasterLookup"))?;
+    let ballot_image = files.iter().find(|f| f.contains("BallotImage"))?;
+
+    Some(Detection::new(
+        "us_ca_sfo",
+        vec![DetectedContest {
+            office: "<office>".to_string(),
+            loader_params: params(&[
+                ("contest", "<contest ID>"),
+                ("masterLookup", master_lookup),
+                ("ballotImage", ballot_image),
+            ]),
+        }],
+    ))
+}
+
+/// Names of the files in a directory, sorted, without hidden files.
+fn list_files(dir: &Path) -> Option<Vec<String>> {
+    let mut files: Vec<String> = read_dir(dir)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str().map(|s| s.to_string()))
+        .filter(|name| !name.starts_with('.'))
+        .collect();
+    files.sort();
+    Some(files)
+}
+
+/// Guess the format of the raw data in an election directory, with loader
+/// parameters for each contest found. Returns `None` if no format matches.
+pub fn detect_format(dir: &Path) -> Option<Detection> {
+    let files = list_files(dir)?;
+    let with_extension = |ext: &str| -> Vec<String> {
+        files
+            .iter()
+            .filter(|f| uncompressed_name(f).to_lowercase().ends_with(ext))
+            .cloned()
+            .collect()
+    };
+
+    if let Some(detection) = detect_nist(dir, &files) {
+        return Some(detection);
+    }
+    if let Some(detection) = detect_nyc(dir, &files) {
+        return Some(detection);
+    }
+    if let Some(detection) = detect_sfo(&files) {
+        return Some(detection);
+    }
+
+    let rcr_files = with_extension(".rcr");
+    if !rcr_files.is_empty() {
+        let contests = rcr_files
+            .iter()
+            .map(|file| {
+                // e.g. "1 Mayor.rcr"
+                let name = uncompressed_name(file);
+                let stem = name[..name.len() - 4].trim_start_matches(|c: char| c.is_ascii_digit());
+                DetectedContest {
+                    office: office_id(stem),
+                    loader_params: params(&[("rcr", file)]),
+                }
+            })
+            .collect();
+        return Some(Detection::new("dominion_rcr", contests));
+    }
+
+    let one_contest_per_file = |format: &str, param: &str, files: Vec<String>| {
+        let contests = files
+            .iter()
+            .map(|file| DetectedContest {
+                office: "<office>".to_string(),
+                loader_params: params(&[(param, file)]),
+            })
+            .collect();
+        Detection::new(format, contests)
+    };
+
+    let mpls_files: Vec<String> = with_extension(".csv")
+        .into_iter()
+        .filter(|f| {
+            first_line(&dir.join(f)).is_some_and(|line| {
+                line.trim_start_matches('\u{feff}')
+                    .to_lowercase()
+                    .starts_with("precinct")
+            })
+        })
+        .collect();
+    if !mpls_files.is_empty() {
+        return Some(one_contest_per_file("us_mn_mpls", "file", mpls_files));
+    }
+
+    let json_files: Vec<String> = with_extension(".json")
+        .into_iter()
+        .filter(|f| {
+            open_raw(&dir.join(f))
+                .ok()
+                .and_then(|file| {
+                    serde_json::from_reader::<_, serde_json::Value>(BufReader::new(file)).ok()
+                })
+                .is_some_and(|value| value.get("ballots").is_some_and(|b| b.is_array()))
+        })
+        .collect();
+    if !json_files.is_empty() {
+        return Some(one_contest_per_file("simple_json", "file", json_files));
+    }
+
+    let xlsx_files = with_extension(".xlsx");
+    if !xlsx_files.is_empty() {
+        return Some(Detection::new(
+            "us_me",
+            vec![DetectedContest {
+                office: "<office>".to_string(),
+                loader_params: params(&[("files", &xlsx_files.join(";"))]),
+            }],
+        ));
+    }
+
+    None
+}
+
+/// Guess the format of an election's raw data from the names of its files,
+/// for the formats whose files are distinctive. Unlike `detect_format`, no
+/// CVRs are read, so this is cheap enough to run before preprocessing.
+fn sniff_format(dir: &Path) -> Option<&'static str> {
+    let files = list_files(dir)?;
+    if nist_cvr(dir, &files).is_some() {
+        Some("nist_sp_1500")
+    } else if nyc_candidates_file(&files).is_some() {
+        Some("us_ny_nyc")
+    } else if detect_sfo(&files).is_some() {
+        Some("us_ca_sfo")
+    } else if files
+        .iter()
+        .any(|f| uncompressed_name(f).to_lowercase().ends_with(".rcr"))
+    {
+        Some("dominion_rcr")
+    } else {
+        None
+    }
+}
+
+/// Raw data whose files look like a different format than the election's
+/// `dataFormat`.
+#[derive(Debug, PartialEq)]
+pub struct FormatMismatch {
+    pub declared: String,
+    pub detected: String,
+}
+
+impl fmt::Display for FormatMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "dataFormat is {}, but the raw data looks like {}",
+            self.declared, self.detected
+        )
+    }
+}
+
+/// Check that the raw data in an election directory looks like the declared
+/// format. Returns `None` if it does, or its format can't be told from the
+/// file names.
+pub fn check_format(declared: &str, dir: &Path) -> Option<FormatMismatch> {
+    sniff_format(dir)
+        .filter(|&detected| detected != declared)
+        .map(|detected| FormatMismatch {
+            declared: declared.to_string(),
+            detected: detected.to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::TempDir;
+    use std::fs::write;
+
+    #[test]
+    fn test_office_id() {
+        assert_eq!(
+            "mayor-city-of-oakland",
+            office_id("Mayor - City of Oakland")
+        );
+        assert_eq!("mayor", office_id(" Mayor"));
+    }
+
+    #[test]
+    fn test_detect_format() {
+        let dir = TempDir::new("detect-format");
+
+        write(dir.join("1 Mayor.rcr"), "").unwrap();
+        let detection = detect_format(&dir).unwrap();
+        assert_eq!("dominion_rcr", detection.format);
+        assert_eq!("mayor", detection.contests[0].office);
+
+        write(dir.join("CandidateManifest.json"), r#"{"List": []}"#).unwrap();
+        write(
+            dir.join("ContestManifest.json"),
+            r#"{"List": [{"Description": "Mayor", "Id": 7}]}"#,
+        )
+        .unwrap();
+        let detection = detect_format(&dir).unwrap();
+        assert_eq!("nist_sp_1500", detection.format);
+        assert_eq!("mayor", detection.contests[0].office);
+        assert_eq!(
+            Some(&"7".to_string()),
+            detection.contests[0].loader_params.get("contest")
+        );
+
+        assert_eq!(None, check_format("nist_sp_1500", &dir));
+        assert_eq!(
+            Some(FormatMismatch {
+                declared: "us_ny_nyc".to_string(),
+                detected: "nist_sp_1500".to_string(),
+            }),
+            check_format("us_ny_nyc", &dir)
+        );
+    }
+}
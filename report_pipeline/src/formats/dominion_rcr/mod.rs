@@ -1,9 +1,10 @@
 mod parser;
 
+pub(super) use crate::formats::dominion_rcr::parser::is_header;
 use crate::formats::dominion_rcr::parser::rcr_file;
 use crate::model::election::Election;
+use crate::util::open_raw;
 use std::collections::BTreeMap;
-use std::fs::read_to_string;
 use std::path::Path;
 
 struct ReaderOptions {
@@ -21,7 +22,9 @@ impl ReaderOptions {
 pub fn dominion_rcr_ballot_reader(path: &Path, params: BTreeMap<String, String>) -> Election {
     let options = ReaderOptions::from_params(params);
 
-    let raw = read_to_string(path.join(options.rcr)).unwrap();
+    let raw = open_raw(&path.join(options.rcr))
+        .and_then(std::io::read_to_string)
+        .unwrap();
 
     rcr_file(&raw)
 }
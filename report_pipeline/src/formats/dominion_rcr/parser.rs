@@ -35,6 +35,11 @@ fn parse_header(i: &str) -> IResult<&str, RcrHeader> {
     Ok((i, header))
 }
 
+/// Whether a line is a valid RCR header.
+pub fn is_header(line: &str) -> bool {
+    parse_header(line).is_ok()
+}
+
 fn candidate(i: &str) -> IResult<&str, Candidate> {
     let (i, name) = terminated(not_line_ending, line_ending)(i)?;
     Ok((
@@ -1,4 +1,6 @@
+mod check;
 mod common;
+mod detect;
 mod dominion_rcr;
 mod nist_sp_1500;
 mod simple_json;
@@ -14,6 +16,10 @@ use std::path::Path;
 
 pub type BallotReader = dyn Fn(&Path, BTreeMap<String, String>) -> Election;
 
+pub use check::check_raw_data;
+pub use common::NameMatching;
+pub use detect::{check_format, detect_format, FormatMismatch};
+
 // Re-export the batch readers for NIST and NYC formats
 pub use nist_sp_1500::nist_batch_reader;
 pub use us_ny_nyc::nyc_batch_reader;
@@ -33,6 +39,62 @@ pub fn get_reader_for_format(format: &str) -> &'static BallotReader {
     }
 }
 
+/// Names of the supported data formats.
+pub const FORMATS: &[&str] = &[
+    "us_ca_sfo",
+    "nist_sp_1500",
+    "us_vt_btv",
+    "dominion_rcr",
+    "us_me",
+    "us_mn_mpls",
+    "simple_json",
+    "us_ny_nyc",
+];
+
+/// Template loader parameters for a contest in the given format, with
+/// placeholders in angle brackets to be filled in by hand.
+pub fn loader_params_template(format: &str) -> Option<BTreeMap<String, String>> {
+    let params: &[(&str, &str)] = match format {
+        "us_ca_sfo" => &[
+            ("contest", "<contest ID>"),
+            ("masterLookup", "<MasterLookup.txt>"),
+            ("ballotImage", "<BallotImage.txt>"),
+        ],
+        "nist_sp_1500" => &[
+            ("contest", "<contest ID>"),
+            ("cvr", "<CVR export .zip or directory>"),
+        ],
+        "us_vt_btv" => &[("archive", "<archive .zip>"), ("ballots", "<ballots .txt>")],
+        "dominion_rcr" => &[("rcr", "<file .rcr>")],
+        "us_me" => &[("files", "<file1.xlsx;file2.xlsx>")],
+        "us_mn_mpls" => &[("file", "<cast vote record .csv>")],
+        "simple_json" => &[("file", "<ballots .json>")],
+        "us_ny_nyc" => &[
+            ("candidatesFile", "<CandidacyID_To_Name.xlsx>"),
+            ("cvrPattern", "<regex matching the CVR workbooks>"),
+            ("jurisdictionName", "<jurisdiction name>"),
+            ("officeName", "<office name>"),
+        ],
+        _ => return None,
+    };
+
+    Some(
+        params
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+    )
+}
+
+/// The normalization usually used with the given format.
+pub fn default_normalization(format: &str) -> &'static str {
+    match format {
+        "us_ny_nyc" => "nyc",
+        "us_me" => "maine",
+        _ => "simple",
+    }
+}
+
 pub fn read_election(format: &str, path: &Path, params: BTreeMap<String, String>) -> Election {
     let reader = get_reader_for_format(format);
     reader(path, params)
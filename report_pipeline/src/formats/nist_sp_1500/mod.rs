@@ -1,20 +1,110 @@
 pub mod model;
 
-use crate::formats::common::{normalize_name, CandidateMap};
-use crate::formats::nist_sp_1500::model::{CandidateManifest, CandidateType, CvrExport, Mark};
-use crate::model::election::{self, Ballot, Candidate, Choice, Election};
-use csv::ReaderBuilder;
+use crate::formats::common::{
+    glob_regex, normalize_name, BallotStyleTally, CandidateMap, CsvDialect,
+};
+use crate::formats::nist_sp_1500::model::{
+    CandidateManifest, CandidateType, ContestMarks, CvrExport, DescriptionManifest, Mark, Session,
+};
+use crate::model::election::{
+    self, AdjudicationImpact, Ballot, Candidate, CandidateId, Choice, Election,
+};
+use crate::util::{open_raw, uncompressed_name};
 use itertools::Itertools;
+use regex::Regex;
 use std::collections::{BTreeMap, HashMap};
 use std::fs::{self, File};
 use std::io::{BufReader, Read};
 
 use std::path::Path;
 
+/// Which version of an adjudicated ballot to read marks from.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum BallotVersion {
+    Original,
+    Modified,
+}
+
+/// Rules for which marks on a ballot are counted as votes.
+#[derive(Clone, Copy, Debug)]
+struct MarkPolicy {
+    /// Count marks the scanner flagged as ambiguous (by default they are ignored).
+    count_ambiguous: bool,
+    /// Only count marks that the tabulator flagged with `IsVote`.
+    require_is_vote: bool,
+    /// Version of the ballot to read. If not given, the original ballot is used
+    /// when it lists contests directly, otherwise the adjudicated one.
+    ballot_version: Option<BallotVersion>,
+}
+
+/// A `true` or `false` loader parameter, `false` if it isn't given.
+fn bool_param(params: &BTreeMap<String, String>, name: &str) -> Result<bool, String> {
+    params.get(name).map_or(Ok(false), |value| {
+        value.parse().map_err(|_| {
+            format!(
+                "{} param should be \"true\" or \"false\", not {:?}.",
+                name, value
+            )
+        })
+    })
+}
+
+impl MarkPolicy {
+    pub fn from_params(params: &BTreeMap<String, String>) -> Result<MarkPolicy, String> {
+        let count_ambiguous = bool_param(params, "countAmbiguousMarks")?;
+        let require_is_vote = bool_param(params, "requireIsVote")?;
+        let ballot_version = match params.get("ballotVersion").map(String::as_str) {
+            None => None,
+            Some("original") => Some(BallotVersion::Original),
+            Some("modified") => Some(BallotVersion::Modified),
+            Some(value) => {
+                return Err(format!(
+                    "ballotVersion param should be \"original\" or \"modified\", not {:?}.",
+                    value
+                ))
+            }
+        };
+
+        Ok(MarkPolicy {
+            count_ambiguous,
+            require_is_vote,
+            ballot_version,
+        })
+    }
+
+    fn counts(&self, mark: &Mark) -> bool {
+        (self.count_ambiguous || !mark.is_ambiguous) && (!self.require_is_vote || mark.is_vote)
+    }
+
+    fn session_contests(&self, session: &Session) -> Vec<ContestMarks> {
+        session_contests(session, self.ballot_version)
+    }
+}
+
+fn session_contests(session: &Session, ballot_version: Option<BallotVersion>) -> Vec<ContestMarks> {
+    match ballot_version {
+        Some(BallotVersion::Original) => session.original.contests(),
+        Some(BallotVersion::Modified) => session.ballot().contests(),
+        None => session.contests(),
+    }
+}
+
+/// For a non-ranked contest, the number of candidates each voter may vote
+/// for, from the `voteFor` parameter.
+fn vote_for(params: &BTreeMap<String, String>) -> Option<u32> {
+    params
+        .get("voteFor")
+        .map(|d| d.parse().expect("voteFor param should be a number."))
+}
+
 struct ReaderOptions {
     cvr: String,
     contest: u32,
     drop_unqualified_write_in: bool,
+    mark_policy: MarkPolicy,
+    cvr_files: Option<Regex>,
+    csv_dialect: CsvDialect,
+    vote_for: Option<u32>,
 }
 
 impl ReaderOptions {
@@ -32,11 +122,35 @@ impl ReaderOptions {
             .get("dropUnqualifiedWriteIn")
             .map(|d| d.parse().unwrap())
             .unwrap_or(false);
+        let mark_policy = MarkPolicy::from_params(&params).unwrap_or_else(|e| panic!("{}", e));
+        let cvr_files = params.get("cvrFiles").map(|d| glob_regex(d));
+        let csv_dialect = CsvDialect::from_params(&params);
+        let vote_for = vote_for(&params);
 
         ReaderOptions {
             contest,
             cvr,
             drop_unqualified_write_in,
+            mark_policy,
+            cvr_files,
+            csv_dialect,
+            vote_for,
+        }
+    }
+}
+
+/// Returns true if `filename` is a CVR export that should be read. Exports may
+/// be split across many files, so a `cvrFiles` glob can be given to select them;
+/// otherwise files are recognized by their standard prefixes. Compressed
+/// exports are matched by their uncompressed names.
+fn is_cvr_file(filename: &str, cvr_files: Option<&Regex>) -> bool {
+    let filename = uncompressed_name(filename);
+    match cvr_files {
+        Some(rx) => rx.is_match(filename),
+        // Support both JSON and CSV formats (CSV files may use CVR_Export prefix)
+        None => {
+            (filename.starts_with("CvrExport") && filename.ends_with(".json"))
+                || (filename.starts_with("CVR_Export") && filename.ends_with(".csv"))
         }
     }
 }
@@ -130,45 +244,324 @@ pub fn nist_ballot_reader(path: &Path, params: BTreeMap<String, String>) -> Elec
     }
 }
 
+/// Ballots read for one contest, along with what is needed to interpret the
+/// marks on them.
+struct ContestBallots {
+    contest_id: u32,
+    candidates: CandidateMap<u32>,
+    dropped_write_in: Option<u32>,
+    mark_policy: MarkPolicy,
+    /// For a non-ranked contest, the number of candidates a ballot may vote
+    /// for.
+    vote_for: Option<u32>,
+    ballots: Vec<Ballot>,
+    adjudicated: u32,
+    /// Adjudicated ballots whose marks in the contest changed.
+    changed: u32,
+    /// Changed ballots by first choice before and after adjudication.
+    first_choices: BTreeMap<(Option<CandidateId>, Option<CandidateId>), u32>,
+}
+
+/// Ranks with no marks at all aren't listed in a CVR, so before adding the
+/// choice for a (1-based) rank, add undervotes for any ranks skipped since
+/// the last one, to keep choices aligned with rank positions.
+fn fill_skipped_ranks(choices: &mut Vec<Choice>, rank: u32) {
+    while (choices.len() as u32) + 1 < rank {
+        choices.push(Choice::Undervote);
+    }
+}
+
+/// The first candidate marked, or `None` if the first mark is an overvote or
+/// there are no marks.
+fn first_candidate(choices: &[Choice]) -> Option<CandidateId> {
+    match choices.iter().find(|choice| **choice != Choice::Undervote) {
+        Some(Choice::Vote(candidate)) => Some(*candidate),
+        _ => None,
+    }
+}
+
+impl ContestBallots {
+    pub fn new(
+        manifest: &CandidateManifest,
+        contest_id: u32,
+        drop_unqualified_write_in: bool,
+        mark_policy: MarkPolicy,
+        vote_for: Option<u32>,
+    ) -> ContestBallots {
+        let (candidates, dropped_write_in) =
+            get_candidates(manifest, contest_id, drop_unqualified_write_in);
+
+        ContestBallots {
+            contest_id,
+            candidates,
+            dropped_write_in,
+            mark_policy,
+            vote_for,
+            ballots: Vec::new(),
+            adjudicated: 0,
+            changed: 0,
+            first_choices: BTreeMap::new(),
+        }
+    }
+
+    /// Convert the marks for this contest on a ballot into a list of choices,
+    /// one per rank, applying the mark policy.
+    fn choices(&self, contest: &ContestMarks) -> Vec<Choice> {
+        if let Some(vote_for) = self.vote_for {
+            return self.plurality_choices(contest, vote_for);
+        }
+        let mut choices: Vec<Choice> = Vec::new();
+        for (rank, marks) in &contest.marks.iter().group_by(|x| x.rank) {
+            fill_skipped_ranks(&mut choices, rank);
+            let marks: Vec<&Mark> = marks.filter(|d| self.mark_policy.counts(d)).collect();
+
+            let choice = match marks.as_slice() {
+                [v] if Some(v.candidate_id) == self.dropped_write_in => Choice::Undervote,
+                [v] => self
+                    .candidates
+                    .get(&v.candidate_id)
+                    .unwrap_or(Choice::Undervote),
+                [] => Choice::Undervote,
+                _ => Choice::Overvote,
+            };
+
+            choices.push(choice);
+        }
+        choices
+    }
+
+    /// For a non-ranked contest, the candidates a ballot votes for, or a
+    /// single overvote if it votes for more than `vote_for`. The marks of a
+    /// non-ranked contest all have the same rank, so their order is lost.
+    fn plurality_choices(&self, contest: &ContestMarks, vote_for: u32) -> Vec<Choice> {
+        let mut choices: Vec<Choice> = contest
+            .marks
+            .iter()
+            .filter(|mark| {
+                self.mark_policy.counts(mark) && Some(mark.candidate_id) != self.dropped_write_in
+            })
+            .filter_map(|mark| self.candidates.get(&mark.candidate_id))
+            .collect();
+        choices.sort();
+        choices.dedup();
+        if choices.len() > vote_for as usize {
+            vec![Choice::Overvote]
+        } else {
+            choices
+        }
+    }
+
+    /// Compare the marks for this contest on an adjudicated ballot as scanned
+    /// to those after adjudication. A contest missing from one version has
+    /// no marks in it.
+    fn record_adjudication(&mut self, session: &Session) {
+        let choices = |version| {
+            session_contests(session, Some(version))
+                .iter()
+                .find(|contest| contest.id == self.contest_id)
+                .map(|contest| self.choices(contest))
+                .unwrap_or_default()
+        };
+        let original = choices(BallotVersion::Original);
+        let adjudicated = choices(BallotVersion::Modified);
+        if original != adjudicated {
+            self.changed += 1;
+            *self
+                .first_choices
+                .entry((first_candidate(&original), first_candidate(&adjudicated)))
+                .or_default() += 1;
+        }
+    }
+
+    /// Add the ballot for this contest from a CVR session.
+    pub fn push_session(
+        &mut self,
+        filename: &str,
+        session: &Session,
+        contest: &ContestMarks,
+        descriptions: &Descriptions,
+    ) {
+        let choices = self.choices(contest);
+        for mark in &contest.marks {
+            if self.mark_policy.counts(mark)
+                && Some(mark.candidate_id) != self.dropped_write_in
+                && self.candidates.get(&mark.candidate_id).is_none()
+            {
+                self.candidates
+                    .record_unknown(mark.candidate_id.to_string());
+            }
+        }
+        let counting_group = describe(&descriptions.counting_groups, session.counting_group_id);
+        let precinct = describe(
+            &descriptions.precinct_portions,
+            session.ballot().precinct_portion_id,
+        );
+
+        self.ballots.push(
+            Ballot::new(format!("{}:{}", filename, session.record_id), choices)
+                .with_counting_group(Some(counting_group))
+                .with_precinct(Some(precinct)),
+        );
+        if session.is_adjudicated() {
+            self.adjudicated += 1;
+            self.record_adjudication(session);
+        }
+    }
+
+    /// Finish reading. `adjudication_known` should be false if no JSON exports
+    /// (the only ones that record adjudication) were read.
+    pub fn into_election(self, adjudication_known: bool) -> Election {
+        crate::log_debug!(
+            "  Contest {}: {} ballots",
+            self.contest_id,
+            self.ballots.len()
+        );
+
+        let unknown_candidates = self.candidates.unknown_candidates();
+        let mut election = Election::new(self.candidates.into_vec(), self.ballots);
+        election.unknown_candidates = unknown_candidates;
+        if adjudication_known {
+            let impact = AdjudicationImpact::new(self.changed, self.first_choices);
+            crate::log_info!(
+                "Contest {}: {} adjudicated ballots, {} changed, {} with a different first choice",
+                self.contest_id,
+                self.adjudicated,
+                impact.changed_ballots,
+                impact
+                    .first_choice_changes
+                    .iter()
+                    .map(|change| change.ballots)
+                    .sum::<u32>()
+            );
+            election.adjudicated_ballots = Some(self.adjudicated);
+            election.adjudication = Some(impact);
+        }
+        election
+    }
+}
+
+/// Names for the counting groups (e.g. "Election Day", "Vote by Mail") and
+/// precinct portions referenced by CVR sessions.
+struct Descriptions {
+    counting_groups: HashMap<u32, String>,
+    precinct_portions: HashMap<u32, String>,
+}
+
+impl Descriptions {
+    pub fn from_dir(dir: &Path) -> Descriptions {
+        Descriptions {
+            counting_groups: read_descriptions(
+                open_raw(&dir.join("CountingGroupManifest.json")).ok(),
+            ),
+            precinct_portions: read_descriptions(
+                open_raw(&dir.join("PrecinctPortionManifest.json")).ok(),
+            ),
+        }
+    }
+}
+
+/// Look up the name for an ID, falling back to the ID itself if the export
+/// has no manifest entry for it.
+fn describe(descriptions: &HashMap<u32, String>, id: u32) -> String {
+    descriptions
+        .get(&id)
+        .cloned()
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// Read a manifest mapping IDs to descriptions. Returns an empty map if the
+/// export has no such manifest.
+fn read_descriptions<R: Read>(reader: Option<R>) -> HashMap<u32, String> {
+    let manifest: Option<DescriptionManifest> =
+        reader.and_then(|r| serde_json::from_reader(BufReader::new(r)).ok());
+
+    manifest
+        .map(|m| {
+            m.list
+                .into_iter()
+                .map(|entry| (entry.id, entry.description))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether a CVR export, given as a directory or ZIP archive, has any CVR
+/// files (see `is_cvr_file`).
+pub(super) fn has_cvr_files(cvr_path: &Path, cvr_files: Option<&Regex>) -> bool {
+    if cvr_path.is_dir() {
+        fs::read_dir(cvr_path).is_ok_and(|entries| {
+            entries.flatten().any(|entry| {
+                entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|f| is_cvr_file(f, cvr_files))
+            })
+        })
+    } else {
+        File::open(cvr_path)
+            .ok()
+            .and_then(|file| zip::ZipArchive::new(file).ok())
+            .is_some_and(|archive| archive.file_names().any(|f| is_cvr_file(f, cvr_files)))
+    }
+}
+
+/// Read the contest IDs and descriptions from the ContestManifest.json of a
+/// CVR export, given as a directory or ZIP archive. Returns `None` if it has
+/// no readable contest manifest.
+pub fn read_contest_manifest(cvr_path: &Path) -> Option<BTreeMap<u32, String>> {
+    let contests = if cvr_path.is_dir() {
+        read_descriptions(open_raw(&cvr_path.join("ContestManifest.json")).ok())
+    } else {
+        let mut archive = zip::ZipArchive::new(File::open(cvr_path).ok()?).ok()?;
+        let contests = read_descriptions(archive.by_name("ContestManifest.json").ok());
+        contests
+    };
+
+    if contests.is_empty() {
+        None
+    } else {
+        Some(contests.into_iter().collect())
+    }
+}
+
+/// Read a CVR file. A file that can't be read (even after `open_raw`'s
+/// retries) fails the contest, rather than being skipped like a malformed
+/// file, because its ballots would silently be missing from the results.
+fn read_cvr_file<R: Read>(reader: R, filename: &str) -> String {
+    std::io::read_to_string(reader)
+        .unwrap_or_else(|e| panic!("Failed to read CVR file {}: {}", filename, e))
+}
+
+/// Parse a CVR file. A file that ends before its JSON does is truncated, and
+/// fails the contest for the same reason as in `read_cvr_file`.
+fn parse_cvr_file(content: &str, filename: &str) -> serde_json::Result<CvrExport> {
+    serde_json::from_str(content).map_err(|e| {
+        if e.is_eof() {
+            panic!("CVR file {} is truncated: {}", filename, e);
+        }
+        e
+    })
+}
+
 /// Stream process a CVR file, extracting only ballots for the target contest
 /// This avoids loading the entire CVR (with all contests) into memory
 fn stream_process_cvr_file<R: Read>(
     reader: R,
     filename: &str,
-    contest_id: u32,
-    candidates: &CandidateMap<u32>,
-    dropped_write_in: Option<u32>,
-    ballots: &mut Vec<Ballot>,
+    contest_ballots: &mut ContestBallots,
+    descriptions: &Descriptions,
 ) -> Result<usize, String> {
     let mut count = 0;
-    let content =
-        std::io::read_to_string(reader).map_err(|e| format!("Failed to read file: {}", e))?;
+    let content = read_cvr_file(reader, filename);
 
     // Parse as CvrExport but immediately process sessions
     let cvr: CvrExport =
-        serde_json::from_str(&content).map_err(|e| format!("Failed to parse JSON: {}", e))?;
+        parse_cvr_file(&content, filename).map_err(|e| format!("Failed to parse JSON: {}", e))?;
 
     for session in &cvr.sessions {
-        for contest in &session.contests() {
-            if contest.id == contest_id {
-                let mut choices: Vec<Choice> = Vec::new();
-                for (_, marks) in &contest.marks.iter().group_by(|x| x.rank) {
-                    let marks: Vec<&Mark> = marks.filter(|d| !d.is_ambiguous).collect();
-
-                    let choice = match marks.as_slice() {
-                        [v] if Some(v.candidate_id) == dropped_write_in => Choice::Undervote,
-                        [v] => candidates.id_to_choice(v.candidate_id),
-                        [] => Choice::Undervote,
-                        _ => Choice::Overvote,
-                    };
-
-                    choices.push(choice);
-                }
-
-                ballots.push(Ballot::new(
-                    format!("{}:{}", filename, session.record_id),
-                    choices,
-                ));
+        for contest in &contest_ballots.mark_policy.session_contests(session) {
+            if contest.id == contest_ballots.contest_id {
+                contest_ballots.push_session(filename, session, contest, descriptions);
                 count += 1;
             }
         }
@@ -185,20 +578,15 @@ fn stream_process_cvr_file<R: Read>(
 /// - Row 3: Column headers (CvrNumber, TabulatorNum, etc.)
 /// - Row 4+: Ballot data
 fn stream_process_csv_cvr_file<R: Read>(
-    reader: R,
+    mut rdr: csv::Reader<R>,
     filename: &str,
     contest_id: u32,
-    candidates: &CandidateMap<u32>,
+    candidates: &mut CandidateMap<u32>,
     dropped_write_in: Option<u32>,
     ballots: &mut Vec<Ballot>,
     candidate_manifest: &CandidateManifest,
 ) -> Result<usize, String> {
     let mut count = 0;
-    let mut rdr = ReaderBuilder::new()
-        .has_headers(false)
-        .buffer_capacity(1024 * 1024) // 1MB buffer for large files
-        .from_reader(reader);
-
     // Read header rows using a reusable buffer
     let mut header_buffer = csv::StringRecord::new();
     let mut rows = Vec::new();
@@ -355,7 +743,8 @@ fn stream_process_csv_cvr_file<R: Read>(
         sorted_marks.sort_by_key(|(_, rank)| *rank);
 
         let mut choices: Vec<Choice> = Vec::new();
-        for (_, rank_group) in &sorted_marks.iter().group_by(|(_, r)| *r) {
+        for (rank, rank_group) in &sorted_marks.iter().group_by(|(_, r)| *r) {
+            fill_skipped_ranks(&mut choices, rank);
             let marks_at_rank: Vec<u32> = rank_group.map(|(candidate_id, _)| *candidate_id).collect();
             let choice = match marks_at_rank.as_slice() {
                 [] => Choice::Undervote,
@@ -381,7 +770,7 @@ fn read_from_directory(dir_path: &Path, options: &ReaderOptions) -> Election {
     let candidate_manifest_path = dir_path.join("CandidateManifest.json");
 
     let candidate_manifest: CandidateManifest = {
-        let file = match File::open(&candidate_manifest_path) {
+        let file = match open_raw(&candidate_manifest_path) {
             Ok(file) => file,
             Err(e) => {
                 crate::log_warn!(
@@ -397,13 +786,15 @@ fn read_from_directory(dir_path: &Path, options: &ReaderOptions) -> Election {
         serde_json::from_reader(reader).unwrap()
     };
 
-    let (candidates, dropped_write_in) = get_candidates(
+    let mut contest_ballots = ContestBallots::new(
         &candidate_manifest,
         options.contest,
         options.drop_unqualified_write_in,
+        options.mark_policy,
+        options.vote_for,
     );
-
-    let mut ballots: Vec<Ballot> = Default::default();
+    let descriptions = Descriptions::from_dir(dir_path);
+    let mut read_json = false;
 
     // Find all CvrExport files in the directory
     let mut cvr_files: Vec<String> = Vec::new();
@@ -411,10 +802,7 @@ fn read_from_directory(dir_path: &Path, options: &ReaderOptions) -> Election {
         for entry in entries {
             if let Ok(entry) = entry {
                 let filename = entry.file_name().to_string_lossy().to_string();
-                // Support both JSON and CSV formats (CSV files may use CVR_Export prefix)
-                if (filename.starts_with("CvrExport") && filename.ends_with(".json"))
-                    || (filename.starts_with("CVR_Export") && filename.ends_with(".csv"))
-                {
+                if is_cvr_file(&filename, options.cvr_files.as_ref()) {
                     cvr_files.push(filename);
                 }
             }
@@ -431,34 +819,26 @@ fn read_from_directory(dir_path: &Path, options: &ReaderOptions) -> Election {
 
     for filename in cvr_files {
         let file_path = dir_path.join(&filename);
-        let file = match File::open(&file_path) {
-            Ok(file) => file,
-            Err(e) => {
-                crate::log_warn!("Warning: Could not open {}: {}", filename, e);
-                continue;
-            }
-        };
+        let file = open_raw(&file_path)
+            .unwrap_or_else(|e| panic!("Failed to open CVR file {}: {}", filename, e));
 
         // Determine file type and process accordingly
-        let result = if filename.ends_with(".csv") {
-            stream_process_csv_cvr_file(
-                file,
-                &filename,
-                options.contest,
-                &candidates,
-                dropped_write_in,
-                &mut ballots,
-                &candidate_manifest,
-            )
+        let result = if uncompressed_name(&filename).ends_with(".csv") {
+            match options.csv_dialect.reader(file, false) {
+                Ok(rdr) => stream_process_csv_cvr_file(
+                    rdr,
+                    &filename,
+                    options.contest,
+                    &mut contest_ballots.candidates,
+                    contest_ballots.dropped_write_in,
+                    &mut contest_ballots.ballots,
+                    &candidate_manifest,
+                ),
+                Err(e) => Err(e.to_string()),
+            }
         } else {
-            stream_process_cvr_file(
-                file,
-                &filename,
-                options.contest,
-                &candidates,
-                dropped_write_in,
-                &mut ballots,
-            )
+            read_json = true;
+            stream_process_cvr_file(file, &filename, &mut contest_ballots, &descriptions)
         };
 
         match result {
@@ -479,9 +859,7 @@ fn read_from_directory(dir_path: &Path, options: &ReaderOptions) -> Election {
         }
     }
 
-    crate::log_debug!("Read {} ballots", ballots.len());
-
-    Election::new(candidates.into_vec(), ballots)
+    contest_ballots.into_election(read_json)
 }
 
 fn read_from_zip(zip_path: &Path, options: &ReaderOptions) -> Election {
@@ -505,19 +883,29 @@ fn read_from_zip(zip_path: &Path, options: &ReaderOptions) -> Election {
         serde_json::from_reader(reader).unwrap()
     };
 
-    let (candidates, dropped_write_in) = get_candidates(
+    let mut contest_ballots = ContestBallots::new(
         &candidate_manifest,
         options.contest,
         options.drop_unqualified_write_in,
+        options.mark_policy,
+        options.vote_for,
     );
-
-    let mut ballots: Vec<Ballot> = Default::default();
+    let descriptions = Descriptions {
+        counting_groups: read_descriptions(archive.by_name("CountingGroupManifest.json").ok()),
+        precinct_portions: read_descriptions(archive.by_name("PrecinctPortionManifest.json").ok()),
+    };
     let filenames: Vec<String> = archive.file_names().map(|d| d.to_string()).collect();
 
-    let cvr_files: Vec<String> = filenames
+    // Only JSON exports are read from ZIP archives.
+    let mut cvr_files: Vec<String> = filenames
         .into_iter()
-        .filter(|f| f.starts_with("CvrExport"))
+        .filter(|f| match &options.cvr_files {
+            Some(rx) => rx.is_match(f) && f.ends_with(".json"),
+            None => f.starts_with("CvrExport"),
+        })
         .collect();
+    // Archive entries are not listed in a guaranteed order.
+    cvr_files.sort();
 
     let file_count = cvr_files.len();
 
@@ -536,14 +924,7 @@ fn read_from_zip(zip_path: &Path, options: &ReaderOptions) -> Election {
         };
 
         // Stream process the CVR file to avoid loading entire file into memory
-        let result = stream_process_cvr_file(
-            file,
-            &filename,
-            options.contest,
-            &candidates,
-            dropped_write_in,
-            &mut ballots,
-        );
+        let result = stream_process_cvr_file(file, &filename, &mut contest_ballots, &descriptions);
 
         match result {
             Ok(count) => {
@@ -563,9 +944,7 @@ fn read_from_zip(zip_path: &Path, options: &ReaderOptions) -> Election {
         }
     }
 
-    crate::log_debug!("Read {} ballots", ballots.len());
-
-    Election::new(candidates.into_vec(), ballots)
+    contest_ballots.into_election(true)
 }
 
 /// Batch process multiple contests from the same CVR files
@@ -584,6 +963,7 @@ pub fn nist_batch_reader(
         .get("cvr")
         .expect("nist_sp_1500 elections should have cvr parameter.")
         .clone();
+    let cvr_file_glob = contests[0].1.get("cvrFiles").map(|d| glob_regex(d));
 
     // Handle "." as current directory
     let mut cvr_path = if cvr_name == "." {
@@ -625,7 +1005,7 @@ pub fn nist_batch_reader(
     // Load candidate manifest once
     let candidate_manifest_path = cvr_path.join("CandidateManifest.json");
     let candidate_manifest: CandidateManifest = {
-        let file = match File::open(&candidate_manifest_path) {
+        let file = match open_raw(&candidate_manifest_path) {
             Ok(file) => file,
             Err(e) => {
                 crate::log_error!(
@@ -641,8 +1021,7 @@ pub fn nist_batch_reader(
     };
 
     // Set up candidate maps and ballot buckets for each contest
-    let mut contest_data: HashMap<u32, (CandidateMap<u32>, Option<u32>, Vec<Ballot>)> =
-        HashMap::new();
+    let mut contest_data: HashMap<u32, ContestBallots> = HashMap::new();
 
     for (contest_id, params) in &contests {
         let drop_unqualified_write_in: bool = params
@@ -650,11 +1029,26 @@ pub fn nist_batch_reader(
             .map(|d| d.parse().unwrap())
             .unwrap_or(false);
 
-        let (candidates, dropped_write_in) =
-            get_candidates(&candidate_manifest, *contest_id, drop_unqualified_write_in);
-
-        contest_data.insert(*contest_id, (candidates, dropped_write_in, Vec::new()));
+        contest_data.insert(
+            *contest_id,
+            ContestBallots::new(
+                &candidate_manifest,
+                *contest_id,
+                drop_unqualified_write_in,
+                MarkPolicy::from_params(params).unwrap_or_else(|e| panic!("{}", e)),
+                vote_for(params),
+            ),
+        );
     }
+    let descriptions = Descriptions::from_dir(&cvr_path);
+
+    // Contests may read different versions of the ballot, so collect the distinct
+    // versions needed and read each one at most once per session.
+    let ballot_versions: Vec<Option<BallotVersion>> = contest_data
+        .values()
+        .map(|d| d.mark_policy.ballot_version)
+        .unique()
+        .collect();
 
     // Find all CVR files
     let mut cvr_files: Vec<String> = Vec::new();
@@ -662,10 +1056,7 @@ pub fn nist_batch_reader(
         for entry in entries {
             if let Ok(entry) = entry {
                 let filename = entry.file_name().to_string_lossy().to_string();
-                // Support both JSON and CSV formats (CSV files may use CVR_Export prefix)
-                if (filename.starts_with("CvrExport") && filename.ends_with(".json"))
-                    || (filename.starts_with("CVR_Export") && filename.ends_with(".csv"))
-                {
+                if is_cvr_file(&filename, cvr_file_glob.as_ref()) {
                     cvr_files.push(filename);
                 }
             }
@@ -674,30 +1065,20 @@ pub fn nist_batch_reader(
 
     cvr_files.sort();
     let file_count = cvr_files.len();
+    let mut styles = BallotStyleTally::default();
 
     crate::log_debug!("  Processing {} CVR files...", file_count);
 
     // Process each CVR file once, distributing ballots to all contests
     for (file_idx, filename) in cvr_files.iter().enumerate() {
         let file_path = cvr_path.join(filename);
-        let file = match File::open(&file_path) {
-            Ok(file) => file,
-            Err(e) => {
-                crate::log_warn!("Warning: Could not open {}: {}", filename, e);
-                continue;
-            }
-        };
+        let file = open_raw(&file_path)
+            .unwrap_or_else(|e| panic!("Failed to open CVR file {}: {}", filename, e));
 
         // Read and parse the CVR file
-        let content = match std::io::read_to_string(file) {
-            Ok(content) => content,
-            Err(e) => {
-                crate::log_warn!("Warning: Failed to read {}: {}", filename, e);
-                continue;
-            }
-        };
+        let content = read_cvr_file(file, filename);
 
-        let cvr: CvrExport = match serde_json::from_str(&content) {
+        let cvr: CvrExport = match parse_cvr_file(&content, filename) {
             Ok(cvr) => cvr,
             Err(e) => {
                 crate::log_warn!("Warning: Failed to parse {}: {}", filename, e);
@@ -707,28 +1088,23 @@ pub fn nist_batch_reader(
 
         // Process each session and distribute ballots to contests
         for session in &cvr.sessions {
-            for contest in &session.contests() {
-                if let Some((candidates, dropped_write_in, ballots)) =
-                    contest_data.get_mut(&contest.id)
-                {
-                    let mut choices: Vec<Choice> = Vec::new();
-                    for (_, marks) in &contest.marks.iter().group_by(|x| x.rank) {
-                        let marks: Vec<&Mark> = marks.filter(|d| !d.is_ambiguous).collect();
-
-                        let choice = match marks.as_slice() {
-                            [v] if Some(v.candidate_id) == *dropped_write_in => Choice::Undervote,
-                            [v] => candidates.id_to_choice(v.candidate_id),
-                            [] => Choice::Undervote,
-                            _ => Choice::Overvote,
-                        };
-
-                        choices.push(choice);
-                    }
+            for (style, contests) in session.style_contests() {
+                styles.record(
+                    &style,
+                    contests
+                        .into_iter()
+                        .filter(|id| contest_data.contains_key(id))
+                        .map(|id| id.to_string()),
+                );
+            }
+            for ballot_version in &ballot_versions {
+                for contest in &session_contests(session, *ballot_version) {
+                    let data = match contest_data.get_mut(&contest.id) {
+                        Some(data) if data.mark_policy.ballot_version == *ballot_version => data,
+                        _ => continue,
+                    };
 
-                    ballots.push(Ballot::new(
-                        format!("{}:{}", filename, session.record_id),
-                        choices,
-                    ));
+                    data.push_session(filename, session, contest, &descriptions);
                 }
             }
         }
@@ -743,18 +1119,85 @@ pub fn nist_batch_reader(
         }
     }
 
+    // A contest that some ballots of a style list and others don't was
+    // probably dropped from them by a parse error.
+    for (contest, inconsistencies) in &styles
+        .inconsistencies()
+        .into_iter()
+        .group_by(|i| i.contest.clone())
+    {
+        let inconsistencies: Vec<_> = inconsistencies.collect();
+        crate::log_warn!(
+            "Contest {} is missing from {} ballots of styles that list it on other ballots ({})",
+            contest,
+            inconsistencies.iter().map(|i| i.missing).sum::<u32>(),
+            inconsistencies.iter().map(|i| i.to_string()).join("; ")
+        );
+    }
+
     // Convert to Election objects
     let mut results = HashMap::new();
-    for (contest_id, (candidates, _dropped_write_in, ballots)) in contest_data {
-        crate::log_debug!(
-            "  Contest {}: {} ballots",
-            contest_id,
-            ballots.len()
-        );
-        results.insert(contest_id, Election::new(candidates.into_vec(), ballots));
+    for (contest_id, data) in contest_data {
+        results.insert(contest_id, data.into_election(true));
     }
 
     crate::log_debug!("{} Batch processing complete\n", "SUCCESS:");
 
     results
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_mark_policy_defaults() {
+        let policy = MarkPolicy::from_params(&params(&[])).unwrap();
+        assert!(!policy.count_ambiguous && !policy.require_is_vote);
+        assert_eq!(None, policy.ballot_version);
+    }
+
+    #[test]
+    fn test_mark_policy_params() {
+        let policy = MarkPolicy::from_params(&params(&[
+            ("countAmbiguousMarks", "true"),
+            ("requireIsVote", "true"),
+            ("ballotVersion", "original"),
+        ]))
+        .unwrap();
+        assert!(policy.count_ambiguous && policy.require_is_vote);
+        assert_eq!(Some(BallotVersion::Original), policy.ballot_version);
+
+        let policy = MarkPolicy::from_params(&params(&[
+            ("countAmbiguousMarks", "false"),
+            ("ballotVersion", "modified"),
+        ]))
+        .unwrap();
+        assert!(!policy.count_ambiguous);
+        assert_eq!(Some(BallotVersion::Modified), policy.ballot_version);
+    }
+
+    #[test]
+    fn test_mark_policy_bad_params() {
+        let error = |pairs: &[(&str, &str)]| MarkPolicy::from_params(&params(pairs)).unwrap_err();
+        assert_eq!(
+            "countAmbiguousMarks param should be \"true\" or \"false\", not \"yes\".",
+            error(&[("countAmbiguousMarks", "yes")])
+        );
+        assert_eq!(
+            "requireIsVote param should be \"true\" or \"false\", not \"1\".",
+            error(&[("requireIsVote", "1")])
+        );
+        assert_eq!(
+            "ballotVersion param should be \"original\" or \"modified\", not \"adjudicated\".",
+            error(&[("ballotVersion", "adjudicated")])
+        );
+    }
+}
@@ -66,6 +66,30 @@ impl Session {
         }
     }
 
+    /// Returns true if the ballot was modified during adjudication.
+    pub fn is_adjudicated(&self) -> bool {
+        self.modified.is_some()
+    }
+
+    /// The IDs of the contests on each card of the ballot, keyed by ballot
+    /// style: the ballot type, with the card's position if the ballot is
+    /// split across cards, since each card lists only its own contests.
+    pub fn style_contests(&self) -> Vec<(String, Vec<u32>)> {
+        let ballot = self.ballot();
+        let ids = |contests: &[ContestMarks]| contests.iter().map(|c| c.id).collect();
+        match (&ballot.contests, &ballot.cards) {
+            (Some(contests), _) => vec![(ballot.ballot_type_id.to_string(), ids(contests))],
+            (None, Some(cards)) => cards
+                .iter()
+                .map(|card| {
+                    let style = format!("{}/{}", ballot.ballot_type_id, card.paper_index);
+                    (style, ids(&card.contests))
+                })
+                .collect(),
+            (None, None) => Vec::new(),
+        }
+    }
+
     pub fn contests(&self) -> Vec<ContestMarks> {
         match &self.original.contests {
             Some(c) => (*c).clone(),
@@ -84,13 +108,28 @@ impl Session {
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct SessionBallot {
-    precinct_portion_id: u32,
+    pub precinct_portion_id: u32,
     ballot_type_id: u32,
     is_current: bool,
     contests: Option<Vec<ContestMarks>>,
     cards: Option<Vec<Card>>,
 }
 
+impl SessionBallot {
+    /// Return the contests on this version of the ballot, whether they are
+    /// listed directly or split across cards.
+    pub fn contests(&self) -> Vec<ContestMarks> {
+        match (&self.contests, &self.cards) {
+            (Some(c), _) => c.clone(),
+            (None, Some(cards)) => cards
+                .iter()
+                .flat_map(|card| card.contests.clone())
+                .collect(),
+            (None, None) => Vec::new(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Card {
@@ -154,7 +193,7 @@ pub struct Mark {
     pub rank: u32,
     mark_density: u32,
     pub is_ambiguous: bool,
-    is_vote: bool,
+    pub is_vote: bool,
 }
 
 // CandidateManifest.json
@@ -185,6 +224,21 @@ pub struct Candidate {
     pub candidate_type: CandidateType,
 }
 
+// CountingGroupManifest.json, PrecinctPortionManifest.json
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DescriptionManifest {
+    pub list: Vec<DescriptionEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DescriptionEntry {
+    pub description: String,
+    pub id: u32,
+}
+
 // ContestManifest.json
 
 #[allow(dead_code)]
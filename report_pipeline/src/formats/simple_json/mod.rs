@@ -1,6 +1,6 @@
-use crate::formats::common::CandidateMap;
+use crate::formats::common::{CandidateMap, NameMatching};
 use crate::model::election::{Ballot, Candidate, CandidateType, Choice, Election};
-use crate::util::read_serialized;
+use crate::util::open_raw;
 use serde::Deserialize;
 use std::collections::BTreeMap;
 use std::path::Path;
@@ -44,10 +44,15 @@ pub fn parse_choice(candidate: &str, candidate_map: &mut CandidateMap<String>) -
 }
 
 pub fn json_reader(path: &Path, params: BTreeMap<String, String>) -> Election {
+    let name_matching = NameMatching::from_params(&params);
     let options = ReaderOptions::from_params(params);
 
-    let raw_ballots: RawBallots = read_serialized(&path.join(options.file));
-    let mut candidate_map = CandidateMap::new();
+    let file_path = path.join(&options.file);
+    let raw = open_raw(&file_path)
+        .and_then(std::io::read_to_string)
+        .unwrap_or_else(|e| panic!("Failed to read {}: {}", file_path.display(), e));
+    let raw_ballots: RawBallots = serde_json::from_str(&raw).unwrap();
+    let mut candidate_map = CandidateMap::with_name_matching(name_matching);
 
     let ballots: Vec<Ballot> = raw_ballots
         .ballots
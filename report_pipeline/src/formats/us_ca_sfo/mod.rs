@@ -1,9 +1,8 @@
 use crate::formats::common::{normalize_name, CandidateMap};
 use crate::model::election::{Ballot, Candidate, CandidateType, Choice, Election};
-use crate::util::UnicodeString;
+use crate::util::{open_raw, UnicodeString};
 use itertools::Itertools;
 use std::collections::BTreeMap;
-use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
@@ -99,7 +98,7 @@ fn read_candidates(reader: &mut dyn BufRead, contest_id: u32) -> CandidateMap<u3
 
 fn read_ballots(
     reader: &mut dyn BufRead,
-    candidates: &CandidateMap<u32>,
+    candidates: &mut CandidateMap<u32>,
     contest: u32,
 ) -> Vec<Ballot> {
     let mut ballots = Vec::new();
@@ -167,24 +166,27 @@ pub fn sfo_ballot_reader(path: &Path, params: BTreeMap<String, String>) -> Elect
 
     // Read from extracted files directly (ZIP files are extracted by extract-from-archives.sh)
     let master_path = path.join(&options.master_file);
-    let mut master_reader = BufReader::new(File::open(&master_path).unwrap_or_else(|e| {
+    let mut master_reader = BufReader::new(open_raw(&master_path).unwrap_or_else(|e| {
         panic!(
             "❌ Failed to open SFO master file '{}': {}\n   Please ensure the file exists and is readable.\n   Run extract-from-archives.sh to extract data from archives.",
             master_path.display(),
             e
         );
     }));
-    let candidates = read_candidates(&mut master_reader, options.contest);
+    let mut candidates = read_candidates(&mut master_reader, options.contest);
 
     let ballot_path = path.join(&options.ballot_file);
-    let mut ballot_reader = BufReader::new(File::open(&ballot_path).unwrap_or_else(|e| {
+    let mut ballot_reader = BufReader::new(open_raw(&ballot_path).unwrap_or_else(|e| {
         panic!(
             "❌ Failed to open SFO ballot file '{}': {}\n   Please ensure the file exists and is readable.\n   Run extract-from-archives.sh to extract data from archives.",
             ballot_path.display(),
             e
         );
     }));
-    let ballots = read_ballots(&mut ballot_reader, &candidates, options.contest);
+    let ballots = read_ballots(&mut ballot_reader, &mut candidates, options.contest);
 
-    Election::new(candidates.into_vec(), ballots)
+    let unknown_candidates = candidates.unknown_candidates();
+    let mut election = Election::new(candidates.into_vec(), ballots);
+    election.unknown_candidates = unknown_candidates;
+    election
 }
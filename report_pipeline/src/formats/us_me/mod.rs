@@ -1,4 +1,4 @@
-use crate::formats::common::{normalize_name, CandidateMap};
+use crate::formats::common::{normalize_name, CandidateMap, NameMatching};
 use crate::model::election::{Ballot, Candidate, CandidateType, Choice, Election};
 use lazy_static::lazy_static;
 use regex::Regex;
@@ -50,9 +50,10 @@ pub fn parse_choice(candidate: &str, candidate_map: &mut CandidateMap<String>) -
 // Inline ballot processing to avoid private type issues
 
 pub fn maine_ballot_reader(path: &Path, params: BTreeMap<String, String>) -> Election {
+    let name_matching = NameMatching::from_params(&params);
     let options = ReaderOptions::from_params(params);
     let mut ballots: Vec<Ballot> = Vec::new();
-    let mut candidate_map: CandidateMap<String> = CandidateMap::new();
+    let mut candidate_map: CandidateMap<String> = CandidateMap::with_name_matching(name_matching);
 
     for file in options.files {
         crate::log_debug!("Reading: {}", file);
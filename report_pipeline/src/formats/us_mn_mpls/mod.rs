@@ -1,11 +1,12 @@
-use crate::formats::common::CandidateMap;
+use crate::formats::common::{CandidateMap, CsvDialect, NameMatching};
 use crate::model::election::{Ballot, Candidate, CandidateType, Choice, Election};
-use csv::ReaderBuilder;
+use crate::util::open_raw;
 use std::collections::BTreeMap;
 use std::path::Path;
 
 struct ReaderOptions {
     file: String,
+    csv_dialect: CsvDialect,
 }
 
 impl ReaderOptions {
@@ -14,8 +15,9 @@ impl ReaderOptions {
             .get("file")
             .expect("Minneapolis elections should have file parameter.")
             .clone();
+        let csv_dialect = CsvDialect::from_params(&params);
 
-        ReaderOptions { file }
+        ReaderOptions { file, csv_dialect }
     }
 }
 
@@ -44,15 +46,15 @@ pub fn parse_choice(candidate: &str, candidate_map: &mut CandidateMap<String>) -
 }
 
 pub fn mpls_ballot_reader(path: &Path, params: BTreeMap<String, String>) -> Election {
+    let name_matching = NameMatching::from_params(&params);
     let options = ReaderOptions::from_params(params);
     let file_path = path.join(&options.file);
 
-    let mut rdr = ReaderBuilder::new()
-        .has_headers(true)
-        .from_path(&file_path)
+    let mut rdr = open_raw(&file_path)
+        .and_then(|file| options.csv_dialect.reader(file, true))
         .expect(&format!("Failed to open CSV file: {}", file_path.display()));
 
-    let mut candidate_map = CandidateMap::new();
+    let mut candidate_map = CandidateMap::with_name_matching(name_matching);
     let mut ballots: Vec<Ballot> = Vec::new();
     let mut ballot_id = 0;
 
@@ -24,7 +24,11 @@
  * 6. **Memory-Efficient Storage**: Only stores ballots with actual votes,
  *    reducing memory usage and improving cache performance.
  *
- * 7. **Profiling Support**: Configured with frame pointers and debug info
+ * 7. **Selective Column Reading**: Streams xlsx worksheets cell by cell, keeping
+ *    only the CVR id and race columns, and skips worksheets whose headers have
+ *    no CVR columns, so the very wide CVR workbooks are never held in memory.
+ *
+ * 8. **Profiling Support**: Configured with frame pointers and debug info
  *    for accurate performance profiling.
  *
  * ## Expected Performance Gains:
@@ -43,11 +47,17 @@
  */
 
 use crate::formats::common::CandidateMap;
-use crate::model::election::{Ballot, Candidate, CandidateType, Choice, Election};
-use calamine::{open_workbook_auto, Data, DataType, Reader};
+#[cfg(feature = "streaming-xlsx")]
+use crate::formats::common::XlsxStream;
+use crate::model::election::{
+    Ballot, Candidate, CandidateType, Choice, Election, UnknownCandidate,
+};
+use calamine::{open_workbook_auto, Data, DataType, Reader, Sheets};
 use regex::Regex;
-use std::collections::HashMap;
-use std::fs::read_dir;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs::{read_dir, File};
+use std::io::BufReader;
 use std::path::Path;
 use std::time::Instant;
 
@@ -71,6 +81,23 @@ pub struct RaceMetadata {
     pub max_rank: u32,
 }
 
+/// Key used to identify a candidate within a race. Write-ins are kept distinct
+/// from the numeric candidacy IDs so that they can never collide with a real ID.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum NycCandidateKey {
+    Id(u32),
+    WriteIn,
+}
+
+impl fmt::Display for NycCandidateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NycCandidateKey::Id(id) => write!(f, "{}", id),
+            NycCandidateKey::WriteIn => write!(f, "Write-in"),
+        }
+    }
+}
+
 /// Pre-compiled regex patterns for performance
 struct CompiledPatterns {
     column_rx: Regex,
@@ -80,7 +107,7 @@ struct CompiledPatterns {
 impl CompiledPatterns {
     fn new(cvr_pattern: &str) -> Self {
         Self {
-            column_rx: Regex::new(r#"(.+) Choice ([1-5]) of ([1-5]) (.+) \((\d+)\)"#).unwrap(),
+            column_rx: Regex::new(r#"(.+) Choice (\d+) of (\d+) (.+) \((\d+)\)"#).unwrap(),
             file_rx: Regex::new(&format!("^{}$", cvr_pattern)).unwrap(),
         }
     }
@@ -93,6 +120,7 @@ pub struct BallotDatabase {
     pub ballots: Vec<RaceBallotVote>,
     pub ballots_by_race: HashMap<String, Vec<usize>>, // race_key -> ballot indices
     pub race_candidates: HashMap<String, Vec<Candidate>>, // race_key -> candidate list
+    pub race_unknown_candidates: HashMap<String, Vec<UnknownCandidate>>,
 }
 
 impl BallotDatabase {
@@ -103,6 +131,7 @@ impl BallotDatabase {
             ballots: Vec::new(),
             ballots_by_race: HashMap::new(),
             race_candidates: HashMap::new(),
+            race_unknown_candidates: HashMap::new(),
         }
     }
 
@@ -131,12 +160,23 @@ impl BallotDatabase {
             ballots.push(ballot);
         }
 
-        Some(Election::new(candidates, ballots))
+        let mut election = Election::new(candidates, ballots);
+        election.unknown_candidates = self
+            .race_unknown_candidates
+            .get(race_key)
+            .cloned()
+            .unwrap_or_default();
+        Some(election)
     }
 }
 
 /// Highly optimized NYC ballot reader
-pub fn read_all_nyc_data(path: &Path, candidates_file: &str, cvr_pattern: &str) -> BallotDatabase {
+pub fn read_all_nyc_data(
+    path: &Path,
+    candidates_file: &str,
+    cvr_pattern: &str,
+    backend: XlsxBackend,
+) -> BallotDatabase {
     let total_start = Instant::now();
     let mut db = BallotDatabase::new();
 
@@ -173,9 +213,13 @@ pub fn read_all_nyc_data(path: &Path, candidates_file: &str, cvr_pattern: &str)
         let file = file.unwrap();
         let filename = file.file_name().to_string_lossy().to_string();
         if patterns.file_rx.is_match(&filename) {
-            file_paths.push((file.path(), filename));
+            let backend = backend.for_file(&file.path());
+            file_paths.push((file.path(), filename, backend));
         }
     }
+    // Candidates are numbered in order of first appearance, so files must be
+    // read in a consistent order for the output to be reproducible.
+    file_paths.sort_by(|a, b| a.1.cmp(&b.1));
 
     let step2_duration = step2_start.elapsed();
     crate::log_debug!(
@@ -190,7 +234,7 @@ pub fn read_all_nyc_data(path: &Path, candidates_file: &str, cvr_pattern: &str)
     let step4_start = Instant::now();
     crate::log_debug!("🗳️  Processing ballot data with optimized pipeline...");
 
-    let mut race_candidate_maps: HashMap<String, CandidateMap<u32>> = HashMap::new();
+    let mut race_candidate_maps: HashMap<String, CandidateMap<NycCandidateKey>> = HashMap::new();
     let mut ballots_by_race: HashMap<String, Vec<usize>> = HashMap::new();
 
     // Conservative pre-allocation
@@ -218,6 +262,8 @@ pub fn read_all_nyc_data(path: &Path, candidates_file: &str, cvr_pattern: &str)
     // Step 6: Finalize candidate lists
     let step6_start = Instant::now();
     for (race_key, candidate_map) in race_candidate_maps {
+        db.race_unknown_candidates
+            .insert(race_key.clone(), candidate_map.unknown_candidates());
         let candidates = candidate_map.into_vec();
         db.race_candidates.insert(race_key, candidates);
     }
@@ -268,189 +314,535 @@ fn read_candidate_ids_optimized(candidates_path: &Path) -> HashMap<u32, String>
     candidates
 }
 
+/// Order a race's `(rank, column)` pairs by rank, returning one entry per rank
+/// from 1 up to `max_rank` (or the highest rank seen, if larger). Ranks with no
+/// column are `None` (and are read as undervotes) so that later ranks keep their
+/// position on the ballot.
+fn order_columns_by_rank(
+    race_key: &str,
+    mut rank_columns: Vec<(u32, usize)>,
+    max_rank: u32,
+) -> Vec<Option<usize>> {
+    rank_columns.sort();
+
+    let max_rank = rank_columns
+        .last()
+        .map(|(rank, _)| *rank)
+        .unwrap_or(0)
+        .max(max_rank);
+    let mut columns: Vec<Option<usize>> = vec![None; max_rank as usize];
+
+    for (rank, col_idx) in rank_columns {
+        let slot = &mut columns[(rank - 1) as usize];
+        if slot.is_some() {
+            crate::log_warn!(
+                "    ⚠️  Duplicate column for rank {} in race {}, ignoring column {}",
+                rank,
+                race_key,
+                col_idx
+            );
+        } else {
+            *slot = Some(col_idx);
+        }
+    }
+
+    let missing: Vec<String> = columns
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| c.is_none())
+        .map(|(i, _)| (i + 1).to_string())
+        .collect();
+    if !missing.is_empty() {
+        crate::log_warn!(
+            "    ⚠️  Race {} is missing columns for rank(s) {}; treating as undervotes",
+            race_key,
+            missing.join(", ")
+        );
+    }
+
+    columns
+}
+
+/// A race whose ranked columns appear in a worksheet.
+#[derive(Debug, PartialEq)]
+struct SheetRace {
+    race_key: String,
+    office_name: String,
+    jurisdiction_name: String,
+    max_rank: u32,
+    /// The column holding each rank, from rank 1; see `order_columns_by_rank`.
+    columns: Vec<Option<usize>>,
+}
+
+/// The columns of a CVR worksheet that hold ballot data.
+#[derive(Debug, PartialEq)]
+struct SheetLayout {
+    cvr_col: usize,
+    races: Vec<SheetRace>,
+}
+
+impl SheetLayout {
+    /// Find the CVR id and race columns in a worksheet header, given as
+    /// `(column, name)` pairs. Returns `None` if the worksheet has no CVR id
+    /// column or no race columns, and an error if a race column's ranks
+    /// aren't between 1 and the number of columns in the header.
+    fn from_header(
+        header: &[(usize, String)],
+        patterns: &CompiledPatterns,
+    ) -> Result<Option<SheetLayout>, String> {
+        let mut cvr_col = None;
+        let mut race_columns: BTreeMap<String, (SheetRace, Vec<(u32, usize)>)> = BTreeMap::new();
+
+        for (col_idx, colname) in header {
+            if colname == "Cast Vote Record" {
+                cvr_col = Some(*col_idx);
+            } else if let Some(caps) = patterns.column_rx.captures(colname) {
+                // Ranks size each ballot's choices, so a rank that no column
+                // could hold is rejected rather than allocated for.
+                let parse_rank = |group: usize| {
+                    let rank = caps.get(group).unwrap().as_str();
+                    match rank.parse::<u32>() {
+                        Ok(rank) if rank >= 1 && rank as usize <= header.len() => Ok(rank),
+                        _ => Err(format!(
+                            "Column \"{}\" has rank {}, but ranks must be from 1 to {}, the number of columns",
+                            colname,
+                            rank,
+                            header.len()
+                        )),
+                    }
+                };
+                let office_name = caps.get(1).unwrap().as_str();
+                let rank = parse_rank(2)?;
+                let max_rank = parse_rank(3)?;
+                let jurisdiction_name = caps.get(4).unwrap().as_str();
+                let race_key = format!("{}|{}", office_name, jurisdiction_name);
+
+                let (race, rank_columns) =
+                    race_columns.entry(race_key.clone()).or_insert_with(|| {
+                        let race = SheetRace {
+                            race_key,
+                            office_name: office_name.to_string(),
+                            jurisdiction_name: jurisdiction_name.to_string(),
+                            max_rank: 0,
+                            columns: Vec::new(),
+                        };
+                        (race, Vec::new())
+                    });
+                // The number of ranks is taken from the "of M" part of the header.
+                race.max_rank = race.max_rank.max(max_rank);
+                rank_columns.push((rank, *col_idx));
+            }
+        }
+
+        if race_columns.is_empty() {
+            return Ok(None);
+        }
+
+        // Columns may appear in any order in the workbook, so order them by the
+        // rank given in the header rather than by position.
+        let races = race_columns
+            .into_values()
+            .map(|(mut race, rank_columns)| {
+                race.columns = order_columns_by_rank(&race.race_key, rank_columns, race.max_rank);
+                race
+            })
+            .collect();
+
+        Ok(cvr_col.map(|cvr_col| SheetLayout { cvr_col, races }))
+    }
+
+    /// The columns to read from each row, and this layout renumbered to
+    /// index into rows containing only those columns.
+    fn select_columns(&self) -> (Vec<usize>, SheetLayout) {
+        let mut columns = vec![self.cvr_col];
+        let races = self
+            .races
+            .iter()
+            .map(|race| SheetRace {
+                race_key: race.race_key.clone(),
+                office_name: race.office_name.clone(),
+                jurisdiction_name: race.jurisdiction_name.clone(),
+                max_rank: race.max_rank,
+                columns: race
+                    .columns
+                    .iter()
+                    .map(|col| {
+                        col.map(|col| {
+                            columns.push(col);
+                            columns.len() - 1
+                        })
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        (columns, SheetLayout { cvr_col: 0, races })
+    }
+}
+
+/// Which parser reads CVR workbooks, set by the `xlsxBackend` loader parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XlsxBackend {
+    Calamine,
+    /// The streaming parser in `formats::common`, which needs the
+    /// `streaming-xlsx` feature.
+    Streaming,
+    /// The streaming parser for xlsx files of at least `STREAMING_THRESHOLD`
+    /// bytes if the `streaming-xlsx` feature is enabled, otherwise calamine.
+    Auto,
+}
+
+/// Size from which `XlsxBackend::Auto` streams a workbook.
+const STREAMING_THRESHOLD: u64 = 256 << 20;
+
+impl XlsxBackend {
+    pub fn from_params(params: &BTreeMap<String, String>) -> XlsxBackend {
+        match params.get("xlsxBackend").map(|b| b.as_str()) {
+            None | Some("auto") => XlsxBackend::Auto,
+            Some("calamine") => XlsxBackend::Calamine,
+            Some("streaming") => {
+                if !cfg!(feature = "streaming-xlsx") {
+                    panic!("xlsxBackend \"streaming\" requires building with the streaming-xlsx feature.");
+                }
+                XlsxBackend::Streaming
+            }
+            Some(b) => panic!(
+                "Unknown xlsxBackend {:?}; expected calamine, streaming or auto.",
+                b
+            ),
+        }
+    }
+
+    /// The backend to read a particular file with.
+    fn for_file(self, path: &Path) -> XlsxBackend {
+        let is_xlsx = path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("xlsx"));
+        match self {
+            XlsxBackend::Auto if cfg!(feature = "streaming-xlsx") && is_xlsx => {
+                let size = path.metadata().map(|m| m.len()).unwrap_or(0);
+                if size >= STREAMING_THRESHOLD {
+                    XlsxBackend::Streaming
+                } else {
+                    XlsxBackend::Calamine
+                }
+            }
+            XlsxBackend::Auto => XlsxBackend::Calamine,
+            backend => backend,
+        }
+    }
+}
+
+/// An open CVR workbook.
+enum CvrWorkbook {
+    Calamine(Sheets<BufReader<File>>),
+    #[cfg(feature = "streaming-xlsx")]
+    Streaming(XlsxStream),
+}
+
+impl CvrWorkbook {
+    fn open(path: &Path, backend: XlsxBackend) -> Result<CvrWorkbook, String> {
+        match backend {
+            #[cfg(feature = "streaming-xlsx")]
+            XlsxBackend::Streaming => XlsxStream::open(path).map(CvrWorkbook::Streaming),
+            _ => open_workbook_auto(path)
+                .map(CvrWorkbook::Calamine)
+                .map_err(|e| e.to_string()),
+        }
+    }
+
+    fn sheet_names(&self) -> Vec<String> {
+        match self {
+            CvrWorkbook::Calamine(workbook) => workbook.sheet_names(),
+            #[cfg(feature = "streaming-xlsx")]
+            CvrWorkbook::Streaming(stream) => stream.sheet_names(),
+        }
+    }
+
+    /// Read the header (first used row) of a worksheet as `(column, name)`
+    /// pairs. For xlsx workbooks only the start of the worksheet is parsed.
+    fn read_header(&mut self, sheet: &str) -> Result<Vec<(usize, String)>, String> {
+        let mut header = Vec::new();
+        match self {
+            CvrWorkbook::Calamine(Sheets::Xlsx(xlsx)) => {
+                let mut cells = xlsx
+                    .worksheet_cells_reader(sheet)
+                    .map_err(|e| e.to_string())?;
+                let mut header_row = None;
+                while let Some(cell) = cells.next_cell().map_err(|e| e.to_string())? {
+                    let (row, col) = cell.get_position();
+                    if *header_row.get_or_insert(row) != row {
+                        break;
+                    }
+                    if let Some(name) = cell.get_value().get_string() {
+                        header.push((col as usize, name.to_string()));
+                    }
+                }
+            }
+            CvrWorkbook::Calamine(workbook) => {
+                let range = workbook.worksheet_range(sheet).map_err(|e| e.to_string())?;
+                if let Some(row) = range.rows().next() {
+                    for (col, cell) in row.iter().enumerate() {
+                        if let Data::String(name) = cell {
+                            header.push((col, name.clone()));
+                        }
+                    }
+                }
+            }
+            #[cfg(feature = "streaming-xlsx")]
+            CvrWorkbook::Streaming(stream) => {
+                stream.for_each_row(
+                    sheet,
+                    |_| true,
+                    |cells| {
+                        for (col, cell) in cells {
+                            if let Data::String(name) = cell {
+                                header.push((*col, name.clone()));
+                            }
+                        }
+                        false
+                    },
+                )?;
+            }
+        }
+        Ok(header)
+    }
+
+    /// Call `on_row` for each row after the header with the values of
+    /// `columns`, in that order. For xlsx workbooks cells are streamed from
+    /// the worksheet and other columns are discarded as they are read, so the
+    /// very wide NYC worksheets are never held in memory in full. Other
+    /// formats fall back to reading the whole worksheet.
+    fn read_rows(
+        &mut self,
+        sheet: &str,
+        columns: &[usize],
+        mut on_row: impl FnMut(&[Data]),
+    ) -> Result<(), String> {
+        let mut values = vec![Data::Empty; columns.len()];
+        let mut slots = vec![None; columns.iter().max().map_or(0, |c| c + 1)];
+        for (slot, &col) in columns.iter().enumerate() {
+            slots[col] = Some(slot);
+        }
+
+        match self {
+            CvrWorkbook::Calamine(Sheets::Xlsx(xlsx)) => {
+                let mut cells = xlsx
+                    .worksheet_cells_reader(sheet)
+                    .map_err(|e| e.to_string())?;
+                let mut header_row = None;
+                let mut current_row = None;
+                while let Some(cell) = cells.next_cell().map_err(|e| e.to_string())? {
+                    let (row, col) = cell.get_position();
+                    if *header_row.get_or_insert(row) == row {
+                        continue;
+                    }
+                    if current_row != Some(row) {
+                        if current_row.is_some() {
+                            on_row(&values);
+                            values.iter_mut().for_each(|v| *v = Data::Empty);
+                        }
+                        current_row = Some(row);
+                    }
+                    if let Some(Some(slot)) = slots.get(col as usize) {
+                        values[*slot] = cell.get_value().clone().into();
+                    }
+                }
+                if current_row.is_some() {
+                    on_row(&values);
+                }
+            }
+            CvrWorkbook::Calamine(workbook) => {
+                let range = workbook.worksheet_range(sheet).map_err(|e| e.to_string())?;
+                for row in range.rows().skip(1) {
+                    for (value, col) in values.iter_mut().zip(columns) {
+                        *value = row.get(*col).cloned().unwrap_or(Data::Empty);
+                    }
+                    on_row(&values);
+                }
+            }
+            #[cfg(feature = "streaming-xlsx")]
+            CvrWorkbook::Streaming(stream) => {
+                let mut is_header = true;
+                stream.for_each_row(
+                    sheet,
+                    |col| slots.get(col).is_some_and(|slot| slot.is_some()),
+                    |cells| {
+                        if std::mem::take(&mut is_header) {
+                            return true;
+                        }
+                        values.iter_mut().for_each(|v| *v = Data::Empty);
+                        for (col, cell) in cells {
+                            if let Some(Some(slot)) = slots.get(*col) {
+                                values[*slot] = cell.clone();
+                            }
+                        }
+                        on_row(&values);
+                        true
+                    },
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Look up a candidacy ID, returning `None` (and recording the ID as
+/// unknown) if it is not in the candidacy file.
+fn candidate_choice(
+    ext_id: u32,
+    candidates: &HashMap<u32, String>,
+    candidate_map: &mut CandidateMap<NycCandidateKey>,
+) -> Option<Choice> {
+    let Some(candidate_name) = candidates.get(&ext_id) else {
+        candidate_map.record_unknown(ext_id.to_string());
+        return None;
+    };
+    Some(candidate_map.add_id_to_choice(
+        NycCandidateKey::Id(ext_id),
+        Candidate::new(candidate_name.clone(), CandidateType::Regular),
+    ))
+}
+
+/// Read a ballot's choices in one race from the cells of its ranked columns.
+/// Returns `None` if the ballot has no votes in the race.
+fn read_race_choices(
+    row: &[Data],
+    columns: &[Option<usize>],
+    candidates: &HashMap<u32, String>,
+    candidate_map: &mut CandidateMap<NycCandidateKey>,
+) -> Option<Vec<Choice>> {
+    let mut choices = Vec::with_capacity(columns.len());
+    let mut has_votes = false;
+
+    for col_idx in columns {
+        let choice = match col_idx.and_then(|c| row.get(c)) {
+            Some(Data::String(s)) => match s.as_str() {
+                "undervote" => None,
+                "overvote" => Some(Choice::Overvote),
+                "Write-in" => Some(candidate_map.add_id_to_choice(
+                    NycCandidateKey::WriteIn,
+                    Candidate::new("Write-in".to_string(), CandidateType::WriteIn),
+                )),
+                _ => match s.parse::<u32>() {
+                    Ok(ext_id) => candidate_choice(ext_id, candidates, candidate_map),
+                    Err(_) if s.trim().is_empty() => None,
+                    Err(_) => {
+                        candidate_map.record_unknown(s.clone());
+                        None
+                    }
+                },
+            },
+            Some(Data::Float(f)) => candidate_choice(*f as u32, candidates, candidate_map),
+            Some(Data::Int(i)) => candidate_choice(*i as u32, candidates, candidate_map),
+            _ => None,
+        };
+        has_votes |= choice.is_some();
+        choices.push(choice.unwrap_or(Choice::Undervote));
+    }
+
+    if has_votes {
+        Some(choices)
+    } else {
+        None
+    }
+}
+
 /// Process all files with on-the-fly race discovery
 fn process_files_with_race_discovery(
-    file_paths: &[(std::path::PathBuf, String)],
+    file_paths: &[(std::path::PathBuf, String, XlsxBackend)],
     patterns: &CompiledPatterns,
     candidates: &HashMap<u32, String>,
     races: &mut HashMap<String, RaceMetadata>,
-    race_candidate_maps: &mut HashMap<String, CandidateMap<u32>>,
+    race_candidate_maps: &mut HashMap<String, CandidateMap<NycCandidateKey>>,
     ballots: &mut Vec<RaceBallotVote>,
     ballots_by_race: &mut HashMap<String, Vec<usize>>,
 ) {
-    for (file_idx, (file_path, filename)) in file_paths.iter().enumerate() {
-        crate::log_debug!("  📊 [{}/{}] {}", file_idx + 1, file_paths.len(), filename);
+    for (file_idx, (file_path, filename, backend)) in file_paths.iter().enumerate() {
+        crate::log_debug!(
+            "  📊 [{}/{}] {} ({:?})",
+            file_idx + 1,
+            file_paths.len(),
+            filename,
+            backend
+        );
 
         let file_start = Instant::now();
-        let mut workbook = open_workbook_auto(file_path).unwrap_or_else(|e| {
+        let mut workbook = CvrWorkbook::open(file_path, *backend).unwrap_or_else(|e| {
             panic!(
                 "❌ Failed to open CVR file '{}': {}\n   Please ensure the file exists and is readable.",
                 file_path.display(),
                 e
             );
         });
-        let first_sheet = workbook.sheet_names().first().unwrap().clone();
-        let range = workbook.worksheet_range(&first_sheet).unwrap();
-
-        // First, scan header to discover races in this file
-        let header_row = range.rows().next().unwrap();
-        let mut cvr_id_col = None;
-        let mut file_race_columns: HashMap<String, Vec<usize>> = HashMap::new();
-
-        for (col_idx, cell) in header_row.iter().enumerate() {
-            if let Data::String(colname) = cell {
-                if colname == "Cast Vote Record" {
-                    cvr_id_col = Some(col_idx);
-                } else if let Some(caps) = patterns.column_rx.captures(colname) {
-                    let office_name = caps.get(1).unwrap().as_str();
-                    let jurisdiction_name = caps.get(4).unwrap().as_str();
-                    let race_key = format!("{}|{}", office_name, jurisdiction_name);
-
-                    // Add race if not seen before
-                    if !races.contains_key(&race_key) {
-                        races.insert(
-                            race_key.clone(),
-                            RaceMetadata {
-                                race_key: race_key.clone(),
-                                office_name: office_name.to_string(),
-                                jurisdiction_name: jurisdiction_name.to_string(),
-                                column_indices: Vec::new(),
-                                max_rank: 5,
-                            },
-                        );
-                        race_candidate_maps.insert(race_key.clone(), CandidateMap::new());
-                        ballots_by_race.insert(race_key.clone(), Vec::new());
-                    }
 
-                    file_race_columns
-                        .entry(race_key)
-                        .or_insert_with(Vec::new)
-                        .push(col_idx);
-                }
-            }
-        }
-
-        let Some(cvr_col) = cvr_id_col else {
-            crate::log_warn!("    ⚠️  No CVR ID column found, skipping file");
-            continue;
-        };
-
-        // Process rows directly without collect() - major performance gain
         let mut processed_count = 0;
+        let mut found_cvr_sheet = false;
+
+        for sheet in workbook.sheet_names() {
+            let read_error = |e: String| -> ! {
+                panic!(
+                    "❌ Failed to read worksheet '{}' in '{}': {}",
+                    sheet,
+                    file_path.display(),
+                    e
+                );
+            };
 
-        for row in range.rows().skip(1) {
-            if let Some(ballot_id_cell) = row.get(cvr_col) {
-                if let Data::String(ballot_id) = ballot_id_cell {
-                    // Process each race for this ballot
-                    for (race_key, race_columns) in &file_race_columns {
-                        if races.contains_key(race_key) {
-                            let mut choices = Vec::with_capacity(race_columns.len());
-                            let mut has_votes = false;
-
-                            // Inline cell processing for maximum speed
-                            for &col_idx in race_columns {
-                                let choice = match row.get(col_idx) {
-                                    Some(cell) => match cell {
-                                        Data::String(s) => match s.as_str() {
-                                            "undervote" => Choice::Undervote,
-                                            "overvote" => {
-                                                has_votes = true;
-                                                Choice::Overvote
-                                            }
-                                            "Write-in" => {
-                                                has_votes = true;
-                                                race_candidate_maps
-                                                    .get_mut(race_key)
-                                                    .unwrap()
-                                                    .add_id_to_choice(
-                                                        0,
-                                                        Candidate::new(
-                                                            "Write-in".to_string(),
-                                                            CandidateType::WriteIn,
-                                                        ),
-                                                    )
-                                            }
-                                            _ => {
-                                                if let Ok(ext_id) = s.parse::<u32>() {
-                                                    if let Some(candidate_name) =
-                                                        candidates.get(&ext_id)
-                                                    {
-                                                        has_votes = true;
-                                                        race_candidate_maps
-                                                            .get_mut(race_key)
-                                                            .unwrap()
-                                                            .add_id_to_choice(
-                                                                ext_id,
-                                                                Candidate::new(
-                                                                    candidate_name.clone(),
-                                                                    CandidateType::Regular,
-                                                                ),
-                                                            )
-                                                    } else {
-                                                        Choice::Undervote
-                                                    }
-                                                } else {
-                                                    Choice::Undervote
-                                                }
-                                            }
-                                        },
-                                        Data::Float(f) => {
-                                            let ext_id = *f as u32;
-                                            if let Some(candidate_name) = candidates.get(&ext_id) {
-                                                has_votes = true;
-                                                race_candidate_maps
-                                                    .get_mut(race_key)
-                                                    .unwrap()
-                                                    .add_id_to_choice(
-                                                        ext_id,
-                                                        Candidate::new(
-                                                            candidate_name.clone(),
-                                                            CandidateType::Regular,
-                                                        ),
-                                                    )
-                                            } else {
-                                                Choice::Undervote
-                                            }
-                                        }
-                                        Data::Int(i) => {
-                                            let ext_id = *i as u32;
-                                            if let Some(candidate_name) = candidates.get(&ext_id) {
-                                                has_votes = true;
-                                                race_candidate_maps
-                                                    .get_mut(race_key)
-                                                    .unwrap()
-                                                    .add_id_to_choice(
-                                                        ext_id,
-                                                        Candidate::new(
-                                                            candidate_name.clone(),
-                                                            CandidateType::Regular,
-                                                        ),
-                                                    )
-                                            } else {
-                                                Choice::Undervote
-                                            }
-                                        }
-                                        _ => Choice::Undervote,
-                                    },
-                                    None => Choice::Undervote,
-                                };
-                                choices.push(choice);
-                            }
+            // Scan the header to discover races in this worksheet
+            let header = workbook
+                .read_header(&sheet)
+                .unwrap_or_else(|e| read_error(e));
+            let layout =
+                SheetLayout::from_header(&header, patterns).unwrap_or_else(|e| read_error(e));
+            let Some(layout) = layout else {
+                crate::log_debug!("    Skipping worksheet {} without CVR columns", sheet);
+                continue;
+            };
+            found_cvr_sheet = true;
+
+            for race in &layout.races {
+                // Add race if not seen before
+                let metadata = races.entry(race.race_key.clone()).or_insert_with(|| {
+                    race_candidate_maps.insert(race.race_key.clone(), CandidateMap::new());
+                    ballots_by_race.insert(race.race_key.clone(), Vec::new());
+                    RaceMetadata {
+                        race_key: race.race_key.clone(),
+                        office_name: race.office_name.clone(),
+                        jurisdiction_name: race.jurisdiction_name.clone(),
+                        column_indices: Vec::new(),
+                        max_rank: race.max_rank,
+                    }
+                });
+                metadata.max_rank = metadata.max_rank.max(race.max_rank);
+            }
 
-                            // Only store ballots with actual votes
-                            if has_votes {
-                                let ballot_index = ballots.len();
-                                ballots.push(RaceBallotVote {
-                                    ballot_id: ballot_id.to_string(),
-                                    race_key: race_key.clone(),
-                                    choices,
-                                });
-
-                                ballots_by_race
-                                    .get_mut(race_key)
-                                    .unwrap()
-                                    .push(ballot_index);
-                            }
+            // Only the CVR id and race columns are read from each row
+            let (columns, layout) = layout.select_columns();
+            workbook
+                .read_rows(&sheet, &columns, |row| {
+                    let ballot_id = match row.get(layout.cvr_col) {
+                        Some(Data::String(ballot_id)) => ballot_id,
+                        _ => return,
+                    };
+
+                    for race in &layout.races {
+                        let candidate_map = race_candidate_maps.get_mut(&race.race_key).unwrap();
+                        // Only store ballots with actual votes
+                        if let Some(choices) =
+                            read_race_choices(row, &race.columns, candidates, candidate_map)
+                        {
+                            let ballot_index = ballots.len();
+                            ballots.push(RaceBallotVote {
+                                ballot_id: ballot_id.to_string(),
+                                race_key: race.race_key.clone(),
+                                choices,
+                            });
+
+                            ballots_by_race
+                                .get_mut(&race.race_key)
+                                .unwrap()
+                                .push(ballot_index);
                         }
                     }
 
@@ -458,8 +850,13 @@ fn process_files_with_race_discovery(
                     if processed_count % 25000 == 0 {
                         crate::log_trace!("\r    ⏳ {} rows...", processed_count);
                     }
-                }
-            }
+                })
+                .unwrap_or_else(|e| read_error(e));
+        }
+
+        if !found_cvr_sheet {
+            crate::log_warn!("    ⚠️  No CVR ID column found, skipping file");
+            continue;
         }
 
         let file_duration = file_start.elapsed();
@@ -470,3 +867,93 @@ fn process_files_with_race_discovery(
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_columns_by_rank() {
+        let columns = order_columns_by_rank("race", vec![(3, 10), (1, 12), (2, 11)], 3);
+        assert_eq!(vec![Some(12), Some(11), Some(10)], columns);
+    }
+
+    #[test]
+    fn test_order_columns_missing_rank() {
+        let columns = order_columns_by_rank("race", vec![(3, 10), (1, 12)], 3);
+        assert_eq!(vec![Some(12), None, Some(10)], columns);
+    }
+
+    #[test]
+    fn test_order_columns_duplicate_rank() {
+        let columns = order_columns_by_rank("race", vec![(1, 12), (1, 13), (2, 14)], 2);
+        assert_eq!(vec![Some(12), Some(14)], columns);
+    }
+
+    #[test]
+    fn test_order_columns_declared_max_rank() {
+        let columns = order_columns_by_rank("race", vec![(1, 12), (2, 14)], 4);
+        assert_eq!(vec![Some(12), Some(14), None, None], columns);
+    }
+
+    fn header(names: &[(usize, &str)]) -> Vec<(usize, String)> {
+        names.iter().map(|(c, n)| (*c, n.to_string())).collect()
+    }
+
+    #[test]
+    fn test_sheet_layout_select_columns() {
+        let patterns = CompiledPatterns::new(".*");
+        let layout = SheetLayout::from_header(
+            &header(&[
+                (0, "Cast Vote Record"),
+                (3, "Precinct"),
+                (7, "Mayor Choice 2 of 3 Citywide (026916)"),
+                (5, "Mayor Choice 1 of 3 Citywide (026916)"),
+                (9, "Mayor Choice 3 of 3 Citywide (026916)"),
+            ]),
+            &patterns,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(vec![Some(5), Some(7), Some(9)], layout.races[0].columns);
+
+        let (columns, selected) = layout.select_columns();
+        assert_eq!(vec![0, 5, 7, 9], columns);
+        assert_eq!(0, selected.cvr_col);
+        assert_eq!(vec![Some(1), Some(2), Some(3)], selected.races[0].columns);
+    }
+
+    #[test]
+    fn test_sheet_layout_requires_cvr_columns() {
+        let patterns = CompiledPatterns::new(".*");
+        assert_eq!(
+            Ok(None),
+            SheetLayout::from_header(&header(&[(0, "Cast Vote Record")]), &patterns)
+        );
+        assert_eq!(
+            Ok(None),
+            SheetLayout::from_header(
+                &header(&[(1, "Mayor Choice 1 of 1 Citywide (026916)")]),
+                &patterns
+            )
+        );
+    }
+
+    #[test]
+    fn test_sheet_layout_rejects_bad_ranks() {
+        let patterns = CompiledPatterns::new(".*");
+        let layout = |name: &str| {
+            SheetLayout::from_header(
+                &header(&[(0, "Cast Vote Record"), (1, name), (2, "Precinct")]),
+                &patterns,
+            )
+        };
+        assert!(layout("Mayor Choice 1 of 2 Citywide (026916)").is_ok());
+        assert!(layout("Mayor Choice 0 of 2 Citywide (026916)")
+            .unwrap_err()
+            .contains("rank 0"));
+        // The number of ranks can't exceed the columns that could hold them.
+        assert!(layout("Mayor Choice 1 of 4000000000 Citywide (026916)").is_err());
+        assert!(layout("Mayor Choice 1 of 99999999999 Citywide (026916)").is_err());
+    }
+}
@@ -1,5 +1,7 @@
 mod efficient_reader;
 
+use efficient_reader::XlsxBackend;
+
 use crate::model::election::Election;
 use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
@@ -23,6 +25,8 @@ pub fn nyc_batch_reader(
         .get("cvrPattern")
         .expect("us_ny_nyc elections should have cvrPattern parameter.");
 
+    let backend = XlsxBackend::from_params(first_params);
+
     // Verify all contests share the same parameters
     let same_params = contests.iter().all(|(_, params)| {
         params.get("candidatesFile") == Some(candidates_file)
@@ -37,7 +41,8 @@ pub fn nyc_batch_reader(
     }
 
     // Parse all files once using efficient_reader
-    let ballot_db = efficient_reader::read_all_nyc_data(path, candidates_file, cvr_pattern);
+    let ballot_db =
+        efficient_reader::read_all_nyc_data(path, candidates_file, cvr_pattern, backend);
 
     // Map race keys to contest office IDs
     let mut elections_by_office: HashMap<String, Election> = HashMap::new();
@@ -1,9 +1,9 @@
 use crate::model::election::{Ballot, Candidate, CandidateId, CandidateType, Choice, Election};
+use crate::util::open_raw;
 use regex::Regex;
 use std::collections::BTreeMap;
-use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 struct ReaderOptions {
     ballots: String,
@@ -45,38 +45,37 @@ pub fn parse_ballot(source: &str) -> Vec<Choice> {
     choices
 }
 
-pub fn btv_ballot_reader(path: &Path, params: BTreeMap<String, String>) -> Election {
-    let options = ReaderOptions::from_params(params);
-
-    // Try multiple path variations to handle archive extraction
-    let mut ballots_path = path.join(&options.ballots);
-    
-    // If the file doesn't exist and we have an archive parameter, try prepending the archive directory name
-    if !ballots_path.exists() {
-        if let Some(ref archive) = options.archive {
-            // Remove .zip extension if present to get the directory name
-            let archive_dir = if archive.ends_with(".zip") {
-                &archive[..archive.len() - 4]
-            } else {
-                archive
-            };
-            // Try: archive_dir/ballots_path
-            let alternative_path = path.join(archive_dir).join(&options.ballots);
-            if alternative_path.exists() {
-                ballots_path = alternative_path;
-            } else {
-                // Try: archive_dir/filename (if ballots_path has a filename component)
-                if let Some(filename) = Path::new(&options.ballots).file_name() {
-                    let alternative_path2 = path.join(archive_dir).join(filename);
-                    if alternative_path2.exists() {
-                        ballots_path = alternative_path2;
-                    }
-                }
+/// Path of the ballots file. If it doesn't exist and the election has an
+/// archive, the directory the archive was extracted to is tried too.
+pub(super) fn ballots_path(path: &Path, ballots: &str, archive: Option<&str>) -> PathBuf {
+    let ballots_path = path.join(ballots);
+    if ballots_path.exists() {
+        return ballots_path;
+    }
+    if let Some(archive) = archive {
+        // Remove .zip extension if present to get the directory name
+        let archive_dir = archive.strip_suffix(".zip").unwrap_or(archive);
+        // Try: archive_dir/ballots_path
+        let alternative_path = path.join(archive_dir).join(ballots);
+        if alternative_path.exists() {
+            return alternative_path;
+        }
+        // Try: archive_dir/filename (if ballots_path has a filename component)
+        if let Some(filename) = Path::new(ballots).file_name() {
+            let alternative_path2 = path.join(archive_dir).join(filename);
+            if alternative_path2.exists() {
+                return alternative_path2;
             }
         }
     }
-    
-    let file = match File::open(&ballots_path) {
+    ballots_path
+}
+
+pub fn btv_ballot_reader(path: &Path, params: BTreeMap<String, String>) -> Election {
+    let options = ReaderOptions::from_params(params);
+
+    let ballots_path = ballots_path(path, &options.ballots, options.archive.as_deref());
+    let file = match open_raw(&ballots_path) {
         Ok(file) => file,
         Err(e) => {
             crate::log_warn!(
@@ -114,10 +113,7 @@ pub fn btv_ballot_reader(path: &Path, params: BTreeMap<String, String>) -> Elect
         }
     }
 
-    Election {
-        candidates,
-        ballots,
-    }
+    Election::new(candidates, ballots)
 }
 
 #[cfg(test)]
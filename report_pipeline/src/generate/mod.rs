@@ -0,0 +1,359 @@
+//! Synthetic elections, for benchmarks, fuzzing and demonstrating reports
+//! without real data. Ballots are drawn from a preference model over the
+//! candidates, then truncated and marked with skipped ranks and overvotes.
+//! Generation is deterministic for a given seed.
+
+mod writers;
+
+pub use writers::{write_raw, write_us_ny_nyc};
+
+use crate::model::election::{Ballot, Candidate, CandidateId, CandidateType, Choice, Election};
+
+/// Xorshift generator, so that generated elections don't depend on a random
+/// number crate's algorithm and are reproducible from their seed.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        // Xorshift gets stuck at zero, and needs a few rounds to mix small
+        // seeds.
+        let mut rng = Rng(seed ^ 0x9e37_79b9_7f4a_7c15);
+        for _ in 0..4 {
+            rng.next_u64();
+        }
+        rng
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number in `0..n`.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// A number in `[0, 1)`.
+    pub fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// True with the given probability.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        self.unit() < probability
+    }
+
+    /// An index into `weights`, chosen with probability proportional to its
+    /// weight.
+    fn weighted(&mut self, weights: &[f64]) -> usize {
+        let total: f64 = weights.iter().sum();
+        let mut target = self.unit() * total;
+        for (i, weight) in weights.iter().enumerate() {
+            if target < *weight {
+                return i;
+            }
+            target -= weight;
+        }
+        weights.len() - 1
+    }
+}
+
+/// How voters' rankings of the candidates are distributed. Candidates are
+/// numbered in order of popularity.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PreferenceModel {
+    /// Every ranking is equally likely.
+    Uniform,
+    /// Plackett-Luce: each rank is filled by one of the remaining candidates
+    /// with probability proportional to its weight, which falls by the given
+    /// factor (between 0 and 1) from each candidate to the next.
+    PlackettLuce { decay: f64 },
+    /// Mallows: the probability of a ranking falls by the given dispersion
+    /// factor (between 0 and 1) for each pair of candidates it orders
+    /// differently from the order of popularity. 0 always gives that order,
+    /// and 1 is uniform.
+    Mallows { dispersion: f64 },
+}
+
+impl PreferenceModel {
+    /// Parse a model such as `uniform`, `plackett-luce:0.8` or `mallows:0.5`.
+    pub fn parse(model: &str) -> Result<PreferenceModel, String> {
+        let (name, parameter) = split_parameter(model)?;
+        match (name, parameter) {
+            ("uniform", None) => Ok(PreferenceModel::Uniform),
+            ("plackett-luce", Some(decay)) if decay > 0. && decay <= 1. => {
+                Ok(PreferenceModel::PlackettLuce { decay })
+            }
+            ("mallows", Some(dispersion)) if (0. ..=1.).contains(&dispersion) => {
+                Ok(PreferenceModel::Mallows { dispersion })
+            }
+            _ => Err(format!(
+                "Invalid preference model: {}; expected uniform, plackett-luce:DECAY or mallows:DISPERSION",
+                model
+            )),
+        }
+    }
+
+    /// Draw a full ranking of `num_candidates` candidates.
+    pub fn ranking(&self, rng: &mut Rng, num_candidates: usize) -> Vec<usize> {
+        match self {
+            PreferenceModel::Uniform => {
+                let mut ranking: Vec<usize> = (0..num_candidates).collect();
+                for i in (1..num_candidates).rev() {
+                    ranking.swap(i, rng.below(i + 1));
+                }
+                ranking
+            }
+            PreferenceModel::PlackettLuce { decay } => {
+                let mut remaining: Vec<usize> = (0..num_candidates).collect();
+                let mut weights: Vec<f64> =
+                    (0..num_candidates).map(|i| decay.powi(i as i32)).collect();
+                let mut ranking = Vec::with_capacity(num_candidates);
+                while !remaining.is_empty() {
+                    let i = rng.weighted(&weights);
+                    ranking.push(remaining.remove(i));
+                    weights.remove(i);
+                }
+                ranking
+            }
+            PreferenceModel::Mallows { dispersion } => {
+                // Repeated insertion: the i-th most popular candidate is
+                // inserted j places above the bottom of the ranking with
+                // probability proportional to dispersion^j.
+                let mut ranking = Vec::with_capacity(num_candidates);
+                for candidate in 0..num_candidates {
+                    let weights: Vec<f64> =
+                        (0..=candidate).map(|j| dispersion.powi(j as i32)).collect();
+                    let places_up = rng.weighted(&weights);
+                    ranking.insert(candidate - places_up, candidate);
+                }
+                ranking
+            }
+        }
+    }
+}
+
+/// How many ranks voters mark, out of those on the ballot.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Truncation {
+    /// Every rank is marked.
+    Full,
+    /// Any number of ranks, from one to all of them, is equally likely.
+    Uniform,
+    /// After each marked rank, the voter stops with the given probability.
+    Geometric { stop: f64 },
+    /// The given number of ranks is marked.
+    Fixed { ranks: usize },
+}
+
+impl Truncation {
+    /// Parse a truncation such as `full`, `uniform`, `geometric:0.3` or
+    /// `fixed:3`.
+    pub fn parse(truncation: &str) -> Result<Truncation, String> {
+        let (name, parameter) = split_parameter(truncation)?;
+        match (name, parameter) {
+            ("full", None) => Ok(Truncation::Full),
+            ("uniform", None) => Ok(Truncation::Uniform),
+            ("geometric", Some(stop)) if (0. ..=1.).contains(&stop) => {
+                Ok(Truncation::Geometric { stop })
+            }
+            ("fixed", Some(ranks)) if ranks >= 1. && ranks.fract() == 0. => Ok(Truncation::Fixed {
+                ranks: ranks as usize,
+            }),
+            _ => Err(format!(
+                "Invalid truncation: {}; expected full, uniform, geometric:STOP or fixed:RANKS",
+                truncation
+            )),
+        }
+    }
+
+    /// Draw the number of ranks a voter marks, given the number available.
+    fn ranks_marked(&self, rng: &mut Rng, available: usize) -> usize {
+        let marked = match self {
+            Truncation::Full => available,
+            Truncation::Uniform => 1 + rng.below(available.max(1)),
+            Truncation::Geometric { stop } => {
+                let mut marked = 1;
+                while marked < available && !rng.chance(*stop) {
+                    marked += 1;
+                }
+                marked
+            }
+            Truncation::Fixed { ranks } => *ranks,
+        };
+        marked.min(available)
+    }
+}
+
+fn split_parameter(spec: &str) -> Result<(&str, Option<f64>), String> {
+    match spec.split_once(':') {
+        Some((name, parameter)) => parameter
+            .trim()
+            .parse()
+            .map(|p| (name.trim(), Some(p)))
+            .map_err(|_| format!("Invalid parameter in {}", spec)),
+        None => Ok((spec.trim(), None)),
+    }
+}
+
+/// Parameters of a synthetic election.
+#[derive(Clone, Debug)]
+pub struct GeneratorOptions {
+    pub num_ballots: usize,
+    pub num_candidates: usize,
+    /// Number of ranks on the ballot.
+    pub ranks: usize,
+    pub model: PreferenceModel,
+    pub truncation: Truncation,
+    /// Probability that a voter leaves a rank blank before marking their next
+    /// choice.
+    pub skip_rate: f64,
+    /// Probability that a marked rank is overvoted.
+    pub overvote_rate: f64,
+    pub seed: u64,
+}
+
+impl Default for GeneratorOptions {
+    fn default() -> Self {
+        GeneratorOptions {
+            num_ballots: 1000,
+            num_candidates: 5,
+            ranks: 5,
+            model: PreferenceModel::PlackettLuce { decay: 0.8 },
+            truncation: Truncation::Geometric { stop: 0.3 },
+            skip_rate: 0.01,
+            overvote_rate: 0.005,
+            seed: 1,
+        }
+    }
+}
+
+/// The marks on one synthetic ballot, at most `options.ranks` long.
+pub fn generate_choices(rng: &mut Rng, options: &GeneratorOptions) -> Vec<Choice> {
+    let ranking = options.model.ranking(rng, options.num_candidates);
+    let available = options.ranks.min(options.num_candidates);
+    let marked = options.truncation.ranks_marked(rng, available);
+
+    let mut choices = Vec::with_capacity(options.ranks);
+    for candidate in ranking.into_iter().take(marked) {
+        if choices.len() + 1 < options.ranks && rng.chance(options.skip_rate) {
+            choices.push(Choice::Undervote);
+        }
+        if choices.len() == options.ranks {
+            break;
+        }
+        choices.push(if rng.chance(options.overvote_rate) {
+            Choice::Overvote
+        } else {
+            Choice::Vote(CandidateId(candidate as u32))
+        });
+    }
+    choices
+}
+
+/// Generate a synthetic election. Candidates are named `Candidate 1`,
+/// `Candidate 2`, ... in order of popularity.
+pub fn generate_election(options: &GeneratorOptions) -> Election {
+    let mut rng = Rng::new(options.seed);
+    let candidates = (0..options.num_candidates)
+        .map(|i| Candidate::new(format!("Candidate {}", i + 1), CandidateType::Regular))
+        .collect();
+    let ballots = (0..options.num_ballots)
+        .map(|i| Ballot::new((i + 1).to_string(), generate_choices(&mut rng, options)))
+        .collect();
+    Election::new(candidates, ballots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_options() {
+        assert_eq!(
+            Ok(PreferenceModel::Uniform),
+            PreferenceModel::parse("uniform")
+        );
+        assert_eq!(
+            Ok(PreferenceModel::Mallows { dispersion: 0.5 }),
+            PreferenceModel::parse("mallows:0.5")
+        );
+        assert!(PreferenceModel::parse("plackett-luce").is_err());
+        assert!(PreferenceModel::parse("mallows:2").is_err());
+
+        assert_eq!(
+            Ok(Truncation::Geometric { stop: 0.25 }),
+            Truncation::parse("geometric:0.25")
+        );
+        assert_eq!(
+            Ok(Truncation::Fixed { ranks: 3 }),
+            Truncation::parse("fixed:3")
+        );
+        assert!(Truncation::parse("fixed:1.5").is_err());
+    }
+
+    #[test]
+    fn test_preference_models() {
+        let mut rng = Rng::new(7);
+        assert_eq!(
+            vec![0, 1, 2, 3],
+            PreferenceModel::Mallows { dispersion: 0. }.ranking(&mut rng, 4)
+        );
+
+        for model in [
+            PreferenceModel::Uniform,
+            PreferenceModel::PlackettLuce { decay: 0.5 },
+            PreferenceModel::Mallows { dispersion: 0.7 },
+        ] {
+            let mut ranking = model.ranking(&mut rng, 6);
+            ranking.sort();
+            assert_eq!(vec![0, 1, 2, 3, 4, 5], ranking);
+        }
+
+        // The most popular candidate should lead first choices.
+        let model = PreferenceModel::PlackettLuce { decay: 0.5 };
+        let first_choices = (0..1000)
+            .filter(|_| model.ranking(&mut rng, 4)[0] == 0)
+            .count();
+        assert!(first_choices > 400, "{}", first_choices);
+    }
+
+    #[test]
+    fn test_generate_election() {
+        let options = GeneratorOptions {
+            num_ballots: 200,
+            num_candidates: 6,
+            ranks: 3,
+            truncation: Truncation::Full,
+            skip_rate: 0.,
+            overvote_rate: 0.,
+            ..GeneratorOptions::default()
+        };
+        let election = generate_election(&options);
+        assert_eq!(6, election.candidates.len());
+        assert_eq!(200, election.ballots.len());
+        assert!(election.ballots.iter().all(
+            |b| b.choices.len() == 3 && b.choices.iter().all(|c| matches!(c, Choice::Vote(_)))
+        ));
+
+        let again = generate_election(&options);
+        assert!(election
+            .ballots
+            .iter()
+            .zip(&again.ballots)
+            .all(|(a, b)| a.choices == b.choices));
+
+        let marked = generate_election(&GeneratorOptions {
+            skip_rate: 0.2,
+            overvote_rate: 0.2,
+            ..options
+        });
+        let choices = marked.ballots.iter().flat_map(|b| &b.choices);
+        assert!(choices.clone().any(|c| *c == Choice::Undervote));
+        assert!(choices.clone().any(|c| *c == Choice::Overvote));
+        assert!(marked.ballots.iter().all(|b| b.choices.len() <= 3));
+    }
+}
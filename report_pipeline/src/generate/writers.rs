@@ -0,0 +1,516 @@
+use crate::model::election::{Choice, Election};
+use rust_xlsxwriter::{Workbook, XlsxError};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Ranks in the Minneapolis and Maine formats.
+const THREE_RANKS: usize = 3;
+
+fn create(path: &Path) -> io::Result<BufWriter<File>> {
+    Ok(BufWriter::new(File::create(path)?))
+}
+
+fn xlsx_error(e: XlsxError) -> io::Error {
+    io::Error::other(e)
+}
+
+fn params(params: &[(&str, &str)]) -> BTreeMap<String, String> {
+    params
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+/// Write an election's ballots to `dir` in the raw format of one of the
+/// readers, returning the loader parameters to read them back with.
+pub fn write_raw(
+    format: &str,
+    election: &Election,
+    dir: &Path,
+) -> io::Result<BTreeMap<String, String>> {
+    match format {
+        "simple_json" => {
+            write_simple_json(election, dir)?;
+            Ok(params(&[("file", "ballots.json")]))
+        }
+        "us_mn_mpls" => {
+            write_us_mn_mpls(election, dir)?;
+            Ok(params(&[("file", "cvr.csv")]))
+        }
+        "us_ca_sfo" => {
+            write_us_ca_sfo(election, dir)?;
+            Ok(params(&[
+                ("contest", "1"),
+                ("masterLookup", "MasterLookup.txt"),
+                ("ballotImage", "BallotImage.txt"),
+            ]))
+        }
+        "us_vt_btv" => {
+            write_us_vt_btv(election, dir)?;
+            Ok(params(&[("ballots", "ballots.txt")]))
+        }
+        "dominion_rcr" => {
+            write_dominion_rcr(election, dir)?;
+            Ok(params(&[("rcr", "ballots.rcr")]))
+        }
+        "nist_sp_1500" => {
+            write_nist_sp_1500(election, dir)?;
+            Ok(params(&[("contest", "1"), ("cvr", ".")]))
+        }
+        "us_me" => {
+            write_us_me(election, dir)?;
+            Ok(params(&[("files", "cvr.xlsx")]))
+        }
+        "us_ny_nyc" => {
+            write_us_ny_nyc(&[("Synthetic Office", election)], dir)?;
+            Ok(params(&[
+                ("candidatesFile", "candidates.xlsx"),
+                ("cvrPattern", r"cvr_\d+\.xlsx"),
+                ("officeName", "Synthetic Office"),
+                ("jurisdictionName", "Citywide"),
+            ]))
+        }
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Can't generate ballots in format {}", format),
+        )),
+    }
+}
+
+/// Number of ranks on the ballot: the most marked on any ballot.
+fn num_ranks(election: &Election) -> usize {
+    election
+        .ballots
+        .iter()
+        .map(|b| b.choices.len())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Choice at a rank of a ballot, counting ranks past its last mark as blank.
+fn rank(choices: &[Choice], rank: usize) -> Choice {
+    choices.get(rank).copied().unwrap_or(Choice::Undervote)
+}
+
+/// `ballots.json`, with `under` and `over` for blank and overvoted ranks.
+fn write_simple_json(election: &Election, dir: &Path) -> io::Result<()> {
+    let ballots: Vec<_> = election
+        .ballots
+        .iter()
+        .map(|ballot| {
+            let votes: Vec<&str> = ballot
+                .choices
+                .iter()
+                .map(|choice| match choice {
+                    Choice::Vote(c) => election.candidates[c.0 as usize].name.as_str(),
+                    Choice::Undervote => "under",
+                    Choice::Overvote => "over",
+                })
+                .collect();
+            json!({ "id": ballot.id, "votes": votes })
+        })
+        .collect();
+    serde_json::to_writer(
+        create(&dir.join("ballots.json"))?,
+        &json!({ "ballots": ballots }),
+    )?;
+    Ok(())
+}
+
+/// `cvr.csv`, with the first three ranks of each ballot.
+fn write_us_mn_mpls(election: &Election, dir: &Path) -> io::Result<()> {
+    let mut out = create(&dir.join("cvr.csv"))?;
+    writeln!(out, "Precinct,1st Choice,2nd Choice,3rd Choice,Count")?;
+    for (i, ballot) in election.ballots.iter().enumerate() {
+        let ranks: Vec<&str> = (0..THREE_RANKS)
+            .map(|r| match rank(&ballot.choices, r) {
+                Choice::Vote(c) => election.candidates[c.0 as usize].name.as_str(),
+                Choice::Undervote => "undervote",
+                Choice::Overvote => "overvote",
+            })
+            .collect();
+        writeln!(out, "P-{},{},1", i % 100, ranks.join(","))?;
+    }
+    out.flush()
+}
+
+/// `MasterLookup.txt` and `BallotImage.txt` for contest 1, in San
+/// Francisco's fixed-width format.
+fn write_us_ca_sfo(election: &Election, dir: &Path) -> io::Result<()> {
+    let mut master = create(&dir.join("MasterLookup.txt"))?;
+    for (i, candidate) in election.candidates.iter().enumerate() {
+        writeln!(
+            master,
+            "{:<10}{:07}{:<50}{:07}{:07}00",
+            "Candidate",
+            i + 1,
+            candidate.name,
+            i + 1,
+            1
+        )?;
+    }
+    master.flush()?;
+
+    let ranks = num_ranks(election);
+    let mut image = create(&dir.join("BallotImage.txt"))?;
+    for (i, ballot) in election.ballots.iter().enumerate() {
+        for r in 0..ranks {
+            let (candidate, over, under) = match rank(&ballot.choices, r) {
+                Choice::Vote(c) => (c.0 + 1, 0, 0),
+                Choice::Overvote => (0, 1, 0),
+                Choice::Undervote => (0, 0, 1),
+            };
+            writeln!(
+                image,
+                "{:07}{:09}{:07}{:03}{:07}{:03}{:07}{}{}",
+                1,
+                i + 1,
+                i + 1,
+                1,
+                i % 100,
+                r + 1,
+                candidate,
+                over,
+                under
+            )?;
+        }
+    }
+    image.flush()
+}
+
+/// `ballots.txt`, in Burlington's format. The format doesn't record blank
+/// ranks, so they are left out, as are ballots with no marks.
+fn write_us_vt_btv(election: &Election, dir: &Path) -> io::Result<()> {
+    let mut out = create(&dir.join("ballots.txt"))?;
+    for (i, candidate) in election.candidates.iter().enumerate() {
+        writeln!(out, ".CANDIDATE C{:02}, \"{}\"", i + 1, candidate.name)?;
+    }
+    for ballot in &election.ballots {
+        let ranks: Vec<String> = ballot
+            .choices
+            .iter()
+            .filter_map(|choice| match choice {
+                Choice::Vote(c) => Some(format!("C{:02}", c.0 + 1)),
+                Choice::Overvote => Some("C01=C02".to_string()),
+                Choice::Undervote => None,
+            })
+            .collect();
+        if !ranks.is_empty() {
+            writeln!(out, "{}, 1) {}", ballot.id, ranks.join(","))?;
+        }
+    }
+    out.flush()
+}
+
+/// `ballots.rcr`, in Dominion's RCR format.
+fn write_dominion_rcr(election: &Election, dir: &Path) -> io::Result<()> {
+    let ranks = num_ranks(election).max(1);
+    let mut out = create(&dir.join("ballots.rcr"))?;
+    writeln!(out, "1\t{}\t1\t1", election.candidates.len())?;
+    writeln!(out, "Synthetic Election")?;
+    for candidate in &election.candidates {
+        writeln!(out, "{}", candidate.name)?;
+    }
+    writeln!(out, "1\tPrecinct 1")?;
+    writeln!(out, "1\tElection Day")?;
+    for ballot in &election.ballots {
+        let marks: Vec<String> = (0..ranks)
+            .map(|r| match rank(&ballot.choices, r) {
+                Choice::Vote(c) => (c.0 + 1).to_string(),
+                Choice::Overvote => "1=2".to_string(),
+                Choice::Undervote => "0".to_string(),
+            })
+            .collect();
+        writeln!(out, "1\t1\t1\t{}", marks.join("\t"))?;
+    }
+    out.flush()
+}
+
+/// Ballots per CVR file in the NIST SP 1500-103 format, as in Dominion's
+/// exports.
+const SESSIONS_PER_FILE: usize = 10_000;
+
+/// `CandidateManifest.json` and `CvrExport_{n}.json` files for contest 1,
+/// in the NIST SP 1500-103 (Dominion CVR export) format.
+fn write_nist_sp_1500(election: &Election, dir: &Path) -> io::Result<()> {
+    let candidates: Vec<_> = election
+        .candidates
+        .iter()
+        .enumerate()
+        .map(|(i, candidate)| {
+            json!({
+                "Description": candidate.name,
+                "Id": i + 1,
+                "ExternalId": null,
+                "ContestId": 1,
+                "Type": "Regular",
+            })
+        })
+        .collect();
+    serde_json::to_writer(
+        create(&dir.join("CandidateManifest.json"))?,
+        &json!({ "Version": "5.10.50.85", "List": candidates }),
+    )?;
+
+    for (file, ballots) in election.ballots.chunks(SESSIONS_PER_FILE).enumerate() {
+        let sessions: Vec<_> = ballots
+            .iter()
+            .enumerate()
+            .map(|(i, ballot)| {
+                let mut marks = Vec::new();
+                for (r, choice) in ballot.choices.iter().enumerate() {
+                    let candidates: &[u32] = match choice {
+                        Choice::Vote(c) => &[c.0 + 1],
+                        Choice::Overvote => &[1, 2],
+                        Choice::Undervote => &[],
+                    };
+                    for candidate in candidates {
+                        marks.push(json!({
+                            "CandidateId": candidate,
+                            "PartyId": null,
+                            "Rank": r + 1,
+                            "MarkDensity": 100,
+                            "IsAmbiguous": false,
+                            "IsVote": true,
+                        }));
+                    }
+                }
+                json!({
+                    "TabulatorId": 1,
+                    "BatchId": file + 1,
+                    "RecordId": i + 1,
+                    "CountingGroupId": 1,
+                    "ImageMask": "",
+                    "Original": {
+                        "PrecinctPortionId": 1,
+                        "BallotTypeId": 1,
+                        "IsCurrent": true,
+                        "Contests": [{ "Id": 1, "Marks": marks }],
+                    },
+                    "Modified": null,
+                })
+            })
+            .collect();
+        serde_json::to_writer(
+            create(&dir.join(format!("CvrExport_{}.json", file)))?,
+            &json!({ "Version": "5.10.50.85", "ElectionId": "Synthetic", "Sessions": sessions }),
+        )?;
+    }
+    Ok(())
+}
+
+/// `cvr.xlsx`, in Maine's format, with the first three ranks of each ballot.
+fn write_us_me(election: &Election, dir: &Path) -> io::Result<()> {
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    let header = ["Cast Vote Record", "Precinct", "Ballot Style"];
+    for (col, name) in header.iter().enumerate() {
+        sheet
+            .write_string(0, col as u16, *name)
+            .map_err(xlsx_error)?;
+    }
+    for r in 0..THREE_RANKS {
+        sheet
+            .write_string(0, (header.len() + r) as u16, format!("Rank {}", r + 1))
+            .map_err(xlsx_error)?;
+    }
+
+    for (i, ballot) in election.ballots.iter().enumerate() {
+        let row = i as u32 + 1;
+        sheet
+            .write_number(row, 0, (i + 1) as f64)
+            .map_err(xlsx_error)?;
+        sheet
+            .write_string(row, 1, "Precinct 1")
+            .map_err(xlsx_error)?;
+        sheet.write_string(row, 2, "Style 1").map_err(xlsx_error)?;
+        for r in 0..THREE_RANKS {
+            let cell = match rank(&ballot.choices, r) {
+                Choice::Vote(c) => election.candidates[c.0 as usize].name.as_str(),
+                Choice::Undervote => "undervote",
+                Choice::Overvote => "overvote",
+            };
+            sheet
+                .write_string(row, (header.len() + r) as u16, cell)
+                .map_err(xlsx_error)?;
+        }
+    }
+    workbook.save(dir.join("cvr.xlsx")).map_err(xlsx_error)
+}
+
+/// A wide NYC-style CVR workbook, `cvr_1.xlsx`, with a column per rank of
+/// each of the given races (offices), and the candidacy ID lookup
+/// `candidates.xlsx`. Row `i` holds the `i`-th ballot of every race, so races
+/// should have the same number of ballots.
+pub fn write_us_ny_nyc(races: &[(&str, &Election)], dir: &Path) -> io::Result<()> {
+    let candidacy_id = |race: usize, candidate: u32| 200_000 + race as u32 * 1000 + candidate;
+
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    sheet
+        .write_string(0, 0, "Candidacy ID")
+        .map_err(xlsx_error)?;
+    sheet
+        .write_string(0, 1, "DefaultBallotName")
+        .map_err(xlsx_error)?;
+    let mut row = 1;
+    for (race, (_, election)) in races.iter().enumerate() {
+        for (candidate, c) in election.candidates.iter().enumerate() {
+            sheet
+                .write_number(row, 0, candidacy_id(race, candidate as u32) as f64)
+                .map_err(xlsx_error)?;
+            sheet.write_string(row, 1, &c.name).map_err(xlsx_error)?;
+            row += 1;
+        }
+    }
+    workbook
+        .save(dir.join("candidates.xlsx"))
+        .map_err(xlsx_error)?;
+
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    sheet
+        .write_string(0, 0, "Cast Vote Record")
+        .map_err(xlsx_error)?;
+    sheet.write_string(0, 1, "Precinct").map_err(xlsx_error)?;
+    let mut col = 2;
+    let mut race_columns = Vec::new();
+    for (race, (office, election)) in races.iter().enumerate() {
+        let ranks = num_ranks(election);
+        race_columns.push((col, ranks));
+        for r in 0..ranks {
+            let name = format!(
+                "{} Choice {} of {} Citywide ({})",
+                office,
+                r + 1,
+                ranks,
+                100_000 + race * 100 + r
+            );
+            sheet.write_string(0, col, name).map_err(xlsx_error)?;
+            col += 1;
+        }
+    }
+
+    let num_ballots = races
+        .iter()
+        .map(|(_, e)| e.ballots.len())
+        .max()
+        .unwrap_or(0);
+    for i in 0..num_ballots {
+        let row = i as u32 + 1;
+        // The reader takes ballot IDs from text cells only.
+        sheet
+            .write_string(row, 0, (i + 1).to_string())
+            .map_err(xlsx_error)?;
+        sheet
+            .write_string(row, 1, "AD 1 - ED 1")
+            .map_err(xlsx_error)?;
+        for (race, (_, election)) in races.iter().enumerate() {
+            let (first_col, ranks) = race_columns[race];
+            let choices = election
+                .ballots
+                .get(i)
+                .map(|b| b.choices.as_slice())
+                .unwrap_or(&[]);
+            for r in 0..ranks {
+                let cell = match rank(choices, r) {
+                    Choice::Vote(c) => candidacy_id(race, c.0).to_string(),
+                    Choice::Undervote => "undervote".to_string(),
+                    Choice::Overvote => "overvote".to_string(),
+                };
+                sheet
+                    .write_string(row, first_col + r as u16, cell)
+                    .map_err(xlsx_error)?;
+            }
+        }
+    }
+    workbook.save(dir.join("cvr_1.xlsx")).map_err(xlsx_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::{nyc_batch_reader, read_election};
+    use crate::generate::{generate_election, GeneratorOptions};
+    use crate::util::TempDir;
+
+    #[test]
+    fn test_write_raw_round_trip() {
+        let election = generate_election(&GeneratorOptions {
+            num_ballots: 50,
+            ranks: 3,
+            skip_rate: 0.,
+            // Minneapolis's format records an overvote in place of the
+            // whole ballot.
+            overvote_rate: 0.,
+            ..GeneratorOptions::default()
+        });
+
+        for format in [
+            "simple_json",
+            "us_mn_mpls",
+            "us_ca_sfo",
+            "dominion_rcr",
+            "nist_sp_1500",
+        ] {
+            let dir = TempDir::new(&format!("generate-{}", format));
+            let params = write_raw(format, &election, &dir).unwrap();
+            let read = read_election(format, &dir, params);
+
+            assert_eq!(election.ballots.len(), read.ballots.len(), "{}", format);
+            // Readers number candidates as they find them, so compare names.
+            let names = |e: &Election, choices: &[Choice]| -> Vec<String> {
+                choices
+                    .iter()
+                    .map(|c| match c {
+                        Choice::Vote(c) => e.candidates[c.0 as usize].name.clone(),
+                        c => format!("{:?}", c),
+                    })
+                    .collect()
+            };
+            for (written, read_ballot) in election.ballots.iter().zip(&read.ballots) {
+                let mut expected = names(&election, &written.choices);
+                let mut actual = names(&read, &read_ballot.choices);
+                // Formats with a fixed number of ranks pad ballots with blanks.
+                expected.resize(3, "Undervote".to_string());
+                actual.resize(3, "Undervote".to_string());
+                assert_eq!(expected, actual, "{}", format);
+            }
+        }
+    }
+
+    #[test]
+    fn test_write_us_ny_nyc() {
+        let options = GeneratorOptions {
+            num_ballots: 20,
+            ..GeneratorOptions::default()
+        };
+        let mayor = generate_election(&options);
+        let council = generate_election(&GeneratorOptions { seed: 2, ..options });
+
+        let dir = TempDir::new("generate-nyc");
+        write_us_ny_nyc(&[("Mayor", &mayor), ("Council", &council)], &dir).unwrap();
+        let contests = ["Mayor", "Council"]
+            .iter()
+            .map(|office| {
+                let params = params(&[
+                    ("candidatesFile", "candidates.xlsx"),
+                    ("cvrPattern", r"cvr_\d+\.xlsx"),
+                    ("officeName", office),
+                    ("jurisdictionName", "Citywide"),
+                ]);
+                (office.to_string(), params)
+            })
+            .collect();
+        let mut read = nyc_batch_reader(&dir, contests);
+
+        for (office, election) in [("Mayor", &mayor), ("Council", &council)] {
+            let read = read.remove(office).unwrap();
+            assert_eq!(election.ballots.len(), read.ballots.len(), "{}", office);
+            assert_eq!(election.ballots[0].id, read.ballots[0].id);
+        }
+    }
+}
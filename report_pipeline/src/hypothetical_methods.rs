@@ -0,0 +1,129 @@
+//! Hypothetical winners of a contest under other single-winner voting
+//! methods, for comparing RCV with the methods readers are familiar with.
+//!
+//! Each method is counted from the normalized ballots, so the rules that
+//! decide which rankings count (overvotes, skipped ranks) are the same as in
+//! the RCV tabulation:
+//!
+//! - Plurality counts each ballot's first choice.
+//! - Approval counts each ballot for every candidate it ranks.
+//! - Borda gives a candidate ranked at position `i` (from zero) on a ballot
+//!   `n - 1 - i` points, where `n` is the number of candidates, and
+//!   unranked candidates none.
+
+use crate::model::election::{CandidateId, NormalizedBallot};
+use crate::model::report::{HypotheticalMethods, HypotheticalResult, MethodScore, VotingMethod};
+use std::cmp::Reverse;
+
+/// Rank a method's scores, and find its winner.
+fn result(method: VotingMethod, scores: Vec<u64>) -> HypotheticalResult {
+    let mut scores: Vec<MethodScore> = scores
+        .into_iter()
+        .enumerate()
+        .map(|(i, score)| MethodScore {
+            candidate: CandidateId(i as u32),
+            score,
+        })
+        .collect();
+    scores.sort_by_key(|s| (Reverse(s.score), s.candidate));
+
+    let winner = match scores.as_slice() {
+        [first, second, ..] if first.score == second.score => None,
+        [first, ..] if first.score > 0 => Some(first.candidate),
+        _ => None,
+    };
+    HypotheticalResult {
+        method,
+        winner,
+        scores,
+    }
+}
+
+/// Count a contest with `num_candidates` candidates under each hypothetical
+/// method.
+pub fn generate_hypothetical_methods(
+    num_candidates: usize,
+    ballots: &[NormalizedBallot],
+) -> HypotheticalMethods {
+    let mut plurality = vec![0u64; num_candidates];
+    let mut approval = vec![0u64; num_candidates];
+    let mut borda = vec![0u64; num_candidates];
+    for ballot in ballots {
+        let choices = ballot.choice_slice();
+        if let Some(first) = choices.first() {
+            plurality[first.0 as usize] += 1;
+        }
+        for (i, choice) in choices.iter().enumerate() {
+            // Normalization removes repeated rankings, but a candidate is
+            // only approved of once regardless.
+            if choices[..i].contains(choice) {
+                continue;
+            }
+            approval[choice.0 as usize] += 1;
+            borda[choice.0 as usize] += num_candidates.saturating_sub(i + 1) as u64;
+        }
+    }
+
+    HypotheticalMethods {
+        results: vec![
+            result(VotingMethod::Plurality, plurality),
+            result(VotingMethod::Approval, approval),
+            result(VotingMethod::Borda, borda),
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ballot(choices: &[u32]) -> NormalizedBallot {
+        let choices = choices.iter().map(|&c| CandidateId(c)).collect();
+        NormalizedBallot::new(String::new(), choices, false)
+    }
+
+    fn winners(methods: &HypotheticalMethods) -> Vec<(VotingMethod, Option<CandidateId>)> {
+        methods
+            .results
+            .iter()
+            .map(|r| (r.method, r.winner))
+            .collect()
+    }
+
+    #[test]
+    fn test_methods_disagree() {
+        // A leads on first choices, but B is ranked by everyone, and C is
+        // nobody's last choice.
+        let mut ballots = vec![];
+        ballots.extend((0..4).map(|_| ballot(&[0, 1])));
+        ballots.extend((0..3).map(|_| ballot(&[2, 1])));
+        ballots.extend((0..2).map(|_| ballot(&[1, 2])));
+        let methods = generate_hypothetical_methods(3, &ballots);
+        assert_eq!(
+            vec![
+                (VotingMethod::Plurality, Some(CandidateId(0))),
+                (VotingMethod::Approval, Some(CandidateId(1))),
+                (VotingMethod::Borda, Some(CandidateId(1))),
+            ],
+            winners(&methods)
+        );
+        // Borda: A 4 * 2, B 4 + 3 + 2 * 2, C 3 * 2 + 2.
+        assert_eq!(
+            vec![(1, 11), (0, 8), (2, 8)],
+            methods.results[2]
+                .scores
+                .iter()
+                .map(|s| (s.candidate.0, s.score))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_tie_has_no_winner() {
+        let methods = generate_hypothetical_methods(2, &[ballot(&[0]), ballot(&[1])]);
+        assert!(methods.results.iter().all(|r| r.winner.is_none()));
+        assert!(generate_hypothetical_methods(2, &[]).results[0]
+            .winner
+            .is_none());
+    }
+}
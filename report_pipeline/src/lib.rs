@@ -0,0 +1,27 @@
+pub mod audit;
+pub mod candidate_history;
+pub mod commands;
+pub mod exhausted_ballots;
+pub mod formats;
+pub mod generate;
+pub mod hypothetical_methods;
+pub mod margin_of_victory;
+pub mod model;
+pub mod normalizers;
+pub mod notify;
+pub mod office_history;
+pub mod patterns;
+pub mod plurality;
+pub mod precinct_map;
+pub mod preprocessed;
+pub mod rctab;
+pub mod read_metadata;
+pub mod report;
+pub mod robustness;
+pub mod rules;
+pub mod site_metadata;
+pub mod strategy;
+pub mod summary;
+pub mod tabulator;
+pub mod transfer_graph;
+pub mod util;
@@ -1,20 +1,49 @@
-mod commands;
-mod formats;
-mod model;
-mod normalizers;
-mod read_metadata;
-mod report;
-mod tabulator;
-mod util;
-
-use crate::commands::{info, rebuild_index, report, sync};
 use clap::{Parser, Subcommand};
+use ranked_vote::commands::{
+    codegen_ts, compare_rctab, convert_preprocessed, detect, du, dump_ballots, export_api, fetch,
+    generate, info, new_jurisdiction, prune, ranking_limits, read_report_config, rebuild_index,
+    recheck, report, schema, signing_public_key, sync, tabulate_patterns, transfer_graph,
+    verify_signatures, view, watch, FetchOptions, NewJurisdictionOptions, ReportOptions,
+    SyncOptions,
+};
+use ranked_vote::generate::{GeneratorOptions, PreferenceModel, Truncation};
+use ranked_vote::model::metadata::TabulationOptions;
+use ranked_vote::model::report::RecheckStatus;
+use ranked_vote::util::{
+    configure_output, configure_threads, parse_size, read_signing_key, CountingAllocator,
+};
 use std::path::PathBuf;
+use std::time::Duration;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
 
 #[derive(Parser)]
 struct Opts {
     #[clap(subcommand)]
     command: Command,
+    /// Number of threads for parallel work, and the default for
+    /// --preprocess-threads and --report-threads (default: number of CPUs)
+    #[clap(long, global = true, env = "RANKED_VOTE_THREADS")]
+    threads: Option<usize>,
+    /// Pin worker threads to CPU cores
+    #[clap(long, global = true, env = "RANKED_VOTE_PIN_THREADS")]
+    pin_threads: bool,
+    /// Only log errors
+    #[clap(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Log progress (-v), debugging details (-vv) or everything (-vvv). Overrides
+    /// RANKED_VOTE_LOG_LEVEL
+    #[clap(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+    /// Don't color output. Also set by a non-empty NO_COLOR environment variable
+    #[clap(long, global = true)]
+    no_color: bool,
+    /// JSON config file with directories, filters, cache policy, analyses,
+    /// threads and log level for the report and watch commands. Options given
+    /// on the command line or in environment variables take precedence
+    #[clap(long, global = true, env = "RANKED_VOTE_CONFIG")]
+    config: Option<PathBuf>,
 }
 
 #[derive(Subcommand)]
@@ -23,30 +52,139 @@ enum Command {
     Info {
         /// Input directory to validate and dump.
         meta_dir: PathBuf,
+        /// Raw data directory to check each contest's data files in.
+        raw_dir: Option<PathBuf>,
     },
-    /// Sync raw data files with metadata.
-    Sync {
+    /// Add a new jurisdiction, election and contests to the metadata, with
+    /// template loader parameters. Values not given as flags are asked for.
+    NewJurisdiction {
+        /// Metadata directory
+        meta_dir: PathBuf,
+        /// Jurisdiction path (e.g. "us/ca/sfo")
+        path: String,
+        /// Jurisdiction name
+        #[clap(long)]
+        name: Option<String>,
+        /// Kind of jurisdiction
+        #[clap(long, default_value = "city")]
+        kind: String,
+        /// Election path (e.g. "2024/11")
+        #[clap(long)]
+        election: Option<String>,
+        /// Election name
+        #[clap(long)]
+        election_name: Option<String>,
+        /// Election date (YYYY-MM-DD)
+        #[clap(long)]
+        date: Option<String>,
+        /// Data format (e.g. "nist_sp_1500")
+        #[clap(long)]
+        format: Option<String>,
+        /// Contest office ID and name (e.g. "mayor=Mayor"). May be given more than once.
+        #[clap(long = "contest")]
+        contests: Vec<String>,
+    },
+    /// Guess the data format and loader parameters of an election's raw data,
+    /// and print a metadata stub for it
+    Detect {
+        /// Raw data directory of the election (e.g. "raw-data/us/ca/sfo/2024/11")
+        raw_election_dir: PathBuf,
+    },
+    /// Write a synthetic election's ballots in one of the raw data formats,
+    /// and print a metadata stub for them
+    Generate {
+        /// Raw data directory to write the ballots to
+        raw_election_dir: PathBuf,
+        /// Data format (e.g. "nist_sp_1500")
+        #[clap(long, default_value = "simple_json")]
+        format: String,
+        /// Number of ballots
+        #[clap(long, default_value = "1000")]
+        ballots: usize,
+        /// Number of candidates
+        #[clap(long, default_value = "5")]
+        candidates: usize,
+        /// Number of ranks on the ballot
+        #[clap(long, default_value = "5")]
+        ranks: usize,
+        /// Preference model: "uniform", "plackett-luce:DECAY" or
+        /// "mallows:DISPERSION", with parameters between 0 and 1
+        #[clap(long, default_value = "plackett-luce:0.8", value_parser = PreferenceModel::parse)]
+        model: PreferenceModel,
+        /// How many ranks voters mark: "full", "uniform", "geometric:STOP"
+        /// (stopping after each rank with probability STOP) or "fixed:RANKS"
+        #[clap(long, default_value = "geometric:0.3", value_parser = Truncation::parse)]
+        truncation: Truncation,
+        /// Probability of leaving a rank blank before each choice
+        #[clap(long, default_value = "0.01")]
+        skip_rate: f64,
+        /// Probability of overvoting a rank
+        #[clap(long, default_value = "0.005")]
+        overvote_rate: f64,
+        /// Random seed; the same seed and options give the same ballots
+        #[clap(long, default_value = "1")]
+        seed: u64,
+    },
+    /// Download raw data files that are missing, from the URLs in the metadata's
+    /// `downloads`, resuming interrupted downloads. Prints a JSON summary to
+    /// stdout, and exits with status 1 if any file couldn't be downloaded.
+    Fetch {
         /// Metadata directory
         meta_dir: PathBuf,
         /// Raw data directory
         raw_data_dir: PathBuf,
+        /// Only fetch files of this jurisdiction or election path
+        #[clap(long)]
+        jurisdiction: Option<String>,
+        /// Limit downloads to this many bytes per second, e.g. "10M"
+        #[clap(long, value_parser = parse_size)]
+        max_rate: Option<u64>,
     },
-    /// Generate reports
-    Report {
+    /// Sync raw data files with metadata. Prints a JSON summary of the files
+    /// that are extra, missing or changed to stdout, and exits with status 1 if
+    /// any file listed in the metadata is missing or changed.
+    Sync {
         /// Metadata directory
         meta_dir: PathBuf,
         /// Raw data directory
         raw_data_dir: PathBuf,
-        /// Preprocessed file output directory
-        preprocessed_dir: PathBuf,
-        /// Report output directory
-        report_dir: PathBuf,
+        /// Only report differences, without creating directories, updating
+        /// metadata or deleting files
+        #[clap(long)]
+        dry_run: bool,
+        /// Remove metadata entries of files missing from the raw data directory
+        #[clap(long)]
+        allow_delete_entries: bool,
+        /// Delete files the metadata doesn't list from the raw data directory,
+        /// instead of adding them to the metadata
+        #[clap(long)]
+        allow_delete_files: bool,
+    },
+    /// Generate reports. Prints a JSON summary of the run to stdout, and exits
+    /// with status 1 if any contest failed.
+    Report {
+        /// Metadata directory (or `metaDir` in --config)
+        #[clap(env = "RANKED_VOTE_META_DIR")]
+        meta_dir: Option<PathBuf>,
+        /// Raw data directory (or `rawDataDir` in --config)
+        #[clap(env = "RANKED_VOTE_RAW_DATA_DIR")]
+        raw_data_dir: Option<PathBuf>,
+        /// Preprocessed file output directory (or `preprocessedDir` in --config)
+        #[clap(env = "RANKED_VOTE_PREPROCESSED_DIR")]
+        preprocessed_dir: Option<PathBuf>,
+        /// Report output directory (or `reportDir` in --config)
+        #[clap(env = "RANKED_VOTE_REPORT_DIR")]
+        report_dir: Option<PathBuf>,
         /// Whether to use cached preprocessed files if they exist (default: regenerate)
         #[clap(long)]
         use_cache_preprocess: bool,
         /// Whether to use cached report files if they exist (default: regenerate)
         #[clap(long)]
         use_cache_report: bool,
+        /// Only generate reports that don't exist yet, leaving existing reports as
+        /// they are regardless of the other cache flags
+        #[clap(long)]
+        only_missing: bool,
         /// Whether to force preprocessing even if preprocessed files exist (deprecated: use --use-cache-preprocess=false)
         #[clap(long, hidden = true)]
         force_preprocess: bool,
@@ -56,26 +194,373 @@ enum Command {
         /// Optional jurisdiction filter (e.g., "us/ca/alameda")
         #[clap(long)]
         jurisdiction: Option<String>,
+        /// Number of jurisdictions to preprocess from raw data at once (default: --threads)
+        #[clap(long, env = "RANKED_VOTE_PREPROCESS_THREADS")]
+        preprocess_threads: Option<usize>,
+        /// Number of reports to generate at once (default: --threads)
+        #[clap(long, env = "RANKED_VOTE_REPORT_THREADS")]
+        report_threads: Option<usize>,
+        /// Limit on the estimated memory of elections preprocessed at once (e.g. "16G").
+        /// Memory is estimated from the size of each election's raw data.
+        #[clap(long, env = "RANKED_VOTE_MAX_MEMORY", value_parser = parse_size)]
+        max_memory: Option<u64>,
+        /// Base URL of the site (e.g. "https://ranked.vote"). If given, also write
+        /// sitemap.xml and a meta.json stub for each contest.
+        #[clap(long, env = "RANKED_VOTE_SITE_URL")]
+        site_url: Option<String>,
+        /// URL to POST a JSON run summary to when the run completes, and a
+        /// notification for each contest whose winner changed since the previous
+        /// run. May be given more than once.
+        #[clap(long = "webhook")]
+        webhooks: Vec<String>,
+        /// Also write round_snapshots.json for each contest, with the ballots at each
+        /// round aggregated by their remaining ranking, for audits
+        #[clap(long)]
+        round_snapshots: bool,
+        /// Also write ranking_patterns.json for each contest, with the number of
+        /// ballots having each distinct ranking
+        #[clap(long)]
+        ranking_patterns: bool,
+        /// Analyze whether each winner is stable under alternative elimination
+        /// orders, treating candidates within this many votes of last place as tied
+        #[clap(long, value_name = "VOTES")]
+        elimination_tolerance: Option<u32>,
+        /// Also count each contest under plurality, approval (every ranked
+        /// candidate) and Borda, and report each method's hypothetical winner
+        #[clap(long)]
+        hypothetical_methods: bool,
+        /// Check each contest for monotonicity failures: whether ranking a candidate
+        /// lower or higher on some ballots would have changed the winner
+        #[clap(long)]
+        strategy_susceptibility: bool,
+        /// Merge runs of rounds that each eliminate one candidate without changing the
+        /// two leaders into one step, keeping the full sequence in expandedRounds
+        #[clap(long)]
+        condense_rounds: bool,
+        /// File with a hex-encoded ed25519 secret key. If given, write a detached
+        /// signature (report.json.sig, index.json.sig) next to each report and the index
+        #[clap(long, env = "RANKED_VOTE_SIGNING_KEY")]
+        signing_key: Option<PathBuf>,
+        /// Seconds after which a contest still being preprocessed or reported is
+        /// marked failed, and the run moves on without it
+        #[clap(long, env = "RANKED_VOTE_CONTEST_TIMEOUT", value_name = "SECONDS")]
+        contest_timeout: Option<u64>,
+        /// Compress preprocessed ballots with zstd at this level (1 to 22), as
+        /// normalized.bin.zst, instead of writing them uncompressed
+        #[clap(long, env = "RANKED_VOTE_ZSTD_LEVEL", value_name = "LEVEL")]
+        zstd_level: Option<i32>,
+    },
+    /// Generate reports, then regenerate them for elections whose raw data changes
+    Watch {
+        /// Metadata directory (or `metaDir` in --config)
+        #[clap(env = "RANKED_VOTE_META_DIR")]
+        meta_dir: Option<PathBuf>,
+        /// Raw data directory (or `rawDataDir` in --config)
+        #[clap(env = "RANKED_VOTE_RAW_DATA_DIR")]
+        raw_data_dir: Option<PathBuf>,
+        /// Preprocessed file output directory (or `preprocessedDir` in --config)
+        #[clap(env = "RANKED_VOTE_PREPROCESSED_DIR")]
+        preprocessed_dir: Option<PathBuf>,
+        /// Report output directory (or `reportDir` in --config)
+        #[clap(env = "RANKED_VOTE_REPORT_DIR")]
+        report_dir: Option<PathBuf>,
+        /// Seconds between checks of the raw data directory
+        #[clap(long, default_value = "30")]
+        interval: u64,
+        /// Optional jurisdiction filter (e.g., "us/ca/alameda")
+        #[clap(long)]
+        jurisdiction: Option<String>,
+        /// Base URL of the site, as for the report command
+        #[clap(long, env = "RANKED_VOTE_SITE_URL")]
+        site_url: Option<String>,
+        /// Webhook URL to notify after each update, as for the report command
+        #[clap(long = "webhook")]
+        webhooks: Vec<String>,
+        /// Key to sign reports and the index with, as for the report command
+        #[clap(long, env = "RANKED_VOTE_SIGNING_KEY")]
+        signing_key: Option<PathBuf>,
+        /// Seconds after which a contest is marked failed, as for the report command
+        #[clap(long, env = "RANKED_VOTE_CONTEST_TIMEOUT", value_name = "SECONDS")]
+        contest_timeout: Option<u64>,
+        /// Compress preprocessed ballots with zstd, as for the report command
+        #[clap(long, env = "RANKED_VOTE_ZSTD_LEVEL", value_name = "LEVEL")]
+        zstd_level: Option<i32>,
+    },
+    /// Show the disk space used by preprocessed files and reports, per
+    /// jurisdiction and election
+    Du {
+        /// Metadata directory
+        meta_dir: PathBuf,
+        /// Preprocessed file output directory
+        preprocessed_dir: PathBuf,
+        /// Report output directory
+        report_dir: PathBuf,
+        /// Warn if artifacts use, or would use once every election is
+        /// generated, more than this much space (e.g. "50G")
+        #[clap(long, value_parser = parse_size)]
+        quota: Option<u64>,
+    },
+    /// Delete preprocessed ballots that the next report run won't read, to keep
+    /// disk usage bounded. Raw data and reports are never deleted. Prints a JSON
+    /// summary of the pruned files to stdout
+    Prune {
+        /// Metadata directory
+        meta_dir: PathBuf,
+        /// Preprocessed file output directory
+        preprocessed_dir: PathBuf,
+        /// Report directory, whose run manifest lists the contests the last
+        /// report run read. Without one, the contests in the metadata are kept
+        report_dir: PathBuf,
+        /// Keep preprocessed ballots of contests that the last report run didn't
+        /// read for this many days
+        #[clap(long, default_value = "30")]
+        retention_days: u64,
+        /// Only list the files that would be deleted
+        #[clap(long)]
+        dry_run: bool,
+    },
+    /// Tabulate published contests again from their cached preprocessed ballots,
+    /// and check that the reports are reproduced. Exits with status 1 if any
+    /// report differs or can't be reproduced
+    Recheck {
+        /// Metadata directory
+        #[clap(env = "RANKED_VOTE_META_DIR")]
+        meta_dir: PathBuf,
+        /// Preprocessed file directory
+        #[clap(env = "RANKED_VOTE_PREPROCESSED_DIR")]
+        preprocessed_dir: PathBuf,
+        /// Report directory
+        #[clap(env = "RANKED_VOTE_REPORT_DIR")]
+        report_dir: PathBuf,
+        /// Only recheck this jurisdiction or election path (e.g. "us/ca/alameda").
+        /// May be given more than once
+        #[clap(long = "jurisdiction")]
+        filters: Vec<String>,
     },
     /// Rebuild index.json from existing reports
     RebuildIndex {
         /// Report output directory
         report_dir: PathBuf,
     },
+    /// Convert preprocessed files from gzipped JSON to the binary format
+    ConvertPreprocessed {
+        /// Preprocessed file directory
+        preprocessed_dir: PathBuf,
+        /// Remove the gzipped JSON files after converting them
+        #[clap(long)]
+        remove_legacy: bool,
+        /// Compress the converted files with zstd at this level (1 to 22)
+        #[clap(long, env = "RANKED_VOTE_ZSTD_LEVEL", value_name = "LEVEL")]
+        zstd_level: Option<i32>,
+    },
+    /// Tabulate every preprocessed contest with each ballot limited to its first
+    /// few rankings, and write each contest's winner under each limit as CSV
+    RankingLimits {
+        /// Preprocessed file directory
+        preprocessed_dir: PathBuf,
+        /// Ranking limits to simulate, comma-separated
+        #[clap(long, value_delimiter = ',', default_value = "2,3,4,5,6")]
+        limits: Vec<usize>,
+        /// Write to this file instead of stdout
+        #[clap(long)]
+        output: Option<PathBuf>,
+    },
+    /// Export reports as a static JSON API with a manifest and ETags
+    ExportApi {
+        /// Report output directory
+        report_dir: PathBuf,
+        /// API output directory
+        api_dir: PathBuf,
+    },
+    /// Print the JSON Schema of the report files (report.json, index.json, etc.), or
+    /// TypeScript definitions of them
+    Schema {
+        /// Print TypeScript definitions instead of JSON Schema
+        #[clap(long)]
+        typescript: bool,
+    },
+    /// Write TypeScript definitions of the report files for the web app, as
+    /// report_types.d.ts
+    CodegenTs {
+        /// Directory to write report_types.d.ts to
+        #[clap(default_value = "../src")]
+        output_dir: PathBuf,
+    },
+    /// Re-tabulate a contest from its ranking_patterns.json and print the rounds
+    TabulatePatterns {
+        /// Ranking pattern frequency table (ranking_patterns.json)
+        patterns_file: PathBuf,
+        /// Follow NYC's tabulation rules
+        #[clap(long)]
+        nyc_style: bool,
+    },
+    /// Write the normalized ballots of a contest as CSV, one row per ballot
+    /// with the candidates in ranked order, for spot-checking against the CVR
+    DumpBallots {
+        /// Preprocessed directory of the contest (e.g.
+        /// "preprocessed/us/ca/sfo/2024/11/mayor"), or its preprocessed file
+        contest_path: PathBuf,
+        /// Write to this file instead of stdout
+        #[clap(long)]
+        output: Option<PathBuf>,
+    },
+    /// Tabulate a contest under the options of an RCTab config and compare
+    /// each round with RCTab's results, printing the differences as JSON.
+    /// Exits with status 1 if the results differ
+    CompareRctab {
+        /// Preprocessed directory of the contest, or its preprocessed file
+        contest_path: PathBuf,
+        /// RCTab contest config (JSON)
+        config_file: PathBuf,
+        /// RCTab results summary (summary.json)
+        summary_file: PathBuf,
+    },
+    /// Print a Graphviz DOT graph of the vote flows between candidates across
+    /// the rounds of a contest
+    TransferGraph {
+        /// Contest report (report.json)
+        report_file: PathBuf,
+        /// Print a Mermaid flowchart instead of DOT
+        #[clap(long)]
+        mermaid: bool,
+    },
+    /// Explore a contest report interactively in the terminal, stepping
+    /// through rounds and transfers
+    View {
+        /// Contest report (report.json)
+        report_file: PathBuf,
+    },
+    /// Print the hex-encoded public key of a signing key
+    SigningPublicKey {
+        /// File with a hex-encoded ed25519 secret key
+        signing_key: PathBuf,
+    },
+    /// Verify the signatures of the reports and index in a report or API
+    /// directory. Exits with status 1 if any is missing or invalid.
+    VerifySignatures {
+        /// Report or API directory
+        dir: PathBuf,
+        /// File with the hex-encoded ed25519 public key of the signing key
+        #[clap(long)]
+        public_key: PathBuf,
+    },
+}
+
+/// A directory given as an argument or environment variable, or else in the
+/// config file.
+fn directory(arg: Option<PathBuf>, config: Option<PathBuf>, name: &str) -> PathBuf {
+    arg.or(config)
+        .unwrap_or_else(|| panic!("{} must be given as an argument or in --config", name))
 }
 
 fn main() {
     let opts = Opts::parse();
+    let config = opts
+        .config
+        .as_deref()
+        .map(read_report_config)
+        .unwrap_or_default();
+    configure_output(
+        opts.quiet,
+        opts.verbose,
+        opts.no_color,
+        config.log_level.as_deref(),
+    );
+    configure_threads(
+        opts.threads.or(config.threads),
+        opts.pin_threads || config.pin_threads,
+    );
 
     match opts.command {
-        Command::Info { meta_dir } => {
-            info(&meta_dir);
+        Command::Info { meta_dir, raw_dir } => {
+            info(&meta_dir, raw_dir.as_deref());
+        }
+        Command::NewJurisdiction {
+            meta_dir,
+            path,
+            name,
+            kind,
+            election,
+            election_name,
+            date,
+            format,
+            contests,
+        } => {
+            new_jurisdiction(
+                &meta_dir,
+                &path,
+                NewJurisdictionOptions {
+                    name,
+                    kind,
+                    election,
+                    election_name,
+                    date,
+                    format,
+                    contests,
+                },
+            );
+        }
+        Command::Detect { raw_election_dir } => {
+            detect(&raw_election_dir);
+        }
+        Command::Generate {
+            raw_election_dir,
+            format,
+            ballots,
+            candidates,
+            ranks,
+            model,
+            truncation,
+            skip_rate,
+            overvote_rate,
+            seed,
+        } => {
+            let options = GeneratorOptions {
+                num_ballots: ballots,
+                num_candidates: candidates,
+                ranks,
+                model,
+                truncation,
+                skip_rate,
+                overvote_rate,
+                seed,
+            };
+
+            generate(&raw_election_dir, &format, &options);
+        }
+        Command::Fetch {
+            meta_dir,
+            raw_data_dir,
+            jurisdiction,
+            max_rate,
+        } => {
+            let options = FetchOptions {
+                jurisdiction: jurisdiction.as_deref(),
+                max_rate,
+            };
+            let summary = fetch(&meta_dir, &raw_data_dir, &options);
+            println!("{}", serde_json::to_string(&summary).unwrap());
+            if !summary.failed.is_empty() {
+                std::process::exit(1);
+            }
         }
         Command::Sync {
             meta_dir,
             raw_data_dir,
+            dry_run,
+            allow_delete_entries,
+            allow_delete_files,
         } => {
-            sync(&meta_dir, &raw_data_dir);
+            let options = SyncOptions {
+                dry_run,
+                allow_delete_entries,
+                allow_delete_files,
+            };
+            let summary = sync(&meta_dir, &raw_data_dir, options);
+            println!("{}", serde_json::to_string(&summary).unwrap());
+            if summary.has_integrity_errors() {
+                std::process::exit(1);
+            }
         }
         Command::Report {
             meta_dir,
@@ -84,33 +569,279 @@ fn main() {
             report_dir,
             use_cache_preprocess,
             use_cache_report,
+            only_missing,
             force_preprocess,
             force_report,
             jurisdiction,
+            preprocess_threads,
+            report_threads,
+            max_memory,
+            site_url,
+            webhooks,
+            round_snapshots,
+            ranking_patterns,
+            elimination_tolerance,
+            hypothetical_methods,
+            strategy_susceptibility,
+            condense_rounds,
+            signing_key,
+            contest_timeout,
+            zstd_level,
         } => {
+            let meta_dir = directory(meta_dir, config.meta_dir, "metaDir");
+            let raw_data_dir = directory(raw_data_dir, config.raw_data_dir, "rawDataDir");
+            let preprocessed_dir =
+                directory(preprocessed_dir, config.preprocessed_dir, "preprocessedDir");
+            let report_dir = directory(report_dir, config.report_dir, "reportDir");
+            let filters = match jurisdiction {
+                Some(jurisdiction) => vec![jurisdiction],
+                None => config.filters,
+            };
+            let webhooks = if webhooks.is_empty() {
+                config.webhooks
+            } else {
+                webhooks
+            };
+            let use_cache_preprocess = use_cache_preprocess || config.use_cache_preprocess;
+            let use_cache_report = use_cache_report || config.use_cache_report;
+
             // Support deprecated flags for backward compatibility
             // If old flags are used, convert them to new cache flags
             let use_cache_preprocess = if force_preprocess { false } else { use_cache_preprocess };
             let use_cache_report = if force_report { false } else { use_cache_report };
+            let signing_key = signing_key
+                .or(config.signing_key)
+                .map(|path| read_signing_key(&path).unwrap_or_else(|e| panic!("{}", e)));
             
             // By default (when flags are false), regenerate everything
             // Only use cache if explicitly requested
-            // If regenerating reports, also regenerate preprocessing
-            let force_preprocess_final = !use_cache_preprocess || !use_cache_report;
-            let force_report_final = !use_cache_report;
-            
-            report(
+            // Reports are always regenerated from freshly preprocessed ballots, but
+            // cached preprocessed ballots can be reused to regenerate reports only
+            let options = ReportOptions {
+                force_preprocess: !use_cache_preprocess,
+                force_report: !use_cache_report,
+                only_missing: only_missing || config.only_missing,
+                filters: &filters,
+                preprocess_threads: preprocess_threads.or(config.preprocess_threads),
+                report_threads: report_threads.or(config.report_threads),
+                max_memory: max_memory.or(config.max_memory),
+                site_url: site_url.as_deref().or(config.site_url.as_deref()),
+                webhooks: &webhooks,
+                stale_elections: None,
+                round_snapshots: round_snapshots || config.round_snapshots,
+                ranking_patterns: ranking_patterns || config.ranking_patterns,
+                elimination_tolerance: elimination_tolerance.or(config.elimination_tolerance),
+                hypothetical_methods: hypothetical_methods || config.hypothetical_methods,
+                strategy_susceptibility: strategy_susceptibility || config.strategy_susceptibility,
+                condense_rounds: condense_rounds || config.condense_rounds,
+                signing_key: signing_key.as_ref(),
+                contest_timeout: contest_timeout
+                    .or(config.contest_timeout)
+                    .map(Duration::from_secs),
+                zstd_level: zstd_level.or(config.zstd_level),
+            };
+
+            let summary = report(
                 &meta_dir,
                 &raw_data_dir,
                 &report_dir,
                 &preprocessed_dir,
-                force_preprocess_final,
-                force_report_final,
-                jurisdiction.as_deref(),
+                &options,
             );
+
+            // Logs go to stderr, so automation can parse the summary from stdout
+            // and gate on the exit code.
+            println!("{}", serde_json::to_string(&summary).unwrap());
+            if summary.failed_contests > 0 {
+                std::process::exit(1);
+            }
+        }
+        Command::Watch {
+            meta_dir,
+            raw_data_dir,
+            preprocessed_dir,
+            report_dir,
+            interval,
+            jurisdiction,
+            site_url,
+            webhooks,
+            signing_key,
+            contest_timeout,
+            zstd_level,
+        } => {
+            let meta_dir = directory(meta_dir, config.meta_dir, "metaDir");
+            let raw_data_dir = directory(raw_data_dir, config.raw_data_dir, "rawDataDir");
+            let preprocessed_dir =
+                directory(preprocessed_dir, config.preprocessed_dir, "preprocessedDir");
+            let report_dir = directory(report_dir, config.report_dir, "reportDir");
+            let signing_key = signing_key
+                .or(config.signing_key)
+                .map(|path| read_signing_key(&path).unwrap_or_else(|e| panic!("{}", e)));
+            let filters = match jurisdiction {
+                Some(jurisdiction) => vec![jurisdiction],
+                None => config.filters,
+            };
+            let webhooks = if webhooks.is_empty() {
+                config.webhooks
+            } else {
+                webhooks
+            };
+
+            // Start from cached results; only elections whose raw data
+            // changes are regenerated.
+            let options = ReportOptions {
+                force_preprocess: false,
+                force_report: false,
+                only_missing: false,
+                filters: &filters,
+                preprocess_threads: config.preprocess_threads,
+                report_threads: config.report_threads,
+                max_memory: config.max_memory,
+                site_url: site_url.as_deref().or(config.site_url.as_deref()),
+                webhooks: &webhooks,
+                stale_elections: None,
+                round_snapshots: false,
+                ranking_patterns: false,
+                elimination_tolerance: None,
+                hypothetical_methods: false,
+                strategy_susceptibility: false,
+                condense_rounds: false,
+                signing_key: signing_key.as_ref(),
+                contest_timeout: contest_timeout
+                    .or(config.contest_timeout)
+                    .map(Duration::from_secs),
+                zstd_level: zstd_level.or(config.zstd_level),
+            };
+
+            watch(
+                &meta_dir,
+                &raw_data_dir,
+                &report_dir,
+                &preprocessed_dir,
+                &options,
+                Duration::from_secs(interval),
+            );
+        }
+        Command::Du {
+            meta_dir,
+            preprocessed_dir,
+            report_dir,
+            quota,
+        } => {
+            du(&meta_dir, &preprocessed_dir, &report_dir, quota);
+        }
+        Command::Prune {
+            meta_dir,
+            preprocessed_dir,
+            report_dir,
+            retention_days,
+            dry_run,
+        } => {
+            let summary = retention_days
+                .checked_mul(24 * 60 * 60)
+                .ok_or_else(|| format!("--retention-days {} is too large", retention_days))
+                .and_then(|secs| {
+                    prune(
+                        &meta_dir,
+                        &preprocessed_dir,
+                        &report_dir,
+                        Duration::from_secs(secs),
+                        dry_run,
+                    )
+                });
+            match summary {
+                Ok(summary) => println!("{}", serde_json::to_string(&summary).unwrap()),
+                Err(e) => {
+                    ranked_vote::log_error!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Command::Recheck {
+            meta_dir,
+            preprocessed_dir,
+            report_dir,
+            filters,
+        } => {
+            let results = recheck(&meta_dir, &preprocessed_dir, &report_dir, &filters);
+            if results
+                .iter()
+                .any(|r| matches!(r.status, RecheckStatus::Differs | RecheckStatus::Failed))
+            {
+                std::process::exit(1);
+            }
         }
         Command::RebuildIndex { report_dir } => {
             rebuild_index(&report_dir);
         }
+        Command::ConvertPreprocessed {
+            preprocessed_dir,
+            remove_legacy,
+            zstd_level,
+        } => {
+            convert_preprocessed(&preprocessed_dir, remove_legacy, zstd_level);
+        }
+        Command::RankingLimits {
+            preprocessed_dir,
+            limits,
+            output,
+        } => {
+            ranking_limits(&preprocessed_dir, &limits, output.as_deref());
+        }
+        Command::ExportApi {
+            report_dir,
+            api_dir,
+        } => {
+            export_api(&report_dir, &api_dir);
+        }
+        Command::Schema { typescript } => {
+            schema(typescript);
+        }
+        Command::CodegenTs { output_dir } => {
+            codegen_ts(&output_dir);
+        }
+        Command::TabulatePatterns {
+            patterns_file,
+            nyc_style,
+        } => {
+            let tabulation_options = TabulationOptions {
+                nyc_style: Some(nyc_style),
+                ..TabulationOptions::default()
+            };
+
+            tabulate_patterns(&patterns_file, &tabulation_options);
+        }
+        Command::DumpBallots {
+            contest_path,
+            output,
+        } => {
+            dump_ballots(&contest_path, output.as_deref());
+        }
+        Command::CompareRctab {
+            contest_path,
+            config_file,
+            summary_file,
+        } => {
+            if !compare_rctab(&contest_path, &config_file, &summary_file).matches {
+                std::process::exit(1);
+            }
+        }
+        Command::TransferGraph {
+            report_file,
+            mermaid,
+        } => {
+            transfer_graph(&report_file, mermaid);
+        }
+        Command::View { report_file } => {
+            view(&report_file);
+        }
+        Command::SigningPublicKey { signing_key } => {
+            signing_public_key(&signing_key);
+        }
+        Command::VerifySignatures { dir, public_key } => {
+            if !verify_signatures(&dir, &public_key) {
+                std::process::exit(1);
+            }
+        }
     }
 }
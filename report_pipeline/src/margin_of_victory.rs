@@ -0,0 +1,191 @@
+//! Bounds on the margin of victory (MOV) of an IRV contest: the fewest
+//! ballots that would have to be changed to elect a different candidate.
+//!
+//! The final-round margin alone overstates the MOV when an earlier
+//! elimination was close. Both bounds are computed from the rounds of the
+//! tabulation:
+//!
+//! - The lower bound follows from the eliminations. Each round eliminates a
+//!   set of candidates whose combined votes are fewer than any continuing
+//!   candidate's, so they are eliminated whatever the order between them.
+//!   Changing a ballot moves the difference between the two by at most two
+//!   votes, so the outcome cannot change with fewer than half of the
+//!   smallest such difference (or half the final-round margin) changed.
+//! - The upper bound is the cheapest way found to eliminate the winner:
+//!   for each round, the number of ballots ranking the winner first that
+//!   must instead rank the round's weakest other candidate first to put the
+//!   winner last. These ballots count for the winner in every round, so the
+//!   change does not affect who else is eliminated before that round.
+//!
+//! The bounds differ when the cheapest change found only ties the winner
+//! with another candidate, or when a cheaper change would alter earlier
+//! eliminations; an exact MOV then needs a search over elimination orders
+//! with an integer program for each.
+
+use crate::model::election::CandidateId;
+use crate::model::report::MarginOfVictory;
+use crate::report::winner;
+use crate::tabulator::TabulatorRound;
+use std::collections::BTreeMap;
+
+/// Votes of each candidate in a round.
+fn candidate_votes(round: &TabulatorRound) -> BTreeMap<CandidateId, u32> {
+    round
+        .allocations
+        .iter()
+        .flat_map(|a| Some((a.allocatee.candidate_id()?, a.votes)))
+        .collect()
+}
+
+/// Half of a vote difference, rounded up, or zero if it is not positive.
+fn half_up(difference: i64) -> u32 {
+    ((difference.max(0) + 1) / 2) as u32
+}
+
+/// Fewest changed ballots that could alter an elimination, or let the
+/// runner-up catch up with the winner in the final round.
+fn lower_bound(rounds: &[TabulatorRound], winner: CandidateId) -> u32 {
+    let votes: Vec<BTreeMap<CandidateId, u32>> = rounds.iter().map(candidate_votes).collect();
+    let eliminations = votes.windows(2).map(|pair| {
+        let (current, next) = (&pair[0], &pair[1]);
+        let eliminated: i64 = current
+            .iter()
+            .filter(|(c, _)| !next.contains_key(c))
+            .map(|(_, &v)| v as i64)
+            .sum();
+        let weakest_continuing = current
+            .iter()
+            .filter(|(c, _)| next.contains_key(c))
+            .map(|(_, &v)| v as i64)
+            .min()
+            .unwrap_or(0);
+        half_up(weakest_continuing - eliminated)
+    });
+
+    let last = votes.last().unwrap();
+    let runner_up = last
+        .iter()
+        .filter(|(&c, _)| c != winner)
+        .map(|(_, &v)| v)
+        .max()
+        .unwrap_or(0);
+    let final_margin = half_up(last[&winner] as i64 - runner_up as i64);
+
+    eliminations.fold(final_margin, u32::min)
+}
+
+/// Compute bounds on the margin of victory from a contest's rounds.
+/// Returns `None` if there is no winner, or the final round has fewer than
+/// two candidates.
+pub fn generate_margin_of_victory(rounds: &[TabulatorRound]) -> Option<MarginOfVictory> {
+    let winner = winner(rounds)?;
+    if candidate_votes(rounds.last()?).len() < 2 {
+        return None;
+    }
+    // Ballots ranking the winner first, which can be changed without
+    // affecting any other candidate's votes.
+    let first_choices = candidate_votes(&rounds[0])[&winner];
+
+    let (upper_bound, round, beneficiary) = rounds
+        .iter()
+        .enumerate()
+        .filter_map(|(i, round)| {
+            let votes = candidate_votes(round);
+            let winner_votes = votes[&winner] as i64;
+            let mut others: Vec<(u32, CandidateId)> = votes
+                .iter()
+                .filter(|(&c, _)| c != winner)
+                .map(|(&c, &v)| (v, c))
+                .collect();
+            others.sort();
+            let (weakest_votes, weakest) = *others.first()?;
+
+            // The winner must fall below the weakest other candidate, who
+            // gains the changed ballots, and below everyone else.
+            let mut changes = (winner_votes - weakest_votes as i64) / 2 + 1;
+            if let Some(&(next_votes, _)) = others.get(1) {
+                changes = changes.max(winner_votes - next_votes as i64 + 1);
+            }
+            let changes = changes.max(1) as u32;
+            (changes <= first_choices).then_some((changes, i as u32 + 1, weakest))
+        })
+        .min()?;
+
+    Some(MarginOfVictory {
+        lower_bound: lower_bound(rounds, winner).min(upper_bound),
+        upper_bound,
+        round,
+        beneficiary,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tabulator::{Allocatee, TabulatorAllocation};
+    use std::cmp::Reverse;
+
+    fn round(votes: &[(u32, u32)]) -> TabulatorRound {
+        let mut allocations: Vec<TabulatorAllocation> = votes
+            .iter()
+            .map(|&(c, votes)| TabulatorAllocation {
+                allocatee: Allocatee::Candidate(CandidateId(c)),
+                votes,
+                shares: None,
+            })
+            .collect();
+        allocations.sort_by_key(|a| Reverse(a.votes));
+        TabulatorRound {
+            allocations,
+            undervote: 0,
+            overvote: 0,
+            continuing_ballots: votes.iter().map(|(_, v)| v).sum(),
+            transfers: vec![],
+            threshold: None,
+        }
+    }
+
+    #[test]
+    fn test_close_elimination() {
+        // The final round is won by 18 votes, but moving one ballot from A
+        // to B eliminates A in the first round.
+        let rounds = vec![
+            round(&[(0, 30), (1, 29), (2, 41)]),
+            round(&[(0, 59), (2, 41)]),
+        ];
+        assert_eq!(
+            Some(MarginOfVictory {
+                lower_bound: 1,
+                upper_bound: 1,
+                round: 1,
+                beneficiary: CandidateId(1),
+            }),
+            generate_margin_of_victory(&rounds)
+        );
+    }
+
+    #[test]
+    fn test_final_round() {
+        // B wins the final round by 10. Moving 5 ballots from B to A ties
+        // them, so the bounds differ by one.
+        let rounds = vec![
+            round(&[(0, 40), (1, 35), (2, 25)]),
+            round(&[(0, 45), (1, 55)]),
+        ];
+        assert_eq!(
+            Some(MarginOfVictory {
+                lower_bound: 5,
+                upper_bound: 6,
+                round: 1,
+                beneficiary: CandidateId(2),
+            }),
+            generate_margin_of_victory(&rounds)
+        );
+    }
+
+    #[test]
+    fn test_no_contest() {
+        assert_eq!(None, generate_margin_of_victory(&[round(&[(0, 10)])]));
+        assert_eq!(None, generate_margin_of_victory(&[]));
+    }
+}
@@ -0,0 +1,35 @@
+use serde::Serialize;
+
+/// Top-level manifest of the static API, written as `manifest.json` in the
+/// API directory.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiManifest {
+    /// Version prefix of artifact paths, e.g. `v1`.
+    pub version: String,
+    pub pipeline_version: String,
+    pub artifacts: Vec<ApiArtifact>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiArtifact {
+    /// Path relative to the API directory, including the version prefix.
+    pub path: String,
+    pub kind: ArtifactKind,
+    pub size: u64,
+    pub sha256: String,
+    /// Strong ETag for the artifact, also written to `{path}.etag`.
+    pub etag: String,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum ArtifactKind {
+    Index,
+    Trends,
+    Summary,
+    Report,
+    PrecinctMap,
+    ContestMetadata,
+}
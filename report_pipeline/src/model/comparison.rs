@@ -0,0 +1,55 @@
+use serde::Serialize;
+
+/// Comparison of a contest's tabulation with the results of RCTab, the
+/// RCVRC's reference tabulator, for the same contest. Printed to stdout by
+/// the `compare-rctab` command.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RctabComparison {
+    /// Whether the winners, and the candidates' votes in every round both
+    /// tabulations have, are the same.
+    pub matches: bool,
+    pub winner: Option<String>,
+    pub rctab_winner: Option<String>,
+    /// Number of rounds of each tabulation. RCTab stops once a candidate
+    /// has a majority, unless configured to continue until two candidates
+    /// remain, as this pipeline does.
+    pub rounds: u32,
+    pub rctab_rounds: u32,
+    /// RCTab rules that this pipeline applied differently, or can't apply.
+    pub option_mismatches: Vec<OptionMismatch>,
+    /// Rounds, among those both tabulations have, in which any candidate's
+    /// votes differ.
+    pub round_differences: Vec<RoundDifference>,
+}
+
+/// An RCTab rule whose setting differs from how this pipeline counted the
+/// contest.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionMismatch {
+    /// Name of the rule in the RCTab config, e.g. `overvoteRule`.
+    pub option: String,
+    pub rctab: String,
+    /// How this pipeline counted the contest, as the RCTab setting.
+    pub ours: String,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RoundDifference {
+    /// Round number, starting from 1.
+    pub round: u32,
+    pub allocations: Vec<AllocationDifference>,
+}
+
+/// A candidate's votes in a round, where the tabulations differ. A
+/// candidate absent from a tabulation's round has no votes in it.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AllocationDifference {
+    pub candidate: String,
+    pub votes: u32,
+    /// RCTab's votes, which are fractional under some rules.
+    pub rctab_votes: f64,
+}
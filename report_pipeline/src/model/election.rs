@@ -1,4 +1,9 @@
-use crate::model::metadata::TabulationOptions;
+use crate::model::metadata::{
+    District, DuplicateBallotPolicy, OvervotePolicy, SkippedRankPolicy, TabulationOptions,
+};
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
 use serde::de::{self, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::BTreeMap;
@@ -42,17 +47,42 @@ impl<'de> Deserialize<'de> for CandidateId {
     }
 }
 
-#[derive(Ord, PartialOrd, Eq, PartialEq, Clone, Serialize, Deserialize, Debug)]
+impl JsonSchema for CandidateId {
+    fn schema_name() -> String {
+        "CandidateId".to_string()
+    }
+
+    /// Serialized as the candidate's index in the report's candidates.
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        u32::json_schema(gen)
+    }
+}
+
+#[derive(Ord, PartialOrd, Eq, PartialEq, Clone, Serialize, Deserialize, JsonSchema, Debug)]
 pub enum CandidateType {
     WriteIn,
     Regular,
     QualifiedWriteIn,
 }
 
-#[derive(Ord, PartialOrd, Eq, PartialEq, Clone, Serialize, Deserialize, Debug)]
+#[derive(Ord, PartialOrd, Eq, PartialEq, Clone, Serialize, Deserialize, JsonSchema, Debug)]
 pub struct Candidate {
     pub name: String,
     pub candidate_type: CandidateType,
+    /// Name to display instead of the name in the raw data, from metadata.
+    #[serde(
+        rename = "displayName",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub display_name: Option<String>,
+    /// Identifier of the candidate across elections, from metadata.
+    #[serde(
+        rename = "canonicalId",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub canonical_id: Option<String>,
 }
 
 impl Candidate {
@@ -60,8 +90,15 @@ impl Candidate {
         Candidate {
             name,
             candidate_type,
+            display_name: None,
+            canonical_id: None,
         }
     }
+
+    /// The name to display for this candidate.
+    pub fn display_name(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.name)
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Hash, Ord, PartialOrd, Eq)]
@@ -71,15 +108,36 @@ pub enum Choice {
     Overvote,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Ballot {
     pub id: String,
     pub choices: Vec<Choice>,
+    /// Counting group (vote method, e.g. election day or vote by mail) the
+    /// ballot was tallied in, for formats that record it.
+    pub counting_group: Option<String>,
+    /// Precinct (or precinct portion) the ballot was cast in, for formats
+    /// that record it.
+    pub precinct: Option<String>,
 }
 
 impl Ballot {
     pub fn new(id: String, choices: Vec<Choice>) -> Ballot {
-        Ballot { id, choices }
+        Ballot {
+            id,
+            choices,
+            counting_group: None,
+            precinct: None,
+        }
+    }
+
+    pub fn with_counting_group(mut self, counting_group: Option<String>) -> Ballot {
+        self.counting_group = counting_group;
+        self
+    }
+
+    pub fn with_precinct(mut self, precinct: Option<String>) -> Ballot {
+        self.precinct = precinct;
+        self
     }
 }
 
@@ -87,7 +145,16 @@ impl Ballot {
 pub struct NormalizedBallot {
     pub id: String,
     choices: Vec<CandidateId>,
+    /// Number of `choices` counted before the ballot is exhausted by
+    /// consecutive skipped ranks, if it is. The choices after them are
+    /// kept to show how the ballot was marked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    exhausted_after: Option<u32>,
     pub overvoted: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counting_group: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub precinct: Option<String>,
 }
 
 impl NormalizedBallot {
@@ -95,17 +162,60 @@ impl NormalizedBallot {
         NormalizedBallot {
             id,
             choices,
+            exhausted_after: None,
             overvoted,
+            counting_group: None,
+            precinct: None,
         }
     }
 
+    /// Exhaust the ballot after its first `counted` choices, if given, as
+    /// consecutive skipped ranks do under some rules. An overvote after
+    /// them is never reached.
+    pub fn with_exhausted_after(mut self, counted: Option<u32>) -> NormalizedBallot {
+        if counted.is_some() {
+            self.exhausted_after = counted;
+            self.overvoted = false;
+        }
+        self
+    }
+
+    pub fn exhausted_after(&self) -> Option<u32> {
+        self.exhausted_after
+    }
+
+    pub fn with_counting_group(mut self, counting_group: Option<String>) -> NormalizedBallot {
+        self.counting_group = counting_group;
+        self
+    }
+
+    pub fn with_precinct(mut self, precinct: Option<String>) -> NormalizedBallot {
+        self.precinct = precinct;
+        self
+    }
+
+    /// The choices counted for the ballot.
     #[allow(unused)]
     pub fn choices(&self) -> Vec<CandidateId> {
-        self.choices.clone()
+        self.choice_slice().to_vec()
+    }
+
+    /// The choices counted for the ballot, without copying them.
+    pub fn choice_slice(&self) -> &[CandidateId] {
+        match self.exhausted_after {
+            Some(counted) => &self.choices[..(counted as usize).min(self.choices.len())],
+            None => &self.choices,
+        }
+    }
+
+    /// Every choice marked on the ballot, including those after it is
+    /// exhausted.
+    pub fn marked_choices(&self) -> &[CandidateId] {
+        &self.choices
     }
 
     pub fn top_vote(&self) -> Choice {
-        match self.choices.first() {
+        match self.choice_slice().first() {
             Some(v) => Choice::Vote(*v),
             None => {
                 if self.overvoted {
@@ -118,16 +228,32 @@ impl NormalizedBallot {
     }
 
     pub fn pop_top_vote(mut self) -> Self {
-        if !self.choices.is_empty() {
+        if !self.choice_slice().is_empty() {
             self.choices.remove(0);
+            if let Some(counted) = &mut self.exhausted_after {
+                *counted -= 1;
+            }
         }
         self
     }
 }
 
+#[derive(Clone)]
 pub struct Election {
     pub candidates: Vec<Candidate>,
     pub ballots: Vec<Ballot>,
+    /// Number of ballots that were modified during adjudication, for formats
+    /// that record it.
+    pub adjudicated_ballots: Option<u32>,
+    /// How adjudication changed the ballots, for formats that record both
+    /// versions of an adjudicated ballot.
+    pub adjudication: Option<AdjudicationImpact>,
+    /// Ballots repeating an earlier ballot's ID, once preprocessing has
+    /// checked for them.
+    pub duplicate_ballots: Option<DuplicateBallots>,
+    /// IDs marked on ballots that the candidate manifest doesn't list, for
+    /// formats with a manifest.
+    pub unknown_candidates: Vec<UnknownCandidate>,
 }
 
 impl Election {
@@ -135,17 +261,299 @@ impl Election {
         Election {
             candidates,
             ballots,
+            adjudicated_ballots: None,
+            adjudication: None,
+            duplicate_ballots: None,
+            unknown_candidates: Vec::new(),
+        }
+    }
+
+    /// Renumber candidates in sorted order (by name), so that candidate IDs
+    /// don't depend on the order in which a reader happened to encounter
+    /// them and stay stable when an election is re-read.
+    pub fn sort_candidates(&mut self) {
+        let mut order: Vec<usize> = (0..self.candidates.len()).collect();
+        order.sort_by(|a, b| self.candidates[*a].cmp(&self.candidates[*b]));
+
+        let mut new_ids = vec![CandidateId(0); order.len()];
+        for (new_index, old_index) in order.iter().enumerate() {
+            new_ids[*old_index] = CandidateId(new_index as u32);
+        }
+
+        self.candidates = order.iter().map(|i| self.candidates[*i].clone()).collect();
+        for ballot in &mut self.ballots {
+            for choice in &mut ballot.choices {
+                if let Choice::Vote(candidate) = choice {
+                    *candidate = new_ids[candidate.0 as usize];
+                }
+            }
         }
+        self.adjudication = self.adjudication.as_ref().map(|a| a.renumber(&new_ids));
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct NormalizedElection {
     pub candidates: Vec<Candidate>,
     pub ballots: Vec<NormalizedBallot>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adjudicated_ballots: Option<u32>,
+    /// How adjudication changed the ballots.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adjudication: Option<AdjudicationImpact>,
+    /// Ballots repeating an earlier ballot's ID in the raw data.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicate_ballots: Option<DuplicateBallots>,
+    /// Differences between the candidate manifest and the candidates marked
+    /// on the ballots.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub candidate_reconciliation: Option<CandidateReconciliation>,
+    /// Marks at each rank position of the ballots before normalization.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rank_positions: Option<Vec<RankPositionStatistics>>,
+    /// Candidates ranked more than once on a ballot, before normalization
+    /// collapses the duplicates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicate_rankings: Option<DuplicateRankings>,
+    /// How normalization changed the first-round votes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_round_adjustments: Option<FirstRoundAdjustments>,
+    /// How ballots with overvotes were handled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overvote_handling: Option<OvervoteHandling>,
+    /// How many ballots were exhausted by skipped ranks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped_rank_handling: Option<SkippedRankHandling>,
+    /// Ballots that normalization cut off before candidates they marked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truncated_ballots: Option<Vec<TruncatedBallot>>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+/// A ballot cut off by an overvote or skipped ranks before some of the
+/// candidates it marked.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TruncatedBallot {
+    /// Index of the ballot in the normalized ballots.
+    pub ballot: u32,
+    /// Candidates marked after the cut, in rank order, that the normalized
+    /// ballot doesn't rank.
+    pub dropped: Vec<CandidateId>,
+}
+
+/// Ballots in a contest's raw data with the same ID as an earlier ballot,
+/// and how many of them the duplicate ballot policy removed.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateBallots {
+    pub policy: DuplicateBallotPolicy,
+    /// Ballots with the ID of an earlier ballot.
+    pub duplicate_ids: u32,
+    /// Of those, ballots whose choices, counting group and precinct are also
+    /// identical to the earlier ballot's.
+    pub identical_records: u32,
+    /// Ballots removed under the policy.
+    pub removed: u32,
+    /// Some of the repeated IDs, to find them by in the raw data.
+    pub examples: Vec<String>,
+}
+
+/// Differences between a contest's candidate manifest and the candidates
+/// marked on its ballots.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CandidateReconciliation {
+    /// IDs marked on ballots that the manifest doesn't list.
+    pub unknown_candidates: Vec<UnknownCandidate>,
+    /// Candidates in the manifest that no ballot marks.
+    pub unmarked_candidates: Vec<CandidateId>,
+}
+
+/// An external candidate ID marked on ballots but missing from the
+/// manifest. Its marks count as skipped ranks.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UnknownCandidate {
+    /// The ID as it appears in the ballot data.
+    pub external_id: String,
+    pub marks: u32,
+}
+
+/// Ballots that ranked the same candidate more than once.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateRankings {
+    /// Ballots that ranked any candidate more than once.
+    pub ballots: u32,
+    /// Per candidate, in descending order of ballots.
+    pub candidates: Vec<CandidateDuplicates>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CandidateDuplicates {
+    pub candidate: CandidateId,
+    /// Ballots that ranked this candidate more than once.
+    pub ballots: u32,
+}
+
+/// Counts of the marks at one rank position of the raw ballots, before
+/// normalization discards overvotes and skipped ranks.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RankPositionStatistics {
+    /// Rank position, starting from 1.
+    pub rank: u32,
+    /// Ballots with a vote for a single candidate at this rank.
+    pub votes: u32,
+    /// Ballots with more than one candidate marked at this rank.
+    pub overvotes: u32,
+    /// Ballots with no candidate marked at this rank.
+    pub undervotes: u32,
+    /// Undervotes followed by a mark at a later rank.
+    pub skipped: u32,
+}
+
+/// How normalization changed the first-round votes, compared to counting
+/// each ballot for the first candidate marked on it. Official canvasses
+/// that treat skipped ranks or exhausted ballots differently differ from
+/// our first round by these counts.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FirstRoundAdjustments {
+    /// Ballots exhausted by an overvote before any candidate was marked.
+    pub overvoted: u32,
+    /// Per candidate with any adjustment, in candidate order.
+    pub candidates: Vec<CandidateAdjustment>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CandidateAdjustment {
+    pub candidate: CandidateId,
+    /// Ballots counted for the candidate although their first rank was
+    /// blank or overvoted.
+    pub skipped_ranks: u32,
+    /// Ballots marking the candidate first that were exhausted or set
+    /// aside before the first round, e.g. for too many skipped ranks.
+    pub exhausted: u32,
+}
+
+/// Counts of ballots with overvotes under the election's overvote policy.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OvervoteHandling {
+    pub policy: OvervotePolicy,
+    /// Ballots with an overvote at any rank.
+    pub overvoted_ballots: u32,
+    /// Ballots exhausted at an overvote (under the `exhaust` policy).
+    pub exhausted: u32,
+    /// Ballots that skipped an overvote and went on to rank a candidate
+    /// (under the `skip` policy).
+    pub skipped: u32,
+}
+
+/// Counts of ballots exhausted under the election's skipped-rank policy.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedRankHandling {
+    pub policy: SkippedRankPolicy,
+    /// Ballots with rankings after two consecutive skipped ranks that were
+    /// exhausted there (under the `exhaustAfterTwo` policy).
+    pub exhausted: u32,
+}
+
+/// How adjudication changed the ballots of a contest, comparing the marks
+/// on each adjudicated ballot as scanned to those after adjudication. First
+/// choices are the first candidate marked, before normalization.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AdjudicationImpact {
+    /// Adjudicated ballots whose marks in the contest changed.
+    pub changed_ballots: u32,
+    /// Ballots whose first choice changed, by their first choice before and
+    /// after adjudication.
+    pub first_choice_changes: Vec<FirstChoiceChange>,
+    /// Per candidate whose first choices changed, in candidate order.
+    pub candidates: Vec<AdjudicationCandidate>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FirstChoiceChange {
+    /// First choice as scanned, or null if the first mark was an overvote
+    /// or there was none.
+    pub original: Option<CandidateId>,
+    /// First choice after adjudication, or null likewise.
+    pub adjudicated: Option<CandidateId>,
+    pub ballots: u32,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AdjudicationCandidate {
+    pub candidate: CandidateId,
+    /// Ballots that adjudication made first choices for the candidate.
+    pub gained: u32,
+    /// Ballots with the candidate as first choice as scanned that
+    /// adjudication gave another first choice, or none.
+    pub lost: u32,
+}
+
+impl AdjudicationImpact {
+    /// Summarize how adjudication changed `changed_ballots` ballots, given
+    /// the number of them with each pair of first choices before and after.
+    pub fn new(
+        changed_ballots: u32,
+        first_choices: BTreeMap<(Option<CandidateId>, Option<CandidateId>), u32>,
+    ) -> AdjudicationImpact {
+        // Ballots gained and lost, by candidate.
+        let mut candidates: BTreeMap<CandidateId, (u32, u32)> = BTreeMap::new();
+        let mut first_choice_changes = Vec::new();
+        for ((original, adjudicated), ballots) in first_choices {
+            if original == adjudicated {
+                continue;
+            }
+            if let Some(candidate) = original {
+                candidates.entry(candidate).or_default().1 += ballots;
+            }
+            if let Some(candidate) = adjudicated {
+                candidates.entry(candidate).or_default().0 += ballots;
+            }
+            first_choice_changes.push(FirstChoiceChange {
+                original,
+                adjudicated,
+                ballots,
+            });
+        }
+
+        AdjudicationImpact {
+            changed_ballots,
+            first_choice_changes,
+            candidates: candidates
+                .into_iter()
+                .map(|(candidate, (gained, lost))| AdjudicationCandidate {
+                    candidate,
+                    gained,
+                    lost,
+                })
+                .collect(),
+        }
+    }
+
+    /// Renumber candidates, as `Election::sort_candidates` does.
+    fn renumber(&self, new_ids: &[CandidateId]) -> AdjudicationImpact {
+        let renumber = |candidate: Option<CandidateId>| candidate.map(|c| new_ids[c.0 as usize]);
+        let first_choices = self
+            .first_choice_changes
+            .iter()
+            .map(|c| ((renumber(c.original), renumber(c.adjudicated)), c.ballots))
+            .collect();
+        AdjudicationImpact::new(self.changed_ballots, first_choices)
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ElectionInfo {
     /// Name of election.
@@ -173,6 +581,89 @@ pub struct ElectionInfo {
     pub loader_params: Option<BTreeMap<String, String>>,
 
     pub website: Option<String>,
+
+    /// BCP 47 language tag of the jurisdiction's names, e.g. "fr-CA".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+
+    /// Translations of the office name, keyed by BCP 47 language tag.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub office_name_translations: BTreeMap<String, String>,
+
+    /// Number of seats elected to the office at once (1 if absent).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seats: Option<u32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub district: Option<District>,
+
+    /// Length of the office's term, in years.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub term_years: Option<u32>,
+
+    /// Caveats about the contest's data, from its metadata.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+
+    /// Stable identifier of the office across elections, if set in its
+    /// metadata.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub office_id: Option<String>,
+
+    /// Number of candidates each voter may vote for, for a non-ranked
+    /// contest tallied by plurality.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vote_for: Option<u32>,
+}
+
+impl ElectionInfo {
+    /// Stable identifier of the office across elections: the one set in
+    /// its metadata, or else `<jurisdiction path>/<office key>`.
+    pub fn office_id(&self) -> String {
+        self.office_id
+            .clone()
+            .unwrap_or_else(|| format!("{}/{}", self.jurisdiction_path, self.office))
+    }
+}
+
+/// A raw data file that a contest was read from.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceFile {
+    /// File name, relative to the election's raw data directory.
+    pub name: String,
+    /// Hex-encoded SHA-256 hash of the file contents.
+    pub sha256: String,
+}
+
+/// Record of the inputs and rules that produced a report, so that it can
+/// be audited and reproduced.
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Provenance {
+    pub source_files: Vec<SourceFile>,
+    pub data_format: String,
+    pub loader_params: Option<BTreeMap<String, String>>,
+    pub normalization: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overvote_policy: Option<OvervotePolicy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped_rank_policy: Option<SkippedRankPolicy>,
+    pub tabulation_options: TabulationOptions,
+    /// The statutory rule set the contest was counted under, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_set: Option<RuleSetReference>,
+    /// Version of the report pipeline, including its git commit.
+    pub pipeline_version: String,
+}
+
+/// A statutory rule set, by name and with the citation of its statute.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleSetReference {
+    pub name: String,
+    pub title: String,
+    pub citation: String,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -180,4 +671,97 @@ pub struct ElectionInfo {
 pub struct ElectionPreprocessed {
     pub info: ElectionInfo,
     pub ballots: NormalizedElection,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_candidates() {
+        let mut election = Election::new(
+            vec![
+                Candidate::new("Carol".to_string(), CandidateType::Regular),
+                Candidate::new("Alice".to_string(), CandidateType::Regular),
+                Candidate::new("Bob".to_string(), CandidateType::Regular),
+            ],
+            vec![Ballot::new(
+                "1".to_string(),
+                vec![
+                    Choice::Vote(CandidateId(0)),
+                    Choice::Undervote,
+                    Choice::Vote(CandidateId(2)),
+                    Choice::Vote(CandidateId(1)),
+                ],
+            )],
+        );
+        election.sort_candidates();
+
+        let names: Vec<&str> = election
+            .candidates
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(vec!["Alice", "Bob", "Carol"], names);
+        assert_eq!(
+            vec![
+                Choice::Vote(CandidateId(2)),
+                Choice::Undervote,
+                Choice::Vote(CandidateId(1)),
+                Choice::Vote(CandidateId(0)),
+            ],
+            election.ballots[0].choices
+        );
+    }
+
+    #[test]
+    fn test_adjudication_impact() {
+        let (a, b, c) = (CandidateId(0), CandidateId(1), CandidateId(2));
+        let mut election = Election::new(
+            vec![
+                Candidate::new("Carol".to_string(), CandidateType::Regular),
+                Candidate::new("Alice".to_string(), CandidateType::Regular),
+                Candidate::new("Bob".to_string(), CandidateType::Regular),
+            ],
+            vec![],
+        );
+        // Adjudication resolved three overvotes for Carol and one for Alice,
+        // moved one ballot from Alice to Bob, and changed a later rank on
+        // another.
+        let first_choices = [
+            ((None, Some(a)), 3),
+            ((None, Some(b)), 1),
+            ((Some(b), Some(c)), 1),
+        ];
+        election.adjudication = Some(AdjudicationImpact::new(
+            6,
+            first_choices.iter().copied().collect(),
+        ));
+        election.sort_candidates();
+
+        let impact = election.adjudication.unwrap();
+        assert_eq!(6, impact.changed_ballots);
+        assert_eq!(
+            vec![
+                (None, Some(a), 1),
+                (None, Some(c), 3),
+                (Some(a), Some(b), 1)
+            ],
+            impact
+                .first_choice_changes
+                .iter()
+                .map(|c| (c.original, c.adjudicated, c.ballots))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![(a, 1, 1), (b, 1, 0), (c, 3, 0)],
+            impact
+                .candidates
+                .iter()
+                .map(|c| (c.candidate, c.gained, c.lost))
+                .collect::<Vec<_>>()
+        );
+    }
 }
@@ -1,7 +1,8 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 /// Represents a body that oversees elections for one or more constituancies.
 pub struct Jurisdiction {
@@ -11,21 +12,86 @@ pub struct Jurisdiction {
     pub path: String,
     /// Kind of electoral commission, e.g. municipal, state.
     pub kind: String,
+    /// BCP 47 language tag of the names in this metadata, e.g. "fr-CA".
+    /// Reports are assumed to be in English if absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// Decimal places of the percentages in reports (1 if absent).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percent_decimals: Option<u32>,
     /// A mapping from id of elected positions to their display names.
     pub offices: BTreeMap<String, Office>,
     /// A list of elections under this commission.
     pub elections: BTreeMap<String, ElectionMetadata>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 /// Represents an elected office in this constituancy.
 pub struct Office {
     /// Name of the office.
     pub name: String,
+    /// Translations of the name, keyed by BCP 47 language tag.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub translations: BTreeMap<String, String>,
+    /// Number of seats elected to the office at once (1 if absent).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seats: Option<u32>,
+    /// The district the office represents, if it is not jurisdiction-wide.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub district: Option<District>,
+    /// Length of the office's term, in years.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub term_years: Option<u32>,
+    /// Identifier of the office that stays the same across elections, so
+    /// offices whose key changed (e.g. after redistricting) share a history.
+    /// Defaults to `<jurisdiction path>/<office key>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+}
+
+impl Office {
+    /// Describe any invalid values in this office's metadata.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.seats == Some(0) {
+            problems.push("seats must be at least 1".to_string());
+        }
+        if self.term_years == Some(0) {
+            problems.push("termYears must be at least 1".to_string());
+        }
+        if let Some(id) = &self.id {
+            if id.is_empty() || id.contains(char::is_whitespace) {
+                problems.push(format!("id {:?} must be non-empty, without spaces", id));
+            }
+        }
+        if let Some(district) = &self.district {
+            if district.id.trim().is_empty() {
+                problems.push("district id must not be empty".to_string());
+            }
+            if let Some(geometry) = &district.geometry {
+                if !geometry.ends_with(".geojson") && !geometry.ends_with(".json") {
+                    problems.push(format!("district geometry {} is not GeoJSON", geometry));
+                }
+            }
+        }
+        problems
+    }
 }
 
-#[derive(Serialize, Deserialize)]
+/// An electoral district within a jurisdiction.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct District {
+    /// Identifier of the district, e.g. "5" for the 5th council district.
+    pub id: String,
+    /// GeoJSON file of the district's boundary, relative to the
+    /// jurisdiction's raw data directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geometry: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ElectionMetadata {
     /// Name of election.
@@ -38,20 +104,122 @@ pub struct ElectionMetadata {
 
     pub tabulation_options: Option<TabulationOptions>,
 
+    /// May be omitted if the election has a rule set, which gives it.
+    #[serde(default)]
     pub normalization: String,
 
+    /// Name of the statutory rule set the election is counted under (see
+    /// `crate::rules`), which gives its normalization, policies and
+    /// tabulation options.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_set: Option<String>,
+
+    /// Whether ballots are exhausted at an overvote (the default) or skip
+    /// over it to their next ranking.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overvote_policy: Option<OvervotePolicy>,
+
+    /// Whether skipped (blank) ranks are passed over (the default) or two
+    /// consecutive ones exhaust the ballot.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped_rank_policy: Option<SkippedRankPolicy>,
+
+    /// Whether ballots repeating an earlier ballot's ID in the raw data are
+    /// kept (the default) or removed. Either way, they are reported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicate_ballot_policy: Option<DuplicateBallotPolicy>,
+
     pub contests: Vec<Contest>,
 
     pub files: BTreeMap<String, String>,
 
+    /// URLs to download raw data files from with `fetch`, keyed by file
+    /// name: the official source first, then any mirrors to fall back on.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub downloads: BTreeMap<String, Vec<String>>,
+
     pub website: Option<String>,
+
+    pub precinct_map: Option<PrecinctMap>,
+
+    /// Region (e.g. borough or county) of each precinct, for contests that
+    /// span several counting jurisdictions. Keys are precinct names, or
+    /// prefixes ending in `*`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub regions: BTreeMap<String, String>,
+
+    /// Share of first-round votes (e.g. 0.01 for 1%) below which candidates
+    /// are grouped into an "Others" row when displayed, for contests with
+    /// many minor candidates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub others_threshold: Option<f64>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
+/// Precinct boundaries used to map per-precinct results.
+pub struct PrecinctMap {
+    /// GeoJSON file of precinct boundaries, relative to the election's raw
+    /// data directory.
+    pub geojson: String,
+    /// Feature property holding the precinct name that ballots are matched
+    /// against.
+    pub property: String,
+}
+
+/// How normalization treats a ballot's overvoted rank.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum OvervotePolicy {
+    /// The ballot is exhausted at the overvote.
+    #[default]
+    Exhaust,
+    /// The overvoted rank is passed over, and the ballot continues to its
+    /// next ranking. It isn't a skipped rank for `SkippedRankPolicy`.
+    Skip,
+}
+
+/// How normalization treats a ballot's skipped (blank) ranks.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum SkippedRankPolicy {
+    /// Skipped ranks are passed over.
+    #[default]
+    Skip,
+    /// Two consecutive skipped ranks exhaust the ballot, as many statutes
+    /// require. Rankings after them are not counted.
+    ExhaustAfterTwo,
+}
+
+/// What preprocessing does with a ballot that has the same ID as an earlier
+/// ballot in a contest's raw data, as when a CVR archive contains an export
+/// batch twice.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum DuplicateBallotPolicy {
+    /// Duplicates are counted, but kept.
+    #[default]
+    Keep,
+    /// Duplicates whose choices, counting group and precinct are identical to
+    /// the earlier ballot's are removed; those that only share its ID are
+    /// kept.
+    RemoveIdentical,
+    /// Every ballot with the ID of an earlier ballot is removed.
+    RemoveDuplicateIds,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct TabulationOptions {
+    /// Eliminate every candidate who can no longer win at once, rather than
+    /// one candidate per round. Defaults to true; `false` was ignored before
+    /// single elimination was supported.
     pub eager: Option<bool>,
     pub nyc_style: Option<bool>,
+    /// Record each round's majority threshold, and the margins in reports,
+    /// as exact fractions alongside their rounded values.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exact_thresholds: Option<bool>,
 }
 
 impl Default for TabulationOptions {
@@ -59,13 +227,189 @@ impl Default for TabulationOptions {
         TabulationOptions {
             eager: Some(true),
             nyc_style: Some(false),
+            exact_thresholds: None,
         }
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Contest {
     pub office: String,
     pub loader_params: Option<BTreeMap<String, String>>,
+    /// Set while results are still being counted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reporting_status: Option<ReportingStatus>,
+    /// Names to display for candidates, keyed by their name in the raw data.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub display_names: BTreeMap<String, String>,
+    /// Identifiers of candidates across elections, keyed by their name in
+    /// the raw data. Candidates not listed are identified by jurisdiction
+    /// and displayed name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub candidate_ids: BTreeMap<String, String>,
+    /// Alternative rule sets to also tabulate the contest's ballots under.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<TabulationVariant>,
+    /// Subsets of the contest's ballots to also tabulate on their own.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subsets: Vec<BallotSubset>,
+    /// Caveats about the contest's data, e.g. "Write-ins are aggregated",
+    /// shown with its report.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+    /// First-round votes per candidate from the official canvass, keyed by
+    /// candidate name (as in the raw data, or as displayed). If given, the
+    /// report reconciles its first round against them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub official_first_round: Option<BTreeMap<String, u32>>,
+    /// For a non-ranked contest read from the same CVR as ranked ones, the
+    /// number of candidates each voter may vote for. Its ballots are
+    /// tallied by plurality, without rounds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vote_for: Option<u32>,
+}
+
+impl Contest {
+    /// Parameters for the contest's ballot reader: its loader parameters,
+    /// with `voteFor` for a non-ranked contest, so that the reader keeps
+    /// every candidate a ballot votes for.
+    pub fn reader_params(&self) -> BTreeMap<String, String> {
+        let mut params = self.loader_params.clone().unwrap_or_default();
+        if let Some(vote_for) = self.vote_for {
+            params.insert("voteFor".to_string(), vote_for.to_string());
+        }
+        params
+    }
+}
+
+/// An alternative rule set for a contest, published as a sibling report
+/// of the official one and tabulated from the same preprocessed ballots.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TabulationVariant {
+    /// Identifier of the variant, used in its report's file name.
+    pub id: String,
+    /// Display name, e.g. "Single elimination".
+    pub name: String,
+    /// Rules to tabulate the variant under, in place of the election's.
+    pub tabulation_options: TabulationOptions,
+}
+
+/// Whether a precinct name matches a pattern: a precinct name, or a prefix
+/// followed by `*`.
+fn precinct_matches(pattern: &str, precinct: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => precinct.starts_with(prefix),
+        None => precinct == pattern,
+    }
+}
+
+/// Look up the region of a precinct in an election's `regions`, preferring
+/// an exact match and then the longest matching prefix.
+pub fn region_for<'a>(regions: &'a BTreeMap<String, String>, precinct: &str) -> Option<&'a str> {
+    regions
+        .iter()
+        .filter(|(pattern, _)| precinct_matches(pattern, precinct))
+        .max_by_key(|(pattern, _)| (!pattern.ends_with('*'), pattern.len()))
+        .map(|(_, region)| region.as_str())
+}
+
+/// A subset of a contest's ballots, e.g. a vote method or a group of
+/// precincts, published as a child report of the contest and tabulated
+/// from the ballots in it alone.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BallotSubset {
+    /// Identifier of the subset, used in its report's file name.
+    pub id: String,
+    /// Display name, e.g. "Vote by mail".
+    pub name: String,
+    /// Precincts whose ballots are included, or all if empty. A name ending
+    /// in `*` matches every precinct starting with the rest of it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub precincts: Vec<String>,
+    /// Counting groups (vote methods) whose ballots are included, or all if
+    /// empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub counting_groups: Vec<String>,
+}
+
+impl BallotSubset {
+    /// Whether a ballot from the given precinct and counting group is in
+    /// the subset. Ballots without a precinct or counting group are only
+    /// included if the subset doesn't filter on it.
+    pub fn includes(&self, precinct: Option<&str>, counting_group: Option<&str>) -> bool {
+        let precinct_matches = self.precincts.is_empty()
+            || precinct.is_some_and(|precinct| {
+                self.precincts
+                    .iter()
+                    .any(|pattern| precinct_matches(pattern, precinct))
+            });
+        let counting_group_matches = self.counting_groups.is_empty()
+            || counting_group.is_some_and(|group| self.counting_groups.iter().any(|g| g == group));
+        precinct_matches && counting_group_matches
+    }
+}
+
+/// Reporting status of a contest whose results are not yet final.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportingStatus {
+    /// Whether the results are preliminary. The leading candidate is not
+    /// declared the winner until this is false.
+    pub preliminary: bool,
+    /// Estimated percentage (0-100) of ballots included in the results.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percent_reported: Option<f32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_office_problems() {
+        let office: Office = serde_json::from_str(
+            r#"{"name": "Council", "seats": 3, "district": {"id": "5"}, "termYears": 4}"#,
+        )
+        .unwrap();
+        assert!(office.problems().is_empty());
+
+        let office: Office = serde_json::from_str(
+            r#"{"name": "Council", "seats": 0, "district": {"id": " ", "geometry": "d5.shp"}}"#,
+        )
+        .unwrap();
+        assert_eq!(3, office.problems().len());
+    }
+
+    #[test]
+    fn test_ballot_subset_includes() {
+        let subset: BallotSubset = serde_json::from_str(
+            r#"{"id": "d5-mail", "name": "District 5 by mail",
+                "precincts": ["PCT 5*", "PCT 1101"], "countingGroups": ["Vote by Mail"]}"#,
+        )
+        .unwrap();
+        assert!(subset.includes(Some("PCT 5012"), Some("Vote by Mail")));
+        assert!(subset.includes(Some("PCT 1101"), Some("Vote by Mail")));
+        assert!(!subset.includes(Some("PCT 1102"), Some("Vote by Mail")));
+        assert!(!subset.includes(Some("PCT 5012"), Some("Election Day")));
+        assert!(!subset.includes(None, Some("Vote by Mail")));
+
+        let everyone: BallotSubset =
+            serde_json::from_str(r#"{"id": "all", "name": "All"}"#).unwrap();
+        assert!(everyone.includes(None, None));
+    }
+
+    #[test]
+    fn test_region_for() {
+        let regions: BTreeMap<String, String> = serde_json::from_str(
+            r#"{"01-*": "Region I", "01-4*": "Region IV", "01-446": "Aurora"}"#,
+        )
+        .unwrap();
+        assert_eq!(Some("Region I"), region_for(&regions, "01-120"));
+        assert_eq!(Some("Region IV"), region_for(&regions, "01-447"));
+        assert_eq!(Some("Aurora"), region_for(&regions, "01-446"));
+        assert_eq!(None, region_for(&regions, "02-120"));
+    }
 }
@@ -1,3 +1,6 @@
+pub mod api;
+pub mod comparison;
 pub mod election;
 pub mod metadata;
+pub mod notification;
 pub mod report;
@@ -0,0 +1,55 @@
+use serde::Serialize;
+
+/// Payload POSTed to webhooks. The `event` field identifies its kind.
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum Notification {
+    /// Sent once when a report run completes.
+    RunCompleted(RunSummary),
+    /// Sent for each contest whose winner differs from the previous run.
+    WinnerChanged(WinnerChange),
+}
+
+/// Outcome of a report run. Also printed to stdout when the `report`
+/// command exits.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RunSummary {
+    pub pipeline_version: String,
+    pub total_contests: usize,
+    /// Contests whose report was generated or loaded from cache.
+    pub successful_contests: usize,
+    pub generated_contests: usize,
+    pub cached_contests: usize,
+    /// Contests skipped because their report has no ballots, candidates or
+    /// rounds.
+    pub empty_contests: usize,
+    pub failed_contests: usize,
+    pub failures: Vec<ContestFailure>,
+    pub elections: usize,
+    pub winner_changes: Vec<WinnerChange>,
+    /// Peak resident memory of the process so far, in bytes, where the
+    /// platform reports it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak_rss: Option<u64>,
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestFailure {
+    /// Path of the election, including the jurisdiction path.
+    pub path: String,
+    pub office: String,
+    pub reason: String,
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WinnerChange {
+    /// Path of the election, including the jurisdiction path.
+    pub path: String,
+    pub office: String,
+    pub office_name: String,
+    pub previous_winner: String,
+    pub winner: String,
+}
@@ -1,15 +1,21 @@
-use crate::model::election::{Candidate, CandidateId, ElectionInfo};
-use crate::tabulator::{Allocatee, TabulatorRound};
+use crate::model::election::{
+    AdjudicationImpact, Candidate, CandidateId, CandidateReconciliation, DuplicateBallots,
+    DuplicateRankings, ElectionInfo, FirstRoundAdjustments, OvervoteHandling, Provenance,
+    RankPositionStatistics, SkippedRankHandling, SourceFile,
+};
+use crate::model::metadata::ReportingStatus;
+use crate::tabulator::{Allocatee, ExactRatio, TabulatorAllocation, TabulatorRound};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ReportIndex {
     pub elections: Vec<ElectionIndexEntry>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ElectionIndexEntry {
     pub path: String,
@@ -19,10 +25,13 @@ pub struct ElectionIndexEntry {
     pub contests: Vec<ContestIndexEntry>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize, JsonSchema, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct ContestIndexEntry {
     pub office: String,
+    /// Stable identifier of the office across elections.
+    #[serde(default)]
+    pub office_id: String,
     pub office_name: String,
     pub name: String,
     pub winner: String,
@@ -31,9 +40,370 @@ pub struct ContestIndexEntry {
     pub condorcet_winner: Option<String>,
     pub has_non_condorcet_winner: bool,
     pub has_write_in_by_name: bool,
+    /// Set if the contest could not be tabulated normally.
+    #[serde(default, skip_serializing_if = "TabulationStatus::is_tabulated")]
+    pub status: TabulationStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reporting_status: Option<ReportingStatus>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<VariantEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subsets: Vec<SubsetEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+}
+
+/// Check if a candidate name is a write-in (handles "Write-in", "Write in", "Undeclared Write-ins", "UWI", etc.)
+fn is_write_in_by_name(name: &str) -> bool {
+    let normalized = name.to_lowercase();
+    normalized == "write-in"
+        || normalized == "write in"
+        || normalized == "undeclared write-ins"
+        || normalized == "uwi"
+}
+
+impl ContestIndexEntry {
+    /// Build the index entry for a contest from its report. This is the only
+    /// place entries are built from reports, so that the index written
+    /// after a run and the one rebuilt from existing reports agree.
+    pub fn from_report(report: &ContestReport) -> ContestIndexEntry {
+        // Check if any candidate is named "Write-in" or "Write in" (case-insensitive)
+        let has_write_in_by_name = report
+            .candidates
+            .iter()
+            .any(|c| is_write_in_by_name(&c.name));
+
+        ContestIndexEntry {
+            office: report.info.office.clone(),
+            office_id: report.info.office_id(),
+            office_name: report.info.office_name.clone(),
+            name: report.info.name.clone(),
+            winner: report
+                .winner()
+                .map(|w| w.display_name().to_string())
+                .unwrap_or_else(|| "No Winner".to_string()),
+            num_candidates: report.num_candidates,
+            num_rounds: report.full_rounds().len() as u32,
+            condorcet_winner: report.condorcet.and_then(|c| {
+                report
+                    .candidates
+                    .get(c.0 as usize)
+                    .map(|candidate| candidate.display_name().to_string())
+            }),
+            has_non_condorcet_winner: report.condorcet.is_some()
+                && report.condorcet != report.winner,
+            has_write_in_by_name,
+            status: report.status,
+            reporting_status: report.reporting_status.clone(),
+            variants: report.variants.clone(),
+            subsets: report.subsets.clone(),
+            notes: report.info.notes.clone(),
+        }
+    }
+}
+
+/// What a report run did to each contest, written as `run-manifest.json`
+/// in the report directory.
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RunManifest {
+    pub pipeline_version: String,
+    pub contests: Vec<ContestManifestEntry>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestManifestEntry {
+    /// Path of the election, including the jurisdiction path.
+    pub path: String,
+    pub office: String,
+    pub status: ContestStatus,
+    /// Raw data files the report was generated from.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub source_files: Vec<SourceFile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub winner: Option<String>,
+    /// Differences from the report this run replaced, if the report was
+    /// regenerated and one existed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changes: Option<ReportChanges>,
+    /// Peak memory allocated while preprocessing the contest or generating
+    /// its report in this run, in bytes. Contests preprocessed in a batch
+    /// are each given the batch's peak.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_memory: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum ContestStatus {
+    /// The report was generated in this run.
+    Generated,
+    /// The report from a previous run was kept.
+    Cached,
+    /// The contest has no ballots, candidates or rounds, so no report is
+    /// listed for it.
+    Empty,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportChanges {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_winner: Option<String>,
+    pub winner_changed: bool,
+    /// Whether the votes or transfers of any round, or the number of
+    /// rounds, changed.
+    pub rounds_changed: bool,
+}
+
+/// How a published report compares to the report tabulated again from the
+/// contest's cached preprocessed ballots.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum RecheckStatus {
+    /// Byte-for-byte identical.
+    Identical,
+    /// The same content, serialized differently.
+    Equivalent,
+    /// Different content.
+    Differs,
+    /// The contest couldn't be tabulated again.
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RecheckResult {
+    /// Path of the contest, `{jurisdiction}/{election}/{office}`.
+    pub path: String,
+    pub status: RecheckStatus,
+    /// Top-level fields of the report whose content differs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A report on a contest tabulated under an alternative rule set, written
+/// next to the contest's official report.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct VariantEntry {
+    pub id: String,
+    pub name: String,
+    /// File name of the variant's report.
+    pub report: String,
+    pub winner: Option<String>,
+    pub num_rounds: u32,
+}
+
+/// A report on a subset of a contest's ballots, written under the
+/// contest's report directory.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SubsetEntry {
+    pub id: String,
+    pub name: String,
+    /// Path of the subset's report, relative to the contest's report
+    /// directory.
+    pub report: String,
+    pub ballot_count: u32,
+    pub winner: Option<String>,
+    pub num_rounds: u32,
+}
+
+/// Summary of all contests in one election, written as `summary.json` in
+/// the election's report directory.
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ElectionSummary {
+    pub path: String,
+    pub jurisdiction_path: String,
+    pub jurisdiction_name: String,
+    pub election_name: String,
+    pub date: String,
+    /// Largest ballot count of any contest. Ballots listing several contests
+    /// are counted in each, so contest ballot counts can't be summed.
+    pub ballots_cast: u32,
+    /// Ballots exhausted during tabulation, summed over contests.
+    pub exhausted_ballots: u32,
+    /// Exhausted ballots as a fraction of first-round continuing ballots,
+    /// over all contests.
+    pub exhaustion_rate: f32,
+    pub average_rounds: f32,
+    pub contests: Vec<ContestSummary>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestSummary {
+    pub office: String,
+    pub office_name: String,
+    pub winner: Option<String>,
+    pub ballot_count: u32,
+    /// Ballot count as a fraction of the election's ballots cast.
+    pub turnout: f32,
+    pub num_candidates: u32,
+    pub num_rounds: u32,
+    /// Winner's first-round votes as a fraction of first-round continuing
+    /// ballots.
+    pub winner_first_choice_share: Option<f32>,
+    /// Ballots continuing in the first round.
+    pub continuing_ballots: u32,
+    /// Ballots continuing in the first round but not in the last.
+    pub exhausted_ballots: u32,
+    pub exhaustion_rate: f32,
+}
+
+/// Ballots at one round of tabulation, aggregated by their remaining
+/// ranking of continuing candidates. Written as `round_snapshots.json` next
+/// to the report, so that each round can be reconstructed without the raw
+/// ballots.
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RoundSnapshot {
+    /// Round number, starting from 1.
+    pub round: u32,
+    /// Patterns in descending order of count.
+    pub patterns: Vec<BallotPattern>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BallotPattern {
+    /// Continuing candidates in the order ranked. The first is the
+    /// candidate the ballots count toward; empty if they are exhausted.
+    pub ranking: Vec<CandidateId>,
+    /// Whether the ballots end in an overvote, so that they count as an
+    /// overvote rather than an undervote once exhausted.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub overvoted: bool,
+    pub count: u32,
+}
+
+/// Number of ballots with each distinct ranking in a contest, written as
+/// `ranking_patterns.json` next to the report. See `crate::patterns` for the
+/// format of patterns.
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RankingPatterns {
+    /// Candidate names, indexed by the candidate IDs in patterns.
+    pub candidates: Vec<String>,
+    pub ballot_count: u32,
+    pub patterns: BTreeMap<String, u32>,
+}
+
+/// Lightweight page metadata for a contest, written as `meta.json` next to
+/// its report when a site URL is given.
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestMetadataStub {
+    pub title: String,
+    pub jurisdiction_name: String,
+    pub office_name: String,
+    pub election_name: String,
+    pub date: String,
+    /// Winner, or leading candidate if the results are preliminary.
+    pub winner: Option<String>,
+    pub preliminary: bool,
+    pub canonical_url: String,
+}
+
+/// One contest's key metrics in the time-series dataset written as
+/// `trends.json` and `trends.csv`.
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TrendPoint {
+    pub jurisdiction_path: String,
+    pub jurisdiction_name: String,
+    pub office: String,
+    pub office_name: String,
+    pub year: String,
+    pub date: String,
+    pub election_path: String,
+    pub ballot_count: u32,
+    pub num_candidates: u32,
+    pub num_rounds: u32,
+    pub exhaustion_rate: f32,
+    pub winner: Option<String>,
+    pub winner_first_choice_share: Option<f32>,
+}
+
+/// Contests of each office across elections, written as `by-office.json`
+/// in the root report directory.
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OfficeIndex {
+    pub offices: Vec<OfficeHistory>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OfficeHistory {
+    /// Stable identifier of the office, as in the index.
+    pub id: String,
+    pub jurisdiction_name: String,
+    /// Name of the office in its most recent contest.
+    pub office_name: String,
+    /// Contests for the office, oldest first.
+    pub contests: Vec<OfficeContestEntry>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OfficeContestEntry {
+    /// Path of the election, including the jurisdiction path.
+    pub election_path: String,
+    pub election_name: String,
+    pub date: String,
+    /// Key of the office in the election, which may differ between
+    /// elections.
+    pub office: String,
+    pub office_name: String,
+    pub winner: String,
+    #[serde(default, skip_serializing_if = "TabulationStatus::is_tabulated")]
+    pub status: TabulationStatus,
+}
+
+/// Contests each candidate appeared in, across elections, written as
+/// `candidates.json` in the root report directory.
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CandidateIndex {
+    pub candidates: Vec<CandidateHistory>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CandidateHistory {
+    /// Identifier of the candidate: the one given in metadata, or else the
+    /// jurisdiction path and a slug of the candidate's name.
+    pub id: String,
+    /// Displayed name of the candidate in their most recent contest.
+    pub name: String,
+    /// Contests the candidate appeared in, oldest first.
+    pub contests: Vec<CandidateContestEntry>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CandidateContestEntry {
+    /// Path of the election, including the jurisdiction path.
+    pub election_path: String,
+    pub election_name: String,
+    pub date: String,
+    pub office: String,
+    pub office_id: String,
+    pub office_name: String,
+    pub first_round_votes: u32,
+    /// Whether the candidate was in the final round.
+    pub finalist: bool,
+    pub won: bool,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CandidateVotes {
     pub candidate: CandidateId,
@@ -42,7 +412,7 @@ pub struct CandidateVotes {
     pub round_eliminated: Option<u32>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CandidatePairEntry {
     pub frac: f32,
@@ -60,7 +430,7 @@ impl CandidatePairEntry {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct CandidatePairTable {
     pub rows: Vec<Allocatee>,
@@ -68,7 +438,7 @@ pub struct CandidatePairTable {
     pub entries: Vec<Vec<Option<CandidatePairEntry>>>,
 }
 
-#[derive(Serialize, Deserialize, Default)]
+#[derive(Serialize, Deserialize, JsonSchema, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct RankingDistribution {
     #[serde(default)]
@@ -81,13 +451,386 @@ pub struct RankingDistribution {
     pub candidate_totals: BTreeMap<CandidateId, u32>,
 }
 
-#[derive(Serialize, Deserialize)]
+/// First-choice and final-round support among ballots from one counting
+/// group (vote method, e.g. election day or vote by mail).
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CountingGroupBreakdown {
+    pub counting_group: String,
+    pub ballot_count: u32,
+    pub first_choice: Vec<TabulatorAllocation>,
+    pub final_round: Vec<TabulatorAllocation>,
+}
+
+/// First-choice and final-round support among ballots from one region
+/// (e.g. borough or county) of a contest spanning several.
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RegionBreakdown {
+    pub region: String,
+    pub ballot_count: u32,
+    pub first_choice: Vec<TabulatorAllocation>,
+    pub final_round: Vec<TabulatorAllocation>,
+}
+
+/// Head-to-head totals between the two candidates in the final round over
+/// all ballots, as in a two-candidate runoff. Unlike the final round, this
+/// counts ballots whose higher-ranked choices are still in the running.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FinalTwo {
+    pub winner: CandidateId,
+    pub runner_up: CandidateId,
+    /// Ballots ranking the winner above the runner-up, or only the winner.
+    pub winner_votes: u32,
+    /// Ballots ranking the runner-up above the winner, or only the runner-up.
+    pub runner_up_votes: u32,
+    /// Ballots ranking neither candidate, including blank ballots and
+    /// ballots with an overvote before either.
+    pub neither: u32,
+}
+
+/// Margins between the leading candidates in the first and final rounds.
+/// Fractions are of the round's continuing ballots.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Margins {
+    pub first_round_leader: CandidateId,
+    /// First-round votes of the leader minus those of the second-place candidate.
+    pub first_round_margin: u32,
+    pub first_round_margin_fraction: f32,
+    /// Final-round votes of the winner minus those of the runner-up.
+    pub final_margin: u32,
+    pub final_margin_fraction: f32,
+    /// Votes needed for a majority of continuing ballots in the final round.
+    pub threshold: u32,
+    /// Final-round votes of the winner minus the threshold; negative if the
+    /// winner did not reach it.
+    pub margin_over_threshold: i64,
+    /// Margin fractions as exact fractions, with the `exactThresholds`
+    /// option.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_round_margin_exact: Option<ExactRatio>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub final_margin_exact: Option<ExactRatio>,
+}
+
+/// Bounds on the margin of victory: the fewest ballots that would have to
+/// be changed to elect a different candidate. The margin is exact when the
+/// bounds are equal.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MarginOfVictory {
+    /// Changing fewer ballots cannot elect a different candidate.
+    pub lower_bound: u32,
+    /// Changing this many ballots can elect a different candidate.
+    pub upper_bound: u32,
+    /// Round (numbered from 1) in which changing `upperBound` ballots
+    /// eliminates the winner, or beats them in the final round.
+    pub round: u32,
+    /// Candidate who gains the changed ballots.
+    pub beneficiary: CandidateId,
+}
+
+/// Ballots that count for no candidate in the final round, by whether they
+/// ranked a finalist at all.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExhaustedBallots {
+    /// Ballots marking any candidate that count for no finalist in the
+    /// final round.
+    pub exhausted: u32,
+    /// Exhausted ballots that ranked none of the finalists anywhere.
+    pub ranked_no_finalist: u32,
+    /// Exhausted ballots that ranked a finalist after an overvote or
+    /// skipped ranks at which the rules cut them off.
+    pub rule_exhausted: u32,
+    /// Rule-exhausted ballots by the finalist ranked first after the cut,
+    /// in descending order of ballots.
+    pub rule_exhausted_preferences: Vec<FinalistPreference>,
+    /// Winner's lead over the runner-up in the final round.
+    pub final_margin: u32,
+    /// Whether there are more rule-exhausted ballots than the final margin.
+    pub exceeds_margin: bool,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FinalistPreference {
+    pub candidate: CandidateId,
+    pub ballots: u32,
+}
+
+/// Winners under plausible alternative elimination orders, in which
+/// candidates within a tolerance of last place may be eliminated instead.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EliminationRobustness {
+    /// Votes within which candidates may be eliminated in either order.
+    pub tolerance: u32,
+    /// Whether the reported winner wins under every plausible order.
+    pub stable: bool,
+    /// Each plausible winner, with one elimination order electing them.
+    pub outcomes: Vec<PlausibleOutcome>,
+    /// Number of distinct sets of continuing candidates explored.
+    pub states: u32,
+    /// Whether every plausible order was explored, rather than stopping at
+    /// the search's limit.
+    pub complete: bool,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PlausibleOutcome {
+    pub winner: CandidateId,
+    /// Candidates in the order they are eliminated.
+    pub elimination_order: Vec<CandidateId>,
+}
+
+/// Winners of the contest had it been counted under other single-winner
+/// methods, from the same ballots. These are hypothetical: voters ranked
+/// candidates for an RCV count, and might have marked their ballots
+/// differently under another method.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HypotheticalMethods {
+    pub results: Vec<HypotheticalResult>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum VotingMethod {
+    /// Each ballot's first choice counts for that candidate.
+    Plurality,
+    /// Each ballot counts for every candidate it ranks.
+    Approval,
+    /// Each ballot gives a candidate one point for every candidate on the
+    /// ballot ranked below them, or unranked.
+    Borda,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HypotheticalResult {
+    pub method: VotingMethod,
+    /// The candidate with the highest score, or none if the highest score
+    /// is tied.
+    pub winner: Option<CandidateId>,
+    /// Score of each candidate, in descending order.
+    pub scores: Vec<MethodScore>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MethodScore {
+    pub candidate: CandidateId,
+    pub score: u64,
+}
+
+/// Whether the outcome would have changed had some voters ranked a candidate
+/// lower or higher than they did.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StrategySusceptibility {
+    /// Losing candidates who would have won had some of the ballots ranking
+    /// them first ranked them second instead.
+    pub downward_monotonicity: Vec<MonotonicityFailure>,
+    /// Candidates whose voters would have defeated the winner by ranking
+    /// the winner first instead of second.
+    pub upward_monotonicity: Vec<MonotonicityFailure>,
+    /// Whether every pair of candidates was checked, rather than stopping
+    /// at the search's limit for some.
+    pub complete: bool,
+}
+
+/// A change of outcome from swapping the first two choices on some ballots
+/// ranking `candidate` and `other` first and second, in either order.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MonotonicityFailure {
+    /// The candidate ranked lower (downward) or higher (upward).
+    pub candidate: CandidateId,
+    /// The candidate they swap places with.
+    pub other: CandidateId,
+    /// Fewest ballots that must swap the two to change the outcome.
+    pub ballots: u32,
+    /// Winner after the swap.
+    pub winner: CandidateId,
+}
+
+/// Initial sample sizes for a risk-limiting audit of the final round's
+/// outcome, at common risk limits.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditSampleSizes {
+    /// Final-round margin as a fraction of all ballots in the contest.
+    pub diluted_margin: f32,
+    pub sizes: Vec<AuditSampleSize>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditSampleSize {
+    /// Largest chance that the audit confirms a wrong outcome.
+    pub risk_limit: f32,
+    /// Expected number of ballots a BRAVO ballot-polling audit examines if
+    /// the reported results are correct.
+    pub ballot_polling: u32,
+    /// Number of ballots a ballot-level comparison audit examines if it
+    /// finds no discrepancies.
+    pub comparison: u32,
+}
+
+/// First-round votes compared to the official canvass.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Reconciliation {
+    pub rows: Vec<ReconciliationRow>,
+    /// Sum of the absolute deltas.
+    pub total_discrepancy: u32,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconciliationRow {
+    /// Name of the candidate, as in the official canvass if listed there.
+    pub name: String,
+    /// Absent for names in the official canvass that match no candidate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub candidate: Option<CandidateId>,
+    pub votes: u32,
+    /// Absent for candidates not listed in the official canvass.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub official_votes: Option<u32>,
+    /// Our votes minus the official votes.
+    pub delta: i64,
+    /// Normalization rules that moved votes in the direction of the delta.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<NormalizationRule>,
+}
+
+/// A normalization rule that can make first-round votes differ from an
+/// official canvass.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum NormalizationRule {
+    /// Ballots with a blank or overvoted first rank count for the next
+    /// marked candidate.
+    SkippedRanks,
+    /// Ballots are exhausted or set aside before the first round, e.g. for
+    /// consecutive skipped ranks.
+    ExhaustedBallots,
+}
+
+/// Whether a contest could be tabulated normally. Reports on degenerate
+/// contests are still complete, but have no meaningful rounds to show.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum TabulationStatus {
+    /// At least two candidates received first-round votes.
+    #[default]
+    Tabulated,
+    /// The contest has no ballots.
+    NoBallots,
+    /// No ballot counted for a candidate in the first round, e.g. because
+    /// all were blank or overvoted.
+    NoVotes,
+    /// Only one candidate received votes, and wins without a count.
+    Uncontested,
+    /// Only one qualified candidate is on the ballot. Write-ins are not
+    /// eliminated, so the report has only the first round's totals.
+    Unopposed,
+    /// Only write-in candidates are on the ballot. As for an unopposed
+    /// contest, the report has only the first round's totals.
+    WriteInOnly,
+}
+
+impl TabulationStatus {
+    pub fn is_tabulated(&self) -> bool {
+        *self == TabulationStatus::Tabulated
+    }
+
+    /// Whether the contest's totals are reported without elimination rounds.
+    pub fn is_unopposed(&self) -> bool {
+        matches!(
+            self,
+            TabulationStatus::Unopposed | TabulationStatus::WriteInOnly
+        )
+    }
+}
+
+/// Candidates grouped into a single "Others" row for display, because each
+/// received less than the threshold share of first-round votes. The rounds
+/// still have each of their votes.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OthersBucket {
+    pub threshold: f64,
+    pub candidates: Vec<CandidateId>,
+    /// Combined votes of the grouped candidates in each round.
+    pub votes: Vec<u32>,
+}
+
+/// Tally of a non-ranked contest, in which each ballot counts once for each
+/// candidate it votes for, up to `voteFor` of them.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PluralityTally {
+    pub vote_for: u32,
+    /// Votes per candidate, in descending order of votes.
+    pub votes: Vec<PluralityVotes>,
+    /// Ballots that vote for no candidate.
+    pub blank_ballots: u32,
+    /// Ballots that vote for more than `voteFor` candidates, and count for
+    /// none of them.
+    pub overvoted_ballots: u32,
+    /// Votes left unused by ballots that vote for fewer than `voteFor`
+    /// candidates, including blank ballots.
+    pub undervotes: u32,
+    /// Candidates elected: the `voteFor` with the most votes, less any tied
+    /// for the last place.
+    pub winners: Vec<CandidateId>,
+    /// Candidates tied for the last place, which a tiebreak decides.
+    pub tied: Vec<CandidateId>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PluralityVotes {
+    pub candidate: CandidateId,
+    pub votes: u32,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ContestReport {
     pub info: ElectionInfo,
     pub ballot_count: u32,
     pub candidates: Vec<Candidate>,
+    #[serde(default)]
+    pub status: TabulationStatus,
+    /// Tally of a non-ranked contest, whose `rounds` then have only the
+    /// single round of its totals.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plurality: Option<PluralityTally>,
     pub rounds: Vec<TabulatorRound>,
+    /// The full sequence of rounds, if `rounds` was condensed by merging
+    /// runs of rounds that don't change the leaders into one step.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expanded_rounds: Option<Vec<TabulatorRound>>,
+    /// For each of the condensed `rounds`, the number (counting from 1) of
+    /// the expanded round whose totals it shows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expanded_round_numbers: Option<Vec<u32>>,
+    /// Each candidate's votes in every round, or null for rounds after they
+    /// are eliminated, for charting without transposing the rounds. Rounds
+    /// merged by condensing are included.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub candidate_series: BTreeMap<CandidateId, Vec<Option<u32>>>,
+    /// Minor candidates to display as one "Others" row, for elections with
+    /// an `othersThreshold`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub others: Option<OthersBucket>,
     pub winner: Option<CandidateId>,
     pub condorcet: Option<CandidateId>,
     pub num_candidates: u32,
@@ -97,6 +840,101 @@ pub struct ContestReport {
     pub first_final: CandidatePairTable,
     pub ranking_distribution: Option<RankingDistribution>,
     pub smith_set: Vec<CandidateId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adjudicated_ballots: Option<u32>,
+    /// How adjudication changed the ballots, for formats that record both
+    /// versions of an adjudicated ballot.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adjudication: Option<AdjudicationImpact>,
+    /// Ballots repeating an earlier ballot's ID in the raw data.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicate_ballots: Option<DuplicateBallots>,
+    /// Differences between the candidate manifest and the candidates marked
+    /// on the ballots.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub candidate_reconciliation: Option<CandidateReconciliation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counting_groups: Option<Vec<CountingGroupBreakdown>>,
+    /// Breakdown by region, for elections with a region lookup in their
+    /// metadata.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regions: Option<Vec<RegionBreakdown>>,
+    /// Marks at each rank position of the ballots before normalization.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rank_positions: Option<Vec<RankPositionStatistics>>,
+    /// Candidates ranked more than once on a ballot.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicate_rankings: Option<DuplicateRankings>,
+    /// How normalization changed the first-round votes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_round_adjustments: Option<FirstRoundAdjustments>,
+    /// How ballots with overvotes were handled under the overvote policy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overvote_handling: Option<OvervoteHandling>,
+    /// How many ballots were exhausted by skipped ranks under the
+    /// skipped-rank policy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped_rank_handling: Option<SkippedRankHandling>,
+    /// Comparison of the first round to the official canvass, if the
+    /// contest's metadata gives official first-round totals.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reconciliation: Option<Reconciliation>,
+    /// Head-to-head totals between the final two candidates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub final_two: Option<FinalTwo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub margins: Option<Margins>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub margin_of_victory: Option<MarginOfVictory>,
+    /// Ballots exhausted before the final round, and how many of them the
+    /// overvote and skipped-rank rules cut off before a finalist.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exhausted_ballots: Option<ExhaustedBallots>,
+    /// Winners under alternative elimination orders, if requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elimination_robustness: Option<EliminationRobustness>,
+    /// Winners under other voting methods, if requested. Hypothetical, for
+    /// comparison only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hypothetical_methods: Option<HypotheticalMethods>,
+    /// Monotonicity failures found in the ballots, if requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strategy_susceptibility: Option<StrategySusceptibility>,
+    /// Sample sizes for a risk-limiting audit of the final round.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audit_sample_sizes: Option<AuditSampleSizes>,
+    /// File name of the per-precinct results GeoJSON written alongside this
+    /// report, if the election has precinct boundaries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub precinct_map: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    /// File name of the per-round ballot snapshots written alongside this
+    /// report, if requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub round_snapshots: Option<String>,
+    /// File name of the ranking pattern frequency table written alongside
+    /// this report, if requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ranking_patterns: Option<String>,
+    /// Reporting status from the contest metadata, if the results are not
+    /// yet final.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reporting_status: Option<ReportingStatus>,
+    /// ID of the tabulation variant this report was tabulated under, if it
+    /// is not the official report.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<String>,
+    /// Reports on this contest under alternative rule sets.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<VariantEntry>,
+    /// ID of the ballot subset this report is restricted to, if it is not
+    /// the report on all ballots.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subset: Option<String>,
+    /// Reports on subsets of this contest's ballots.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subsets: Vec<SubsetEntry>,
 }
 
 impl ContestReport {
@@ -104,4 +942,9 @@ impl ContestReport {
         self.winner
             .map(|winner_id| &self.candidates[winner_id.0 as usize])
     }
+
+    /// Every round of the tabulation, whether or not `rounds` was condensed.
+    pub fn full_rounds(&self) -> &[TabulatorRound] {
+        self.expanded_rounds.as_deref().unwrap_or(&self.rounds)
+    }
 }
@@ -8,7 +8,7 @@ pub fn maine_normalizer(ballot: Ballot) -> NormalizedBallot {
     // [IB 2015, c. 3, §5 (NEW).]
 
     let mut seen = BTreeSet::new();
-    let Ballot { id, choices } = ballot;
+    let Ballot { id, choices, .. } = ballot;
     let mut new_choices = Vec::new();
     let mut last_skipped = false;
     let mut overvoted = false;
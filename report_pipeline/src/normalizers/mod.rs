@@ -2,7 +2,16 @@ mod maine;
 mod nyc;
 mod simple;
 
-use crate::model::election::{Ballot, Election, NormalizedBallot, NormalizedElection};
+use crate::model::election::{
+    Ballot, CandidateAdjustment, CandidateDuplicates, CandidateId, CandidateReconciliation, Choice,
+    DuplicateBallots, DuplicateRankings, Election, FirstRoundAdjustments, NormalizedBallot,
+    NormalizedElection, OvervoteHandling, RankPositionStatistics, SkippedRankHandling,
+    TruncatedBallot,
+};
+use crate::model::metadata::{DuplicateBallotPolicy, OvervotePolicy, SkippedRankPolicy};
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 type BallotNormalizer = dyn Fn(Ballot) -> NormalizedBallot;
 type OptionalBallotNormalizer = dyn Fn(Ballot) -> Option<NormalizedBallot>;
@@ -22,27 +31,744 @@ fn get_optional_normalizer_for_format(format: &str) -> Option<&'static OptionalB
     }
 }
 
-pub fn normalize_election(format: &str, election: Election) -> NormalizedElection {
+/// Count the votes, overvotes and undervotes at each rank position of the
+/// raw ballots.
+pub fn rank_position_statistics(ballots: &[Ballot]) -> Vec<RankPositionStatistics> {
+    let mut positions: Vec<RankPositionStatistics> = Vec::new();
+
+    for ballot in ballots {
+        // Index of the last rank with any mark, to tell skipped ranks from
+        // trailing blank ones.
+        let last_marked = ballot
+            .choices
+            .iter()
+            .rposition(|choice| *choice != Choice::Undervote);
+
+        for (i, choice) in ballot.choices.iter().enumerate() {
+            if positions.len() <= i {
+                positions.push(RankPositionStatistics {
+                    rank: i as u32 + 1,
+                    votes: 0,
+                    overvotes: 0,
+                    undervotes: 0,
+                    skipped: 0,
+                });
+            }
+            let position = &mut positions[i];
+            match choice {
+                Choice::Vote(_) => position.votes += 1,
+                Choice::Overvote => position.overvotes += 1,
+                Choice::Undervote => {
+                    position.undervotes += 1;
+                    if last_marked.is_some_and(|last| i < last) {
+                        position.skipped += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    positions
+}
+
+/// Compare an election's candidates to those marked on its ballots, along
+/// with the IDs its reader found on ballots but not in the manifest.
+pub fn reconcile_candidates(election: &Election) -> CandidateReconciliation {
+    let mut marked = vec![false; election.candidates.len()];
+    for ballot in &election.ballots {
+        for choice in &ballot.choices {
+            if let Choice::Vote(candidate) = choice {
+                marked[candidate.0 as usize] = true;
+            }
+        }
+    }
+    CandidateReconciliation {
+        unknown_candidates: election.unknown_candidates.clone(),
+        unmarked_candidates: (0..marked.len())
+            .filter(|&i| !marked[i])
+            .map(|i| CandidateId(i as u32))
+            .collect(),
+    }
+}
+
+/// Count the ballots that ranked a candidate more than once, overall and
+/// per candidate.
+pub fn duplicate_rankings(ballots: &[Ballot]) -> DuplicateRankings {
+    let mut ballot_count = 0;
+    let mut candidate_counts: BTreeMap<CandidateId, u32> = BTreeMap::new();
+
+    for ballot in ballots {
+        let mut seen = BTreeSet::new();
+        let mut duplicated = BTreeSet::new();
+        for choice in &ballot.choices {
+            if let Choice::Vote(candidate) = choice {
+                if !seen.insert(*candidate) {
+                    duplicated.insert(*candidate);
+                }
+            }
+        }
+
+        if !duplicated.is_empty() {
+            ballot_count += 1;
+            for candidate in duplicated {
+                *candidate_counts.entry(candidate).or_insert(0) += 1;
+            }
+        }
+    }
+
+    let mut candidates: Vec<CandidateDuplicates> = candidate_counts
+        .into_iter()
+        .map(|(candidate, ballots)| CandidateDuplicates { candidate, ballots })
+        .collect();
+    candidates.sort_by_key(|c| Reverse(c.ballots));
+
+    DuplicateRankings {
+        ballots: ballot_count,
+        candidates,
+    }
+}
+
+/// The first mark on a raw ballot, skipping blank ranks, and whether any
+/// blank ranks came before it.
+fn first_mark(choices: &[Choice]) -> (Option<Choice>, bool) {
+    let blanks = choices
+        .iter()
+        .take_while(|choice| **choice == Choice::Undervote)
+        .count();
+    (choices.get(blanks).copied(), blanks > 0)
+}
+
+/// Tallies how normalization changes the first-round votes, one ballot at a
+/// time.
+#[derive(Default)]
+struct FirstRoundTally {
+    overvoted: u32,
+    /// Skipped-rank and exhausted ballots, by candidate.
+    candidates: BTreeMap<CandidateId, (u32, u32)>,
+}
+
+impl FirstRoundTally {
+    /// Record a ballot's first mark (see `first_mark`) and the candidate it
+    /// counts for in the first round after normalization, if any.
+    fn record(&mut self, (mark, after_blank): (Option<Choice>, bool), first: Option<CandidateId>) {
+        match (mark, first) {
+            (Some(Choice::Overvote), Some(first)) => self.candidate(first).0 += 1,
+            (Some(Choice::Vote(_)), Some(first)) if after_blank => self.candidate(first).0 += 1,
+            (Some(Choice::Vote(candidate)), None) => self.candidate(candidate).1 += 1,
+            (Some(Choice::Overvote), None) => self.overvoted += 1,
+            _ => (),
+        }
+    }
+
+    fn candidate(&mut self, candidate: CandidateId) -> &mut (u32, u32) {
+        self.candidates.entry(candidate).or_default()
+    }
+
+    fn into_adjustments(self) -> FirstRoundAdjustments {
+        FirstRoundAdjustments {
+            overvoted: self.overvoted,
+            candidates: self
+                .candidates
+                .into_iter()
+                .map(
+                    |(candidate, (skipped_ranks, exhausted))| CandidateAdjustment {
+                        candidate,
+                        skipped_ranks,
+                        exhausted,
+                    },
+                )
+                .collect(),
+        }
+    }
+}
+
+/// Tallies ballots with overvotes under an overvote policy.
+struct OvervoteTally {
+    handling: OvervoteHandling,
+}
+
+impl OvervoteTally {
+    fn new(policy: OvervotePolicy) -> OvervoteTally {
+        OvervoteTally {
+            handling: OvervoteHandling {
+                policy,
+                overvoted_ballots: 0,
+                exhausted: 0,
+                skipped: 0,
+            },
+        }
+    }
+
+    /// Apply the policy to a raw ballot before the skipped-rank policy, and
+    /// count it if it has an overvote. Under the `exhaust` policy, the ranks
+    /// after the first overvote are dropped, since they are never reached.
+    /// Overvoted ranks are left in place, so that the skipped-rank policy
+    /// doesn't take them for blank ranks.
+    fn apply(&mut self, ballot: &mut Ballot) {
+        let first_overvote = match ballot.choices.iter().position(|c| *c == Choice::Overvote) {
+            Some(position) => position,
+            None => return,
+        };
+        self.handling.overvoted_ballots += 1;
+
+        match self.handling.policy {
+            OvervotePolicy::Exhaust => ballot.choices.truncate(first_overvote + 1),
+            OvervotePolicy::Skip => {
+                let continues = ballot.choices[first_overvote..]
+                    .iter()
+                    .any(|c| matches!(c, Choice::Vote(_)));
+                if continues {
+                    self.handling.skipped += 1;
+                }
+            }
+        }
+    }
+
+    /// Under the `skip` policy, remove a ballot's overvoted ranks once the
+    /// skipped-rank policy has been applied, so that normalization passes
+    /// over them.
+    fn pass_over(&self, ballot: &mut Ballot) {
+        if self.handling.policy == OvervotePolicy::Skip {
+            ballot.choices.retain(|c| *c != Choice::Overvote);
+        }
+    }
+
+    /// Record the normalized ballot, if normalization kept it.
+    fn record(&mut self, normalized: Option<&NormalizedBallot>) {
+        if normalized.is_some_and(|b| b.overvoted) {
+            self.handling.exhausted += 1;
+        }
+    }
+}
+
+/// Applies the skipped-rank policy to raw ballots, counting the ballots it
+/// exhausts.
+struct SkippedRankTally {
+    handling: SkippedRankHandling,
+}
+
+impl SkippedRankTally {
+    fn new(policy: SkippedRankPolicy) -> SkippedRankTally {
+        SkippedRankTally {
+            handling: SkippedRankHandling {
+                policy,
+                exhausted: 0,
+            },
+        }
+    }
+
+    /// Under the `exhaustAfterTwo` policy, find where a raw ballot is
+    /// exhausted by its first two consecutive blank ranks, if anything is
+    /// marked after them. Overvoted ranks aren't blank, even when the
+    /// overvote policy skips them. Returns the number of distinct candidates
+    /// marked before them, which are the normalized ballot's choices that
+    /// are counted; the ballot itself is left whole.
+    fn apply(&mut self, ballot: &Ballot) -> Option<u32> {
+        if self.handling.policy != SkippedRankPolicy::ExhaustAfterTwo {
+            return None;
+        }
+        let skips = ballot
+            .choices
+            .windows(2)
+            .position(|pair| pair == [Choice::Undervote, Choice::Undervote])?;
+        if ballot.choices[skips..]
+            .iter()
+            .all(|c| *c == Choice::Undervote)
+        {
+            return None;
+        }
+        self.handling.exhausted += 1;
+        let counted: BTreeSet<CandidateId> = marked_candidates(&ballot.choices[..skips])
+            .into_iter()
+            .collect();
+        Some(counted.len() as u32)
+    }
+}
+
+/// Number of repeated ballot IDs kept as examples.
+const DUPLICATE_EXAMPLES: usize = 10;
+
+/// Find the ballots of an election that have the same ID as an earlier
+/// ballot, some CVR archives having an export batch twice, and remove them
+/// as the policy says. The duplicates found are recorded on the election.
+pub fn remove_duplicate_ballots(election: &mut Election, policy: DuplicateBallotPolicy) {
+    let mut duplicates = DuplicateBallots {
+        policy,
+        duplicate_ids: 0,
+        identical_records: 0,
+        removed: 0,
+        examples: Vec::new(),
+    };
+    let mut first: HashMap<&str, usize> = HashMap::new();
+    let mut keep = Vec::with_capacity(election.ballots.len());
+    for (index, ballot) in election.ballots.iter().enumerate() {
+        let earlier = match first.entry(&ballot.id) {
+            Entry::Vacant(entry) => {
+                entry.insert(index);
+                keep.push(true);
+                continue;
+            }
+            Entry::Occupied(entry) => &election.ballots[*entry.get()],
+        };
+        duplicates.duplicate_ids += 1;
+        if duplicates.examples.len() < DUPLICATE_EXAMPLES
+            && !duplicates.examples.contains(&ballot.id)
+        {
+            duplicates.examples.push(ballot.id.clone());
+        }
+        let identical = earlier == ballot;
+        if identical {
+            duplicates.identical_records += 1;
+        }
+        keep.push(match policy {
+            DuplicateBallotPolicy::Keep => true,
+            DuplicateBallotPolicy::RemoveIdentical => !identical,
+            DuplicateBallotPolicy::RemoveDuplicateIds => false,
+        });
+    }
+
+    duplicates.removed = keep.iter().filter(|&&kept| !kept).count() as u32;
+    let mut keep = keep.into_iter();
+    election.ballots.retain(|_| keep.next().unwrap());
+    election.duplicate_ballots = Some(duplicates);
+}
+
+/// Candidates marked on a raw ballot, in rank order.
+fn marked_candidates(choices: &[Choice]) -> Vec<CandidateId> {
+    choices
+        .iter()
+        .filter_map(|choice| match choice {
+            Choice::Vote(candidate) => Some(*candidate),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Records the ballots that normalization cut off before candidates they
+/// marked, e.g. at an overvote or consecutive skipped ranks.
+#[derive(Default)]
+struct TruncationTally {
+    ballots: Vec<TruncatedBallot>,
+    /// Number of normalized ballots kept so far.
+    kept: u32,
+}
+
+impl TruncationTally {
+    /// Record the normalized ballot, if normalization kept it, given the
+    /// candidates marked on the raw ballot.
+    fn record(&mut self, marked: Vec<CandidateId>, normalized: Option<&NormalizedBallot>) {
+        let normalized = match normalized {
+            Some(normalized) => normalized,
+            None => return,
+        };
+        let ranked: BTreeSet<CandidateId> = normalized.choices().into_iter().collect();
+        let mut dropped: Vec<CandidateId> = Vec::new();
+        for candidate in marked {
+            if !ranked.contains(&candidate) && !dropped.contains(&candidate) {
+                dropped.push(candidate);
+            }
+        }
+        if !dropped.is_empty() {
+            self.ballots.push(TruncatedBallot {
+                ballot: self.kept,
+                dropped,
+            });
+        }
+        self.kept += 1;
+    }
+}
+
+/// The candidate a normalized ballot counts for in the first round.
+fn first_choice(ballot: &NormalizedBallot) -> Option<CandidateId> {
+    match ballot.top_vote() {
+        Choice::Vote(candidate) => Some(candidate),
+        _ => None,
+    }
+}
+
+pub fn normalize_election(
+    format: &str,
+    overvote_policy: OvervotePolicy,
+    skipped_rank_policy: SkippedRankPolicy,
+    mut election: Election,
+) -> NormalizedElection {
+    // Readers number candidates in the order they encounter them; renumber
+    // them so that reports are comparable across regenerations.
+    election.sort_candidates();
+    let rank_positions = Some(rank_position_statistics(&election.ballots));
+    let duplicate_rankings = Some(duplicate_rankings(&election.ballots));
+    let candidate_reconciliation = Some(reconcile_candidates(&election));
+    let mut first_round = FirstRoundTally::default();
+    let mut overvotes = OvervoteTally::new(overvote_policy);
+    let mut skipped_ranks = SkippedRankTally::new(skipped_rank_policy);
+    let mut truncations = TruncationTally::default();
+
     if let Some(optional_normalizer) = get_optional_normalizer_for_format(format) {
         // For NYC-style normalization, filter out inactive ballots
         let ballots: Vec<NormalizedBallot> = election
             .ballots
             .into_iter()
-            .filter_map(optional_normalizer)
+            .filter_map(|mut ballot| {
+                let (counting_group, precinct) =
+                    (ballot.counting_group.clone(), ballot.precinct.clone());
+                let mark = first_mark(&ballot.choices);
+                let marked = marked_candidates(&ballot.choices);
+                overvotes.apply(&mut ballot);
+                let counted = skipped_ranks.apply(&ballot);
+                overvotes.pass_over(&mut ballot);
+                // Ballots with no counted choices are inactive, as when they
+                // mark no one.
+                let normalized = optional_normalizer(ballot)
+                    .map(|b| b.with_exhausted_after(counted))
+                    .filter(|b| !b.choice_slice().is_empty());
+                first_round.record(mark, normalized.as_ref().and_then(first_choice));
+                overvotes.record(normalized.as_ref());
+                truncations.record(marked, normalized.as_ref());
+                normalized.map(|b| {
+                    b.with_counting_group(counting_group)
+                        .with_precinct(precinct)
+                })
+            })
             .collect();
 
         NormalizedElection {
             candidates: election.candidates,
             ballots,
+            adjudicated_ballots: election.adjudicated_ballots,
+            adjudication: election.adjudication,
+            duplicate_ballots: election.duplicate_ballots,
+            candidate_reconciliation,
+            rank_positions,
+            duplicate_rankings,
+            first_round_adjustments: Some(first_round.into_adjustments()),
+            overvote_handling: Some(overvotes.handling),
+            skipped_rank_handling: Some(skipped_ranks.handling),
+            truncated_ballots: Some(truncations.ballots),
         }
     } else {
         // For standard normalization, process all ballots
         let normalizer = get_normalizer_for_format(format);
-        let ballots = election.ballots.into_iter().map(normalizer).collect();
+        let ballots = election
+            .ballots
+            .into_iter()
+            .map(|mut ballot| {
+                let (counting_group, precinct) =
+                    (ballot.counting_group.clone(), ballot.precinct.clone());
+                let mark = first_mark(&ballot.choices);
+                let marked = marked_candidates(&ballot.choices);
+                overvotes.apply(&mut ballot);
+                let counted = skipped_ranks.apply(&ballot);
+                overvotes.pass_over(&mut ballot);
+                let normalized = normalizer(ballot).with_exhausted_after(counted);
+                first_round.record(mark, first_choice(&normalized));
+                overvotes.record(Some(&normalized));
+                truncations.record(marked, Some(&normalized));
+                normalized
+                    .with_counting_group(counting_group)
+                    .with_precinct(precinct)
+            })
+            .collect();
 
         NormalizedElection {
             candidates: election.candidates,
             ballots,
+            adjudicated_ballots: election.adjudicated_ballots,
+            adjudication: election.adjudication,
+            duplicate_ballots: election.duplicate_ballots,
+            candidate_reconciliation,
+            rank_positions,
+            duplicate_rankings,
+            first_round_adjustments: Some(first_round.into_adjustments()),
+            overvote_handling: Some(overvotes.handling),
+            skipped_rank_handling: Some(skipped_ranks.handling),
+            truncated_ballots: Some(truncations.ballots),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::{Candidate, CandidateType};
+
+    #[test]
+    fn test_rank_position_statistics() {
+        let ballots = vec![
+            Ballot::new(
+                "1".to_string(),
+                vec![
+                    Choice::Undervote,
+                    Choice::Vote(CandidateId(0)),
+                    Choice::Undervote,
+                ],
+            ),
+            Ballot::new(
+                "2".to_string(),
+                vec![Choice::Overvote, Choice::Vote(CandidateId(1))],
+            ),
+        ];
+
+        let positions = rank_position_statistics(&ballots);
+        assert_eq!(3, positions.len());
+        assert_eq!(
+            RankPositionStatistics {
+                rank: 1,
+                votes: 0,
+                overvotes: 1,
+                undervotes: 1,
+                skipped: 1,
+            },
+            positions[0]
+        );
+        assert_eq!(2, positions[1].votes);
+        assert_eq!((1, 0), (positions[2].undervotes, positions[2].skipped));
+    }
+
+    #[test]
+    fn test_first_round_tally() {
+        let (a, b) = (CandidateId(0), CandidateId(1));
+        let mut tally = FirstRoundTally::default();
+        // Counted for the first marked candidate: no adjustment.
+        tally.record(first_mark(&[Choice::Vote(a)]), Some(a));
+        // Counted for a candidate below a blank or overvoted rank.
+        tally.record(first_mark(&[Choice::Undervote, Choice::Vote(b)]), Some(b));
+        tally.record(first_mark(&[Choice::Overvote, Choice::Vote(b)]), Some(b));
+        // Exhausted although a candidate is marked.
+        tally.record(
+            first_mark(&[Choice::Undervote, Choice::Undervote, Choice::Vote(a)]),
+            None,
+        );
+        // Exhausted by an overvote, or blank.
+        tally.record(first_mark(&[Choice::Overvote]), None);
+        tally.record(first_mark(&[Choice::Undervote]), None);
+
+        assert_eq!(
+            FirstRoundAdjustments {
+                overvoted: 1,
+                candidates: vec![
+                    CandidateAdjustment {
+                        candidate: a,
+                        skipped_ranks: 0,
+                        exhausted: 1,
+                    },
+                    CandidateAdjustment {
+                        candidate: b,
+                        skipped_ranks: 2,
+                        exhausted: 0,
+                    },
+                ],
+            },
+            tally.into_adjustments()
+        );
+    }
+
+    #[test]
+    fn test_overvote_policy() {
+        let (a, b) = (Choice::Vote(CandidateId(0)), Choice::Vote(CandidateId(1)));
+        let election = || {
+            Election::new(
+                vec![
+                    Candidate::new("A".to_string(), CandidateType::Regular),
+                    Candidate::new("B".to_string(), CandidateType::Regular),
+                ],
+                vec![
+                    Ballot::new("1".to_string(), vec![a, Choice::Overvote, b]),
+                    Ballot::new("2".to_string(), vec![Choice::Overvote]),
+                    Ballot::new("3".to_string(), vec![b, a]),
+                ],
+            )
+        };
+
+        let exhaust = normalize_election(
+            "simple",
+            OvervotePolicy::Exhaust,
+            SkippedRankPolicy::Skip,
+            election(),
+        );
+        assert_eq!(vec![CandidateId(0)], exhaust.ballots[0].choices());
+        assert_eq!(
+            Some(OvervoteHandling {
+                policy: OvervotePolicy::Exhaust,
+                overvoted_ballots: 2,
+                exhausted: 2,
+                skipped: 0,
+            }),
+            exhaust.overvote_handling
+        );
+
+        let skip = normalize_election(
+            "simple",
+            OvervotePolicy::Skip,
+            SkippedRankPolicy::Skip,
+            election(),
+        );
+        assert_eq!(
+            vec![CandidateId(0), CandidateId(1)],
+            skip.ballots[0].choices()
+        );
+        assert!(!skip.ballots[1].overvoted);
+        assert_eq!(
+            Some(OvervoteHandling {
+                policy: OvervotePolicy::Skip,
+                overvoted_ballots: 2,
+                exhausted: 0,
+                skipped: 1,
+            }),
+            skip.overvote_handling
+        );
+    }
+
+    #[test]
+    fn test_exhaust_after_two_skipped_ranks() {
+        let (a, b) = (Choice::Vote(CandidateId(0)), Choice::Vote(CandidateId(1)));
+        let blank = Choice::Undervote;
+        let candidates = vec![
+            Candidate::new("A".to_string(), CandidateType::Regular),
+            Candidate::new("B".to_string(), CandidateType::Regular),
+        ];
+        let election = Election::new(
+            candidates.clone(),
+            vec![
+                // A single skipped rank is passed over.
+                Ballot::new("1".to_string(), vec![a, blank, b]),
+                // Two consecutive ones exhaust the ballot.
+                Ballot::new("2".to_string(), vec![a, blank, blank, b]),
+                Ballot::new("3".to_string(), vec![blank, blank, b]),
+                // Trailing blank ranks don't exhaust anything.
+                Ballot::new("4".to_string(), vec![b, blank, blank]),
+                // An overvote skipped by the overvote policy isn't a skipped
+                // rank, so it doesn't make one blank rank two.
+                Ballot::new("5".to_string(), vec![b, Choice::Overvote, blank, a]),
+                Ballot::new("6".to_string(), vec![b, blank, Choice::Overvote, a]),
+                // Two blank ranks after it still exhaust the ballot.
+                Ballot::new("7".to_string(), vec![b, Choice::Overvote, blank, blank, a]),
+            ],
+        );
+
+        let normalized = normalize_election(
+            "simple",
+            OvervotePolicy::Skip,
+            SkippedRankPolicy::ExhaustAfterTwo,
+            election,
+        );
+        let choices: Vec<Vec<CandidateId>> =
+            normalized.ballots.iter().map(|b| b.choices()).collect();
+        assert_eq!(
+            vec![
+                vec![CandidateId(0), CandidateId(1)],
+                vec![CandidateId(0)],
+                vec![],
+                vec![CandidateId(1)],
+                vec![CandidateId(1), CandidateId(0)],
+                vec![CandidateId(1), CandidateId(0)],
+                vec![CandidateId(1)],
+            ],
+            choices
+        );
+        // The rankings after the skipped ranks are kept, but not counted.
+        let cut = &normalized.ballots[6];
+        assert_eq!(&[CandidateId(1), CandidateId(0)], cut.marked_choices());
+        assert_eq!(Some(1), cut.exhausted_after());
+        assert_eq!(Choice::Undervote, cut.clone().pop_top_vote().top_vote());
+        assert_eq!(
+            Some(SkippedRankHandling {
+                policy: SkippedRankPolicy::ExhaustAfterTwo,
+                exhausted: 3,
+            }),
+            normalized.skipped_rank_handling
+        );
+        assert_eq!(
+            Some(OvervoteHandling {
+                policy: OvervotePolicy::Skip,
+                overvoted_ballots: 3,
+                exhausted: 0,
+                skipped: 3,
+            }),
+            normalized.overvote_handling
+        );
+        let truncated = |ballot, dropped| TruncatedBallot {
+            ballot,
+            dropped: vec![CandidateId(dropped)],
+        };
+        assert_eq!(
+            Some(vec![truncated(1, 1), truncated(2, 1), truncated(6, 0)]),
+            normalized.truncated_ballots
+        );
+
+        // When overvotes exhaust ballots, blank ranks after one are never
+        // reached.
+        let overvoted = Election::new(
+            candidates,
+            vec![Ballot::new(
+                "1".to_string(),
+                vec![a, Choice::Overvote, blank, blank, b],
+            )],
+        );
+        let normalized = normalize_election(
+            "simple",
+            OvervotePolicy::Exhaust,
+            SkippedRankPolicy::ExhaustAfterTwo,
+            overvoted,
+        );
+        assert_eq!(0, normalized.skipped_rank_handling.unwrap().exhausted);
+        assert_eq!(1, normalized.overvote_handling.unwrap().exhausted);
+    }
+
+    #[test]
+    fn test_remove_duplicate_ballots() {
+        let (a, b) = (Choice::Vote(CandidateId(0)), Choice::Vote(CandidateId(1)));
+        let election = Election::new(
+            vec![],
+            vec![
+                Ballot::new("1".to_string(), vec![a, b]),
+                Ballot::new("2".to_string(), vec![b]),
+                // A batch exported twice, and a ballot that only shares an ID.
+                Ballot::new("1".to_string(), vec![a, b]),
+                Ballot::new("2".to_string(), vec![b]),
+                Ballot::new("2".to_string(), vec![a]),
+                Ballot::new("3".to_string(), vec![a]),
+            ],
+        );
+
+        let removed = |policy| {
+            let mut election = election.clone();
+            remove_duplicate_ballots(&mut election, policy);
+            let duplicates = election.duplicate_ballots.unwrap();
+            assert_eq!(3, duplicates.duplicate_ids);
+            assert_eq!(2, duplicates.identical_records);
+            assert_eq!(vec!["1".to_string(), "2".to_string()], duplicates.examples);
+            assert_eq!(6 - duplicates.removed as usize, election.ballots.len());
+            duplicates.removed
+        };
+        assert_eq!(0, removed(DuplicateBallotPolicy::Keep));
+        assert_eq!(2, removed(DuplicateBallotPolicy::RemoveIdentical));
+        assert_eq!(3, removed(DuplicateBallotPolicy::RemoveDuplicateIds));
+    }
+
+    #[test]
+    fn test_duplicate_rankings() {
+        let (a, b) = (Choice::Vote(CandidateId(0)), Choice::Vote(CandidateId(1)));
+        let ballots = vec![
+            Ballot::new("1".to_string(), vec![a, a, a, b]),
+            Ballot::new("2".to_string(), vec![b, Choice::Undervote, b]),
+            Ballot::new("3".to_string(), vec![b, a]),
+            Ballot::new("4".to_string(), vec![a, b, a, b]),
+        ];
+
+        let duplicates = duplicate_rankings(&ballots);
+        assert_eq!(3, duplicates.ballots);
+        assert_eq!(
+            vec![
+                CandidateDuplicates {
+                    candidate: CandidateId(0),
+                    ballots: 2,
+                },
+                CandidateDuplicates {
+                    candidate: CandidateId(1),
+                    ballots: 2,
+                },
+            ],
+            duplicates.candidates
+        );
+    }
+}
@@ -5,7 +5,7 @@ pub fn nyc_normalizer(ballot: Ballot) -> Option<NormalizedBallot> {
     // NYC-style normalization: completely exclude ballots with no valid votes
     // This prevents inactive ballots from being processed at all
     let mut seen = BTreeSet::new();
-    let Ballot { id, choices } = ballot;
+    let Ballot { id, choices, .. } = ballot;
     let mut new_choices = Vec::new();
     let mut overvoted = false;
     let mut has_valid_votes = false;
@@ -8,7 +8,7 @@ pub fn simple_normalizer(ballot: Ballot) -> NormalizedBallot {
     // is ambiguous (i.e. an overvote), consider the ballot
     // exhausted.
     let mut seen = BTreeSet::new();
-    let Ballot { id, choices } = ballot;
+    let Ballot { id, choices, .. } = ballot;
     let mut new_choices = Vec::new();
     let mut overvoted = false;
 
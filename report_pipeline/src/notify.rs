@@ -0,0 +1,98 @@
+use crate::log_warn;
+use crate::model::notification::{Notification, WinnerChange};
+use crate::model::report::ReportIndex;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Timeout for each webhook request, so that an unresponsive endpoint
+/// doesn't hold up the pipeline.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Find contests whose winner differs between two runs. Contests that only
+/// appear in one of the runs are not reported.
+pub fn winner_changes(previous: &ReportIndex, current: &ReportIndex) -> Vec<WinnerChange> {
+    let previous_winners: BTreeMap<(&str, &str), &str> = previous
+        .elections
+        .iter()
+        .flat_map(|election| {
+            election.contests.iter().map(move |contest| {
+                (
+                    (election.path.as_str(), contest.office.as_str()),
+                    contest.winner.as_str(),
+                )
+            })
+        })
+        .collect();
+
+    let mut changes = Vec::new();
+    for election in &current.elections {
+        for contest in &election.contests {
+            let key = (election.path.as_str(), contest.office.as_str());
+            if let Some(previous_winner) = previous_winners.get(&key) {
+                if *previous_winner != contest.winner {
+                    changes.push(WinnerChange {
+                        path: election.path.clone(),
+                        office: contest.office.clone(),
+                        office_name: contest.office_name.clone(),
+                        previous_winner: previous_winner.to_string(),
+                        winner: contest.winner.clone(),
+                    });
+                }
+            }
+        }
+    }
+    changes
+}
+
+/// POST a notification as JSON to each webhook URL. Failures are logged but
+/// don't fail the run.
+pub fn send_notification(webhooks: &[String], notification: &Notification) {
+    let agent = ureq::AgentBuilder::new().timeout(WEBHOOK_TIMEOUT).build();
+    for url in webhooks {
+        if let Err(e) = agent.post(url).send_json(notification) {
+            log_warn!("Failed to notify webhook {}: {}", url, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::report::{ContestIndexEntry, ElectionIndexEntry};
+
+    fn index(contests: &[(&str, &str)]) -> ReportIndex {
+        ReportIndex {
+            elections: vec![ElectionIndexEntry {
+                path: "us/ca/sfo/2024/11".to_string(),
+                jurisdiction_name: "San Francisco".to_string(),
+                election_name: "General Election".to_string(),
+                date: "2024-11-05".to_string(),
+                contests: contests
+                    .iter()
+                    .map(|(office, winner)| ContestIndexEntry {
+                        office: office.to_string(),
+                        office_id: format!("us/ca/sfo/{}", office),
+                        office_name: office.to_string(),
+                        name: office.to_string(),
+                        winner: winner.to_string(),
+                        num_candidates: 2,
+                        num_rounds: 1,
+                        ..Default::default()
+                    })
+                    .collect(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_winner_changes() {
+        let previous = index(&[("mayor", "Alice"), ("d1", "Carol"), ("d3", "Erin")]);
+        let current = index(&[("mayor", "Bob"), ("d1", "Carol"), ("d5", "Frank")]);
+
+        let changes = winner_changes(&previous, &current);
+        assert_eq!(1, changes.len());
+        assert_eq!("mayor", changes[0].office);
+        assert_eq!("Alice", changes[0].previous_winner);
+        assert_eq!("Bob", changes[0].winner);
+    }
+}
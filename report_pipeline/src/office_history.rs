@@ -0,0 +1,125 @@
+use crate::model::report::{ElectionIndexEntry, OfficeContestEntry, OfficeHistory, OfficeIndex};
+use std::collections::BTreeMap;
+
+/// File name of the office index, written in the root report directory.
+pub const OFFICE_INDEX_FILE: &str = "by-office.json";
+
+/// Group the contests in the index by their stable office identifier, so
+/// that each office's history can be listed without scanning every
+/// election. Offices are ordered by identifier and contests by date.
+pub fn generate_office_index(elections: &[ElectionIndexEntry]) -> OfficeIndex {
+    let mut offices: BTreeMap<&str, OfficeHistory> = BTreeMap::new();
+    for election in elections {
+        for contest in &election.contests {
+            offices
+                .entry(&contest.office_id)
+                .or_insert_with(|| OfficeHistory {
+                    id: contest.office_id.clone(),
+                    jurisdiction_name: election.jurisdiction_name.clone(),
+                    office_name: String::new(),
+                    contests: Vec::new(),
+                })
+                .contests
+                .push(OfficeContestEntry {
+                    election_path: election.path.clone(),
+                    election_name: election.election_name.clone(),
+                    date: election.date.clone(),
+                    office: contest.office.clone(),
+                    office_name: contest.office_name.clone(),
+                    winner: contest.winner.clone(),
+                    status: contest.status,
+                });
+        }
+    }
+
+    let mut offices: Vec<OfficeHistory> = offices.into_values().collect();
+    for office in &mut offices {
+        office
+            .contests
+            .sort_by(|a, b| (&a.date, &a.election_path).cmp(&(&b.date, &b.election_path)));
+        office.office_name = office.contests.last().unwrap().office_name.clone();
+    }
+    OfficeIndex { offices }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::report::ContestIndexEntry;
+
+    fn contest(
+        office: &str,
+        office_id: &str,
+        office_name: &str,
+        winner: &str,
+    ) -> ContestIndexEntry {
+        ContestIndexEntry {
+            office: office.to_string(),
+            office_id: office_id.to_string(),
+            office_name: office_name.to_string(),
+            name: office_name.to_string(),
+            winner: winner.to_string(),
+            num_candidates: 3,
+            num_rounds: 2,
+            ..Default::default()
+        }
+    }
+
+    fn election(path: &str, date: &str, contests: Vec<ContestIndexEntry>) -> ElectionIndexEntry {
+        ElectionIndexEntry {
+            path: path.to_string(),
+            jurisdiction_name: "San Francisco".to_string(),
+            election_name: "General Election".to_string(),
+            date: date.to_string(),
+            contests,
+        }
+    }
+
+    #[test]
+    fn test_office_index() {
+        // The index lists elections newest first. The district 1 seat was
+        // renamed, but shares its history through its stable identifier.
+        let elections = vec![
+            election(
+                "us/ca/sfo/2024/11",
+                "2024-11-05",
+                vec![
+                    contest("mayor", "us/ca/sfo/mayor", "Mayor", "Daniel Lurie"),
+                    contest(
+                        "board-d1",
+                        "us/ca/sfo/d1",
+                        "Supervisor, District 1",
+                        "Connie Chan",
+                    ),
+                ],
+            ),
+            election(
+                "us/ca/sfo/2020/11",
+                "2020-11-03",
+                vec![contest("d1", "us/ca/sfo/d1", "District 1", "Connie Chan")],
+            ),
+        ];
+
+        let index = generate_office_index(&elections);
+        assert_eq!(
+            vec!["us/ca/sfo/d1", "us/ca/sfo/mayor"],
+            index
+                .offices
+                .iter()
+                .map(|o| o.id.as_str())
+                .collect::<Vec<_>>()
+        );
+        let d1 = &index.offices[0];
+        assert_eq!("Supervisor, District 1", d1.office_name);
+        assert_eq!(
+            vec![
+                ("us/ca/sfo/2020/11", "d1"),
+                ("us/ca/sfo/2024/11", "board-d1")
+            ],
+            d1.contests
+                .iter()
+                .map(|c| (c.election_path.as_str(), c.office.as_str()))
+                .collect::<Vec<_>>()
+        );
+    }
+}
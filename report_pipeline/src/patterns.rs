@@ -0,0 +1,154 @@
+//! Ranking pattern frequency tables: the distinct rankings on a contest's
+//! normalized ballots, with the number of ballots having each.
+//!
+//! A pattern is written as the IDs of the ranked candidates separated by
+//! `>`, e.g. `3>0>5`, followed by `>overvote` if the ballot ends in an
+//! overvote. A blank ballot is the empty string, and a ballot with an
+//! overvote at its first rank is `overvote`.
+
+use crate::model::election::{CandidateId, NormalizedBallot};
+use crate::model::report::RankingPatterns;
+use std::collections::BTreeMap;
+
+/// File name of the ranking pattern frequency table, written next to
+/// `report.json`.
+pub const RANKING_PATTERNS_FILE: &str = "ranking_patterns.json";
+
+const OVERVOTE: &str = "overvote";
+
+/// Write a ballot's ranking as a pattern string.
+pub fn pattern_string(ballot: &NormalizedBallot) -> String {
+    let mut parts: Vec<String> = ballot.choices().iter().map(|c| c.0.to_string()).collect();
+    if ballot.overvoted {
+        parts.push(OVERVOTE.to_string());
+    }
+    parts.join(">")
+}
+
+/// Count the ballots with each distinct ranking.
+pub fn generate_ranking_patterns(
+    candidate_names: Vec<String>,
+    ballots: &[NormalizedBallot],
+) -> RankingPatterns {
+    let mut patterns: BTreeMap<String, u32> = BTreeMap::new();
+    for ballot in ballots {
+        *patterns.entry(pattern_string(ballot)).or_insert(0) += 1;
+    }
+
+    RankingPatterns {
+        candidates: candidate_names,
+        ballot_count: ballots.len() as u32,
+        patterns,
+    }
+}
+
+/// Parse a pattern string back into a ballot, identified by the pattern.
+pub fn parse_pattern(pattern: &str) -> Result<NormalizedBallot, String> {
+    let mut choices = Vec::new();
+    let mut overvoted = false;
+
+    if !pattern.is_empty() {
+        for part in pattern.split('>') {
+            if overvoted {
+                return Err(format!("Choice after overvote in pattern: {}", pattern));
+            }
+            if part == OVERVOTE {
+                overvoted = true;
+            } else {
+                let id = part.parse::<u32>().map_err(|_| {
+                    format!("Invalid candidate ID {:?} in pattern: {}", part, pattern)
+                })?;
+                choices.push(CandidateId(id));
+            }
+        }
+    }
+
+    Ok(NormalizedBallot::new(
+        pattern.to_string(),
+        choices,
+        overvoted,
+    ))
+}
+
+/// Expand a frequency table into one ballot per distinct pattern, weighted
+/// by the number of ballots having it, for `tabulate_weighted`.
+pub fn weighted_ballots(
+    patterns: &RankingPatterns,
+) -> Result<Vec<(NormalizedBallot, u32)>, String> {
+    let num_candidates = patterns.candidates.len() as u32;
+    let mut ballots = Vec::with_capacity(patterns.patterns.len());
+
+    for (pattern, &count) in &patterns.patterns {
+        let ballot = parse_pattern(pattern)?;
+        if let Some(c) = ballot.choices().iter().find(|c| c.0 >= num_candidates) {
+            return Err(format!(
+                "Unknown candidate ID {} in pattern: {}",
+                c.0, pattern
+            ));
+        }
+        ballots.push((ballot, count));
+    }
+
+    Ok(ballots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::metadata::TabulationOptions;
+    use crate::tabulator::{tabulate, tabulate_weighted};
+
+    #[test]
+    fn test_pattern_string() {
+        let ballots = [
+            NormalizedBallot::new("1".to_string(), vec![CandidateId(3), CandidateId(0)], false),
+            NormalizedBallot::new("2".to_string(), vec![CandidateId(1)], true),
+            NormalizedBallot::new("3".to_string(), vec![], true),
+            NormalizedBallot::new("4".to_string(), vec![], false),
+        ];
+
+        let patterns: Vec<String> = ballots.iter().map(pattern_string).collect();
+        assert_eq!(vec!["3>0", "1>overvote", "overvote", ""], patterns);
+    }
+
+    #[test]
+    fn test_parse_pattern() {
+        for pattern in &["3>0", "1>overvote", "overvote", ""] {
+            assert_eq!(*pattern, pattern_string(&parse_pattern(pattern).unwrap()));
+        }
+        assert!(parse_pattern("1>x").is_err());
+        assert!(parse_pattern("overvote>1").is_err());
+    }
+
+    #[test]
+    fn test_weighted_tabulation_matches_ballots() {
+        let rankings: [(&[u32], bool); 9] = [
+            (&[0, 1], false),
+            (&[0, 2], false),
+            (&[0], true),
+            (&[1, 2], false),
+            (&[1, 2], false),
+            (&[1], false),
+            (&[2, 0], false),
+            (&[2, 1], false),
+            (&[], true),
+        ];
+        let ballots: Vec<NormalizedBallot> = rankings
+            .iter()
+            .enumerate()
+            .map(|(i, (choices, overvoted))| {
+                let choices = choices.iter().map(|&c| CandidateId(c)).collect();
+                NormalizedBallot::new(i.to_string(), choices, *overvoted)
+            })
+            .collect();
+
+        let names = vec!["A".to_string(), "B".to_string(), "C".to_string()];
+        let patterns = generate_ranking_patterns(names, &ballots);
+        assert_eq!(Some(&2), patterns.patterns.get("1>2"));
+
+        let options = TabulationOptions::default();
+        let expected = serde_json::to_value(tabulate(&ballots, &options)).unwrap();
+        let weighted = tabulate_weighted(weighted_ballots(&patterns).unwrap(), &options);
+        assert_eq!(expected, serde_json::to_value(weighted).unwrap());
+    }
+}
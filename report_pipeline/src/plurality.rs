@@ -0,0 +1,154 @@
+//! Plurality tallies of non-ranked contests, for CVRs that include vote-for-one
+//! or vote-for-N contests alongside ranked ones. Each ballot counts once for
+//! each candidate it votes for, unless it votes for more than allowed.
+
+use crate::model::election::{CandidateId, NormalizedBallot};
+use crate::model::report::{PluralityTally, PluralityVotes};
+use crate::tabulator::{Allocatee, TabulatorAllocation, TabulatorRound};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+/// Tally the ballots of a contest in which each voter may vote for up to
+/// `vote_for` candidates. A ballot's choices are the candidates it votes
+/// for; one with more than `vote_for`, or ending in an overvote, counts for
+/// none of them.
+pub fn generate_plurality_tally(ballots: &[NormalizedBallot], vote_for: u32) -> PluralityTally {
+    let mut votes: BTreeMap<CandidateId, u32> = BTreeMap::new();
+    let mut blank_ballots = 0;
+    let mut overvoted_ballots = 0;
+    let mut undervotes = 0;
+    for ballot in ballots {
+        let choices = ballot.choice_slice();
+        if ballot.overvoted || choices.len() > vote_for as usize {
+            overvoted_ballots += 1;
+            continue;
+        }
+        if choices.is_empty() {
+            blank_ballots += 1;
+        }
+        undervotes += vote_for - choices.len() as u32;
+        for candidate in choices {
+            *votes.entry(*candidate).or_default() += 1;
+        }
+    }
+
+    let mut votes: Vec<PluralityVotes> = votes
+        .into_iter()
+        .map(|(candidate, votes)| PluralityVotes { candidate, votes })
+        .collect();
+    votes.sort_by_key(|v| (Reverse(v.votes), v.candidate));
+
+    // The last place is tied if the first candidate not elected has as many
+    // votes as the last one elected.
+    let seats = vote_for as usize;
+    let (winners, tied) = match votes.get(seats) {
+        Some(next) if seats > 0 && votes[seats - 1].votes == next.votes => (
+            votes
+                .iter()
+                .filter(|v| v.votes > next.votes)
+                .map(|v| v.candidate)
+                .collect(),
+            votes
+                .iter()
+                .filter(|v| v.votes == next.votes)
+                .map(|v| v.candidate)
+                .collect(),
+        ),
+        _ => (
+            votes.iter().take(seats).map(|v| v.candidate).collect(),
+            Vec::new(),
+        ),
+    };
+
+    PluralityTally {
+        vote_for,
+        votes,
+        blank_ballots,
+        overvoted_ballots,
+        undervotes,
+        winners,
+        tied,
+    }
+}
+
+/// The tally as a single round, so that the report of a non-ranked contest
+/// has its totals where those of a ranked contest are. Ballots that count
+/// for no candidate are exhausted.
+pub fn plurality_round(tally: &PluralityTally) -> TabulatorRound {
+    let mut allocations: Vec<TabulatorAllocation> = tally
+        .votes
+        .iter()
+        .map(|v| TabulatorAllocation {
+            allocatee: Allocatee::Candidate(v.candidate),
+            votes: v.votes,
+            shares: None,
+        })
+        .collect();
+    allocations.push(TabulatorAllocation {
+        allocatee: Allocatee::Exhausted,
+        votes: tally.blank_ballots + tally.overvoted_ballots,
+        shares: None,
+    });
+
+    TabulatorRound {
+        allocations,
+        undervote: tally.blank_ballots,
+        overvote: tally.overvoted_ballots,
+        continuing_ballots: tally.votes.iter().map(|v| v.votes).sum(),
+        transfers: Vec::new(),
+        threshold: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ballots(votes: &[(&[u32], usize)]) -> Vec<NormalizedBallot> {
+        votes
+            .iter()
+            .flat_map(|&(candidates, count)| std::iter::repeat_n(candidates, count))
+            .map(|candidates| {
+                let choices = candidates.iter().map(|&c| CandidateId(c)).collect();
+                NormalizedBallot::new(String::new(), choices, false)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_vote_for_two() {
+        let mut ballots = ballots(&[(&[0, 1], 5), (&[1, 2], 3), (&[2], 2), (&[0, 1, 2], 1)]);
+        ballots.push(NormalizedBallot::new(String::new(), vec![], false));
+        ballots.push(NormalizedBallot::new(String::new(), vec![], true));
+
+        let tally = generate_plurality_tally(&ballots, 2);
+        assert_eq!(
+            vec![(1, 8), (0, 5), (2, 5)],
+            tally
+                .votes
+                .iter()
+                .map(|v| (v.candidate.0, v.votes))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(1, tally.blank_ballots);
+        assert_eq!(2, tally.overvoted_ballots);
+        assert_eq!(2 + 2, tally.undervotes);
+        // A and C are tied for the second seat.
+        assert_eq!(vec![CandidateId(1)], tally.winners);
+        assert_eq!(vec![CandidateId(0), CandidateId(2)], tally.tied);
+
+        let round = plurality_round(&tally);
+        assert_eq!(18, round.continuing_ballots);
+        let exhausted = round.allocations.last().unwrap();
+        assert!(exhausted.allocatee == Allocatee::Exhausted);
+        assert_eq!(3, exhausted.votes);
+    }
+
+    #[test]
+    fn test_vote_for_one() {
+        let tally = generate_plurality_tally(&ballots(&[(&[0], 4), (&[1], 6)]), 1);
+        assert_eq!(vec![CandidateId(1)], tally.winners);
+        assert!(tally.tied.is_empty());
+        assert_eq!(0, tally.undervotes);
+    }
+}
@@ -0,0 +1,188 @@
+use crate::model::election::{Candidate, CandidateId, NormalizedBallot};
+use serde_json::{Map, Value};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+/// File name of the per-precinct results, written next to `report.json`.
+pub const PRECINCT_MAP_FILE: &str = "precincts.geojson";
+
+#[derive(Default)]
+struct PrecinctResults {
+    ballot_count: u32,
+    first_choice: HashMap<CandidateId, u32>,
+    final_round: HashMap<CandidateId, u32>,
+}
+
+/// Return the candidate with the most votes, their vote count, and their
+/// margin over the runner-up. Ties go to the lower candidate ID.
+fn leader(votes: &HashMap<CandidateId, u32>) -> Option<(CandidateId, u32, u32)> {
+    let mut sorted: Vec<(CandidateId, u32)> = votes.iter().map(|(c, v)| (*c, *v)).collect();
+    sorted.sort_by_key(|(c, v)| (Reverse(*v), *c));
+
+    let (top, top_votes) = *sorted.first()?;
+    let runner_up_votes = sorted.get(1).map(|(_, v)| *v).unwrap_or(0);
+    Some((top, top_votes, top_votes - runner_up_votes))
+}
+
+/// Tally first-choice and final-round support for each precinct. A ballot
+/// counts in the final round toward its highest-ranked remaining candidate;
+/// exhausted ballots only count toward the precinct's ballot count.
+fn tally_precincts<'a>(
+    ballots: &'a [NormalizedBallot],
+    final_round_candidates: &HashSet<CandidateId>,
+) -> HashMap<&'a str, PrecinctResults> {
+    let mut precincts: HashMap<&'a str, PrecinctResults> = HashMap::new();
+
+    for ballot in ballots {
+        let precinct = match &ballot.precinct {
+            Some(precinct) => precinct.as_str(),
+            None => continue,
+        };
+        let results = precincts.entry(precinct).or_default();
+        results.ballot_count += 1;
+
+        let choices = ballot.choices();
+        if let Some(first) = choices.first() {
+            *results.first_choice.entry(*first).or_insert(0) += 1;
+        }
+        if let Some(last) = choices.iter().find(|c| final_round_candidates.contains(c)) {
+            *results.final_round.entry(*last).or_insert(0) += 1;
+        }
+    }
+
+    precincts
+}
+
+/// Read the value of a feature property as a string, so that numeric
+/// precinct IDs in the GeoJSON match the names recorded on ballots.
+fn property_string(feature: &Value, property: &str) -> Option<String> {
+    match feature.get("properties")?.get(property)? {
+        Value::String(s) => Some(s.clone()),
+        Value::Null => None,
+        v => Some(v.to_string()),
+    }
+}
+
+/// Join per-precinct results onto the features of a precinct GeoJSON
+/// `FeatureCollection`, matching the given feature property against the
+/// precinct recorded on each ballot. Features are kept even if no ballots
+/// matched them, so that the map has no holes.
+pub fn generate_precinct_map(
+    mut geojson: Value,
+    property: &str,
+    ballots: &[NormalizedBallot],
+    candidates: &[Candidate],
+    final_round_candidates: &HashSet<CandidateId>,
+) -> Value {
+    let precincts = tally_precincts(ballots, final_round_candidates);
+    let candidate_name = |c: CandidateId| Value::from(candidates[c.0 as usize].display_name());
+    let mut matched: HashSet<String> = HashSet::new();
+
+    let features = match geojson.get_mut("features").and_then(|f| f.as_array_mut()) {
+        Some(features) => features,
+        None => {
+            crate::log_warn!("Precinct GeoJSON has no features.");
+            return geojson;
+        }
+    };
+
+    for feature in features {
+        let key = property_string(feature, property);
+        let results = key.as_deref().and_then(|k| precincts.get(k));
+        let first_choice = results.and_then(|r| leader(&r.first_choice));
+        let final_round = results.and_then(|r| leader(&r.final_round));
+
+        if !feature.get("properties").is_some_and(Value::is_object) {
+            feature["properties"] = Value::Object(Map::new());
+        }
+        let properties = feature["properties"].as_object_mut().unwrap();
+
+        properties.insert(
+            "ballotCount".to_string(),
+            Value::from(results.map_or(0, |r| r.ballot_count)),
+        );
+        properties.insert(
+            "firstChoiceLeader".to_string(),
+            first_choice.map_or(Value::Null, |(c, _, _)| candidate_name(c)),
+        );
+        properties.insert(
+            "firstChoiceVotes".to_string(),
+            first_choice.map_or(Value::Null, |(_, v, _)| Value::from(v)),
+        );
+        properties.insert(
+            "finalRoundLeader".to_string(),
+            final_round.map_or(Value::Null, |(c, _, _)| candidate_name(c)),
+        );
+        properties.insert(
+            "finalRoundMargin".to_string(),
+            final_round.map_or(Value::Null, |(_, _, m)| Value::from(m)),
+        );
+
+        if let (Some(key), Some(_)) = (key, results) {
+            matched.insert(key);
+        }
+    }
+
+    let unmatched = precincts.keys().filter(|p| !matched.contains(**p)).count();
+    if unmatched > 0 {
+        crate::log_warn!(
+            "{} of {} precincts with ballots have no matching GeoJSON feature.",
+            unmatched,
+            precincts.len()
+        );
+    }
+
+    geojson
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::CandidateType;
+    use serde_json::json;
+
+    fn ballot(precinct: &str, choices: Vec<u32>) -> NormalizedBallot {
+        NormalizedBallot::new(
+            "1".to_string(),
+            choices.into_iter().map(CandidateId).collect(),
+            false,
+        )
+        .with_precinct(Some(precinct.to_string()))
+    }
+
+    #[test]
+    fn test_generate_precinct_map() {
+        let candidates = vec![
+            Candidate::new("Alice".to_string(), CandidateType::Regular),
+            Candidate::new("Bob".to_string(), CandidateType::Regular),
+            Candidate::new("Carol".to_string(), CandidateType::Regular),
+        ];
+        let final_round: HashSet<CandidateId> = [CandidateId(0), CandidateId(1)].into();
+        let ballots = vec![
+            ballot("101", vec![2, 1]),
+            ballot("101", vec![2, 1]),
+            ballot("101", vec![0]),
+            ballot("102", vec![1]),
+        ];
+        let geojson = json!({
+            "type": "FeatureCollection",
+            "features": [
+                {"type": "Feature", "properties": {"PCT": 101}, "geometry": null},
+                {"type": "Feature", "properties": {"PCT": "103"}, "geometry": null},
+            ]
+        });
+
+        let map = generate_precinct_map(geojson, "PCT", &ballots, &candidates, &final_round);
+
+        let first = &map["features"][0]["properties"];
+        assert_eq!(3, first["ballotCount"]);
+        assert_eq!("Carol", first["firstChoiceLeader"]);
+        assert_eq!(2, first["firstChoiceVotes"]);
+        assert_eq!("Bob", first["finalRoundLeader"]);
+        assert_eq!(1, first["finalRoundMargin"]);
+
+        let second = &map["features"][1]["properties"];
+        assert_eq!(0, second["ballotCount"]);
+        assert_eq!(Value::Null, second["finalRoundLeader"]);
+    }
+}
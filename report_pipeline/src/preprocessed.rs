@@ -0,0 +1,548 @@
+//! Storage of preprocessed ballots between the preprocessing and report
+//! passes.
+//!
+//! Preprocessed ballots are written in a compact binary columnar format
+//! (`normalized.bin`), so that reports can be regenerated without parsing
+//! large JSON files. When read, the file is memory-mapped rather than read
+//! into a buffer, but its columns are still decoded into ballots in memory.
+//! Optionally, the binary format is compressed with zstd
+//! (`normalized.bin.zst`), which is decompressed into memory when read. The older gzipped JSON format
+//! (`normalized.json.gz`) can still be read, and converted with the
+//! `convert-preprocessed` command. Readers tell the formats apart by their
+//! magic bytes.
+//!
+//! The binary format is little-endian and laid out as:
+//!
+//! - the magic bytes `RVNB` and a `u32` format version;
+//! - a `u32` length followed by a JSON header holding the election info,
+//!   candidates, and dictionaries of counting group and precinct names;
+//! - a `u64` ballot count `n`;
+//! - `n + 1` `u32` offsets into the choice column, then the choice column
+//!   of `u32` candidate IDs;
+//! - `n` `u8` overvote flags;
+//! - `n` `u32` numbers of choices counted before consecutive skipped ranks
+//!   exhaust the ballot, with `u32::MAX` for ballots they don't (from
+//!   version 3; version 2 files, which lack it, are still read);
+//! - `n` `u32` counting group indices and `n` `u32` precinct indices into
+//!   the header dictionaries, with `u32::MAX` for none;
+//! - `n + 1` `u32` offsets into the ballot ID bytes, then the UTF-8 bytes of
+//!   the ballot IDs.
+
+use crate::model::election::{
+    AdjudicationImpact, Candidate, CandidateId, CandidateReconciliation, DuplicateBallots,
+    DuplicateRankings, ElectionInfo, ElectionPreprocessed, FirstRoundAdjustments, NormalizedBallot,
+    NormalizedElection, OvervoteHandling, Provenance, RankPositionStatistics, SkippedRankHandling,
+    TruncatedBallot,
+};
+use crate::util::write_serialized;
+use flate2::read::GzDecoder;
+use memmap2::Mmap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::{TryFrom, TryInto};
+use std::fs::{remove_file, rename, File};
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// File name of preprocessed ballots in the binary format.
+pub const PREPROCESSED_FILE: &str = "normalized.bin";
+
+/// File name of preprocessed ballots in the binary format, compressed with
+/// zstd.
+pub const ZSTD_PREPROCESSED_FILE: &str = "normalized.bin.zst";
+
+/// File name of preprocessed ballots in the legacy gzipped JSON format.
+pub const LEGACY_PREPROCESSED_FILE: &str = "normalized.json.gz";
+
+/// File names of preprocessed ballots, in the order cached files are
+/// preferred.
+pub const PREPROCESSED_FILES: &[&str] = &[
+    PREPROCESSED_FILE,
+    ZSTD_PREPROCESSED_FILE,
+    LEGACY_PREPROCESSED_FILE,
+];
+
+const ZSTD_MAGIC: &[u8; 4] = b"\x28\xb5\x2f\xfd";
+const GZIP_MAGIC: &[u8; 2] = b"\x1f\x8b";
+
+const MAGIC: &[u8; 4] = b"RVNB";
+const VERSION: u32 = 3;
+/// Oldest format version that can be read.
+const MIN_VERSION: u32 = 2;
+const NONE_INDEX: u32 = u32::MAX;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Header {
+    info: ElectionInfo,
+    candidates: Vec<Candidate>,
+    adjudicated_ballots: Option<u32>,
+    #[serde(default)]
+    adjudication: Option<AdjudicationImpact>,
+    #[serde(default)]
+    duplicate_ballots: Option<DuplicateBallots>,
+    #[serde(default)]
+    candidate_reconciliation: Option<CandidateReconciliation>,
+    counting_groups: Vec<String>,
+    precincts: Vec<String>,
+    #[serde(default)]
+    provenance: Option<Provenance>,
+    #[serde(default)]
+    rank_positions: Option<Vec<RankPositionStatistics>>,
+    #[serde(default)]
+    duplicate_rankings: Option<DuplicateRankings>,
+    #[serde(default)]
+    first_round_adjustments: Option<FirstRoundAdjustments>,
+    #[serde(default)]
+    overvote_handling: Option<OvervoteHandling>,
+    #[serde(default)]
+    skipped_rank_handling: Option<SkippedRankHandling>,
+    #[serde(default)]
+    truncated_ballots: Option<Vec<TruncatedBallot>>,
+}
+
+/// Assigns indices to distinct strings, in order of first appearance.
+#[derive(Default)]
+struct Dictionary<'a> {
+    values: Vec<&'a str>,
+    indices: HashMap<&'a str, u32>,
+}
+
+impl<'a> Dictionary<'a> {
+    fn index(&mut self, value: Option<&'a str>) -> u32 {
+        let value = match value {
+            Some(value) => value,
+            None => return NONE_INDEX,
+        };
+        let values = &mut self.values;
+        *self.indices.entry(value).or_insert_with(|| {
+            values.push(value);
+            (values.len() - 1) as u32
+        })
+    }
+
+    fn to_vec(&self) -> Vec<String> {
+        self.values.iter().map(|v| v.to_string()).collect()
+    }
+}
+
+fn write_u32s<W: Write>(writer: &mut W, values: impl Iterator<Item = u32>) -> std::io::Result<()> {
+    for value in values {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Write the `n + 1` offsets of `n` consecutive items with the given lengths.
+/// Fails if the items don't fit in the `u32` range of offsets.
+fn write_offsets<W: Write>(
+    writer: &mut W,
+    lengths: impl Iterator<Item = usize>,
+) -> std::io::Result<()> {
+    let mut offset: u32 = 0;
+    writer.write_all(&offset.to_le_bytes())?;
+    for len in lengths {
+        offset = u32::try_from(len)
+            .ok()
+            .and_then(|len| offset.checked_add(len))
+            .ok_or_else(|| too_large("column"))?;
+        writer.write_all(&offset.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// The error for a section too large for the binary format's `u32` sizes.
+fn too_large(section: &str) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("{} is too large for the preprocessed format", section),
+    )
+}
+
+/// Write preprocessed ballots in the binary columnar format. Each column
+/// is written straight from the ballots rather than collected first, so
+/// writing takes little memory beyond the ballots themselves; only the
+/// dictionaries, which the header holds, are built up front.
+fn write_binary<W: Write>(
+    writer: &mut W,
+    preprocessed: &ElectionPreprocessed,
+) -> std::io::Result<()> {
+    let ballots = &preprocessed.ballots.ballots;
+    let mut counting_groups = Dictionary::default();
+    let mut precincts = Dictionary::default();
+    for ballot in ballots {
+        counting_groups.index(ballot.counting_group.as_deref());
+        precincts.index(ballot.precinct.as_deref());
+    }
+
+    let header = serde_json::to_vec(&Header {
+        info: preprocessed.info.clone(),
+        candidates: preprocessed.ballots.candidates.clone(),
+        adjudicated_ballots: preprocessed.ballots.adjudicated_ballots,
+        adjudication: preprocessed.ballots.adjudication.clone(),
+        duplicate_ballots: preprocessed.ballots.duplicate_ballots.clone(),
+        candidate_reconciliation: preprocessed.ballots.candidate_reconciliation.clone(),
+        counting_groups: counting_groups.to_vec(),
+        precincts: precincts.to_vec(),
+        provenance: preprocessed.provenance.clone(),
+        rank_positions: preprocessed.ballots.rank_positions.clone(),
+        duplicate_rankings: preprocessed.ballots.duplicate_rankings.clone(),
+        first_round_adjustments: preprocessed.ballots.first_round_adjustments.clone(),
+        overvote_handling: preprocessed.ballots.overvote_handling.clone(),
+        skipped_rank_handling: preprocessed.ballots.skipped_rank_handling.clone(),
+        truncated_ballots: preprocessed.ballots.truncated_ballots.clone(),
+    })
+    .unwrap();
+
+    writer.write_all(MAGIC)?;
+    writer.write_all(&VERSION.to_le_bytes())?;
+    let header_len = u32::try_from(header.len()).map_err(|_| too_large("header"))?;
+    writer.write_all(&header_len.to_le_bytes())?;
+    writer.write_all(&header)?;
+    writer.write_all(&(ballots.len() as u64).to_le_bytes())?;
+    write_offsets(writer, ballots.iter().map(|b| b.marked_choices().len()))?;
+    for ballot in ballots {
+        write_u32s(writer, ballot.marked_choices().iter().map(|c| c.0))?;
+    }
+    for ballot in ballots {
+        writer.write_all(&[ballot.overvoted as u8])?;
+    }
+    write_u32s(
+        writer,
+        ballots
+            .iter()
+            .map(|b| b.exhausted_after().unwrap_or(NONE_INDEX)),
+    )?;
+    write_u32s(
+        writer,
+        ballots
+            .iter()
+            .map(|b| counting_groups.index(b.counting_group.as_deref())),
+    )?;
+    write_u32s(
+        writer,
+        ballots
+            .iter()
+            .map(|b| precincts.index(b.precinct.as_deref())),
+    )?;
+    write_offsets(writer, ballots.iter().map(|b| b.id.len()))?;
+    for ballot in ballots {
+        writer.write_all(ballot.id.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Write a file with `write`, to a temporary file in the same directory that
+/// then replaces it, so that the file is never seen partly written, and a
+/// reader that has the old file memory-mapped keeps its contents.
+fn write_replacing(path: &Path, write: impl FnOnce(BufWriter<File>) -> std::io::Result<()>) {
+    let name = path.file_name().unwrap().to_string_lossy();
+    let temp = path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()));
+    let file = File::create(&temp)
+        .unwrap_or_else(|e| panic!("Failed to create {}: {}", temp.display(), e));
+    write(BufWriter::new(file))
+        .and_then(|()| rename(&temp, path))
+        .unwrap_or_else(|e| {
+            let _ = remove_file(&temp);
+            panic!("Failed to write {}: {}", path.display(), e)
+        });
+}
+
+/// Write preprocessed ballots in the binary columnar format.
+pub fn write_preprocessed_binary(path: &Path, preprocessed: &ElectionPreprocessed) {
+    write_replacing(path, |mut writer| {
+        write_binary(&mut writer, preprocessed)?;
+        writer.flush()
+    });
+}
+
+/// Write preprocessed ballots in the binary columnar format, compressed
+/// with zstd at the given level as they are written.
+pub fn write_preprocessed_zstd(path: &Path, preprocessed: &ElectionPreprocessed, level: i32) {
+    write_replacing(path, |writer| {
+        let mut encoder = zstd::Encoder::new(writer, level)?;
+        write_binary(&mut encoder, preprocessed)?;
+        encoder.finish()?.flush()
+    });
+}
+
+/// Write preprocessed ballots to `dir`, in the binary format, compressed
+/// with zstd at `zstd_level` if given. A file in the other binary format is
+/// removed, so that it isn't read instead. Returns the path written.
+pub fn write_preprocessed_to(
+    dir: &Path,
+    preprocessed: &ElectionPreprocessed,
+    zstd_level: Option<i32>,
+) -> PathBuf {
+    let (name, other) = match zstd_level {
+        Some(_) => (ZSTD_PREPROCESSED_FILE, PREPROCESSED_FILE),
+        None => (PREPROCESSED_FILE, ZSTD_PREPROCESSED_FILE),
+    };
+    let path = dir.join(name);
+    match zstd_level {
+        Some(level) => write_preprocessed_zstd(&path, preprocessed, level),
+        None => write_preprocessed_binary(&path, preprocessed),
+    }
+    let other = dir.join(other);
+    if other.exists() {
+        remove_file(&other)
+            .unwrap_or_else(|e| panic!("Failed to remove {}: {}", other.display(), e));
+    }
+    path
+}
+
+/// Reads sections from a byte buffer in order.
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, len: usize) -> &'a [u8] {
+        let slice = self
+            .bytes
+            .get(self.pos..self.pos + len)
+            .expect("Preprocessed file is truncated.");
+        self.pos += len;
+        slice
+    }
+
+    fn u32(&mut self) -> u32 {
+        u32::from_le_bytes(self.take(4).try_into().unwrap())
+    }
+
+    fn u64(&mut self) -> u64 {
+        u64::from_le_bytes(self.take(8).try_into().unwrap())
+    }
+
+    fn u32s(&mut self, len: usize) -> Vec<u32> {
+        self.take(len * 4)
+            .chunks_exact(4)
+            .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+            .collect()
+    }
+}
+
+/// Parse preprocessed ballots from the bytes of a binary columnar file.
+fn parse_preprocessed_binary(bytes: &[u8]) -> ElectionPreprocessed {
+    let mut cursor = Cursor { bytes, pos: 0 };
+
+    if cursor.take(4) != MAGIC {
+        panic!("Not a preprocessed ballot file.");
+    }
+    let version = cursor.u32();
+    if !(MIN_VERSION..=VERSION).contains(&version) {
+        panic!("Unsupported preprocessed ballot file version {}.", version);
+    }
+    let header_len = cursor.u32() as usize;
+    let header: Header = serde_json::from_slice(cursor.take(header_len)).unwrap();
+
+    let n = cursor.u64() as usize;
+    let choice_offsets = cursor.u32s(n + 1);
+    let choices = cursor.u32s(choice_offsets[n] as usize);
+    let overvoted = cursor.take(n);
+    let exhausted_after = if version >= 3 {
+        cursor.u32s(n)
+    } else {
+        vec![NONE_INDEX; n]
+    };
+    let counting_group_indices = cursor.u32s(n);
+    let precinct_indices = cursor.u32s(n);
+    let id_offsets = cursor.u32s(n + 1);
+    let ids = cursor.take(id_offsets[n] as usize);
+
+    let lookup = |dictionary: &[String], index: u32| {
+        if index == NONE_INDEX {
+            None
+        } else {
+            Some(dictionary[index as usize].clone())
+        }
+    };
+
+    let ballots: Vec<NormalizedBallot> = (0..n)
+        .map(|i| {
+            let id = std::str::from_utf8(&ids[id_offsets[i] as usize..id_offsets[i + 1] as usize])
+                .unwrap()
+                .to_string();
+            let ballot_choices = choices
+                [choice_offsets[i] as usize..choice_offsets[i + 1] as usize]
+                .iter()
+                .map(|c| CandidateId(*c))
+                .collect();
+
+            NormalizedBallot::new(id, ballot_choices, overvoted[i] != 0)
+                .with_exhausted_after(Some(exhausted_after[i]).filter(|&c| c != NONE_INDEX))
+                .with_counting_group(lookup(&header.counting_groups, counting_group_indices[i]))
+                .with_precinct(lookup(&header.precincts, precinct_indices[i]))
+        })
+        .collect();
+
+    ElectionPreprocessed {
+        info: header.info,
+        ballots: NormalizedElection {
+            candidates: header.candidates,
+            ballots,
+            adjudicated_ballots: header.adjudicated_ballots,
+            adjudication: header.adjudication,
+            duplicate_ballots: header.duplicate_ballots,
+            candidate_reconciliation: header.candidate_reconciliation,
+            rank_positions: header.rank_positions,
+            duplicate_rankings: header.duplicate_rankings,
+            first_round_adjustments: header.first_round_adjustments,
+            overvote_handling: header.overvote_handling,
+            skipped_rank_handling: header.skipped_rank_handling,
+            truncated_ballots: header.truncated_ballots,
+        },
+        provenance: header.provenance,
+    }
+}
+
+/// Parse preprocessed ballots from the bytes of a file in any of the
+/// formats, told apart by their magic bytes.
+fn parse_preprocessed(bytes: &[u8]) -> ElectionPreprocessed {
+    if bytes.starts_with(MAGIC) {
+        parse_preprocessed_binary(bytes)
+    } else if bytes.starts_with(ZSTD_MAGIC) {
+        parse_preprocessed(&zstd::decode_all(bytes).unwrap())
+    } else if bytes.starts_with(GZIP_MAGIC) {
+        let mut decompressed = Vec::new();
+        GzDecoder::new(bytes)
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        parse_preprocessed(&decompressed)
+    } else {
+        serde_json::from_slice(bytes).unwrap()
+    }
+}
+
+/// Read preprocessed ballots in any of the formats, whatever the file is
+/// named. Uncompressed files are memory-mapped rather than read into a
+/// buffer; either way, every ballot is decoded into memory.
+pub fn read_preprocessed(path: &Path) -> ElectionPreprocessed {
+    let file =
+        File::open(path).unwrap_or_else(|e| panic!("Failed to read {}: {}", path.display(), e));
+    // Safety: preprocessed files are only written by this program, which
+    // replaces them rather than writing to them in place.
+    let mmap = unsafe { Mmap::map(&file) }.unwrap();
+    parse_preprocessed(&mmap)
+}
+
+/// The preprocessed ballots of a contest, given either the file or the
+/// contest's directory in the preprocessed directory.
+pub fn contest_preprocessed_file(path: &Path) -> PathBuf {
+    if !path.is_dir() {
+        return path.to_path_buf();
+    }
+    PREPROCESSED_FILES
+        .iter()
+        .map(|name| path.join(name))
+        .find(|p| p.exists())
+        .unwrap_or_else(|| panic!("No preprocessed ballots in {}", path.display()))
+}
+
+/// Write preprocessed ballots, in either the uncompressed binary or legacy
+/// JSON format depending on the file name.
+pub fn write_preprocessed(path: &Path, preprocessed: &ElectionPreprocessed) {
+    if path.extension().is_some_and(|e| e == "bin") {
+        write_preprocessed_binary(path, preprocessed)
+    } else {
+        write_serialized(path, preprocessed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::CandidateType;
+    use crate::model::metadata::TabulationOptions;
+    use crate::util::TempDir;
+
+    #[test]
+    fn test_write_offsets() {
+        let mut bytes = Vec::new();
+        write_offsets(&mut bytes, vec![2, 3].into_iter()).unwrap();
+        assert_eq!(vec![0, 0, 0, 0, 2, 0, 0, 0, 5, 0, 0, 0], bytes);
+
+        // Offsets past the u32 range are an error rather than wrapping.
+        let lengths = vec![u32::MAX as usize, 1].into_iter();
+        assert!(write_offsets(&mut Vec::new(), lengths).is_err());
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let preprocessed = ElectionPreprocessed {
+            info: ElectionInfo {
+                name: "Mayor".to_string(),
+                date: "2024-11-05".to_string(),
+                data_format: "nist_sp_1500".to_string(),
+                tabulation_options: TabulationOptions::default(),
+                jurisdiction_path: "us/ca/sfo".to_string(),
+                election_path: "2024/11".to_string(),
+                office: "mayor".to_string(),
+                office_name: "Mayor".to_string(),
+                jurisdiction_name: "San Francisco".to_string(),
+                election_name: "General Election".to_string(),
+                loader_params: None,
+                website: None,
+                locale: None,
+                office_name_translations: Default::default(),
+                seats: None,
+                district: None,
+                term_years: None,
+                notes: Vec::new(),
+                office_id: None,
+                vote_for: None,
+            },
+            ballots: NormalizedElection {
+                candidates: vec![
+                    Candidate::new("Alice".to_string(), CandidateType::Regular),
+                    Candidate::new("Bob".to_string(), CandidateType::Regular),
+                ],
+                ballots: vec![
+                    NormalizedBallot::new("a:1".to_string(), vec![CandidateId(1)], false)
+                        .with_counting_group(Some("Vote by Mail".to_string()))
+                        .with_precinct(Some("1101".to_string())),
+                    NormalizedBallot::new("a:2".to_string(), vec![], true),
+                    NormalizedBallot::new(
+                        "a:3".to_string(),
+                        vec![CandidateId(0), CandidateId(1)],
+                        false,
+                    )
+                    .with_counting_group(Some("Election Day".to_string()))
+                    .with_exhausted_after(Some(1)),
+                ],
+                adjudicated_ballots: Some(1),
+                adjudication: None,
+                duplicate_ballots: None,
+                candidate_reconciliation: None,
+                rank_positions: None,
+                duplicate_rankings: None,
+                first_round_adjustments: None,
+                overvote_handling: None,
+                skipped_rank_handling: None,
+                truncated_ballots: None,
+            },
+            provenance: None,
+        };
+
+        let dir = TempDir::new("preprocessed");
+        for zstd_level in [None, Some(3)] {
+            let path = write_preprocessed_to(&dir, &preprocessed, zstd_level);
+            let read = read_preprocessed(&path);
+
+            assert_eq!(preprocessed.ballots.ballots, read.ballots.ballots);
+            assert_eq!(preprocessed.ballots.candidates, read.ballots.candidates);
+            assert_eq!(Some(1), read.ballots.adjudicated_ballots);
+            assert_eq!("us/ca/sfo", read.info.jurisdiction_path);
+        }
+        // Only the last format written is kept.
+        assert!(!dir.join(PREPROCESSED_FILE).exists());
+
+        let legacy_path = dir.join(LEGACY_PREPROCESSED_FILE);
+        write_preprocessed(&legacy_path, &preprocessed);
+        assert_eq!(
+            preprocessed.ballots.ballots,
+            read_preprocessed(&legacy_path).ballots.ballots
+        );
+    }
+}
@@ -0,0 +1,309 @@
+//! Comparison with RCTab, the Ranked Choice Voting Resource Center's
+//! reference tabulator, which many jurisdictions certify their results
+//! with.
+//!
+//! Given the RCTab contest config and the `summary.json` results it wrote,
+//! a contest is tabulated here under equivalent options and each round's
+//! votes are compared. Rules that RCTab applies while counting but this
+//! pipeline settles when preprocessing ballots (overvotes, skipped ranks)
+//! are compared with the ballots' provenance instead, since preprocessed
+//! ballots can't be normalized again.
+
+use crate::formats::NameMatching;
+use crate::model::comparison::{
+    AllocationDifference, OptionMismatch, RctabComparison, RoundDifference,
+};
+use crate::model::election::{Candidate, NormalizedBallot, Provenance};
+use crate::model::metadata::{OvervotePolicy, SkippedRankPolicy, TabulationOptions};
+use crate::report::winner;
+use crate::tabulator::tabulate;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// The parts of an RCTab contest config that affect tabulation.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RctabConfig {
+    pub rules: RctabRules,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RctabRules {
+    #[serde(default)]
+    pub winner_election_mode: Option<String>,
+    #[serde(default)]
+    pub overvote_rule: Option<String>,
+    /// A number, or `"unlimited"`. Older versions write numbers as strings.
+    #[serde(default)]
+    pub max_skipped_ranks_allowed: Option<Value>,
+    #[serde(default)]
+    pub batch_elimination: bool,
+    #[serde(default)]
+    pub continue_until_two_candidates_remain: bool,
+}
+
+/// The results RCTab writes as `summary.json`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RctabSummary {
+    pub results: Vec<RctabRound>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RctabRound {
+    /// Votes of each continuing candidate, by name, as numbers or strings.
+    pub tally: BTreeMap<String, Value>,
+    #[serde(default)]
+    pub tally_results: Vec<RctabTallyResult>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RctabTallyResult {
+    #[serde(default)]
+    pub elected: Option<String>,
+}
+
+/// A number in RCTab output, which may be written as a string.
+fn number(value: &Value) -> f64 {
+    match value {
+        Value::Number(n) => n.as_f64().unwrap_or(0.0),
+        Value::String(s) => s.trim().parse().unwrap_or(0.0),
+        _ => 0.0,
+    }
+}
+
+/// An RCTab setting without case, spaces or punctuation, so that the
+/// setting names of newer versions (`exhaustImmediately`) match the labels
+/// of older ones (`Exhaust immediately`).
+fn setting_key(setting: &str) -> String {
+    setting
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Compare an RCTab rule with how this pipeline counted the contest.
+fn check_rule(
+    mismatches: &mut Vec<OptionMismatch>,
+    option: &str,
+    rctab: Option<String>,
+    ours: &str,
+    equivalent: &[&str],
+) {
+    if let Some(rctab) = rctab {
+        let key = setting_key(&rctab);
+        if !equivalent.iter().any(|e| setting_key(e) == key) {
+            mismatches.push(OptionMismatch {
+                option: option.to_string(),
+                rctab,
+                ours: ours.to_string(),
+            });
+        }
+    }
+}
+
+/// RCTab rules that this pipeline applied differently when counting the
+/// contest whose ballots have the given provenance.
+fn option_mismatches(rules: &RctabRules, provenance: Option<&Provenance>) -> Vec<OptionMismatch> {
+    let mut mismatches = Vec::new();
+    check_rule(
+        &mut mismatches,
+        "winnerElectionMode",
+        rules.winner_election_mode.clone(),
+        "singleWinnerMajority",
+        &[
+            "singleWinnerMajority",
+            "Single-winner majority determines winner",
+        ],
+    );
+
+    let overvote_rule = match provenance
+        .and_then(|p| p.overvote_policy)
+        .unwrap_or_default()
+    {
+        OvervotePolicy::Exhaust => "exhaustImmediately",
+        OvervotePolicy::Skip => "alwaysSkipToNextRank",
+    };
+    check_rule(
+        &mut mismatches,
+        "overvoteRule",
+        rules.overvote_rule.clone(),
+        overvote_rule,
+        &[overvote_rule],
+    );
+
+    let max_skipped_ranks = match provenance
+        .and_then(|p| p.skipped_rank_policy)
+        .unwrap_or_default()
+    {
+        SkippedRankPolicy::Skip => "unlimited",
+        SkippedRankPolicy::ExhaustAfterTwo => "1",
+    };
+    check_rule(
+        &mut mismatches,
+        "maxSkippedRanksAllowed",
+        rules
+            .max_skipped_ranks_allowed
+            .as_ref()
+            .map(|value| match value {
+                Value::String(s) => s.clone(),
+                value => value.to_string(),
+            }),
+        max_skipped_ranks,
+        &[max_skipped_ranks],
+    );
+    mismatches
+}
+
+/// Tabulate a contest's ballots under the options equivalent to an RCTab
+/// config, and compare the rounds with RCTab's results. Candidates are
+/// matched by name, ignoring differences in Unicode encoding and spacing.
+pub fn compare_with_rctab(
+    candidates: &[Candidate],
+    ballots: &[NormalizedBallot],
+    provenance: Option<&Provenance>,
+    config: &RctabConfig,
+    summary: &RctabSummary,
+) -> RctabComparison {
+    let options = TabulationOptions {
+        eager: Some(config.rules.batch_elimination),
+        ..TabulationOptions::default()
+    };
+    let rounds = tabulate(ballots, &options);
+
+    let matching = NameMatching::default();
+    let mut round_differences = Vec::new();
+    for (i, (round, rctab_round)) in rounds.iter().zip(&summary.results).enumerate() {
+        // Votes of each candidate by name key, from us and from RCTab.
+        let mut votes: BTreeMap<String, (String, u32, f64)> = BTreeMap::new();
+        for allocation in &round.allocations {
+            if let Some(id) = allocation.allocatee.candidate_id() {
+                let name = &candidates[id.0 as usize].name;
+                votes
+                    .entry(matching.key(name))
+                    .or_insert((name.clone(), 0, 0.0))
+                    .1 += allocation.votes;
+            }
+        }
+        for (name, value) in &rctab_round.tally {
+            votes
+                .entry(matching.key(name))
+                .or_insert((name.clone(), 0, 0.0))
+                .2 += number(value);
+        }
+
+        let allocations: Vec<AllocationDifference> = votes
+            .into_values()
+            .filter(|(_, ours, rctab)| (*ours as f64 - rctab).abs() > 1e-9)
+            .map(|(candidate, votes, rctab_votes)| AllocationDifference {
+                candidate,
+                votes,
+                rctab_votes,
+            })
+            .collect();
+        if !allocations.is_empty() {
+            round_differences.push(RoundDifference {
+                round: i as u32 + 1,
+                allocations,
+            });
+        }
+    }
+
+    let winner = winner(&rounds).map(|id| candidates[id.0 as usize].name.clone());
+    let rctab_winner = summary
+        .results
+        .iter()
+        .flat_map(|round| &round.tally_results)
+        .find_map(|result| result.elected.clone());
+    let same_winner = winner.as_deref().map(|w| matching.key(w))
+        == rctab_winner.as_deref().map(|w| matching.key(w));
+    // Only RCTab configured like this pipeline counts until two remain.
+    let same_rounds =
+        !config.rules.continue_until_two_candidates_remain || rounds.len() == summary.results.len();
+
+    RctabComparison {
+        matches: round_differences.is_empty() && same_winner && same_rounds,
+        winner,
+        rctab_winner,
+        rounds: rounds.len() as u32,
+        rctab_rounds: summary.results.len() as u32,
+        option_mismatches: option_mismatches(&config.rules, provenance),
+        round_differences,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::{CandidateId, CandidateType};
+
+    fn ballots(rankings: &[(&[u32], usize)]) -> Vec<NormalizedBallot> {
+        rankings
+            .iter()
+            .flat_map(|&(ranking, count)| std::iter::repeat_n(ranking, count))
+            .enumerate()
+            .map(|(i, ranking)| {
+                let choices = ranking.iter().map(|&c| CandidateId(c)).collect();
+                NormalizedBallot::new(i.to_string(), choices, false)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compare_with_rctab() {
+        let candidates: Vec<Candidate> = vec!["Alice", "Bob", "Carol"]
+            .into_iter()
+            .map(|name| Candidate::new(name.to_string(), CandidateType::Regular))
+            .collect();
+        let ballots = ballots(&[(&[0], 4), (&[1], 3), (&[2, 1], 2)]);
+        let config: RctabConfig = serde_json::from_str(
+            r#"{"rules": {"winnerElectionMode": "singleWinnerMajority",
+                "overvoteRule": "alwaysSkipToNextRank", "maxSkippedRanksAllowed": "unlimited"}}"#,
+        )
+        .unwrap();
+        let summary = |bob_votes: &str| -> RctabSummary {
+            serde_json::from_str(&format!(
+                r#"{{"results": [
+                    {{"round": 1, "tally": {{"Alice": "4", "Bob": "3", "Carol": "2"}},
+                      "tallyResults": [{{"eliminated": "Carol", "transfers": {{"Bob": "2"}}}}]}},
+                    {{"round": 2, "tally": {{"Alice": "4", "Bob ": "{}"}},
+                      "tallyResults": [{{"elected": "Bob", "transfers": {{}}}}]}}
+                ]}}"#,
+                bob_votes
+            ))
+            .unwrap()
+        };
+
+        let comparison = compare_with_rctab(&candidates, &ballots, None, &config, &summary("5"));
+        assert!(comparison.matches);
+        assert_eq!(Some("Bob".to_string()), comparison.winner);
+        assert_eq!(2, comparison.rctab_rounds);
+        assert_eq!(
+            vec![OptionMismatch {
+                option: "overvoteRule".to_string(),
+                rctab: "alwaysSkipToNextRank".to_string(),
+                ours: "exhaustImmediately".to_string(),
+            }],
+            comparison.option_mismatches
+        );
+
+        let comparison = compare_with_rctab(&candidates, &ballots, None, &config, &summary("4.5"));
+        assert!(!comparison.matches);
+        assert_eq!(
+            vec![RoundDifference {
+                round: 2,
+                allocations: vec![AllocationDifference {
+                    candidate: "Bob".to_string(),
+                    votes: 5,
+                    rctab_votes: 4.5,
+                }],
+            }],
+            comparison.round_differences
+        );
+    }
+}
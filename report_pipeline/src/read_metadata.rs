@@ -1,4 +1,5 @@
 use crate::model::metadata::Jurisdiction;
+use crate::rules::apply_rule_set;
 use crate::util::{get_files_from_path, read_serialized};
 use std::path::{Path, PathBuf};
 
@@ -9,7 +10,11 @@ pub fn read_meta(path: &Path) -> impl Iterator<Item = (PathBuf, Jurisdiction)> {
 
     files.into_iter().map(|file| {
         crate::log_debug!("File: {}", file.to_string_lossy());
-        let ec = read_serialized(&file);
+        let mut ec: Jurisdiction = read_serialized(&file);
+        for (election_path, election) in &mut ec.elections {
+            apply_rule_set(election)
+                .unwrap_or_else(|e| panic!("{} in {}: {}", election_path, file.display(), e));
+        }
         (file, ec)
     })
 }
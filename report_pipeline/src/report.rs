@@ -1,11 +1,25 @@
+use crate::audit::generate_audit_sample_sizes;
+use crate::exhausted_ballots::generate_exhausted_ballots;
 use crate::formats::read_election;
+use crate::log_warn;
+use crate::margin_of_victory::generate_margin_of_victory;
 use crate::model::election::{
-    CandidateId, CandidateType, Election, ElectionInfo, ElectionPreprocessed, NormalizedBallot,
+    Candidate, CandidateId, CandidateType, Election, ElectionInfo, ElectionPreprocessed,
+    FirstRoundAdjustments, NormalizedBallot,
 };
-use crate::model::metadata::{Contest, ElectionMetadata, Jurisdiction};
-use crate::model::report::{CandidatePairEntry, CandidatePairTable, CandidateVotes, ContestReport, RankingDistribution};
-use crate::normalizers::normalize_election;
-use crate::tabulator::{tabulate, Allocatee, TabulatorRound};
+use crate::model::metadata::{region_for, Contest, ElectionMetadata, Jurisdiction};
+use crate::model::report::{
+    BallotPattern, CandidatePairEntry, CandidatePairTable, CandidateVotes, ContestReport,
+    CountingGroupBreakdown, FinalTwo, Margins, NormalizationRule, OthersBucket,
+    RankingDistribution, Reconciliation, ReconciliationRow, RegionBreakdown, RoundSnapshot,
+    TabulationStatus,
+};
+use crate::normalizers::{normalize_election, remove_duplicate_ballots};
+use crate::plurality::{generate_plurality_tally, plurality_round};
+use crate::tabulator::{
+    tabulate, Allocatee, ExactRatio, Shares, TabulatorAllocation, TabulatorRound,
+};
+use std::cmp::Reverse;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::Path;
 
@@ -58,6 +72,21 @@ pub fn total_votes(rounds: &[TabulatorRound]) -> Vec<CandidateVotes> {
     result
 }
 
+/// Each candidate's votes in every round, with `None` for rounds they are
+/// not in.
+pub fn candidate_series(rounds: &[TabulatorRound]) -> BTreeMap<CandidateId, Vec<Option<u32>>> {
+    let mut series: BTreeMap<CandidateId, Vec<Option<u32>>> = BTreeMap::new();
+    for (i, round) in rounds.iter().enumerate() {
+        for allocation in &round.allocations {
+            if let Allocatee::Candidate(c) = allocation.allocatee {
+                series.entry(c).or_insert_with(|| vec![None; rounds.len()])[i] =
+                    Some(allocation.votes);
+            }
+        }
+    }
+    series
+}
+
 pub fn generate_pairwise_counts(
     candidates: &[CandidateId],
     ballots: &[NormalizedBallot],
@@ -175,6 +204,348 @@ pub fn generate_first_alternate(
     }
 }
 
+/// Candidates still in the running in the last round of tabulation.
+pub fn final_round_candidates(rounds: &[TabulatorRound]) -> HashSet<CandidateId> {
+    rounds
+        .last()
+        .map(|round| {
+            round
+                .allocations
+                .iter()
+                .flat_map(|a| a.allocatee.candidate_id())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Group the candidates of a report whose first-round share of votes is
+/// below `threshold` into an "Others" bucket for display. Candidates in
+/// the final round of a count with eliminations, and winners, are never
+/// grouped, and there is no bucket unless it groups at least two
+/// candidates.
+pub fn group_others(report: &ContestReport, threshold: f64) -> Option<OthersBucket> {
+    let first_round = report.rounds.first()?;
+    let mut kept: HashSet<CandidateId> = HashSet::new();
+    if report.rounds.len() > 1 {
+        kept = final_round_candidates(&report.rounds);
+    }
+    kept.extend(report.winner);
+    if let Some(tally) = &report.plurality {
+        kept.extend(tally.winners.iter().chain(&tally.tied));
+    }
+
+    let total = first_round.continuing_ballots as f64;
+    let mut candidates: Vec<CandidateId> = candidate_votes(first_round)
+        .into_iter()
+        .filter(|(c, votes)| !kept.contains(c) && (*votes as f64) < threshold * total)
+        .map(|(c, _)| c)
+        .collect();
+    if candidates.len() < 2 {
+        return None;
+    }
+    candidates.sort();
+
+    let votes = report
+        .rounds
+        .iter()
+        .map(|round| {
+            candidate_votes(round)
+                .into_iter()
+                .filter(|(c, _)| candidates.contains(c))
+                .map(|(_, votes)| votes)
+                .sum()
+        })
+        .collect();
+    Some(OthersBucket {
+        threshold,
+        candidates,
+        votes,
+    })
+}
+
+/// Whether a round can be merged with the one before it for display: it
+/// follows the elimination of a single candidate, and the two leading
+/// candidates are the same, in the same order, as before it.
+fn is_trivial_round(previous: &TabulatorRound, round: &TabulatorRound) -> bool {
+    let leaders = |round: &TabulatorRound| -> Vec<CandidateId> {
+        candidate_votes(round)
+            .into_iter()
+            .take(2)
+            .map(|(c, _)| c)
+            .collect()
+    };
+    let mut eliminated = round.transfers.iter().map(|t| t.from);
+    let single = eliminated
+        .next()
+        .is_some_and(|first| eliminated.all(|c| c == first));
+    single && leaders(previous) == leaders(round)
+}
+
+/// Condense a long tabulation for display by merging each run of
+/// consecutive trivial rounds (see `is_trivial_round`) into one step, with
+/// the totals of the run's last round and the transfers of all of its
+/// rounds. The first and final rounds are always shown. The full sequence
+/// is kept in `expanded_rounds`. Reports with nothing to merge are left as
+/// they are.
+pub fn condense_rounds(report: &mut ContestReport) {
+    let rounds = report.full_rounds();
+    let last = rounds.len().saturating_sub(1);
+    // The first and last expanded round of each condensed round.
+    let mut steps: Vec<(usize, usize)> = Vec::new();
+    let mut previous_trivial = false;
+    for i in 0..rounds.len() {
+        let trivial = i > 0 && i < last && is_trivial_round(&rounds[i - 1], &rounds[i]);
+        match steps.last_mut() {
+            Some((_, end)) if trivial && previous_trivial => *end = i,
+            _ => steps.push((i, i)),
+        }
+        previous_trivial = trivial;
+    }
+    if steps.len() == rounds.len() {
+        return;
+    }
+
+    let condensed: Vec<TabulatorRound> = steps
+        .iter()
+        .map(|&(first, end)| {
+            let mut round = rounds[end].clone();
+            round.transfers = rounds[first..=end]
+                .iter()
+                .flat_map(|r| r.transfers.iter().cloned())
+                .collect();
+            round
+        })
+        .collect();
+    report.expanded_round_numbers = Some(steps.iter().map(|&(_, end)| end as u32 + 1).collect());
+    if report.expanded_rounds.is_none() {
+        report.expanded_rounds = Some(std::mem::take(&mut report.rounds));
+    }
+    report.rounds = condensed;
+    report.candidate_series = candidate_series(report.full_rounds());
+}
+
+/// Candidate vote totals of a round, in descending order of votes.
+fn candidate_votes(round: &TabulatorRound) -> Vec<(CandidateId, u32)> {
+    let mut votes: Vec<(CandidateId, u32)> = round
+        .allocations
+        .iter()
+        .flat_map(|a| Some((a.allocatee.candidate_id()?, a.votes)))
+        .collect();
+    // The tabulator already sorts allocations, so ties keep its order.
+    votes.sort_by_key(|&(_, v)| Reverse(v));
+    votes
+}
+
+/// Compute the margins between the top two candidates in the first and
+/// final rounds, and if `exact`, their fractions in exact arithmetic.
+/// Returns `None` if either round has fewer than two candidates.
+pub fn generate_margins(rounds: &[TabulatorRound], exact: bool) -> Option<Margins> {
+    let first = candidate_votes(rounds.first()?);
+    let last = candidate_votes(rounds.last()?);
+    if first.len() < 2 || last.len() < 2 {
+        return None;
+    }
+
+    let fraction = |margin: u32, votes: &[(CandidateId, u32)]| {
+        let continuing: u32 = votes.iter().map(|(_, v)| v).sum();
+        if continuing == 0 {
+            0.0
+        } else {
+            margin as f32 / continuing as f32
+        }
+    };
+    let first_round_margin = first[0].1 - first[1].1;
+    let final_margin = last[0].1 - last[1].1;
+    let threshold = last.iter().map(|(_, v)| v).sum::<u32>() / 2 + 1;
+    let exact_fraction = |margin: u32, votes: &[(CandidateId, u32)]| {
+        let continuing: u32 = votes.iter().map(|(_, v)| v).sum();
+        Some(ExactRatio::new(margin as u64, continuing as u64)).filter(|_| exact)
+    };
+
+    Some(Margins {
+        first_round_leader: first[0].0,
+        first_round_margin,
+        first_round_margin_fraction: fraction(first_round_margin, &first),
+        final_margin,
+        final_margin_fraction: fraction(final_margin, &last),
+        threshold,
+        margin_over_threshold: last[0].1 as i64 - threshold as i64,
+        first_round_margin_exact: exact_fraction(first_round_margin, &first),
+        final_margin_exact: exact_fraction(final_margin, &last),
+    })
+}
+
+/// Decimal places of the percentages in reports, unless the jurisdiction
+/// sets its own.
+pub const DEFAULT_PERCENT_DECIMALS: u32 = 1;
+
+/// Format `part` as a percentage of `whole`, e.g. "45.3%".
+fn format_percent(part: u32, whole: u32, decimals: u32) -> String {
+    let percent = if whole == 0 {
+        0.0
+    } else {
+        100.0 * part as f64 / whole as f64
+    };
+    format!("{:.*}%", decimals as usize, percent)
+}
+
+/// Fill in the formatted shares of each round's allocations and transfers.
+/// Transfers are into the round they are listed in, so they are shares of
+/// that round's ballots. Returns whether any share changed.
+pub fn format_shares(rounds: &mut [TabulatorRound], decimals: u32) -> bool {
+    let mut changed = false;
+    let mut update = |shares: &mut Option<Shares>, new: Shares| {
+        if shares.as_ref() != Some(&new) {
+            *shares = Some(new);
+            changed = true;
+        }
+    };
+
+    for round in rounds {
+        let continuing = round.continuing_ballots;
+        let total: u32 = round.allocations.iter().map(|a| a.votes).sum();
+        for allocation in &mut round.allocations {
+            let of_continuing = match allocation.allocatee {
+                Allocatee::Candidate(_) => {
+                    Some(format_percent(allocation.votes, continuing, decimals))
+                }
+                Allocatee::Exhausted => None,
+            };
+            let of_total = format_percent(allocation.votes, total, decimals);
+            update(
+                &mut allocation.shares,
+                Shares {
+                    of_continuing,
+                    of_total,
+                },
+            );
+        }
+        for transfer in &mut round.transfers {
+            let shares = Shares {
+                of_continuing: Some(format_percent(transfer.count, continuing, decimals)),
+                of_total: format_percent(transfer.count, total, decimals),
+            };
+            update(&mut transfer.shares, shares);
+        }
+    }
+    changed
+}
+
+/// Compare first-round votes to official totals, keyed by candidate name
+/// or display name. Each row lists the normalization rules that moved
+/// votes in the direction of its delta, per the first-round adjustments.
+pub fn reconcile_first_round(
+    candidates: &[Candidate],
+    rounds: &[TabulatorRound],
+    adjustments: Option<&FirstRoundAdjustments>,
+    official: &BTreeMap<String, u32>,
+) -> Reconciliation {
+    let votes: HashMap<CandidateId, u32> = rounds
+        .first()
+        .map(|round| candidate_votes(round).into_iter().collect())
+        .unwrap_or_default();
+    let adjustment = |candidate: CandidateId| {
+        adjustments
+            .and_then(|a| a.candidates.iter().find(|c| c.candidate == candidate))
+            .map(|c| (c.skipped_ranks, c.exhausted))
+            .unwrap_or_default()
+    };
+
+    let mut rows = Vec::new();
+    let mut matched: HashSet<&str> = HashSet::new();
+    for (i, candidate) in candidates.iter().enumerate() {
+        let id = CandidateId(i as u32);
+        let votes = votes.get(&id).copied().unwrap_or(0);
+        let (name, official_votes) = match official
+            .get_key_value(candidate.display_name())
+            .or_else(|| official.get_key_value(&candidate.name))
+        {
+            Some((name, official_votes)) => (name.as_str(), Some(*official_votes)),
+            None => (candidate.display_name(), None),
+        };
+        if official_votes.is_none() && votes == 0 {
+            continue;
+        }
+        matched.insert(name);
+
+        let delta = votes as i64 - official_votes.unwrap_or(0) as i64;
+        let (skipped_ranks, exhausted) = adjustment(id);
+        let mut rules = Vec::new();
+        if delta > 0 && skipped_ranks > 0 {
+            rules.push(NormalizationRule::SkippedRanks);
+        }
+        if delta < 0 && exhausted > 0 {
+            rules.push(NormalizationRule::ExhaustedBallots);
+        }
+        rows.push(ReconciliationRow {
+            name: name.to_string(),
+            candidate: Some(id),
+            votes,
+            official_votes,
+            delta,
+            rules,
+        });
+    }
+
+    for (name, official_votes) in official {
+        if !matched.contains(name.as_str()) {
+            rows.push(ReconciliationRow {
+                name: name.clone(),
+                candidate: None,
+                votes: 0,
+                official_votes: Some(*official_votes),
+                delta: -(*official_votes as i64),
+                rules: Vec::new(),
+            });
+        }
+    }
+
+    Reconciliation {
+        total_discrepancy: rows.iter().map(|row| row.delta.unsigned_abs() as u32).sum(),
+        rows,
+    }
+}
+
+/// Count, over all ballots, which of the two final-round candidates each
+/// ballot prefers. Returns `None` unless exactly two candidates remain in
+/// the final round.
+pub fn generate_final_two(
+    ballots: &[NormalizedBallot],
+    rounds: &[TabulatorRound],
+) -> Option<FinalTwo> {
+    let finalists: Vec<CandidateId> = rounds
+        .last()?
+        .allocations
+        .iter()
+        .flat_map(|a| a.allocatee.candidate_id())
+        .collect();
+    let (winner, runner_up) = match finalists.as_slice() {
+        [winner, runner_up] => (*winner, *runner_up),
+        _ => return None,
+    };
+
+    let mut final_two = FinalTwo {
+        winner,
+        runner_up,
+        winner_votes: 0,
+        runner_up_votes: 0,
+        neither: 0,
+    };
+    for ballot in ballots {
+        match ballot
+            .choices()
+            .into_iter()
+            .find(|c| *c == winner || *c == runner_up)
+        {
+            Some(c) if c == winner => final_two.winner_votes += 1,
+            Some(_) => final_two.runner_up_votes += 1,
+            None => final_two.neither += 1,
+        }
+    }
+
+    Some(final_two)
+}
+
 pub fn generate_first_final(
     candidates: &[CandidateId],
     ballots: &[NormalizedBallot],
@@ -239,6 +610,147 @@ pub fn generate_first_final(
     }
 }
 
+/// First-choice and final-round support among the ballots of one group.
+struct GroupBreakdown {
+    group: String,
+    ballot_count: u32,
+    first_choice: Vec<TabulatorAllocation>,
+    final_round: Vec<TabulatorAllocation>,
+}
+
+/// Break down first-choice and final-round support by a group that each
+/// ballot may belong to. A ballot counts toward its highest-ranked candidate
+/// in the final round, or as exhausted if it ranks none of them. Returns
+/// `None` if no ballot belongs to a group.
+fn generate_breakdown<'a>(
+    candidates: &[CandidateId],
+    ballots: &'a [NormalizedBallot],
+    final_round_candidates: &HashSet<CandidateId>,
+    group_of: impl Fn(&'a NormalizedBallot) -> Option<&'a str>,
+) -> Option<Vec<GroupBreakdown>> {
+    let mut first_choice: BTreeMap<&str, HashMap<Allocatee, u32>> = BTreeMap::new();
+    let mut final_round: BTreeMap<&str, HashMap<Allocatee, u32>> = BTreeMap::new();
+    let mut ballot_count: BTreeMap<&str, u32> = BTreeMap::new();
+
+    for ballot in ballots {
+        let group = match group_of(ballot) {
+            Some(group) => group,
+            None => continue,
+        };
+        let choices = ballot.choices();
+
+        let first = match choices.first() {
+            Some(v) => Allocatee::Candidate(*v),
+            None => Allocatee::Exhausted,
+        };
+        let last = match choices.iter().find(|x| final_round_candidates.contains(x)) {
+            Some(v) => Allocatee::Candidate(*v),
+            None => Allocatee::Exhausted,
+        };
+
+        *first_choice
+            .entry(group)
+            .or_default()
+            .entry(first)
+            .or_insert(0) += 1;
+        *final_round
+            .entry(group)
+            .or_default()
+            .entry(last)
+            .or_insert(0) += 1;
+        *ballot_count.entry(group).or_insert(0) += 1;
+    }
+
+    if ballot_count.is_empty() {
+        return None;
+    }
+
+    let allocations = |counts: &HashMap<Allocatee, u32>, allocatees: &[Allocatee]| {
+        allocatees
+            .iter()
+            .map(|a| TabulatorAllocation {
+                allocatee: *a,
+                votes: *counts.get(a).unwrap_or(&0),
+                shares: None,
+            })
+            .collect()
+    };
+
+    let mut first_allocatees: Vec<Allocatee> = candidates
+        .iter()
+        .map(|c| Allocatee::Candidate(*c))
+        .collect();
+    first_allocatees.push(Allocatee::Exhausted);
+
+    let mut final_allocatees: Vec<Allocatee> = candidates
+        .iter()
+        .filter(|c| final_round_candidates.contains(c))
+        .map(|c| Allocatee::Candidate(*c))
+        .collect();
+    final_allocatees.push(Allocatee::Exhausted);
+
+    Some(
+        ballot_count
+            .into_iter()
+            .map(|(group, count)| GroupBreakdown {
+                group: group.to_string(),
+                ballot_count: count,
+                first_choice: allocations(&first_choice[group], &first_allocatees),
+                final_round: allocations(&final_round[group], &final_allocatees),
+            })
+            .collect(),
+    )
+}
+
+/// Break down first-choice and final-round support by counting group (vote
+/// method). Returns `None` if no ballot records a counting group.
+pub fn generate_counting_group_breakdown(
+    candidates: &[CandidateId],
+    ballots: &[NormalizedBallot],
+    final_round_candidates: &HashSet<CandidateId>,
+) -> Option<Vec<CountingGroupBreakdown>> {
+    let breakdown = generate_breakdown(candidates, ballots, final_round_candidates, |b| {
+        b.counting_group.as_deref()
+    })?;
+    Some(
+        breakdown
+            .into_iter()
+            .map(|group| CountingGroupBreakdown {
+                counting_group: group.group,
+                ballot_count: group.ballot_count,
+                first_choice: group.first_choice,
+                final_round: group.final_round,
+            })
+            .collect(),
+    )
+}
+
+/// Break down first-choice and final-round support by region (e.g. borough
+/// or county), looking up the region of each ballot's precinct in an
+/// election's `regions`. Ballots in no region are left out. Returns `None`
+/// if no ballot is in a region.
+pub fn generate_region_breakdown(
+    candidates: &[CandidateId],
+    ballots: &[NormalizedBallot],
+    final_round_candidates: &HashSet<CandidateId>,
+    regions: &BTreeMap<String, String>,
+) -> Option<Vec<RegionBreakdown>> {
+    let breakdown = generate_breakdown(candidates, ballots, final_round_candidates, |b| {
+        region_for(regions, b.precinct.as_deref()?)
+    })?;
+    Some(
+        breakdown
+            .into_iter()
+            .map(|group| RegionBreakdown {
+                region: group.group,
+                ballot_count: group.ballot_count,
+                first_choice: group.first_choice,
+                final_round: group.final_round,
+            })
+            .collect(),
+    )
+}
+
 /// Generate ranking distribution statistics from normalized ballots.
 /// This function is format-agnostic and works with all CVR formats since
 /// all formats normalize to NormalizedBallot before report generation.
@@ -282,6 +794,56 @@ pub fn generate_ranking_distribution(
     }
 }
 
+/// Aggregate ballots at each round by their remaining ranking of the
+/// candidates not yet eliminated. Candidates are eliminated in the round
+/// whose transfers are from them.
+pub fn generate_round_snapshots(
+    candidates: &[CandidateId],
+    ballots: &[NormalizedBallot],
+    rounds: &[TabulatorRound],
+) -> Vec<RoundSnapshot> {
+    let mut eliminated: HashSet<CandidateId> = HashSet::new();
+
+    rounds
+        .iter()
+        .enumerate()
+        .map(|(i, round)| {
+            eliminated.extend(round.transfers.iter().map(|t| t.from));
+            let continuing: HashSet<CandidateId> = candidates
+                .iter()
+                .filter(|c| !eliminated.contains(c))
+                .copied()
+                .collect();
+
+            let mut counts: BTreeMap<(Vec<CandidateId>, bool), u32> = BTreeMap::new();
+            for ballot in ballots {
+                let ranking: Vec<CandidateId> = ballot
+                    .choices()
+                    .into_iter()
+                    .filter(|c| continuing.contains(c))
+                    .collect();
+                *counts.entry((ranking, ballot.overvoted)).or_insert(0) += 1;
+            }
+
+            let mut patterns: Vec<BallotPattern> = counts
+                .into_iter()
+                .map(|((ranking, overvoted), count)| BallotPattern {
+                    ranking,
+                    overvoted,
+                    count,
+                })
+                .collect();
+            // Stable sort, so ties stay in ranking order.
+            patterns.sort_by_key(|p| Reverse(p.count));
+
+            RoundSnapshot {
+                round: i as u32 + 1,
+                patterns,
+            }
+        })
+        .collect()
+}
+
 pub fn graph(
     candidates: &[CandidateId],
     preference_map: &HashMap<(CandidateId, CandidateId), u32>,
@@ -324,49 +886,34 @@ pub fn smith_set(
     last_set
 }
 
+/// Whether a contest with `ballots` ballots, `num_candidates` qualified
+/// (non-write-in) candidates and the given rounds could be tabulated
+/// normally.
+pub fn contest_status(
+    ballots: usize,
+    num_candidates: u32,
+    rounds: &[TabulatorRound],
+) -> TabulationStatus {
+    let candidates = rounds
+        .first()
+        .map_or(0, |round| candidate_votes(round).len());
+    match (ballots, candidates, num_candidates) {
+        (0, _, _) => TabulationStatus::NoBallots,
+        (_, 0, _) => TabulationStatus::NoVotes,
+        (_, _, 0) => TabulationStatus::WriteInOnly,
+        (_, _, 1) => TabulationStatus::Unopposed,
+        (_, 1, _) => TabulationStatus::Uncontested,
+        _ => TabulationStatus::Tabulated,
+    }
+}
+
 /// Generate a `ContestReport` from preprocessed election data.
 pub fn generate_report(election: &ElectionPreprocessed) -> ContestReport {
-    let ballots = &election.ballots.ballots;
-
-    // Handle empty elections
-    if ballots.is_empty() {
-        return ContestReport {
-            info: election.info.clone(),
-            ballot_count: 0,
-            candidates: election.ballots.candidates.clone(),
-            winner: None,
-            num_candidates: 0,
-            rounds: vec![],
-            total_votes: vec![],
-            pairwise_preferences: CandidatePairTable {
-                entries: vec![],
-                rows: vec![],
-                cols: vec![],
-            },
-            first_alternate: CandidatePairTable {
-                entries: vec![],
-                rows: vec![],
-                cols: vec![],
-            },
-            first_final: CandidatePairTable {
-                entries: vec![],
-                rows: vec![],
-                cols: vec![],
-            },
-            ranking_distribution: Some(RankingDistribution {
-                overall_distribution: BTreeMap::new(),
-                candidate_distributions: BTreeMap::new(),
-                total_ballots: 0,
-                candidate_totals: BTreeMap::new(),
-            }),
-            smith_set: vec![],
-            condorcet: None,
-        };
+    if let Some(vote_for) = election.info.vote_for {
+        return generate_plurality_report(election, vote_for);
     }
+    let ballots = &election.ballots.ballots;
 
-    crate::log_debug!("  - Tabulating rounds...");
-    let rounds = tabulate(ballots, &election.info.tabulation_options);
-    let winner = winner(&rounds);
     let num_candidates = election
         .ballots
         .candidates
@@ -374,6 +921,16 @@ pub fn generate_report(election: &ElectionPreprocessed) -> ContestReport {
         .filter(|d| d.candidate_type != CandidateType::WriteIn)
         .count() as u32;
 
+    crate::log_debug!("  - Tabulating rounds...");
+    let mut rounds = tabulate(ballots, &election.info.tabulation_options);
+    let status = contest_status(ballots.len(), num_candidates, &rounds);
+    if status.is_unopposed() {
+        // Only the totals are of interest, so the leader of the first round
+        // wins without eliminations.
+        rounds.truncate(1);
+    }
+    let winner = winner(&rounds);
+
     crate::log_debug!("  - Calculating total votes...");
     let total_votes = total_votes(&rounds);
     let mut candidates: Vec<CandidateId> = total_votes.iter().map(|d| d.candidate).collect();
@@ -407,20 +964,37 @@ pub fn generate_report(election: &ElectionPreprocessed) -> ContestReport {
     let first_alternate = generate_first_alternate(&candidates, ballots);
 
     crate::log_debug!("  - Determining final round candidates...");
-    let final_round_candidates: HashSet<CandidateId> = rounds
-        .last()
-        .map(|round| {
-            round
-                .allocations
-                .iter()
-                .flat_map(|a| a.allocatee.candidate_id())
-                .collect()
-        })
-        .unwrap_or_default();
+    let final_round_candidates = final_round_candidates(&rounds);
 
     crate::log_debug!("  - Generating first-final matrix...");
     let first_final = generate_first_final(&candidates, ballots, &final_round_candidates);
 
+    crate::log_debug!("  - Computing margins...");
+    let margins = generate_margins(
+        &rounds,
+        election
+            .info
+            .tabulation_options
+            .exact_thresholds
+            .unwrap_or(false),
+    );
+
+    crate::log_debug!("  - Computing margin of victory...");
+    let margin_of_victory = generate_margin_of_victory(&rounds);
+
+    crate::log_debug!("  - Classifying exhausted ballots...");
+    let exhausted_ballots = generate_exhausted_ballots(&election.ballots, &rounds);
+
+    crate::log_debug!("  - Computing audit sample sizes...");
+    let audit_sample_sizes = generate_audit_sample_sizes(ballots.len() as u32, &rounds);
+
+    crate::log_debug!("  - Generating final two head-to-head...");
+    let final_two = generate_final_two(ballots, &rounds);
+
+    crate::log_debug!("  - Generating counting group breakdown...");
+    let counting_groups =
+        generate_counting_group_breakdown(&candidates, ballots, &final_round_candidates);
+
     crate::log_debug!("  - Generating ranking distribution...");
     let ranking_distribution = generate_ranking_distribution(&candidates, ballots);
 
@@ -437,8 +1011,14 @@ pub fn generate_report(election: &ElectionPreprocessed) -> ContestReport {
         info: election.info.clone(),
         ballot_count: election.ballots.ballots.len() as u32,
         candidates: election.ballots.candidates.clone(),
+        status,
+        plurality: None,
         winner,
         num_candidates,
+        candidate_series: candidate_series(&rounds),
+        others: None,
+        expanded_rounds: None,
+        expanded_round_numbers: None,
         rounds,
         total_votes: sorted_total_votes,
         pairwise_preferences,
@@ -447,6 +1027,110 @@ pub fn generate_report(election: &ElectionPreprocessed) -> ContestReport {
         ranking_distribution: Some(ranking_distribution),
         smith_set: sorted_smith_set,
         condorcet,
+        adjudicated_ballots: election.ballots.adjudicated_ballots,
+        adjudication: election.ballots.adjudication.clone(),
+        duplicate_ballots: election.ballots.duplicate_ballots.clone(),
+        candidate_reconciliation: election.ballots.candidate_reconciliation.clone(),
+        counting_groups,
+        regions: None,
+        rank_positions: election.ballots.rank_positions.clone(),
+        duplicate_rankings: election.ballots.duplicate_rankings.clone(),
+        first_round_adjustments: election.ballots.first_round_adjustments.clone(),
+        overvote_handling: election.ballots.overvote_handling.clone(),
+        skipped_rank_handling: election.ballots.skipped_rank_handling.clone(),
+        reconciliation: None,
+        final_two,
+        margins,
+        margin_of_victory,
+        exhausted_ballots,
+        elimination_robustness: None,
+        hypothetical_methods: None,
+        strategy_susceptibility: None,
+        audit_sample_sizes,
+        precinct_map: None,
+        round_snapshots: None,
+        ranking_patterns: None,
+        provenance: election.provenance.clone(),
+        reporting_status: None,
+        variant: None,
+        subset: None,
+        variants: Vec::new(),
+        subsets: Vec::new(),
+    }
+}
+
+/// Generate a `ContestReport` for a non-ranked contest, from its plurality
+/// tally. Its only round is the tally's totals, and the analyses of
+/// rankings are left out.
+fn generate_plurality_report(election: &ElectionPreprocessed, vote_for: u32) -> ContestReport {
+    let ballots = &election.ballots.ballots;
+    let num_candidates = election
+        .ballots
+        .candidates
+        .iter()
+        .filter(|d| d.candidate_type != CandidateType::WriteIn)
+        .count() as u32;
+
+    crate::log_debug!("  - Tallying votes...");
+    let tally = generate_plurality_tally(ballots, vote_for);
+    let rounds = vec![plurality_round(&tally)];
+    let status = contest_status(ballots.len(), num_candidates, &rounds);
+    let winner = match tally.winners.as_slice() {
+        [winner] if vote_for == 1 => Some(*winner),
+        _ => None,
+    };
+    let mut total_votes = total_votes(&rounds);
+    total_votes.sort_by_key(|v| v.candidate);
+
+    ContestReport {
+        info: election.info.clone(),
+        ballot_count: ballots.len() as u32,
+        candidates: election.ballots.candidates.clone(),
+        status,
+        plurality: Some(tally),
+        winner,
+        num_candidates,
+        candidate_series: candidate_series(&rounds),
+        others: None,
+        expanded_rounds: None,
+        expanded_round_numbers: None,
+        rounds,
+        total_votes,
+        pairwise_preferences: CandidatePairTable::default(),
+        first_alternate: CandidatePairTable::default(),
+        first_final: CandidatePairTable::default(),
+        ranking_distribution: None,
+        smith_set: Vec::new(),
+        condorcet: None,
+        adjudicated_ballots: election.ballots.adjudicated_ballots,
+        adjudication: election.ballots.adjudication.clone(),
+        duplicate_ballots: election.ballots.duplicate_ballots.clone(),
+        candidate_reconciliation: election.ballots.candidate_reconciliation.clone(),
+        counting_groups: None,
+        regions: None,
+        rank_positions: None,
+        duplicate_rankings: None,
+        first_round_adjustments: None,
+        overvote_handling: None,
+        skipped_rank_handling: None,
+        reconciliation: None,
+        final_two: None,
+        margins: None,
+        margin_of_victory: None,
+        exhausted_ballots: None,
+        elimination_robustness: None,
+        hypothetical_methods: None,
+        strategy_susceptibility: None,
+        audit_sample_sizes: None,
+        precinct_map: None,
+        round_snapshots: None,
+        ranking_patterns: None,
+        provenance: election.provenance.clone(),
+        reporting_status: None,
+        variant: None,
+        subset: None,
+        variants: Vec::new(),
+        subsets: Vec::new(),
     }
 }
 
@@ -461,42 +1145,57 @@ pub fn preprocess_election(
 ) -> ElectionPreprocessed {
     let election = read_election(
         &metadata.data_format,
-        &raw_base.join(&election_path),
-        contest.loader_params.clone().unwrap_or_default(),
+        &raw_base.join(election_path),
+        contest.reader_params(),
     );
-    let office = ec.offices.get(&contest.office).unwrap();
-
-    let normalized_election = normalize_election(&metadata.normalization, election);
-
-    ElectionPreprocessed {
-        info: ElectionInfo {
-            name: office.name.clone(),
-            office: contest.office.clone(),
-            date: metadata.date.clone(),
-            data_format: metadata.data_format.clone(),
-            tabulation_options: metadata.tabulation_options.clone().unwrap_or_default(),
-            loader_params: contest.loader_params.clone(),
-            jurisdiction_path: ec.path.clone(),
-            election_path: election_path.to_string(),
-            jurisdiction_name: ec.name.clone(),
-            office_name: office.name.clone(),
-            election_name: metadata.name.clone(),
-            website: metadata.website.clone(),
-        },
-        ballots: normalized_election,
-    }
+    preprocess_election_from_data(election, metadata, ec, contest, election_path)
 }
 
 /// Preprocess an election from already-loaded election data
 /// This is used for batch processing where elections are loaded once and reused
 pub fn preprocess_election_from_data(
-    election: Election,
+    mut election: Election,
     metadata: &ElectionMetadata,
     jurisdiction: &Jurisdiction,
     contest: &Contest,
     election_path: &str,
 ) -> ElectionPreprocessed {
-    let normalized_election = normalize_election(&metadata.normalization, election);
+    remove_duplicate_ballots(
+        &mut election,
+        metadata.duplicate_ballot_policy.unwrap_or_default(),
+    );
+    if let Some(duplicates) = election
+        .duplicate_ballots
+        .as_ref()
+        .filter(|d| d.duplicate_ids > 0)
+    {
+        log_warn!(
+            "{} ballots for {} in {}/{} repeat an earlier ballot's ID ({} identical, e.g. {}); {} removed",
+            duplicates.duplicate_ids,
+            contest.office,
+            jurisdiction.path,
+            election_path,
+            duplicates.identical_records,
+            duplicates.examples.join(", "),
+            duplicates.removed
+        );
+    }
+    for unknown in &election.unknown_candidates {
+        log_warn!(
+            "Candidate ID {} is marked {} times on ballots for {} in {}/{}, but not in the candidate manifest; its marks count as skipped ranks",
+            unknown.external_id,
+            unknown.marks,
+            contest.office,
+            jurisdiction.path,
+            election_path
+        );
+    }
+    let normalized_election = normalize_election(
+        &metadata.normalization,
+        metadata.overvote_policy.unwrap_or_default(),
+        metadata.skipped_rank_policy.unwrap_or_default(),
+        election,
+    );
     let office = jurisdiction.offices.get(&contest.office).unwrap();
 
     ElectionPreprocessed {
@@ -513,7 +1212,592 @@ pub fn preprocess_election_from_data(
             office_name: office.name.clone(),
             election_name: metadata.name.clone(),
             website: metadata.website.clone(),
+            locale: jurisdiction.locale.clone(),
+            office_name_translations: office.translations.clone(),
+            seats: office.seats,
+            district: office.district.clone(),
+            term_years: office.term_years,
+            notes: contest.notes.clone(),
+            office_id: office.id.clone(),
+            vote_for: contest.vote_for,
         },
         ballots: normalized_election,
+        provenance: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::{CandidateAdjustment, NormalizedElection};
+    use crate::model::metadata::TabulationOptions;
+    use crate::model::report::ContestIndexEntry;
+    use crate::tabulator::Transfer;
+    use crate::util::TempDir;
+
+    fn round(transfers: Vec<Transfer>) -> TabulatorRound {
+        TabulatorRound {
+            allocations: vec![],
+            undervote: 0,
+            overvote: 0,
+            continuing_ballots: 0,
+            transfers,
+            threshold: None,
+        }
+    }
+
+    #[test]
+    fn test_round_snapshots() {
+        let (a, b, c) = (CandidateId(0), CandidateId(1), CandidateId(2));
+        let ballots = vec![
+            NormalizedBallot::new("1".to_string(), vec![a, b], false),
+            NormalizedBallot::new("2".to_string(), vec![c, b], false),
+            NormalizedBallot::new("3".to_string(), vec![c], true),
+            NormalizedBallot::new("4".to_string(), vec![a, b], false),
+        ];
+        let rounds = vec![
+            round(vec![]),
+            round(vec![Transfer {
+                from: c,
+                to: Allocatee::Candidate(b),
+                count: 1,
+                shares: None,
+            }]),
+        ];
+
+        let snapshots = generate_round_snapshots(&[a, b, c], &ballots, &rounds);
+        assert_eq!(2, snapshots.len());
+        assert_eq!(vec![a, b], snapshots[0].patterns[0].ranking);
+        assert_eq!(2, snapshots[0].patterns[0].count);
+
+        let last = &snapshots[1].patterns;
+        assert_eq!(3, last.len());
+        assert_eq!((vec![a, b], 2), (last[0].ranking.clone(), last[0].count));
+        assert!(last[1].ranking.is_empty() && last[1].overvoted);
+        assert_eq!(vec![b], last[2].ranking);
+    }
+
+    #[test]
+    fn test_final_two() {
+        let (a, b, c) = (CandidateId(0), CandidateId(1), CandidateId(2));
+        let ballots = vec![
+            NormalizedBallot::new("1".to_string(), vec![a, b], false),
+            NormalizedBallot::new("2".to_string(), vec![c, b], false),
+            NormalizedBallot::new("3".to_string(), vec![c, a, b], false),
+            NormalizedBallot::new("4".to_string(), vec![c], true),
+            NormalizedBallot::new("5".to_string(), vec![], false),
+        ];
+        let mut last = round(vec![]);
+        last.allocations = vec![
+            TabulatorAllocation {
+                allocatee: Allocatee::Candidate(a),
+                votes: 2,
+                shares: None,
+            },
+            TabulatorAllocation {
+                allocatee: Allocatee::Candidate(b),
+                votes: 1,
+                shares: None,
+            },
+            TabulatorAllocation {
+                allocatee: Allocatee::Exhausted,
+                votes: 2,
+                shares: None,
+            },
+        ];
+
+        assert_eq!(
+            Some(FinalTwo {
+                winner: a,
+                runner_up: b,
+                winner_votes: 2,
+                runner_up_votes: 1,
+                neither: 2,
+            }),
+            generate_final_two(&ballots, &[last])
+        );
+        assert_eq!(None, generate_final_two(&ballots, &[round(vec![])]));
+    }
+
+    #[test]
+    fn test_margins() {
+        let (a, b, c) = (CandidateId(0), CandidateId(1), CandidateId(2));
+        let with_votes = |votes: &[(CandidateId, u32)]| {
+            let mut r = round(vec![]);
+            r.allocations = votes
+                .iter()
+                .map(|(c, v)| TabulatorAllocation {
+                    allocatee: Allocatee::Candidate(*c),
+                    votes: *v,
+                    shares: None,
+                })
+                .chain(std::iter::once(TabulatorAllocation {
+                    allocatee: Allocatee::Exhausted,
+                    votes: 5,
+                    shares: None,
+                }))
+                .collect();
+            r
+        };
+        let rounds = [
+            with_votes(&[(b, 40), (a, 35), (c, 25)]),
+            with_votes(&[(a, 52), (b, 43)]),
+        ];
+
+        let margins = generate_margins(&rounds, true).unwrap();
+        assert_eq!(b, margins.first_round_leader);
+        assert_eq!(5, margins.first_round_margin);
+        assert_eq!(0.05, margins.first_round_margin_fraction);
+        assert_eq!(9, margins.final_margin);
+        assert_eq!(48, margins.threshold);
+        assert_eq!(4, margins.margin_over_threshold);
+        assert_eq!(Some(ExactRatio::new(9, 95)), margins.final_margin_exact);
+        assert_eq!(1, margins.first_round_margin_exact.unwrap().numerator);
+
+        assert_eq!(None, generate_margins(&[with_votes(&[(a, 10)])], false));
+    }
+
+    #[test]
+    fn test_format_shares() {
+        let (a, b) = (CandidateId(0), CandidateId(1));
+        let mut r = round(vec![Transfer {
+            from: CandidateId(2),
+            to: Allocatee::Candidate(a),
+            count: 10,
+            shares: None,
+        }]);
+        r.continuing_ballots = 3;
+        r.allocations = vec![
+            (Allocatee::Candidate(a), 2),
+            (Allocatee::Candidate(b), 1),
+            (Allocatee::Exhausted, 1),
+        ]
+        .into_iter()
+        .map(|(allocatee, votes)| TabulatorAllocation {
+            allocatee,
+            votes,
+            shares: None,
+        })
+        .collect();
+        let mut rounds = vec![r];
+
+        assert!(format_shares(&mut rounds, 1));
+        let shares =
+            |rounds: &[TabulatorRound], i: usize| rounds[0].allocations[i].shares.clone().unwrap();
+        assert_eq!(Some("66.7%".to_string()), shares(&rounds, 0).of_continuing);
+        assert_eq!("50.0%", shares(&rounds, 0).of_total);
+        assert_eq!(None, shares(&rounds, 2).of_continuing);
+        assert_eq!("25.0%", shares(&rounds, 2).of_total);
+        assert!(!format_shares(&mut rounds, 1));
+
+        assert!(format_shares(&mut rounds, 0));
+        assert_eq!(Some("33%".to_string()), shares(&rounds, 1).of_continuing);
+        assert_eq!(
+            "250%",
+            rounds[0].transfers[0].shares.as_ref().unwrap().of_total
+        );
+    }
+
+    #[test]
+    fn test_reconcile_first_round() {
+        let (a, b) = (CandidateId(0), CandidateId(1));
+        let mut first = round(vec![]);
+        first.allocations = vec![
+            TabulatorAllocation {
+                allocatee: Allocatee::Candidate(a),
+                votes: 105,
+                shares: None,
+            },
+            TabulatorAllocation {
+                allocatee: Allocatee::Candidate(b),
+                votes: 90,
+                shares: None,
+            },
+        ];
+        let mut candidates = vec![
+            Candidate::new("ALICE".to_string(), CandidateType::Regular),
+            Candidate::new("Bob".to_string(), CandidateType::Regular),
+        ];
+        candidates[0].display_name = Some("Alice".to_string());
+        let adjustments = FirstRoundAdjustments {
+            overvoted: 3,
+            candidates: vec![
+                CandidateAdjustment {
+                    candidate: a,
+                    skipped_ranks: 5,
+                    exhausted: 0,
+                },
+                CandidateAdjustment {
+                    candidate: b,
+                    skipped_ranks: 2,
+                    exhausted: 4,
+                },
+            ],
+        };
+        let official: BTreeMap<String, u32> = vec![
+            ("Alice".to_string(), 100),
+            ("Bob".to_string(), 94),
+            ("Write-in".to_string(), 2),
+        ]
+        .into_iter()
+        .collect();
+
+        let reconciliation =
+            reconcile_first_round(&candidates, &[first], Some(&adjustments), &official);
+        assert_eq!(11, reconciliation.total_discrepancy);
+        assert_eq!(
+            vec![
+                ("Alice", Some(a), 5, vec![NormalizationRule::SkippedRanks]),
+                (
+                    "Bob",
+                    Some(b),
+                    -4,
+                    vec![NormalizationRule::ExhaustedBallots]
+                ),
+                ("Write-in", None, -2, vec![]),
+            ],
+            reconciliation
+                .rows
+                .iter()
+                .map(|row| (
+                    row.name.as_str(),
+                    row.candidate,
+                    row.delta,
+                    row.rules.clone()
+                ))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    fn preprocessed(ballots: Vec<NormalizedBallot>) -> ElectionPreprocessed {
+        ElectionPreprocessed {
+            info: ElectionInfo {
+                name: "Mayor".to_string(),
+                date: "2024-11-05".to_string(),
+                data_format: "simple_json".to_string(),
+                tabulation_options: TabulationOptions::default(),
+                jurisdiction_path: "us/zz/test".to_string(),
+                election_path: "2024/11".to_string(),
+                office: "mayor".to_string(),
+                office_name: "Mayor".to_string(),
+                jurisdiction_name: "Test".to_string(),
+                election_name: "General Election".to_string(),
+                loader_params: None,
+                website: None,
+                locale: None,
+                office_name_translations: Default::default(),
+                seats: None,
+                district: None,
+                term_years: None,
+                notes: Vec::new(),
+                office_id: None,
+                vote_for: None,
+            },
+            ballots: NormalizedElection {
+                candidates: vec![
+                    Candidate::new("Alice".to_string(), CandidateType::Regular),
+                    Candidate::new("Bob".to_string(), CandidateType::Regular),
+                ],
+                ballots,
+                adjudicated_ballots: None,
+                adjudication: None,
+                duplicate_ballots: None,
+                candidate_reconciliation: None,
+                rank_positions: None,
+                duplicate_rankings: None,
+                first_round_adjustments: None,
+                overvote_handling: None,
+                skipped_rank_handling: None,
+                truncated_ballots: None,
+            },
+            provenance: None,
+        }
+    }
+
+    #[test]
+    fn test_candidate_series() {
+        let (a, b, c) = (CandidateId(0), CandidateId(1), CandidateId(2));
+        let mut ballots = vec![];
+        for (i, choices) in vec![vec![a], vec![a], vec![b], vec![b], vec![c, b]]
+            .into_iter()
+            .enumerate()
+        {
+            ballots.push(NormalizedBallot::new(i.to_string(), choices, false));
+        }
+        let report = generate_report(&preprocessed(ballots));
+        assert_eq!(2, report.rounds.len());
+        assert_eq!(
+            vec![
+                (a, vec![Some(2), Some(2)]),
+                (b, vec![Some(2), Some(3)]),
+                (c, vec![Some(1), None]),
+            ],
+            report.candidate_series.into_iter().collect::<Vec<_>>()
+        );
+    }
+
+    /// Write a NIST SP 1500-103 export of contest 1 to `dir`, with a
+    /// session of each ranking, given as candidate IDs, in each counting
+    /// group.
+    fn write_nist_export(dir: &Path, groups: &[(u32, &str, Vec<Vec<u32>>)]) {
+        use serde_json::json;
+        let candidates: Vec<_> = ["Alice", "Bob", "Carol"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                json!({"Description": name, "Id": i + 1, "ContestId": 1, "Type": "Regular"})
+            })
+            .collect();
+        let counting_groups: Vec<_> = groups
+            .iter()
+            .map(|(id, name, _)| json!({"Description": name, "Id": id}))
+            .collect();
+        let mut sessions = Vec::new();
+        for (group, _, rankings) in groups {
+            for ranking in rankings {
+                let marks: Vec<_> = ranking
+                    .iter()
+                    .enumerate()
+                    .map(|(r, candidate)| {
+                        json!({"CandidateId": candidate, "Rank": r + 1, "MarkDensity": 100,
+                            "IsAmbiguous": false, "IsVote": true})
+                    })
+                    .collect();
+                sessions.push(json!({
+                    "TabulatorId": 1, "BatchId": 1, "RecordId": sessions.len() + 1,
+                    "CountingGroupId": group, "ImageMask": "",
+                    "Original": {"PrecinctPortionId": 1, "BallotTypeId": 1, "IsCurrent": true,
+                        "Contests": [{"Id": 1, "Marks": marks}]},
+                }));
+            }
+        }
+        let write = |name: &str, value: serde_json::Value| {
+            std::fs::write(dir.join(name), value.to_string()).unwrap();
+        };
+        write(
+            "CandidateManifest.json",
+            json!({"Version": "5.2.16.1", "List": candidates}),
+        );
+        write(
+            "CountingGroupManifest.json",
+            json!({"Version": "5.2.16.1", "List": counting_groups}),
+        );
+        write(
+            "CvrExport.json",
+            json!({"Version": "5.2.16.1", "ElectionId": "Test", "Sessions": sessions}),
+        );
+    }
+
+    #[test]
+    fn test_counting_group_breakdown() {
+        let dir = TempDir::new("counting-groups");
+        write_nist_export(
+            &dir,
+            &[
+                (
+                    1,
+                    "Election Day",
+                    vec![vec![1, 2], vec![1], vec![3, 1], vec![1]],
+                ),
+                (
+                    2,
+                    "Vote by Mail",
+                    vec![vec![2], vec![2, 3], vec![2], vec![1], vec![3, 2]],
+                ),
+            ],
+        );
+        let params: BTreeMap<String, String> = [("cvr", "."), ("contest", "1")]
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let election = read_election("nist_sp_1500", &dir, params);
+        let mut contest = preprocessed(vec![]);
+        contest.ballots =
+            normalize_election("simple", Default::default(), Default::default(), election);
+        let report = generate_report(&contest);
+
+        let groups = report.counting_groups.as_ref().unwrap();
+        assert_eq!(
+            vec![("Election Day", 4), ("Vote by Mail", 5)],
+            groups
+                .iter()
+                .map(|g| (g.counting_group.as_str(), g.ballot_count))
+                .collect::<Vec<_>>()
+        );
+        let votes = |allocations: &[TabulatorAllocation], allocatee: Allocatee| -> u32 {
+            allocations
+                .iter()
+                .filter(|a| a.allocatee == allocatee)
+                .map(|a| a.votes)
+                .sum()
+        };
+        let alice = Allocatee::Candidate(CandidateId(0));
+        assert_eq!(
+            vec![3, 1],
+            groups
+                .iter()
+                .map(|g| votes(&g.first_choice, alice))
+                .collect::<Vec<_>>()
+        );
+
+        // The groups add up to the contest's first and final rounds.
+        let (first, last) = (&report.rounds[0], report.rounds.last().unwrap());
+        for i in 0..report.candidates.len() {
+            let allocatee = Allocatee::Candidate(CandidateId(i as u32));
+            let group_votes = |round: fn(&CountingGroupBreakdown) -> &[TabulatorAllocation]| {
+                groups
+                    .iter()
+                    .map(|g| votes(round(g), allocatee))
+                    .sum::<u32>()
+            };
+            assert_eq!(
+                votes(&first.allocations, allocatee),
+                group_votes(|g| &g.first_choice)
+            );
+            assert_eq!(
+                votes(&last.allocations, allocatee),
+                group_votes(|g| &g.final_round)
+            );
+        }
+        assert_eq!(9, groups.iter().map(|g| g.ballot_count).sum::<u32>());
+    }
+
+    #[test]
+    fn test_group_others() {
+        let (a, b, c, d) = (
+            CandidateId(0),
+            CandidateId(1),
+            CandidateId(2),
+            CandidateId(3),
+        );
+        let mut ballots = vec![];
+        for (choices, count) in [(vec![a], 10), (vec![b], 9), (vec![c], 1), (vec![d, b], 2)] {
+            for _ in 0..count {
+                ballots.push(NormalizedBallot::new(String::new(), choices.clone(), false));
+            }
+        }
+        let report = generate_report(&preprocessed(ballots));
+
+        let others = group_others(&report, 0.1).unwrap();
+        assert_eq!(vec![c, d], others.candidates);
+        assert_eq!(report.rounds.len(), others.votes.len());
+        assert_eq!(Some(&3), others.votes.first());
+        assert_eq!(Some(&0), others.votes.last());
+
+        // A single minor candidate isn't worth a bucket, and finalists are
+        // never grouped.
+        assert_eq!(None, group_others(&report, 0.06));
+        assert_eq!(vec![c, d], group_others(&report, 0.9).unwrap().candidates);
+    }
+
+    #[test]
+    fn test_condense_rounds() {
+        let (a, b, c, d, e) = (
+            CandidateId(0),
+            CandidateId(1),
+            CandidateId(2),
+            CandidateId(3),
+            CandidateId(4),
+        );
+        let mut ballots = vec![];
+        let rankings = [
+            (vec![a], 30),
+            (vec![b], 12),
+            (vec![c, b], 8),
+            (vec![d], 6),
+            (vec![e, d, b], 5),
+        ];
+        for (choices, count) in rankings {
+            for _ in 0..count {
+                ballots.push(NormalizedBallot::new(String::new(), choices.clone(), false));
+            }
+        }
+        let mut report = generate_report(&preprocessed(ballots));
+        assert_eq!(4, report.rounds.len());
+
+        // Eliminating E and then C leaves A and B leading, so those rounds
+        // are one step; D's elimination ends the count.
+        condense_rounds(&mut report);
+        assert_eq!(4, report.full_rounds().len());
+        assert_eq!(Some(vec![1, 3, 4]), report.expanded_round_numbers);
+        let merged = &report.rounds[1];
+        assert!(merged.allocations == report.full_rounds()[2].allocations);
+        let from: Vec<CandidateId> = merged.transfers.iter().map(|t| t.from).collect();
+        assert_eq!(vec![e, c], from);
+        // The series has every round, including those merged.
+        assert_eq!(4, report.candidate_series[&a].len());
+
+        let mut short = generate_report(&preprocessed(vec![]));
+        condense_rounds(&mut short);
+        assert_eq!(None, short.expanded_rounds.map(|r| r.len()));
+    }
+
+    #[test]
+    fn test_degenerate_contests() {
+        let ballot = |id: &str, choices: Vec<CandidateId>, overvoted| {
+            NormalizedBallot::new(id.to_string(), choices, overvoted)
+        };
+
+        let empty = generate_report(&preprocessed(vec![]));
+        assert_eq!(TabulationStatus::NoBallots, empty.status);
+        assert_eq!(None, empty.winner);
+        assert_eq!(1, empty.rounds.len());
+
+        let exhausted = generate_report(&preprocessed(vec![
+            ballot("1", vec![], false),
+            ballot("2", vec![], true),
+        ]));
+        assert_eq!(TabulationStatus::NoVotes, exhausted.status);
+        assert_eq!(None, exhausted.winner);
+        assert_eq!(0, exhausted.rounds[0].continuing_ballots);
+
+        let ballots = vec![
+            ballot("1", vec![CandidateId(0)], false),
+            ballot("2", vec![], true),
+        ];
+        let uncontested = generate_report(&preprocessed(ballots.clone()));
+        assert_eq!(TabulationStatus::Uncontested, uncontested.status);
+        assert_eq!(Some(CandidateId(0)), uncontested.winner);
+        assert_eq!(None, uncontested.margins);
+
+        // With a single qualified candidate, the write-ins are not eliminated.
+        let mut unopposed = preprocessed(vec![
+            ballot("1", vec![CandidateId(0)], false),
+            ballot("2", vec![CandidateId(0)], false),
+            ballot("3", vec![CandidateId(1), CandidateId(0)], false),
+            ballot("4", vec![CandidateId(2)], false),
+        ]);
+        unopposed.ballots.candidates[1].candidate_type = CandidateType::WriteIn;
+        unopposed
+            .ballots
+            .candidates
+            .push(Candidate::new("Carol".to_string(), CandidateType::WriteIn));
+        let report = generate_report(&unopposed);
+        assert_eq!(TabulationStatus::Unopposed, report.status);
+        assert_eq!(1, report.rounds.len());
+        assert_eq!(Some(CandidateId(0)), report.winner);
+
+        unopposed.ballots.candidates[0].candidate_type = CandidateType::WriteIn;
+        unopposed.ballots.ballots = ballots;
+        let report = generate_report(&unopposed);
+        assert_eq!(TabulationStatus::WriteInOnly, report.status);
+    }
+
+    #[test]
+    fn test_index_entry_from_report() {
+        let ballot =
+            |id: &str, c: u32| NormalizedBallot::new(id.to_string(), vec![CandidateId(c)], false);
+        let mut election = preprocessed(vec![ballot("1", 0), ballot("2", 0), ballot("3", 1)]);
+        election.ballots.candidates[1].name = "Write-in".to_string();
+        let mut report = generate_report(&election);
+        report.candidates[0].display_name = Some("Alice Smith".to_string());
+
+        let entry = ContestIndexEntry::from_report(&report);
+        assert_eq!("us/zz/test/mayor", entry.office_id);
+        assert_eq!("Alice Smith", entry.winner);
+        assert_eq!(Some("Alice Smith".to_string()), entry.condorcet_winner);
+        assert!(!entry.has_non_condorcet_winner);
+        assert!(entry.has_write_in_by_name);
+        assert_eq!(1, entry.num_rounds);
     }
 }
@@ -0,0 +1,184 @@
+//! Elimination-order robustness: whether a contest's winner depends on
+//! eliminations so close that rounding or adjudication differences could
+//! have reversed them.
+//!
+//! Candidates whose votes are within a tolerance of the round's last-place
+//! candidate could plausibly have been eliminated instead. The search
+//! follows every such alternative, one elimination at a time, and collects
+//! the candidates who win under some plausible order. In the final round,
+//! the runner-up is a plausible winner if within the tolerance of the
+//! winner.
+
+use crate::model::election::{CandidateId, NormalizedBallot};
+use crate::model::report::{EliminationRobustness, PlausibleOutcome};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// Limit on the number of sets of continuing candidates explored, since the
+/// number of plausible orders grows exponentially with the tolerance.
+const MAX_STATES: usize = 10_000;
+
+struct Search {
+    /// Distinct rankings, with the number of ballots having each.
+    rankings: Vec<(Vec<CandidateId>, u32)>,
+    tolerance: u32,
+    visited: HashSet<BTreeSet<CandidateId>>,
+    /// Plausible winners, with the first elimination order found for each.
+    outcomes: BTreeMap<CandidateId, Vec<CandidateId>>,
+    complete: bool,
+}
+
+impl Search {
+    /// Votes of each continuing candidate, in descending order.
+    fn tally(&self, continuing: &BTreeSet<CandidateId>) -> Vec<(CandidateId, u32)> {
+        let mut votes: BTreeMap<CandidateId, u32> = continuing.iter().map(|&c| (c, 0)).collect();
+        for (ranking, count) in &self.rankings {
+            if let Some(c) = ranking.iter().find(|c| continuing.contains(c)) {
+                *votes.get_mut(c).unwrap() += count;
+            }
+        }
+        let mut votes: Vec<(CandidateId, u32)> = votes.into_iter().collect();
+        votes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        votes
+    }
+
+    fn record(&mut self, winner: CandidateId, order: &[CandidateId]) {
+        self.outcomes
+            .entry(winner)
+            .or_insert_with(|| order.to_vec());
+    }
+
+    fn explore(&mut self, continuing: BTreeSet<CandidateId>, order: &mut Vec<CandidateId>) {
+        if self.visited.len() >= MAX_STATES {
+            self.complete = false;
+            return;
+        }
+        if !self.visited.insert(continuing.clone()) {
+            return;
+        }
+
+        let votes = self.tally(&continuing);
+        let (leader, leader_votes) = votes[0];
+        let rest: u32 = votes[1..].iter().map(|(_, v)| v).sum();
+        if votes.len() <= 2 || leader_votes > rest + self.tolerance {
+            // A leader with a majority beyond the tolerance can't be caught.
+            self.record(leader, order);
+            if votes.len() == 2 && votes[1].1 + self.tolerance >= leader_votes {
+                self.record(votes[1].0, order);
+            }
+            return;
+        }
+
+        let last_votes = votes.last().unwrap().1;
+        for &(candidate, candidate_votes) in votes.iter().rev() {
+            if candidate_votes > last_votes + self.tolerance {
+                break;
+            }
+            let mut next = continuing.clone();
+            next.remove(&candidate);
+            order.push(candidate);
+            self.explore(next, order);
+            order.pop();
+        }
+    }
+}
+
+/// Find the candidates who win under some elimination order in which
+/// candidates within `tolerance` votes of last place may be eliminated
+/// instead of it. `winner` is the reported winner.
+pub fn generate_elimination_robustness(
+    ballots: &[NormalizedBallot],
+    winner: CandidateId,
+    tolerance: u32,
+) -> EliminationRobustness {
+    let mut rankings: HashMap<Vec<CandidateId>, u32> = HashMap::new();
+    for ballot in ballots {
+        let choices = ballot.choices();
+        if !choices.is_empty() {
+            *rankings.entry(choices).or_default() += 1;
+        }
+    }
+    // Like the tabulator, only candidates with first-round votes are in the
+    // count.
+    let continuing: BTreeSet<CandidateId> = rankings.keys().map(|r| r[0]).collect();
+
+    let mut search = Search {
+        rankings: rankings.into_iter().collect(),
+        tolerance,
+        visited: HashSet::new(),
+        outcomes: BTreeMap::new(),
+        complete: true,
+    };
+    if !continuing.is_empty() {
+        search.explore(continuing, &mut Vec::new());
+    }
+
+    EliminationRobustness {
+        tolerance,
+        stable: search.outcomes.keys().all(|&c| c == winner),
+        outcomes: search
+            .outcomes
+            .into_iter()
+            .map(|(winner, elimination_order)| PlausibleOutcome {
+                winner,
+                elimination_order,
+            })
+            .collect(),
+        states: search.visited.len() as u32,
+        complete: search.complete,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ballots(rankings: &[(&[u32], usize)]) -> Vec<NormalizedBallot> {
+        rankings
+            .iter()
+            .flat_map(|&(ranking, count)| std::iter::repeat_n(ranking, count))
+            .enumerate()
+            .map(|(i, ranking)| {
+                let choices = ranking.iter().map(|&c| CandidateId(c)).collect();
+                NormalizedBallot::new(i.to_string(), choices, false)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_close_elimination() {
+        // B is eliminated two votes behind A. Had A been eliminated instead,
+        // A's ballots would have elected C.
+        let ballots = ballots(&[(&[0, 2], 30), (&[1, 0], 29), (&[2], 41), (&[0, 1], 1)]);
+
+        let strict = generate_elimination_robustness(&ballots, CandidateId(0), 0);
+        assert!(strict.stable);
+        assert!(strict.complete);
+        assert_eq!(
+            vec![PlausibleOutcome {
+                winner: CandidateId(0),
+                elimination_order: vec![CandidateId(1)],
+            }],
+            strict.outcomes
+        );
+
+        let tolerant = generate_elimination_robustness(&ballots, CandidateId(0), 2);
+        assert!(!tolerant.stable);
+        assert_eq!(
+            vec![CandidateId(0), CandidateId(2)],
+            tolerant
+                .outcomes
+                .iter()
+                .map(|o| o.winner)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(vec![CandidateId(0)], tolerant.outcomes[1].elimination_order);
+    }
+
+    #[test]
+    fn test_majority_is_stable() {
+        let ballots = ballots(&[(&[0], 60), (&[1], 21), (&[2], 19)]);
+        let robustness = generate_elimination_robustness(&ballots, CandidateId(0), 5);
+        assert!(robustness.stable);
+        assert_eq!(1, robustness.states);
+    }
+}
@@ -0,0 +1,171 @@
+//! Named statutory rule sets: the ballot validity and tabulation rules a
+//! jurisdiction's law prescribes, with the citation of the statute.
+//!
+//! Election metadata refers to a rule set by name (`"ruleSet"`) instead of
+//! spelling out its normalization, overvote and skipped-rank policies and
+//! tabulation options, so that contests under the same law share one
+//! definition, and each report's provenance cites the statute it was
+//! counted under.
+
+mod statutes;
+
+pub use statutes::RULE_SETS;
+
+use crate::model::election::RuleSetReference;
+use crate::model::metadata::{
+    ElectionMetadata, OvervotePolicy, SkippedRankPolicy, TabulationOptions,
+};
+
+pub struct StatutoryRules {
+    /// Name that metadata refers to the rule set by.
+    pub name: &'static str,
+    pub title: &'static str,
+    /// Citation of the statute, e.g. "Me. Rev. Stat. tit. 21-A, § 723-A".
+    pub citation: &'static str,
+    pub normalization: &'static str,
+    pub overvote_policy: OvervotePolicy,
+    pub skipped_rank_policy: SkippedRankPolicy,
+    pub eager: bool,
+    pub nyc_style: bool,
+}
+
+impl StatutoryRules {
+    pub fn tabulation_options(&self) -> TabulationOptions {
+        TabulationOptions {
+            eager: Some(self.eager),
+            nyc_style: Some(self.nyc_style),
+            exact_thresholds: None,
+        }
+    }
+
+    pub fn reference(&self) -> RuleSetReference {
+        RuleSetReference {
+            name: self.name.to_string(),
+            title: self.title.to_string(),
+            citation: self.citation.to_string(),
+        }
+    }
+}
+
+/// The rule set with the given name, if any.
+pub fn rule_set(name: &str) -> Option<&'static StatutoryRules> {
+    RULE_SETS.iter().find(|rules| rules.name == name)
+}
+
+/// Check that a setting given in the metadata agrees with the rule set, or
+/// fill it in from the rule set if it's not given.
+fn apply_setting<T: PartialEq + std::fmt::Debug>(
+    setting: &mut Option<T>,
+    statute: T,
+    name: &str,
+    rules: &StatutoryRules,
+) -> Result<(), String> {
+    match setting {
+        Some(value) if *value != statute => Err(format!(
+            "{} is {:?}, but rule set {} ({}) requires {:?}",
+            name, value, rules.name, rules.citation, statute
+        )),
+        _ => {
+            *setting = Some(statute);
+            Ok(())
+        }
+    }
+}
+
+/// Fill in an election's normalization, policies and tabulation options
+/// from its rule set, if it has one. Settings given in the metadata as well
+/// must agree with the rule set; the rule set describes the law, so a
+/// deviation from it is an error rather than an override. Tabulation
+/// variants are alternatives to the statute, and are left as they are.
+pub fn apply_rule_set(election: &mut ElectionMetadata) -> Result<(), String> {
+    let rules = match &election.rule_set {
+        Some(name) => rule_set(name).ok_or_else(|| {
+            let known: Vec<&str> = RULE_SETS.iter().map(|rules| rules.name).collect();
+            format!(
+                "Unknown rule set {}; known rule sets are {}",
+                name,
+                known.join(", ")
+            )
+        })?,
+        None if election.normalization.is_empty() => {
+            return Err("Election has neither a normalization nor a rule set".to_string())
+        }
+        None => return Ok(()),
+    };
+
+    let mut normalization = Some(election.normalization.clone()).filter(|n| !n.is_empty());
+    apply_setting(
+        &mut normalization,
+        rules.normalization.to_string(),
+        "normalization",
+        rules,
+    )?;
+    election.normalization = normalization.unwrap();
+    apply_setting(
+        &mut election.overvote_policy,
+        rules.overvote_policy,
+        "overvotePolicy",
+        rules,
+    )?;
+    apply_setting(
+        &mut election.skipped_rank_policy,
+        rules.skipped_rank_policy,
+        "skippedRankPolicy",
+        rules,
+    )?;
+
+    let options = election
+        .tabulation_options
+        .get_or_insert_with(|| rules.tabulation_options());
+    let mut eager = Some(options.eager.unwrap_or(true));
+    apply_setting(&mut eager, rules.eager, "tabulationOptions.eager", rules)?;
+    let mut nyc_style = Some(options.nyc_style.unwrap_or(false));
+    apply_setting(
+        &mut nyc_style,
+        rules.nyc_style,
+        "tabulationOptions.nycStyle",
+        rules,
+    )?;
+    options.eager = eager;
+    options.nyc_style = nyc_style;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn election(json: &str) -> ElectionMetadata {
+        serde_json::from_str(&format!(
+            r#"{{"name": "General", "date": "2024-11-05", "dataFormat": "us_me",
+                "contests": [], "files": {{}}, "website": null, "precinctMap": null, {}}}"#,
+            json
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_apply_rule_set() {
+        let mut maine = election(r#""ruleSet": "us/me/21-A-723-A""#);
+        apply_rule_set(&mut maine).unwrap();
+        assert_eq!("maine", maine.normalization);
+        assert_eq!(Some(OvervotePolicy::Exhaust), maine.overvote_policy);
+        assert_eq!(Some(true), maine.tabulation_options.unwrap().eager);
+
+        // Settings that agree with the statute may be given as well.
+        let mut agreeing = election(r#""ruleSet": "us/ak/15.15.350", "normalization": "simple""#);
+        apply_rule_set(&mut agreeing).unwrap();
+        assert_eq!(
+            Some(SkippedRankPolicy::ExhaustAfterTwo),
+            agreeing.skipped_rank_policy
+        );
+
+        let mut conflicting = election(r#""ruleSet": "us/ak/15.15.350", "overvotePolicy": "skip""#);
+        let error = apply_rule_set(&mut conflicting).unwrap_err();
+        assert!(error.contains("Alaska Stat. § 15.15.350"), "{}", error);
+
+        assert!(apply_rule_set(&mut election(r#""ruleSet": "us/xx/1""#)).is_err());
+        assert!(apply_rule_set(&mut election(r#""normalization": """#)).is_err());
+        assert!(apply_rule_set(&mut election(r#""normalization": "simple""#)).is_ok());
+    }
+}
@@ -0,0 +1,50 @@
+use super::StatutoryRules;
+use crate::model::metadata::{OvervotePolicy, SkippedRankPolicy};
+
+/// The statutory rule sets that election metadata can refer to by name.
+/// Names are the jurisdiction's path with the section of law, so that a
+/// jurisdiction whose rules change can have a rule set for each version.
+pub const RULE_SETS: &[StatutoryRules] = &[
+    StatutoryRules {
+        name: "us/ak/15.15.350",
+        title: "Alaska ranked-choice general elections",
+        citation: "Alaska Stat. § 15.15.350",
+        normalization: "simple",
+        overvote_policy: OvervotePolicy::Exhaust,
+        skipped_rank_policy: SkippedRankPolicy::ExhaustAfterTwo,
+        eager: true,
+        nyc_style: false,
+    },
+    StatutoryRules {
+        name: "us/ca/sfo/13.102",
+        title: "San Francisco ranked-choice voting",
+        citation: "San Francisco Charter § 13.102",
+        normalization: "simple",
+        overvote_policy: OvervotePolicy::Exhaust,
+        skipped_rank_policy: SkippedRankPolicy::Skip,
+        eager: true,
+        nyc_style: false,
+    },
+    StatutoryRules {
+        name: "us/me/21-A-723-A",
+        title: "Maine ranked-choice voting",
+        citation: "Me. Rev. Stat. tit. 21-A, § 723-A",
+        // The maine normalizer exhausts a ballot at two sequential skipped
+        // rankings itself.
+        normalization: "maine",
+        overvote_policy: OvervotePolicy::Exhaust,
+        skipped_rank_policy: SkippedRankPolicy::Skip,
+        eager: true,
+        nyc_style: false,
+    },
+    StatutoryRules {
+        name: "us/ny/nyc/1057-g",
+        title: "New York City ranked-choice voting",
+        citation: "New York City Charter § 1057-g",
+        normalization: "nyc",
+        overvote_policy: OvervotePolicy::Exhaust,
+        skipped_rank_policy: SkippedRankPolicy::Skip,
+        eager: true,
+        nyc_style: true,
+    },
+];
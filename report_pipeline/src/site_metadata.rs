@@ -0,0 +1,111 @@
+use crate::model::report::{ContestMetadataStub, ContestReport, ElectionIndexEntry};
+
+/// File name of the sitemap, written in the root report directory.
+pub const SITEMAP_FILE: &str = "sitemap.xml";
+
+/// File name of a contest's metadata stub, written next to `report.json`.
+pub const CONTEST_METADATA_FILE: &str = "meta.json";
+
+/// Pages of the site that aren't generated from reports.
+const STATIC_PAGES: &[&str] = &["", "about"];
+
+/// Canonical URL of the report page for a contest. `election_path` includes
+/// the jurisdiction path, as in the index.
+pub fn contest_url(site_url: &str, election_path: &str, office: &str) -> String {
+    format!(
+        "{}/report/{}/{}",
+        site_url.trim_end_matches('/'),
+        election_path,
+        office
+    )
+}
+
+/// Build the metadata stub for a contest, titled the same way as its
+/// report page.
+pub fn contest_metadata_stub(site_url: &str, report: &ContestReport) -> ContestMetadataStub {
+    let info = &report.info;
+    let election_path = format!("{}/{}", info.jurisdiction_path, info.election_path);
+
+    ContestMetadataStub {
+        title: format!(
+            "ranked.vote: {} / {} / {}",
+            info.jurisdiction_name,
+            info.name,
+            info.date.chars().take(4).collect::<String>()
+        ),
+        jurisdiction_name: info.jurisdiction_name.clone(),
+        office_name: info.office_name.clone(),
+        election_name: info.election_name.clone(),
+        date: info.date.clone(),
+        winner: report.winner().map(|w| w.display_name().to_string()),
+        preliminary: report
+            .reporting_status
+            .as_ref()
+            .is_some_and(|status| status.preliminary),
+        canonical_url: contest_url(site_url, &election_path, &info.office),
+    }
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Generate a sitemap listing the static pages of the site and the report
+/// page of every contest in the index.
+pub fn generate_sitemap(site_url: &str, elections: &[ElectionIndexEntry]) -> String {
+    let site_url = site_url.trim_end_matches('/');
+    let mut urls: Vec<String> = STATIC_PAGES
+        .iter()
+        .map(|page| format!("{}/{}", site_url, page))
+        .collect();
+    for election in elections {
+        for contest in &election.contests {
+            urls.push(contest_url(site_url, &election.path, &contest.office));
+        }
+    }
+
+    let mut sitemap = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+    for url in urls {
+        sitemap.push_str(&format!("  <url><loc>{}</loc></url>\n", escape_xml(&url)));
+    }
+    sitemap.push_str("</urlset>\n");
+    sitemap
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::report::ContestIndexEntry;
+
+    #[test]
+    fn test_generate_sitemap() {
+        let elections = vec![ElectionIndexEntry {
+            path: "us/ca/sfo/2024/11".to_string(),
+            jurisdiction_name: "San Francisco".to_string(),
+            election_name: "General Election".to_string(),
+            date: "2024-11-05".to_string(),
+            contests: vec![ContestIndexEntry {
+                office: "mayor".to_string(),
+                office_id: "us/ca/sfo/mayor".to_string(),
+                office_name: "Mayor".to_string(),
+                name: "Mayor".to_string(),
+                winner: "Daniel Lurie".to_string(),
+                num_candidates: 13,
+                num_rounds: 14,
+                ..Default::default()
+            }],
+        }];
+
+        let sitemap = generate_sitemap("https://ranked.vote/", &elections);
+        assert!(sitemap.contains("<loc>https://ranked.vote/about</loc>"));
+        assert!(sitemap.contains("<loc>https://ranked.vote/report/us/ca/sfo/2024/11/mayor</loc>"));
+        assert!(sitemap.ends_with("</urlset>\n"));
+    }
+}
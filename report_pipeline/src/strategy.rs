@@ -0,0 +1,283 @@
+//! Strategy susceptibility: whether a contest's outcome would have been
+//! different had some voters ranked a candidate lower or higher, which IRV's
+//! critics point to as its lack of monotonicity.
+//!
+//! IRV satisfies later-no-harm (ranking another candidate after your first
+//! choice never hurts your first choice), so only monotonicity is checked:
+//!
+//! - Downward: a losing candidate would have won had some ballots ranking
+//!   them first and another candidate second swapped the two.
+//! - Upward: the winner would have lost had some ballots ranking another
+//!   candidate first and the winner second swapped the two.
+//!
+//! Swapping the first two choices of `k` ballots moves `k` votes from the
+//! first to the second candidate in every round where both continue, and
+//! changes nothing in other rounds. The outcome can only change when one
+//! of the two candidates' totals crosses another continuing candidate's in
+//! some round, so only those numbers of swapped ballots are tabulated. This
+//! finds the fewest swaps that change the outcome when candidates are
+//! eliminated one at a time; with batch elimination, a change to which
+//! candidates are eliminated together may be missed.
+
+use crate::model::election::{CandidateId, NormalizedBallot};
+use crate::model::metadata::TabulationOptions;
+use crate::model::report::{MonotonicityFailure, StrategySusceptibility};
+use crate::report::winner;
+use crate::tabulator::{tabulate_weighted, TabulatorRound};
+use std::collections::{BTreeMap, HashMap};
+
+/// Limit on the number of tabulations for each pair of candidates.
+const MAX_STEPS: usize = 200;
+
+struct Swaps<'a> {
+    /// Distinct rankings, with the number of ballots having each.
+    rankings: Vec<(Vec<CandidateId>, u32)>,
+    options: &'a TabulationOptions,
+    complete: bool,
+}
+
+/// Votes of each candidate in a round.
+fn candidate_votes(round: &TabulatorRound) -> BTreeMap<CandidateId, i64> {
+    round
+        .allocations
+        .iter()
+        .flat_map(|a| Some((a.allocatee.candidate_id()?, a.votes as i64)))
+        .collect()
+}
+
+impl Swaps<'_> {
+    /// Tabulate with the first two choices of `k` ballots ranking `first`
+    /// then `second` swapped.
+    fn tabulate(&self, first: CandidateId, second: CandidateId, k: u32) -> Vec<TabulatorRound> {
+        let mut remaining = k;
+        let mut ballots = Vec::with_capacity(self.rankings.len() + 1);
+        for (ranking, count) in &self.rankings {
+            let mut count = *count;
+            if remaining > 0 && ranking.starts_with(&[first, second]) {
+                let swapped = count.min(remaining);
+                let mut choices = ranking.clone();
+                choices.swap(0, 1);
+                ballots.push((
+                    NormalizedBallot::new(String::new(), choices, false),
+                    swapped,
+                ));
+                remaining -= swapped;
+                count -= swapped;
+            }
+            if count > 0 {
+                ballots.push((
+                    NormalizedBallot::new(String::new(), ranking.clone(), false),
+                    count,
+                ));
+            }
+        }
+        tabulate_weighted(ballots, self.options)
+    }
+
+    /// The fewest additional swaps, beyond those the rounds were tabulated
+    /// with, at which an elimination could change.
+    fn next_step(
+        rounds: &[TabulatorRound],
+        first: CandidateId,
+        second: CandidateId,
+    ) -> Option<u32> {
+        let mut steps = Vec::new();
+        for round in rounds {
+            let votes = candidate_votes(round);
+            let (Some(&a), Some(&b)) = (votes.get(&first), votes.get(&second)) else {
+                continue;
+            };
+            // The two candidates pass each other after (a - b) / 2 swaps.
+            steps.push((a - b) / 2);
+            for (&c, &v) in &votes {
+                if c != first && c != second {
+                    steps.push(a - v);
+                    steps.push(v - b);
+                }
+            }
+        }
+        // A crossing can be a tie first, so test both sides of it.
+        steps
+            .into_iter()
+            .flat_map(|d| vec![d, d + 1])
+            .filter(|&d| d > 0)
+            .min()
+            .map(|d| d as u32)
+    }
+
+    /// The fewest swaps of `first` and `second`, up to the number of ballots
+    /// ranking them first and second, after which `changed` holds of the
+    /// winner.
+    fn fewest_swaps(
+        &mut self,
+        first: CandidateId,
+        second: CandidateId,
+        baseline: &[TabulatorRound],
+        changed: impl Fn(CandidateId) -> bool,
+    ) -> Option<(u32, CandidateId)> {
+        let available: u32 = self
+            .rankings
+            .iter()
+            .filter(|(ranking, _)| ranking.starts_with(&[first, second]))
+            .map(|(_, count)| count)
+            .sum();
+
+        let mut k = 0;
+        let mut rounds = None;
+        for _ in 0..MAX_STEPS {
+            k += Self::next_step(rounds.as_deref().unwrap_or(baseline), first, second)?;
+            if k > available {
+                return None;
+            }
+            let next = self.tabulate(first, second, k);
+            if let Some(w) = winner(&next).filter(|&w| changed(w)) {
+                return Some((k, w));
+            }
+            rounds = Some(next);
+        }
+        self.complete = false;
+        None
+    }
+}
+
+/// Find the monotonicity failures of a contest, given its ballots, the
+/// options it was tabulated with, and its rounds. Returns `None` if there
+/// is no winner.
+pub fn generate_strategy_susceptibility(
+    ballots: &[NormalizedBallot],
+    options: &TabulationOptions,
+    rounds: &[TabulatorRound],
+) -> Option<StrategySusceptibility> {
+    let reported_winner = winner(rounds)?;
+    let mut rankings: HashMap<Vec<CandidateId>, u32> = HashMap::new();
+    for ballot in ballots {
+        *rankings.entry(ballot.choices()).or_default() += 1;
+    }
+    let mut rankings: Vec<(Vec<CandidateId>, u32)> = rankings.into_iter().collect();
+    rankings.sort();
+
+    // Pairs of candidates ranked first and second on some ballot.
+    let mut pairs: Vec<(CandidateId, CandidateId)> = rankings
+        .iter()
+        .filter(|(ranking, _)| ranking.len() >= 2)
+        .map(|(ranking, _)| (ranking[0], ranking[1]))
+        .collect();
+    pairs.dedup();
+
+    let mut swaps = Swaps {
+        rankings,
+        options,
+        complete: true,
+    };
+    let mut downward_failures = Vec::new();
+    let mut upward_failures = Vec::new();
+    for (first, second) in pairs {
+        if first == reported_winner {
+            continue;
+        }
+        // Swapping moves votes from `first` to `second`, so only the winner
+        // gaining votes can change the outcome upward, and only a loser losing
+        // votes downward.
+        let upward = second == reported_winner;
+        let changed = |w| {
+            if upward {
+                w != reported_winner
+            } else {
+                w == first
+            }
+        };
+        if let Some((ballots, winner)) = swaps.fewest_swaps(first, second, rounds, changed) {
+            if upward {
+                upward_failures.push(MonotonicityFailure {
+                    candidate: second,
+                    other: first,
+                    ballots,
+                    winner,
+                });
+            } else {
+                downward_failures.push(MonotonicityFailure {
+                    candidate: first,
+                    other: second,
+                    ballots,
+                    winner,
+                });
+            }
+        }
+    }
+
+    Some(StrategySusceptibility {
+        downward_monotonicity: downward_failures,
+        upward_monotonicity: upward_failures,
+        complete: swaps.complete,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tabulator::tabulate;
+
+    fn ballots(rankings: &[(&[u32], usize)]) -> Vec<NormalizedBallot> {
+        rankings
+            .iter()
+            .flat_map(|&(ranking, count)| std::iter::repeat_n(ranking, count))
+            .map(|ranking| {
+                let choices = ranking.iter().map(|&c| CandidateId(c)).collect();
+                NormalizedBallot::new(String::new(), choices, false)
+            })
+            .collect()
+    }
+
+    fn susceptibility(ballots: &[NormalizedBallot]) -> StrategySusceptibility {
+        let options = TabulationOptions::default();
+        let rounds = tabulate(ballots, &options);
+        generate_strategy_susceptibility(ballots, &options, &rounds).unwrap()
+    }
+
+    #[test]
+    fn test_downward_monotonicity() {
+        // B is eliminated first and its ballots elect A over C. Had two of
+        // C's voters ranked B first, A would have been eliminated instead,
+        // and C would have won.
+        let s = susceptibility(&ballots(&[
+            (&[0, 1], 2),
+            (&[0, 2], 7),
+            (&[1, 0], 8),
+            (&[2, 0], 4),
+            (&[2, 1], 8),
+        ]));
+        assert!(s.complete);
+        assert_eq!(
+            vec![MonotonicityFailure {
+                candidate: CandidateId(2),
+                other: CandidateId(1),
+                ballots: 2,
+                winner: CandidateId(2),
+            }],
+            s.downward_monotonicity
+        );
+    }
+
+    #[test]
+    fn test_upward_monotonicity() {
+        // C is eliminated first and its ballots elect A. Had ten of B's
+        // voters ranked A first, B would have been eliminated instead, and
+        // its other ballots would have elected C.
+        let s = susceptibility(&ballots(&[
+            (&[0, 1], 39),
+            (&[1, 0], 10),
+            (&[1, 2], 25),
+            (&[2, 0], 26),
+        ]));
+        assert!(s.complete);
+        assert_eq!(
+            vec![MonotonicityFailure {
+                candidate: CandidateId(0),
+                other: CandidateId(1),
+                ballots: 10,
+                winner: CandidateId(2),
+            }],
+            s.upward_monotonicity
+        );
+    }
+}
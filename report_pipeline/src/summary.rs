@@ -0,0 +1,163 @@
+use crate::model::report::{ContestReport, ContestSummary, ElectionSummary, TrendPoint};
+use crate::tabulator::Allocatee;
+
+/// File name of the election summary, written in the election's report
+/// directory.
+pub const SUMMARY_FILE: &str = "summary.json";
+
+/// File names of the trend dataset, written in the root report directory.
+pub const TRENDS_JSON_FILE: &str = "trends.json";
+pub const TRENDS_CSV_FILE: &str = "trends.csv";
+
+fn fraction(numerator: u32, denominator: u32) -> f32 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f32 / denominator as f32
+    }
+}
+
+/// Summarize a contest report. `turnout` is filled in by
+/// `generate_election_summary`, once the election's ballots cast are known.
+pub fn contest_summary(report: &ContestReport) -> ContestSummary {
+    let first_continuing = report.rounds.first().map_or(0, |r| r.continuing_ballots);
+    let last_continuing = report.rounds.last().map_or(0, |r| r.continuing_ballots);
+    let exhausted_ballots = first_continuing.saturating_sub(last_continuing);
+    let winner_first_choice_votes = report.winner.and_then(|winner| {
+        report.rounds.first().and_then(|round| {
+            round
+                .allocations
+                .iter()
+                .find(|a| a.allocatee == Allocatee::Candidate(winner))
+                .map(|a| a.votes)
+        })
+    });
+
+    ContestSummary {
+        office: report.info.office.clone(),
+        office_name: report.info.office_name.clone(),
+        winner: report.winner().map(|w| w.display_name().to_string()),
+        ballot_count: report.ballot_count,
+        turnout: 0.0,
+        num_candidates: report.num_candidates,
+        num_rounds: report.full_rounds().len() as u32,
+        winner_first_choice_share: winner_first_choice_votes
+            .map(|votes| fraction(votes, first_continuing)),
+        continuing_ballots: first_continuing,
+        exhausted_ballots,
+        exhaustion_rate: fraction(exhausted_ballots, first_continuing),
+    }
+}
+
+/// Aggregate the summaries of the contests in an election.
+pub fn generate_election_summary(
+    path: String,
+    jurisdiction_path: String,
+    jurisdiction_name: String,
+    election_name: String,
+    date: String,
+    mut contests: Vec<ContestSummary>,
+) -> ElectionSummary {
+    let ballots_cast = contests.iter().map(|c| c.ballot_count).max().unwrap_or(0);
+    let exhausted_ballots: u32 = contests.iter().map(|c| c.exhausted_ballots).sum();
+    let continuing_ballots: u32 = contests.iter().map(|c| c.continuing_ballots).sum();
+    let total_rounds: u32 = contests.iter().map(|c| c.num_rounds).sum();
+
+    for contest in &mut contests {
+        contest.turnout = fraction(contest.ballot_count, ballots_cast);
+    }
+
+    ElectionSummary {
+        path,
+        jurisdiction_path,
+        jurisdiction_name,
+        election_name,
+        date,
+        ballots_cast,
+        exhausted_ballots,
+        exhaustion_rate: fraction(exhausted_ballots, continuing_ballots),
+        average_rounds: fraction(total_rounds, contests.len() as u32),
+        contests,
+    }
+}
+
+/// Flatten election summaries into one row per contest, ordered by
+/// jurisdiction, office and date.
+pub fn generate_trends(summaries: &[ElectionSummary]) -> Vec<TrendPoint> {
+    let mut trends: Vec<TrendPoint> = summaries
+        .iter()
+        .flat_map(|election| {
+            election.contests.iter().map(move |contest| TrendPoint {
+                jurisdiction_path: election.jurisdiction_path.clone(),
+                jurisdiction_name: election.jurisdiction_name.clone(),
+                office: contest.office.clone(),
+                office_name: contest.office_name.clone(),
+                year: election.date.chars().take(4).collect(),
+                date: election.date.clone(),
+                election_path: election.path.clone(),
+                ballot_count: contest.ballot_count,
+                num_candidates: contest.num_candidates,
+                num_rounds: contest.num_rounds,
+                exhaustion_rate: contest.exhaustion_rate,
+                winner: contest.winner.clone(),
+                winner_first_choice_share: contest.winner_first_choice_share,
+            })
+        })
+        .collect();
+
+    trends.sort_by(|a, b| {
+        (&a.jurisdiction_path, &a.office, &a.date, &a.election_path).cmp(&(
+            &b.jurisdiction_path,
+            &b.office,
+            &b.date,
+            &b.election_path,
+        ))
+    });
+    trends
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contest(office: &str, ballot_count: u32, continuing: u32, exhausted: u32) -> ContestSummary {
+        ContestSummary {
+            office: office.to_string(),
+            office_name: office.to_string(),
+            winner: None,
+            ballot_count,
+            turnout: 0.0,
+            num_candidates: 3,
+            num_rounds: if exhausted > 0 { 2 } else { 1 },
+            winner_first_choice_share: None,
+            continuing_ballots: continuing,
+            exhausted_ballots: exhausted,
+            exhaustion_rate: fraction(exhausted, continuing),
+        }
+    }
+
+    #[test]
+    fn test_election_summary() {
+        let summary = generate_election_summary(
+            "us/ca/sfo/2024/11".to_string(),
+            "us/ca/sfo".to_string(),
+            "San Francisco".to_string(),
+            "General Election".to_string(),
+            "2024-11-05".to_string(),
+            vec![contest("mayor", 400, 380, 30), contest("d1", 100, 90, 0)],
+        );
+
+        assert_eq!(400, summary.ballots_cast);
+        assert_eq!(30, summary.exhausted_ballots);
+        assert_eq!(30.0 / 470.0, summary.exhaustion_rate);
+        assert_eq!(1.5, summary.average_rounds);
+        assert_eq!(0.25, summary.contests[1].turnout);
+
+        let trends = generate_trends(&[summary]);
+        assert_eq!(
+            vec!["d1", "mayor"],
+            trends.iter().map(|t| t.office.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!("2024", trends[0].year);
+    }
+}
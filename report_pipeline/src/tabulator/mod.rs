@@ -1,8 +1,13 @@
+mod rules;
 mod schema;
 
 use crate::model::election::{CandidateId, Choice, NormalizedBallot};
 use crate::model::metadata::TabulationOptions;
-pub use crate::tabulator::schema::{Allocatee, TabulatorAllocation, TabulatorRound, Transfer};
+use crate::tabulator::rules::RuleSet;
+pub use crate::tabulator::schema::{
+    Allocatee, ExactRatio, MajorityThreshold, Shares, TabulatorAllocation, TabulatorRound, Transfer,
+};
+use num_rational::Ratio;
 use std::collections::{BTreeMap, BTreeSet, HashSet};
 
 /// Represents the number of ballots considered to be allocated to
@@ -20,7 +25,8 @@ impl Allocations {
         Allocations { votes, exhausted }
     }
 
-    /// Returns true if a winner can be declared from this allocation.
+    /// Returns true if a winner can be declared from this allocation, which
+    /// is never the case if no candidate has votes.
     #[allow(unused)]
     pub fn is_final(&self) -> bool {
         match self.votes.first() {
@@ -28,7 +34,7 @@ impl Allocations {
                 let rest_votes = self.continuing() - first_votes;
                 *first_votes > rest_votes
             }
-            _ => panic!("The contest should have at least one candidate."),
+            None => false,
         }
     }
 
@@ -39,11 +45,13 @@ impl Allocations {
             v.push(TabulatorAllocation {
                 allocatee: Allocatee::Candidate(id),
                 votes,
+                shares: None,
             });
         }
         v.push(TabulatorAllocation {
             allocatee: Allocatee::Exhausted,
             votes: self.exhausted,
+            shares: None,
         });
         v
     }
@@ -54,38 +62,50 @@ impl Allocations {
     }
 }
 
+/// A ballot, or a group of identical ballots, with the number of ballots
+/// it stands for.
+#[derive(Clone)]
+struct WeightedBallot {
+    ballot: NormalizedBallot,
+    weight: u32,
+}
+
+/// Total number of ballots represented by a list of weighted ballots.
+fn ballot_count(ballots: &[WeightedBallot]) -> u32 {
+    ballots.iter().map(|b| b.weight).sum()
+}
+
 struct TabulatorState {
     /// Map from candidate to ballots attributed to that candidate at this round.
     /// Eliminated candidates ranking above the top non-eliminated candidate have
     /// been removed from each ballot.
-    pub candidate_ballots: BTreeMap<Choice, Vec<NormalizedBallot>>,
+    pub candidate_ballots: BTreeMap<Choice, Vec<WeightedBallot>>,
 
     /// Transfers incoming from the prior round.
     pub transfers: Vec<Transfer>,
 
     /// Set of candidates who have already been eliminated prior to this round.
     eliminated: HashSet<CandidateId>,
+
+    /// Rules for counting the ballots in each round.
+    rules: RuleSet,
 }
 
 impl TabulatorState {
     /// Obtain the `TabulatorRound` representation of a `TabulatorState`.
     /// The `TabulatorRound` representation is the one that is serialized
     /// into the report.
-    pub fn as_round(
-        &self,
-        tabulation_options: &TabulationOptions,
-        round_number: usize,
-    ) -> TabulatorRound {
-        let allocations = self.allocations(tabulation_options, round_number);
+    pub fn as_round(&self, round_number: usize) -> TabulatorRound {
+        let allocations = self.allocations(round_number);
         let undervote = self
             .candidate_ballots
             .get(&Choice::Undervote)
-            .map(|x| x.len() as u32)
+            .map(|x| ballot_count(x))
             .unwrap_or(0);
         let overvote = self
             .candidate_ballots
             .get(&Choice::Overvote)
-            .map(|x| x.len() as u32)
+            .map(|x| ballot_count(x))
             .unwrap_or(0);
         let continuing_ballots = allocations.continuing();
 
@@ -95,56 +115,38 @@ impl TabulatorState {
             overvote,
             continuing_ballots,
             transfers: self.transfers.clone(),
+            threshold: None,
         }
     }
 
-    pub fn new(ballots: &[NormalizedBallot]) -> TabulatorState {
-        let mut allocations: BTreeMap<Choice, Vec<NormalizedBallot>> = BTreeMap::new();
+    pub fn new(ballots: Vec<WeightedBallot>, rules: RuleSet) -> TabulatorState {
+        let mut allocations: BTreeMap<Choice, Vec<WeightedBallot>> = BTreeMap::new();
         for ballot in ballots {
-            let choice = ballot.top_vote();
+            let choice = ballot.ballot.top_vote();
             allocations
                 .entry(choice)
                 .or_insert_with(Vec::new)
-                .push(ballot.clone());
+                .push(ballot);
         }
         TabulatorState {
             candidate_ballots: allocations,
             transfers: Vec::new(),
             eliminated: HashSet::new(),
+            rules,
         }
     }
 
     /// Count the ballots attributed to each candidate at this round, as well as the
     /// number of exhausted ballots.
-    pub fn allocations(
-        &self,
-        tabulation_options: &TabulationOptions,
-        round_number: usize,
-    ) -> Allocations {
+    pub fn allocations(&self, round_number: usize) -> Allocations {
         let mut alloc: BTreeMap<CandidateId, u32> = BTreeMap::new();
         let mut exhausted: u32 = 0;
         for (choice, ballots) in &self.candidate_ballots {
-            let count = ballots.len() as u32;
-            match choice {
-                Choice::Undervote => {
-                    // In NYC-style tabulation, undervotes in the first round are not counted as exhausted
-                    if tabulation_options.nyc_style.unwrap_or(false) && round_number == 0 {
-                        // Don't count undervotes as exhausted in first round
-                    } else {
-                        exhausted += count;
-                    }
-                }
-                Choice::Overvote => {
-                    // In NYC-style tabulation, overvotes in the first round are not counted as exhausted
-                    if tabulation_options.nyc_style.unwrap_or(false) && round_number == 0 {
-                        // Don't count overvotes as exhausted in first round
-                    } else {
-                        exhausted += count;
-                    }
-                }
-                Choice::Vote(c) => {
-                    alloc.insert(*c, count);
-                }
+            let count = ballot_count(ballots);
+            if let Choice::Vote(c) = choice {
+                alloc.insert(*c, count);
+            } else if self.rules.is_exhausted(*choice, round_number) {
+                exhausted += count;
             }
         }
 
@@ -158,25 +160,12 @@ impl TabulatorState {
         tabulation_options: &TabulationOptions,
         round_number: usize,
     ) -> TabulatorState {
-        let allocations = self.allocations(tabulation_options, round_number);
+        let allocations = self.allocations(round_number);
 
         // Determine which candidates to eliminate.
-        let candidates_to_eliminate: BTreeSet<CandidateId> = {
-            let mut ai = allocations.votes.iter();
-            let mut remaining_votes = allocations.continuing();
-
-            for (i, (_, votes)) in (&mut ai).enumerate() {
-                remaining_votes -= votes;
-                if votes > &remaining_votes && i > 0 {
-                    break;
-                }
-            }
-
-            let to_eliminate: BTreeSet<CandidateId> = ai.map(|d| d.0).collect();
-
-            // If no candidates would be eliminated (e.g., all tied), eliminate the last one
-            if to_eliminate.is_empty() && !allocations.votes.is_empty() {
-                // Eliminate the candidate with the fewest votes (last in sorted list)
+        let candidates_to_eliminate: BTreeSet<CandidateId> =
+            if !tabulation_options.eager.unwrap_or(true) {
+                // Eliminate only the candidate with the fewest votes.
                 allocations
                     .votes
                     .last()
@@ -184,9 +173,31 @@ impl TabulatorState {
                     .into_iter()
                     .collect()
             } else {
-                to_eliminate
-            }
-        };
+                let mut ai = allocations.votes.iter();
+                let mut remaining_votes = allocations.continuing();
+
+                for (i, (_, votes)) in (&mut ai).enumerate() {
+                    remaining_votes -= votes;
+                    if votes > &remaining_votes && i > 0 {
+                        break;
+                    }
+                }
+
+                let to_eliminate: BTreeSet<CandidateId> = ai.map(|d| d.0).collect();
+
+                // If no candidates would be eliminated (e.g., all tied), eliminate the last one
+                if to_eliminate.is_empty() && !allocations.votes.is_empty() {
+                    // Eliminate the candidate with the fewest votes (last in sorted list)
+                    allocations
+                        .votes
+                        .last()
+                        .map(|(id, _)| *id)
+                        .into_iter()
+                        .collect()
+                } else {
+                    to_eliminate
+                }
+            };
 
         let mut transfers: BTreeSet<Transfer> = BTreeSet::new();
         let mut eliminated = self.eliminated;
@@ -204,12 +215,12 @@ impl TabulatorState {
                 .remove(&Choice::Vote(*to_eliminate))
                 .unwrap();
 
-            for mut ballot in ballots {
+            for mut weighted in ballots {
                 // Remove the top candidate from the ballot until we find one who has
                 // not been eliminated.
                 let new_choice = loop {
-                    ballot = ballot.pop_top_vote();
-                    let next_choice = ballot.top_vote();
+                    weighted.ballot = weighted.ballot.pop_top_vote();
+                    let next_choice = weighted.ballot.top_vote();
 
                     if let Choice::Vote(c) = next_choice {
                         if !eliminated.contains(&c) {
@@ -220,14 +231,14 @@ impl TabulatorState {
                     }
                 };
 
+                *transfer_map
+                    .entry(Allocatee::from_choice(new_choice))
+                    .or_default() += weighted.weight;
+
                 candidate_ballots
                     .entry(new_choice)
                     .or_insert_with(Vec::new)
-                    .push(ballot.clone());
-
-                *transfer_map
-                    .entry(Allocatee::from_choice(new_choice))
-                    .or_default() += 1;
+                    .push(weighted);
             }
 
             // Add data about transfers from the eliminated candidate to the transfers list.
@@ -238,6 +249,7 @@ impl TabulatorState {
                         from: *to_eliminate,
                         to: a,
                         count,
+                        shares: None,
                     })
                     .collect(),
             );
@@ -250,7 +262,7 @@ impl TabulatorState {
         transfers.sort_by_key(|x| match x.to {
             Allocatee::Exhausted => 0,
             Allocatee::Candidate(c) => {
-                -(candidate_ballots.get(&Choice::Vote(c)).unwrap().len() as i32)
+                -(ballot_count(candidate_ballots.get(&Choice::Vote(c)).unwrap()) as i64)
             }
         });
 
@@ -258,22 +270,67 @@ impl TabulatorState {
             candidate_ballots,
             transfers,
             eliminated,
+            rules: self.rules,
         }
     }
 }
 
+/// The majority threshold of a round, compared exactly with the leader's
+/// votes.
+fn majority_threshold(round: &TabulatorRound) -> MajorityThreshold {
+    let continuing = round.continuing_ballots as u64;
+    let leader = round
+        .allocations
+        .iter()
+        .filter(|a| a.allocatee != Allocatee::Exhausted)
+        .map(|a| a.votes as u64)
+        .max()
+        .unwrap_or(0);
+    let threshold = Ratio::new(continuing, 2);
+
+    MajorityThreshold {
+        threshold: ExactRatio::from_ratio(threshold),
+        leader_share: ExactRatio::new(leader, continuing),
+        decisive: Ratio::from_integer(leader) > threshold,
+    }
+}
+
 pub fn tabulate(
     ballots: &[NormalizedBallot],
     tabulation_options: &TabulationOptions,
 ) -> Vec<TabulatorRound> {
-    let mut state = TabulatorState::new(ballots);
+    tabulate_weighted(
+        ballots.iter().map(|ballot| (ballot.clone(), 1)),
+        tabulation_options,
+    )
+}
+
+/// Tabulate ballots given as (ballot, count) pairs, e.g. from a ranking
+/// pattern frequency table, where each pair stands for `count` identical
+/// ballots. This is equivalent to tabulating the ballots individually, but
+/// much faster when many ballots are identical.
+pub fn tabulate_weighted<I: IntoIterator<Item = (NormalizedBallot, u32)>>(
+    ballots: I,
+    tabulation_options: &TabulationOptions,
+) -> Vec<TabulatorRound> {
+    let mut state = TabulatorState::new(
+        ballots
+            .into_iter()
+            .map(|(ballot, weight)| WeightedBallot { ballot, weight })
+            .collect(),
+        RuleSet::from_options(tabulation_options),
+    );
     let mut rounds = Vec::new();
     let mut round_number = 0;
     let max_rounds = 1000; // Safety limit to prevent infinite loops
 
     loop {
-        let allocations = state.allocations(tabulation_options, round_number);
-        rounds.push(state.as_round(tabulation_options, round_number));
+        let allocations = state.allocations(round_number);
+        let mut round = state.as_round(round_number);
+        if tabulation_options.exact_thresholds.unwrap_or(false) {
+            round.threshold = Some(majority_threshold(&round));
+        }
+        rounds.push(round);
 
         crate::log_debug!(
             "    Round {}: {} candidates remaining",
@@ -299,3 +356,148 @@ pub fn tabulate(
 
     rounds
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::CandidateId;
+    use crate::read_metadata::read_meta;
+    use std::path::Path;
+
+    #[test]
+    fn test_single_elimination() {
+        // A leads; C and D together have fewer votes than B, so eager
+        // tabulation eliminates both at once.
+        let first_choices = [0, 0, 0, 0, 1, 1, 1, 2, 3];
+        let ballots: Vec<NormalizedBallot> = first_choices
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| NormalizedBallot::new(i.to_string(), vec![CandidateId(c)], false))
+            .collect();
+
+        let eager = tabulate(&ballots, &TabulationOptions::default());
+        assert_eq!(2, eager.len());
+
+        let single = tabulate(
+            &ballots,
+            &TabulationOptions {
+                eager: Some(false),
+                nyc_style: Some(false),
+                exact_thresholds: None,
+            },
+        );
+        assert_eq!(3, single.len());
+        assert_eq!(
+            vec![CandidateId(3)],
+            single[1]
+                .transfers
+                .iter()
+                .map(|t| t.from)
+                .collect::<Vec<_>>()
+        );
+
+        // Contests that leave `eager` unset are still tabulated eagerly.
+        let unset = tabulate(
+            &ballots,
+            &TabulationOptions {
+                eager: None,
+                nyc_style: Some(false),
+                exact_thresholds: None,
+            },
+        );
+        assert!(unset == eager);
+    }
+
+    #[test]
+    fn test_metadata_tabulation_unchanged() {
+        // Before single elimination, `eager` was ignored and every contest
+        // was tabulated eagerly. No election in the metadata turns it off,
+        // so none of their results change.
+        let meta_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("election-metadata");
+        for (file, jurisdiction) in read_meta(&meta_dir) {
+            for (election_key, election) in &jurisdiction.elections {
+                let eager = election.tabulation_options.as_ref().and_then(|o| o.eager);
+                assert_ne!(
+                    Some(false),
+                    eager,
+                    "{} in {} sets eager to false",
+                    election_key,
+                    file.display()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_exhausted_after_skipped_ranks() {
+        // Two ballots rank C, then A after two skipped ranks, so they are
+        // exhausted when C is eliminated rather than transferred to A.
+        let ballot = |c: u32| NormalizedBallot::new(c.to_string(), vec![CandidateId(c)], false);
+        let mut ballots = vec![
+            ballot(0),
+            ballot(0),
+            ballot(0),
+            ballot(1),
+            ballot(1),
+            ballot(1),
+        ];
+        for _ in 0..2 {
+            ballots.push(
+                NormalizedBallot::new("c".to_string(), vec![CandidateId(2), CandidateId(0)], false)
+                    .with_exhausted_after(Some(1)),
+            );
+        }
+
+        let rounds = tabulate(&ballots, &TabulationOptions::default());
+        let votes = |allocatee| {
+            rounds[1]
+                .allocations
+                .iter()
+                .find(|a| a.allocatee == allocatee)
+                .map(|a| a.votes)
+        };
+        assert_eq!(Some(3), votes(Allocatee::Candidate(CandidateId(0))));
+        assert_eq!(Some(2), votes(Allocatee::Exhausted));
+    }
+
+    #[test]
+    fn test_exact_threshold() {
+        // A is half a vote short of a majority, though its share shows as
+        // 50.0% in a report. Rounds are decided on whole votes, so the exact
+        // threshold is what shows the round isn't decisive.
+        let ballot = |c: u32| NormalizedBallot::new(c.to_string(), vec![CandidateId(c)], false);
+        let rounds = tabulate_weighted(
+            vec![
+                (ballot(0), 50_000_000),
+                (ballot(1), 49_999_999),
+                (ballot(2), 2),
+            ],
+            &TabulationOptions {
+                exact_thresholds: Some(true),
+                ..TabulationOptions::default()
+            },
+        );
+
+        let threshold = rounds[0].threshold.as_ref().unwrap();
+        assert_eq!(
+            (100_000_001, 2),
+            (
+                threshold.threshold.numerator,
+                threshold.threshold.denominator
+            )
+        );
+        assert_eq!(50_000_000, threshold.leader_share.numerator);
+        assert!(!threshold.decisive);
+        assert!(rounds[1].threshold.as_ref().unwrap().decisive);
+
+        let rounds = tabulate_weighted(vec![(ballot(0), 3)], &TabulationOptions::default());
+        assert!(rounds[0].threshold.is_none());
+    }
+
+    #[test]
+    fn test_is_final() {
+        assert!(Allocations::new(vec![(CandidateId(0), 3), (CandidateId(1), 2)], 1).is_final());
+        assert!(!Allocations::new(vec![(CandidateId(0), 2), (CandidateId(1), 2)], 0).is_final());
+        assert!(!Allocations::new(vec![], 5).is_final());
+    }
+}
@@ -0,0 +1,58 @@
+use crate::model::election::Choice;
+use crate::model::metadata::TabulationOptions;
+
+/// Jurisdiction-specific rules for counting ballots in a round. Quirks of
+/// a jurisdiction's tabulation belong here, as a rule set, rather than as
+/// conditions in the tabulation loop.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RuleSet {
+    /// Ballots without a continuing choice are exhausted in every round.
+    Standard,
+    /// New York City: ballots that are blank or overvoted at their top
+    /// ranking in the first round are inactive rather than exhausted, so
+    /// they are left out of the first round's exhausted count.
+    Nyc,
+}
+
+impl RuleSet {
+    pub fn from_options(tabulation_options: &TabulationOptions) -> RuleSet {
+        if tabulation_options.nyc_style.unwrap_or(false) {
+            RuleSet::Nyc
+        } else {
+            RuleSet::Standard
+        }
+    }
+
+    /// Whether ballots whose top remaining choice is the given undervote or
+    /// overvote count as exhausted in a round (numbered from 0).
+    pub fn is_exhausted(&self, choice: Choice, round_number: usize) -> bool {
+        match (self, choice) {
+            (_, Choice::Vote(_)) => false,
+            (RuleSet::Standard, _) => true,
+            (RuleSet::Nyc, _) => round_number > 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::CandidateId;
+
+    #[test]
+    fn test_nyc_first_round_inactive_ballots() {
+        let nyc = RuleSet::from_options(&TabulationOptions {
+            eager: Some(true),
+            nyc_style: Some(true),
+            exact_thresholds: None,
+        });
+        assert_eq!(RuleSet::Nyc, nyc);
+        assert!(!nyc.is_exhausted(Choice::Undervote, 0));
+        assert!(!nyc.is_exhausted(Choice::Overvote, 0));
+        assert!(nyc.is_exhausted(Choice::Overvote, 1));
+
+        let standard = RuleSet::from_options(&TabulationOptions::default());
+        assert!(standard.is_exhausted(Choice::Undervote, 0));
+        assert!(!standard.is_exhausted(Choice::Vote(CandidateId(0)), 0));
+    }
+}
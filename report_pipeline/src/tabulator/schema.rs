@@ -1,8 +1,12 @@
 use crate::model::election::{CandidateId, Choice};
+use num_rational::Ratio;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, Schema, SchemaObject, SubschemaValidation};
+use schemars::JsonSchema;
 use serde::de::{self, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct TabulatorRound {
     pub allocations: Vec<TabulatorAllocation>,
@@ -11,13 +15,77 @@ pub struct TabulatorRound {
     pub continuing_ballots: u32,
     pub transfers: Vec<Transfer>,
     //eliminated: Vec<u32>,
+    /// The round's majority threshold, with the `exactThresholds` option.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<MajorityThreshold>,
 }
 
-#[derive(Serialize, Deserialize)]
+/// A fraction, both exact and rounded to a float.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExactRatio {
+    /// Numerator of the fraction in lowest terms.
+    pub numerator: u64,
+    /// Denominator of the fraction in lowest terms.
+    pub denominator: u64,
+    /// The fraction as a float, for display only.
+    pub value: f64,
+}
+
+impl ExactRatio {
+    pub fn from_ratio(ratio: Ratio<u64>) -> ExactRatio {
+        ExactRatio {
+            numerator: *ratio.numer(),
+            denominator: *ratio.denom(),
+            value: *ratio.numer() as f64 / *ratio.denom() as f64,
+        }
+    }
+
+    /// `numerator / denominator` in lowest terms, or zero if the
+    /// denominator is zero.
+    pub fn new(numerator: u64, denominator: u64) -> ExactRatio {
+        if denominator == 0 {
+            ExactRatio::from_ratio(Ratio::from_integer(0))
+        } else {
+            ExactRatio::from_ratio(Ratio::new(numerator, denominator))
+        }
+    }
+}
+
+/// Whether a round's leader has a majority of its continuing ballots,
+/// decided in exact arithmetic so that a leader within a rounding error of
+/// half the ballots is not misclassified.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MajorityThreshold {
+    /// Half of the round's continuing ballots, which the leader must exceed.
+    pub threshold: ExactRatio,
+    /// The leader's votes as a fraction of continuing ballots.
+    pub leader_share: ExactRatio,
+    /// Whether the leader's votes exceed the threshold.
+    pub decisive: bool,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct TabulatorAllocation {
     pub allocatee: Allocatee,
     pub votes: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shares: Option<Shares>,
+}
+
+/// Votes as percentages, formatted for display when the report is
+/// generated so that every reader rounds them the same way, e.g. "45.3%".
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Ord, PartialOrd, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Shares {
+    /// Share of the round's continuing ballots. Absent for exhausted
+    /// ballots, which aren't continuing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub of_continuing: Option<String>,
+    /// Share of all ballots in the contest, including exhausted ones.
+    pub of_total: String,
 }
 
 #[derive(Clone, Hash, Eq, PartialEq, Ord, PartialOrd, Copy, Debug)]
@@ -90,10 +158,35 @@ impl<'de> Deserialize<'de> for Allocatee {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, Ord, PartialOrd, Eq)]
+impl JsonSchema for Allocatee {
+    fn schema_name() -> String {
+        "Allocatee".to_string()
+    }
+
+    /// A candidate ID, or `"X"` for exhausted ballots.
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let exhausted = SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            enum_values: Some(vec!["X".into()]),
+            ..Default::default()
+        };
+        SchemaObject {
+            subschemas: Some(Box::new(SubschemaValidation {
+                any_of: Some(vec![gen.subschema_for::<CandidateId>(), exhausted.into()]),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Ord, PartialOrd, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct Transfer {
     pub from: CandidateId,
     pub to: Allocatee,
     pub count: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shares: Option<Shares>,
 }
@@ -0,0 +1,258 @@
+use crate::model::election::Candidate;
+use crate::tabulator::{Allocatee, TabulatorRound};
+use std::fmt::Write;
+
+/// The votes of a candidate, or the exhausted ballots, in one round.
+struct Node {
+    id: String,
+    label: String,
+    votes: u32,
+}
+
+/// Votes flowing from a node in one round to a node in the next.
+struct Edge {
+    from: String,
+    to: String,
+    votes: u32,
+    /// Whether the votes stay with the same candidate (or stay exhausted),
+    /// rather than being transferred from an eliminated candidate.
+    carried_over: bool,
+}
+
+/// Graph of vote flows between candidates across the rounds of a contest,
+/// for rendering with Graphviz or Mermaid.
+pub struct TransferGraph {
+    /// Nodes of each round.
+    rounds: Vec<Vec<Node>>,
+    edges: Vec<Edge>,
+}
+
+fn node_id(round: usize, allocatee: Allocatee) -> String {
+    match allocatee {
+        Allocatee::Candidate(c) => format!("r{}_c{}", round + 1, c.0),
+        Allocatee::Exhausted => format!("r{}_x", round + 1),
+    }
+}
+
+fn votes_for(round: &TabulatorRound, allocatee: Allocatee) -> Option<u32> {
+    round
+        .allocations
+        .iter()
+        .find(|a| a.allocatee == allocatee)
+        .map(|a| a.votes)
+}
+
+/// Build the transfer graph of a contest's rounds.
+pub fn transfer_graph(candidates: &[Candidate], rounds: &[TabulatorRound]) -> TransferGraph {
+    let nodes = rounds
+        .iter()
+        .enumerate()
+        .map(|(i, round)| {
+            round
+                .allocations
+                .iter()
+                .filter(|a| a.allocatee != Allocatee::Exhausted || a.votes > 0)
+                .map(|allocation| Node {
+                    id: node_id(i, allocation.allocatee),
+                    label: match allocation.allocatee {
+                        Allocatee::Candidate(c) => candidates[c.0 as usize].display_name(),
+                        Allocatee::Exhausted => "Exhausted",
+                    }
+                    .to_string(),
+                    votes: allocation.votes,
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut edges = Vec::new();
+    for (i, pair) in rounds.windows(2).enumerate() {
+        let (previous, current) = (&pair[0], &pair[1]);
+
+        // Candidates still in the running, and ballots already exhausted,
+        // keep their votes from the previous round.
+        for allocation in &current.allocations {
+            if let Some(votes) = votes_for(previous, allocation.allocatee) {
+                if votes > 0 {
+                    edges.push(Edge {
+                        from: node_id(i, allocation.allocatee),
+                        to: node_id(i + 1, allocation.allocatee),
+                        votes,
+                        carried_over: true,
+                    });
+                }
+            }
+        }
+
+        for transfer in &current.transfers {
+            edges.push(Edge {
+                from: node_id(i, Allocatee::Candidate(transfer.from)),
+                to: node_id(i + 1, transfer.to),
+                votes: transfer.count,
+                carried_over: false,
+            });
+        }
+    }
+
+    TransferGraph {
+        rounds: nodes,
+        edges,
+    }
+}
+
+impl TransferGraph {
+    /// Render the graph in Graphviz DOT, with one cluster per round.
+    /// Transfers are drawn solid and carried-over votes dashed.
+    pub fn to_dot(&self) -> String {
+        let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+
+        let mut dot = String::new();
+        writeln!(dot, "digraph transfers {{").unwrap();
+        writeln!(dot, "  rankdir=LR;").unwrap();
+        writeln!(dot, "  node [shape=box];").unwrap();
+        for (i, nodes) in self.rounds.iter().enumerate() {
+            writeln!(dot, "  subgraph cluster_round_{} {{", i + 1).unwrap();
+            writeln!(dot, "    label=\"Round {}\";", i + 1).unwrap();
+            for node in nodes {
+                writeln!(
+                    dot,
+                    "    {} [label=\"{}\\n{}\"];",
+                    node.id,
+                    escape(&node.label),
+                    node.votes
+                )
+                .unwrap();
+            }
+            writeln!(dot, "  }}").unwrap();
+        }
+        for edge in &self.edges {
+            let style = if edge.carried_over {
+                ", style=dashed, color=gray"
+            } else {
+                ""
+            };
+            writeln!(
+                dot,
+                "  {} -> {} [label=\"{}\"{}];",
+                edge.from, edge.to, edge.votes, style
+            )
+            .unwrap();
+        }
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+
+    /// Render the graph as a Mermaid flowchart, with one subgraph per round.
+    /// Transfers are drawn solid and carried-over votes dotted.
+    pub fn to_mermaid(&self) -> String {
+        let escape = |s: &str| s.replace('"', "#quot;");
+
+        let mut mermaid = String::new();
+        writeln!(mermaid, "flowchart LR").unwrap();
+        for (i, nodes) in self.rounds.iter().enumerate() {
+            writeln!(mermaid, "  subgraph round_{} [\"Round {}\"]", i + 1, i + 1).unwrap();
+            for node in nodes {
+                writeln!(
+                    mermaid,
+                    "    {}[\"{}<br/>{}\"]",
+                    node.id,
+                    escape(&node.label),
+                    node.votes
+                )
+                .unwrap();
+            }
+            writeln!(mermaid, "  end").unwrap();
+        }
+        for edge in &self.edges {
+            let arrow = if edge.carried_over { "-.->" } else { "-->" };
+            writeln!(
+                mermaid,
+                "  {} {}|{}| {}",
+                edge.from, arrow, edge.votes, edge.to
+            )
+            .unwrap();
+        }
+        mermaid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::{CandidateId, CandidateType};
+    use crate::tabulator::{TabulatorAllocation, Transfer};
+
+    fn round(allocations: Vec<(Allocatee, u32)>, transfers: Vec<Transfer>) -> TabulatorRound {
+        TabulatorRound {
+            allocations: allocations
+                .into_iter()
+                .map(|(allocatee, votes)| TabulatorAllocation {
+                    allocatee,
+                    votes,
+                    shares: None,
+                })
+                .collect(),
+            undervote: 0,
+            overvote: 0,
+            continuing_ballots: 0,
+            transfers,
+            threshold: None,
+        }
+    }
+
+    #[test]
+    fn test_transfer_graph() {
+        let candidates = vec![
+            Candidate::new("Alice".to_string(), CandidateType::Regular),
+            Candidate::new("Bob \"Bobby\" Jones".to_string(), CandidateType::Regular),
+            Candidate::new("Carol".to_string(), CandidateType::Regular),
+        ];
+        let (alice, bob, carol) = (
+            Allocatee::Candidate(CandidateId(0)),
+            Allocatee::Candidate(CandidateId(1)),
+            Allocatee::Candidate(CandidateId(2)),
+        );
+        let rounds = vec![
+            round(vec![(alice, 5), (bob, 4), (carol, 3)], vec![]),
+            round(
+                vec![(alice, 6), (bob, 5), (Allocatee::Exhausted, 1)],
+                vec![
+                    Transfer {
+                        from: CandidateId(2),
+                        to: alice,
+                        count: 1,
+                        shares: None,
+                    },
+                    Transfer {
+                        from: CandidateId(2),
+                        to: bob,
+                        count: 1,
+                        shares: None,
+                    },
+                    Transfer {
+                        from: CandidateId(2),
+                        to: Allocatee::Exhausted,
+                        count: 1,
+                        shares: None,
+                    },
+                ],
+            ),
+        ];
+
+        let graph = transfer_graph(&candidates, &rounds);
+        assert_eq!(2, graph.rounds.len());
+        // Two carried over, three transferred.
+        assert_eq!(5, graph.edges.len());
+
+        let dot = graph.to_dot();
+        assert!(dot.contains("    r1_c1 [label=\"Bob \\\"Bobby\\\" Jones\\n4\"];\n"));
+        assert!(dot.contains("  r1_c0 -> r2_c0 [label=\"5\", style=dashed, color=gray];\n"));
+        assert!(dot.contains("  r1_c2 -> r2_x [label=\"1\"];\n"));
+
+        let mermaid = graph.to_mermaid();
+        assert!(mermaid.starts_with("flowchart LR\n  subgraph round_1 [\"Round 1\"]\n"));
+        assert!(mermaid.contains("    r1_c1[\"Bob #quot;Bobby#quot; Jones<br/>4\"]\n"));
+        assert!(mermaid.contains("  r1_c0 -.->|5| r2_c0\n"));
+        assert!(mermaid.contains("  r1_c2 -->|1| r2_c1\n"));
+    }
+}
@@ -1,7 +1,8 @@
 use sha1::{Digest, Sha1};
+use sha2::Sha256;
 use std::fs::File;
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Return the SHA-1 hash of the file at the given location.
 pub fn hash_file(path: PathBuf) -> String {
@@ -11,3 +12,11 @@ pub fn hash_file(path: PathBuf) -> String {
     let hash = hasher.finalize();
     format!("{:x}", hash)
 }
+
+/// Return the SHA-256 hash of the file at the given location.
+pub fn hash_file_sha256(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
@@ -1,59 +1,426 @@
-use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use crate::util::storage::{storage_for, Storage};
+use flate2::{
+    read::{GzDecoder, MultiGzDecoder},
+    write::GzEncoder,
+    Compression,
+};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use serde_json::Value;
 use std::ffi::OsString;
 use std::fs::File;
-use std::fs::OpenOptions;
-use std::io::Read;
-use std::io::{BufReader, BufWriter};
-use std::path::Path;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Extensions of compressed raw data files, which are decompressed as they
+/// are read.
+const COMPRESSED_EXTENSIONS: &[&str] = &["gz", "zst"];
+
+/// How many times a raw data file is opened or read before giving up, when
+/// it fails with an error that may be transient (see `is_transient`).
+const RAW_READ_ATTEMPTS: u32 = 4;
+
+/// The delay before retrying a raw data file for the first time, doubled
+/// for each subsequent attempt.
+const RAW_RETRY_DELAY: Duration = Duration::from_millis(250);
 
 /// Read a JSON-serialized file into an object. Applies GZ decompression
-/// if the file path ends in `.gz`.
+/// if the file path ends in `.gz`. Paths of the form `s3://bucket/key` are
+/// read from S3 (see `storage_for`).
 pub fn read_serialized<T: DeserializeOwned>(path: &Path) -> T {
+    read_serialized_from(&*storage_for(path), path)
+}
+
+/// Read a JSON-serialized file into an object from the given storage, as
+/// for `read_serialized`.
+pub fn read_serialized_from<T: DeserializeOwned>(storage: &dyn Storage, path: &Path) -> T {
     // Only log for non-preprocessed files to reduce noise
     if !path.to_string_lossy().contains("normalized.json.gz") {
         crate::log_debug!("Reading {}", path.to_str().unwrap());
     }
-    let file = File::open(path).unwrap();
+    let contents = storage
+        .read(path)
+        .unwrap_or_else(|e| panic!("Failed to read {}: {}", path.display(), e));
 
     if path.extension() == Some(&OsString::from("gz")) {
-        // For some reason, reading from a BufReader fails so we instead
-        // load the whole file into memory. As it happens, this should be
-        // faster anyway because of
+        // Decompress the whole file before parsing, which is faster than
+        // parsing from a reader because of
         // https://github.com/serde-rs/json/issues/160
-        let mut gzfile = GzDecoder::new(file);
+        let mut gzfile = GzDecoder::new(&contents[..]);
         let mut contents = String::new();
         gzfile.read_to_string(&mut contents).unwrap();
         serde_json::from_str(&contents).unwrap()
     } else {
-        let reader = BufReader::new(file);
-        serde_json::from_reader(reader).unwrap()
+        serde_json::from_slice(&contents).unwrap()
+    }
+}
+
+/// The name of a raw data file without its compression extension, if any,
+/// e.g. `CvrExport_1.json` for `CvrExport_1.json.zst`.
+pub fn uncompressed_name(filename: &str) -> &str {
+    COMPRESSED_EXTENSIONS
+        .iter()
+        .find_map(|ext| filename.strip_suffix(ext)?.strip_suffix('.'))
+        .unwrap_or(filename)
+}
+
+/// Open a raw data file, decompressing it if its name ends in `.gz` or
+/// `.zst`. If `path` doesn't exist but a compressed copy of it does (e.g.
+/// `ballots.txt.gz` for `ballots.txt`), the copy is read instead, so raw
+/// data can be stored compressed without changing the metadata. Transient
+/// errors opening or reading the file, as on a network filesystem, are
+/// retried with backoff (see `RetryingReader`).
+pub fn open_raw(path: &Path) -> io::Result<Box<dyn Read>> {
+    let path = if path.exists() {
+        path.to_path_buf()
+    } else {
+        COMPRESSED_EXTENSIONS
+            .iter()
+            .map(|ext| {
+                let mut compressed = path.as_os_str().to_owned();
+                compressed.push(".");
+                compressed.push(ext);
+                PathBuf::from(compressed)
+            })
+            .find(|compressed| compressed.exists())
+            .unwrap_or_else(|| path.to_path_buf())
+    };
+
+    let file = RetryingReader::open(RAW_RETRY_DELAY, {
+        let path = path.clone();
+        move || File::open(&path)
+    })?;
+    let inner: Box<dyn Read> = match path.extension().and_then(|e| e.to_str()) {
+        Some("gz") => Box::new(MultiGzDecoder::new(BufReader::new(file))),
+        Some("zst") => Box::new(zstd::Decoder::new(file)?),
+        _ => Box::new(file),
+    };
+    Ok(Box::new(RawReader { path, inner }))
+}
+
+/// A decoded raw data file, whose read errors say which file failed.
+struct RawReader {
+    path: PathBuf,
+    inner: Box<dyn Read>,
+}
+
+impl Read for RawReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner
+            .read(buf)
+            .map_err(|e| describe_error(&self.path, e))
+    }
+}
+
+/// Whether an I/O error may go away if the operation is retried, as when a
+/// file on a network filesystem is briefly unavailable.
+fn is_transient(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::Interrupted
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::WouldBlock
+            | io::ErrorKind::ResourceBusy
+            | io::ErrorKind::StaleNetworkFileHandle
+    ) || e.raw_os_error() == Some(EIO)
+}
+
+/// `EIO`, which network filesystems return for reads that time out.
+const EIO: i32 = 5;
+
+/// An error reading a raw data file, with the file's path and, if it ended
+/// before it should have, a note that it is truncated.
+fn describe_error(path: &Path, e: io::Error) -> io::Error {
+    let note = if e.kind() == io::ErrorKind::UnexpectedEof {
+        " (the file is truncated)"
+    } else {
+        ""
+    };
+    io::Error::new(e.kind(), format!("{}{}: {}", path.display(), note, e))
+}
+
+/// A reader that, when opening or reading fails with a transient error,
+/// waits, reopens the underlying reader, seeks back to where it left off
+/// and tries again, doubling the wait each time, up to `RAW_READ_ATTEMPTS`
+/// attempts in all.
+struct RetryingReader<R, F> {
+    reader: R,
+    reopen: F,
+    position: u64,
+    delay: Duration,
+}
+
+impl<R: Read + Seek, F: FnMut() -> io::Result<R>> RetryingReader<R, F> {
+    fn open(delay: Duration, mut reopen: F) -> io::Result<Self> {
+        let reader = Self::retry(delay, &mut reopen)?;
+        Ok(RetryingReader {
+            reader,
+            reopen,
+            position: 0,
+            delay,
+        })
+    }
+
+    /// Call `attempt` until it succeeds, fails with an error that isn't
+    /// transient, or has been tried `RAW_READ_ATTEMPTS` times.
+    fn retry<T>(delay: Duration, mut attempt: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+        let mut delay = delay;
+        for remaining in (0..RAW_READ_ATTEMPTS).rev() {
+            match attempt() {
+                Err(e) if remaining > 0 && is_transient(&e) => {
+                    crate::log_warn!("{}; retrying in {:?}", e, delay);
+                    std::thread::sleep(delay);
+                    delay *= 2;
+                }
+                Err(e) if is_transient(&e) => {
+                    return Err(io::Error::new(
+                        e.kind(),
+                        format!("{} (after {} attempts)", e, RAW_READ_ATTEMPTS),
+                    ))
+                }
+                result => return result,
+            }
+        }
+        unreachable!()
+    }
+}
+
+impl<R: Read + Seek, F: FnMut() -> io::Result<R>> Read for RetryingReader<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut first = true;
+        let RetryingReader {
+            reader,
+            reopen,
+            position,
+            delay,
+        } = self;
+        let read = Self::retry(*delay, || {
+            if !std::mem::take(&mut first) {
+                *reader = reopen()?;
+                reader.seek(SeekFrom::Start(*position))?;
+            }
+            reader.read(buf)
+        })?;
+        *position += read as u64;
+        Ok(read)
     }
 }
 
 /// Write the given object as JSON. Applies GZ compression if the file
 /// path ends in `.gz`. Creates the file if it doesn't exist, otherwise
-/// overwrites it.
+/// overwrites it. Paths of the form `s3://bucket/key` are written to S3.
 pub fn write_serialized<T: Serialize>(path: &Path, value: &T) {
+    write_serialized_to(&*storage_for(path), path, value)
+}
+
+/// Write the given object as JSON to the given storage, as for
+/// `write_serialized`.
+pub fn write_serialized_to<T: Serialize>(storage: &dyn Storage, path: &Path, value: &T) {
     // Only log for non-preprocessed files to reduce noise
     if !path.to_string_lossy().contains("normalized.json.gz") {
         crate::log_debug!("Writing {}", path.to_str().unwrap());
     }
 
-    let file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(path)
+    let contents = if path.extension() == Some(&OsString::from("gz")) {
+        let mut gzfile = GzEncoder::new(Vec::new(), Compression::best());
+        serde_json::to_writer(&mut gzfile, &value).unwrap();
+        gzfile.finish().unwrap()
+    } else {
+        serde_json::to_vec_pretty(&value).unwrap()
+    };
+    storage
+        .write(path, &contents)
+        .unwrap_or_else(|e| panic!("Failed to write {}: {}", path.display(), e));
+}
+
+/// Serialize the given object as canonical JSON: object keys are sorted,
+/// regardless of the order of fields or map iteration, and the output is
+/// pretty-printed with a trailing newline. Serializing the same value
+/// always produces the same bytes.
+pub fn to_canonical_json<T: Serialize>(value: &T) -> String {
+    // Without the `preserve_order` feature, `serde_json::Value` stores
+    // objects in a `BTreeMap`, so converting through it sorts all keys.
+    let value: Value = serde_json::to_value(value).unwrap();
+    let mut json = serde_json::to_string_pretty(&value).unwrap();
+    json.push('\n');
+    json
+}
+
+/// Write the given object as canonical JSON (see `to_canonical_json`).
+/// Used for generated files that are checked into the reports repo, so
+/// that regenerating them from the same inputs produces no diff.
+pub fn write_canonical<T: Serialize>(path: &Path, value: &T) {
+    write_canonical_to(&*storage_for(path), path, value)
+}
+
+/// Write the given object as canonical JSON to the given storage, as for
+/// `write_canonical`.
+pub fn write_canonical_to<T: Serialize>(storage: &dyn Storage, path: &Path, value: &T) {
+    crate::log_debug!("Writing {}", path.to_str().unwrap());
+
+    storage
+        .write(path, to_canonical_json(value).as_bytes())
+        .unwrap_or_else(|e| panic!("Failed to write {}: {}", path.display(), e));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::storage::MemoryStorage;
+    use crate::util::TempDir;
+    use std::collections::HashMap;
+    use std::io::Write;
+
+    #[derive(Serialize)]
+    struct Example {
+        zebra: u32,
+        apple: HashMap<String, u32>,
+    }
+
+    #[test]
+    fn test_canonical_json_sorts_keys() {
+        let apple: HashMap<String, u32> = (0..20).map(|i| (format!("k{}", i), i)).collect();
+        let json = to_canonical_json(&Example { zebra: 1, apple });
+
+        assert!(json.find("\"apple\"").unwrap() < json.find("\"zebra\"").unwrap());
+        assert!(json.find("\"k10\"").unwrap() < json.find("\"k2\"").unwrap());
+        assert!(json.ends_with("}\n"));
+    }
+
+    #[test]
+    fn test_serialized_round_trip() {
+        let storage = MemoryStorage::default();
+        let value: HashMap<String, u32> = (0..3).map(|i| (format!("k{}", i), i)).collect();
+
+        for path in ["a/value.json", "a/value.json.gz"] {
+            write_serialized_to(&storage, Path::new(path), &value);
+            let read: HashMap<String, u32> = read_serialized_from(&storage, Path::new(path));
+            assert_eq!(value, read);
+        }
+        assert_ne!(
+            storage.read(Path::new("a/value.json")).unwrap(),
+            storage.read(Path::new("a/value.json.gz")).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_uncompressed_name() {
+        assert_eq!(
+            "CvrExport_1.json",
+            uncompressed_name("CvrExport_1.json.zst")
+        );
+        assert_eq!("ballots.txt", uncompressed_name("ballots.txt.gz"));
+        assert_eq!("ballots.txt", uncompressed_name("ballots.txt"));
+    }
+
+    #[test]
+    fn test_open_raw_compressed() {
+        let dir = TempDir::new("open-raw");
+
+        let mut gz = GzEncoder::new(
+            File::create(dir.join("a.txt.gz")).unwrap(),
+            Compression::fast(),
+        );
+        gz.write_all(b"gzipped").unwrap();
+        gz.finish().unwrap();
+        std::fs::write(
+            dir.join("b.txt.zst"),
+            zstd::encode_all(&b"zstd"[..], 0).unwrap(),
+        )
         .unwrap();
+        std::fs::write(dir.join("c.txt"), b"plain").unwrap();
 
-    if path.extension() == Some(&OsString::from("gz")) {
-        let gzfile = GzEncoder::new(file, Compression::best());
-        let writer = BufWriter::new(gzfile);
-        serde_json::to_writer(writer, &value).unwrap();
-    } else {
-        let writer = BufWriter::new(file);
-        serde_json::to_writer_pretty(writer, &value).unwrap();
+        let read = |name: &str| io::read_to_string(open_raw(&dir.join(name)).unwrap()).unwrap();
+        assert_eq!("gzipped", read("a.txt.gz"));
+        assert_eq!("gzipped", read("a.txt"));
+        assert_eq!("zstd", read("b.txt"));
+        assert_eq!("plain", read("c.txt"));
+        assert!(open_raw(&dir.join("d.txt")).is_err());
+    }
+
+    #[test]
+    fn test_open_raw_truncated() {
+        let dir = TempDir::new("open-raw-truncated");
+
+        let mut gz = GzEncoder::new(Vec::new(), Compression::fast());
+        gz.write_all(&[b'x'; 1000]).unwrap();
+        let gz = gz.finish().unwrap();
+        std::fs::write(dir.join("a.txt.gz"), &gz[..gz.len() / 2]).unwrap();
+
+        let error = io::read_to_string(open_raw(&dir.join("a.txt")).unwrap()).unwrap_err();
+        assert!(error.to_string().contains("a.txt.gz"));
+    }
+
+    /// A reader over `data` that fails with a transient error once, after
+    /// `fail_at` bytes.
+    struct Flaky {
+        data: io::Cursor<Vec<u8>>,
+        fail_at: Option<u64>,
+    }
+
+    impl Read for Flaky {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if let Some(fail_at) = self.fail_at {
+                if self.data.position() >= fail_at {
+                    return Err(io::Error::from(io::ErrorKind::TimedOut));
+                }
+                let len = buf.len().min((fail_at - self.data.position()) as usize);
+                return self.data.read(&mut buf[..len]);
+            }
+            self.data.read(buf)
+        }
+    }
+
+    impl Seek for Flaky {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.data.seek(pos)
+        }
+    }
+
+    #[test]
+    fn test_retrying_reader_resumes() {
+        let data: Vec<u8> = (0..=255).collect();
+        let mut opened = 0;
+        let reader = RetryingReader::open(Duration::ZERO, || {
+            opened += 1;
+            Ok(Flaky {
+                data: io::Cursor::new(data.clone()),
+                fail_at: (opened == 1).then_some(100),
+            })
+        })
+        .unwrap();
+
+        let mut read = Vec::new();
+        BufReader::with_capacity(16, reader)
+            .read_to_end(&mut read)
+            .unwrap();
+        assert_eq!(data, read);
+    }
+
+    #[test]
+    fn test_retrying_reader_gives_up() {
+        let mut opened = 0;
+        let result = RetryingReader::<Flaky, _>::open(Duration::ZERO, || {
+            opened += 1;
+            Err(io::Error::from(io::ErrorKind::TimedOut))
+        });
+        assert!(result
+            .map(|_| ())
+            .unwrap_err()
+            .to_string()
+            .contains("after 4 attempts"));
+        assert_eq!(RAW_READ_ATTEMPTS, opened);
+
+        let mut opened = 0;
+        let result = RetryingReader::<Flaky, _>::open(Duration::ZERO, || {
+            opened += 1;
+            Err(io::Error::from(io::ErrorKind::NotFound))
+        });
+        assert_eq!(
+            io::ErrorKind::NotFound,
+            result.map(|_| ()).unwrap_err().kind()
+        );
+        assert_eq!(1, opened);
     }
 }
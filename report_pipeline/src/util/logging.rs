@@ -1,5 +1,6 @@
 use colored::*;
 use std::env;
+use std::sync::atomic::{AtomicU8, Ordering};
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
 pub enum LogLevel {
@@ -27,51 +28,82 @@ impl LogLevel {
             .map(|s| Self::from_str(&s))
             .unwrap_or(LogLevel::Warn)
     }
+
+    /// The level for the `--quiet` and `--verbose` flags: errors only if
+    /// quiet, otherwise one level above the default of warnings for each
+    /// `--verbose`. `None` if neither flag was given.
+    pub fn from_flags(quiet: bool, verbose: u8) -> Option<Self> {
+        match (quiet, verbose) {
+            (true, _) => Some(LogLevel::Error),
+            (false, 0) => None,
+            (false, 1) => Some(LogLevel::Info),
+            (false, 2) => Some(LogLevel::Debug),
+            (false, _) => Some(LogLevel::Trace),
+        }
+    }
+
+    fn from_u8(level: u8) -> Self {
+        match level {
+            0 => LogLevel::Error,
+            1 => LogLevel::Warn,
+            2 => LogLevel::Info,
+            3 => LogLevel::Debug,
+            _ => LogLevel::Trace,
+        }
+    }
 }
 
 pub struct Logger {
-    level: LogLevel,
+    level: AtomicU8,
 }
 
 impl Logger {
     pub fn new() -> Self {
         Self {
-            level: LogLevel::from_env(),
+            level: AtomicU8::new(LogLevel::from_env() as u8),
         }
     }
 
+    pub fn level(&self) -> LogLevel {
+        LogLevel::from_u8(self.level.load(Ordering::Relaxed))
+    }
+
+    pub fn set_level(&self, level: LogLevel) {
+        self.level.store(level as u8, Ordering::Relaxed);
+    }
+
     pub fn error(&self, msg: &str) {
-        if self.level >= LogLevel::Error {
+        if self.level() >= LogLevel::Error {
             eprintln!("{}", msg.red());
         }
     }
 
     pub fn warn(&self, msg: &str) {
-        if self.level >= LogLevel::Warn {
+        if self.level() >= LogLevel::Warn {
             eprintln!("{}", msg.yellow());
         }
     }
 
     pub fn info(&self, msg: &str) {
-        if self.level >= LogLevel::Info {
+        if self.level() >= LogLevel::Info {
             eprintln!("{}", msg);
         }
     }
 
     pub fn debug(&self, msg: &str) {
-        if self.level >= LogLevel::Debug {
+        if self.level() >= LogLevel::Debug {
             eprintln!("{}", msg.bright_black());
         }
     }
 
     pub fn trace(&self, msg: &str) {
-        if self.level >= LogLevel::Trace {
+        if self.level() >= LogLevel::Trace {
             eprintln!("{}", msg.bright_black());
         }
     }
 
     pub fn race(&self, jurisdiction: &str, election: &str, office: &str) {
-        if self.level >= LogLevel::Warn {
+        if self.level() >= LogLevel::Warn {
             eprintln!(
                 "{} {} - {} - {}",
                 "🏁".green(),
@@ -94,6 +126,25 @@ lazy_static::lazy_static! {
     pub static ref LOG: Logger = Logger::new();
 }
 
+/// Configure CLI-wide output: the log level, if `--quiet` or `--verbose`
+/// was given (see `LogLevel::from_flags`), overriding
+/// `RANKED_VOTE_LOG_LEVEL`, which in turn overrides `config_level` from a
+/// config file, and whether output is colored. Color is turned off by
+/// `--no-color` or a non-empty `NO_COLOR` environment variable
+/// (https://no-color.org).
+pub fn configure_output(quiet: bool, verbose: u8, no_color: bool, config_level: Option<&str>) {
+    let config_level = config_level
+        .filter(|_| env::var_os("RANKED_VOTE_LOG_LEVEL").is_none())
+        .map(LogLevel::from_str);
+    if let Some(level) = LogLevel::from_flags(quiet, verbose).or(config_level) {
+        LOG.set_level(level);
+    }
+    let no_color_env = env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    if no_color || no_color_env {
+        colored::control::set_override(false);
+    }
+}
+
 // Convenience macros
 #[macro_export]
 macro_rules! log_error {
@@ -136,3 +187,25 @@ macro_rules! log_race {
         $crate::util::LOG.race($jurisdiction, $election, $office);
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_from_flags() {
+        assert_eq!(None, LogLevel::from_flags(false, 0));
+        assert_eq!(Some(LogLevel::Error), LogLevel::from_flags(true, 0));
+        assert_eq!(Some(LogLevel::Info), LogLevel::from_flags(false, 1));
+        assert_eq!(Some(LogLevel::Trace), LogLevel::from_flags(false, 5));
+    }
+
+    #[test]
+    fn test_set_level() {
+        let logger = Logger::new();
+        logger.set_level(LogLevel::Debug);
+        assert_eq!(LogLevel::Debug, logger.level());
+        logger.set_level(LogLevel::Error);
+        assert_eq!(LogLevel::Error, logger.level());
+    }
+}
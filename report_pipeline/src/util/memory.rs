@@ -0,0 +1,255 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex};
+
+/// Limits the total estimated memory of tasks running at once. Tasks
+/// reserve their estimate before starting and block until it fits in the
+/// budget. A task estimated to need more than the whole budget still runs,
+/// but only once nothing else holds a reservation.
+pub struct MemoryBudget {
+    limit: u64,
+    in_use: Mutex<u64>,
+    released: Condvar,
+}
+
+/// A reservation against a `MemoryBudget`, released when dropped.
+pub struct MemoryReservation<'a> {
+    budget: &'a MemoryBudget,
+    bytes: u64,
+}
+
+impl MemoryBudget {
+    pub fn new(limit: u64) -> MemoryBudget {
+        MemoryBudget {
+            limit,
+            in_use: Mutex::new(0),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Block until `bytes` fits in the budget, then reserve it.
+    pub fn reserve(&self, bytes: u64) -> MemoryReservation<'_> {
+        let mut in_use = self.in_use.lock().unwrap();
+        while *in_use > 0 && *in_use + bytes > self.limit {
+            in_use = self.released.wait(in_use).unwrap();
+        }
+        *in_use += bytes;
+
+        MemoryReservation {
+            budget: self,
+            bytes,
+        }
+    }
+
+    fn release(&self, bytes: u64) {
+        *self.in_use.lock().unwrap() -= bytes;
+        self.released.notify_all();
+    }
+}
+
+impl<'a> Drop for MemoryReservation<'a> {
+    fn drop(&mut self) {
+        self.budget.release(self.bytes);
+    }
+}
+
+/// Whether `CountingAllocator` is the global allocator, i.e. it has
+/// allocated anything.
+static COUNTING: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Bytes allocated and not yet freed by the current thread. Memory freed
+    /// by another thread than the one that allocated it is counted against
+    /// the thread that freed it, so this can be negative.
+    static ALLOCATED: Cell<i64> = const { Cell::new(0) };
+    /// The most `ALLOCATED` has been since `track_peak_memory` started.
+    static PEAK: Cell<i64> = const { Cell::new(0) };
+}
+
+/// A global allocator that counts the bytes each thread allocates, so that
+/// `track_peak_memory` can measure the peak memory of a task. Install it in
+/// a binary with `#[global_allocator]`.
+pub struct CountingAllocator;
+
+impl CountingAllocator {
+    fn count(delta: i64) {
+        // `try_with` fails while the thread's locals are being destroyed,
+        // when allocations are no longer counted.
+        let _ = ALLOCATED.try_with(|allocated| {
+            let now = allocated.get() + delta;
+            allocated.set(now);
+            let _ = PEAK.try_with(|peak| peak.set(peak.get().max(now)));
+        });
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if !COUNTING.load(Ordering::Relaxed) {
+            COUNTING.store(true, Ordering::Relaxed);
+        }
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            Self::count(layout.size() as i64);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        Self::count(-(layout.size() as i64));
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            Self::count(layout.size() as i64);
+        }
+        ptr
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            Self::count(new_size as i64 - layout.size() as i64);
+        }
+        new_ptr
+    }
+}
+
+/// Run `task` and return its result with the peak memory, in bytes, it
+/// allocated on the current thread beyond what was allocated when it
+/// started. Memory allocated by other threads the task starts or runs work
+/// on is not counted. The peak is `None` unless `CountingAllocator` is the
+/// global allocator.
+pub fn track_peak_memory<T>(task: impl FnOnce() -> T) -> (T, Option<u64>) {
+    let start = ALLOCATED.with(Cell::get);
+    let outer_peak = PEAK.with(|peak| peak.replace(start));
+    let result = task();
+    let peak = PEAK.with(|peak| {
+        let task_peak = peak.get();
+        // Restore the peak of an enclosing `track_peak_memory`.
+        peak.set(outer_peak.max(task_peak));
+        task_peak
+    });
+
+    let counting = COUNTING.load(Ordering::Relaxed);
+    (result, counting.then(|| (peak - start).max(0) as u64))
+}
+
+/// The peak resident set size of the process so far, in bytes, from
+/// `/proc/self/status`. `None` where that isn't available (e.g. macOS).
+pub fn peak_rss() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let kib = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))?
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(kib << 10)
+}
+
+/// Parse a byte size such as `512M`, `8G` or `1073741824`. Suffixes are
+/// binary (`K` = 1024) and case-insensitive, with an optional trailing `B`.
+pub fn parse_size(size: &str) -> Result<u64, String> {
+    let upper = size.trim().to_uppercase();
+    let digits = upper.strip_suffix('B').unwrap_or(&upper);
+
+    let (number, multiplier) = match digits.chars().last() {
+        Some('K') => (&digits[..digits.len() - 1], 1 << 10),
+        Some('M') => (&digits[..digits.len() - 1], 1 << 20),
+        Some('G') => (&digits[..digits.len() - 1], 1 << 30),
+        Some('T') => (&digits[..digits.len() - 1], 1 << 40),
+        _ => (digits, 1),
+    };
+
+    let number = number
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| format!("Invalid size: {}", size))?;
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("Size is too large: {}", size))
+}
+
+/// Format a byte size for display, e.g. `1.5G`, using the same binary
+/// suffixes as `parse_size`.
+pub fn format_size(bytes: u64) -> String {
+    const SUFFIXES: [&str; 4] = ["K", "M", "G", "T"];
+    if bytes < 1 << 10 {
+        return format!("{}B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut suffix = 0;
+    while size >= 1024.0 && suffix < SUFFIXES.len() - 1 {
+        size /= 1024.0;
+        suffix += 1;
+    }
+    format!("{:.1}{}", size, SUFFIXES[suffix])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    #[test]
+    fn test_track_peak_memory() {
+        let (len, outer) = track_peak_memory(|| {
+            let (_, inner) = track_peak_memory(|| vec![0u8; 4 << 20].len());
+            assert!(inner.unwrap() >= 4 << 20);
+            vec![0u8; 1 << 20].len()
+        });
+        assert_eq!(1 << 20, len);
+        // The inner task's peak counts towards the outer task's.
+        assert!(outer.unwrap() >= 4 << 20);
+    }
+
+    #[test]
+    fn test_peak_rss() {
+        if cfg!(target_os = "linux") {
+            assert!(peak_rss().unwrap() > 0);
+        }
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(Ok(1024), parse_size("1024"));
+        assert_eq!(Ok(512 << 20), parse_size("512M"));
+        assert_eq!(Ok(8 << 30), parse_size("8g"));
+        assert_eq!(Ok(2 << 30), parse_size("2GB"));
+        assert!(parse_size("lots").is_err());
+        assert_eq!(
+            Err("Size is too large: 20000000T".to_string()),
+            parse_size("20000000T")
+        );
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!("512B", format_size(512));
+        assert_eq!("1.5K", format_size(1536));
+        assert_eq!("8.0G", format_size(8 << 30));
+        assert_eq!("2048.0T", format_size(2 << 50));
+    }
+
+    #[test]
+    fn test_oversized_reservation_runs_alone() {
+        let budget = MemoryBudget::new(100);
+        {
+            let _reservation = budget.reserve(250);
+            assert_eq!(250, *budget.in_use.lock().unwrap());
+        }
+        assert_eq!(0, *budget.in_use.lock().unwrap());
+
+        let _a = budget.reserve(40);
+        let _b = budget.reserve(60);
+        assert_eq!(100, *budget.in_use.lock().unwrap());
+    }
+}
@@ -1,11 +1,30 @@
 mod hash;
 mod io;
 mod logging;
+mod memory;
 mod path;
+mod signing;
+mod storage;
 mod string;
+mod threads;
 
-pub use hash::hash_file;
-pub use io::{read_serialized, write_serialized};
-pub use logging::LOG;
+pub use hash::{hash_file, hash_file_sha256};
+pub use io::{
+    open_raw, read_serialized, read_serialized_from, to_canonical_json, uncompressed_name,
+    write_canonical, write_canonical_to, write_serialized,
+};
+pub use logging::{configure_output, LOG};
+pub use memory::{
+    format_size, parse_size, peak_rss, track_peak_memory, CountingAllocator, MemoryBudget,
+};
 pub use path::get_files_from_path;
+#[cfg(test)]
+pub use path::TempDir;
+pub use signing::{
+    public_key_hex, read_signing_key, read_verifying_key, sign_file, signature_path, verify_file,
+};
+#[cfg(test)]
+pub use storage::MemoryStorage;
+pub use storage::{storage_for, Storage};
 pub use string::UnicodeString;
+pub use threads::{configure_threads, run_with_timeout, thread_pool, TimeoutGuard};
@@ -1,6 +1,10 @@
 use std::fs;
 use std::io::{self};
+#[cfg(test)]
+use std::ops::Deref;
 use std::path::{Path, PathBuf};
+#[cfg(test)]
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 /// Crawl a directory tree, appending non-hidden files encountered to
 /// a passed mutable `result` vector.
@@ -10,9 +14,14 @@ fn walk_path(path: &Path, result: &mut Vec<PathBuf>) -> io::Result<()> {
         return Ok(());
     }
     if path.is_dir() {
-        for entry in fs::read_dir(path)? {
-            let entry = entry?;
-            walk_path(&entry.path(), result)?;
+        // Visit entries in sorted order so that results don't depend on
+        // the order the filesystem happens to return them in.
+        let mut entries = fs::read_dir(path)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<io::Result<Vec<PathBuf>>>()?;
+        entries.sort();
+        for entry in entries {
+            walk_path(&entry, result)?;
         }
     } else {
         result.push(PathBuf::from(path))
@@ -31,3 +40,40 @@ pub fn get_files_from_path(path: &Path) -> io::Result<Vec<PathBuf>> {
         panic!("Path {} does not exist.", path.to_string_lossy())
     }
 }
+
+/// A directory for a test under the system temp directory, removed with
+/// its contents when dropped, so that it is cleaned up even if the test
+/// fails. Each is unique, so tests running in parallel don't collide.
+#[cfg(test)]
+pub struct TempDir(PathBuf);
+
+#[cfg(test)]
+impl TempDir {
+    pub fn new(name: &str) -> TempDir {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "ranked-vote-{}-{}-{}",
+            name,
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+}
+
+#[cfg(test)]
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,131 @@
+use super::storage::Storage;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Extension appended to a file's name to get the name of its detached
+/// signature, e.g. `report.json.sig`.
+pub const SIGNATURE_EXTENSION: &str = "sig";
+
+/// Path of the detached signature of a file.
+pub fn signature_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(SIGNATURE_EXTENSION);
+    path.with_file_name(name)
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex<const N: usize>(hex: &str) -> Option<[u8; N]> {
+    let hex = hex.trim();
+    if hex.len() != N * 2 || !hex.is_ascii() {
+        return None;
+    }
+    let mut bytes = [0; N];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(bytes)
+}
+
+fn invalid_key(path: &Path, kind: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "{} should contain a hex-encoded 32-byte ed25519 {}",
+            path.display(),
+            kind
+        ),
+    )
+}
+
+/// Read an ed25519 signing key stored as 64 hex digits, e.g. generated by
+/// `openssl rand -hex 32`.
+pub fn read_signing_key(path: &Path) -> io::Result<SigningKey> {
+    decode_hex(&fs::read_to_string(path)?)
+        .map(|secret| SigningKey::from_bytes(&secret))
+        .ok_or_else(|| invalid_key(path, "secret key"))
+}
+
+/// Read an ed25519 public key stored as 64 hex digits.
+pub fn read_verifying_key(path: &Path) -> io::Result<VerifyingKey> {
+    decode_hex(&fs::read_to_string(path)?)
+        .and_then(|public| VerifyingKey::from_bytes(&public).ok())
+        .ok_or_else(|| invalid_key(path, "public key"))
+}
+
+/// The public key of a signing key, as hex, for publishing.
+pub fn public_key_hex(key: &SigningKey) -> String {
+    encode_hex(key.verifying_key().as_bytes())
+}
+
+/// Sign the contents of a file, writing the hex-encoded signature next to
+/// it.
+pub fn sign_file(storage: &dyn Storage, key: &SigningKey, path: &Path) -> io::Result<()> {
+    let signature = key.sign(&storage.read(path)?);
+    storage.write(
+        &signature_path(path),
+        encode_hex(&signature.to_bytes()).as_bytes(),
+    )
+}
+
+/// Check a file against its detached signature. Returns `Ok(false)` if the
+/// signature is malformed or doesn't match the contents, and an error if
+/// the file or its signature can't be read.
+pub fn verify_file(storage: &dyn Storage, key: &VerifyingKey, path: &Path) -> io::Result<bool> {
+    let contents = storage.read(path)?;
+    let signature = storage.read(&signature_path(path))?;
+    Ok(String::from_utf8(signature)
+        .ok()
+        .and_then(|hex| decode_hex(&hex))
+        .is_some_and(|bytes| {
+            key.verify(&contents, &Signature::from_bytes(&bytes))
+                .is_ok()
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::storage::MemoryStorage;
+
+    #[test]
+    fn test_signature_path() {
+        assert_eq!(
+            Path::new("reports/us/ca/sfo/report.json.sig"),
+            signature_path(Path::new("reports/us/ca/sfo/report.json"))
+        );
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let storage = MemoryStorage::default();
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let path = Path::new("reports/index.json");
+        storage.write(path, b"{\"elections\":[]}").unwrap();
+
+        sign_file(&storage, &key, path).unwrap();
+        assert!(verify_file(&storage, &key.verifying_key(), path).unwrap());
+
+        // Another key, or modified contents, don't verify.
+        let other = SigningKey::from_bytes(&[8; 32]).verifying_key();
+        assert!(!verify_file(&storage, &other, path).unwrap());
+        storage.write(path, b"{\"elections\":[{}]}").unwrap();
+        assert!(!verify_file(&storage, &key.verifying_key(), path).unwrap());
+
+        // A missing signature is an error.
+        storage.write(Path::new("reports/a.json"), b"{}").unwrap();
+        assert!(verify_file(&storage, &key.verifying_key(), Path::new("reports/a.json")).is_err());
+    }
+
+    #[test]
+    fn test_decode_hex() {
+        assert_eq!(Some([0xab, 0x01]), decode_hex::<2>("ab01\n"));
+        assert_eq!(None, decode_hex::<2>("ab0"));
+        assert_eq!(None, decode_hex::<2>("zz01"));
+    }
+}
@@ -0,0 +1,545 @@
+use super::path::get_files_from_path;
+use hmac::{Hmac, Mac};
+use regex::Regex;
+use sha2::{Digest, Sha256};
+#[cfg(test)]
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+#[cfg(test)]
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// Where serialized files are read from and written to. Paths are given
+/// as the caller would for local files; backends map them to their own
+/// keys.
+pub trait Storage: Send + Sync {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Write a file, replacing it if it exists. Its directory must exist
+    /// on backends that have directories (see `create_dir_all`).
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+
+    fn exists(&self, path: &Path) -> io::Result<bool>;
+
+    /// Create a directory and its parents. Backends without directories
+    /// have nothing to do.
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    /// The non-hidden files under a directory, recursively, in sorted
+    /// order. A directory that doesn't exist has none.
+    fn list(&self, dir: &Path) -> io::Result<Vec<PathBuf>>;
+}
+
+/// The local filesystem.
+pub struct LocalStorage;
+
+impl Storage for LocalStorage {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        fs::write(path, contents)
+    }
+
+    fn exists(&self, path: &Path) -> io::Result<bool> {
+        path.try_exists()
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(path)
+    }
+
+    fn list(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        if dir.is_dir() {
+            get_files_from_path(dir)
+        } else {
+            Ok(Vec::new())
+        }
+    }
+}
+
+/// Whether any component of a path is hidden, as `list` leaves out.
+fn is_hidden(path: &Path) -> bool {
+    path.iter().any(|c| {
+        c.to_str()
+            .is_some_and(|c| c.starts_with('.') && c != "." && c != "..")
+    })
+}
+
+/// Files held in memory, for tests.
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemoryStorage {
+    files: Mutex<BTreeMap<PathBuf, Vec<u8>>>,
+}
+
+#[cfg(test)]
+impl Storage for MemoryStorage {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.files
+            .lock()
+            .unwrap()
+            .get(path)
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, path.display().to_string()))
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.files
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), contents.to_vec());
+        Ok(())
+    }
+
+    fn exists(&self, path: &Path) -> io::Result<bool> {
+        Ok(self.files.lock().unwrap().contains_key(path))
+    }
+
+    fn create_dir_all(&self, _path: &Path) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn list(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        Ok(self
+            .files
+            .lock()
+            .unwrap()
+            .keys()
+            .filter(|path| {
+                path.strip_prefix(dir)
+                    .is_ok_and(|relative| !is_hidden(relative))
+            })
+            .cloned()
+            .collect())
+    }
+}
+
+const S3_SCHEME: &str = "s3://";
+const S3_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// An S3 (or S3-compatible) bucket, addressed by `s3://bucket/key` paths.
+/// Requests are signed with AWS Signature Version 4 using credentials from
+/// the standard `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and optional
+/// `AWS_SESSION_TOKEN` environment variables. The region is read from
+/// `AWS_REGION` (default `us-east-1`), and `AWS_ENDPOINT_URL` can point
+/// at an S3-compatible service, which is then addressed path-style.
+pub struct S3Storage {
+    region: String,
+    endpoint: Option<String>,
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+    agent: ureq::Agent,
+}
+
+fn not_s3_path(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("Not an s3://bucket/key path: {}", path.display()),
+    )
+}
+
+/// Split an `s3://bucket/key` path into its bucket and key.
+fn s3_location(path: &Path) -> io::Result<(&str, &str)> {
+    path.to_str()
+        .and_then(|p| p.strip_prefix(S3_SCHEME))
+        .and_then(|p| p.split_once('/'))
+        .filter(|(bucket, key)| !bucket.is_empty() && !key.is_empty())
+        .ok_or_else(|| not_s3_path(path))
+}
+
+/// Split an `s3://bucket/dir` path into its bucket and the prefix of the
+/// keys under it, which is empty for the whole bucket.
+fn s3_prefix(dir: &Path) -> io::Result<(&str, String)> {
+    let location = dir
+        .to_str()
+        .and_then(|p| p.strip_prefix(S3_SCHEME))
+        .ok_or_else(|| not_s3_path(dir))?;
+    let (bucket, key) = location.split_once('/').unwrap_or((location, ""));
+    if bucket.is_empty() {
+        return Err(not_s3_path(dir));
+    }
+    let key = key.trim_matches('/');
+    let prefix = if key.is_empty() {
+        String::new()
+    } else {
+        format!("{}/", key)
+    };
+    Ok((bucket, prefix))
+}
+
+/// The text of each `tag` element in an S3 XML response, unescaped.
+fn xml_values(xml: &str, tag: &str) -> Vec<String> {
+    let element = Regex::new(&format!("<{0}>([^<]*)</{0}>", tag)).unwrap();
+    element
+        .captures_iter(xml)
+        .map(|c| {
+            c[1].replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&quot;", "\"")
+                .replace("&apos;", "'")
+                .replace("&amp;", "&")
+        })
+        .collect()
+}
+
+/// Percent-encode a key for use in a URL path, leaving `/` separators.
+fn uri_encode(key: &str) -> String {
+    let mut encoded = String::with_capacity(key.len());
+    for b in key.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(b as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap();
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Derive the Signature Version 4 signing key for a day, region and service.
+fn signing_key(secret_access_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let key = hmac_sha256(format!("AWS4{}", secret_access_key).as_bytes(), date);
+    let key = hmac_sha256(&key, region);
+    let key = hmac_sha256(&key, service);
+    hmac_sha256(&key, "aws4_request")
+}
+
+/// Format a time as the `YYYYMMDDTHHMMSSZ` timestamp used in signatures.
+fn amz_date(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let (days, secs_of_day) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+
+    // Convert days since the epoch to a civil date (Howard Hinnant's
+    // days_from_civil, inverted).
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60
+    )
+}
+
+impl S3Storage {
+    pub fn from_env() -> io::Result<S3Storage> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let missing = |name: &str| {
+            io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{} must be set to use S3 storage", name),
+            )
+        };
+
+        Ok(S3Storage {
+            region: var("AWS_REGION").unwrap_or_else(|| "us-east-1".to_string()),
+            endpoint: var("AWS_ENDPOINT_URL").map(|e| e.trim_end_matches('/').to_string()),
+            access_key_id: var("AWS_ACCESS_KEY_ID").ok_or_else(|| missing("AWS_ACCESS_KEY_ID"))?,
+            secret_access_key: var("AWS_SECRET_ACCESS_KEY")
+                .ok_or_else(|| missing("AWS_SECRET_ACCESS_KEY"))?,
+            session_token: var("AWS_SESSION_TOKEN"),
+            agent: ureq::AgentBuilder::new().timeout(S3_TIMEOUT).build(),
+        })
+    }
+
+    /// Send a signed request for an object, returning the response.
+    fn request(&self, method: &str, path: &Path, body: &[u8]) -> io::Result<ureq::Response> {
+        let (bucket, key) = s3_location(path)?;
+        self.send(method, path, (bucket, key), &mut [], body)
+    }
+
+    /// Send a signed request for a key in a bucket, or for the bucket itself
+    /// if the key is empty, with the given query parameters. Errors name
+    /// `path`.
+    fn send(
+        &self,
+        method: &str,
+        path: &Path,
+        (bucket, key): (&str, &str),
+        query: &mut [(&str, String)],
+        body: &[u8],
+    ) -> io::Result<ureq::Response> {
+        let (url, host, canonical_uri) = match &self.endpoint {
+            Some(endpoint) => {
+                let host = endpoint.split("://").last().unwrap_or(endpoint).to_string();
+                let uri = format!("/{}/{}", bucket, uri_encode(key));
+                (format!("{}{}", endpoint, uri), host, uri)
+            }
+            None => {
+                let host = format!("{}.s3.{}.amazonaws.com", bucket, self.region);
+                let uri = format!("/{}", uri_encode(key));
+                (format!("https://{}{}", host, uri), host, uri)
+            }
+        };
+        // The signature requires parameters sorted by name, and `/` encoded.
+        query.sort();
+        let canonical_query = query
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, uri_encode(value).replace('/', "%2F")))
+            .collect::<Vec<_>>()
+            .join("&");
+        let url = if canonical_query.is_empty() {
+            url
+        } else {
+            format!("{}?{}", url, canonical_query)
+        };
+
+        let amz_date = amz_date(SystemTime::now());
+        let date = &amz_date[..8];
+        let payload_hash = format!("{:x}", Sha256::digest(body));
+
+        // Headers to sign, in the sorted order the signature requires.
+        let mut headers = vec![
+            ("host", host),
+            ("x-amz-content-sha256", payload_hash.clone()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        let signed_headers = headers
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(name, value)| format!("{}:{}\n", name, value))
+            .collect();
+
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            method, canonical_uri, canonical_query, canonical_headers, signed_headers, payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{:x}",
+            amz_date,
+            scope,
+            Sha256::digest(canonical_request.as_bytes())
+        );
+        let key = signing_key(&self.secret_access_key, date, &self.region, "s3");
+        let signature: String = hmac_sha256(&key, &string_to_sign)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+
+        let mut request = self.agent.request(method, &url).set(
+            "Authorization",
+            &format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                self.access_key_id, scope, signed_headers, signature
+            ),
+        );
+        // Host is set by ureq from the URL.
+        for (name, value) in headers.iter().skip(1) {
+            request = request.set(name, value);
+        }
+
+        let result = if method == "PUT" {
+            request.send_bytes(body)
+        } else {
+            request.call()
+        };
+        result.map_err(|e| match e {
+            ureq::Error::Status(404, _) => {
+                io::Error::new(io::ErrorKind::NotFound, path.display().to_string())
+            }
+            e => io::Error::other(format!("{}: {}", path.display(), e)),
+        })
+    }
+}
+
+impl Storage for S3Storage {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let mut contents = Vec::new();
+        self.request("GET", path, &[])?
+            .into_reader()
+            .read_to_end(&mut contents)?;
+        Ok(contents)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.request("PUT", path, contents).map(|_| ())
+    }
+
+    fn exists(&self, path: &Path) -> io::Result<bool> {
+        match self.request("HEAD", path, &[]) {
+            Ok(_) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn create_dir_all(&self, _path: &Path) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn list(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        let (bucket, prefix) = s3_prefix(dir)?;
+        let mut files = Vec::new();
+        let mut continuation = None;
+        loop {
+            // Listed with ListObjectsV2, a page of up to 1,000 keys at a time.
+            let mut query = vec![("list-type", "2".to_string()), ("prefix", prefix.clone())];
+            if let Some(token) = continuation {
+                query.push(("continuation-token", token));
+            }
+            let mut response = String::new();
+            self.send("GET", dir, (bucket, ""), &mut query, &[])?
+                .into_reader()
+                .read_to_string(&mut response)?;
+            files.extend(
+                xml_values(&response, "Key")
+                    .into_iter()
+                    .filter(|key| !is_hidden(Path::new(key.strip_prefix(&prefix).unwrap_or(key))))
+                    .map(|key| PathBuf::from(format!("{}{}/{}", S3_SCHEME, bucket, key))),
+            );
+            continuation = xml_values(&response, "NextContinuationToken").pop();
+            if continuation.is_none() {
+                break;
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+}
+
+/// The storage backend for a path: S3 for `s3://bucket/key` paths, and
+/// otherwise the local filesystem.
+pub fn storage_for(path: &Path) -> Box<dyn Storage> {
+    if path.to_str().is_some_and(|p| p.starts_with(S3_SCHEME)) {
+        Box::new(S3Storage::from_env().unwrap_or_else(|e| panic!("{}", e)))
+    } else {
+        Box::new(LocalStorage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_s3_location() {
+        let path = Path::new("s3://reports-bucket/us/ca/sfo/report.json");
+        assert_eq!(
+            ("reports-bucket", "us/ca/sfo/report.json"),
+            s3_location(path).unwrap()
+        );
+        assert!(s3_location(Path::new("reports/report.json")).is_err());
+        assert!(s3_location(Path::new("s3://bucket")).is_err());
+    }
+
+    #[test]
+    fn test_s3_prefix() {
+        let prefix = |dir: &'static str| s3_prefix(Path::new(dir)).unwrap();
+        assert_eq!(
+            ("bucket", "reports/".to_string()),
+            prefix("s3://bucket/reports")
+        );
+        assert_eq!(
+            ("bucket", "reports/".to_string()),
+            prefix("s3://bucket/reports/")
+        );
+        assert_eq!(("bucket", String::new()), prefix("s3://bucket"));
+        assert!(s3_prefix(Path::new("s3://")).is_err());
+    }
+
+    #[test]
+    fn test_xml_values() {
+        let response = "<ListBucketResult><Contents><Key>a/report.json</Key></Contents>\
+            <Contents><Key>a/b&amp;c.json</Key></Contents>\
+            <NextContinuationToken>1/x==</NextContinuationToken></ListBucketResult>";
+        assert_eq!(
+            vec!["a/report.json", "a/b&c.json"],
+            xml_values(response, "Key")
+        );
+        assert_eq!(vec!["1/x=="], xml_values(response, "NextContinuationToken"));
+    }
+
+    #[test]
+    fn test_uri_encode() {
+        assert_eq!("us/ca/a%20b%2Bc.json", uri_encode("us/ca/a b+c.json"));
+    }
+
+    #[test]
+    fn test_signing_key() {
+        // Example from the AWS Signature Version 4 documentation.
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        let hex: String = key.iter().map(|b| format!("{:02x}", b)).collect();
+        assert_eq!(
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d",
+            hex
+        );
+    }
+
+    #[test]
+    fn test_amz_date() {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        assert_eq!("20231114T221320Z", amz_date(time));
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(951_782_400);
+        assert_eq!("20000229T000000Z", amz_date(time));
+    }
+
+    #[test]
+    fn test_memory_storage() {
+        let storage = MemoryStorage::default();
+        let path = Path::new("reports/index.json");
+        assert_eq!(
+            io::ErrorKind::NotFound,
+            storage.read(path).unwrap_err().kind()
+        );
+
+        storage.write(path, b"{}").unwrap();
+        assert_eq!(b"{}".to_vec(), storage.read(path).unwrap());
+        assert!(storage.exists(path).unwrap());
+        assert!(!storage.exists(Path::new("reports/trends.json")).unwrap());
+
+        storage
+            .write(Path::new("reports/us/report.json"), b"{}")
+            .unwrap();
+        storage
+            .write(Path::new("reports/.cache/a.json"), b"{}")
+            .unwrap();
+        storage
+            .write(Path::new("reports-old/index.json"), b"{}")
+            .unwrap();
+        assert_eq!(
+            vec![
+                PathBuf::from("reports/index.json"),
+                PathBuf::from("reports/us/report.json")
+            ],
+            storage.list(Path::new("reports")).unwrap()
+        );
+    }
+}
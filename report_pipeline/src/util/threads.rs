@@ -0,0 +1,133 @@
+use rayon::ThreadPoolBuilder;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+/// Whether worker threads are pinned to CPU cores (see `configure_threads`).
+static PIN_THREADS: AtomicBool = AtomicBool::new(false);
+
+/// Configure the global thread pool, which runs parallel work outside the
+/// stages of `report`, and is the default size of each stage's pool. With
+/// `threads` of `None`, there is one thread per CPU. If `pin` is set, the
+/// threads of this and every pool built by `thread_pool` are pinned to CPU
+/// cores, in order, so that they don't migrate between cores.
+pub fn configure_threads(threads: Option<usize>, pin: bool) {
+    PIN_THREADS.store(pin, Ordering::Relaxed);
+    builder(threads)
+        .build_global()
+        .unwrap_or_else(|e| panic!("Failed to configure thread pool: {}", e));
+}
+
+/// Build a thread pool with the given number of threads, or as many as the
+/// global pool has.
+pub fn thread_pool(threads: Option<usize>) -> rayon::ThreadPool {
+    builder(Some(threads.unwrap_or_else(rayon::current_num_threads)))
+        .build()
+        .unwrap()
+}
+
+fn builder(threads: Option<usize>) -> ThreadPoolBuilder {
+    let builder = ThreadPoolBuilder::new().num_threads(threads.unwrap_or(0));
+    if !PIN_THREADS.load(Ordering::Relaxed) {
+        return builder;
+    }
+    let cores = core_affinity::get_core_ids().unwrap_or_default();
+    if cores.is_empty() {
+        crate::log_warn!("Can't determine CPU cores; threads will not be pinned");
+        return builder;
+    }
+    builder.start_handler(move |index| {
+        if !core_affinity::set_for_current(cores[index % cores.len()]) {
+            crate::log_warn!("Failed to pin thread {} to a CPU core", index);
+        }
+    })
+}
+
+const RUNNING: u8 = 0;
+const WRITING: u8 = 1;
+const TIMED_OUT: u8 = 2;
+
+/// Decides between a task run by `run_with_timeout` writing its output and
+/// timing out, so that a task that timed out never writes anything, and
+/// one that started writing is waited for rather than left half written.
+#[derive(Clone, Default)]
+pub struct TimeoutGuard(Arc<AtomicU8>);
+
+impl TimeoutGuard {
+    /// Claim the time to write the task's output, however long it takes.
+    /// Returns `false` if the task has already timed out, in which case it
+    /// must not write anything.
+    pub fn start_writing(&self) -> bool {
+        match self
+            .0
+            .compare_exchange(RUNNING, WRITING, Ordering::SeqCst, Ordering::SeqCst)
+        {
+            Ok(_) => true,
+            Err(state) => state == WRITING,
+        }
+    }
+
+    /// Time the task out, unless it has started writing.
+    fn time_out(&self) -> bool {
+        self.0
+            .compare_exchange(RUNNING, TIMED_OUT, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+    }
+}
+
+/// Run `task` on a thread of its own and wait up to `timeout` for it. Like
+/// `catch_unwind`, returns the panic payload if the task panics. Returns
+/// `None` if the task hasn't finished in time; threads can't be stopped
+/// from outside, so it is left running in the background, and its result
+/// is discarded. The task is given a guard to claim before writing output
+/// (see `TimeoutGuard`), after which it is waited for until it finishes.
+pub fn run_with_timeout<T: Send + 'static>(
+    timeout: Duration,
+    task: impl FnOnce(&TimeoutGuard) -> T + Send + 'static,
+) -> Option<std::thread::Result<T>> {
+    let (sender, receiver) = mpsc::channel();
+    let guard = TimeoutGuard::default();
+    let task_guard = guard.clone();
+    std::thread::Builder::new()
+        .spawn(move || {
+            // The receiver is gone if the task timed out.
+            let _ = sender.send(catch_unwind(AssertUnwindSafe(|| task(&task_guard))));
+        })
+        .unwrap_or_else(|e| panic!("Failed to start thread: {}", e));
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => Some(result),
+        Err(mpsc::RecvTimeoutError::Timeout) if !guard.time_out() => receiver.recv().ok(),
+        Err(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_with_timeout() {
+        let result = run_with_timeout(Duration::from_secs(10), |_| 42);
+        assert_eq!(42, result.unwrap().unwrap());
+        let result = run_with_timeout(Duration::from_secs(10), |_| panic!("bad file"));
+        assert!(result.unwrap().is_err());
+
+        // A task that times out can't start writing.
+        let (sender, receiver) = mpsc::channel();
+        let result = run_with_timeout(Duration::from_millis(10), move |guard| {
+            std::thread::sleep(Duration::from_millis(200));
+            sender.send(guard.start_writing()).unwrap();
+        });
+        assert!(result.is_none());
+        assert!(!receiver.recv().unwrap());
+
+        // One that started writing is waited for.
+        let result = run_with_timeout(Duration::from_millis(10), |guard| {
+            assert!(guard.start_writing());
+            std::thread::sleep(Duration::from_millis(200));
+            42
+        });
+        assert_eq!(42, result.unwrap().unwrap());
+    }
+}
@@ -1,4 +1,4 @@
 #!/bin/sh
 
-cargo run --release -- sync election-metadata raw-data
+cargo run --release -- sync election-metadata raw-data "$@"
 
@@ -0,0 +1,835 @@
+// Generated from the report pipeline's Rust types by `codegen-ts`. Do not edit.
+
+// index.json: IReportIndex
+// by-office.json: IOfficeIndex
+// candidates.json: ICandidateIndex
+// run-manifest.json: IRunManifest
+// summary.json: IElectionSummary
+// round_snapshots.json: IRoundSnapshot[]
+// ranking_patterns.json: IRankingPatterns
+// meta.json: IContestMetadataStub
+// trends.json: ITrendPoint[]
+// report.json: IContestReport
+
+export interface IReportIndex {
+  elections: IElectionIndexEntry[];
+}
+
+export interface IElectionIndexEntry {
+  path: string;
+  jurisdictionName: string;
+  electionName: string;
+  date: string;
+  contests: IContestIndexEntry[];
+}
+
+export interface IContestIndexEntry {
+  office: string;
+  /** Stable identifier of the office across elections. */
+  officeId: string;
+  officeName: string;
+  name: string;
+  winner: string;
+  numCandidates: number;
+  numRounds: number;
+  condorcetWinner?: string;
+  hasNonCondorcetWinner: boolean;
+  hasWriteInByName: boolean;
+  /** Set if the contest could not be tabulated normally. */
+  status?: TabulationStatus;
+  reportingStatus?: IReportingStatus;
+  variants?: IVariantEntry[];
+  subsets?: ISubsetEntry[];
+  notes?: string[];
+}
+
+/** Whether a contest could be tabulated normally. Reports on degenerate contests are still complete, but have no meaningful rounds to show. */
+export type TabulationStatus = "tabulated" | "noBallots" | "noVotes" | "uncontested" | "unopposed" | "writeInOnly";
+
+/** Reporting status of a contest whose results are not yet final. */
+export interface IReportingStatus {
+  /** Whether the results are preliminary. The leading candidate is not declared the winner until this is false. */
+  preliminary: boolean;
+  /** Estimated percentage (0-100) of ballots included in the results. */
+  percentReported?: number;
+}
+
+/** A report on a contest tabulated under an alternative rule set, written next to the contest's official report. */
+export interface IVariantEntry {
+  id: string;
+  name: string;
+  /** File name of the variant's report. */
+  report: string;
+  winner?: string;
+  numRounds: number;
+}
+
+/** A report on a subset of a contest's ballots, written under the contest's report directory. */
+export interface ISubsetEntry {
+  id: string;
+  name: string;
+  /** Path of the subset's report, relative to the contest's report directory. */
+  report: string;
+  ballotCount: number;
+  winner?: string;
+  numRounds: number;
+}
+
+/** Contests of each office across elections, written as `by-office.json` in the root report directory. */
+export interface IOfficeIndex {
+  offices: IOfficeHistory[];
+}
+
+export interface IOfficeHistory {
+  /** Stable identifier of the office, as in the index. */
+  id: string;
+  jurisdictionName: string;
+  /** Name of the office in its most recent contest. */
+  officeName: string;
+  /** Contests for the office, oldest first. */
+  contests: IOfficeContestEntry[];
+}
+
+export interface IOfficeContestEntry {
+  /** Path of the election, including the jurisdiction path. */
+  electionPath: string;
+  electionName: string;
+  date: string;
+  /** Key of the office in the election, which may differ between elections. */
+  office: string;
+  officeName: string;
+  winner: string;
+  status?: TabulationStatus;
+}
+
+/** Contests each candidate appeared in, across elections, written as `candidates.json` in the root report directory. */
+export interface ICandidateIndex {
+  candidates: ICandidateHistory[];
+}
+
+export interface ICandidateHistory {
+  /** Identifier of the candidate: the one given in metadata, or else the jurisdiction path and a slug of the candidate's name. */
+  id: string;
+  /** Displayed name of the candidate in their most recent contest. */
+  name: string;
+  /** Contests the candidate appeared in, oldest first. */
+  contests: ICandidateContestEntry[];
+}
+
+export interface ICandidateContestEntry {
+  /** Path of the election, including the jurisdiction path. */
+  electionPath: string;
+  electionName: string;
+  date: string;
+  office: string;
+  officeId: string;
+  officeName: string;
+  firstRoundVotes: number;
+  /** Whether the candidate was in the final round. */
+  finalist: boolean;
+  won: boolean;
+}
+
+/** What a report run did to each contest, written as `run-manifest.json` in the report directory. */
+export interface IRunManifest {
+  pipelineVersion: string;
+  contests: IContestManifestEntry[];
+}
+
+export interface IContestManifestEntry {
+  /** Path of the election, including the jurisdiction path. */
+  path: string;
+  office: string;
+  status: ContestStatus;
+  /** Raw data files the report was generated from. */
+  sourceFiles?: ISourceFile[];
+  winner?: string;
+  /** Differences from the report this run replaced, if the report was regenerated and one existed. */
+  changes?: IReportChanges;
+  /** Peak memory allocated while preprocessing the contest or generating its report in this run, in bytes. Contests preprocessed in a batch are each given the batch's peak. */
+  peakMemory?: number;
+}
+
+export type ContestStatus = "failed" | "generated" | "cached" | "empty";
+
+/** A raw data file that a contest was read from. */
+export interface ISourceFile {
+  /** File name, relative to the election's raw data directory. */
+  name: string;
+  /** Hex-encoded SHA-256 hash of the file contents. */
+  sha256: string;
+}
+
+export interface IReportChanges {
+  previousWinner?: string;
+  winnerChanged: boolean;
+  /** Whether the votes or transfers of any round, or the number of rounds, changed. */
+  roundsChanged: boolean;
+}
+
+/** Summary of all contests in one election, written as `summary.json` in the election's report directory. */
+export interface IElectionSummary {
+  path: string;
+  jurisdictionPath: string;
+  jurisdictionName: string;
+  electionName: string;
+  date: string;
+  /** Largest ballot count of any contest. Ballots listing several contests are counted in each, so contest ballot counts can't be summed. */
+  ballotsCast: number;
+  /** Ballots exhausted during tabulation, summed over contests. */
+  exhaustedBallots: number;
+  /** Exhausted ballots as a fraction of first-round continuing ballots, over all contests. */
+  exhaustionRate: number;
+  averageRounds: number;
+  contests: IContestSummary[];
+}
+
+export interface IContestSummary {
+  office: string;
+  officeName: string;
+  winner?: string;
+  ballotCount: number;
+  /** Ballot count as a fraction of the election's ballots cast. */
+  turnout: number;
+  numCandidates: number;
+  numRounds: number;
+  /** Winner's first-round votes as a fraction of first-round continuing ballots. */
+  winnerFirstChoiceShare?: number;
+  /** Ballots continuing in the first round. */
+  continuingBallots: number;
+  /** Ballots continuing in the first round but not in the last. */
+  exhaustedBallots: number;
+  exhaustionRate: number;
+}
+
+/** Ballots at one round of tabulation, aggregated by their remaining ranking of continuing candidates. Written as `round_snapshots.json` next to the report, so that each round can be reconstructed without the raw ballots. */
+export interface IRoundSnapshot {
+  /** Round number, starting from 1. */
+  round: number;
+  /** Patterns in descending order of count. */
+  patterns: IBallotPattern[];
+}
+
+export interface IBallotPattern {
+  /** Continuing candidates in the order ranked. The first is the candidate the ballots count toward; empty if they are exhausted. */
+  ranking: CandidateId[];
+  /** Whether the ballots end in an overvote, so that they count as an overvote rather than an undervote once exhausted. */
+  overvoted?: boolean;
+  count: number;
+}
+
+export type CandidateId = number;
+
+/** Number of ballots with each distinct ranking in a contest, written as `ranking_patterns.json` next to the report. See `crate::patterns` for the format of patterns. */
+export interface IRankingPatterns {
+  /** Candidate names, indexed by the candidate IDs in patterns. */
+  candidates: string[];
+  ballotCount: number;
+  patterns: Record<string, number>;
+}
+
+/** Lightweight page metadata for a contest, written as `meta.json` next to its report when a site URL is given. */
+export interface IContestMetadataStub {
+  title: string;
+  jurisdictionName: string;
+  officeName: string;
+  electionName: string;
+  date: string;
+  /** Winner, or leading candidate if the results are preliminary. */
+  winner?: string;
+  preliminary: boolean;
+  canonicalUrl: string;
+}
+
+/** One contest's key metrics in the time-series dataset written as `trends.json` and `trends.csv`. */
+export interface ITrendPoint {
+  jurisdictionPath: string;
+  jurisdictionName: string;
+  office: string;
+  officeName: string;
+  year: string;
+  date: string;
+  electionPath: string;
+  ballotCount: number;
+  numCandidates: number;
+  numRounds: number;
+  exhaustionRate: number;
+  winner?: string;
+  winnerFirstChoiceShare?: number;
+}
+
+export interface IContestReport {
+  info: IElectionInfo;
+  ballotCount: number;
+  candidates: ICandidate[];
+  status: TabulationStatus;
+  /** Tally of a non-ranked contest, whose `rounds` then have only the single round of its totals. */
+  plurality?: IPluralityTally;
+  rounds: ITabulatorRound[];
+  /** The full sequence of rounds, if `rounds` was condensed by merging runs of rounds that don't change the leaders into one step. */
+  expandedRounds?: ITabulatorRound[];
+  /** For each of the condensed `rounds`, the number (counting from 1) of the expanded round whose totals it shows. */
+  expandedRoundNumbers?: number[];
+  /** Each candidate's votes in every round, or null for rounds after they are eliminated, for charting without transposing the rounds. Rounds merged by condensing are included. */
+  candidateSeries?: Record<string, (number | null)[]>;
+  /** Minor candidates to display as one "Others" row, for elections with an `othersThreshold`. */
+  others?: IOthersBucket;
+  winner?: CandidateId;
+  condorcet?: CandidateId;
+  numCandidates: number;
+  totalVotes: ICandidateVotes[];
+  pairwisePreferences: ICandidatePairTable;
+  firstAlternate: ICandidatePairTable;
+  firstFinal: ICandidatePairTable;
+  rankingDistribution?: IRankingDistribution;
+  smithSet: CandidateId[];
+  adjudicatedBallots?: number;
+  /** How adjudication changed the ballots, for formats that record both versions of an adjudicated ballot. */
+  adjudication?: IAdjudicationImpact;
+  /** Ballots repeating an earlier ballot's ID in the raw data. */
+  duplicateBallots?: IDuplicateBallots;
+  /** Differences between the candidate manifest and the candidates marked on the ballots. */
+  candidateReconciliation?: ICandidateReconciliation;
+  countingGroups?: ICountingGroupBreakdown[];
+  /** Breakdown by region, for elections with a region lookup in their metadata. */
+  regions?: IRegionBreakdown[];
+  /** Marks at each rank position of the ballots before normalization. */
+  rankPositions?: IRankPositionStatistics[];
+  /** Candidates ranked more than once on a ballot. */
+  duplicateRankings?: IDuplicateRankings;
+  /** How normalization changed the first-round votes. */
+  firstRoundAdjustments?: IFirstRoundAdjustments;
+  /** How ballots with overvotes were handled under the overvote policy. */
+  overvoteHandling?: IOvervoteHandling;
+  /** How many ballots were exhausted by skipped ranks under the skipped-rank policy. */
+  skippedRankHandling?: ISkippedRankHandling;
+  /** Comparison of the first round to the official canvass, if the contest's metadata gives official first-round totals. */
+  reconciliation?: IReconciliation;
+  /** Head-to-head totals between the final two candidates. */
+  finalTwo?: IFinalTwo;
+  margins?: IMargins;
+  marginOfVictory?: IMarginOfVictory;
+  /** Ballots exhausted before the final round, and how many of them the overvote and skipped-rank rules cut off before a finalist. */
+  exhaustedBallots?: IExhaustedBallots;
+  /** Winners under alternative elimination orders, if requested. */
+  eliminationRobustness?: IEliminationRobustness;
+  /** Winners under other voting methods, if requested. Hypothetical, for comparison only. */
+  hypotheticalMethods?: IHypotheticalMethods;
+  /** Monotonicity failures found in the ballots, if requested. */
+  strategySusceptibility?: IStrategySusceptibility;
+  /** Sample sizes for a risk-limiting audit of the final round. */
+  auditSampleSizes?: IAuditSampleSizes;
+  /** File name of the per-precinct results GeoJSON written alongside this report, if the election has precinct boundaries. */
+  precinctMap?: string;
+  provenance?: IProvenance;
+  /** File name of the per-round ballot snapshots written alongside this report, if requested. */
+  roundSnapshots?: string;
+  /** File name of the ranking pattern frequency table written alongside this report, if requested. */
+  rankingPatterns?: string;
+  /** Reporting status from the contest metadata, if the results are not yet final. */
+  reportingStatus?: IReportingStatus;
+  /** ID of the tabulation variant this report was tabulated under, if it is not the official report. */
+  variant?: string;
+  /** Reports on this contest under alternative rule sets. */
+  variants?: IVariantEntry[];
+  /** ID of the ballot subset this report is restricted to, if it is not the report on all ballots. */
+  subset?: string;
+  /** Reports on subsets of this contest's ballots. */
+  subsets?: ISubsetEntry[];
+}
+
+export interface IElectionInfo {
+  /** Name of election. */
+  name: string;
+  /** Date of election: */
+  date: string;
+  dataFormat: string;
+  tabulationOptions: ITabulationOptions;
+  jurisdictionPath: string;
+  electionPath: string;
+  office: string;
+  officeName: string;
+  jurisdictionName: string;
+  electionName: string;
+  loaderParams?: Record<string, string>;
+  website?: string;
+  /** BCP 47 language tag of the jurisdiction's names, e.g. "fr-CA". */
+  locale?: string;
+  /** Translations of the office name, keyed by BCP 47 language tag. */
+  officeNameTranslations?: Record<string, string>;
+  /** Number of seats elected to the office at once (1 if absent). */
+  seats?: number;
+  district?: IDistrict;
+  /** Length of the office's term, in years. */
+  termYears?: number;
+  /** Caveats about the contest's data, from its metadata. */
+  notes?: string[];
+  /** Stable identifier of the office across elections, if set in its metadata. */
+  officeId?: string;
+  /** Number of candidates each voter may vote for, for a non-ranked contest tallied by plurality. */
+  voteFor?: number;
+}
+
+export interface ITabulationOptions {
+  /** Eliminate every candidate who can no longer win at once, rather than one candidate per round. Defaults to true; `false` was ignored before single elimination was supported. */
+  eager?: boolean;
+  nycStyle?: boolean;
+  /** Record each round's majority threshold, and the margins in reports, as exact fractions alongside their rounded values. */
+  exactThresholds?: boolean;
+}
+
+/** An electoral district within a jurisdiction. */
+export interface IDistrict {
+  /** Identifier of the district, e.g. "5" for the 5th council district. */
+  id: string;
+  /** GeoJSON file of the district's boundary, relative to the jurisdiction's raw data directory. */
+  geometry?: string;
+}
+
+export interface ICandidate {
+  name: string;
+  candidate_type: CandidateType;
+  /** Name to display instead of the name in the raw data, from metadata. */
+  displayName?: string;
+  /** Identifier of the candidate across elections, from metadata. */
+  canonicalId?: string;
+}
+
+export type CandidateType = "WriteIn" | "Regular" | "QualifiedWriteIn";
+
+/** Tally of a non-ranked contest, in which each ballot counts once for each candidate it votes for, up to `voteFor` of them. */
+export interface IPluralityTally {
+  voteFor: number;
+  /** Votes per candidate, in descending order of votes. */
+  votes: IPluralityVotes[];
+  /** Ballots that vote for no candidate. */
+  blankBallots: number;
+  /** Ballots that vote for more than `voteFor` candidates, and count for none of them. */
+  overvotedBallots: number;
+  /** Votes left unused by ballots that vote for fewer than `voteFor` candidates, including blank ballots. */
+  undervotes: number;
+  /** Candidates elected: the `voteFor` with the most votes, less any tied for the last place. */
+  winners: CandidateId[];
+  /** Candidates tied for the last place, which a tiebreak decides. */
+  tied: CandidateId[];
+}
+
+export interface IPluralityVotes {
+  candidate: CandidateId;
+  votes: number;
+}
+
+export interface ITabulatorRound {
+  allocations: ITabulatorAllocation[];
+  undervote: number;
+  overvote: number;
+  continuingBallots: number;
+  transfers: ITransfer[];
+  /** The round's majority threshold, with the `exactThresholds` option. */
+  threshold?: IMajorityThreshold;
+}
+
+export interface ITabulatorAllocation {
+  allocatee: Allocatee;
+  votes: number;
+  shares?: IShares;
+}
+
+export type Allocatee = CandidateId | "X";
+
+/** Votes as percentages, formatted for display when the report is generated so that every reader rounds them the same way, e.g. "45.3%". */
+export interface IShares {
+  /** Share of the round's continuing ballots. Absent for exhausted ballots, which aren't continuing. */
+  ofContinuing?: string;
+  /** Share of all ballots in the contest, including exhausted ones. */
+  ofTotal: string;
+}
+
+export interface ITransfer {
+  from: CandidateId;
+  to: Allocatee;
+  count: number;
+  shares?: IShares;
+}
+
+/** Whether a round's leader has a majority of its continuing ballots, decided in exact arithmetic so that a leader within a rounding error of half the ballots is not misclassified. */
+export interface IMajorityThreshold {
+  /** Half of the round's continuing ballots, which the leader must exceed. */
+  threshold: IExactRatio;
+  /** The leader's votes as a fraction of continuing ballots. */
+  leaderShare: IExactRatio;
+  /** Whether the leader's votes exceed the threshold. */
+  decisive: boolean;
+}
+
+/** A fraction, both exact and rounded to a float. */
+export interface IExactRatio {
+  /** Numerator of the fraction in lowest terms. */
+  numerator: number;
+  /** Denominator of the fraction in lowest terms. */
+  denominator: number;
+  /** The fraction as a float, for display only. */
+  value: number;
+}
+
+/** Candidates grouped into a single "Others" row for display, because each received less than the threshold share of first-round votes. The rounds still have each of their votes. */
+export interface IOthersBucket {
+  threshold: number;
+  candidates: CandidateId[];
+  /** Combined votes of the grouped candidates in each round. */
+  votes: number[];
+}
+
+export interface ICandidateVotes {
+  candidate: CandidateId;
+  firstRoundVotes: number;
+  transferVotes: number;
+  roundEliminated?: number;
+}
+
+export interface ICandidatePairTable {
+  rows: Allocatee[];
+  cols: Allocatee[];
+  entries: (ICandidatePairEntry | null)[][];
+}
+
+export interface ICandidatePairEntry {
+  frac: number;
+  numerator: number;
+  denominator: number;
+}
+
+export interface IRankingDistribution {
+  overallDistribution: Record<string, number>;
+  candidateDistributions: Record<string, Record<string, number>>;
+  totalBallots: number;
+  candidateTotals: Record<string, number>;
+}
+
+/** How adjudication changed the ballots of a contest, comparing the marks on each adjudicated ballot as scanned to those after adjudication. First choices are the first candidate marked, before normalization. */
+export interface IAdjudicationImpact {
+  /** Adjudicated ballots whose marks in the contest changed. */
+  changedBallots: number;
+  /** Ballots whose first choice changed, by their first choice before and after adjudication. */
+  firstChoiceChanges: IFirstChoiceChange[];
+  /** Per candidate whose first choices changed, in candidate order. */
+  candidates: IAdjudicationCandidate[];
+}
+
+export interface IFirstChoiceChange {
+  /** First choice as scanned, or null if the first mark was an overvote or there was none. */
+  original?: CandidateId;
+  /** First choice after adjudication, or null likewise. */
+  adjudicated?: CandidateId;
+  ballots: number;
+}
+
+export interface IAdjudicationCandidate {
+  candidate: CandidateId;
+  /** Ballots that adjudication made first choices for the candidate. */
+  gained: number;
+  /** Ballots with the candidate as first choice as scanned that adjudication gave another first choice, or none. */
+  lost: number;
+}
+
+/** Ballots in a contest's raw data with the same ID as an earlier ballot, and how many of them the duplicate ballot policy removed. */
+export interface IDuplicateBallots {
+  policy: DuplicateBallotPolicy;
+  /** Ballots with the ID of an earlier ballot. */
+  duplicateIds: number;
+  /** Of those, ballots whose choices, counting group and precinct are also identical to the earlier ballot's. */
+  identicalRecords: number;
+  /** Ballots removed under the policy. */
+  removed: number;
+  /** Some of the repeated IDs, to find them by in the raw data. */
+  examples: string[];
+}
+
+/** What preprocessing does with a ballot that has the same ID as an earlier ballot in a contest's raw data, as when a CVR archive contains an export batch twice. */
+export type DuplicateBallotPolicy = "keep" | "removeIdentical" | "removeDuplicateIds";
+
+/** Differences between a contest's candidate manifest and the candidates marked on its ballots. */
+export interface ICandidateReconciliation {
+  /** IDs marked on ballots that the manifest doesn't list. */
+  unknownCandidates: IUnknownCandidate[];
+  /** Candidates in the manifest that no ballot marks. */
+  unmarkedCandidates: CandidateId[];
+}
+
+/** An external candidate ID marked on ballots but missing from the manifest. Its marks count as skipped ranks. */
+export interface IUnknownCandidate {
+  /** The ID as it appears in the ballot data. */
+  externalId: string;
+  marks: number;
+}
+
+/** First-choice and final-round support among ballots from one counting group (vote method, e.g. election day or vote by mail). */
+export interface ICountingGroupBreakdown {
+  countingGroup: string;
+  ballotCount: number;
+  firstChoice: ITabulatorAllocation[];
+  finalRound: ITabulatorAllocation[];
+}
+
+/** First-choice and final-round support among ballots from one region (e.g. borough or county) of a contest spanning several. */
+export interface IRegionBreakdown {
+  region: string;
+  ballotCount: number;
+  firstChoice: ITabulatorAllocation[];
+  finalRound: ITabulatorAllocation[];
+}
+
+/** Counts of the marks at one rank position of the raw ballots, before normalization discards overvotes and skipped ranks. */
+export interface IRankPositionStatistics {
+  /** Rank position, starting from 1. */
+  rank: number;
+  /** Ballots with a vote for a single candidate at this rank. */
+  votes: number;
+  /** Ballots with more than one candidate marked at this rank. */
+  overvotes: number;
+  /** Ballots with no candidate marked at this rank. */
+  undervotes: number;
+  /** Undervotes followed by a mark at a later rank. */
+  skipped: number;
+}
+
+/** Ballots that ranked the same candidate more than once. */
+export interface IDuplicateRankings {
+  /** Ballots that ranked any candidate more than once. */
+  ballots: number;
+  /** Per candidate, in descending order of ballots. */
+  candidates: ICandidateDuplicates[];
+}
+
+export interface ICandidateDuplicates {
+  candidate: CandidateId;
+  /** Ballots that ranked this candidate more than once. */
+  ballots: number;
+}
+
+/** How normalization changed the first-round votes, compared to counting each ballot for the first candidate marked on it. Official canvasses that treat skipped ranks or exhausted ballots differently differ from our first round by these counts. */
+export interface IFirstRoundAdjustments {
+  /** Ballots exhausted by an overvote before any candidate was marked. */
+  overvoted: number;
+  /** Per candidate with any adjustment, in candidate order. */
+  candidates: ICandidateAdjustment[];
+}
+
+export interface ICandidateAdjustment {
+  candidate: CandidateId;
+  /** Ballots counted for the candidate although their first rank was blank or overvoted. */
+  skippedRanks: number;
+  /** Ballots marking the candidate first that were exhausted or set aside before the first round, e.g. for too many skipped ranks. */
+  exhausted: number;
+}
+
+/** Counts of ballots with overvotes under the election's overvote policy. */
+export interface IOvervoteHandling {
+  policy: OvervotePolicy;
+  /** Ballots with an overvote at any rank. */
+  overvotedBallots: number;
+  /** Ballots exhausted at an overvote (under the `exhaust` policy). */
+  exhausted: number;
+  /** Ballots that skipped an overvote and went on to rank a candidate (under the `skip` policy). */
+  skipped: number;
+}
+
+/** How normalization treats a ballot's overvoted rank. */
+export type OvervotePolicy = "exhaust" | "skip";
+
+/** Counts of ballots exhausted under the election's skipped-rank policy. */
+export interface ISkippedRankHandling {
+  policy: SkippedRankPolicy;
+  /** Ballots with rankings after two consecutive skipped ranks that were exhausted there (under the `exhaustAfterTwo` policy). */
+  exhausted: number;
+}
+
+/** How normalization treats a ballot's skipped (blank) ranks. */
+export type SkippedRankPolicy = "skip" | "exhaustAfterTwo";
+
+/** First-round votes compared to the official canvass. */
+export interface IReconciliation {
+  rows: IReconciliationRow[];
+  /** Sum of the absolute deltas. */
+  totalDiscrepancy: number;
+}
+
+export interface IReconciliationRow {
+  /** Name of the candidate, as in the official canvass if listed there. */
+  name: string;
+  /** Absent for names in the official canvass that match no candidate. */
+  candidate?: CandidateId;
+  votes: number;
+  /** Absent for candidates not listed in the official canvass. */
+  officialVotes?: number;
+  /** Our votes minus the official votes. */
+  delta: number;
+  /** Normalization rules that moved votes in the direction of the delta. */
+  rules?: NormalizationRule[];
+}
+
+/** A normalization rule that can make first-round votes differ from an official canvass. */
+export type NormalizationRule = "skippedRanks" | "exhaustedBallots";
+
+/** Head-to-head totals between the two candidates in the final round over all ballots, as in a two-candidate runoff. Unlike the final round, this counts ballots whose higher-ranked choices are still in the running. */
+export interface IFinalTwo {
+  winner: CandidateId;
+  runnerUp: CandidateId;
+  /** Ballots ranking the winner above the runner-up, or only the winner. */
+  winnerVotes: number;
+  /** Ballots ranking the runner-up above the winner, or only the runner-up. */
+  runnerUpVotes: number;
+  /** Ballots ranking neither candidate, including blank ballots and ballots with an overvote before either. */
+  neither: number;
+}
+
+/** Margins between the leading candidates in the first and final rounds. Fractions are of the round's continuing ballots. */
+export interface IMargins {
+  firstRoundLeader: CandidateId;
+  /** First-round votes of the leader minus those of the second-place candidate. */
+  firstRoundMargin: number;
+  firstRoundMarginFraction: number;
+  /** Final-round votes of the winner minus those of the runner-up. */
+  finalMargin: number;
+  finalMarginFraction: number;
+  /** Votes needed for a majority of continuing ballots in the final round. */
+  threshold: number;
+  /** Final-round votes of the winner minus the threshold; negative if the winner did not reach it. */
+  marginOverThreshold: number;
+  /** Margin fractions as exact fractions, with the `exactThresholds` option. */
+  firstRoundMarginExact?: IExactRatio;
+  finalMarginExact?: IExactRatio;
+}
+
+/** Bounds on the margin of victory: the fewest ballots that would have to be changed to elect a different candidate. The margin is exact when the bounds are equal. */
+export interface IMarginOfVictory {
+  /** Changing fewer ballots cannot elect a different candidate. */
+  lowerBound: number;
+  /** Changing this many ballots can elect a different candidate. */
+  upperBound: number;
+  /** Round (numbered from 1) in which changing `upperBound` ballots eliminates the winner, or beats them in the final round. */
+  round: number;
+  /** Candidate who gains the changed ballots. */
+  beneficiary: CandidateId;
+}
+
+/** Ballots that count for no candidate in the final round, by whether they ranked a finalist at all. */
+export interface IExhaustedBallots {
+  /** Ballots marking any candidate that count for no finalist in the final round. */
+  exhausted: number;
+  /** Exhausted ballots that ranked none of the finalists anywhere. */
+  rankedNoFinalist: number;
+  /** Exhausted ballots that ranked a finalist after an overvote or skipped ranks at which the rules cut them off. */
+  ruleExhausted: number;
+  /** Rule-exhausted ballots by the finalist ranked first after the cut, in descending order of ballots. */
+  ruleExhaustedPreferences: IFinalistPreference[];
+  /** Winner's lead over the runner-up in the final round. */
+  finalMargin: number;
+  /** Whether there are more rule-exhausted ballots than the final margin. */
+  exceedsMargin: boolean;
+}
+
+export interface IFinalistPreference {
+  candidate: CandidateId;
+  ballots: number;
+}
+
+/** Winners under plausible alternative elimination orders, in which candidates within a tolerance of last place may be eliminated instead. */
+export interface IEliminationRobustness {
+  /** Votes within which candidates may be eliminated in either order. */
+  tolerance: number;
+  /** Whether the reported winner wins under every plausible order. */
+  stable: boolean;
+  /** Each plausible winner, with one elimination order electing them. */
+  outcomes: IPlausibleOutcome[];
+  /** Number of distinct sets of continuing candidates explored. */
+  states: number;
+  /** Whether every plausible order was explored, rather than stopping at the search's limit. */
+  complete: boolean;
+}
+
+export interface IPlausibleOutcome {
+  winner: CandidateId;
+  /** Candidates in the order they are eliminated. */
+  eliminationOrder: CandidateId[];
+}
+
+/** Winners of the contest had it been counted under other single-winner methods, from the same ballots. These are hypothetical: voters ranked candidates for an RCV count, and might have marked their ballots differently under another method. */
+export interface IHypotheticalMethods {
+  results: IHypotheticalResult[];
+}
+
+export interface IHypotheticalResult {
+  method: VotingMethod;
+  /** The candidate with the highest score, or none if the highest score is tied. */
+  winner?: CandidateId;
+  /** Score of each candidate, in descending order. */
+  scores: IMethodScore[];
+}
+
+export type VotingMethod = "plurality" | "approval" | "borda";
+
+export interface IMethodScore {
+  candidate: CandidateId;
+  score: number;
+}
+
+/** Whether the outcome would have changed had some voters ranked a candidate lower or higher than they did. */
+export interface IStrategySusceptibility {
+  /** Losing candidates who would have won had some of the ballots ranking them first ranked them second instead. */
+  downwardMonotonicity: IMonotonicityFailure[];
+  /** Candidates whose voters would have defeated the winner by ranking the winner first instead of second. */
+  upwardMonotonicity: IMonotonicityFailure[];
+  /** Whether every pair of candidates was checked, rather than stopping at the search's limit for some. */
+  complete: boolean;
+}
+
+/** A change of outcome from swapping the first two choices on some ballots ranking `candidate` and `other` first and second, in either order. */
+export interface IMonotonicityFailure {
+  /** The candidate ranked lower (downward) or higher (upward). */
+  candidate: CandidateId;
+  /** The candidate they swap places with. */
+  other: CandidateId;
+  /** Fewest ballots that must swap the two to change the outcome. */
+  ballots: number;
+  /** Winner after the swap. */
+  winner: CandidateId;
+}
+
+/** Initial sample sizes for a risk-limiting audit of the final round's outcome, at common risk limits. */
+export interface IAuditSampleSizes {
+  /** Final-round margin as a fraction of all ballots in the contest. */
+  dilutedMargin: number;
+  sizes: IAuditSampleSize[];
+}
+
+export interface IAuditSampleSize {
+  /** Largest chance that the audit confirms a wrong outcome. */
+  riskLimit: number;
+  /** Expected number of ballots a BRAVO ballot-polling audit examines if the reported results are correct. */
+  ballotPolling: number;
+  /** Number of ballots a ballot-level comparison audit examines if it finds no discrepancies. */
+  comparison: number;
+}
+
+/** Record of the inputs and rules that produced a report, so that it can be audited and reproduced. */
+export interface IProvenance {
+  sourceFiles: ISourceFile[];
+  dataFormat: string;
+  loaderParams?: Record<string, string>;
+  normalization: string;
+  overvotePolicy?: OvervotePolicy;
+  skippedRankPolicy?: SkippedRankPolicy;
+  tabulationOptions: ITabulationOptions;
+  /** The statutory rule set the contest was counted under, if any. */
+  ruleSet?: IRuleSetReference;
+  /** Version of the report pipeline, including its git commit. */
+  pipelineVersion: string;
+}
+
+/** A statutory rule set, by name and with the citation of its statute. */
+export interface IRuleSetReference {
+  name: string;
+  title: string;
+  citation: string;
+}